@@ -71,6 +71,9 @@ pub enum Command {
     /// Prints general information about the configured environment
     #[clap(name = "whoami")]
     WhoAmiI(whoami::WhoAmI),
+    /// Inspect the nodes and partitions of the cluster
+    #[clap(subcommand)]
+    Cluster(cluster::Cluster),
     /// Manage Restate's components registry
     #[clap(subcommand)]
     Components(components::Components),
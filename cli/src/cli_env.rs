@@ -39,6 +39,7 @@ pub const RESTATE_AUTH_TOKEN_ENV: &str = "RESTATE_AUTH_TOKEN";
 // TODO: Deprecated, will be removed once this is provided by the admin server
 pub const INGRESS_URL_ENV: &str = "RESTATE_INGRESS_URL";
 pub const ADMIN_URL_ENV: &str = "RESTATE_ADMIN_URL";
+pub const NODE_URL_ENV: &str = "RESTATE_NODE_URL";
 pub const EDITOR_ENV: &str = "RESTATE_EDITOR";
 
 #[derive(Clone, Default)]
@@ -51,6 +52,7 @@ pub struct CliEnv {
     pub config_file: PathBuf,
     pub ingress_base_url: Url,
     pub admin_base_url: Url,
+    pub node_base_url: Url,
     pub bearer_token: Option<String>,
     /// Should we use colors and emojis or not?
     pub colorful: bool,
@@ -120,6 +122,14 @@ impl CliEnv {
                 Url::parse(&format!("{}://{}:9070/", restate_host_scheme, restate_host))
             })?;
 
+        let node_base_url = os_env
+            .get(NODE_URL_ENV)
+            .as_deref()
+            .map(Url::parse)
+            .unwrap_or_else(|| {
+                Url::parse(&format!("{}://{}:5122/", restate_host_scheme, restate_host))
+            })?;
+
         let default_editor = os_env
             .get(EDITOR_ENV)
             .or_else(|| os_env.get("VISUAL"))
@@ -170,6 +180,7 @@ impl CliEnv {
             config_file,
             ingress_base_url,
             admin_base_url,
+            node_base_url,
             bearer_token,
             connect_timeout: Duration::from_millis(global_opts.connect_timeout),
             request_timeout: global_opts.request_timeout.map(Duration::from_millis),
@@ -309,6 +320,10 @@ mod tests {
             cli_env.admin_base_url.to_string(),
             "http://localhost:9070/".to_string()
         );
+        assert_eq!(
+            cli_env.node_base_url.to_string(),
+            "http://localhost:5122/".to_string()
+        );
 
         // Defaults are templated over RESTATE_HOST
         os_env.clear();
@@ -324,11 +339,16 @@ mod tests {
             cli_env.admin_base_url.to_string(),
             "http://example.com:9070/".to_string()
         );
+        assert_eq!(
+            cli_env.node_base_url.to_string(),
+            "http://example.com:5122/".to_string()
+        );
 
         // RESTATE_INGRESS_URL/RESTATE_META_URL override the base URLs!
         os_env.clear();
         os_env.insert(INGRESS_URL_ENV, "https://api.restate.dev:4567".to_string());
         os_env.insert(ADMIN_URL_ENV, "https://admin.restate.dev:4567".to_string());
+        os_env.insert(NODE_URL_ENV, "https://node.restate.dev:4567".to_string());
         os_env.insert(RESTATE_HOST_SCHEME_ENV, "https".to_string());
 
         let cli_env = CliEnv::load_from_env(&os_env, &GlobalOpts::default())?;
@@ -341,6 +361,10 @@ mod tests {
             cli_env.admin_base_url.to_string(),
             "https://admin.restate.dev:4567/".to_string()
         );
+        assert_eq!(
+            cli_env.node_base_url.to_string(),
+            "https://node.restate.dev:4567/".to_string()
+        );
 
         Ok(())
     }
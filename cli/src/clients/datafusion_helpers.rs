@@ -482,7 +482,7 @@ pub async fn get_components_status(
                 ss.created_at
             FROM sys_invocation_status ss
             LEFT JOIN sys_invocation_state sis ON ss.id = sis.id
-            WHERE ss.component IN {}
+            WHERE ss.component IN {} AND ss.status <> 'completed'
             )
             SELECT component, handler, combined_status, COUNT(id), MIN(created_at), FIRST_VALUE(id ORDER BY created_at ASC)
             FROM enriched_invokes GROUP BY component, handler, combined_status ORDER BY method",
@@ -629,7 +629,7 @@ pub async fn get_locked_keys_status(
                 sis.last_start_at
             FROM sys_invocation_status ss
             LEFT JOIN sys_invocation_state sis ON ss.id = sis.id
-            WHERE ss.service IN {}
+            WHERE ss.service IN {} AND ss.status <> 'completed'
             )
             SELECT
                 component,
@@ -737,7 +737,7 @@ pub async fn find_active_invocations(
             comp.deployment_id as comp_latest_deployment,
             dp.id as known_deployment_id,
             ss.trace_id
-        FROM sys_invocation_status ss
+        FROM (SELECT * FROM sys_invocation_status WHERE status <> 'completed') ss
         LEFT JOIN sys_invocation_state sis ON ss.id = sis.id
         LEFT JOIN sys_component comp ON comp.name = ss.component
         LEFT JOIN sys_deployment dp ON dp.id = ss.pinned_deployment_id
@@ -12,6 +12,7 @@ use std::fmt::Display;
 use super::metas_client::Envelope;
 use super::MetasClient;
 
+use restate_meta_rest_model::cluster::*;
 use restate_meta_rest_model::components::*;
 use restate_meta_rest_model::deployments::*;
 
@@ -34,6 +35,8 @@ pub trait MetaClientInterface {
 
     async fn cancel_invocation(&self, id: &str, kill: bool) -> reqwest::Result<Envelope<()>>;
 
+    async fn get_cluster_status(&self) -> reqwest::Result<Envelope<ClusterStatusResponse>>;
+
     async fn patch_state(
         &self,
         service: &str,
@@ -110,6 +113,11 @@ impl MetaClientInterface for MetasClient {
         self.run(reqwest::Method::DELETE, url).await
     }
 
+    async fn get_cluster_status(&self) -> reqwest::Result<Envelope<ClusterStatusResponse>> {
+        let url = self.base_url.join("/cluster/status").expect("Bad url!");
+        self.run(reqwest::Method::GET, url).await
+    }
+
     async fn patch_state(
         &self,
         service: &str,
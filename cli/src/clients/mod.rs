@@ -13,8 +13,10 @@ mod datafusion_http_client;
 mod errors;
 mod metas_client;
 mod metas_interface;
+mod node_client;
 
 pub use self::datafusion_http_client::DataFusionHttpClient;
 pub use self::metas_client::Error as MetasClientError;
 pub use self::metas_client::MetasClient;
 pub use self::metas_interface::MetaClientInterface;
+pub use self::node_client::NodeClient;
@@ -0,0 +1,79 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A wrapper client for the node HTTP service.
+
+use std::time::Duration;
+
+use restate_meta_rest_model::tasks::TasksResponse;
+use serde::de::DeserializeOwned;
+use tracing::debug;
+use url::Url;
+
+use crate::build_info;
+use crate::cli_env::CliEnv;
+
+use super::metas_client::Envelope;
+
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A handy client for the introspection endpoints of a single node.
+#[derive(Clone)]
+pub struct NodeClient {
+    inner: reqwest::Client,
+    base_url: Url,
+    bearer_token: Option<String>,
+    request_timeout: Duration,
+}
+
+impl NodeClient {
+    pub fn new(env: &CliEnv) -> reqwest::Result<Self> {
+        let raw_client = reqwest::Client::builder()
+            .user_agent(format!(
+                "{}/{} {}-{}",
+                env!("CARGO_PKG_NAME"),
+                build_info::RESTATE_CLI_VERSION,
+                std::env::consts::OS,
+                std::env::consts::ARCH,
+            ))
+            .connect_timeout(env.connect_timeout)
+            .build()?;
+
+        Ok(Self {
+            inner: raw_client,
+            base_url: env.node_base_url.clone(),
+            bearer_token: env.bearer_token.clone(),
+            request_timeout: env.request_timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT),
+        })
+    }
+
+    pub async fn get_tasks(&self) -> reqwest::Result<Envelope<TasksResponse>> {
+        let url = self.base_url.join("/tasks").expect("Bad url!");
+        self.run(reqwest::Method::GET, url).await
+    }
+
+    /// Execute a request and return the response as a lazy Envelope.
+    async fn run<T>(&self, method: reqwest::Method, path: Url) -> reqwest::Result<Envelope<T>>
+    where
+        T: DeserializeOwned + Send,
+    {
+        debug!("Sending request {} ({})", method, path);
+        let request_builder = self
+            .inner
+            .request(method, path)
+            .timeout(self.request_timeout);
+        let request_builder = match self.bearer_token.as_deref() {
+            Some(token) => request_builder.bearer_auth(token),
+            None => request_builder,
+        };
+        let resp = request_builder.send().await?;
+        Ok(resp.into())
+    }
+}
@@ -0,0 +1,22 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+mod status;
+mod tasks;
+
+use cling::prelude::*;
+
+#[derive(Run, Subcommand, Clone)]
+pub enum Cluster {
+    /// Prints the nodes and partitions of the cluster
+    Status(status::Status),
+    /// Lists the tasks running on a node, similar to a thread dump
+    Tasks(tasks::Tasks),
+}
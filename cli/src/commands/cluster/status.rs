@@ -0,0 +1,148 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::c_title;
+use crate::cli_env::CliEnv;
+use crate::clients::MetaClientInterface;
+use crate::console::c_println;
+use crate::ui::console::StyledTable;
+use crate::ui::watcher::Watch;
+
+use restate_meta_rest_model::cluster::{
+    NodeLiveness, NodeState, PartitionProcessorStatusResponse, RunMode,
+};
+
+use anyhow::Result;
+use cling::prelude::*;
+use comfy_table::{Cell, Color, Table};
+
+#[derive(Run, Parser, Collect, Clone)]
+#[cling(run = "run_status")]
+pub struct Status {
+    #[clap(flatten)]
+    watch: Watch,
+}
+
+pub async fn run_status(State(env): State<CliEnv>, opts: &Status) -> Result<()> {
+    opts.watch.run(|| status(&env)).await
+}
+
+async fn status(env: &CliEnv) -> Result<()> {
+    let client = crate::clients::MetasClient::new(env)?;
+    let cluster_status = client.get_cluster_status().await?.into_body().await?;
+
+    c_title!("🖥️", "Nodes");
+    let mut nodes_table = Table::new_styled(&env.ui_config);
+    nodes_table.set_styled_header(vec![
+        "NODE",
+        "NAME",
+        "ROLES",
+        "STATE",
+        "LIVENESS",
+        "PARTITIONS",
+    ]);
+    for node in &cluster_status.nodes {
+        nodes_table.add_row(vec![
+            Cell::new(&node.node_id),
+            Cell::new(&node.name),
+            Cell::new(
+                node.roles
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            render_node_state(node.state),
+            render_liveness(node.liveness),
+            Cell::new(render_partition_processors(&node.partition_processors)),
+        ]);
+    }
+    c_println!("{}", nodes_table);
+    c_println!();
+
+    c_title!("🗂️", "Partitions");
+    let mut partitions_table = Table::new_styled(&env.ui_config);
+    partitions_table.set_styled_header(vec![
+        "PARTITION",
+        "KEYS",
+        "LEADER",
+        "FOLLOWERS",
+        "APPLIED LSN",
+        "LOG TAIL",
+        "LAG",
+    ]);
+    for partition in &cluster_status.partitions {
+        partitions_table.add_row(vec![
+            Cell::new(partition.partition_id),
+            Cell::new(format!("{}..={}", partition.start_key, partition.end_key)),
+            Cell::new(render_optional(partition.leader.as_ref())),
+            Cell::new(partition.followers.join(", ")),
+            Cell::new(render_optional(partition.applied_lsn)),
+            Cell::new(render_optional(partition.log_tail)),
+            render_lag(partition.applied_lsn, partition.log_tail),
+        ]);
+    }
+    c_println!("{}", partitions_table);
+
+    Ok(())
+}
+
+fn render_node_state(state: NodeState) -> Cell {
+    let color = match state {
+        NodeState::Active => Color::Green,
+        NodeState::Draining => Color::Yellow,
+        NodeState::Drained => Color::Grey,
+    };
+    Cell::new(state).fg(color)
+}
+
+fn render_liveness(liveness: Option<NodeLiveness>) -> Cell {
+    match liveness {
+        Some(NodeLiveness::Alive) => Cell::new(NodeLiveness::Alive).fg(Color::Green),
+        Some(NodeLiveness::Suspect) => Cell::new(NodeLiveness::Suspect).fg(Color::Yellow),
+        Some(NodeLiveness::Dead) => Cell::new(NodeLiveness::Dead).fg(Color::Red),
+        None => Cell::new("unknown").fg(Color::Grey),
+    }
+}
+
+fn render_partition_processors(processors: &[PartitionProcessorStatusResponse]) -> String {
+    processors
+        .iter()
+        .map(|processor| {
+            let run_mode = match processor.run_mode {
+                RunMode::Leader => "leader",
+                RunMode::Follower => "follower",
+            };
+            format!(
+                "{} ({run_mode}, applied {})",
+                processor.partition_id,
+                render_optional(processor.last_applied_lsn)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_lag(applied_lsn: Option<u64>, log_tail: Option<u64>) -> Cell {
+    let Some(log_tail) = log_tail else {
+        return Cell::new("-");
+    };
+
+    let lag = log_tail.saturating_sub(applied_lsn.unwrap_or_default());
+    if lag > 0 {
+        Cell::new(lag).fg(Color::Yellow)
+    } else {
+        Cell::new(lag).fg(Color::Grey)
+    }
+}
+
+fn render_optional(value: Option<impl ToString>) -> String {
+    value.map_or_else(|| "-".to_owned(), |value| value.to_string())
+}
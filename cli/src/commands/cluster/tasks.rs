@@ -0,0 +1,92 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::c_title;
+use crate::cli_env::CliEnv;
+use crate::clients::NodeClient;
+use crate::console::c_println;
+use crate::ui::console::StyledTable;
+use crate::ui::watcher::Watch;
+
+use restate_meta_rest_model::tasks::TaskResponse;
+
+use anyhow::Result;
+use cling::prelude::*;
+use comfy_table::{Cell, Color, Table};
+
+#[derive(Run, Parser, Collect, Clone)]
+#[cling(run = "run_tasks")]
+pub struct Tasks {
+    /// Only show tasks of this kind
+    #[clap(long)]
+    kind: Option<String>,
+
+    /// Only show tasks which belong to this partition
+    #[clap(long)]
+    partition: Option<u64>,
+
+    #[clap(flatten)]
+    watch: Watch,
+}
+
+pub async fn run_tasks(State(env): State<CliEnv>, opts: &Tasks) -> Result<()> {
+    opts.watch.run(|| tasks(&env, opts)).await
+}
+
+async fn tasks(env: &CliEnv, opts: &Tasks) -> Result<()> {
+    let client = NodeClient::new(env)?;
+    let tasks: Vec<TaskResponse> = client
+        .get_tasks()
+        .await?
+        .into_body()
+        .await?
+        .tasks
+        .into_iter()
+        .filter(|task| {
+            opts.kind
+                .as_ref()
+                .map_or(true, |kind| task.kind.eq_ignore_ascii_case(kind))
+        })
+        .filter(|task| {
+            opts.partition
+                .map_or(true, |partition_id| task.partition_id == Some(partition_id))
+        })
+        .collect();
+
+    c_title!("🧵", "Tasks of {}", env.node_base_url);
+    let mut table = Table::new_styled(&env.ui_config);
+    table.set_styled_header(vec!["ID", "NAME", "KIND", "PARTITION", "AGE", "CANCELLING"]);
+    for task in &tasks {
+        table.add_row(vec![
+            Cell::new(task.id),
+            Cell::new(&task.name),
+            Cell::new(&task.kind),
+            Cell::new(
+                task.partition_id
+                    .map_or_else(|| "-".to_owned(), |id| id.to_string()),
+            ),
+            Cell::new(task.age),
+            render_cancellation_requested(task.cancellation_requested),
+        ]);
+    }
+    c_println!("{}", table);
+    c_println!();
+    c_println!("{} tasks", tasks.len());
+
+    Ok(())
+}
+
+fn render_cancellation_requested(cancellation_requested: bool) -> Cell {
+    if cancellation_requested {
+        Cell::new("yes").fg(Color::Yellow)
+    } else {
+        Cell::new("no").fg(Color::Grey)
+    }
+}
@@ -8,7 +8,8 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use restate_meta::{FileMetaReader, MetaHandle};
+use restate_core::metadata_store::MetadataStoreClient;
+use restate_meta::MetaHandle;
 use restate_schema_impl::Schemas;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
@@ -48,8 +49,8 @@ impl Options {
         self,
         schemas: Schemas,
         meta_handle: MetaHandle,
-        schema_reader: FileMetaReader,
+        metadata_store_client: MetadataStoreClient,
     ) -> AdminService {
-        AdminService::new(self, schemas, meta_handle, schema_reader)
+        AdminService::new(self, schemas, meta_handle, metadata_store_client)
     }
 }
@@ -0,0 +1,291 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::error::*;
+use crate::state::AdminServiceState;
+
+use restate_meta_rest_model::cluster::*;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use okapi_operation::*;
+use restate_cluster_controller::{ClusterStatus, RunMode as ClusterRunMode};
+use restate_types::identifiers::PartitionId;
+use restate_types::metadata_store::keys::NODES_LIVENESS_KEY;
+use restate_types::node_liveness::NodesLiveness;
+use restate_types::PlainNodeId;
+
+/// Get the liveness of the cluster's nodes
+#[openapi(
+    summary = "Get nodes liveness",
+    description = "Get the liveness of the current generation of every node as observed by the failure detector of the cluster controller.",
+    operation_id = "get_nodes_liveness",
+    tags = "cluster"
+)]
+pub async fn get_nodes_liveness(
+    State(state): State<AdminServiceState>,
+) -> Result<Json<NodesLivenessResponse>, MetaApiError> {
+    let nodes_liveness = state
+        .metadata_store_client()
+        .get::<NodesLiveness>(NODES_LIVENESS_KEY.clone())
+        .await?
+        .unwrap_or_default();
+
+    let mut nodes: Vec<_> = nodes_liveness.iter().collect();
+    nodes.sort_by_key(|(node_id, _)| node_id.as_plain());
+
+    Ok(NodesLivenessResponse {
+        version: nodes_liveness.version().into(),
+        nodes: nodes
+            .into_iter()
+            .map(|(node_id, liveness)| NodeLivenessResponse {
+                node_id: node_id.to_string(),
+                liveness,
+            })
+            .collect(),
+    }
+    .into())
+}
+
+/// Get the status of the cluster
+#[openapi(
+    summary = "Get cluster status",
+    description = "Get the nodes of the cluster with their roles, liveness and partition processors, and the partitions with their leader, followers and log tail. Every partition reports the LSN of the last record its leader has applied, which can be compared to the log tail of the partition.",
+    operation_id = "get_cluster_status",
+    tags = "cluster"
+)]
+pub async fn get_cluster_status(
+    State(state): State<AdminServiceState>,
+) -> Result<Json<ClusterStatusResponse>, MetaApiError> {
+    let ClusterStatus { nodes, partitions } =
+        state.cluster_controller_handle().cluster_status().await?;
+
+    Ok(ClusterStatusResponse {
+        nodes: nodes
+            .into_iter()
+            .map(|node| NodeStatusResponse {
+                node_id: node.node_id.to_string(),
+                name: node.name,
+                roles: node.roles.iter().collect(),
+                state: node.state,
+                liveness: node.liveness,
+                partition_processors: node
+                    .partition_processors
+                    .into_iter()
+                    .map(|processor| PartitionProcessorStatusResponse {
+                        partition_id: processor.partition_id,
+                        run_mode: match processor.run_mode {
+                            ClusterRunMode::Leader => RunMode::Leader,
+                            ClusterRunMode::Follower => RunMode::Follower,
+                        },
+                        last_applied_lsn: processor.last_applied_lsn.map(Into::into),
+                    })
+                    .collect(),
+            })
+            .collect(),
+        partitions: partitions
+            .into_iter()
+            .map(|partition| PartitionStatusResponse {
+                partition_id: partition.partition_id,
+                start_key: *partition.key_range.start(),
+                end_key: *partition.key_range.end(),
+                leader: partition
+                    .replicas
+                    .as_ref()
+                    .map(|replicas| replicas.leader.to_string()),
+                leader_epoch: partition
+                    .replicas
+                    .as_ref()
+                    .map(|replicas| replicas.leader_epoch.into()),
+                followers: partition
+                    .replicas
+                    .iter()
+                    .flat_map(|replicas| &replicas.followers)
+                    .map(ToString::to_string)
+                    .collect(),
+                applied_lsn: partition.applied_lsn.map(Into::into),
+                log_tail: partition.log_tail.map(Into::into),
+            })
+            .collect(),
+    }
+    .into())
+}
+
+/// Split a partition
+#[openapi(
+    summary = "Split a partition",
+    description = "Split the key range of a partition into two. The partition keeps the lower part of its key range, and a new partition takes over the upper part together with its state, inbox and timers.",
+    operation_id = "split_partition",
+    tags = "cluster",
+    parameters(path(
+        name = "partition_id",
+        description = "Id of the partition to split.",
+        schema = "u64"
+    ))
+)]
+pub async fn split_partition(
+    State(state): State<AdminServiceState>,
+    Path(partition_id): Path<PartitionId>,
+    #[request_body(required = true)] Json(SplitPartitionRequest { split_key }): Json<
+        SplitPartitionRequest,
+    >,
+) -> Result<Json<PartitionCreatedResponse>, MetaApiError> {
+    let partition_id = state
+        .cluster_controller_handle()
+        .split_partition(partition_id, split_key)
+        .await?;
+
+    Ok(PartitionCreatedResponse { partition_id }.into())
+}
+
+/// Merge two partitions
+#[openapi(
+    summary = "Merge two partitions",
+    description = "Merge two partitions with adjacent key ranges. A new partition takes over the key ranges of both partitions together with their state, inboxes and timers.",
+    operation_id = "merge_partitions",
+    tags = "cluster",
+    parameters(path(
+        name = "partition_id",
+        description = "Id of one of the partitions to merge.",
+        schema = "u64"
+    ))
+)]
+pub async fn merge_partitions(
+    State(state): State<AdminServiceState>,
+    Path(partition_id): Path<PartitionId>,
+    #[request_body(required = true)] Json(MergePartitionsRequest { other_partition_id }): Json<
+        MergePartitionsRequest,
+    >,
+) -> Result<Json<PartitionCreatedResponse>, MetaApiError> {
+    let partition_id = state
+        .cluster_controller_handle()
+        .merge_partitions(partition_id, other_partition_id)
+        .await?;
+
+    Ok(PartitionCreatedResponse { partition_id }.into())
+}
+
+/// Drain a node
+#[openapi(
+    summary = "Drain a node",
+    description = "Mark a node as draining before removing it from the cluster. Its partitions are moved to other nodes, and its ingress stops accepting new requests and waits for the in-flight ones. Afterwards the node is drained.",
+    operation_id = "drain_node",
+    tags = "cluster",
+    parameters(path(
+        name = "node_id",
+        description = "Plain id of the node to drain.",
+        schema = "u32"
+    ))
+)]
+pub async fn drain_node(
+    State(state): State<AdminServiceState>,
+    Path(node_id): Path<u32>,
+) -> Result<Json<DrainNodeResponse>, MetaApiError> {
+    let state = state
+        .cluster_controller_handle()
+        .drain_node(PlainNodeId::from(node_id))
+        .await?;
+
+    Ok(DrainNodeResponse { state }.into())
+}
+
+/// Assign a role to a node
+#[openapi(
+    summary = "Assign a role to a node",
+    description = "Assign the worker or admin role to a running node. The node starts the role's services without a restart. Workers are assigned partitions afterwards. Assigned roles are kept when the node restarts, the configured worker and admin roles only apply when the node joins the cluster.",
+    operation_id = "assign_node_role",
+    tags = "cluster",
+    parameters(
+        path(
+            name = "node_id",
+            description = "Plain id of the node.",
+            schema = "u32"
+        ),
+        path(name = "role", description = "Role to assign.", schema = "Role")
+    )
+)]
+pub async fn assign_node_role(
+    State(state): State<AdminServiceState>,
+    Path((node_id, role)): Path<(u32, Role)>,
+) -> Result<Json<NodeRolesResponse>, MetaApiError> {
+    let roles = state
+        .cluster_controller_handle()
+        .assign_role(PlainNodeId::from(node_id), role)
+        .await?;
+
+    Ok(NodeRolesResponse {
+        roles: roles.iter().collect(),
+    }
+    .into())
+}
+
+/// Unassign a role from a node
+#[openapi(
+    summary = "Unassign a role from a node",
+    description = "Remove the worker or admin role from a running node. The node stops the role's services without a restart. The partitions of a former worker are moved to other workers. A role can only be removed if another active node which is alive has it.",
+    operation_id = "unassign_node_role",
+    tags = "cluster",
+    parameters(
+        path(
+            name = "node_id",
+            description = "Plain id of the node.",
+            schema = "u32"
+        ),
+        path(name = "role", description = "Role to remove.", schema = "Role")
+    )
+)]
+pub async fn unassign_node_role(
+    State(state): State<AdminServiceState>,
+    Path((node_id, role)): Path<(u32, Role)>,
+) -> Result<Json<NodeRolesResponse>, MetaApiError> {
+    let roles = state
+        .cluster_controller_handle()
+        .unassign_role(PlainNodeId::from(node_id), role)
+        .await?;
+
+    Ok(NodeRolesResponse {
+        roles: roles.iter().collect(),
+    }
+    .into())
+}
+
+/// Remove a node
+#[openapi(
+    summary = "Remove a node",
+    description = "Remove a drained node from the cluster. Its node id won't be reused.",
+    operation_id = "remove_node",
+    tags = "cluster",
+    parameters(path(
+        name = "node_id",
+        description = "Plain id of the node to remove.",
+        schema = "u32"
+    )),
+    responses(
+        ignore_return_type = true,
+        response(
+            status = "204",
+            description = "Removed",
+            content = "okapi_operation::Empty",
+        ),
+        from_type = "MetaApiError",
+    )
+)]
+pub async fn remove_node(
+    State(state): State<AdminServiceState>,
+    Path(node_id): Path<u32>,
+) -> Result<StatusCode, MetaApiError> {
+    state
+        .cluster_controller_handle()
+        .remove_node(PlainNodeId::from(node_id))
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -8,8 +8,8 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use super::create_envelope_header;
 use super::error::*;
-use super::{create_envelope_header, notify_node_about_schema_changes};
 use crate::state::AdminServiceState;
 
 use axum::extract::{Path, State};
@@ -88,8 +88,6 @@ pub async fn modify_component(
         .modify_component(component_name.clone(), public)
         .await?;
 
-    notify_node_about_schema_changes(state.schema_reader(), state.node_svc_client()).await;
-
     state
         .schemas()
         .resolve_latest_component(&component_name)
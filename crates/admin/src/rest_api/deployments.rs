@@ -9,7 +9,6 @@
 // by the Apache License, Version 2.0.
 
 use super::error::*;
-use super::notify_node_about_schema_changes;
 use crate::state::AdminServiceState;
 
 use axum::body::Bytes;
@@ -96,8 +95,6 @@ pub async fn create_deployment(
         .register_deployment(discover_endpoint, force, apply_changes)
         .await?;
 
-    notify_node_about_schema_changes(state.schema_reader(), state.node_svc_client()).await;
-
     let response_body = RegisterDeploymentResponse {
         id: registration_result.deployment,
         components: registration_result.components,
@@ -218,7 +215,6 @@ pub async fn delete_deployment(
 ) -> Result<StatusCode, MetaApiError> {
     if let Some(true) = force {
         state.meta_handle().remove_deployment(deployment_id).await?;
-        notify_node_about_schema_changes(state.schema_reader(), state.node_svc_client()).await;
         Ok(StatusCode::ACCEPTED)
     } else {
         Ok(StatusCode::NOT_IMPLEMENTED)
@@ -16,9 +16,13 @@ use okapi_operation::anyhow::Error;
 use okapi_operation::okapi::map;
 use okapi_operation::okapi::openapi3::Responses;
 use okapi_operation::{okapi, Components, ToMediaTypes, ToResponses};
+use restate_cluster_controller::Error as ClusterControllerError;
+use restate_core::metadata_store::{ReadError, SnapshotCodecError, WriteError};
 use restate_meta::Error as MetaError;
 use restate_schema_impl::{ComponentError, DeploymentError, ErrorKind};
-use restate_types::identifiers::{DeploymentId, SubscriptionId};
+use restate_types::identifiers::{DeploymentId, InvocationId, SubscriptionId};
+use restate_types::nodes_config::NodesConfigError;
+use restate_types::partition_table::PartitionTableUpdateError;
 use schemars::JsonSchema;
 use serde::Serialize;
 
@@ -41,10 +45,20 @@ pub enum MetaApiError {
     },
     #[error("The requested subscription '{0}' does not exist")]
     SubscriptionNotFound(SubscriptionId),
+    #[error("The requested invocation '{0}' does not exist")]
+    InvocationNotFound(InvocationId),
     #[error(transparent)]
     Meta(#[from] MetaError),
     #[error(transparent)]
     Worker(#[from] restate_worker_api::Error),
+    #[error("The metadata store snapshot is invalid. Reason: {0}")]
+    InvalidSnapshot(#[from] SnapshotCodecError),
+    #[error("Failed reading from the metadata store: {0}")]
+    MetadataStoreRead(#[from] ReadError),
+    #[error("Failed writing to the metadata store: {0}")]
+    MetadataStoreWrite(#[from] WriteError),
+    #[error(transparent)]
+    ClusterController(#[from] ClusterControllerError),
     #[error("Internal server error: {0}")]
     Internal(String),
 }
@@ -67,9 +81,37 @@ impl IntoResponse for MetaApiError {
             MetaApiError::ComponentNotFound(_)
             | MetaApiError::HandlerNotFound { .. }
             | MetaApiError::DeploymentNotFound(_)
-            | MetaApiError::SubscriptionNotFound(_) => StatusCode::NOT_FOUND,
-            MetaApiError::InvalidField(_, _) => StatusCode::BAD_REQUEST,
-            MetaApiError::Worker(_) => StatusCode::SERVICE_UNAVAILABLE,
+            | MetaApiError::SubscriptionNotFound(_)
+            | MetaApiError::InvocationNotFound(_) => StatusCode::NOT_FOUND,
+            MetaApiError::ClusterController(ClusterControllerError::PartitionTableUpdate(
+                PartitionTableUpdateError::UnknownPartition(_),
+            )) => StatusCode::NOT_FOUND,
+            MetaApiError::ClusterController(ClusterControllerError::NodesConfig(
+                NodesConfigError::UnknownNodeId(_) | NodesConfigError::Deleted(_),
+            )) => StatusCode::NOT_FOUND,
+            MetaApiError::ClusterController(
+                ClusterControllerError::PartitionTableUpdate(
+                    PartitionTableUpdateError::PendingHandOver(_),
+                )
+                | ClusterControllerError::NodeNotDrained(..)
+                | ClusterControllerError::NodeNotActive(..)
+                | ClusterControllerError::LastRoleHolder(..),
+            ) => StatusCode::CONFLICT,
+            MetaApiError::InvalidField(_, _)
+            | MetaApiError::InvalidSnapshot(_)
+            | MetaApiError::ClusterController(
+                ClusterControllerError::PartitionTableUpdate(_)
+                | ClusterControllerError::UnsupportedRoleChange(_),
+            ) => StatusCode::BAD_REQUEST,
+            MetaApiError::Worker(_)
+            | MetaApiError::ClusterController(ClusterControllerError::ControllerClosed)
+            | MetaApiError::MetadataStoreRead(ReadError::Network(_))
+            | MetaApiError::MetadataStoreWrite(WriteError::Network(_)) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            MetaApiError::MetadataStoreWrite(WriteError::FailedPrecondition(_)) => {
+                StatusCode::CONFLICT
+            }
             MetaApiError::Meta(MetaError::SchemaRegistry(schema_registry_error)) => {
                 match schema_registry_error.kind() {
                     ErrorKind::NotFound => StatusCode::NOT_FOUND,
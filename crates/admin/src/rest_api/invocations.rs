@@ -12,15 +12,110 @@ use super::error::*;
 
 use crate::rest_api::create_envelope_header;
 use crate::state::AdminServiceState;
+use arrow_flight::decode::FlightRecordBatchStream;
+use arrow_flight::error::FlightError;
+use arrow_flight::FlightData;
 use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
+use axum::Json;
+use datafusion::arrow::array::{Array, AsArray};
+use datafusion::arrow::datatypes::Date64Type;
+use datafusion::arrow::record_batch::RecordBatch;
+use futures::TryStreamExt;
 use okapi_operation::*;
+use restate_meta_rest_model::invocations::{InvocationResponse, MillisSinceEpoch};
+use restate_node_services::node_svc::StorageQueryRequest;
 use restate_types::identifiers::{InvocationId, WithPartitionKey};
-use restate_types::invocation::InvocationTermination;
+use restate_types::invocation::{InvocationTermination, PurgeInvocationRequest};
 use restate_wal_protocol::{append_envelope_to_bifrost, Command, Envelope};
 use serde::Deserialize;
 use tracing::warn;
 
+/// Get an invocation
+#[openapi(
+    summary = "Get an invocation",
+    description = "Get the status of the given invocation. Completed invocations are returned until \
+    their completion retention time expired or they are purged.",
+    operation_id = "get_invocation",
+    tags = "invocation",
+    parameters(path(
+        name = "invocation_id",
+        description = "Invocation identifier.",
+        schema = "std::string::String"
+    ))
+)]
+pub async fn get_invocation(
+    State(state): State<AdminServiceState>,
+    Path(invocation_id): Path<String>,
+) -> Result<Json<InvocationResponse>, MetaApiError> {
+    let invocation_id = invocation_id
+        .parse::<InvocationId>()
+        .map_err(|e| MetaApiError::InvalidField("invocation_id", e.to_string()))?;
+
+    let response_stream = state
+        .node_svc_client()
+        .clone()
+        .query_storage(StorageQueryRequest {
+            query: format!(
+                "SELECT id, status, component, component_key, handler, created_at, modified_at, \
+                completion_result, completion_failure FROM sys_invocation_status \
+                WHERE id = '{invocation_id}'"
+            ),
+        })
+        .await
+        .map_err(|err| MetaApiError::Internal(format!("Failed querying the invocation: {err}")))?
+        .into_inner();
+
+    let record_batches: Vec<RecordBatch> = FlightRecordBatchStream::new_from_flight_data(
+        response_stream
+            .map_ok(|response| FlightData {
+                data_header: response.header,
+                data_body: response.data,
+                ..FlightData::default()
+            })
+            .map_err(FlightError::from),
+    )
+    .try_collect()
+    .await
+    .map_err(|err| MetaApiError::Internal(format!("Failed querying the invocation: {err}")))?;
+
+    let record_batch = record_batches
+        .iter()
+        .find(|record_batch| record_batch.num_rows() > 0)
+        .ok_or_else(|| MetaApiError::InvocationNotFound(invocation_id))?;
+
+    Ok(InvocationResponse {
+        id: string_value(record_batch, "id").unwrap_or_default(),
+        status: string_value(record_batch, "status").unwrap_or_default(),
+        component: string_value(record_batch, "component"),
+        component_key: string_value(record_batch, "component_key"),
+        handler: string_value(record_batch, "handler"),
+        created_at: timestamp_value(record_batch, "created_at"),
+        modified_at: timestamp_value(record_batch, "modified_at"),
+        completion_result: string_value(record_batch, "completion_result"),
+        completion_failure: string_value(record_batch, "completion_failure"),
+    }
+    .into())
+}
+
+/// Value of the given string column in the first row of the record batch.
+fn string_value(record_batch: &RecordBatch, column: &str) -> Option<String> {
+    let array = record_batch
+        .column_by_name(column)?
+        .as_string_opt::<i64>()?;
+    array.is_valid(0).then(|| array.value(0).to_owned())
+}
+
+/// Value of the given timestamp column in the first row of the record batch.
+fn timestamp_value(record_batch: &RecordBatch, column: &str) -> Option<MillisSinceEpoch> {
+    let array = record_batch
+        .column_by_name(column)?
+        .as_primitive_opt::<Date64Type>()?;
+    array
+        .is_valid(0)
+        .then(|| MillisSinceEpoch::new(array.value(0) as u64))
+}
+
 #[derive(Debug, Default, Deserialize, JsonSchema)]
 pub enum TerminationMode {
     #[default]
@@ -28,6 +123,8 @@ pub enum TerminationMode {
     Cancel,
     #[serde(alias = "kill")]
     Kill,
+    #[serde(alias = "purge")]
+    Purge,
 }
 #[derive(Debug, Default, Deserialize, JsonSchema)]
 pub struct DeleteInvocationParams {
@@ -39,7 +136,8 @@ pub struct DeleteInvocationParams {
     summary = "Terminate an invocation",
     description = "Terminate the given invocation. By default, an invocation is terminated by gracefully \
     cancelling it. This ensures virtual object state consistency. Alternatively, an invocation can be killed which \
-    does not guarantee consistency for virtual object instance state, in-flight invocations to other components, etc.",
+    does not guarantee consistency for virtual object instance state, in-flight invocations to other components, etc. \
+    A completed invocation can be purged, which drops its retained result.",
     operation_id = "terminate_invocation",
     tags = "invocation",
     parameters(
@@ -50,7 +148,7 @@ pub struct DeleteInvocationParams {
         ),
         query(
             name = "mode",
-            description = "If cancel, it will gracefully terminate the invocation. If kill, it will terminate the invocation with a hard stop.",
+            description = "If cancel, it will gracefully terminate the invocation. If kill, it will terminate the invocation with a hard stop. If purge, it will drop the retained result of the completed invocation.",
             required = false,
             style = "simple",
             allow_empty_value = false,
@@ -76,13 +174,19 @@ pub async fn delete_invocation(
         .parse::<InvocationId>()
         .map_err(|e| MetaApiError::InvalidField("invocation_id", e.to_string()))?;
 
-    let invocation_termination = match mode.unwrap_or_default() {
-        TerminationMode::Cancel => InvocationTermination::cancel(invocation_id),
-        TerminationMode::Kill => InvocationTermination::kill(invocation_id),
+    let partition_key = invocation_id.partition_key();
+    let command = match mode.unwrap_or_default() {
+        TerminationMode::Cancel => {
+            Command::TerminateInvocation(InvocationTermination::cancel(invocation_id))
+        }
+        TerminationMode::Kill => {
+            Command::TerminateInvocation(InvocationTermination::kill(invocation_id))
+        }
+        TerminationMode::Purge => {
+            Command::PurgeInvocation(PurgeInvocationRequest { invocation_id })
+        }
     };
 
-    let partition_key = invocation_termination.maybe_fid.partition_key();
-
     let result = state
         .task_center
         .run_in_scope(
@@ -90,10 +194,7 @@ pub async fn delete_invocation(
             None,
             append_envelope_to_bifrost(
                 &mut state.bifrost,
-                Envelope::new(
-                    create_envelope_header(partition_key),
-                    Command::TerminateInvocation(invocation_termination),
-                ),
+                Envelope::new(create_envelope_header(partition_key), command),
             ),
         )
         .await;
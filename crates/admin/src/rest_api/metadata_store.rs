@@ -0,0 +1,102 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::error::*;
+use crate::state::AdminServiceState;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use okapi_operation::anyhow::Error;
+use okapi_operation::okapi::openapi3::{MediaType, Responses};
+use okapi_operation::okapi::Map;
+use okapi_operation::*;
+use restate_core::metadata_store::MetadataStoreSnapshot;
+
+/// Export a snapshot of the metadata store
+#[openapi(
+    summary = "Export metadata store snapshot",
+    description = "Export a consistent snapshot of all key-value pairs of the metadata store, including the nodes configuration, the partition table and the schema information. The snapshot can be restored into a fresh metadata store.",
+    operation_id = "export_metadata_store_snapshot",
+    tags = "metadata_store",
+    responses(
+        ignore_return_type = true,
+        response(status = "200", description = "OK", content = "SnapshotBytes"),
+        from_type = "MetaApiError",
+    )
+)]
+pub async fn export_snapshot(
+    State(state): State<AdminServiceState>,
+) -> Result<SnapshotBytes, MetaApiError> {
+    let snapshot = state.metadata_store_client().snapshot().await?;
+    let bytes = snapshot
+        .encode()
+        .map_err(|err| MetaApiError::Internal(err.to_string()))?;
+
+    Ok(SnapshotBytes(bytes))
+}
+
+/// Restore a snapshot into the metadata store
+#[openapi(
+    summary = "Restore metadata store snapshot",
+    description = "Restore a snapshot which has been exported via the export endpoint. The metadata store must be empty, otherwise the request fails.",
+    operation_id = "restore_metadata_store_snapshot",
+    tags = "metadata_store",
+    responses(
+        ignore_return_type = true,
+        response(
+            status = "200",
+            description = "Restored",
+            content = "okapi_operation::Empty",
+        ),
+        from_type = "MetaApiError",
+    )
+)]
+pub async fn restore_snapshot(
+    State(state): State<AdminServiceState>,
+    body: Bytes,
+) -> Result<StatusCode, MetaApiError> {
+    let snapshot = MetadataStoreSnapshot::decode(&body)?;
+    state.metadata_store_client().restore(snapshot).await?;
+
+    Ok(StatusCode::OK)
+}
+
+pub struct SnapshotBytes(Bytes);
+
+impl IntoResponse for SnapshotBytes {
+    fn into_response(self) -> Response {
+        (
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/octet-stream"),
+            )],
+            self.0,
+        )
+            .into_response()
+    }
+}
+
+impl ToMediaTypes for SnapshotBytes {
+    fn generate(_components: &mut Components) -> Result<Map<String, MediaType>, anyhow::Error> {
+        Ok(okapi::map! {
+            "application/octet-stream".into() => {
+                MediaType { ..Default::default() }
+            }
+        })
+    }
+}
+
+impl ToResponses for SnapshotBytes {
+    fn generate(_components: &mut Components) -> Result<Responses, Error> {
+        Ok(Responses::default())
+    }
+}
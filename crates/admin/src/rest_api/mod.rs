@@ -10,23 +10,21 @@
 
 //! This module implements the Meta API endpoint.
 
+mod cluster;
 mod components;
 mod deployments;
 mod error;
 mod handlers;
 mod health;
 mod invocations;
+mod metadata_store;
 mod subscriptions;
 
 use okapi_operation::axum_integration::{delete, get, patch, post};
 use okapi_operation::*;
-use restate_meta::{FileMetaReader, MetaReader};
-use restate_node_services::node_svc::node_svc_client::NodeSvcClient;
-use restate_node_services::node_svc::UpdateSchemaRequest;
 use restate_types::identifiers::PartitionKey;
+use restate_types::time::MillisSinceEpoch;
 use restate_wal_protocol::{Destination, Header, Source};
-use tonic::transport::Channel;
-use tracing::debug;
 
 use crate::state::AdminServiceState;
 
@@ -73,6 +71,10 @@ pub fn create_router(state: AdminServiceState) -> axum::Router<()> {
             "/components/:component/handlers/:handler",
             get(openapi_handler!(handlers::get_component_handler)),
         )
+        .route(
+            "/invocations/:invocation_id",
+            get(openapi_handler!(invocations::get_invocation)),
+        )
         .route(
             "/invocations/:invocation_id",
             delete(openapi_handler!(invocations::delete_invocation)),
@@ -93,6 +95,46 @@ pub fn create_router(state: AdminServiceState) -> axum::Router<()> {
             "/subscriptions/:subscription",
             delete(openapi_handler!(subscriptions::delete_subscription)),
         )
+        .route(
+            "/metadata-store/snapshot",
+            get(openapi_handler!(metadata_store::export_snapshot)),
+        )
+        .route(
+            "/metadata-store/snapshot",
+            post(openapi_handler!(metadata_store::restore_snapshot)),
+        )
+        .route(
+            "/cluster/status",
+            get(openapi_handler!(cluster::get_cluster_status)),
+        )
+        .route(
+            "/cluster/liveness",
+            get(openapi_handler!(cluster::get_nodes_liveness)),
+        )
+        .route(
+            "/cluster/partitions/:partition_id/split",
+            post(openapi_handler!(cluster::split_partition)),
+        )
+        .route(
+            "/cluster/partitions/:partition_id/merge",
+            post(openapi_handler!(cluster::merge_partitions)),
+        )
+        .route(
+            "/cluster/nodes/:node_id/drain",
+            post(openapi_handler!(cluster::drain_node)),
+        )
+        .route(
+            "/cluster/nodes/:node_id",
+            delete(openapi_handler!(cluster::remove_node)),
+        )
+        .route(
+            "/cluster/nodes/:node_id/roles/:role",
+            post(openapi_handler!(cluster::assign_node_role)),
+        )
+        .route(
+            "/cluster/nodes/:node_id/roles/:role",
+            delete(openapi_handler!(cluster::unassign_node_role)),
+        )
         .route("/health", get(openapi_handler!(health::health)))
         .route_openapi_specification(
             "/openapi",
@@ -102,39 +144,6 @@ pub fn create_router(state: AdminServiceState) -> axum::Router<()> {
         .with_state(state)
 }
 
-/// Notifies the node about schema changes. This method is best-effort and will not fail if the node
-/// could not be reached or the schema changes cannot serialized.
-async fn notify_node_about_schema_changes(
-    schema_reader: &FileMetaReader,
-    mut node_svc_client: NodeSvcClient<Channel>,
-) {
-    let schema_updates = schema_reader
-        .read()
-        .await
-        .map_err(anyhow::Error::from)
-        .and_then(|schema_updates| {
-            bincode::serde::encode_to_vec(schema_updates, bincode::config::standard())
-                .map_err(anyhow::Error::from)
-        });
-
-    if let Err(err) = schema_updates {
-        debug!("Failed serializing schema changes for notifying node about schema changes: {err}");
-        return;
-    }
-
-    let schema_updates = schema_updates.unwrap();
-
-    let result = node_svc_client
-        .update_schemas(UpdateSchemaRequest {
-            schema_bin: schema_updates.into(),
-        })
-        .await;
-
-    if let Err(err) = result {
-        debug!("Failed notifying node about schema changes: {err}");
-    }
-}
-
 fn create_envelope_header(partition_key: PartitionKey) -> Header {
     Header {
         source: Source::ControlPlane {},
@@ -142,5 +151,6 @@ fn create_envelope_header(partition_key: PartitionKey) -> Header {
             partition_key,
             dedup: None,
         },
+        created_at: Some(MillisSinceEpoch::now()),
     }
 }
@@ -14,7 +14,6 @@ use crate::state::AdminServiceState;
 use restate_meta_rest_model::subscriptions::*;
 use restate_schema_api::subscription::SubscriptionResolver;
 
-use crate::rest_api::notify_node_about_schema_changes;
 use axum::extract::Query;
 use axum::extract::{Path, State};
 use axum::http::StatusCode;
@@ -53,8 +52,6 @@ pub async fn create_subscription(
         )
         .await?;
 
-    notify_node_about_schema_changes(state.schema_reader(), state.node_svc_client()).await;
-
     Ok((
         StatusCode::CREATED,
         [(
@@ -171,7 +168,5 @@ pub async fn delete_subscription(
         .delete_subscription(subscription_id)
         .await?;
 
-    notify_node_about_schema_changes(state.schema_reader(), state.node_svc_client()).await;
-
     Ok(StatusCode::ACCEPTED)
 }
@@ -14,12 +14,14 @@ use std::sync::Arc;
 use axum::error_handling::HandleErrorLayer;
 use http::StatusCode;
 use restate_bifrost::Bifrost;
+use restate_cluster_controller::ClusterControllerHandle;
 use tonic::transport::Channel;
 use tower::ServiceBuilder;
 use tracing::info;
 
+use restate_core::metadata_store::MetadataStoreClient;
 use restate_core::{cancellation_watcher, task_center};
-use restate_meta::{FileMetaReader, MetaHandle};
+use restate_meta::MetaHandle;
 use restate_node_services::node_svc::node_svc_client::NodeSvcClient;
 use restate_schema_impl::Schemas;
 
@@ -31,7 +33,7 @@ pub struct AdminService {
     opts: Options,
     schemas: Schemas,
     meta_handle: MetaHandle,
-    schema_reader: FileMetaReader,
+    metadata_store_client: MetadataStoreClient,
 }
 
 impl AdminService {
@@ -39,13 +41,13 @@ impl AdminService {
         opts: Options,
         schemas: Schemas,
         meta_handle: MetaHandle,
-        schema_reader: FileMetaReader,
+        metadata_store_client: MetadataStoreClient,
     ) -> Self {
         Self {
             opts,
             schemas,
             meta_handle,
-            schema_reader,
+            metadata_store_client,
         }
     }
 
@@ -53,12 +55,14 @@ impl AdminService {
         self,
         node_svc_client: NodeSvcClient<Channel>,
         bifrost: Bifrost,
+        cluster_controller_handle: ClusterControllerHandle,
     ) -> anyhow::Result<()> {
         let rest_state = state::AdminServiceState::new(
             self.meta_handle,
             self.schemas,
+            self.metadata_store_client,
+            cluster_controller_handle,
             node_svc_client.clone(),
-            self.schema_reader,
             bifrost,
             task_center(),
         );
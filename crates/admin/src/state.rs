@@ -10,8 +10,10 @@
 //
 
 use restate_bifrost::Bifrost;
+use restate_cluster_controller::ClusterControllerHandle;
+use restate_core::metadata_store::MetadataStoreClient;
 use restate_core::TaskCenter;
-use restate_meta::{FileMetaReader, MetaHandle};
+use restate_meta::MetaHandle;
 use restate_node_services::node_svc::node_svc_client::NodeSvcClient;
 use restate_schema_impl::Schemas;
 use tonic::transport::Channel;
@@ -20,8 +22,9 @@ use tonic::transport::Channel;
 pub struct AdminServiceState {
     meta_handle: MetaHandle,
     schemas: Schemas,
+    metadata_store_client: MetadataStoreClient,
+    cluster_controller_handle: ClusterControllerHandle,
     node_svc_client: NodeSvcClient<Channel>,
-    schema_reader: FileMetaReader,
     pub bifrost: Bifrost,
     pub task_center: TaskCenter,
 }
@@ -35,16 +38,18 @@ impl AdminServiceState {
     pub fn new(
         meta_handle: MetaHandle,
         schemas: Schemas,
+        metadata_store_client: MetadataStoreClient,
+        cluster_controller_handle: ClusterControllerHandle,
         node_svc_client: NodeSvcClient<Channel>,
-        schema_reader: FileMetaReader,
         bifrost: Bifrost,
         task_center: TaskCenter,
     ) -> Self {
         Self {
             meta_handle,
             schemas,
+            metadata_store_client,
+            cluster_controller_handle,
             node_svc_client,
-            schema_reader,
             bifrost,
             task_center,
         }
@@ -58,11 +63,15 @@ impl AdminServiceState {
         &self.schemas
     }
 
-    pub fn node_svc_client(&self) -> NodeSvcClient<Channel> {
-        self.node_svc_client.clone()
+    pub fn metadata_store_client(&self) -> &MetadataStoreClient {
+        &self.metadata_store_client
     }
 
-    pub fn schema_reader(&self) -> &FileMetaReader {
-        &self.schema_reader
+    pub fn cluster_controller_handle(&self) -> &ClusterControllerHandle {
+        &self.cluster_controller_handle
+    }
+
+    pub fn node_svc_client(&self) -> &NodeSvcClient<Channel> {
+        &self.node_svc_client
     }
 }
@@ -22,9 +22,7 @@ use tokio::runtime::Runtime;
 
 use restate_core::{TaskCenter, TaskCenterFactory};
 use restate_node::Node;
-use restate_node::{
-    MetaOptionsBuilder, NodeOptionsBuilder, RocksdbOptionsBuilder, WorkerOptionsBuilder,
-};
+use restate_node::{NodeOptionsBuilder, RocksdbOptionsBuilder, WorkerOptionsBuilder};
 use restate_server::config::ConfigurationBuilder;
 use restate_server::Configuration;
 use restate_types::retries::RetryPolicy;
@@ -90,11 +88,6 @@ pub fn flamegraph_options<'a>() -> Options<'a> {
 }
 
 pub fn restate_configuration() -> Configuration {
-    let meta_options = MetaOptionsBuilder::default()
-        .storage_path(tempfile::tempdir().expect("tempdir failed").into_path())
-        .build()
-        .expect("building meta options should work");
-
     let rocksdb_options = RocksdbOptionsBuilder::default()
         .path(tempfile::tempdir().expect("tempdir failed").into_path())
         .build()
@@ -108,7 +101,6 @@ pub fn restate_configuration() -> Configuration {
 
     let node_options = NodeOptionsBuilder::default()
         .worker(worker_options)
-        .meta(meta_options)
         .build()
         .expect("building the configuration should work");
 
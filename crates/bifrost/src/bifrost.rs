@@ -11,22 +11,30 @@
 // TODO: Remove after fleshing the code out.
 #![allow(dead_code)]
 
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use enum_map::EnumMap;
 use once_cell::sync::OnceCell;
 
+use restate_core::metadata_store::Operation;
+use restate_core::MetadataWriter;
+use restate_types::logs::metadata::{LogletConfig, LogletParams, Logs, ProviderKind, Segment};
 use restate_types::logs::{LogId, Lsn, Payload, SequenceNumber};
-use restate_types::Version;
-use tracing::{error, instrument};
+use restate_types::metadata_store::keys::BIFROST_CONFIG_KEY;
+use restate_types::{Version, Versioned};
+use tokio::sync::watch;
+use tracing::{debug, error, info, instrument};
 
-use crate::loglet::{LogletBase, LogletProvider, LogletWrapper, ProviderKind};
-use crate::metadata::Logs;
+use crate::loglet::{LogletBase, LogletProvider, LogletWrapper};
 use crate::options::Options;
 use crate::watchdog::{WatchdogCommand, WatchdogSender};
-use crate::{create_static_metadata, Error, FindTailAttributes, LogReadStream, LogRecord};
+use crate::{
+    create_static_metadata, default_chain, Error, FindTailAttributes, LogReadStream, LogRecord,
+    Record, SealReason,
+};
 
 /// Bifrost is Restate's durable interconnect system
 ///
@@ -44,8 +52,8 @@ impl Bifrost {
     }
 
     #[cfg(any(test, feature = "memory_loglet"))]
-    pub async fn new_in_memory(num_logs: u64) -> Self {
-        let bifrost_svc = Options::memory().build(num_logs);
+    pub async fn new_in_memory(metadata_writer: MetadataWriter, num_logs: u64) -> Self {
+        let bifrost_svc = Options::memory().build(num_logs, metadata_writer);
         let bifrost = bifrost_svc.handle();
 
         // start bifrost service in the background
@@ -63,6 +71,20 @@ impl Bifrost {
         self.inner.append(log_id, payload).await
     }
 
+    /// Appends a batch of records to a log. The records are assigned consecutive LSNs in the
+    /// order of the batch and are committed atomically. The log id must exist, otherwise the
+    /// operation fails with [`Error::UnknownLogId`]
+    ///
+    /// Returns the range of LSNs of the appended records.
+    #[instrument(level = "debug", skip(self, payloads), fields(num_records = payloads.len()), err)]
+    pub async fn append_batch(
+        &mut self,
+        log_id: LogId,
+        payloads: Vec<Payload>,
+    ) -> Result<Range<Lsn>, Error> {
+        self.inner.append_batch(log_id, &payloads).await
+    }
+
     /// Read the next record after the LSN provided. The `start` indicates the LSN where we will
     /// read after. This means that the record returned will have a LSN strictly greater than
     /// `after`. If no records are committed yet after this LSN, this read operation will "wait"
@@ -96,9 +118,55 @@ impl Bifrost {
         self.inner.find_tail(log_id, attributes).await
     }
 
+    /// Trims the log prefix up to and including the `trim_point`. Trimmed records cannot be read
+    /// anymore, readers will observe a trim gap instead. The trim point is clamped to the tail of
+    /// the log, and trimming to an LSN that is at or before the current trim point is a no-op.
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn trim(&mut self, log_id: LogId, trim_point: Lsn) -> Result<(), Error> {
+        self.inner.trim(log_id, trim_point).await
+    }
+
+    /// Returns the LSN of the last trimmed record of the log, or [`Lsn::INVALID`] if the log
+    /// has not been trimmed yet.
+    pub async fn get_trim_point(&self, log_id: LogId) -> Result<Lsn, Error> {
+        self.inner.get_trim_point(log_id).await
+    }
+
+    /// Seals the log. Appends to a sealed log fail with [`Error::LogSealed`] and readers observe
+    /// a [`crate::Record::Seal`] record after the last record of the log.
+    ///
+    /// Returns the LSN of the last record in the sealed log.
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn seal(&mut self, log_id: LogId, reason: SealReason) -> Result<Lsn, Error> {
+        self.inner.seal(log_id, reason).await
+    }
+
+    /// Seals the tail segment of the log and continues the log in a new segment backed by a
+    /// loglet of the given `kind` and `params`. Readers and writers transparently move over to the
+    /// new segment, which allows moving a log to another loglet provider or storage location
+    /// without downtime.
+    ///
+    /// Returns the base LSN of the new segment.
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn seal_and_extend(
+        &mut self,
+        log_id: LogId,
+        kind: ProviderKind,
+        params: LogletParams,
+    ) -> Result<Lsn, Error> {
+        self.inner.seal_and_extend(log_id, kind, params).await
+    }
+
+    /// Creates a new log with a single segment of the default loglet provider kind. Creating a log
+    /// which exists already is a no-op.
+    #[instrument(level = "debug", skip(self), err)]
+    pub async fn create_log(&mut self, log_id: LogId) -> Result<(), Error> {
+        self.inner.create_log(log_id).await
+    }
+
     /// The version of the currently loaded logs metadata
     pub fn version(&self) -> Version {
-        self.inner.log_metadata.lock().unwrap().version
+        self.inner.log_metadata.lock().unwrap().version()
     }
 
     #[cfg(test)]
@@ -110,25 +178,52 @@ impl Bifrost {
 // compile-time check
 static_assertions::assert_impl_all!(Bifrost: Send, Sync, Clone);
 
+/// How long to wait for the next segment of a log that is sealed for a reconfiguration before
+/// syncing the logs metadata again.
+const RECONFIGURATION_SYNC_INTERVAL: Duration = Duration::from_millis(500);
+
+enum SealedLoglet {
+    /// Retry the operation against the current logs metadata
+    Retry,
+    /// The log is sealed
+    Sealed,
+}
+
 // Locks in this data-structure are held for very short time and should never be
 // held across an async boundary.
 pub struct BifrostInner {
-    opts: Options,
+    pub(crate) opts: Options,
     num_partitions: u64,
     watchdog: WatchdogSender,
+    metadata_writer: MetadataWriter,
     log_metadata: Mutex<Logs>,
+    // Notified with the new version whenever the logs metadata changes.
+    metadata_watch: watch::Sender<Version>,
+    // Serializes reconfigurations of log chains.
+    reconfiguration_lock: tokio::sync::Mutex<()>,
     providers: EnumMap<ProviderKind, OnceCell<Arc<dyn LogletProvider>>>,
+    // Providers that cannot be created from the options alone, they are started on first use.
+    registered_providers: Mutex<EnumMap<ProviderKind, Option<Arc<dyn LogletProvider>>>>,
     shutting_down: AtomicBool,
 }
 
 impl BifrostInner {
-    pub fn new(opts: Options, watchdog: WatchdogSender, num_partitions: u64) -> Self {
+    pub fn new(
+        opts: Options,
+        watchdog: WatchdogSender,
+        metadata_writer: MetadataWriter,
+        num_partitions: u64,
+    ) -> Self {
         Self {
             opts,
             num_partitions,
             watchdog,
+            metadata_writer,
             log_metadata: Mutex::new(Logs::empty()),
+            metadata_watch: watch::channel(Version::INVALID).0,
+            reconfiguration_lock: tokio::sync::Mutex::new(()),
             providers: Default::default(),
+            registered_providers: Default::default(),
             shutting_down: AtomicBool::new(false),
         }
     }
@@ -144,15 +239,57 @@ impl BifrostInner {
     /// operation fails with [`Error::UnknownLogId`]
     pub async fn append(&self, log_id: LogId, payload: Payload) -> Result<Lsn, Error> {
         self.fail_if_shutting_down()?;
-        let loglet = self.writeable_loglet(log_id).await?;
-        loglet.append(payload).await
+        loop {
+            let mut metadata_watch = self.metadata_watch.subscribe();
+            let loglet = self.writeable_loglet(log_id).await?;
+            match loglet.append(payload.clone()).await {
+                Err(Error::LogletSealed(reason)) => {
+                    match self.on_sealed_loglet(&mut metadata_watch, &reason).await? {
+                        SealedLoglet::Retry => continue,
+                        SealedLoglet::Sealed => return Err(Error::LogSealed(log_id, reason)),
+                    }
+                }
+                result => return result,
+            }
+        }
     }
 
-    pub async fn read_next_single(&self, log_id: LogId, after: Lsn) -> Result<LogRecord, Error> {
+    pub async fn append_batch(
+        &self,
+        log_id: LogId,
+        payloads: &[Payload],
+    ) -> Result<Range<Lsn>, Error> {
         self.fail_if_shutting_down()?;
+        loop {
+            let mut metadata_watch = self.metadata_watch.subscribe();
+            let loglet = self.writeable_loglet(log_id).await?;
+            match loglet.append_batch(payloads).await {
+                Err(Error::LogletSealed(reason)) => {
+                    match self.on_sealed_loglet(&mut metadata_watch, &reason).await? {
+                        SealedLoglet::Retry => continue,
+                        SealedLoglet::Sealed => return Err(Error::LogSealed(log_id, reason)),
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
 
-        let loglet = self.find_loglet_for_lsn(log_id, after.next()).await?;
-        loglet.read_next_single(after).await
+    pub async fn read_next_single(&self, log_id: LogId, after: Lsn) -> Result<LogRecord, Error> {
+        self.fail_if_shutting_down()?;
+        loop {
+            let mut metadata_watch = self.metadata_watch.subscribe();
+            let loglet = self.find_loglet_for_lsn(log_id, after.next()).await?;
+            let record = loglet.read_next_single(after).await?;
+            if let Record::Seal(reason) = &record.record {
+                if let SealedLoglet::Retry =
+                    self.on_sealed_loglet(&mut metadata_watch, reason).await?
+                {
+                    continue;
+                }
+            }
+            return Ok(record);
+        }
     }
 
     pub async fn read_next_single_opt(
@@ -161,9 +298,162 @@ impl BifrostInner {
         after: Lsn,
     ) -> Result<Option<LogRecord>, Error> {
         self.fail_if_shutting_down()?;
+        loop {
+            let metadata_watch = self.metadata_watch.subscribe();
+            let loglet = self.find_loglet_for_lsn(log_id, after.next()).await?;
+            let record = loglet.read_next_single_opt(after).await?;
+            if let Some(LogRecord {
+                record: Record::Seal(reason),
+                ..
+            }) = &record
+            {
+                if metadata_watch.has_changed().unwrap_or_default() {
+                    // the log might continue in a new segment
+                    continue;
+                }
+                if *reason == SealReason::Reconfiguration {
+                    // the next segment is not known yet, there is nothing to read
+                    return Ok(None);
+                }
+            }
+            return Ok(record);
+        }
+    }
 
-        let loglet = self.find_loglet_for_lsn(log_id, after.next()).await?;
-        loglet.read_next_single_opt(after).await
+    /// Decides how to proceed after hitting a sealed loglet. If the logs metadata has changed since
+    /// the loglet was resolved, or once it changes in case the loglet has been sealed for a
+    /// reconfiguration, the operation should be retried against the new metadata.
+    ///
+    /// The reconfiguration might have been started by another node, hence the logs metadata is
+    /// synced periodically until the new segment shows up.
+    async fn on_sealed_loglet(
+        &self,
+        metadata_watch: &mut watch::Receiver<Version>,
+        reason: &SealReason,
+    ) -> Result<SealedLoglet, Error> {
+        if metadata_watch.has_changed().unwrap_or_default() {
+            return Ok(SealedLoglet::Retry);
+        }
+        if *reason != SealReason::Reconfiguration {
+            return Ok(SealedLoglet::Sealed);
+        }
+        // The log continues in a new segment which is about to be added to the chain.
+        self.wait_for_metadata_change(metadata_watch).await?;
+        Ok(SealedLoglet::Retry)
+    }
+
+    /// Watches the version of the logs metadata.
+    pub(crate) fn watch_metadata(&self) -> watch::Receiver<Version> {
+        self.metadata_watch.subscribe()
+    }
+
+    /// Waits until the logs metadata changes, e.g. because a sealed log has been extended with a
+    /// new segment. The chain might be extended by another node, hence the logs metadata is
+    /// synced periodically while waiting.
+    pub(crate) async fn wait_for_metadata_change(
+        &self,
+        metadata_watch: &mut watch::Receiver<Version>,
+    ) -> Result<(), Error> {
+        loop {
+            let _ = self.watchdog.send(WatchdogCommand::ScheduleMetadataSync);
+            match tokio::time::timeout(RECONFIGURATION_SYNC_INTERVAL, metadata_watch.changed())
+                .await
+            {
+                Ok(changed) => {
+                    return changed.map_err(|_| Error::Shutdown(restate_core::ShutdownError));
+                }
+                Err(_) => continue,
+            }
+        }
+    }
+
+    pub async fn seal(&self, log_id: LogId, reason: SealReason) -> Result<Lsn, Error> {
+        self.fail_if_shutting_down()?;
+        let _guard = self.reconfiguration_lock.lock().await;
+
+        let loglet = self.writeable_loglet(log_id).await?;
+        loglet.seal(reason).await
+    }
+
+    pub async fn seal_and_extend(
+        &self,
+        log_id: LogId,
+        kind: ProviderKind,
+        params: LogletParams,
+    ) -> Result<Lsn, Error> {
+        self.fail_if_shutting_down()?;
+        let _guard = self.reconfiguration_lock.lock().await;
+
+        // Create the new loglet before sealing the current one, once the tail is sealed, all
+        // readers and writers wait for the new segment.
+        self.provider_for(kind).get_loglet(&params).await?;
+
+        let tail_segment = self.tail_segment(log_id).await?;
+        let tail_loglet = self.loglet_for_segment(tail_segment.clone()).await?;
+        let sealed_tail = tail_loglet.seal(SealReason::Reconfiguration).await?;
+        let base_lsn = sealed_tail.next();
+
+        let config = LogletConfig::new(kind, params);
+        let logs = self
+            .metadata_writer
+            .metadata_store_client()
+            .read_modify_write(BIFROST_CONFIG_KEY.clone(), |logs: Option<Logs>| {
+                let Some(mut logs) = logs else {
+                    return Operation::Fail("logs metadata has not been initialized".to_owned());
+                };
+                // Another node might have extended the chain in the meantime, only the segment
+                // which has been sealed here may be continued.
+                match logs.tail_segment(log_id) {
+                    Some(segment)
+                        if segment.base_lsn == tail_segment.base_lsn
+                            && segment.config == tail_segment.config => {}
+                    Some(_) => {
+                        return Operation::Fail(format!(
+                            "log '{}' has been extended concurrently",
+                            log_id
+                        ));
+                    }
+                    None => return Operation::Fail(format!("unknown log '{}'", log_id)),
+                }
+                if let Err(err) = logs.append_segment(log_id, base_lsn, config.clone()) {
+                    return Operation::Fail(err.to_string());
+                }
+                Operation::Upsert(logs)
+            })
+            .await
+            .map_err(|e| Error::MetadataSync(Arc::new(e)))?;
+        self.apply_metadata(&logs);
+        self.metadata_writer.submit(logs);
+        info!(%log_id, %base_lsn, %kind, "Log chain has been extended with a new segment");
+
+        Ok(base_lsn)
+    }
+
+    pub async fn create_log(&self, log_id: LogId) -> Result<(), Error> {
+        self.fail_if_shutting_down()?;
+        let _guard = self.reconfiguration_lock.lock().await;
+
+        let chain = default_chain(&self.opts, log_id.into());
+        let logs = self
+            .metadata_writer
+            .metadata_store_client()
+            .read_modify_write(BIFROST_CONFIG_KEY.clone(), |logs: Option<Logs>| {
+                let Some(mut logs) = logs else {
+                    return Operation::Fail("logs metadata has not been initialized".to_owned());
+                };
+                if logs.add_log(log_id, chain.clone()) {
+                    Operation::Upsert(logs)
+                } else {
+                    Operation::Return(logs)
+                }
+            })
+            .await
+            .map_err(|e| Error::MetadataSync(Arc::new(e)))?;
+        self.apply_metadata(&logs);
+        self.metadata_writer.submit(logs);
+        info!(%log_id, "Log has been created");
+
+        Ok(())
     }
 
     pub async fn find_tail(
@@ -173,7 +463,42 @@ impl BifrostInner {
     ) -> Result<Option<Lsn>, Error> {
         self.fail_if_shutting_down()?;
         let loglet = self.writeable_loglet(log_id).await?;
-        loglet.find_tail().await
+        let tail = loglet.find_tail().await?;
+        // A tail segment without records continues right after the tail of the previous segment
+        Ok(tail.or_else(|| (loglet.base_lsn > Lsn::OLDEST).then(|| loglet.base_lsn.prev())))
+    }
+
+    pub async fn trim(&self, log_id: LogId, trim_point: Lsn) -> Result<(), Error> {
+        self.fail_if_shutting_down()?;
+
+        // All segments that start at or before the trim point are (partially) trimmed. Loglets
+        // clamp the trim point to their tail.
+        for segment in self.segments(log_id)? {
+            if segment.base_lsn > trim_point {
+                break;
+            }
+            let loglet = self.loglet_for_segment(segment).await?;
+            loglet.trim(trim_point).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get_trim_point(&self, log_id: LogId) -> Result<Lsn, Error> {
+        self.fail_if_shutting_down()?;
+
+        // Trimming happens in segment order, the trim point of the log is the trim point of the
+        // last segment that has been trimmed.
+        let mut trim_point = Lsn::INVALID;
+        for segment in self.segments(log_id)? {
+            let base_lsn = segment.base_lsn;
+            let loglet = self.loglet_for_segment(segment).await?;
+            let segment_trim_point = loglet.get_trim_point().await?;
+            if segment_trim_point < base_lsn {
+                break;
+            }
+            trim_point = segment_trim_point;
+        }
+        Ok(trim_point)
     }
 
     #[inline]
@@ -185,20 +510,45 @@ impl BifrostInner {
         }
     }
 
-    /// Immediately fetch new metadata from metadata store and update the local copy
-    ///
-    /// NOTE: The current version assumes static metadata map!
+    /// Immediately fetch new metadata from metadata store and update the local copy. If the
+    /// metadata store doesn't hold any logs metadata yet, it's initialized with a log per
+    /// partition.
     pub async fn sync_metadata(&self) -> Result<(), Error> {
         self.fail_if_shutting_down()?;
 
-        let logs = create_static_metadata(&self.opts, self.num_partitions);
-        let mut guard = self.log_metadata.lock().unwrap();
-        if logs.version > guard.version {
-            *guard = logs;
-        }
+        let logs = self
+            .metadata_writer
+            .metadata_store_client()
+            .read_modify_write(
+                BIFROST_CONFIG_KEY.clone(),
+                |logs: Option<Logs>| match logs {
+                    Some(logs) => Operation::Return(logs),
+                    None => {
+                        Operation::Upsert(create_static_metadata(&self.opts, self.num_partitions))
+                    }
+                },
+            )
+            .await
+            .map_err(|e| Error::MetadataSync(Arc::new(e)))?;
+        self.apply_metadata(&logs);
+        // Let the metadata manager share the metadata with the rest of the node
+        self.metadata_writer.submit(logs);
         Ok(())
     }
 
+    /// Replaces the local copy of the logs metadata if `logs` is newer.
+    pub(crate) fn apply_metadata(&self, logs: &Logs) {
+        let version = {
+            let mut guard = self.log_metadata.lock().unwrap();
+            if logs.version() <= guard.version() {
+                return;
+            }
+            *guard = logs.clone();
+            guard.version()
+        };
+        self.metadata_watch.send_replace(version);
+    }
+
     // --- Helper functions --- //
 
     /// Get the provider for a given kind. If the provider is not yet initialized, it will be
@@ -206,7 +556,10 @@ impl BifrostInner {
     fn provider_for(&self, kind: ProviderKind) -> &dyn LogletProvider {
         self.providers[kind]
             .get_or_init(|| {
-                let provider = crate::loglet::create_provider(kind, &self.opts)
+                let registered = self.registered_providers.lock().unwrap()[kind].take();
+                let provider = registered
+                    .map(Ok)
+                    .unwrap_or_else(|| crate::loglet::create_provider(kind, &self.opts))
                     .expect("provider is able to get created");
                 if let Err(e) = provider.start() {
                     error!("Failed to start loglet provider {}: {}", kind, e);
@@ -223,6 +576,13 @@ impl BifrostInner {
             .deref()
     }
 
+    /// Registers a provider that is used instead of creating one from the options. The provider
+    /// is started and monitored by the watchdog on first use.
+    /// This will only work if the provider was never accessed by bifrost before this call.
+    pub fn register_provider(&self, kind: ProviderKind, provider: Arc<dyn LogletProvider>) {
+        self.registered_providers.lock().unwrap()[kind] = Some(provider);
+    }
+
     /// Injects a provider for testing purposes. The call is responsible for starting the provider
     /// and that it's monitored by watchdog if necessary.
     /// This will only work if the provider was never accessed by bifrost before this call.
@@ -233,18 +593,25 @@ impl BifrostInner {
     }
 
     async fn writeable_loglet(&self, log_id: LogId) -> Result<LogletWrapper, Error> {
+        let tail_segment = self.tail_segment(log_id).await?;
+        self.loglet_for_segment(tail_segment).await
+    }
+
+    async fn tail_segment(&self, log_id: LogId) -> Result<Segment, Error> {
         // Locks the logs mutex.
-        let tail_segment = self
-            .log_metadata
-            .lock()
-            .unwrap()
-            .tail_segment(log_id)
-            .ok_or(Error::UnknownLogId(log_id))?;
+        let tail_segment = self.log_metadata.lock().unwrap().tail_segment(log_id);
         // Logs lock released here.
-        let provider = self.provider_for(tail_segment.config.kind);
-        let loglet = provider.get_loglet(&tail_segment.config.params).await?;
-
-        Ok(LogletWrapper::new(tail_segment.base_lsn, loglet))
+        match tail_segment {
+            Some(tail_segment) => Ok(tail_segment),
+            None => {
+                self.sync_unknown_log(log_id).await?;
+                self.log_metadata
+                    .lock()
+                    .unwrap()
+                    .tail_segment(log_id)
+                    .ok_or(Error::UnknownLogId(log_id))
+            }
+        }
     }
 
     async fn find_loglet_for_lsn(&self, log_id: LogId, lsn: Lsn) -> Result<LogletWrapper, Error> {
@@ -253,10 +620,42 @@ impl BifrostInner {
             .log_metadata
             .lock()
             .unwrap()
-            .find_segment_for_lsn(log_id, lsn)
-            .ok_or(Error::UnknownLogId(log_id))?;
-
+            .find_segment_for_lsn(log_id, lsn);
         // Logs lock released here.
+        let segment = match segment {
+            Some(segment) => segment,
+            None => {
+                self.sync_unknown_log(log_id).await?;
+                self.log_metadata
+                    .lock()
+                    .unwrap()
+                    .find_segment_for_lsn(log_id, lsn)
+                    .ok_or(Error::UnknownLogId(log_id))?
+            }
+        };
+        self.loglet_for_segment(segment).await
+    }
+
+    /// Logs can be created by other nodes, e.g. when partitions are split. The local logs
+    /// metadata is synced once before an unknown log id is reported.
+    async fn sync_unknown_log(&self, log_id: LogId) -> Result<(), Error> {
+        let known = self.log_metadata.lock().unwrap().segments(log_id).is_some();
+        if !known {
+            debug!(%log_id, "Syncing logs metadata to look up unknown log");
+            self.sync_metadata().await?;
+        }
+        Ok(())
+    }
+
+    fn segments(&self, log_id: LogId) -> Result<Vec<Segment>, Error> {
+        self.log_metadata
+            .lock()
+            .unwrap()
+            .segments(log_id)
+            .ok_or(Error::UnknownLogId(log_id))
+    }
+
+    async fn loglet_for_segment(&self, segment: Segment) -> Result<LogletWrapper, Error> {
         let provider = self.provider_for(segment.config.kind);
         let loglet = provider.get_loglet(&segment.config.params).await?;
 
@@ -270,13 +669,15 @@ mod tests {
 
     use super::*;
 
-    use crate::loglet::ProviderKind;
     use crate::loglets::memory_loglet::MemoryLogletProvider;
+    use crate::ProviderKind;
     use googletest::prelude::*;
 
     use restate_core::task_center;
     use restate_core::TestCoreEnv;
+    use restate_test_util::let_assert;
     use restate_types::logs::SequenceNumber;
+    use tokio::task::JoinHandle;
     use tracing::info;
     use tracing_test::traced_test;
 
@@ -288,7 +689,8 @@ mod tests {
         tc.run_in_scope("test", None, async {
             // start a simple bifrost service with 5 logs.
             let num_partitions = 5;
-            let mut bifrost = Bifrost::new_in_memory(num_partitions).await;
+            let mut bifrost =
+                Bifrost::new_in_memory(node_env.metadata_writer.clone(), num_partitions).await;
 
             let mut clean_bifrost_clone = bifrost.clone();
 
@@ -353,6 +755,338 @@ mod tests {
         .await
     }
 
+    #[tokio::test]
+    async fn test_trim() -> Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let log_id = LogId::from(0);
+            let mut bifrost = Bifrost::new_in_memory(node_env.metadata_writer.clone(), 1).await;
+
+            for _ in 1..=10 {
+                bifrost.append(log_id, Payload::default()).await?;
+            }
+            assert_eq!(Lsn::INVALID, bifrost.get_trim_point(log_id).await?);
+
+            bifrost.trim(log_id, Lsn::from(5)).await?;
+            assert_eq!(Lsn::from(5), bifrost.get_trim_point(log_id).await?);
+
+            // Reading from the start of the log yields a trim gap followed by the untrimmed records.
+            let mut reader = bifrost.create_reader(log_id, Lsn::INVALID);
+            let record = reader.read_next().await?;
+            assert_eq!(Lsn::OLDEST, record.offset);
+            let_assert!(Record::TrimGap(trim_gap) = record.record);
+            assert_eq!(Lsn::from(5), trim_gap.until);
+            let record = reader.read_next().await?;
+            assert_eq!(Lsn::from(6), record.offset);
+            assert!(record.record.is_data());
+
+            // Trimming backwards is a no-op
+            bifrost.trim(log_id, Lsn::from(3)).await?;
+            assert_eq!(Lsn::from(5), bifrost.get_trim_point(log_id).await?);
+
+            // Trimming beyond the tail is clamped to the tail
+            bifrost.trim(log_id, Lsn::from(100)).await?;
+            assert_eq!(Lsn::from(10), bifrost.get_trim_point(log_id).await?);
+
+            // New records are appended after the trimmed prefix
+            let lsn = bifrost.append(log_id, Payload::default()).await?;
+            assert_eq!(Lsn::from(11), lsn);
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_append_batch() -> Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let log_id = LogId::from(0);
+            let mut bifrost = Bifrost::new_in_memory(node_env.metadata_writer.clone(), 1).await;
+
+            let lsn = bifrost.append(log_id, Payload::from("record1")).await?;
+            assert_eq!(Lsn::from(1), lsn);
+
+            let payloads = (2..=5)
+                .map(|i| Payload::from(format!("record{}", i)))
+                .collect();
+            let lsns = bifrost.append_batch(log_id, payloads).await?;
+            assert_eq!(Lsn::from(2)..Lsn::from(6), lsns);
+
+            // An empty batch doesn't move the tail
+            let lsns = bifrost.append_batch(log_id, Vec::new()).await?;
+            assert_eq!(Lsn::from(6)..Lsn::from(6), lsns);
+
+            let lsn = bifrost.append(log_id, Payload::from("record6")).await?;
+            assert_eq!(Lsn::from(6), lsn);
+
+            let mut reader = bifrost.create_reader(log_id, Lsn::INVALID);
+            for i in 1..=6 {
+                let record = reader.read_next().await?;
+                assert_eq!(Lsn::from(i), record.offset);
+                assert_eq!(
+                    Payload::from(format!("record{}", i)),
+                    record.record.into_payload_unchecked()
+                );
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_seal_and_extend() -> Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let log_id = LogId::from(0);
+            let mut bifrost = Bifrost::new_in_memory(node_env.metadata_writer.clone(), 1).await;
+
+            for i in 1..=5 {
+                bifrost
+                    .append(log_id, Payload::from(format!("record{}", i)))
+                    .await?;
+            }
+
+            // A reader crosses the segment boundary transparently
+            let mut reader = bifrost.create_reader(log_id, Lsn::INVALID);
+            let reader_handle: JoinHandle<Result<()>> = tokio::spawn(async move {
+                for i in 1..=10 {
+                    let record = reader.read_next().await?;
+                    assert_eq!(Lsn::from(i), record.offset);
+                    assert_eq!(
+                        Payload::from(format!("record{}", i)),
+                        record.record.into_payload_unchecked()
+                    );
+                }
+                Ok(())
+            });
+
+            let base_lsn = bifrost
+                .seal_and_extend(
+                    log_id,
+                    ProviderKind::InMemory,
+                    LogletParams::from("segment-2".to_string()),
+                )
+                .await?;
+            assert_eq!(Lsn::from(6), base_lsn);
+            assert_eq!(
+                Some(Lsn::from(5)),
+                bifrost
+                    .find_tail(log_id, FindTailAttributes::default())
+                    .await?
+            );
+
+            for i in 6..=10 {
+                let lsn = bifrost
+                    .append(log_id, Payload::from(format!("record{}", i)))
+                    .await?;
+                assert_eq!(Lsn::from(i), lsn);
+            }
+            reader_handle.await.unwrap()?;
+            assert_eq!(
+                Some(Lsn::from(10)),
+                bifrost
+                    .find_tail(log_id, FindTailAttributes::default())
+                    .await?
+            );
+
+            // Trimming spans over segments
+            bifrost.trim(log_id, Lsn::from(7)).await?;
+            assert_eq!(Lsn::from(7), bifrost.get_trim_point(log_id).await?);
+            let mut reader = bifrost.create_reader(log_id, Lsn::INVALID);
+            let record = reader.read_next().await?;
+            let_assert!(Record::TrimGap(trim_gap) = record.record);
+            assert_eq!(Lsn::from(5), trim_gap.until);
+            let record = reader.read_next().await?;
+            assert_eq!(Lsn::from(6), record.offset);
+            let_assert!(Record::TrimGap(trim_gap) = record.record);
+            assert_eq!(Lsn::from(7), trim_gap.until);
+            let record = reader.read_next().await?;
+            assert_eq!(Lsn::from(8), record.offset);
+            assert!(record.record.is_data());
+
+            // Sealing the log rejects appends and readers observe the seal
+            let tail = bifrost.seal(log_id, SealReason::Resharding).await?;
+            assert_eq!(Lsn::from(10), tail);
+            let res = bifrost.append(log_id, Payload::default()).await;
+            assert!(matches!(
+                res,
+                Err(Error::LogSealed(id, SealReason::Resharding)) if id == log_id
+            ));
+            let_assert!(Some(record) = bifrost.read_next_single_opt(log_id, tail).await?);
+            assert_eq!(Lsn::from(11), record.offset);
+            let_assert!(Record::Seal(SealReason::Resharding) = record.record);
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_seal_and_extend_concurrently_extended_log() -> Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let log_id = LogId::from(0);
+            let mut bifrost = Bifrost::new_in_memory(node_env.metadata_writer.clone(), 1).await;
+            bifrost.append(log_id, Payload::default()).await?;
+
+            // Another node extends the log, this node doesn't know the new segment yet
+            let other_segment = LogletConfig::new(
+                ProviderKind::InMemory,
+                LogletParams::from("other-node".to_string()),
+            );
+            node_env
+                .metadata_writer
+                .metadata_store_client()
+                .read_modify_write(BIFROST_CONFIG_KEY.clone(), |logs: Option<Logs>| {
+                    let mut logs = logs.expect("logs metadata has been initialized");
+                    logs.append_segment(log_id, Lsn::from(2), other_segment.clone())
+                        .expect("log exists");
+                    Operation::Upsert(logs)
+                })
+                .await?;
+
+            let res = bifrost
+                .seal_and_extend(
+                    log_id,
+                    ProviderKind::InMemory,
+                    LogletParams::from("this-node".to_string()),
+                )
+                .await;
+            assert!(matches!(res, Err(Error::MetadataSync(_))));
+
+            // The segment of the other node is kept
+            let logs: Logs = node_env
+                .metadata_writer
+                .metadata_store_client()
+                .get(BIFROST_CONFIG_KEY.clone())
+                .await?
+                .expect("logs metadata has been initialized");
+            let_assert!(Some(tail_segment) = logs.tail_segment(log_id));
+            assert_eq!(Lsn::from(2), tail_segment.base_lsn);
+            assert_eq!(other_segment, *tail_segment.config);
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_extend_sealed_local_loglet() -> Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let base_dir = tempfile::tempdir()?;
+            let log_id = LogId::from(0);
+            let bifrost_opts = Options {
+                default_provider: ProviderKind::Local,
+                local: crate::loglets::local_loglet::Options {
+                    path: base_dir.path().join("local_loglet"),
+                    ..Default::default()
+                },
+                ..Options::default()
+            };
+            let bifrost_svc = bifrost_opts.build(1, node_env.metadata_writer.clone());
+            let mut bifrost = bifrost_svc.handle();
+            bifrost_svc.start().await.unwrap();
+
+            for i in 1..=3 {
+                bifrost
+                    .append(log_id, Payload::from(format!("record{}", i)))
+                    .await?;
+            }
+            let tail = bifrost.seal(log_id, SealReason::Resharding).await?;
+            assert_eq!(Lsn::from(3), tail);
+
+            let mut reader = bifrost.create_reader(log_id, Lsn::INVALID);
+            for i in 1..=3 {
+                let record = reader.read_next().await?;
+                assert_eq!(Lsn::from(i), record.offset);
+                assert!(record.record.is_data());
+            }
+            // the seal is delivered once, it doesn't occupy an lsn
+            let record = reader.read_next().await?;
+            assert_eq!(Lsn::from(4), record.offset);
+            let_assert!(Record::Seal(SealReason::Resharding) = record.record);
+            assert_eq!(Lsn::from(3), reader.current_read_pointer());
+            assert!(reader.read_next_opt().await?.is_none());
+
+            // afterwards, the reader waits until the log continues
+            let reader_handle: JoinHandle<Result<()>> = tokio::spawn(async move {
+                for i in 4..=5 {
+                    let record = reader.read_next().await?;
+                    assert_eq!(Lsn::from(i), record.offset);
+                    assert_eq!(
+                        Payload::from(format!("record{}", i)),
+                        record.record.into_payload_unchecked()
+                    );
+                }
+                Ok(())
+            });
+            tokio::task::yield_now().await;
+            assert!(!reader_handle.is_finished());
+
+            let base_lsn = bifrost
+                .seal_and_extend(
+                    log_id,
+                    ProviderKind::Local,
+                    LogletParams::from("100".to_string()),
+                )
+                .await?;
+            assert_eq!(Lsn::from(4), base_lsn);
+
+            for i in 4..=5 {
+                let lsn = bifrost
+                    .append(log_id, Payload::from(format!("record{}", i)))
+                    .await?;
+                assert_eq!(Lsn::from(i), lsn);
+            }
+            reader_handle.await.unwrap()?;
+            assert_eq!(
+                Some(Lsn::from(5)),
+                bifrost
+                    .find_tail(log_id, FindTailAttributes::default())
+                    .await?
+            );
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_create_log() -> Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let mut bifrost = Bifrost::new_in_memory(node_env.metadata_writer.clone(), 2).await;
+            let new_log = LogId::from(2);
+
+            let res = bifrost.append(new_log, Payload::default()).await;
+            assert!(matches!(res, Err(Error::UnknownLogId(id)) if id == new_log));
+
+            let version = bifrost.version();
+            bifrost.create_log(new_log).await?;
+            assert_eq!(version.next(), bifrost.version());
+            assert_eq!(
+                Lsn::from(1),
+                bifrost.append(new_log, Payload::default()).await?
+            );
+
+            // creating an existing log keeps its records
+            bifrost.create_log(new_log).await?;
+            assert_eq!(version.next(), bifrost.version());
+            assert_eq!(
+                Some(Lsn::from(1)),
+                bifrost
+                    .find_tail(new_log, FindTailAttributes::default())
+                    .await?
+            );
+            Ok(())
+        })
+        .await
+    }
+
     #[tokio::test(start_paused = true)]
     async fn test_lazy_initialization() -> Result<()> {
         let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
@@ -368,7 +1102,7 @@ mod tests {
                 default_provider: ProviderKind::InMemory,
                 ..Options::default()
             };
-            let bifrost_svc = bifrost_opts.build(num_partitions);
+            let bifrost_svc = bifrost_opts.build(num_partitions, node_env.metadata_writer.clone());
             let mut bifrost = bifrost_svc.handle();
 
             // Inject out preconfigured memory provider
@@ -8,6 +8,9 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::sync::Arc;
+
+use restate_core::metadata_store::ReadModifyWriteError;
 use restate_core::ShutdownError;
 use thiserror::Error;
 
@@ -15,23 +18,32 @@ use restate_types::logs::{LogId, Lsn};
 
 #[cfg(any(test, feature = "local_loglet"))]
 use crate::loglets::local_loglet::LogStoreError;
+#[cfg(any(test, feature = "replicated_loglet"))]
+use crate::loglets::replicated_loglet::ReplicatedLogletError;
 use crate::types::SealReason;
 
 #[derive(Error, Debug, Clone)]
 pub enum Error {
     #[error("log '{0}' is sealed")]
     LogSealed(LogId, SealReason),
+    /// Returned by loglets which are not aware of the log they belong to. Bifrost translates it
+    /// into [`Error::LogSealed`].
+    #[error("loglet is sealed")]
+    LogletSealed(SealReason),
     #[error("unknown log '{0}")]
     UnknownLogId(LogId),
     #[error("invalid log sequence number '{0}")]
     InvalidLsn(Lsn),
-    #[error("cannot fetch log metadata")]
-    MetadataSync,
+    #[error("cannot sync log metadata: {0}")]
+    MetadataSync(Arc<ReadModifyWriteError>),
     #[error("operation failed due to an ongoing shutdown")]
     Shutdown(#[from] ShutdownError),
     #[cfg(any(test, feature = "local_loglet"))]
     #[error(transparent)]
     LogStoreError(#[from] LogStoreError),
+    #[cfg(any(test, feature = "replicated_loglet"))]
+    #[error(transparent)]
+    ReplicatedLoglet(#[from] ReplicatedLogletError),
 }
 
 #[derive(Debug, thiserror::Error)]
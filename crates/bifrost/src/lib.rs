@@ -12,7 +12,6 @@ mod bifrost;
 mod error;
 mod loglet;
 mod loglets;
-mod metadata;
 mod options;
 mod read_stream;
 mod service;
@@ -23,15 +22,23 @@ use std::collections::HashMap;
 
 pub use bifrost::Bifrost;
 pub use error::{Error, ProviderError};
+#[cfg(any(test, feature = "local_loglet"))]
+pub use loglets::local_loglet::{
+    LocalLogReader, LocalLogState, LogStoreError, Options as LocalLogletOptions,
+};
+#[cfg(any(test, feature = "replicated_loglet"))]
+pub use loglets::replicated_loglet::{
+    LogServer, Options as ReplicatedLogletOptions, ReplicatedLogletParams,
+};
 pub use options::Options;
 pub use read_stream::LogReadStream;
+use restate_types::logs::metadata::{Chain, Logs};
+pub use restate_types::logs::metadata::{LogletParams, ProviderKind};
 use restate_types::logs::LogId;
 use restate_types::Version;
 pub use service::BifrostService;
 pub use types::*;
 
-use self::metadata::{Chain, LogletParams, Logs};
-
 /// Initializes the bifrost metadata with static log metadata, it creates a log for every partition
 /// with a chain of the default loglet provider kind.
 pub(crate) fn create_static_metadata(opts: &Options, num_partitions: u64) -> Logs {
@@ -40,10 +47,27 @@ pub(crate) fn create_static_metadata(opts: &Options, num_partitions: u64) -> Log
 
     // pre-fill with all possible logs up to `num_partitions`
     (0..num_partitions).for_each(|i| {
-        // fixed config that uses the log-id as loglet identifier/config
-        let config = LogletParams::from(i.to_string());
-        log_chain.insert(LogId::from(i), Chain::new(opts.default_provider, config));
+        log_chain.insert(LogId::from(i), default_chain(opts, i));
     });
 
     Logs::new(Version::MIN, log_chain)
 }
+
+/// Creates the chain of a new log which consists of a single segment of the default loglet
+/// provider kind.
+pub(crate) fn default_chain(opts: &Options, log_id: u64) -> Chain {
+    let config = match opts.default_provider {
+        #[cfg(any(test, feature = "replicated_loglet"))]
+        ProviderKind::Replicated => ReplicatedLogletParams::new(
+            log_id,
+            opts.replicated
+                .sequencer
+                .expect("replicated loglets require a sequencer"),
+            opts.replicated.nodeset.clone(),
+        )
+        .to_loglet_params(),
+        // fixed config that uses the log-id as loglet identifier/config
+        _ => LogletParams::from(log_id.to_string()),
+    };
+    Chain::new(opts.default_provider, config)
+}
@@ -8,41 +8,15 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::ops::Range;
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use enum_map::Enum;
 
+use restate_types::logs::metadata::{LogletParams, ProviderKind};
 use restate_types::logs::{Lsn, Payload, SequenceNumber};
 
-use crate::metadata::LogletParams;
-use crate::{Error, LogRecord, LsnExt, Options, ProviderError};
-
-/// An enum with the list of supported loglet providers.
-/// For each variant we must have a corresponding implementation of the
-/// [`crate::loglet::Loglet`] trait
-#[derive(
-    Debug,
-    Clone,
-    Hash,
-    Eq,
-    PartialEq,
-    Copy,
-    serde::Serialize,
-    serde::Deserialize,
-    Enum,
-    strum_macros::EnumIter,
-    strum_macros::Display,
-)]
-#[serde(rename_all = "snake_case")]
-pub enum ProviderKind {
-    #[cfg(any(test, feature = "local_loglet"))]
-    /// A local rocksdb-backed loglet.
-    Local,
-    #[cfg(any(test, feature = "memory_loglet"))]
-    /// An in-memory loglet, primarily for testing.
-    InMemory,
-}
+use crate::{Error, LogRecord, LsnExt, Options, ProviderError, SealReason};
 
 // why? because if all loglet features are disabled, clippy will complain about options being
 // unused.
@@ -58,6 +32,17 @@ pub fn create_provider(
         )?),
         #[cfg(any(test, feature = "memory_loglet"))]
         ProviderKind::InMemory => Ok(crate::loglets::memory_loglet::MemoryLogletProvider::new()?),
+        // The replicated loglet provider needs networking, it's registered by
+        // `BifrostService::with_networking`.
+        #[cfg(any(test, feature = "replicated_loglet"))]
+        ProviderKind::Replicated => Err(ProviderError::Other(anyhow::anyhow!(
+            "replicated loglet provider requires networking"
+        ))),
+        #[allow(unreachable_patterns)]
+        _ => Err(ProviderError::Other(anyhow::anyhow!(
+            "loglet provider {} is not enabled",
+            kind
+        ))),
     }
 }
 
@@ -139,6 +124,13 @@ pub trait LogletBase: Send + Sync {
     /// Append a record to the loglet.
     async fn append(&self, payload: Payload) -> Result<Self::Offset, Error>;
 
+    /// Append a batch of records to the loglet. The records are assigned consecutive offsets in
+    /// the order of the batch and are committed atomically.
+    ///
+    /// Returns the range of offsets of the appended records. An empty batch results in an empty
+    /// range starting at the next offset to be written.
+    async fn append_batch(&self, payloads: &[Payload]) -> Result<Range<Self::Offset>, Error>;
+
     /// Find the tail of the loglet. If the loglet is empty or have been trimmed, the loglet should
     /// return `None`.
     async fn find_tail(&self) -> Result<Option<Self::Offset>, Error>;
@@ -147,6 +139,21 @@ pub trait LogletBase: Send + Sync {
     /// before the next slot that will be written to.
     async fn get_trim_point(&self) -> Result<Self::Offset, Error>;
 
+    /// Trim the loglet prefix up to and including the `trim_point`. Trimmed records are not
+    /// readable anymore, readers observe a trim gap instead.
+    ///
+    /// The trim point is clamped to the current tail of the loglet. Trimming to an offset that is
+    /// at or before the current trim point is a no-op.
+    async fn trim(&self, trim_point: Self::Offset) -> Result<(), Error>;
+
+    /// Seal the loglet. A sealed loglet rejects all future appends with [`Error::LogSealed`] and
+    /// readers observe a [`crate::Record::Seal`] record after the last record of the loglet.
+    ///
+    /// Returns the offset of the last record in the sealed loglet, or the offset before the first
+    /// slot if the loglet is empty. All records up to this offset are durably committed when this
+    /// call returns. Sealing an already sealed loglet keeps the original seal reason.
+    async fn seal(&self, reason: SealReason) -> Result<Self::Offset, Error>;
+
     /// Read or wait for the record at `from` offset, or the next available record if `from` isn't
     /// defined for the loglet.
     async fn read_next_single(&self, after: Self::Offset)
@@ -169,6 +176,11 @@ impl LogletBase for LogletWrapper {
         Ok(self.base_lsn.offset_by(offset))
     }
 
+    async fn append_batch(&self, payloads: &[Payload]) -> Result<Range<Lsn>, Error> {
+        let offsets = self.loglet.append_batch(payloads).await?;
+        Ok(self.base_lsn.offset_by(offsets.start)..self.base_lsn.offset_by(offsets.end))
+    }
+
     async fn find_tail(&self) -> Result<Option<Lsn>, Error> {
         let offset = self.loglet.find_tail().await?;
         Ok(offset.map(|o| self.base_lsn.offset_by(o)))
@@ -176,7 +188,29 @@ impl LogletBase for LogletWrapper {
 
     async fn get_trim_point(&self) -> Result<Self::Offset, Error> {
         let offset = self.loglet.get_trim_point().await?;
-        Ok(self.base_lsn.offset_by(offset))
+        if offset == LogletOffset::INVALID {
+            // Nothing has been trimmed yet, the trim point is the slot before the base LSN.
+            Ok(self.base_lsn.prev_or_invalid())
+        } else {
+            Ok(self.base_lsn.offset_by(offset))
+        }
+    }
+
+    async fn trim(&self, trim_point: Lsn) -> Result<(), Error> {
+        // An LSN before the base LSN of this loglet maps to the invalid offset which
+        // loglets treat as a no-op.
+        let offset = trim_point.into_offset(self.base_lsn);
+        self.loglet.trim(offset).await
+    }
+
+    async fn seal(&self, reason: SealReason) -> Result<Lsn, Error> {
+        let offset = self.loglet.seal(reason).await?;
+        if offset == LogletOffset::INVALID {
+            // The loglet is empty, the tail is the slot before the base LSN.
+            Ok(self.base_lsn.prev_or_invalid())
+        } else {
+            Ok(self.base_lsn.offset_by(offset))
+        }
     }
 
     async fn read_next_single(&self, after: Lsn) -> Result<LogRecord<Lsn>, Error> {
@@ -53,14 +53,12 @@ impl LogStateUpdates {
         self
     }
 
-    #[allow(dead_code)]
     pub fn update_trim_point(mut self, trim_point: LogletOffset) -> Self {
         self.updates
             .push(LogStateUpdate::TrimPoint(trim_point.into()));
         self
     }
 
-    #[allow(dead_code)]
     pub fn seal(mut self, reason: SealReason) -> Self {
         self.updates.push(LogStateUpdate::Seal(reason));
         self
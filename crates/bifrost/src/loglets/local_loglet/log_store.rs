@@ -41,32 +41,26 @@ pub struct RocksDbLogStore {
 
 impl RocksDbLogStore {
     pub fn new(options: &Options) -> Result<Self, LogStoreError> {
-        let cache = if options.rocksdb_cache_size > 0 {
-            Some(Cache::new_lru_cache(options.rocksdb_cache_size))
-        } else {
-            None
-        };
-
-        let mut metadata_cf_options = cf_common_options(options, cache.clone());
-        metadata_cf_options.set_min_write_buffer_number_to_merge(10);
-        metadata_cf_options.set_max_successive_merges(10);
-        // Merge operator for log state updates
-        metadata_cf_options.set_merge_operator(
-            "LogStateMerge",
-            log_state_full_merge,
-            log_state_partial_merge,
-        );
-
-        let cfs = [
-            rocksdb::ColumnFamilyDescriptor::new(
-                DATA_CF,
-                cf_common_options(options, cache.clone()),
-            ),
-            rocksdb::ColumnFamilyDescriptor::new(METADATA_CF, metadata_cf_options),
-        ];
         let db_options = db_options(options);
 
-        let db = DB::open_cf_descriptors(&db_options, &options.path, cfs)?;
+        let db = DB::open_cf_descriptors(&db_options, &options.path, cf_descriptors(options))?;
+
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    /// Opens an existing log store without write access. The store can be opened while a node
+    /// is writing to it, but only the records that were written before opening it are visible.
+    pub fn open_read_only(options: &Options) -> Result<Self, LogStoreError> {
+        let mut db_options = db_options(options);
+        db_options.create_if_missing(false);
+        db_options.create_missing_column_families(false);
+
+        let db = DB::open_cf_descriptors_read_only(
+            &db_options,
+            &options.path,
+            cf_descriptors(options),
+            false,
+        )?;
 
         Ok(Self { db: Arc::new(db) })
     }
@@ -114,6 +108,29 @@ impl RocksDbLogStore {
     }
 }
 
+fn cf_descriptors(options: &Options) -> [rocksdb::ColumnFamilyDescriptor; 2] {
+    let cache = if options.rocksdb_cache_size > 0 {
+        Some(Cache::new_lru_cache(options.rocksdb_cache_size))
+    } else {
+        None
+    };
+
+    let mut metadata_cf_options = cf_common_options(options, cache.clone());
+    metadata_cf_options.set_min_write_buffer_number_to_merge(10);
+    metadata_cf_options.set_max_successive_merges(10);
+    // Merge operator for log state updates
+    metadata_cf_options.set_merge_operator(
+        "LogStateMerge",
+        log_state_full_merge,
+        log_state_partial_merge,
+    );
+
+    [
+        rocksdb::ColumnFamilyDescriptor::new(DATA_CF, cf_common_options(options, cache)),
+        rocksdb::ColumnFamilyDescriptor::new(METADATA_CF, metadata_cf_options),
+    ]
+}
+
 fn db_options(opts: &Options) -> rocksdb::Options {
     let mut db_options = rocksdb::Options::default();
     db_options.create_if_missing(true);
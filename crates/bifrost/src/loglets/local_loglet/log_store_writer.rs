@@ -9,18 +9,17 @@
 // by the Apache License, Version 2.0.
 
 use std::sync::Arc;
-use std::time::Duration;
 
 use bytes::Bytes;
 use rocksdb::{WriteBatch, DB};
 use tokio::sync::{mpsc, oneshot};
-use tokio::time::MissedTickBehavior;
 use tracing::{debug, trace, warn};
 
 use restate_core::{cancellation_watcher, task_center, ShutdownError, TaskKind};
+use restate_types::logs::SequenceNumber;
 
 use crate::loglet::LogletOffset;
-use crate::Error;
+use crate::{Error, SealReason};
 
 use super::keys::{MetadataKey, MetadataKind, RecordKey};
 use super::log_state::LogStateUpdates;
@@ -29,7 +28,6 @@ use super::log_store::{DATA_CF, METADATA_CF};
 #[derive(Debug, Clone)]
 pub struct WriterOptions {
     pub channel_size: usize,
-    pub commit_time_interval: Duration,
     pub batch_size_threshold: usize,
     pub flush_wal_on_commit: bool,
     pub disable_wal: bool,
@@ -46,7 +44,13 @@ pub struct LogStoreWriteCommand {
 }
 
 enum DataUpdate {
-    PutRecord { offset: LogletOffset, data: Bytes },
+    PutRecords {
+        first_offset: LogletOffset,
+        data: Vec<Bytes>,
+    },
+    TrimLog {
+        trim_point: LogletOffset,
+    },
 }
 
 pub(crate) struct LogStoreWriter {
@@ -55,15 +59,12 @@ pub(crate) struct LogStoreWriter {
     current_batch: WriteBatch,
     current_batch_acks: Vec<Ack>,
     db: Arc<DB>,
-    commit_timer: tokio::time::Interval,
 }
 
 impl LogStoreWriter {
     pub(crate) fn new(db: Arc<DB>, opts: WriterOptions) -> Self {
         let mut rocksdb_write_options = rocksdb::WriteOptions::new();
         rocksdb_write_options.disable_wal(opts.disable_wal);
-        let mut commit_timer = tokio::time::interval(opts.commit_time_interval);
-        commit_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
 
         Self {
             opts,
@@ -71,7 +72,6 @@ impl LogStoreWriter {
             current_batch: WriteBatch::default(),
             current_batch_acks: Default::default(),
             db,
-            commit_timer,
         }
     }
 
@@ -89,18 +89,21 @@ impl LogStoreWriter {
                         _ = cancellation_watcher() => {
                             break;
                         }
-                        _ = self.commit_timer.tick() => {
-                            if !self.current_batch.is_empty() {
-                                trace!("Triggering local loglet write batch commit due to max latency");
-                                self.commit();
-                            }
-                        }
                         cmd = receiver.recv() => {
-                            if let Some(cmd) = cmd {
-                                self.handle_command(cmd);
-                            } else {
+                            let Some(cmd) = cmd else {
                                 break;
+                            };
+                            self.handle_command(cmd);
+                            // Group commit: all commands that were enqueued while the previous
+                            // batch was being committed join the current batch. This way
+                            // concurrent appends share a single write and WAL flush.
+                            while self.current_batch.len() < self.opts.batch_size_threshold {
+                                let Ok(cmd) = receiver.try_recv() else {
+                                    break;
+                                };
+                                self.handle_command(cmd);
                             }
+                            self.commit();
                         }
                     }
                 }
@@ -115,9 +118,10 @@ impl LogStoreWriter {
     fn handle_command(&mut self, command: LogStoreWriteCommand) {
         if let Some(data_command) = command.data_update {
             match data_command {
-                DataUpdate::PutRecord { offset, data } => {
-                    self.put_record(command.log_id, offset, data)
+                DataUpdate::PutRecords { first_offset, data } => {
+                    self.put_records(command.log_id, first_offset, data)
                 }
+                DataUpdate::TrimLog { trim_point } => self.trim_log(command.log_id, trim_point),
             }
         }
 
@@ -128,11 +132,6 @@ impl LogStoreWriter {
         if let Some(ack) = command.ack {
             self.current_batch_acks.push(ack);
         }
-
-        if self.current_batch.len() >= self.opts.batch_size_threshold {
-            trace!("Local loglet write batch is full, committing");
-            self.commit();
-        }
     }
 
     fn update_log_state(&mut self, log_id: u64, updates: LogStateUpdates) {
@@ -144,10 +143,23 @@ impl LogStoreWriter {
         );
     }
 
-    fn put_record(&mut self, id: u64, offset: LogletOffset, data: Bytes) {
-        let key = RecordKey::new(id, offset);
+    fn put_records(&mut self, id: u64, first_offset: LogletOffset, data: Vec<Bytes>) {
+        let data_cf = self.db.cf_handle(DATA_CF).expect("data cf exists");
+        let mut offset = first_offset;
+        for data in data {
+            let key = RecordKey::new(id, offset);
+            self.current_batch.put_cf(data_cf, &key.to_bytes(), data);
+            offset = offset.next();
+        }
+    }
+
+    fn trim_log(&mut self, id: u64, trim_point: LogletOffset) {
         let data_cf = self.db.cf_handle(DATA_CF).expect("data cf exists");
-        self.current_batch.put_cf(data_cf, &key.to_bytes(), data);
+        // the end key of the range is exclusive
+        let from = RecordKey::new(id, LogletOffset::OLDEST);
+        let to = RecordKey::new(id, trim_point.next());
+        self.current_batch
+            .delete_range_cf(data_cf, from.to_bytes(), to.to_bytes());
     }
 
     fn commit(&mut self) {
@@ -157,6 +169,9 @@ impl LogStoreWriter {
             current_batch.len(),
         );
 
+        if current_batch.is_empty() && self.current_batch_acks.is_empty() {
+            return;
+        }
         if let Err(e) = self
             .db
             .write_opt(current_batch, &self.rocksdb_write_options)
@@ -173,8 +188,6 @@ impl LogStoreWriter {
                 return;
             }
         }
-        self.commit_timer
-            .reset_after(self.opts.commit_time_interval);
         self.send_acks(Ok(()));
     }
 
@@ -191,37 +204,80 @@ pub struct RocksDbLogWriterHandle {
 }
 
 impl RocksDbLogWriterHandle {
-    pub async fn enqueue_put_record(
+    /// Writes the records at consecutive offsets starting from `first_offset`.
+    pub async fn enqueue_put_records(
         &self,
         log_id: u64,
-        offset: LogletOffset,
-        data: Bytes,
+        first_offset: LogletOffset,
+        data: Vec<Bytes>,
         release_immediately: bool,
     ) -> Result<AckRecv, ShutdownError> {
+        debug_assert!(!data.is_empty());
         let (ack, receiver) = oneshot::channel();
-        let data_update = Some(DataUpdate::PutRecord { offset, data });
+        let last_offset = LogletOffset(first_offset.0 + data.len() as u64 - 1);
+        let data_update = Some(DataUpdate::PutRecords { first_offset, data });
         let log_state_updates = if release_immediately {
-            Some(LogStateUpdates::default().update_release_pointer(offset))
+            Some(LogStateUpdates::default().update_release_pointer(last_offset))
         } else {
             None
         };
-        if let Err(e) = self
-            .sender
-            .send(LogStoreWriteCommand {
-                log_id,
-                data_update,
-                log_state_updates,
-                ack: Some(ack),
-            })
-            .await
-        {
+        self.send_command(LogStoreWriteCommand {
+            log_id,
+            data_update,
+            log_state_updates,
+            ack: Some(ack),
+        })
+        .await?;
+
+        Ok(receiver)
+    }
+
+    /// Deletes all records up to and including the `trim_point` and persists the new trim point
+    /// in the log state.
+    pub async fn enqueue_trim(
+        &self,
+        log_id: u64,
+        trim_point: LogletOffset,
+    ) -> Result<AckRecv, ShutdownError> {
+        let (ack, receiver) = oneshot::channel();
+        self.send_command(LogStoreWriteCommand {
+            log_id,
+            data_update: Some(DataUpdate::TrimLog { trim_point }),
+            log_state_updates: Some(LogStateUpdates::default().update_trim_point(trim_point)),
+            ack: Some(ack),
+        })
+        .await?;
+
+        Ok(receiver)
+    }
+
+    /// Persists the seal in the log state. The seal is committed after all previously enqueued
+    /// records.
+    pub async fn enqueue_seal(
+        &self,
+        log_id: u64,
+        reason: SealReason,
+    ) -> Result<AckRecv, ShutdownError> {
+        let (ack, receiver) = oneshot::channel();
+        self.send_command(LogStoreWriteCommand {
+            log_id,
+            data_update: None,
+            log_state_updates: Some(LogStateUpdates::default().seal(reason)),
+            ack: Some(ack),
+        })
+        .await?;
+
+        Ok(receiver)
+    }
+
+    async fn send_command(&self, command: LogStoreWriteCommand) -> Result<(), ShutdownError> {
+        if let Err(e) = self.sender.send(command).await {
             warn!(
-                "Local loglet writer task is gone, not accepting the record: {}",
+                "Local loglet writer task is gone, not accepting the command: {}",
                 e
             );
             return Err(ShutdownError);
         }
-
-        Ok(receiver)
+        Ok(())
     }
 }
@@ -8,24 +8,27 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-mod keys;
+pub(crate) mod keys;
 mod log_state;
-mod log_store;
-mod log_store_writer;
+pub(crate) mod log_store;
+pub(crate) mod log_store_writer;
 mod options;
 mod provider;
-mod utils;
+mod reader;
+pub(crate) mod utils;
 
 use async_trait::async_trait;
 use bytes::Bytes;
 pub use log_store::LogStoreError;
 pub use options::Options;
 pub use provider::LocalLogletProvider;
+pub use reader::{LocalLogReader, LocalLogState};
 use restate_core::ShutdownError;
 use restate_types::logs::{Payload, SequenceNumber};
 use tokio::sync::Mutex;
 use tracing::{debug, warn};
 
+use std::ops::Range;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 use crate::loglet::{LogletBase, LogletOffset};
@@ -43,11 +46,12 @@ pub struct LocalLoglet {
     log_writer: RocksDbLogWriterHandle,
     // internal offset of the first record (or slot available)
     trim_point_offset: AtomicU64,
+    // serializes trims, the trim point is published only after it is durable
+    trim_lock: Mutex<()>,
     // In local loglet, the release point == the last committed offset
     last_committed_offset: AtomicU64,
     next_write_offset: Mutex<LogletOffset>,
-    #[allow(dead_code)]
-    seal: Option<SealReason>,
+    seal: std::sync::Mutex<Option<SealReason>>,
     release_watch: OffsetWatch,
 }
 
@@ -67,16 +71,21 @@ impl LocalLoglet {
         let next_write_offset_raw = log_state.release_pointer + 1;
         let next_write_offset = Mutex::new(LogletOffset::from(next_write_offset_raw));
         let release_pointer = LogletOffset::from(log_state.release_pointer);
-        let seal = log_state.seal;
+        let release_watch = OffsetWatch::new(release_pointer);
+        if log_state.seal.is_some() {
+            release_watch.seal();
+        }
+        let seal = std::sync::Mutex::new(log_state.seal);
         let loglet = Self {
             log_id,
             log_store,
             log_writer,
             trim_point_offset,
+            trim_lock: Mutex::new(()),
             next_write_offset,
             last_committed_offset,
             seal,
-            release_watch: OffsetWatch::new(release_pointer),
+            release_watch,
         };
         debug!(
             log_id = log_id,
@@ -107,7 +116,13 @@ impl LocalLoglet {
         // Are we reading after commit offset?
         let commit_offset = LogletOffset(self.last_committed_offset.load(Ordering::Relaxed));
         if from_offset > commit_offset {
-            Ok(None)
+            // The seal is only visible after all records before it have been committed.
+            Ok(self
+                .seal
+                .lock()
+                .unwrap()
+                .clone()
+                .map(|reason| LogRecord::new_seal(from_offset, reason)))
         } else {
             let key = RecordKey::new(self.log_id, from_offset);
             let data_cf = self.log_store.data_cf();
@@ -121,7 +136,7 @@ impl LocalLoglet {
             );
             let record = iter.next().transpose().map_err(LogStoreError::Rocksdb)?;
             let Some(record) = record else {
-                return Ok(None);
+                return self.trim_gap_if_trimmed(from_offset);
             };
 
             let (key, data) = record;
@@ -136,47 +151,88 @@ impl LocalLoglet {
                 );
                 return Ok(None);
             }
+            if key.offset != from_offset {
+                // The record we were looking for has been trimmed after we checked the trim point.
+                return self.trim_gap_if_trimmed(from_offset);
+            }
             let data = Bytes::from(data);
             Ok(Some(LogRecord::new_data(key.offset, Payload::from(data))))
         }
     }
+
+    /// Returns a trim gap starting at `from_offset` if the record at this offset has been
+    /// trimmed. The trim point is published only after the trim is durable, while the records
+    /// are already deleted when the trim commits. So if the published trim point doesn't cover a
+    /// missing record yet, we fall back to the trim point that is persisted in the log state.
+    fn trim_gap_if_trimmed(
+        &self,
+        from_offset: LogletOffset,
+    ) -> Result<Option<LogRecord<LogletOffset>>, Error> {
+        let mut trim_point = LogletOffset(self.trim_point_offset.load(Ordering::Relaxed));
+        if trim_point < from_offset {
+            if let Some(log_state) = self.log_store.get_log_state(self.log_id)? {
+                trim_point = trim_point.max(LogletOffset(log_state.trim_point));
+            }
+        }
+        if trim_point >= from_offset {
+            Ok(Some(LogRecord::new_trim_gap(from_offset, trim_point)))
+        } else {
+            Ok(None)
+        }
+    }
 }
 
 #[async_trait]
 impl LogletBase for LocalLoglet {
     type Offset = LogletOffset;
     async fn append(&self, payload: Payload) -> Result<LogletOffset, Error> {
+        let offsets = self.append_batch(std::slice::from_ref(&payload)).await?;
+        Ok(offsets.start)
+    }
+
+    async fn append_batch(&self, payloads: &[Payload]) -> Result<Range<LogletOffset>, Error> {
         // We hold the lock to ensure that offsets are enqueued in the order of
         // their offsets in the logstore writer. This means that acknowledgements
         // that an offset N from the writer imply that all previous offsets have
         // been durably committed, therefore, such offsets can be released to readers.
-        let (receiver, offset) = {
+        let (receiver, offsets) = {
             let mut next_offset_guard = self.next_write_offset.lock().await;
             // lock acquired
-            let offset = next_offset_guard.next();
+            if let Some(reason) = self.seal.lock().unwrap().as_ref() {
+                return Err(Error::LogletSealed(reason.clone()));
+            }
+            let first_offset = *next_offset_guard;
+            let end_offset = LogletOffset(first_offset.0 + payloads.len() as u64);
+            if payloads.is_empty() {
+                return Ok(first_offset..end_offset);
+            }
             let receiver = self
                 .log_writer
-                .enqueue_put_record(
+                .enqueue_put_records(
                     self.log_id,
-                    offset,
-                    payload.into(),
+                    first_offset,
+                    payloads
+                        .iter()
+                        .map(|payload| payload.clone().into())
+                        .collect(),
                     true, /* release_immediately */
                 )
                 .await?;
-            *next_offset_guard = offset;
-            (receiver, offset)
+            *next_offset_guard = end_offset;
+            (receiver, first_offset..end_offset)
             // lock dropped
         };
 
         let _ = receiver.await.unwrap_or_else(|_| {
-            warn!("Unsure if the local loglet record was written, the ack channel was dropped");
+            warn!("Unsure if the local loglet records were written, the ack channel was dropped");
             Err(Error::Shutdown(ShutdownError))
         })?;
 
+        let last_offset = LogletOffset(offsets.end.0 - 1);
         self.last_committed_offset
-            .fetch_max(offset.into(), Ordering::Relaxed);
+            .fetch_max(last_offset.into(), Ordering::Relaxed);
         self.notify_readers();
-        Ok(offset)
+        Ok(offsets)
     }
 
     async fn find_tail(&self) -> Result<Option<LogletOffset>, Error> {
@@ -192,6 +248,74 @@ impl LogletBase for LocalLoglet {
         Ok(LogletOffset(self.trim_point_offset.load(Ordering::Relaxed)))
     }
 
+    async fn trim(&self, trim_point: Self::Offset) -> Result<(), Error> {
+        // We cannot trim beyond the release pointer.
+        let last_committed = LogletOffset::from(self.last_committed_offset.load(Ordering::Relaxed));
+        let trim_point = trim_point.min(last_committed);
+
+        // Trims are serialized so that the published trim point only moves forward.
+        let _trim_guard = self.trim_lock.lock().await;
+        let previous_trim_point = LogletOffset(self.trim_point_offset.load(Ordering::Relaxed));
+        if trim_point <= previous_trim_point {
+            return Ok(());
+        }
+
+        debug!(
+            log_id = self.log_id,
+            previous_trim_point = %previous_trim_point,
+            trim_point = %trim_point,
+            "Trimming local loglet"
+        );
+        let receiver = self
+            .log_writer
+            .enqueue_trim(self.log_id, trim_point)
+            .await?;
+
+        let _ = receiver.await.unwrap_or_else(|_| {
+            warn!("Unsure if the local loglet was trimmed, the ack channel was dropped");
+            Err(Error::Shutdown(ShutdownError))
+        })?;
+
+        // Readers only observe the new trim point once it is durable, a crash cannot roll back a
+        // trim point that readers have already acted upon.
+        self.trim_point_offset
+            .fetch_max(trim_point.into(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn seal(&self, reason: SealReason) -> Result<Self::Offset, Error> {
+        // We hold the write lock until the seal is durable, appends which are waiting for the lock
+        // will observe the seal and fail.
+        let next_offset_guard = self.next_write_offset.lock().await;
+        let tail = LogletOffset(next_offset_guard.0 - 1);
+        if self.seal.lock().unwrap().is_some() {
+            return Ok(tail);
+        }
+
+        debug!(
+            log_id = self.log_id,
+            tail = %tail,
+            "Sealing local loglet, reason: {:?}",
+            reason
+        );
+        // The seal is committed after all previously enqueued records.
+        let receiver = self
+            .log_writer
+            .enqueue_seal(self.log_id, reason.clone())
+            .await?;
+        let _ = receiver.await.unwrap_or_else(|_| {
+            warn!("Unsure if the local loglet was sealed, the ack channel was dropped");
+            Err(Error::Shutdown(ShutdownError))
+        })?;
+
+        // All records up to the tail are durable, release them before readers observe the seal.
+        self.last_committed_offset
+            .fetch_max(tail.into(), Ordering::Relaxed);
+        *self.seal.lock().unwrap() = Some(reason);
+        self.release_watch.seal();
+        Ok(tail)
+    }
+
     async fn read_next_single(
         &self,
         after: Self::Offset,
@@ -213,3 +337,217 @@ impl LogletBase for LocalLoglet {
         self.read_after(after)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use restate_core::TestCoreEnv;
+    use restate_test_util::let_assert;
+
+    use crate::loglets::local_loglet::log_store_writer::WriterOptions;
+    use crate::Record;
+
+    async fn create_loglet(
+        log_store: &RocksDbLogStore,
+        options: &Options,
+    ) -> anyhow::Result<LocalLoglet> {
+        let log_writer = log_store
+            .create_writer(WriterOptions {
+                channel_size: options.writer_queue_len,
+                batch_size_threshold: options.writer_commit_batch_size_threshold,
+                flush_wal_on_commit: options.flush_wal_on_commit,
+                disable_wal: options.rocksdb_disable_wal,
+            })
+            .start()?;
+        Ok(LocalLoglet::create(1, log_store.clone(), log_writer).await?)
+    }
+
+    #[tokio::test]
+    async fn test_local_loglet_append_batch() -> anyhow::Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let base_dir = tempfile::tempdir()?;
+            let options = Options {
+                path: base_dir.path().join("local_loglet"),
+                ..Options::default()
+            };
+            let log_store = RocksDbLogStore::new(&options)?;
+            let loglet = create_loglet(&log_store, &options).await?;
+
+            let offsets = loglet
+                .append_batch(&[Payload::from("a"), Payload::from("b"), Payload::from("c")])
+                .await?;
+            assert_eq!(LogletOffset(1)..LogletOffset(4), offsets);
+            assert_eq!(Some(LogletOffset(3)), loglet.find_tail().await?);
+
+            // an empty batch doesn't move the tail
+            let offsets = loglet.append_batch(&[]).await?;
+            assert_eq!(LogletOffset(4)..LogletOffset(4), offsets);
+
+            let offset = loglet.append(Payload::from("d")).await?;
+            assert_eq!(LogletOffset(4), offset);
+
+            for (i, expected) in ["a", "b", "c", "d"].into_iter().enumerate() {
+                let LogRecord { offset, record } =
+                    loglet.read_next_single(LogletOffset(i as u64)).await?;
+                assert_eq!(LogletOffset(i as u64 + 1), offset);
+                assert_eq!(Payload::from(expected), record.into_payload_unchecked());
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_local_loglet_group_commit() -> anyhow::Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let base_dir = tempfile::tempdir()?;
+            let options = Options {
+                path: base_dir.path().join("local_loglet"),
+                // commands which queue up while a batch is committed share the next commit
+                writer_commit_batch_size_threshold: 10,
+                ..Options::default()
+            };
+            let log_store = RocksDbLogStore::new(&options)?;
+            let loglet = std::sync::Arc::new(create_loglet(&log_store, &options).await?);
+
+            let appends: Vec<_> = (0..50)
+                .map(|i| {
+                    let loglet = loglet.clone();
+                    tokio::spawn(async move {
+                        let payloads = [
+                            Payload::from(format!("{i}-1")),
+                            Payload::from(format!("{i}-2")),
+                        ];
+                        let offsets = loglet.append_batch(&payloads).await?;
+                        Ok::<_, Error>((i, offsets))
+                    })
+                })
+                .collect();
+
+            let mut appended = Vec::with_capacity(appends.len());
+            for append in appends {
+                appended.push(append.await??);
+            }
+            assert_eq!(Some(LogletOffset(100)), loglet.find_tail().await?);
+
+            // every batch occupies consecutive offsets and all writes are durable
+            let log_state = log_store.get_log_state(1)?.expect("log state exists");
+            assert_eq!(100, log_state.release_pointer);
+            for (i, offsets) in appended {
+                assert_eq!(2, offsets.end.0 - offsets.start.0);
+                let LogRecord { record, .. } =
+                    loglet.read_next_single(offsets.start.prev()).await?;
+                assert_eq!(
+                    Payload::from(format!("{i}-1")),
+                    record.into_payload_unchecked()
+                );
+                let LogRecord { record, .. } = loglet.read_next_single(offsets.start).await?;
+                assert_eq!(
+                    Payload::from(format!("{i}-2")),
+                    record.into_payload_unchecked()
+                );
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_local_loglet_trim() -> anyhow::Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let base_dir = tempfile::tempdir()?;
+            let options = Options {
+                path: base_dir.path().join("local_loglet"),
+                ..Options::default()
+            };
+            let log_store = RocksDbLogStore::new(&options)?;
+            let loglet = create_loglet(&log_store, &options).await?;
+
+            for i in 1..=5 {
+                loglet.append(Payload::from(format!("record{}", i))).await?;
+            }
+
+            // trimming to the invalid offset is a no-op
+            loglet.trim(LogletOffset::INVALID).await?;
+            assert_eq!(LogletOffset::INVALID, loglet.get_trim_point().await?);
+
+            loglet.trim(LogletOffset(3)).await?;
+            assert_eq!(LogletOffset(3), loglet.get_trim_point().await?);
+            assert_eq!(Some(LogletOffset(5)), loglet.find_tail().await?);
+
+            // the trim point is durable once the trim returns
+            let log_state = log_store.get_log_state(1)?.expect("log state exists");
+            assert_eq!(3, log_state.trim_point);
+
+            // trim points cannot move backwards
+            loglet.trim(LogletOffset(2)).await?;
+            assert_eq!(LogletOffset(3), loglet.get_trim_point().await?);
+
+            // trimming beyond the tail is clamped to the tail
+            loglet.trim(LogletOffset(10)).await?;
+            assert_eq!(LogletOffset(5), loglet.get_trim_point().await?);
+
+            // appends continue after the trimmed tail
+            let offset = loglet.append(Payload::from("record6")).await?;
+            assert_eq!(LogletOffset(6), offset);
+
+            // a loglet that is re-created from the store recovers the trim point
+            drop(loglet);
+            let loglet = create_loglet(&log_store, &options).await?;
+            assert_eq!(LogletOffset(5), loglet.get_trim_point().await?);
+            assert_eq!(Some(LogletOffset(6)), loglet.find_tail().await?);
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_local_loglet_read_trim_gap() -> anyhow::Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let base_dir = tempfile::tempdir()?;
+            let options = Options {
+                path: base_dir.path().join("local_loglet"),
+                ..Options::default()
+            };
+            let log_store = RocksDbLogStore::new(&options)?;
+            let loglet = create_loglet(&log_store, &options).await?;
+
+            for i in 1..=5 {
+                loglet.append(Payload::from(format!("record{}", i))).await?;
+            }
+            loglet.trim(LogletOffset(3)).await?;
+
+            // reading a trimmed record returns a trim gap until the trim point
+            let_assert!(
+                Some(log_record) = loglet.read_next_single_opt(LogletOffset::INVALID).await?
+            );
+            let LogRecord { offset, record } = log_record;
+            assert_eq!(LogletOffset::OLDEST, offset);
+            let_assert!(Record::TrimGap(trim_gap) = record);
+            assert_eq!(LogletOffset(3), trim_gap.until);
+
+            // reading from within the trimmed range reports the rest of the gap
+            let LogRecord { offset, record } =
+                loglet.read_next_single(LogletOffset::OLDEST).await?;
+            assert_eq!(LogletOffset(2), offset);
+            let_assert!(Record::TrimGap(trim_gap) = record);
+            assert_eq!(LogletOffset(3), trim_gap.until);
+
+            // the first record after the trim point is still readable
+            let LogRecord { offset, record } = loglet.read_next_single(LogletOffset(3)).await?;
+            assert_eq!(LogletOffset(4), offset);
+            assert_eq!(Payload::from("record4"), record.into_payload_unchecked());
+            Ok(())
+        })
+        .await
+    }
+}
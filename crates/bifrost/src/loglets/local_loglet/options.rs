@@ -15,6 +15,8 @@ use restate_types::DEFAULT_STORAGE_DIRECTORY;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 
+pub(crate) const DEFAULT_WRITER_COMMIT_TIME_INTERVAL: Duration = Duration::from_millis(13);
+
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize, derive_builder::Builder)]
 #[cfg_attr(feature = "options_schema", derive(schemars::JsonSchema))]
@@ -28,10 +30,12 @@ pub struct Options {
     pub rocksdb_cache_size: usize,
     pub rocksdb_max_total_wal_size: u64,
     pub rocksdb_write_buffer_size: usize,
-    /// Trigger a commit when the batch size exceeds this threshold. Set to 0 or 1 to commit the
-    /// write batch on every command.
+    /// The writer commits all writes that queued up while the previous batch was committed
+    /// in a single batch (group commit). Trigger a commit when the batch size exceeds this
+    /// threshold. Set to 0 or 1 to commit the write batch on every command.
     pub writer_commit_batch_size_threshold: usize,
-    /// Trigger a commit when the time since the last commit exceeds this threshold.
+    /// Deprecated, has no effect. The writer commits the next batch as soon as the previous
+    /// commit has finished instead of waiting for this interval.
     #[serde_as(as = "serde_with::DisplayFromStr")]
     #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
     pub writer_commit_time_interval: humantime::Duration,
@@ -54,7 +58,7 @@ impl Default for Options {
             rocksdb_max_total_wal_size: 2 * (1 << 30), // 2 GiB
             rocksdb_write_buffer_size: 0,
             writer_commit_batch_size_threshold: 200,
-            writer_commit_time_interval: Duration::from_millis(13).into(),
+            writer_commit_time_interval: DEFAULT_WRITER_COMMIT_TIME_INTERVAL.into(),
             writer_queue_len: 200,
             flush_wal_on_commit: true,
         }
@@ -14,10 +14,11 @@ use std::sync::{Arc, OnceLock};
 use anyhow::Context;
 use async_trait::async_trait;
 use tokio::sync::Mutex as AsyncMutex;
-use tracing::debug;
+use tracing::{debug, warn};
 
 use super::log_store::RocksDbLogStore;
 use super::log_store_writer::RocksDbLogWriterHandle;
+use super::options::DEFAULT_WRITER_COMMIT_TIME_INTERVAL;
 use super::{LocalLoglet, Options};
 use crate::loglet::{Loglet, LogletOffset, LogletProvider};
 use crate::loglets::local_loglet::log_store_writer::WriterOptions;
@@ -85,10 +86,12 @@ impl LogletProvider for LocalLogletProvider {
     }
 
     fn start(&self) -> Result<(), ProviderError> {
+        if *self.opts.writer_commit_time_interval != DEFAULT_WRITER_COMMIT_TIME_INTERVAL {
+            warn!("The local loglet option 'writer_commit_time_interval' is deprecated and has no effect");
+        }
         let writer_options = WriterOptions {
             channel_size: self.opts.writer_queue_len,
-            commit_time_interval: self.opts.writer_commit_time_interval.into(),
-            batch_size_threshold: self.opts.writer_queue_len,
+            batch_size_threshold: self.opts.writer_commit_batch_size_threshold,
             flush_wal_on_commit: self.opts.flush_wal_on_commit,
             disable_wal: self.opts.rocksdb_disable_wal,
         };
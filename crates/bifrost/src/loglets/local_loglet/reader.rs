@@ -0,0 +1,201 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use bytes::Bytes;
+use restate_types::logs::{Lsn, Payload, SequenceNumber};
+
+use super::keys::RecordKey;
+use super::log_store::{LogStoreError, RocksDbLogStore};
+use super::Options;
+use crate::loglet::LogletOffset;
+use crate::types::LsnExt;
+use crate::SealReason;
+
+/// Read-only access to the loglets in the store of the local loglet, for instance to inspect the
+/// logs of a node post-mortem.
+///
+/// A local loglet is a segment of a log chain, its records are addressed by loglet offsets. The
+/// reader maps the offsets to the LSNs of the log through the base LSN of the segment. With the
+/// static log metadata that bifrost creates for local loglets, the loglet of a log uses the log
+/// id as its identifier and is the first segment of the log, starting at [`Lsn::OLDEST`].
+pub struct LocalLogReader {
+    log_store: RocksDbLogStore,
+}
+
+/// The state of a local loglet as persisted by the local loglet, in LSNs of the log.
+#[derive(Debug, Clone)]
+pub struct LocalLogState {
+    /// The LSN of the last committed record.
+    pub release_pointer: Lsn,
+    /// The LSN of the last trimmed record.
+    pub trim_point: Lsn,
+    pub seal: Option<SealReason>,
+}
+
+impl LocalLogReader {
+    /// Opens the store of the local loglet at the configured path read-only.
+    pub fn open(options: &Options) -> Result<Self, LogStoreError> {
+        Ok(Self {
+            log_store: RocksDbLogStore::open_read_only(options)?,
+        })
+    }
+
+    /// Returns the state of the loglet that starts at `base_lsn` in its log or `None` if the
+    /// store doesn't know the loglet.
+    pub fn log_state(
+        &self,
+        loglet_id: u64,
+        base_lsn: Lsn,
+    ) -> Result<Option<LocalLogState>, LogStoreError> {
+        let log_state = self.log_store.get_log_state(loglet_id)?;
+        let to_lsn = |offset: u64| match LogletOffset(offset) {
+            LogletOffset::INVALID => base_lsn.prev_or_invalid(),
+            offset => base_lsn.offset_by(offset),
+        };
+        Ok(log_state.map(|log_state| LocalLogState {
+            release_pointer: to_lsn(log_state.release_pointer),
+            trim_point: to_lsn(log_state.trim_point),
+            seal: log_state.seal,
+        }))
+    }
+
+    /// Iterates over the committed records of the loglet that starts at `base_lsn` in its log,
+    /// in LSN order starting at `from`. Trimmed records are not stored anymore and are skipped.
+    /// Records after the release pointer are not committed yet and are not returned.
+    pub fn records(
+        &self,
+        loglet_id: u64,
+        base_lsn: Lsn,
+        from: Lsn,
+    ) -> Result<impl Iterator<Item = Result<(Lsn, Payload), LogStoreError>> + '_, LogStoreError>
+    {
+        let release_pointer = self
+            .log_store
+            .get_log_state(loglet_id)?
+            .map(|log_state| LogletOffset(log_state.release_pointer))
+            .unwrap_or(LogletOffset::INVALID);
+        let from = RecordKey::new(
+            loglet_id,
+            from.into_offset(base_lsn).max(LogletOffset::OLDEST),
+        );
+        let mut read_opts = rocksdb::ReadOptions::default();
+        read_opts
+            .set_iterate_upper_bound(RecordKey::new(loglet_id, release_pointer.next()).to_bytes());
+
+        Ok(self
+            .log_store
+            .db()
+            .iterator_cf_opt(
+                self.log_store.data_cf(),
+                read_opts,
+                rocksdb::IteratorMode::From(&from.to_bytes(), rocksdb::Direction::Forward),
+            )
+            .map(move |record| {
+                let (key, data) = record?;
+                let key = RecordKey::from_slice(&key);
+                Ok((
+                    base_lsn.offset_by(key.offset),
+                    Payload::from(Bytes::from(data)),
+                ))
+            }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use restate_core::TestCoreEnv;
+
+    use crate::loglet::LogletBase;
+    use crate::loglets::local_loglet::log_store_writer::WriterOptions;
+    use crate::loglets::local_loglet::LocalLoglet;
+
+    #[tokio::test]
+    async fn test_read_only_reader() -> anyhow::Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let base_dir = tempfile::tempdir()?;
+            let options = Options {
+                path: base_dir.path().join("local_loglet"),
+                ..Options::default()
+            };
+            let log_store = RocksDbLogStore::new(&options)?;
+            let log_writer = log_store
+                .create_writer(WriterOptions {
+                    channel_size: options.writer_queue_len,
+                    batch_size_threshold: options.writer_commit_batch_size_threshold,
+                    flush_wal_on_commit: options.flush_wal_on_commit,
+                    disable_wal: options.rocksdb_disable_wal,
+                })
+                .start()?;
+            let loglet = LocalLoglet::create(1, log_store.clone(), log_writer.clone()).await?;
+            loglet
+                .append_batch(&[Payload::from("a"), Payload::from("b"), Payload::from("c")])
+                .await?;
+            loglet.trim(LogletOffset(1)).await?;
+            loglet.seal(SealReason::Resharding).await?;
+            // The log continues with a second loglet after the sealed tail.
+            let next_loglet = LocalLoglet::create(2, log_store.clone(), log_writer).await?;
+            next_loglet
+                .append_batch(&[Payload::from("d"), Payload::from("e")])
+                .await?;
+            // A record after the release pointer is not committed.
+            log_store.db().put_cf(
+                log_store.data_cf(),
+                RecordKey::new(2, LogletOffset(3)).to_bytes(),
+                "f",
+            )?;
+
+            // The store can be read while the loglets keep it open.
+            let reader = LocalLogReader::open(&options)?;
+            let state = reader.log_state(1, Lsn::OLDEST)?.expect("loglet 1 exists");
+            assert_eq!(Lsn::from(3), state.release_pointer);
+            assert_eq!(Lsn::from(1), state.trim_point);
+            assert!(matches!(state.seal, Some(SealReason::Resharding)));
+            let state = reader.log_state(2, Lsn::from(4))?.expect("loglet 2 exists");
+            assert_eq!(Lsn::from(5), state.release_pointer);
+            assert_eq!(Lsn::from(3), state.trim_point);
+            assert!(state.seal.is_none());
+            assert!(reader.log_state(3, Lsn::OLDEST)?.is_none());
+
+            let records = reader
+                .records(1, Lsn::OLDEST, Lsn::INVALID)?
+                .collect::<Result<Vec<_>, _>>()?;
+            assert_eq!(
+                vec![
+                    (Lsn::from(2), Payload::from("b")),
+                    (Lsn::from(3), Payload::from("c"))
+                ],
+                records
+            );
+
+            let records = reader
+                .records(1, Lsn::OLDEST, Lsn::from(3))?
+                .collect::<Result<Vec<_>, _>>()?;
+            assert_eq!(vec![(Lsn::from(3), Payload::from("c"))], records);
+
+            let records = reader
+                .records(2, Lsn::from(4), Lsn::OLDEST)?
+                .collect::<Result<Vec<_>, _>>()?;
+            assert_eq!(
+                vec![
+                    (Lsn::from(4), Payload::from("d")),
+                    (Lsn::from(5), Payload::from("e"))
+                ],
+                records
+            );
+            assert_eq!(0, reader.records(3, Lsn::OLDEST, Lsn::OLDEST)?.count());
+            Ok(())
+        })
+        .await
+    }
+}
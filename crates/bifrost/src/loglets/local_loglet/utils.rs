@@ -11,6 +11,7 @@
 use tokio::sync::watch;
 
 use restate_core::ShutdownError;
+use restate_types::logs::SequenceNumber;
 
 use crate::loglet::LogletOffset;
 
@@ -41,6 +42,12 @@ impl OffsetWatch {
         });
     }
 
+    /// Wakes up all current and future waiters. Once a loglet is sealed, no more records will be
+    /// released and readers need to observe the seal instead.
+    pub fn seal(&self) {
+        self.sender.send_replace(LogletOffset::MAX);
+    }
+
     /// Blocks until the offset is greater or equal to the given offset.
     pub async fn wait_for(&self, offset: LogletOffset) -> Result<(), ShutdownError> {
         self.receive
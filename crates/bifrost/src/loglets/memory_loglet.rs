@@ -10,6 +10,7 @@
 
 use std::cmp::Reverse;
 use std::collections::{hash_map, BinaryHeap, HashMap};
+use std::ops::Range;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
@@ -21,9 +22,9 @@ use tokio::sync::Mutex as AsyncMutex;
 use tracing::{debug, info};
 
 use crate::loglet::{Loglet, LogletBase, LogletOffset, LogletProvider};
-use crate::metadata::LogletParams;
 use crate::LogRecord;
-use crate::{Error, ProviderError};
+use crate::{Error, ProviderError, SealReason};
+use restate_types::logs::metadata::LogletParams;
 
 #[derive(Default)]
 pub struct MemoryLogletProvider {
@@ -115,6 +116,7 @@ pub struct MemoryLoglet {
     // internal offset of the first record (or slot available)
     trim_point_offset: AtomicU64,
     last_committed_offset: AtomicU64,
+    seal: Mutex<Option<SealReason>>,
     // reversed comparator. The watcher with the lowest offset ranks
     // higher in the binary heap.
     watchers: Mutex<BinaryHeap<Reverse<OffsetWatcher>>>,
@@ -128,6 +130,7 @@ impl MemoryLoglet {
             // Trim point is 0 initially
             trim_point_offset: AtomicU64::new(0),
             last_committed_offset: AtomicU64::new(0),
+            seal: Mutex::new(None),
             watchers: Mutex::new(BinaryHeap::new()),
         })
     }
@@ -151,13 +154,28 @@ impl MemoryLoglet {
     pub fn watch_for_offset(&self, offset: LogletOffset) -> Receiver<()> {
         let mut watchers = self.watchers.lock().unwrap();
         let (snd, rcv) = tokio::sync::oneshot::channel();
-        watchers.push(Reverse(OffsetWatcher {
-            offset,
-            channel: snd,
-        }));
+        let committed = LogletOffset(self.last_committed_offset.load(Ordering::Acquire));
+        // The seal is set before watchers are notified about it, checking it while holding the
+        // watchers lock guarantees that we don't miss the notification.
+        if offset <= committed || self.seal.lock().unwrap().is_some() {
+            let _ = snd.send(());
+        } else {
+            watchers.push(Reverse(OffsetWatcher {
+                offset,
+                channel: snd,
+            }));
+        }
         rcv
     }
 
+    /// Notifies all watchers, the loglet has been sealed and no more records will be committed.
+    fn notify_all_watchers(&self) {
+        let mut watchers = self.watchers.lock().unwrap();
+        for Reverse(watcher) in watchers.drain() {
+            let _ = watcher.channel.send(());
+        }
+    }
+
     pub fn notify_watchers(&self) {
         // it's safe to not lock the logs mutex because commit offset increases monotonically.
         let committed = LogletOffset(self.last_committed_offset.load(Ordering::Acquire));
@@ -186,7 +204,13 @@ impl MemoryLoglet {
         // are we reading after commit offset?
         let commit_offset = LogletOffset(self.last_committed_offset.load(Ordering::Acquire));
         if from_offset > commit_offset {
-            Ok(None)
+            // A sealed loglet will never commit more records.
+            Ok(self
+                .seal
+                .lock()
+                .unwrap()
+                .clone()
+                .map(|reason| LogRecord::new_seal(from_offset, reason)))
         } else {
             let index = self.saturating_offset_to_index(from_offset);
             Ok(Some(LogRecord::new_data(
@@ -202,17 +226,29 @@ impl LogletBase for MemoryLoglet {
     type Offset = LogletOffset;
 
     async fn append(&self, payload: Payload) -> Result<LogletOffset, Error> {
+        let offsets = self.append_batch(std::slice::from_ref(&payload)).await?;
+        Ok(offsets.start)
+    }
+
+    async fn append_batch(&self, payloads: &[Payload]) -> Result<Range<LogletOffset>, Error> {
         let mut log = self.log.lock().unwrap();
-        let offset = self.index_to_offset(log.len());
+        if let Some(reason) = self.seal.lock().unwrap().as_ref() {
+            return Err(Error::LogletSealed(reason.clone()));
+        }
+        let first_offset = self.index_to_offset(log.len());
         debug!(
-            "Appending record to in-memory loglet {:?} at offset {}",
-            self.params, offset,
+            "Appending {} records to in-memory loglet {:?} at offset {}",
+            payloads.len(),
+            self.params,
+            first_offset,
         );
-        log.push(payload);
+        log.extend_from_slice(payloads);
+        let end_offset = LogletOffset(first_offset.0 + payloads.len() as u64);
         // mark as committed immediately.
-        let offset = LogletOffset(self.last_committed_offset.load(Ordering::Acquire)).next();
-        self.advance_commit_offset(offset);
-        Ok(offset)
+        if !payloads.is_empty() {
+            self.advance_commit_offset(LogletOffset(end_offset.0 - 1));
+        }
+        Ok(first_offset..end_offset)
     }
 
     async fn find_tail(&self) -> Result<Option<LogletOffset>, Error> {
@@ -230,6 +266,46 @@ impl LogletBase for MemoryLoglet {
         Ok(LogletOffset(self.trim_point_offset.load(Ordering::Acquire)))
     }
 
+    async fn trim(&self, trim_point: LogletOffset) -> Result<(), Error> {
+        let mut log = self.log.lock().unwrap();
+        // we cannot trim beyond the tail of the loglet
+        let committed = LogletOffset(self.last_committed_offset.load(Ordering::Acquire));
+        let trim_point = trim_point.min(committed);
+        let current_trim_point = LogletOffset(self.trim_point_offset.load(Ordering::Acquire));
+        if trim_point <= current_trim_point {
+            return Ok(());
+        }
+
+        let trim_point_index = self.saturating_offset_to_index(trim_point);
+        debug!(
+            "Trimming in-memory loglet {:?} from offset {} to {}",
+            self.params, current_trim_point, trim_point,
+        );
+        log.drain(0..=trim_point_index);
+        self.trim_point_offset
+            .store(trim_point.0, Ordering::Release);
+        Ok(())
+    }
+
+    async fn seal(&self, reason: SealReason) -> Result<LogletOffset, Error> {
+        {
+            // Holding the log lock guarantees that no append is in progress.
+            let _log = self.log.lock().unwrap();
+            let mut seal = self.seal.lock().unwrap();
+            if seal.is_none() {
+                debug!(
+                    "Sealing in-memory loglet {:?}, reason: {:?}",
+                    self.params, reason
+                );
+                *seal = Some(reason);
+            }
+        }
+        self.notify_all_watchers();
+        Ok(LogletOffset(
+            self.last_committed_offset.load(Ordering::Acquire),
+        ))
+    }
+
     async fn read_next_single(
         &self,
         after: LogletOffset,
@@ -264,6 +340,8 @@ mod tests {
     use tokio::task::JoinHandle;
     use tracing_test::traced_test;
 
+    use crate::Record;
+
     #[tokio::test(start_paused = true)]
     #[traced_test]
     async fn test_memory_loglet() -> Result<()> {
@@ -365,4 +443,93 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_memory_loglet_trim() -> Result<()> {
+        let loglet = MemoryLoglet::new(LogletParams::from("113".to_string()));
+
+        for i in 1..=5 {
+            loglet.append(Payload::from(format!("record{}", i))).await?;
+        }
+
+        // trimming to the invalid offset is a no-op
+        loglet.trim(LogletOffset::INVALID).await?;
+        assert_eq!(LogletOffset::INVALID, loglet.get_trim_point().await?);
+
+        loglet.trim(LogletOffset(3)).await?;
+        assert_eq!(LogletOffset(3), loglet.get_trim_point().await?);
+        assert_eq!(Some(LogletOffset(5)), loglet.find_tail().await?);
+
+        // reading a trimmed record returns a trim gap until the trim point
+        let_assert!(Some(log_record) = loglet.read_next_single_opt(LogletOffset::INVALID).await?);
+        let LogRecord { offset, record } = log_record;
+        assert_eq!(LogletOffset::OLDEST, offset);
+        let_assert!(Record::TrimGap(trim_gap) = record);
+        assert_eq!(LogletOffset(3), trim_gap.until);
+
+        // the first record after the trim point is still readable
+        let LogRecord { offset, record } = loglet.read_next_single(LogletOffset(3)).await?;
+        assert_eq!(LogletOffset(4), offset);
+        assert_eq!(Payload::from("record4"), record.into_payload_unchecked());
+
+        // trim points cannot move backwards
+        loglet.trim(LogletOffset(2)).await?;
+        assert_eq!(LogletOffset(3), loglet.get_trim_point().await?);
+
+        // trimming beyond the tail is clamped to the tail
+        loglet.trim(LogletOffset(10)).await?;
+        assert_eq!(LogletOffset(5), loglet.get_trim_point().await?);
+
+        // appends continue after the trimmed tail
+        let offset = loglet.append(Payload::from("record6")).await?;
+        assert_eq!(LogletOffset(6), offset);
+        let LogRecord { offset, record } = loglet.read_next_single(LogletOffset(5)).await?;
+        assert_eq!(LogletOffset(6), offset);
+        assert_eq!(Payload::from("record6"), record.into_payload_unchecked());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_memory_loglet_seal() -> Result<()> {
+        let loglet = MemoryLoglet::new(LogletParams::from("114".to_string()));
+
+        for i in 1..=3 {
+            loglet.append(Payload::from(format!("record{}", i))).await?;
+        }
+
+        // a reader waiting for a future record is woken up by the seal
+        let reader: JoinHandle<Result<LogRecord<LogletOffset>>> = tokio::spawn({
+            let loglet = loglet.clone();
+            async move { Ok(loglet.read_next_single(LogletOffset(3)).await?) }
+        });
+        tokio::task::yield_now().await;
+        assert!(!reader.is_finished());
+
+        let tail = loglet.seal(SealReason::Resharding).await?;
+        assert_eq!(LogletOffset(3), tail);
+
+        let LogRecord { offset, record } = reader.await.unwrap()?;
+        assert_eq!(LogletOffset(4), offset);
+        let_assert!(Record::Seal(SealReason::Resharding) = record);
+
+        // sealing twice keeps the original reason
+        let tail = loglet.seal(SealReason::Other("again".to_string())).await?;
+        assert_eq!(LogletOffset(3), tail);
+
+        // records before the seal are still readable
+        let LogRecord { offset, record } = loglet.read_next_single(LogletOffset(2)).await?;
+        assert_eq!(LogletOffset(3), offset);
+        assert_eq!(Payload::from("record3"), record.into_payload_unchecked());
+
+        // appends are rejected
+        let res = loglet.append(Payload::from("record4")).await;
+        assert!(matches!(
+            res,
+            Err(Error::LogletSealed(SealReason::Resharding))
+        ));
+        assert_eq!(Some(LogletOffset(3)), loglet.find_tail().await?);
+
+        Ok(())
+    }
 }
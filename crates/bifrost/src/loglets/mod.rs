@@ -12,3 +12,5 @@
 pub mod local_loglet;
 #[cfg(any(test, feature = "memory_loglet"))]
 pub mod memory_loglet;
+#[cfg(any(test, feature = "replicated_loglet"))]
+pub mod replicated_loglet;
@@ -0,0 +1,426 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::{hash_map, HashMap};
+use std::future::{self, Future};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use tokio::sync::oneshot;
+use tracing::{debug, warn};
+
+use restate_core::network::{MessageRouterBuilder, NetworkSender};
+use restate_core::{cancellation_watcher, task_center, TaskKind};
+use restate_node_protocol::log_server::{
+    GetRecords, GetTailInfo, LogServerRequest, LogServerResponse, Records, Seal, Sealed, Status,
+    Store, Stored, TailInfo, Trim, Trimmed,
+};
+use restate_node_protocol::MessageEnvelope;
+use restate_types::logs::{SealReason, SequenceNumber};
+use restate_types::GenerationalNodeId;
+
+use crate::loglet::LogletOffset;
+use crate::loglets::local_loglet::keys::RecordKey;
+use crate::loglets::local_loglet::log_store::{LogStoreError, RocksDbLogStore};
+use crate::loglets::local_loglet::log_store_writer::{RocksDbLogWriterHandle, WriterOptions};
+use crate::{Error, ProviderError};
+
+use super::Options;
+
+/// The state of the copy of a loglet that is stored on this log server.
+#[derive(Debug, Clone)]
+struct ReplicaState {
+    /// The highest offset that has been durably stored. Lower offsets might be missing if stores
+    /// were lost, the sequencer only sends records once all previous records are committed.
+    local_tail: LogletOffset,
+    /// The highest offset that has been enqueued for storing. Records up to it are durable once
+    /// any later write to the log store is durable.
+    enqueued_tail: LogletOffset,
+    known_global_tail: LogletOffset,
+    trim_point: LogletOffset,
+    seal: Option<SealReason>,
+}
+
+/// Stores the records of replicated loglets on nodes with the log server role.
+///
+/// The copies of the loglets are stored in a rocksdb log store with the same layout as the local
+/// loglet, the loglet id is used as the log id.
+pub struct LogServer<N> {
+    opts: Options,
+    log_store: RocksDbLogStore,
+    networking: N,
+    requests: BoxStream<'static, MessageEnvelope<LogServerRequest>>,
+}
+
+impl<N> LogServer<N>
+where
+    N: NetworkSender + 'static,
+{
+    pub fn create(
+        opts: Options,
+        networking: N,
+        router_builder: &mut MessageRouterBuilder,
+    ) -> Result<Self, ProviderError> {
+        let log_store =
+            RocksDbLogStore::new(&opts.log_server).context("RocksDb LogStore of the log server")?;
+        let requests = router_builder.subscribe_to_stream(opts.log_server.writer_queue_len);
+
+        Ok(Self {
+            opts,
+            log_store,
+            networking,
+            requests,
+        })
+    }
+
+    /// Processes requests until the node shuts down. Must be called from a task center task.
+    pub async fn run(self) -> anyhow::Result<()> {
+        let LogServer {
+            opts,
+            log_store,
+            networking,
+            mut requests,
+        } = self;
+
+        let writer_options = WriterOptions {
+            channel_size: opts.log_server.writer_queue_len,
+            batch_size_threshold: opts.log_server.writer_commit_batch_size_threshold,
+            flush_wal_on_commit: opts.log_server.flush_wal_on_commit,
+            disable_wal: opts.log_server.rocksdb_disable_wal,
+        };
+        let log_writer = log_store.create_writer(writer_options).start()?;
+        let handler = RequestHandler {
+            log_store: log_store.clone(),
+            log_writer,
+            networking,
+            replicas: Default::default(),
+        };
+        debug!("Log server started");
+
+        loop {
+            tokio::select! {
+                biased;
+                _ = cancellation_watcher() => {
+                    break;
+                }
+                Some(envelope) = requests.next() => {
+                    let (peer, request) = envelope.split();
+                    if let Err(e) = handler.on_request(peer, request).await {
+                        warn!("Log server failed to process request from {}: {}", peer, e);
+                    }
+                }
+            }
+        }
+
+        log_store.shutdown();
+        debug!("Log server stopped");
+        Ok(())
+    }
+}
+
+struct RequestHandler<N> {
+    log_store: RocksDbLogStore,
+    log_writer: RocksDbLogWriterHandle,
+    networking: N,
+    // Locks are never held across await points.
+    replicas: Arc<Mutex<HashMap<u64, ReplicaState>>>,
+}
+
+impl<N> RequestHandler<N>
+where
+    N: NetworkSender + 'static,
+{
+    /// Enqueues the writes of the request in the order of arrival. Waiting for the writes to be
+    /// committed and sending the response happens in the background.
+    async fn on_request(
+        &self,
+        peer: GenerationalNodeId,
+        request: LogServerRequest,
+    ) -> Result<(), Error> {
+        match request {
+            LogServerRequest::Store(store) => self.on_store(peer, store).await,
+            LogServerRequest::Seal(seal) => self.on_seal(peer, seal).await,
+            LogServerRequest::GetTailInfo(get_tail_info) => {
+                self.on_get_tail_info(peer, get_tail_info)
+            }
+            LogServerRequest::GetRecords(get_records) => self.on_get_records(peer, get_records),
+            LogServerRequest::Trim(trim) => self.on_trim(peer, trim).await,
+        }
+    }
+
+    async fn on_store(&self, peer: GenerationalNodeId, store: Store) -> Result<(), Error> {
+        let first_offset = LogletOffset::from(store.first_offset);
+        let last_offset = LogletOffset::from(
+            (store.first_offset + store.payloads.len() as u64).saturating_sub(1),
+        );
+        {
+            let mut replicas = self.replicas.lock().unwrap();
+            let replica = self.replica_state(&mut replicas, store.loglet_id)?;
+            replica.known_global_tail = replica
+                .known_global_tail
+                .max(LogletOffset::from(store.known_global_tail));
+            let rejection = match replica.seal.as_ref() {
+                Some(reason) if !store.repair => Some(Status::Sealed(reason.clone())),
+                _ if first_offset == LogletOffset::INVALID || store.payloads.is_empty() => {
+                    Some(Status::Failed("invalid offset or empty batch".to_owned()))
+                }
+                _ => None,
+            };
+            if let Some(status) = rejection {
+                let response = Stored {
+                    request_id: store.request_id,
+                    status,
+                    local_tail: replica.local_tail.into(),
+                };
+                return self.respond(peer, future::ready(response.into()));
+            }
+            replica.enqueued_tail = replica.enqueued_tail.max(last_offset);
+        }
+
+        let receiver = self
+            .log_writer
+            .enqueue_put_records(store.loglet_id, first_offset, store.payloads, true)
+            .await?;
+        let replicas = Arc::clone(&self.replicas);
+        self.respond(peer, async move {
+            let status = ack_status(receiver.await);
+            // The local tail only covers durable records, otherwise readers and the sequencer
+            // could consider records committed which are lost on a crash.
+            let local_tail = match replicas.lock().unwrap().get_mut(&store.loglet_id) {
+                Some(replica) if status == Status::Ok => {
+                    replica.local_tail = replica.local_tail.max(last_offset);
+                    replica.local_tail
+                }
+                Some(replica) => replica.local_tail,
+                None => LogletOffset::INVALID,
+            };
+            Stored {
+                request_id: store.request_id,
+                status,
+                local_tail: local_tail.into(),
+            }
+            .into()
+        })
+    }
+
+    async fn on_seal(&self, peer: GenerationalNodeId, seal: Seal) -> Result<(), Error> {
+        let local_tail = {
+            let mut replicas = self.replicas.lock().unwrap();
+            let replica = self.replica_state(&mut replicas, seal.loglet_id)?;
+            if let Some(reason) = replica.seal.clone() {
+                let response = Sealed {
+                    request_id: seal.request_id,
+                    status: Status::Sealed(reason),
+                    local_tail: replica.local_tail.into(),
+                };
+                return self.respond(peer, future::ready(response.into()));
+            }
+            // Stores that arrive from now on are rejected.
+            replica.seal = Some(seal.reason.clone());
+            replica.enqueued_tail
+        };
+        debug!(
+            loglet_id = seal.loglet_id,
+            local_tail = %local_tail,
+            "Sealing loglet on log server, reason: {:?}",
+            seal.reason
+        );
+
+        // The seal is committed after all previously enqueued stores, once it's durable so are
+        // the records up to the enqueued tail.
+        let receiver = self
+            .log_writer
+            .enqueue_seal(seal.loglet_id, seal.reason)
+            .await?;
+        let replicas = Arc::clone(&self.replicas);
+        self.respond(peer, async move {
+            let status = ack_status(receiver.await);
+            let local_tail = match replicas.lock().unwrap().get_mut(&seal.loglet_id) {
+                Some(replica) if status == Status::Ok => {
+                    replica.local_tail = replica.local_tail.max(local_tail);
+                    replica.local_tail
+                }
+                Some(replica) => replica.local_tail,
+                None => LogletOffset::INVALID,
+            };
+            Sealed {
+                request_id: seal.request_id,
+                status,
+                local_tail: local_tail.into(),
+            }
+            .into()
+        })
+    }
+
+    fn on_get_tail_info(
+        &self,
+        peer: GenerationalNodeId,
+        get_tail_info: GetTailInfo,
+    ) -> Result<(), Error> {
+        let response = {
+            let mut replicas = self.replicas.lock().unwrap();
+            let replica = self.replica_state(&mut replicas, get_tail_info.loglet_id)?;
+            TailInfo {
+                request_id: get_tail_info.request_id,
+                local_tail: replica.local_tail.into(),
+                known_global_tail: replica.known_global_tail.into(),
+                trim_point: replica.trim_point.into(),
+                seal: replica.seal.clone(),
+            }
+        };
+        self.respond(peer, future::ready(response.into()))
+    }
+
+    fn on_get_records(
+        &self,
+        peer: GenerationalNodeId,
+        get_records: GetRecords,
+    ) -> Result<(), Error> {
+        let trim_point = {
+            let mut replicas = self.replicas.lock().unwrap();
+            self.replica_state(&mut replicas, get_records.loglet_id)?
+                .trim_point
+        };
+        let log_store = self.log_store.clone();
+        self.respond(peer, async move {
+            let from_offset = LogletOffset::from(get_records.from_offset).max(trim_point.next());
+            let (status, records) = match read_records(
+                &log_store,
+                get_records.loglet_id,
+                from_offset,
+                LogletOffset::from(get_records.to_offset),
+                get_records.max_records as usize,
+            ) {
+                Ok(records) => (Status::Ok, records),
+                Err(e) => (Status::Failed(e.to_string()), Vec::new()),
+            };
+            Records {
+                request_id: get_records.request_id,
+                status,
+                records,
+                trim_point: trim_point.into(),
+            }
+            .into()
+        })
+    }
+
+    async fn on_trim(&self, peer: GenerationalNodeId, trim: Trim) -> Result<(), Error> {
+        let trim_point = {
+            let mut replicas = self.replicas.lock().unwrap();
+            let replica = self.replica_state(&mut replicas, trim.loglet_id)?;
+            // Records that are not stored on this log server cannot be trimmed.
+            let trim_point = LogletOffset::from(trim.trim_point).min(replica.local_tail);
+            if trim_point <= replica.trim_point {
+                let response = Trimmed {
+                    request_id: trim.request_id,
+                    status: Status::Ok,
+                    trim_point: replica.trim_point.into(),
+                };
+                return self.respond(peer, future::ready(response.into()));
+            }
+            replica.trim_point = trim_point;
+            trim_point
+        };
+
+        let receiver = self
+            .log_writer
+            .enqueue_trim(trim.loglet_id, trim_point)
+            .await?;
+        self.respond(peer, async move {
+            Trimmed {
+                request_id: trim.request_id,
+                status: ack_status(receiver.await),
+                trim_point: trim_point.into(),
+            }
+            .into()
+        })
+    }
+
+    /// Returns the state of the loglet's copy, it's loaded from the log store on first access.
+    fn replica_state<'a>(
+        &self,
+        replicas: &'a mut HashMap<u64, ReplicaState>,
+        loglet_id: u64,
+    ) -> Result<&'a mut ReplicaState, LogStoreError> {
+        if let hash_map::Entry::Vacant(entry) = replicas.entry(loglet_id) {
+            let log_state = self.log_store.get_log_state(loglet_id)?.unwrap_or_default();
+            let local_tail = LogletOffset::from(log_state.release_pointer);
+            entry.insert(ReplicaState {
+                local_tail,
+                enqueued_tail: local_tail,
+                known_global_tail: LogletOffset::INVALID,
+                trim_point: LogletOffset::from(log_state.trim_point),
+                seal: log_state.seal,
+            });
+        }
+        Ok(replicas.get_mut(&loglet_id).expect("replica state exists"))
+    }
+
+    /// Sends the response once it's ready in the background.
+    fn respond(
+        &self,
+        peer: GenerationalNodeId,
+        response: impl Future<Output = LogServerResponse> + Send + 'static,
+    ) -> Result<(), Error> {
+        let networking = self.networking.clone();
+        task_center().spawn_child(
+            TaskKind::Disposable,
+            "log-server-response",
+            None,
+            async move {
+                let response = response.await;
+                networking.send(peer.into(), &response).await?;
+                Ok(())
+            },
+        )?;
+        Ok(())
+    }
+}
+
+fn ack_status(ack: Result<Result<(), Error>, oneshot::error::RecvError>) -> Status {
+    match ack {
+        Ok(Ok(())) => Status::Ok,
+        Ok(Err(e)) => Status::Failed(e.to_string()),
+        Err(_) => Status::Failed("log server is shutting down".to_owned()),
+    }
+}
+
+/// Reads the stored records in the inclusive range `from_offset..=to_offset`.
+fn read_records(
+    log_store: &RocksDbLogStore,
+    loglet_id: u64,
+    from_offset: LogletOffset,
+    to_offset: LogletOffset,
+    max_records: usize,
+) -> Result<Vec<(u64, Bytes)>, LogStoreError> {
+    let mut records = Vec::new();
+    if from_offset > to_offset {
+        return Ok(records);
+    }
+    let mut read_opts = rocksdb::ReadOptions::default();
+    read_opts.set_iterate_upper_bound(RecordKey::new(loglet_id, to_offset.next()).to_bytes());
+    let iter = log_store.db().iterator_cf_opt(
+        log_store.data_cf(),
+        read_opts,
+        rocksdb::IteratorMode::From(
+            &RecordKey::new(loglet_id, from_offset).to_bytes(),
+            rocksdb::Direction::Forward,
+        ),
+    );
+    for record in iter.take(max_records) {
+        let (key, data) = record?;
+        let key = RecordKey::from_slice(&key);
+        records.push((key.offset.into(), Bytes::from(data)));
+    }
+    Ok(records)
+}
@@ -0,0 +1,780 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A loglet that replicates its records to the log servers of a nodeset.
+//!
+//! Appends are sequenced by a single node, the sequencer, which assigns offsets and stores every
+//! record on all log servers of the nodeset. A record is committed once a majority of the nodeset
+//! stored it. Sealing a loglet seals a majority of its log servers and re-replicates the records
+//! which might not have reached a majority before (repair). Since any two majorities intersect,
+//! the tail of a sealed loglet contains every committed record.
+
+mod log_server;
+mod options;
+mod params;
+mod provider;
+mod rpc;
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::sync::{Mutex, MutexGuard};
+use tracing::{debug, warn};
+
+use restate_core::network::NetworkSender;
+use restate_node_protocol::log_server::{
+    GetRecords, GetTailInfo, LogServerResponse, Seal, Status, Store, TailInfo, Trim,
+};
+use restate_types::logs::{Payload, SequenceNumber};
+use restate_types::PlainNodeId;
+
+pub use log_server::LogServer;
+pub use options::Options;
+pub use params::ReplicatedLogletParams;
+pub use provider::ReplicatedLogletProvider;
+
+use crate::loglet::{LogletBase, LogletOffset};
+use crate::loglets::local_loglet::utils::OffsetWatch;
+use crate::{Error, LogRecord, SealReason};
+
+use self::rpc::LogServerClient;
+
+/// The maximum number of records that are re-replicated at once.
+const REPAIR_BATCH_SIZE: u64 = 100;
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ReplicatedLogletError {
+    #[error("appends must be sent to the sequencer {0}")]
+    NotSequencer(PlainNodeId),
+    #[error("invalid replicated loglet params: {0}")]
+    InvalidParams(String),
+    #[error("record at offset {0} is not available on any log server")]
+    RecordUnavailable(LogletOffset),
+}
+
+pub struct ReplicatedLoglet<N> {
+    params: ReplicatedLogletParams,
+    is_sequencer: bool,
+    client: Arc<LogServerClient<N>>,
+    read_poll_interval: Duration,
+    /// The next offset the sequencer writes to. It's `None` until the tail of the loglet has
+    /// been recovered from the log servers.
+    next_write_offset: Mutex<Option<LogletOffset>>,
+    /// The highest offset that is known to be committed.
+    committed_tail: AtomicU64,
+    seal: std::sync::Mutex<Option<SealReason>>,
+    release_watch: OffsetWatch,
+}
+
+impl<N> ReplicatedLoglet<N>
+where
+    N: NetworkSender + 'static,
+{
+    fn new(
+        params: ReplicatedLogletParams,
+        is_sequencer: bool,
+        client: Arc<LogServerClient<N>>,
+        read_poll_interval: Duration,
+    ) -> Self {
+        Self {
+            params,
+            is_sequencer,
+            client,
+            read_poll_interval,
+            next_write_offset: Mutex::new(None),
+            committed_tail: AtomicU64::new(LogletOffset::INVALID.into()),
+            seal: std::sync::Mutex::new(None),
+            release_watch: OffsetWatch::new(LogletOffset::INVALID),
+        }
+    }
+
+    fn committed_tail(&self) -> LogletOffset {
+        LogletOffset(self.committed_tail.load(Ordering::Relaxed))
+    }
+
+    fn sealed(&self) -> Option<SealReason> {
+        self.seal.lock().unwrap().clone()
+    }
+
+    fn release(&self, tail: LogletOffset) {
+        self.committed_tail
+            .fetch_max(tail.into(), Ordering::Relaxed);
+        self.release_watch.notify(self.committed_tail());
+    }
+
+    fn set_sealed(&self, reason: SealReason) {
+        self.seal.lock().unwrap().get_or_insert(reason);
+        self.release_watch.seal();
+    }
+
+    /// Returns the next offset the sequencer writes to, the tail is recovered on first use.
+    async fn next_write_offset(
+        &self,
+        guard: &mut MutexGuard<'_, Option<LogletOffset>>,
+    ) -> Result<LogletOffset, Error> {
+        match **guard {
+            Some(next_offset) => Ok(next_offset),
+            None => {
+                let next_offset = self.recover().await?.next();
+                **guard = Some(next_offset);
+                Ok(next_offset)
+            }
+        }
+    }
+
+    async fn tail_infos(&self) -> Result<Vec<TailInfo>, Error> {
+        let loglet_id = self.params.loglet_id;
+        let infos = self
+            .client
+            .quorum_call(
+                &self.params.nodeset,
+                self.params.write_quorum(),
+                &|request_id| {
+                    GetTailInfo {
+                        request_id,
+                        loglet_id,
+                    }
+                    .into()
+                },
+                |_, response| match response {
+                    LogServerResponse::TailInfo(info) => Ok(Some(info)),
+                    _ => Ok(None),
+                },
+            )
+            .await?;
+        Ok(infos.into_iter().map(|(_, info)| info).collect())
+    }
+
+    /// Recovers the tail of the loglet from a majority of the log servers and re-replicates the
+    /// records that might not be committed yet. Returns the recovered tail.
+    async fn recover(&self) -> Result<LogletOffset, Error> {
+        let infos = self.tail_infos().await?;
+        let tail = infos
+            .iter()
+            .map(|info| LogletOffset(info.local_tail))
+            .max()
+            .unwrap_or(LogletOffset::INVALID);
+        // Records up to the known global tail and the trim point are committed already.
+        let committed = infos
+            .iter()
+            .map(|info| LogletOffset(info.known_global_tail.max(info.trim_point)))
+            .max()
+            .unwrap_or(LogletOffset::INVALID)
+            .max(self.committed_tail())
+            .min(tail);
+        debug!(
+            loglet_id = self.params.loglet_id,
+            committed = %committed,
+            tail = %tail,
+            "Recovering replicated loglet"
+        );
+
+        self.repair(committed, tail).await?;
+        self.release(tail);
+        if let Some(reason) = infos.into_iter().find_map(|info| info.seal) {
+            self.set_sealed(reason);
+        }
+        Ok(tail)
+    }
+
+    /// Stores the records in `(committed, tail]` on a majority of the log servers.
+    async fn repair(&self, committed: LogletOffset, tail: LogletOffset) -> Result<(), Error> {
+        let mut from_offset = committed.next();
+        while from_offset <= tail {
+            let to_offset = tail.min(LogletOffset(from_offset.0 + REPAIR_BATCH_SIZE - 1));
+            let payloads = self.fetch_records(from_offset, to_offset).await?;
+            self.store(from_offset, payloads, true).await?;
+            from_offset = to_offset.next();
+        }
+        Ok(())
+    }
+
+    /// Fetches the records in `from_offset..=to_offset` from the log servers of the nodeset.
+    async fn fetch_records(
+        &self,
+        from_offset: LogletOffset,
+        to_offset: LogletOffset,
+    ) -> Result<Vec<Bytes>, Error> {
+        let expected = (to_offset.0 - from_offset.0 + 1) as usize;
+        let mut records = BTreeMap::new();
+        for node in &self.params.nodeset {
+            let request = GetRecords {
+                request_id: self.client.next_request_id(),
+                loglet_id: self.params.loglet_id,
+                from_offset: from_offset.into(),
+                to_offset: to_offset.into(),
+                max_records: expected as u32,
+            };
+            match self.client.call(*node, request.into()).await {
+                Ok(LogServerResponse::Records(response)) if response.status == Status::Ok => {
+                    records.extend(response.records);
+                }
+                Ok(response) => {
+                    debug!(
+                        "Log server {} failed to return records: {:?}",
+                        node, response
+                    );
+                }
+                Err(e) => {
+                    debug!("Failed fetching records from log server {}: {}", node, e);
+                }
+            }
+            if records.len() >= expected {
+                break;
+            }
+        }
+
+        let mut offset = from_offset;
+        let mut payloads = Vec::with_capacity(expected);
+        while offset <= to_offset {
+            let Some(payload) = records.remove(&offset.0) else {
+                return Err(ReplicatedLogletError::RecordUnavailable(offset).into());
+            };
+            payloads.push(payload);
+            offset = offset.next();
+        }
+        Ok(payloads)
+    }
+
+    /// Stores the records on a majority of the log servers.
+    async fn store(
+        &self,
+        first_offset: LogletOffset,
+        payloads: Vec<Bytes>,
+        repair: bool,
+    ) -> Result<(), Error> {
+        let loglet_id = self.params.loglet_id;
+        let known_global_tail = self.committed_tail().into();
+        self.client
+            .quorum_call(
+                &self.params.nodeset,
+                self.params.write_quorum(),
+                &|request_id| {
+                    Store {
+                        request_id,
+                        loglet_id,
+                        first_offset: first_offset.into(),
+                        payloads: payloads.clone(),
+                        known_global_tail,
+                        repair,
+                    }
+                    .into()
+                },
+                |node, response| match response {
+                    LogServerResponse::Stored(stored) => match stored.status {
+                        Status::Ok => Ok(Some(())),
+                        Status::Sealed(reason) => Err(Error::LogletSealed(reason)),
+                        Status::Failed(e) => {
+                            warn!("Log server {} failed to store records: {}", node, e);
+                            Ok(None)
+                        }
+                    },
+                    _ => Ok(None),
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Refreshes the committed tail and the seal. The sequencer knows the committed tail,
+    /// other nodes learn it from the log servers.
+    async fn refresh_tail(&self) -> Result<(), Error> {
+        if self.sealed().is_some() {
+            // The tail of a sealed loglet doesn't change anymore.
+            return Ok(());
+        }
+        if self.is_sequencer {
+            let mut guard = self.next_write_offset.lock().await;
+            self.next_write_offset(&mut guard).await?;
+            return Ok(());
+        }
+
+        let infos = self.tail_infos().await?;
+        match infos.iter().find_map(|info| info.seal.clone()) {
+            Some(reason) => {
+                // Sealing re-replicates all records up to the highest local tail of a majority.
+                let tail = infos.iter().map(|info| info.local_tail).max();
+                self.release(LogletOffset(tail.unwrap_or_default()));
+                self.set_sealed(reason);
+            }
+            None => {
+                // Log servers only learn about commits with the next store. But records up to the
+                // lowest local tail of a write quorum are stored on a majority, batches are
+                // stored atomically and are only sent once all previous records are committed.
+                let known_global_tail = infos.iter().map(|info| info.known_global_tail).max();
+                let quorum_tail = infos.iter().map(|info| info.local_tail).min();
+                let tail = known_global_tail.max(quorum_tail);
+                self.release(LogletOffset(tail.unwrap_or_default()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the committed record at `offset` from a quorum of the log servers.
+    ///
+    /// A log server might still store a record of a failed append at an offset that has been
+    /// committed with another record later. The committed record is stored on a majority of the
+    /// nodeset, hence any quorum of log servers contains at least one copy of it. A copy is only
+    /// returned once a quorum responded and all returned copies agree, or once a quorum stores
+    /// the same copy.
+    async fn read_record(&self, offset: LogletOffset) -> Result<LogRecord<LogletOffset>, Error> {
+        let quorum = self.params.write_quorum();
+        let mut responded = 0;
+        let mut copies: Vec<(Bytes, usize)> = Vec::new();
+        for node in &self.params.nodeset {
+            let request = GetRecords {
+                request_id: self.client.next_request_id(),
+                loglet_id: self.params.loglet_id,
+                from_offset: offset.into(),
+                to_offset: offset.into(),
+                max_records: 1,
+            };
+            match self.client.call(*node, request.into()).await {
+                Ok(LogServerResponse::Records(response)) if response.status == Status::Ok => {
+                    // Only committed records are trimmed.
+                    if offset.0 <= response.trim_point {
+                        return Ok(LogRecord::new_trim_gap(
+                            offset,
+                            LogletOffset(response.trim_point),
+                        ));
+                    }
+                    responded += 1;
+                    if let Some((_, data)) = response.records.into_iter().next() {
+                        match copies.iter_mut().find(|(copy, _)| *copy == data) {
+                            Some((_, count)) => *count += 1,
+                            None => copies.push((data, 1)),
+                        }
+                    }
+                }
+                Ok(response) => {
+                    debug!(
+                        "Log server {} failed to return records: {:?}",
+                        node, response
+                    );
+                }
+                Err(e) => {
+                    debug!("Failed reading from log server {}: {}", node, e);
+                }
+            }
+
+            let agreed = if responded >= quorum && copies.len() == 1 {
+                copies.pop()
+            } else {
+                copies
+                    .iter()
+                    .position(|(_, count)| *count >= quorum)
+                    .map(|idx| copies.swap_remove(idx))
+            };
+            if let Some((data, _)) = agreed {
+                return Ok(LogRecord::new_data(offset, Payload::from(data)));
+            }
+        }
+        if copies.len() > 1 {
+            warn!(
+                loglet_id = self.params.loglet_id,
+                "Log servers store {} different records at offset {}",
+                copies.len(),
+                offset
+            );
+        }
+        Err(ReplicatedLogletError::RecordUnavailable(offset).into())
+    }
+}
+
+#[async_trait]
+impl<N> LogletBase for ReplicatedLoglet<N>
+where
+    N: NetworkSender + 'static,
+{
+    type Offset = LogletOffset;
+
+    async fn append(&self, payload: Payload) -> Result<LogletOffset, Error> {
+        let offsets = self.append_batch(std::slice::from_ref(&payload)).await?;
+        Ok(offsets.start)
+    }
+
+    async fn append_batch(&self, payloads: &[Payload]) -> Result<Range<LogletOffset>, Error> {
+        if !self.is_sequencer {
+            return Err(ReplicatedLogletError::NotSequencer(self.params.sequencer).into());
+        }
+        // Appends are serialized, records are only sent once all previous records have been
+        // committed. If the sequencer fails, only the records of the last batch might be missing
+        // on a majority of the log servers.
+        let mut guard = self.next_write_offset.lock().await;
+        let first_offset = self.next_write_offset(&mut guard).await?;
+        if let Some(reason) = self.sealed() {
+            return Err(Error::LogletSealed(reason));
+        }
+        let end_offset = LogletOffset(first_offset.0 + payloads.len() as u64);
+        if payloads.is_empty() {
+            return Ok(first_offset..end_offset);
+        }
+
+        let payloads = payloads
+            .iter()
+            .map(|payload| payload.clone().into())
+            .collect();
+        if let Err(e) = self.store(first_offset, payloads, false).await {
+            if let Error::LogletSealed(reason) = &e {
+                self.set_sealed(reason.clone());
+            }
+            return Err(e);
+        }
+
+        *guard = Some(end_offset);
+        self.release(LogletOffset(end_offset.0 - 1));
+        Ok(first_offset..end_offset)
+    }
+
+    async fn find_tail(&self) -> Result<Option<LogletOffset>, Error> {
+        self.refresh_tail().await?;
+        let tail = self.committed_tail();
+        if tail == LogletOffset::INVALID {
+            Ok(None)
+        } else {
+            Ok(Some(tail))
+        }
+    }
+
+    async fn get_trim_point(&self) -> Result<LogletOffset, Error> {
+        let trim_point = self
+            .tail_infos()
+            .await?
+            .into_iter()
+            .map(|info| info.trim_point)
+            .max();
+        Ok(LogletOffset(trim_point.unwrap_or_default()))
+    }
+
+    async fn trim(&self, trim_point: LogletOffset) -> Result<(), Error> {
+        // We cannot trim beyond the committed tail.
+        self.refresh_tail().await?;
+        let trim_point = trim_point.min(self.committed_tail());
+        if trim_point == LogletOffset::INVALID {
+            return Ok(());
+        }
+
+        let loglet_id = self.params.loglet_id;
+        self.client
+            .quorum_call(
+                &self.params.nodeset,
+                self.params.write_quorum(),
+                &|request_id| {
+                    Trim {
+                        request_id,
+                        loglet_id,
+                        trim_point: trim_point.into(),
+                    }
+                    .into()
+                },
+                |node, response| match response {
+                    LogServerResponse::Trimmed(trimmed) => match trimmed.status {
+                        Status::Failed(e) => {
+                            warn!("Log server {} failed to trim: {}", node, e);
+                            Ok(None)
+                        }
+                        _ => Ok(Some(())),
+                    },
+                    _ => Ok(None),
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn seal(&self, reason: SealReason) -> Result<LogletOffset, Error> {
+        // Holding the write lock prevents concurrent appends of this sequencer.
+        let mut guard = self.next_write_offset.lock().await;
+        if self.sealed().is_some() {
+            return Ok(self.committed_tail());
+        }
+
+        let loglet_id = self.params.loglet_id;
+        self.client
+            .quorum_call(
+                &self.params.nodeset,
+                self.params.write_quorum(),
+                &|request_id| {
+                    Seal {
+                        request_id,
+                        loglet_id,
+                        reason: reason.clone(),
+                    }
+                    .into()
+                },
+                |node, response| match response {
+                    LogServerResponse::Sealed(sealed) => match sealed.status {
+                        Status::Failed(e) => {
+                            warn!("Log server {} failed to seal: {}", node, e);
+                            Ok(None)
+                        }
+                        // Already sealed log servers keep the original seal reason
+                        _ => Ok(Some(())),
+                    },
+                    _ => Ok(None),
+                },
+            )
+            .await?;
+
+        // A majority of the log servers is sealed, no more records can be committed. The
+        // recovery sees every committed record and re-replicates the uncommitted ones.
+        let tail = self.recover().await?;
+        *guard = Some(tail.next());
+        Ok(tail)
+    }
+
+    async fn read_next_single(
+        &self,
+        after: LogletOffset,
+    ) -> Result<LogRecord<LogletOffset>, Error> {
+        loop {
+            match self.read_next_single_opt(after).await {
+                Ok(Some(record)) => return Ok(record),
+                Ok(None) if self.is_sequencer => {
+                    self.release_watch.wait_for(after.next()).await?;
+                }
+                Ok(None) => tokio::time::sleep(self.read_poll_interval).await,
+                Err(Error::ReplicatedLoglet(ReplicatedLogletError::RecordUnavailable(offset))) => {
+                    debug!(
+                        loglet_id = self.params.loglet_id,
+                        "Record at offset {} is not available, retrying", offset
+                    );
+                    tokio::time::sleep(self.read_poll_interval).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    async fn read_next_single_opt(
+        &self,
+        after: LogletOffset,
+    ) -> Result<Option<LogRecord<LogletOffset>>, Error> {
+        let from_offset = after.next();
+        if from_offset > self.committed_tail() {
+            self.refresh_tail().await?;
+        }
+        if from_offset > self.committed_tail() {
+            // The seal is only visible after all records before it have been committed.
+            return Ok(self
+                .sealed()
+                .map(|reason| LogRecord::new_seal(from_offset, reason)));
+        }
+        self.read_record(from_offset).await.map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::TempDir;
+
+    use restate_core::network::MessageRouterBuilder;
+    use restate_core::{task_center, MockClusterNetwork, MockClusterSender, TaskKind, TestCoreEnv};
+    use restate_test_util::let_assert;
+    use restate_types::GenerationalNodeId;
+
+    use crate::Record;
+
+    struct TestCluster {
+        network: MockClusterNetwork,
+        nodeset: Vec<PlainNodeId>,
+        // Keeps the storage of the log servers alive.
+        _base_dir: TempDir,
+    }
+
+    impl TestCluster {
+        /// Starts a log server on every node of the nodeset.
+        fn start(nodeset_size: u32) -> anyhow::Result<Self> {
+            let base_dir = tempfile::tempdir()?;
+            let network = MockClusterNetwork::default();
+            let mut nodeset = Vec::new();
+            for i in 1..=nodeset_size {
+                let node_id = GenerationalNodeId::new(10 + i, 1);
+                let mut opts = Options::default();
+                opts.log_server.path = base_dir.path().join(format!("log-server-{}", i));
+                let mut router_builder = MessageRouterBuilder::default();
+                let log_server =
+                    LogServer::create(opts, network.sender_for(node_id), &mut router_builder)?;
+                network.add_node(&task_center(), node_id, router_builder.build())?;
+                task_center().spawn(TaskKind::LogServer, "log-server", None, log_server.run())?;
+                nodeset.push(node_id.as_plain());
+            }
+            Ok(Self {
+                network,
+                nodeset,
+                _base_dir: base_dir,
+            })
+        }
+
+        /// Creates the loglet on `node_id`, the loglet of N1 sequences the appends.
+        fn loglet(
+            &self,
+            node_id: GenerationalNodeId,
+        ) -> anyhow::Result<ReplicatedLoglet<MockClusterSender>> {
+            let sequencer = GenerationalNodeId::new(1, 1);
+            let mut router_builder = MessageRouterBuilder::default();
+            let client = LogServerClient::new(
+                self.network.sender_for(node_id),
+                Duration::from_millis(500),
+                &mut router_builder,
+            );
+            self.network
+                .add_node(&task_center(), node_id, router_builder.build())?;
+            let params = ReplicatedLogletParams::new(1, sequencer.as_plain(), self.nodeset.clone());
+            Ok(ReplicatedLoglet::new(
+                params,
+                node_id == sequencer,
+                Arc::new(client),
+                Duration::from_millis(10),
+            ))
+        }
+    }
+
+    fn assert_data(record: LogRecord<LogletOffset>, offset: u64, data: &'static str) {
+        assert_eq!(LogletOffset(offset), record.offset);
+        let_assert!(Record::Data(payload) = record.record);
+        assert_eq!(Payload::from(data), payload);
+    }
+
+    #[tokio::test]
+    async fn test_replicated_loglet_append_read_seal() -> anyhow::Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let cluster = TestCluster::start(3)?;
+            let loglet = cluster.loglet(GenerationalNodeId::new(1, 1))?;
+
+            assert_eq!(None, loglet.find_tail().await?);
+            assert_eq!(LogletOffset(1), loglet.append(Payload::from("a")).await?);
+            let offsets = loglet
+                .append_batch(&[Payload::from("b"), Payload::from("c")])
+                .await?;
+            assert_eq!(LogletOffset(2)..LogletOffset(4), offsets);
+            assert_eq!(Some(LogletOffset(3)), loglet.find_tail().await?);
+
+            assert_data(
+                loglet.read_next_single(LogletOffset::INVALID).await?,
+                1,
+                "a",
+            );
+            assert_data(loglet.read_next_single(LogletOffset(2)).await?, 3, "c");
+            assert!(loglet
+                .read_next_single_opt(LogletOffset(3))
+                .await?
+                .is_none());
+
+            // appends are only accepted by the sequencer
+            let reader = cluster.loglet(GenerationalNodeId::new(2, 1))?;
+            assert!(matches!(
+                reader.append(Payload::from("x")).await,
+                Err(Error::ReplicatedLoglet(
+                    ReplicatedLogletError::NotSequencer(sequencer)
+                )) if sequencer == PlainNodeId::from(1)
+            ));
+            assert_data(reader.read_next_single(LogletOffset(1)).await?, 2, "b");
+
+            assert_eq!(LogletOffset(3), loglet.seal(SealReason::Resharding).await?);
+            // sealing again keeps the tail and the original reason
+            assert_eq!(
+                LogletOffset(3),
+                reader.seal(SealReason::Reconfiguration).await?
+            );
+            assert!(matches!(
+                loglet.append(Payload::from("d")).await,
+                Err(Error::LogletSealed(SealReason::Resharding))
+            ));
+
+            let_assert!(Some(record) = reader.read_next_single_opt(LogletOffset(3)).await?);
+            assert_eq!(LogletOffset(4), record.offset);
+            assert!(matches!(
+                record.record,
+                Record::Seal(SealReason::Resharding)
+            ));
+            assert_eq!(Some(LogletOffset(3)), reader.find_tail().await?);
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_replicated_loglet_tolerates_minority_failure() -> anyhow::Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let cluster = TestCluster::start(3)?;
+            let loglet = cluster.loglet(GenerationalNodeId::new(1, 1))?;
+            assert_eq!(LogletOffset(1), loglet.append(Payload::from("a")).await?);
+
+            cluster
+                .network
+                .remove_node(&task_center(), cluster.nodeset[0]);
+            assert_eq!(LogletOffset(2), loglet.append(Payload::from("b")).await?);
+
+            // a new sequencer recovers the tail from the remaining log servers
+            let recovered = cluster.loglet(GenerationalNodeId::new(1, 1))?;
+            assert_eq!(Some(LogletOffset(2)), recovered.find_tail().await?);
+            assert_eq!(LogletOffset(3), recovered.append(Payload::from("c")).await?);
+            assert_data(recovered.read_next_single(LogletOffset(1)).await?, 2, "b");
+
+            // without a majority, appends cannot be committed
+            cluster
+                .network
+                .remove_node(&task_center(), cluster.nodeset[1]);
+            let append = recovered.append(Payload::from("d"));
+            assert!(tokio::time::timeout(Duration::from_millis(200), append)
+                .await
+                .is_err());
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_replicated_loglet_reads_committed_copy() -> anyhow::Result<()> {
+        let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
+        let tc = node_env.tc;
+        tc.run_in_scope("test", None, async {
+            let cluster = TestCluster::start(3)?;
+            let loglet = cluster.loglet(GenerationalNodeId::new(1, 1))?;
+            assert_eq!(LogletOffset(1), loglet.append(Payload::from("a")).await?);
+
+            // the first log server keeps a diverging copy, e.g. of a lost append
+            let store = Store {
+                request_id: loglet.client.next_request_id(),
+                loglet_id: 1,
+                first_offset: 1,
+                payloads: vec![Bytes::from_static(b"x")],
+                known_global_tail: 0,
+                repair: true,
+            };
+            let_assert!(
+                Ok(LogServerResponse::Stored(stored)) =
+                    loglet.client.call(cluster.nodeset[0], store.into()).await
+            );
+            assert_eq!(Status::Ok, stored.status);
+
+            let reader = cluster.loglet(GenerationalNodeId::new(2, 1))?;
+            assert_data(
+                reader.read_next_single(LogletOffset::INVALID).await?,
+                1,
+                "a",
+            );
+            Ok(())
+        })
+        .await
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::path::Path;
+use std::time::Duration;
+
+use restate_types::{PlainNodeId, DEFAULT_STORAGE_DIRECTORY};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+use crate::loglets::local_loglet;
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize, derive_builder::Builder)]
+#[cfg_attr(feature = "options_schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "options_schema",
+    schemars(rename = "ReplicatedLoglet", default)
+)]
+#[builder(default)]
+pub struct Options {
+    /// # Nodeset
+    ///
+    /// The log servers that store the records of the replicated loglets which are created from
+    /// the static log configuration. A record is committed once it has been stored on a majority
+    /// of the nodeset.
+    #[cfg_attr(feature = "options_schema", schemars(with = "Vec<u32>"))]
+    pub nodeset: Vec<PlainNodeId>,
+    /// # Sequencer
+    ///
+    /// The node that sequences the appends to the replicated loglets which are created from the
+    /// static log configuration. Must be set if replicated loglets are the default provider.
+    #[cfg_attr(feature = "options_schema", schemars(with = "Option<u32>"))]
+    pub sequencer: Option<PlainNodeId>,
+    /// # Request timeout
+    ///
+    /// Time to wait for the response of a log server before the request is retried.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    pub request_timeout: humantime::Duration,
+    /// # Read poll interval
+    ///
+    /// Readers on nodes other than the sequencer poll the log servers with this interval while
+    /// waiting for new records.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    pub read_poll_interval: humantime::Duration,
+    /// # Log server
+    ///
+    /// Storage of the log server which is running on nodes with the `log_server` role.
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    pub log_server: local_loglet::Options,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            nodeset: Vec::new(),
+            sequencer: None,
+            request_timeout: Duration::from_secs(2).into(),
+            read_poll_interval: Duration::from_millis(50).into(),
+            log_server: local_loglet::Options {
+                path: Path::new(DEFAULT_STORAGE_DIRECTORY).join("log_server"),
+                ..Default::default()
+            },
+        }
+    }
+}
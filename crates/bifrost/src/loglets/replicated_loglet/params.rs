@@ -0,0 +1,51 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use restate_types::PlainNodeId;
+use serde::{Deserialize, Serialize};
+
+use crate::LogletParams;
+
+/// Configuration of a replicated loglet. It's stored as JSON in the [`LogletParams`] of the
+/// segment.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ReplicatedLogletParams {
+    /// Identifies the loglet on the log servers, it must be unique across all replicated
+    /// loglets that share a log server.
+    pub loglet_id: u64,
+    /// The node that sequences appends. Appends on other nodes are rejected.
+    pub sequencer: PlainNodeId,
+    /// The log servers that store a copy of every record.
+    pub nodeset: Vec<PlainNodeId>,
+}
+
+impl ReplicatedLogletParams {
+    pub fn new(loglet_id: u64, sequencer: PlainNodeId, nodeset: Vec<PlainNodeId>) -> Self {
+        Self {
+            loglet_id,
+            sequencer,
+            nodeset,
+        }
+    }
+
+    /// The number of log servers that must store a record before it's committed. Any two
+    /// quorums of the nodeset intersect.
+    pub fn write_quorum(&self) -> usize {
+        self.nodeset.len() / 2 + 1
+    }
+
+    pub fn from_loglet_params(params: &LogletParams) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(params.id())
+    }
+
+    pub fn to_loglet_params(&self) -> LogletParams {
+        LogletParams::from(serde_json::to_string(self).expect("params can be serialized"))
+    }
+}
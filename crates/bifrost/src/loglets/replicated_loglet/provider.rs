@@ -0,0 +1,90 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::{hash_map, HashMap};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::Mutex as AsyncMutex;
+use tracing::debug;
+
+use restate_core::metadata;
+use restate_core::network::{MessageRouterBuilder, NetworkSender};
+
+use super::rpc::LogServerClient;
+use super::{Options, ReplicatedLoglet, ReplicatedLogletError, ReplicatedLogletParams};
+use crate::loglet::{Loglet, LogletOffset, LogletProvider};
+use crate::ProviderError;
+use crate::{Error, LogletParams};
+
+pub struct ReplicatedLogletProvider<N> {
+    client: Arc<LogServerClient<N>>,
+    active_loglets: AsyncMutex<HashMap<u64, Arc<ReplicatedLoglet<N>>>>,
+    opts: Options,
+}
+
+impl<N> ReplicatedLogletProvider<N>
+where
+    N: NetworkSender + 'static,
+{
+    /// Creates the provider. Responses of log servers are received through the message router
+    /// that is built from `router_builder`.
+    pub fn new(
+        opts: Options,
+        networking: N,
+        router_builder: &mut MessageRouterBuilder,
+    ) -> Arc<Self> {
+        let client = LogServerClient::new(networking, opts.request_timeout.into(), router_builder);
+        Arc::new(Self {
+            client: Arc::new(client),
+            active_loglets: Default::default(),
+            opts,
+        })
+    }
+}
+
+#[async_trait]
+impl<N> LogletProvider for ReplicatedLogletProvider<N>
+where
+    N: NetworkSender + 'static,
+{
+    async fn get_loglet(
+        &self,
+        params: &LogletParams,
+    ) -> Result<Arc<dyn Loglet<Offset = LogletOffset>>, Error> {
+        let params = ReplicatedLogletParams::from_loglet_params(params)
+            .map_err(|e| ReplicatedLogletError::InvalidParams(e.to_string()))?;
+        if params.nodeset.is_empty() {
+            return Err(ReplicatedLogletError::InvalidParams("empty nodeset".to_owned()).into());
+        }
+
+        let mut guard = self.active_loglets.lock().await;
+        let loglet = match guard.entry(params.loglet_id) {
+            hash_map::Entry::Vacant(entry) => {
+                let is_sequencer = metadata().my_node_id().as_plain() == params.sequencer;
+                let loglet = ReplicatedLoglet::new(
+                    params,
+                    is_sequencer,
+                    Arc::clone(&self.client),
+                    self.opts.read_poll_interval.into(),
+                );
+                Arc::clone(entry.insert(Arc::new(loglet)))
+            }
+            hash_map::Entry::Occupied(entry) => entry.get().clone(),
+        };
+
+        Ok(loglet as Arc<dyn Loglet>)
+    }
+
+    fn start(&self) -> Result<(), ProviderError> {
+        debug!("Started a bifrost replicated loglet provider");
+        Ok(())
+    }
+}
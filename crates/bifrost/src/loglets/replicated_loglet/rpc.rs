@@ -0,0 +1,209 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use tokio::sync::oneshot;
+use tracing::{debug, trace};
+
+use restate_core::network::{
+    MessageHandler, MessageRouterBuilder, NetworkSendError, NetworkSender,
+};
+use restate_node_protocol::log_server::{LogServerRequest, LogServerResponse};
+use restate_node_protocol::MessageEnvelope;
+use restate_types::PlainNodeId;
+
+use crate::Error;
+
+type InFlightRequests = Arc<Mutex<HashMap<u64, oneshot::Sender<LogServerResponse>>>>;
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum RpcError {
+    #[error(transparent)]
+    Network(#[from] NetworkSendError),
+    #[error("no response within {0:?}")]
+    Timeout(Duration),
+}
+
+/// Sends requests to log servers and correlates their responses by request id.
+pub(crate) struct LogServerClient<N> {
+    networking: N,
+    next_request_id: AtomicU64,
+    in_flight: InFlightRequests,
+    request_timeout: Duration,
+}
+
+impl<N> LogServerClient<N>
+where
+    N: NetworkSender + 'static,
+{
+    /// Creates the client and registers the handler for log server responses in the router.
+    pub fn new(
+        networking: N,
+        request_timeout: Duration,
+        router_builder: &mut MessageRouterBuilder,
+    ) -> Self {
+        let in_flight = InFlightRequests::default();
+        router_builder.add_message_handler(ResponseHandler {
+            in_flight: Arc::clone(&in_flight),
+        });
+        Self {
+            networking,
+            next_request_id: AtomicU64::new(1),
+            in_flight,
+            request_timeout,
+        }
+    }
+
+    pub fn next_request_id(&self) -> u64 {
+        self.next_request_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Sends the request to the log server and waits for its response.
+    pub async fn call(
+        &self,
+        to: PlainNodeId,
+        request: LogServerRequest,
+    ) -> Result<LogServerResponse, RpcError> {
+        let request_id = request.request_id();
+        let (sender, receiver) = oneshot::channel();
+        self.in_flight.lock().unwrap().insert(request_id, sender);
+        // Makes sure that the request is forgotten if the response doesn't arrive or the caller
+        // gives up on it.
+        let _guard = InFlightGuard {
+            in_flight: &self.in_flight,
+            request_id,
+        };
+
+        self.networking.send(to.into(), &request).await?;
+        match tokio::time::timeout(self.request_timeout, receiver).await {
+            Ok(Ok(response)) => Ok(response),
+            // The sender is only dropped together with the in-flight entry.
+            Ok(Err(_)) | Err(_) => Err(RpcError::Timeout(self.request_timeout)),
+        }
+    }
+
+    /// Sends the request to the log server until it responds, optionally after an initial
+    /// delay. Failed and timed out requests are retried with a new request id.
+    async fn call_until_response(
+        &self,
+        to: PlainNodeId,
+        make_request: &(dyn Fn(u64) -> LogServerRequest + Sync),
+        delay: Option<Duration>,
+    ) -> (PlainNodeId, LogServerResponse) {
+        if let Some(delay) = delay {
+            tokio::time::sleep(delay).await;
+        }
+        loop {
+            let request = make_request(self.next_request_id());
+            match self.call(to, request).await {
+                Ok(response) => return (to, response),
+                Err(RpcError::Timeout(timeout)) => {
+                    debug!(
+                        "Log server {} did not respond within {:?}, retrying",
+                        to, timeout
+                    );
+                }
+                Err(RpcError::Network(e)) => {
+                    debug!(
+                        "Failed sending request to log server {}, retrying: {}",
+                        to, e
+                    );
+                    tokio::time::sleep(self.request_timeout).await;
+                }
+            }
+        }
+    }
+
+    /// Sends a request to every node of the nodeset and waits until `quorum` nodes accepted it.
+    ///
+    /// `on_response` decides about every response: `Ok(Some(_))` counts towards the quorum,
+    /// `Ok(None)` sends the request to the node again and an error aborts the operation.
+    pub async fn quorum_call<T>(
+        &self,
+        nodeset: &[PlainNodeId],
+        quorum: usize,
+        make_request: &(dyn Fn(u64) -> LogServerRequest + Sync),
+        mut on_response: impl FnMut(PlainNodeId, LogServerResponse) -> Result<Option<T>, Error>,
+    ) -> Result<Vec<(PlainNodeId, T)>, Error> {
+        let mut pending: FuturesUnordered<_> = nodeset
+            .iter()
+            .map(|node| self.call_until_response(*node, make_request, None))
+            .collect();
+        let mut accepted = Vec::with_capacity(nodeset.len());
+
+        while let Some((node, response)) = pending.next().await {
+            trace!("Log server {} responded: {:?}", node, response);
+            match on_response(node, response)? {
+                Some(result) => {
+                    accepted.push((node, result));
+                    if accepted.len() >= quorum {
+                        // Responses of the remaining nodes are ignored.
+                        return Ok(accepted);
+                    }
+                }
+                None => {
+                    pending.push(self.call_until_response(
+                        node,
+                        make_request,
+                        Some(self.request_timeout),
+                    ));
+                }
+            }
+        }
+        unreachable!("requests are retried until a quorum accepted them");
+    }
+}
+
+struct InFlightGuard<'a> {
+    in_flight: &'a InFlightRequests,
+    request_id: u64,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.in_flight.lock().unwrap().remove(&self.request_id);
+    }
+}
+
+/// Routes the responses of log servers to the waiting requests.
+struct ResponseHandler {
+    in_flight: InFlightRequests,
+}
+
+impl MessageHandler for ResponseHandler {
+    type MessageType = LogServerResponse;
+
+    async fn on_message(&self, envelope: MessageEnvelope<LogServerResponse>) {
+        let (peer, response) = envelope.split();
+        let sender = self
+            .in_flight
+            .lock()
+            .unwrap()
+            .remove(&response.request_id());
+        match sender {
+            Some(sender) => {
+                let _ = sender.send(response);
+            }
+            None => {
+                trace!(
+                    "Dropping response of log server {} to unknown request {}",
+                    peer,
+                    response.request_id()
+                );
+            }
+        }
+    }
+}
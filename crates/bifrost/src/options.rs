@@ -8,8 +8,12 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use crate::loglet::ProviderKind;
+use restate_core::MetadataWriter;
+use restate_types::logs::metadata::ProviderKind;
+
 use crate::loglets::local_loglet;
+#[cfg(any(test, feature = "replicated_loglet"))]
+use crate::loglets::replicated_loglet;
 use crate::service::BifrostService;
 
 /// # Bifrost options
@@ -28,6 +32,10 @@ pub struct Options {
     #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
     /// Configuration of local loglet provider
     pub local: local_loglet::Options,
+    #[cfg(any(test, feature = "replicated_loglet"))]
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    /// Configuration of replicated loglet provider
+    pub replicated: replicated_loglet::Options,
 }
 
 impl Default for Options {
@@ -36,13 +44,15 @@ impl Default for Options {
             default_provider: ProviderKind::Local,
             #[cfg(any(test, feature = "local_loglet"))]
             local: local_loglet::Options::default(),
+            #[cfg(any(test, feature = "replicated_loglet"))]
+            replicated: replicated_loglet::Options::default(),
         }
     }
 }
 
 impl Options {
-    pub fn build(self, num_partitions: u64) -> BifrostService {
-        BifrostService::new(self, num_partitions)
+    pub fn build(self, num_partitions: u64, metadata_writer: MetadataWriter) -> BifrostService {
+        BifrostService::new(self, num_partitions, metadata_writer)
     }
 
     #[cfg(any(test, feature = "memory_loglet"))]
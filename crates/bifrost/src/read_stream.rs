@@ -19,6 +19,9 @@ pub struct LogReadStream {
     inner: Arc<BifrostInner>,
     log_id: LogId,
     read_pointer: Lsn,
+    // Lsn of the seal record which has been delivered last. A sealed log can still be extended
+    // with a new segment, readers wait for it instead of observing the same seal again.
+    delivered_seal: Option<Lsn>,
 }
 
 impl LogReadStream {
@@ -27,6 +30,7 @@ impl LogReadStream {
             inner,
             log_id,
             read_pointer: after,
+            delivered_seal: None,
         }
     }
 
@@ -37,7 +41,12 @@ impl LogReadStream {
             // skips over the boundary of the gap.
             crate::Record::TrimGap(trim_gap) => trim_gap.until,
             crate::Record::Data(_) => record.offset,
-            crate::Record::Seal(_) => record.offset,
+            // The seal doesn't occupy an lsn, if the log is extended, its next segment starts at
+            // the offset of the seal.
+            crate::Record::Seal(_) => {
+                self.delivered_seal = Some(record.offset);
+                return;
+            }
         };
         self.read_pointer = read_pointer;
     }
@@ -46,15 +55,27 @@ impl LogReadStream {
     /// after the record is available to read, this will async-block indefinitely if no records are
     /// ever written to the log beyond the read pointer.
     ///
+    /// A seal is delivered once. Afterwards, the stream waits until the log is extended with a
+    /// new segment.
+    ///
     /// This future is "Cancellation" safe.
     pub async fn read_next(&mut self) -> Result<LogRecord, Error> {
-        let record = self
-            .inner
-            .read_next_single(self.log_id, self.read_pointer)
-            .await?;
-
-        self.seek_to(&record);
-        Ok(record)
+        loop {
+            let mut metadata_watch = self.inner.watch_metadata();
+            let record = self
+                .inner
+                .read_next_single(self.log_id, self.read_pointer)
+                .await?;
+
+            if self.is_delivered_seal(&record) {
+                self.inner
+                    .wait_for_metadata_change(&mut metadata_watch)
+                    .await?;
+                continue;
+            }
+            self.seek_to(&record);
+            return Ok(record);
+        }
     }
 
     /// Like `read_next` but returns `None` if there are no more records to read.
@@ -63,10 +84,18 @@ impl LogReadStream {
             .inner
             .read_next_single_opt(self.log_id, self.read_pointer)
             .await?;
-        if let Some(ref record) = record_opt {
-            self.seek_to(record);
+        match record_opt {
+            Some(ref record) if self.is_delivered_seal(record) => Ok(None),
+            Some(record) => {
+                self.seek_to(&record);
+                Ok(Some(record))
+            }
+            None => Ok(None),
         }
-        Ok(record_opt)
+    }
+
+    fn is_delivered_seal(&self, record: &LogRecord) -> bool {
+        record.record.is_seal() && self.delivered_seal == Some(record.offset)
     }
 
     /// Current read pointer. This is the LSN of the last read record, or the
@@ -89,8 +118,8 @@ mod tests {
 
     use restate_types::logs::Payload;
 
-    use crate::loglet::ProviderKind;
     use crate::Options;
+    use restate_types::logs::metadata::ProviderKind;
 
     #[tokio::test]
     #[traced_test]
@@ -106,7 +135,7 @@ mod tests {
                 default_provider: ProviderKind::InMemory,
                 ..Options::default()
             };
-            let bifrost_svc = bifrost_opts.build(num_partitions);
+            let bifrost_svc = bifrost_opts.build(num_partitions, node_env.metadata_writer.clone());
             let mut bifrost = bifrost_svc.handle();
 
             // start bifrost service in the background
@@ -11,7 +11,7 @@
 use std::sync::Arc;
 
 use anyhow::Context;
-use restate_core::{task_center, TaskKind};
+use restate_core::{task_center, MetadataWriter, TaskKind};
 
 use crate::bifrost::BifrostInner;
 use crate::options::Options;
@@ -25,9 +25,14 @@ pub struct BifrostService {
 }
 
 impl BifrostService {
-    pub fn new(opts: Options, num_partitions: u64) -> Self {
+    pub fn new(opts: Options, num_partitions: u64, metadata_writer: MetadataWriter) -> Self {
         let (watchdog_sender, watchdog_receiver) = tokio::sync::mpsc::unbounded_channel();
-        let inner = Arc::new(BifrostInner::new(opts, watchdog_sender, num_partitions));
+        let inner = Arc::new(BifrostInner::new(
+            opts,
+            watchdog_sender,
+            metadata_writer,
+            num_partitions,
+        ));
         let bifrost = Bifrost::new(inner.clone());
         let watchdog = Watchdog::new(inner.clone(), watchdog_receiver);
         Self {
@@ -37,6 +42,28 @@ impl BifrostService {
         }
     }
 
+    /// Enables the replicated loglet provider. Records are sent to the log servers through
+    /// `networking` and their responses are received through the message router that is built
+    /// from `router_builder`.
+    #[cfg(any(test, feature = "replicated_loglet"))]
+    pub fn with_networking<N>(
+        self,
+        networking: N,
+        router_builder: &mut restate_core::network::MessageRouterBuilder,
+    ) -> Self
+    where
+        N: restate_core::network::NetworkSender + 'static,
+    {
+        let provider = crate::loglets::replicated_loglet::ReplicatedLogletProvider::new(
+            self.inner.opts.replicated.clone(),
+            networking,
+            router_builder,
+        );
+        self.inner
+            .register_provider(crate::ProviderKind::Replicated, provider);
+        self
+    }
+
     pub fn handle(&self) -> Bifrost {
         self.bifrost.clone()
     }
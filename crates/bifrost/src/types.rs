@@ -12,8 +12,8 @@
 #![allow(dead_code)]
 
 use crate::loglet::LogletOffset;
+pub use restate_types::logs::SealReason;
 use restate_types::logs::{Lsn, Payload, SequenceNumber};
-use serde::{Deserialize, Serialize};
 
 pub(crate) trait LsnExt: SequenceNumber {
     /// Converts a loglet offset into the virtual address (LSN).
@@ -30,6 +30,13 @@ pub(crate) trait LsnExt: SequenceNumber {
         Self::from(self_raw.wrapping_add(offset_raw) - S::OLDEST.into())
     }
 
+    /// The sequence number before this one. Unlike [`SequenceNumber::prev`], the oldest
+    /// sequence number maps to the invalid sequence number.
+    fn prev_or_invalid(self) -> Self {
+        let self_raw: u64 = self.into();
+        Self::from(self_raw.saturating_sub(1))
+    }
+
     /// Convert an LSN back to a loglet offset given a base_lsn.
     fn into_offset(self, base_lsn: Lsn) -> LogletOffset {
         let base_lsn_raw: u64 = base_lsn.into();
@@ -43,15 +50,6 @@ pub(crate) trait LsnExt: SequenceNumber {
 
 impl LsnExt for Lsn {}
 
-/// Details about why a log was sealed
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
-pub enum SealReason {
-    /// Log was sealed to perform a repartitioning operation (split or unsplit).
-    /// The reader/writer need to figure out where to read/write next.
-    Resharding,
-    Other(String),
-}
-
 /// A single entry in the log.
 #[derive(Debug, Clone)]
 pub struct LogRecord<S: SequenceNumber = Lsn> {
@@ -74,9 +72,18 @@ impl<S: SequenceNumber> LogRecord<S> {
         }
     }
 
+    pub(crate) fn new_seal(offset: S, reason: SealReason) -> Self {
+        LogRecord {
+            offset,
+            record: Record::Seal(reason),
+        }
+    }
+
     pub(crate) fn with_base_lsn(self, base_lsn: Lsn) -> LogRecord<Lsn> {
         let record = match self.record {
-            Record::TrimGap(_) => todo!(),
+            Record::TrimGap(trim_gap) => Record::TrimGap(TrimGap {
+                until: base_lsn.offset_by(trim_gap.until),
+            }),
             Record::Data(payload) => Record::Data(payload),
             Record::Seal(reason) => Record::Seal(reason),
         };
@@ -126,8 +133,11 @@ pub struct FindTailAttributes {
 
 #[cfg(test)]
 mod tests {
+    use restate_test_util::let_assert;
+
     use crate::loglet::LogletOffset;
     use crate::types::LsnExt;
+    use crate::{LogRecord, Record};
     use restate_types::logs::{Lsn, SequenceNumber};
 
     #[test]
@@ -138,4 +148,15 @@ mod tests {
 
         assert_eq!(offset, LogletOffset::INVALID);
     }
+
+    #[test]
+    fn trim_gap_with_base_lsn() {
+        let record = LogRecord::new_trim_gap(LogletOffset(1), LogletOffset(5));
+
+        let record = record.with_base_lsn(Lsn::from(11));
+
+        assert_eq!(Lsn::from(11), record.offset);
+        let_assert!(Record::TrimGap(trim_gap) = record.record);
+        assert_eq!(Lsn::from(15), trim_gap.until);
+    }
 }
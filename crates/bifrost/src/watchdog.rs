@@ -12,12 +12,14 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use enum_map::Enum;
-use restate_core::cancellation_watcher;
+use restate_core::{cancellation_watcher, metadata};
+use restate_node_protocol::metadata::MetadataKind;
+use restate_types::logs::metadata::ProviderKind;
 use tokio::task::JoinSet;
 use tracing::{debug, info, warn};
 
 use crate::bifrost::BifrostInner;
-use crate::loglet::{LogletProvider, ProviderKind};
+use crate::loglet::LogletProvider;
 
 pub type WatchdogSender = tokio::sync::mpsc::UnboundedSender<WatchdogCommand>;
 type WatchdogReceiver = tokio::sync::mpsc::UnboundedReceiver<WatchdogCommand>;
@@ -63,6 +65,8 @@ impl Watchdog {
     pub async fn run(mut self) -> anyhow::Result<()> {
         let shutdown = cancellation_watcher();
         tokio::pin!(shutdown);
+        // Picks up the logs metadata that the metadata manager received from other nodes.
+        let mut logs_watch = metadata().watch(MetadataKind::Logs);
         info!("Bifrost watchdog started");
 
         loop {
@@ -75,6 +79,9 @@ impl Watchdog {
             Some(cmd) = self.inbound.recv() => {
                 self.handle_command(cmd)
             }
+            Ok(_) = logs_watch.changed() => {
+                self.inner.apply_metadata(&metadata().logs());
+            }
             }
         }
         Ok(())
@@ -130,7 +137,6 @@ impl Watchdog {
 pub enum WatchdogCommand {
     /// Request to sync metadata if the client believes that it's outdated.
     /// i.e. attempting to write to a sealed segment.
-    #[allow(dead_code)]
     ScheduleMetadataSync,
     WatchProvider(Arc<dyn LogletProvider>),
 }
@@ -0,0 +1,58 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::ops::RangeInclusive;
+
+use enumset::EnumSet;
+use restate_types::identifiers::{PartitionId, PartitionKey};
+use restate_types::logs::Lsn;
+use restate_types::node_liveness::NodeLiveness;
+use restate_types::nodes_config::{NodeState, Role};
+use restate_types::partition_placement::PartitionReplicas;
+use restate_types::GenerationalNodeId;
+
+pub use restate_node_protocol::partition_processor_manager::{PartitionProcessorStatus, RunMode};
+
+/// The state of the cluster as observed by the cluster controller.
+#[derive(Debug, Clone)]
+pub struct ClusterStatus {
+    pub nodes: Vec<NodeStatus>,
+    pub partitions: Vec<PartitionStatus>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeStatus {
+    pub node_id: GenerationalNodeId,
+    pub name: String,
+    pub roles: EnumSet<Role>,
+    pub state: NodeState,
+    /// The liveness of the node's current generation, `None` if the failure detector has not
+    /// started tracking it yet.
+    pub liveness: Option<NodeLiveness>,
+    /// The partition processors which the node has reported in its last status. Nodes which
+    /// haven't reported their status yet or have failed have no partition processors.
+    pub partition_processors: Vec<PartitionProcessorStatus>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PartitionStatus {
+    pub partition_id: PartitionId,
+    pub key_range: RangeInclusive<PartitionKey>,
+    /// The nodes which have been chosen to run the partition, `None` if it hasn't been placed
+    /// yet.
+    pub replicas: Option<PartitionReplicas>,
+    /// The lsn of the last record which the leader of the partition has applied, or the furthest
+    /// replica if the leader has not reported its status. `None` if no replica has reported
+    /// an applied record.
+    pub applied_lsn: Option<Lsn>,
+    /// The lsn of the last record in the partition's log, `None` if the log has no readable
+    /// records or its tail could not be found.
+    pub log_tail: Option<Lsn>,
+}
@@ -0,0 +1,243 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use restate_types::node_liveness::NodeLiveness;
+use restate_types::nodes_config::NodesConfiguration;
+use restate_types::GenerationalNodeId;
+use tracing::{debug, info};
+
+/// Tracks the liveness of the current generation of every node in the nodes configuration based
+/// on the heartbeat responses it receives.
+///
+/// Nodes start out as [`NodeLiveness::Suspect`] and become [`NodeLiveness::Alive`] once they
+/// respond to a heartbeat. If a node has not responded for longer than the suspect timeout, it
+/// becomes suspect again, and after the dead timeout, it is considered [`NodeLiveness::Dead`].
+/// Previous generations of restarted nodes are forgotten.
+pub(crate) struct FailureDetector {
+    suspect_timeout: Duration,
+    dead_timeout: Duration,
+    nodes: HashMap<GenerationalNodeId, NodeState>,
+}
+
+struct NodeState {
+    liveness: NodeLiveness,
+    last_seen: Instant,
+}
+
+impl FailureDetector {
+    pub(crate) fn new(suspect_timeout: Duration, dead_timeout: Duration) -> Self {
+        Self {
+            suspect_timeout,
+            dead_timeout,
+            nodes: HashMap::default(),
+        }
+    }
+
+    /// Starts tracking the nodes of the nodes configuration. Returns whether the set of tracked
+    /// nodes has changed.
+    pub(crate) fn update_nodes(&mut self, nodes_config: &NodesConfiguration, now: Instant) -> bool {
+        let current_generations: Vec<GenerationalNodeId> = nodes_config
+            .iter()
+            .map(|(_, node)| node.current_generation)
+            .collect();
+
+        let tracked_nodes = self.nodes.len();
+        self.nodes
+            .retain(|node_id, _| current_generations.contains(node_id));
+        let mut changed = tracked_nodes != self.nodes.len();
+
+        for node_id in current_generations {
+            self.nodes.entry(node_id).or_insert_with(|| {
+                debug!("Start tracking the liveness of node {node_id}");
+                changed = true;
+                NodeState {
+                    liveness: NodeLiveness::Suspect,
+                    last_seen: now,
+                }
+            });
+        }
+
+        changed
+    }
+
+    /// Records a heartbeat response of the given node. Returns whether its liveness has changed.
+    pub(crate) fn on_heartbeat_response(
+        &mut self,
+        node_id: GenerationalNodeId,
+        now: Instant,
+    ) -> bool {
+        let Some(state) = self.nodes.get_mut(&node_id) else {
+            return false;
+        };
+
+        state.last_seen = now;
+        Self::transition(node_id, state, NodeLiveness::Alive)
+    }
+
+    /// Updates the liveness of nodes which have not responded in time. Returns whether the
+    /// liveness of any node has changed.
+    pub(crate) fn evaluate(&mut self, now: Instant) -> bool {
+        let mut changed = false;
+
+        for (node_id, state) in &mut self.nodes {
+            let elapsed = now.saturating_duration_since(state.last_seen);
+
+            let liveness = if elapsed > self.dead_timeout {
+                NodeLiveness::Dead
+            } else if elapsed > self.suspect_timeout {
+                NodeLiveness::Suspect
+            } else {
+                continue;
+            };
+
+            // dead nodes only come back to life by responding to heartbeats
+            if state.liveness != NodeLiveness::Dead {
+                changed |= Self::transition(*node_id, state, liveness);
+            }
+        }
+
+        changed
+    }
+
+    /// The nodes whose liveness is tracked.
+    pub(crate) fn nodes(&self) -> impl Iterator<Item = GenerationalNodeId> + '_ {
+        self.nodes.keys().copied()
+    }
+
+    pub(crate) fn liveness(&self) -> HashMap<GenerationalNodeId, NodeLiveness> {
+        self.nodes
+            .iter()
+            .map(|(node_id, state)| (*node_id, state.liveness))
+            .collect()
+    }
+
+    fn transition(
+        node_id: GenerationalNodeId,
+        state: &mut NodeState,
+        liveness: NodeLiveness,
+    ) -> bool {
+        if state.liveness == liveness {
+            return false;
+        }
+
+        info!(
+            "Liveness of node {node_id} changed from {} to {liveness}",
+            state.liveness
+        );
+        state.liveness = liveness;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use enumset::EnumSet;
+    use restate_types::nodes_config::{NodeConfig, Role};
+    use restate_types::Version;
+
+    const SUSPECT_TIMEOUT: Duration = Duration::from_secs(3);
+    const DEAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+    fn nodes_config(nodes: &[GenerationalNodeId]) -> NodesConfiguration {
+        let mut nodes_config = NodesConfiguration::new(Version::MIN, "test-cluster".to_owned());
+        for node_id in nodes {
+            nodes_config.upsert_node(NodeConfig::new(
+                format!("node-{}", node_id.as_plain()),
+                *node_id,
+                "unix:/tmp/my_socket".parse().unwrap(),
+                EnumSet::only(Role::Worker),
+            ));
+        }
+        nodes_config
+    }
+
+    #[test]
+    fn nodes_become_alive_suspect_and_dead() {
+        let node = GenerationalNodeId::new(1, 1);
+        let start = Instant::now();
+        let mut failure_detector = FailureDetector::new(SUSPECT_TIMEOUT, DEAD_TIMEOUT);
+
+        assert!(failure_detector.update_nodes(&nodes_config(&[node]), start));
+        assert_eq!(
+            failure_detector.liveness().get(&node),
+            Some(&NodeLiveness::Suspect)
+        );
+
+        assert!(failure_detector.on_heartbeat_response(node, start + Duration::from_secs(1)));
+        assert_eq!(
+            failure_detector.liveness().get(&node),
+            Some(&NodeLiveness::Alive)
+        );
+        assert!(!failure_detector.evaluate(start + Duration::from_secs(2)));
+
+        assert!(failure_detector.evaluate(start + Duration::from_secs(5)));
+        assert_eq!(
+            failure_detector.liveness().get(&node),
+            Some(&NodeLiveness::Suspect)
+        );
+
+        assert!(failure_detector.evaluate(start + Duration::from_secs(12)));
+        assert_eq!(
+            failure_detector.liveness().get(&node),
+            Some(&NodeLiveness::Dead)
+        );
+        assert!(!failure_detector.evaluate(start + Duration::from_secs(20)));
+
+        // a dead node which responds again is alive
+        assert!(failure_detector.on_heartbeat_response(node, start + Duration::from_secs(21)));
+        assert_eq!(
+            failure_detector.liveness().get(&node),
+            Some(&NodeLiveness::Alive)
+        );
+    }
+
+    #[test]
+    fn nodes_which_never_respond_become_dead() {
+        let node = GenerationalNodeId::new(1, 1);
+        let start = Instant::now();
+        let mut failure_detector = FailureDetector::new(SUSPECT_TIMEOUT, DEAD_TIMEOUT);
+        failure_detector.update_nodes(&nodes_config(&[node]), start);
+
+        assert!(!failure_detector.evaluate(start + Duration::from_secs(5)));
+        assert!(failure_detector.evaluate(start + Duration::from_secs(11)));
+        assert_eq!(
+            failure_detector.liveness().get(&node),
+            Some(&NodeLiveness::Dead)
+        );
+    }
+
+    #[test]
+    fn restarted_nodes_replace_their_previous_generation() {
+        let old_generation = GenerationalNodeId::new(1, 1);
+        let new_generation = GenerationalNodeId::new(1, 2);
+        let other_node = GenerationalNodeId::new(2, 1);
+        let start = Instant::now();
+        let mut failure_detector = FailureDetector::new(SUSPECT_TIMEOUT, DEAD_TIMEOUT);
+        failure_detector.update_nodes(&nodes_config(&[old_generation, other_node]), start);
+        failure_detector.on_heartbeat_response(old_generation, start);
+        failure_detector.on_heartbeat_response(other_node, start);
+
+        assert!(failure_detector.update_nodes(&nodes_config(&[new_generation, other_node]), start));
+        assert!(!failure_detector.update_nodes(&nodes_config(&[new_generation, other_node]), start));
+
+        let liveness = failure_detector.liveness();
+        assert_eq!(liveness.get(&old_generation), None);
+        assert_eq!(liveness.get(&new_generation), Some(&NodeLiveness::Suspect));
+        assert_eq!(liveness.get(&other_node), Some(&NodeLiveness::Alive));
+
+        // responses of the previous generation are ignored
+        assert!(!failure_detector.on_heartbeat_response(old_generation, start));
+    }
+}
@@ -0,0 +1,47 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use restate_core::network::{MessageHandler, NetworkSender};
+use restate_core::{task_center, TaskKind};
+use restate_node_protocol::heartbeat::{Heartbeat, HeartbeatResponse};
+use restate_node_protocol::MessageEnvelope;
+
+/// Answers the heartbeats of the cluster controller. Every node needs to register it in its
+/// message router, otherwise the cluster controller considers the node to be dead.
+pub struct HeartbeatResponder<N> {
+    networking: N,
+}
+
+impl<N> HeartbeatResponder<N> {
+    pub fn new(networking: N) -> Self {
+        Self { networking }
+    }
+}
+
+impl<N> MessageHandler for HeartbeatResponder<N>
+where
+    N: NetworkSender + 'static,
+{
+    type MessageType = Heartbeat;
+
+    async fn on_message(&self, envelope: MessageEnvelope<Heartbeat>) {
+        let (peer, heartbeat) = envelope.split();
+
+        let _ = task_center().spawn_child(TaskKind::Disposable, "send-heartbeat-response", None, {
+            let networking = self.networking.clone();
+            async move {
+                networking
+                    .send(peer.into(), &HeartbeatResponse { seq: heartbeat.seq })
+                    .await?;
+                Ok(())
+            }
+        });
+    }
+}
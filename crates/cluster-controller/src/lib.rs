@@ -8,8 +8,16 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+mod cluster_status;
+mod failure_detector;
+mod heartbeat;
 mod options;
+mod scheduler;
 mod service;
 
+pub use cluster_status::{
+    ClusterStatus, NodeStatus, PartitionProcessorStatus, PartitionStatus, RunMode,
+};
+pub use heartbeat::HeartbeatResponder;
 pub use options::Options;
 pub use service::{ClusterControllerHandle, Error, Service};
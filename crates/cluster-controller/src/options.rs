@@ -8,12 +8,74 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::time::Duration;
+
+use serde_with::serde_as;
+
 /// # Controller service options
-#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize, derive_builder::Builder)]
+#[serde_as]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, derive_builder::Builder)]
 #[cfg_attr(feature = "options_schema", derive(schemars::JsonSchema))]
 #[cfg_attr(
     feature = "options_schema",
     schemars(rename = "ClusterControllerOptions")
 )]
 #[cfg_attr(feature = "options_schema", schemars(default))]
-pub struct Options {}
+#[builder(default)]
+pub struct Options {
+    /// # Scheduling interval
+    ///
+    /// Interval at which the cluster controller re-evaluates the placement of partitions onto
+    /// worker nodes and re-sends the resulting instructions to the workers. The placement is
+    /// also re-evaluated whenever the nodes configuration or the partition table changes.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    pub scheduling_interval: humantime::Duration,
+    /// # Partition replication factor
+    ///
+    /// Number of worker nodes which run a partition processor for each partition. One of them
+    /// leads the partition, the others follow its log so that they can take over if the leader
+    /// fails. If there are fewer workers, every worker runs a partition processor for every
+    /// partition.
+    pub partition_replication_factor: usize,
+    /// # Heartbeat interval
+    ///
+    /// Interval at which the cluster controller sends heartbeats to all nodes to detect failed
+    /// nodes.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    pub heartbeat_interval: humantime::Duration,
+    /// # Node suspect timeout
+    ///
+    /// A node which has not answered heartbeats for this long is suspected to have failed.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    pub node_suspect_timeout: humantime::Duration,
+    /// # Node dead timeout
+    ///
+    /// A node which has not answered heartbeats for this long is considered dead. Its partitions
+    /// are moved to other worker nodes.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    pub node_dead_timeout: humantime::Duration,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            scheduling_interval: Duration::from_secs(5).into(),
+            partition_replication_factor: 2,
+            heartbeat_interval: Duration::from_secs(1).into(),
+            node_suspect_timeout: Duration::from_secs(3).into(),
+            node_dead_timeout: Duration::from_secs(10).into(),
+        }
+    }
+}
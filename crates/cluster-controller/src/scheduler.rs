@@ -0,0 +1,836 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::iter;
+
+use restate_types::identifiers::{LeaderEpoch, PartitionId};
+use restate_types::node_liveness::NodesLiveness;
+use restate_types::nodes_config::{NodeState, NodesConfiguration, Role};
+use restate_types::partition_placement::{PartitionPlacement, PartitionReplicas};
+use restate_types::partition_table::PartitionTable;
+use restate_types::{GenerationalNodeId, PlainNodeId};
+
+/// Computes the placement of the partitions of the partition table onto the worker nodes.
+///
+/// Every partition is led by one worker and followed by up to `replication_factor - 1` other
+/// workers. Leaders stay on their current node as long as it is still a worker which is not dead.
+/// If the leader of a partition is gone or dead, one of its followers takes over, or the least
+/// loaded worker if the partition has no followers left. Afterwards, leaderships are moved from the
+/// most to the least loaded workers until their number of led partitions differs by at most one.
+/// Followers stay on their current node where possible, missing followers are assigned to the
+/// workers with the fewest replicas.
+///
+/// Workers which are draining or have been drained are not assigned any partitions, so that their
+/// partitions are moved onto the remaining workers before they are removed from the cluster.
+///
+/// Partitions which are created by a split or merge start on the replicas of the partition which
+/// hands over the keys to them. They receive the data of the keys through their log and are placed
+/// like any other partition afterwards. Partitions which have been merged keep running on the
+/// replicas of the partition they have been merged into to deliver their outbox.
+///
+/// Whenever the leader of a partition changes, including restarts of the leading node, the
+/// partition gets a new leader epoch which is derived from the version of the next placement.
+///
+/// Returns the next version of the placement if it differs from the current placement.
+pub fn compute_placement(
+    current_placement: &PartitionPlacement,
+    partition_table: &PartitionTable,
+    nodes_config: &NodesConfiguration,
+    nodes_liveness: &NodesLiveness,
+    replication_factor: usize,
+) -> Option<PartitionPlacement> {
+    let next_version = current_placement.version().next();
+    let workers: BTreeMap<PlainNodeId, GenerationalNodeId> = nodes_config
+        .iter()
+        .filter(|(_, node)| {
+            node.roles.contains(Role::Worker)
+                && node.state == NodeState::Active
+                && !nodes_liveness.is_dead(node.current_generation)
+        })
+        .map(|(node_id, node)| (node_id, node.current_generation))
+        .collect();
+
+    let mut partitions = BTreeMap::new();
+
+    if !workers.is_empty() {
+        let effective_placement =
+            inherit_replicas_of_hand_overs(current_placement, partition_table);
+        let leaders = place_leaders(&effective_placement, partition_table, &workers);
+        let mut followers = place_followers(
+            &effective_placement,
+            &leaders,
+            &workers,
+            replication_factor.saturating_sub(1),
+        );
+
+        for (partition_id, leader) in leaders {
+            let leader = workers[&leader];
+            let leader_epoch = match effective_placement.replicas(partition_id) {
+                Some(replicas) if replicas.leader == leader => replicas.leader_epoch,
+                _ => LeaderEpoch::from(u64::from(u32::from(next_version))),
+            };
+
+            partitions.insert(
+                partition_id,
+                PartitionReplicas {
+                    leader,
+                    leader_epoch,
+                    followers: followers.remove(&partition_id).unwrap_or_default(),
+                },
+            );
+        }
+
+        for (partition_id, merged_into) in partition_table.merged_partitions() {
+            let Some(replicas) = partitions.get(&merged_into) else {
+                continue;
+            };
+            let leader_epoch = match current_placement.replicas(partition_id) {
+                Some(current_replicas) if current_replicas.leader == replicas.leader => {
+                    current_replicas.leader_epoch
+                }
+                _ => LeaderEpoch::from(u64::from(u32::from(next_version))),
+            };
+            let replicas = PartitionReplicas {
+                leader_epoch,
+                ..replicas.clone()
+            };
+
+            partitions.insert(partition_id, replicas);
+        }
+    }
+
+    let next_placement = PartitionPlacement::new(next_version, partitions);
+
+    if current_placement.iter().eq(next_placement.iter()) {
+        None
+    } else {
+        Some(next_placement)
+    }
+}
+
+/// Returns the current placement in which partitions which take over keys and haven't been placed
+/// yet are placed onto the replicas of the partition which hands over the keys.
+fn inherit_replicas_of_hand_overs(
+    current_placement: &PartitionPlacement,
+    partition_table: &PartitionTable,
+) -> PartitionPlacement {
+    let mut partitions: BTreeMap<PartitionId, PartitionReplicas> = current_placement
+        .iter()
+        .map(|(partition_id, replicas)| (partition_id, replicas.clone()))
+        .collect();
+
+    for hand_over in partition_table.pending_hand_overs() {
+        if partitions.contains_key(&hand_over.to) {
+            continue;
+        }
+        if let Some(replicas) = current_placement.replicas(hand_over.from) {
+            partitions.insert(hand_over.to, replicas.clone());
+        }
+    }
+
+    PartitionPlacement::new(current_placement.version(), partitions)
+}
+
+fn place_leaders(
+    current_placement: &PartitionPlacement,
+    partition_table: &PartitionTable,
+    workers: &BTreeMap<PlainNodeId, GenerationalNodeId>,
+) -> BTreeMap<PartitionId, PlainNodeId> {
+    let is_follower = |partition_id: PartitionId, node_id: &PlainNodeId| {
+        current_placement
+            .replicas(partition_id)
+            .is_some_and(|replicas| replicas.followers.contains(node_id))
+    };
+
+    let mut assignments: BTreeMap<PlainNodeId, BTreeSet<PartitionId>> = workers
+        .keys()
+        .map(|node_id| (*node_id, BTreeSet::new()))
+        .collect();
+    let mut unassigned_partitions = Vec::new();
+
+    for (partition_id, _) in partition_table.partitioner() {
+        match current_placement
+            .leader_for_partition(partition_id)
+            .and_then(|leader| assignments.get_mut(&leader.as_plain()))
+        {
+            Some(led_partitions) => {
+                led_partitions.insert(partition_id);
+            }
+            None => unassigned_partitions.push(partition_id),
+        }
+    }
+
+    for partition_id in unassigned_partitions {
+        // followers have already applied the log of the partition and can take over right away
+        let node_id = assignments
+            .iter()
+            .filter(|(node_id, _)| is_follower(partition_id, node_id))
+            .min_by_key(|(node_id, led_partitions)| (led_partitions.len(), **node_id))
+            .map(|(node_id, _)| *node_id)
+            .unwrap_or_else(|| least_loaded_node(&assignments));
+        assignments
+            .get_mut(&node_id)
+            .expect("node to exist")
+            .insert(partition_id);
+    }
+
+    loop {
+        let least_loaded = least_loaded_node(&assignments);
+        let most_loaded = most_loaded_node(&assignments);
+
+        if assignments[&most_loaded].len() <= assignments[&least_loaded].len() + 1 {
+            break;
+        }
+
+        // prefer handing over partitions which the least loaded node already follows
+        let mut movable_partitions = assignments[&most_loaded].iter().rev();
+        let Some(partition_id) = movable_partitions
+            .clone()
+            .find(|partition_id| is_follower(**partition_id, &least_loaded))
+            .or_else(|| movable_partitions.next())
+            .copied()
+        else {
+            break;
+        };
+
+        assignments
+            .get_mut(&most_loaded)
+            .expect("node to exist")
+            .remove(&partition_id);
+        assignments
+            .get_mut(&least_loaded)
+            .expect("node to exist")
+            .insert(partition_id);
+    }
+
+    assignments
+        .into_iter()
+        .flat_map(|(node_id, led_partitions)| {
+            led_partitions
+                .into_iter()
+                .map(move |partition_id| (partition_id, node_id))
+        })
+        .collect()
+}
+
+fn place_followers(
+    current_placement: &PartitionPlacement,
+    leaders: &BTreeMap<PartitionId, PlainNodeId>,
+    workers: &BTreeMap<PlainNodeId, GenerationalNodeId>,
+    num_followers: usize,
+) -> BTreeMap<PartitionId, BTreeSet<PlainNodeId>> {
+    let mut replica_counts: BTreeMap<PlainNodeId, usize> =
+        workers.keys().map(|node_id| (*node_id, 0)).collect();
+    for leader in leaders.values() {
+        *replica_counts.get_mut(leader).expect("node to exist") += 1;
+    }
+
+    // keep the current replicas of a partition, including a replaced leader, as followers
+    let mut followers = BTreeMap::new();
+    for (partition_id, leader) in leaders {
+        let partition_followers: BTreeSet<PlainNodeId> = current_placement
+            .replicas(*partition_id)
+            .into_iter()
+            .flat_map(|replicas| {
+                iter::once(replicas.leader.as_plain()).chain(replicas.followers.iter().copied())
+            })
+            .filter(|node_id| node_id != leader && workers.contains_key(node_id))
+            .take(num_followers)
+            .collect();
+
+        for node_id in &partition_followers {
+            *replica_counts.get_mut(node_id).expect("node to exist") += 1;
+        }
+        followers.insert(*partition_id, partition_followers);
+    }
+
+    for (partition_id, partition_followers) in &mut followers {
+        let leader = leaders[partition_id];
+
+        while partition_followers.len() < num_followers {
+            let Some(node_id) = replica_counts
+                .iter()
+                .filter(|(node_id, _)| {
+                    **node_id != leader && !partition_followers.contains(node_id)
+                })
+                .min_by_key(|(node_id, count)| (**count, **node_id))
+                .map(|(node_id, _)| *node_id)
+            else {
+                // not enough workers
+                break;
+            };
+
+            partition_followers.insert(node_id);
+            *replica_counts.get_mut(&node_id).expect("node to exist") += 1;
+        }
+    }
+
+    followers
+}
+
+fn least_loaded_node(assignments: &BTreeMap<PlainNodeId, BTreeSet<PartitionId>>) -> PlainNodeId {
+    *assignments
+        .iter()
+        .min_by_key(|(node_id, partitions)| (partitions.len(), **node_id))
+        .expect("at least one worker")
+        .0
+}
+
+fn most_loaded_node(assignments: &BTreeMap<PlainNodeId, BTreeSet<PartitionId>>) -> PlainNodeId {
+    *assignments
+        .iter()
+        .max_by_key(|(node_id, partitions)| (partitions.len(), std::cmp::Reverse(**node_id)))
+        .expect("at least one worker")
+        .0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use enumset::EnumSet;
+    use restate_types::node_liveness::NodeLiveness;
+    use restate_types::nodes_config::NodeConfig;
+    use restate_types::Version;
+
+    fn nodes_config(workers: &[u32], others: &[u32]) -> NodesConfiguration {
+        let mut nodes_config = NodesConfiguration::new(Version::MIN, "test-cluster".to_owned());
+        for (node_ids, roles) in [
+            (workers, EnumSet::only(Role::Worker)),
+            (others, EnumSet::only(Role::Admin)),
+        ] {
+            for node_id in node_ids {
+                nodes_config.upsert_node(NodeConfig::new(
+                    format!("node-{node_id}"),
+                    GenerationalNodeId::new(*node_id, 1),
+                    format!("http://localhost:{}", 5122 + node_id)
+                        .parse()
+                        .unwrap(),
+                    roles,
+                ));
+            }
+        }
+        nodes_config
+    }
+
+    fn partition_counts(placement: &PartitionPlacement) -> BTreeMap<PlainNodeId, usize> {
+        let mut counts = BTreeMap::new();
+        for (_, replicas) in placement.iter() {
+            *counts.entry(replicas.leader.as_plain()).or_default() += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn places_partitions_evenly_on_workers() {
+        let partition_table = PartitionTable::new(Version::MIN, 10);
+        let nodes_config = nodes_config(&[1, 2, 3], &[4]);
+
+        let placement = compute_placement(
+            &PartitionPlacement::default(),
+            &partition_table,
+            &nodes_config,
+            &NodesLiveness::default(),
+            1,
+        )
+        .expect("initial placement");
+
+        assert_eq!(placement.version(), Version::MIN);
+        assert_eq!(placement.iter().count(), 10);
+        assert_eq!(
+            partition_counts(&placement),
+            BTreeMap::from([
+                (PlainNodeId::from(1), 4),
+                (PlainNodeId::from(2), 3),
+                (PlainNodeId::from(3), 3)
+            ])
+        );
+
+        // the placement is stable
+        assert_eq!(
+            compute_placement(
+                &placement,
+                &partition_table,
+                &nodes_config,
+                &NodesLiveness::default(),
+                1
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn moves_only_partitions_of_removed_workers() {
+        let partition_table = PartitionTable::new(Version::MIN, 9);
+        let placement = compute_placement(
+            &PartitionPlacement::default(),
+            &partition_table,
+            &nodes_config(&[1, 2, 3], &[]),
+            &NodesLiveness::default(),
+            1,
+        )
+        .expect("initial placement");
+
+        let next_placement = compute_placement(
+            &placement,
+            &partition_table,
+            &nodes_config(&[1, 3], &[2]),
+            &NodesLiveness::default(),
+            1,
+        )
+        .expect("placement to change");
+
+        assert_eq!(next_placement.version(), placement.version().next());
+        for (partition_id, replicas) in placement.iter() {
+            if replicas.leader.as_plain() != PlainNodeId::from(2) {
+                assert_eq!(next_placement.replicas(partition_id), Some(replicas));
+            }
+        }
+        assert_eq!(
+            next_placement
+                .partitions_of_node(PlainNodeId::from(2))
+                .count(),
+            0
+        );
+        assert_eq!(
+            partition_counts(&next_placement),
+            BTreeMap::from([(PlainNodeId::from(1), 5), (PlainNodeId::from(3), 4)])
+        );
+    }
+
+    #[test]
+    fn rebalances_onto_new_workers() {
+        let partition_table = PartitionTable::new(Version::MIN, 8);
+        let placement = compute_placement(
+            &PartitionPlacement::default(),
+            &partition_table,
+            &nodes_config(&[1], &[]),
+            &NodesLiveness::default(),
+            1,
+        )
+        .expect("initial placement");
+        assert_eq!(
+            placement.partitions_of_node(PlainNodeId::from(1)).count(),
+            8
+        );
+
+        let next_placement = compute_placement(
+            &placement,
+            &partition_table,
+            &nodes_config(&[1, 2], &[]),
+            &NodesLiveness::default(),
+            1,
+        )
+        .expect("placement to change");
+
+        assert_eq!(
+            partition_counts(&next_placement),
+            BTreeMap::from([(PlainNodeId::from(1), 4), (PlainNodeId::from(2), 4)])
+        );
+    }
+
+    #[test]
+    fn moves_partitions_of_dead_workers() {
+        let partition_table = PartitionTable::new(Version::MIN, 6);
+        let nodes_config = nodes_config(&[1, 2], &[]);
+        let placement = compute_placement(
+            &PartitionPlacement::default(),
+            &partition_table,
+            &nodes_config,
+            &NodesLiveness::default(),
+            1,
+        )
+        .expect("initial placement");
+
+        let nodes_liveness = NodesLiveness::new(
+            Version::MIN,
+            [
+                (GenerationalNodeId::new(1, 1), NodeLiveness::Alive),
+                (GenerationalNodeId::new(2, 1), NodeLiveness::Dead),
+            ]
+            .into(),
+        );
+        let next_placement = compute_placement(
+            &placement,
+            &partition_table,
+            &nodes_config,
+            &nodes_liveness,
+            1,
+        )
+        .expect("placement to change");
+
+        assert_eq!(
+            partition_counts(&next_placement),
+            BTreeMap::from([(PlainNodeId::from(1), 6)])
+        );
+    }
+
+    #[test]
+    fn moves_partitions_and_followers_of_draining_workers() {
+        let partition_table = PartitionTable::new(Version::MIN, 6);
+        let mut nodes_config = nodes_config(&[1, 2, 3], &[]);
+        let placement = compute_placement(
+            &PartitionPlacement::default(),
+            &partition_table,
+            &nodes_config,
+            &NodesLiveness::default(),
+            2,
+        )
+        .expect("initial placement");
+        assert!(placement.partitions_of_node(PlainNodeId::from(2)).count() > 0);
+
+        nodes_config
+            .set_node_state(PlainNodeId::from(2), NodeState::Draining)
+            .unwrap();
+        let next_placement = compute_placement(
+            &placement,
+            &partition_table,
+            &nodes_config,
+            &NodesLiveness::default(),
+            2,
+        )
+        .expect("placement to change");
+
+        assert_eq!(
+            next_placement
+                .partitions_of_node(PlainNodeId::from(2))
+                .count(),
+            0
+        );
+        for (partition_id, replicas) in next_placement.iter() {
+            assert_eq!(replicas.followers.len(), 1);
+            let current_replicas = placement.replicas(partition_id).unwrap();
+            if current_replicas.leader.as_plain() != PlainNodeId::from(2) {
+                // leaders of the remaining workers stay in place
+                assert_eq!(replicas.leader, current_replicas.leader);
+                assert_eq!(replicas.leader_epoch, current_replicas.leader_epoch);
+            }
+        }
+        assert_eq!(
+            partition_counts(&next_placement),
+            BTreeMap::from([(PlainNodeId::from(1), 3), (PlainNodeId::from(3), 3)])
+        );
+    }
+
+    #[test]
+    fn unassigns_partitions_without_workers() {
+        let partition_table = PartitionTable::new(Version::MIN, 4);
+        let placement = compute_placement(
+            &PartitionPlacement::default(),
+            &partition_table,
+            &nodes_config(&[1], &[]),
+            &NodesLiveness::default(),
+            1,
+        )
+        .expect("initial placement");
+
+        let next_placement = compute_placement(
+            &placement,
+            &partition_table,
+            &nodes_config(&[], &[1]),
+            &NodesLiveness::default(),
+            1,
+        )
+        .expect("placement to change");
+
+        assert_eq!(next_placement.iter().count(), 0);
+    }
+
+    #[test]
+    fn places_followers_on_other_workers() {
+        let partition_table = PartitionTable::new(Version::MIN, 6);
+        let nodes_config = nodes_config(&[1, 2, 3], &[]);
+
+        let placement = compute_placement(
+            &PartitionPlacement::default(),
+            &partition_table,
+            &nodes_config,
+            &NodesLiveness::default(),
+            2,
+        )
+        .expect("initial placement");
+
+        for (_, replicas) in placement.iter() {
+            assert_eq!(replicas.leader_epoch, LeaderEpoch::INITIAL);
+            assert_eq!(replicas.followers.len(), 1);
+            assert!(!replicas.followers.contains(&replicas.leader.as_plain()));
+        }
+        for node_id in [1, 2, 3] {
+            assert_eq!(
+                placement
+                    .partitions_of_node(PlainNodeId::from(node_id))
+                    .count(),
+                4
+            );
+        }
+
+        // a replication factor larger than the number of workers places followers on all workers
+        let placement = compute_placement(
+            &PartitionPlacement::default(),
+            &partition_table,
+            &nodes_config,
+            &NodesLiveness::default(),
+            5,
+        )
+        .expect("initial placement");
+        for (_, replicas) in placement.iter() {
+            assert_eq!(replicas.followers.len(), 2);
+        }
+    }
+
+    #[test]
+    fn fails_over_to_followers_of_dead_leaders() {
+        let partition_table = PartitionTable::new(Version::MIN, 6);
+        let nodes_config = nodes_config(&[1, 2, 3], &[]);
+        let placement = compute_placement(
+            &PartitionPlacement::default(),
+            &partition_table,
+            &nodes_config,
+            &NodesLiveness::default(),
+            2,
+        )
+        .expect("initial placement");
+
+        let nodes_liveness = NodesLiveness::new(
+            Version::MIN,
+            [(GenerationalNodeId::new(1, 1), NodeLiveness::Dead)].into(),
+        );
+        let next_placement = compute_placement(
+            &placement,
+            &partition_table,
+            &nodes_config,
+            &nodes_liveness,
+            2,
+        )
+        .expect("placement to change");
+
+        for (partition_id, replicas) in placement.iter() {
+            let next_replicas = next_placement
+                .replicas(partition_id)
+                .expect("partition to be placed");
+            assert!(!next_replicas.contains(PlainNodeId::from(1)));
+            assert_eq!(next_replicas.followers.len(), 1);
+
+            if replicas.leader.as_plain() == PlainNodeId::from(1) {
+                // the follower took over in a new epoch
+                assert!(replicas
+                    .followers
+                    .contains(&next_replicas.leader.as_plain()));
+                assert!(next_replicas.leader_epoch > replicas.leader_epoch);
+                assert_eq!(
+                    next_replicas.leader_epoch,
+                    LeaderEpoch::from(u64::from(u32::from(next_placement.version())))
+                );
+            } else {
+                assert_eq!(next_replicas.leader, replicas.leader);
+                assert_eq!(next_replicas.leader_epoch, replicas.leader_epoch);
+            }
+        }
+    }
+
+    #[test]
+    fn restarted_leaders_get_new_leader_epoch() {
+        let partition_table = PartitionTable::new(Version::MIN, 2);
+        let placement = compute_placement(
+            &PartitionPlacement::default(),
+            &partition_table,
+            &nodes_config(&[1], &[]),
+            &NodesLiveness::default(),
+            1,
+        )
+        .expect("initial placement");
+
+        let mut nodes_config = nodes_config(&[1], &[]);
+        nodes_config.upsert_node(NodeConfig::new(
+            "node-1".to_owned(),
+            GenerationalNodeId::new(1, 2),
+            "http://localhost:5123".parse().unwrap(),
+            EnumSet::only(Role::Worker),
+        ));
+        let next_placement = compute_placement(
+            &placement,
+            &partition_table,
+            &nodes_config,
+            &NodesLiveness::default(),
+            1,
+        )
+        .expect("placement to change");
+
+        for (partition_id, replicas) in next_placement.iter() {
+            assert_eq!(replicas.leader, GenerationalNodeId::new(1, 2));
+            assert!(
+                replicas.leader_epoch
+                    > placement
+                        .replicas(partition_id)
+                        .expect("partition to be placed")
+                        .leader_epoch
+            );
+        }
+    }
+
+    #[test]
+    fn places_partitions_taking_over_keys_onto_replicas_of_their_source() {
+        let mut partition_table = PartitionTable::new(Version::MIN, 2);
+        let nodes_config = nodes_config(&[1, 2, 3], &[]);
+        let placement = compute_placement(
+            &PartitionPlacement::default(),
+            &partition_table,
+            &nodes_config,
+            &NodesLiveness::default(),
+            2,
+        )
+        .expect("initial placement");
+
+        let new_partition_id = partition_table.allocate_partition_id();
+        partition_table
+            .split(1, u64::MAX - 100, new_partition_id)
+            .unwrap();
+        let next_placement = compute_placement(
+            &placement,
+            &partition_table,
+            &nodes_config,
+            &NodesLiveness::default(),
+            2,
+        )
+        .expect("placement to change");
+
+        let replicas = placement.replicas(1).expect("partition to be placed");
+        let new_replicas = next_placement
+            .replicas(new_partition_id)
+            .expect("new partition to be placed");
+        assert!(replicas.contains(new_replicas.leader.as_plain()));
+        assert_eq!(new_replicas.followers.len(), replicas.followers.len());
+        for node_id in &new_replicas.followers {
+            assert!(replicas.contains(*node_id));
+        }
+        assert_eq!(next_placement.replicas(0), placement.replicas(0));
+
+        // the new partition receives its data through its log and gets a follower on the worker
+        // which holds no replica of the partition it took over the keys from
+        let nodes_liveness = NodesLiveness::new(
+            Version::MIN,
+            [(new_replicas.leader, NodeLiveness::Dead)].into(),
+        );
+        let next_placement = compute_placement(
+            &next_placement,
+            &partition_table,
+            &nodes_config,
+            &nodes_liveness,
+            2,
+        )
+        .expect("placement to change");
+        let next_replicas = next_placement
+            .replicas(new_partition_id)
+            .expect("new partition to be placed");
+        assert!(replicas.contains(next_replicas.leader.as_plain()));
+        assert_eq!(next_replicas.followers.len(), 1);
+        for node_id in &next_replicas.followers {
+            assert!(!replicas.contains(*node_id));
+        }
+    }
+
+    #[test]
+    fn rebalances_partitions_taking_over_keys_onto_other_workers() {
+        let mut partition_table = PartitionTable::new(Version::MIN, 2);
+        let nodes_config = nodes_config(&[1, 2], &[]);
+        let mut placement = compute_placement(
+            &PartitionPlacement::default(),
+            &partition_table,
+            &nodes_config,
+            &NodesLiveness::default(),
+            1,
+        )
+        .expect("initial placement");
+        let source_leader = placement.leader_for_partition(1);
+
+        let mut new_partition_ids = Vec::new();
+        let mut split_key = u64::MAX - 100;
+        for _ in 0..5 {
+            let new_partition_id = partition_table.allocate_partition_id();
+            partition_table
+                .split(1, split_key, new_partition_id)
+                .unwrap();
+            new_partition_ids.push(new_partition_id);
+            split_key -= 100;
+
+            placement = compute_placement(
+                &placement,
+                &partition_table,
+                &nodes_config,
+                &NodesLiveness::default(),
+                1,
+            )
+            .expect("placement to change");
+            for hand_over in partition_table.pending_hand_overs().to_vec() {
+                partition_table.complete_hand_over(&hand_over);
+            }
+        }
+
+        // the partitions receive the data of their keys through their log and can be moved onto
+        // the worker which doesn't hold the data of the partition they took over the keys from
+        let counts = partition_counts(&placement);
+        assert!(counts.values().max().unwrap() - counts.values().min().unwrap() <= 1);
+        assert!(new_partition_ids
+            .iter()
+            .any(|partition_id| placement.leader_for_partition(*partition_id) != source_leader));
+    }
+
+    #[test]
+    fn places_merged_partitions_onto_replicas_of_their_target() {
+        let mut partition_table = PartitionTable::new(Version::MIN, 2);
+        let nodes_config = nodes_config(&[1, 2], &[]);
+        let placement = compute_placement(
+            &PartitionPlacement::default(),
+            &partition_table,
+            &nodes_config,
+            &NodesLiveness::default(),
+            1,
+        )
+        .expect("initial placement");
+        assert_ne!(
+            placement.leader_for_partition(0),
+            placement.leader_for_partition(1)
+        );
+
+        let new_partition_id = partition_table.allocate_partition_id();
+        partition_table.merge(0, 1, new_partition_id).unwrap();
+        let next_placement = compute_placement(
+            &placement,
+            &partition_table,
+            &nodes_config,
+            &NodesLiveness::default(),
+            1,
+        )
+        .expect("placement to change");
+
+        let replicas = next_placement
+            .replicas(new_partition_id)
+            .expect("partition to be placed");
+        assert_eq!(Some(replicas.leader), placement.leader_for_partition(0));
+        for partition_id in [0, 1] {
+            let merged_replicas = next_placement
+                .replicas(partition_id)
+                .expect("merged partition to be placed");
+            assert_eq!(merged_replicas.leader, replicas.leader);
+        }
+        // partition 0 keeps its leader while partition 1 moves to it
+        assert_eq!(
+            next_placement
+                .replicas(0)
+                .map(|replicas| replicas.leader_epoch),
+            placement.replicas(0).map(|replicas| replicas.leader_epoch)
+        );
+        assert!(
+            next_placement.replicas(1).unwrap().leader_epoch
+                > placement.replicas(1).unwrap().leader_epoch
+        );
+    }
+}
@@ -8,37 +8,1201 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::{BTreeMap, HashMap};
+use std::iter;
+use std::time::Instant;
+
+use crate::cluster_status::{ClusterStatus, NodeStatus, PartitionStatus, RunMode};
+use crate::failure_detector::FailureDetector;
 use crate::options::Options;
+use crate::scheduler;
 use codederror::CodedError;
-use restate_core::cancellation_watcher;
+use enumset::EnumSet;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use restate_bifrost::{Bifrost, FindTailAttributes};
+use restate_core::metadata_store::{MetadataStoreClient, Precondition, ReadError, WriteError};
+use restate_core::network::{MessageRouterBuilder, NetworkSender};
+use restate_core::{
+    cancellation_watcher, metadata, task_center, MetadataWriter, ShutdownError, TaskKind,
+};
+use restate_futures_util::command::{
+    Command as HandleCommand, UnboundedCommandReceiver, UnboundedCommandSender,
+};
+use restate_node_protocol::heartbeat::{Heartbeat, HeartbeatResponse};
+use restate_node_protocol::metadata::MetadataKind;
+use restate_node_protocol::partition_processor_manager::{
+    ControlProcessor, ControlProcessors, GetPartitionProcessorsStatus, PartitionProcessorStatus,
+    PartitionProcessorsStatus, ProcessorCommand,
+};
+use restate_node_protocol::MessageEnvelope;
+use restate_types::identifiers::{LeaderEpoch, PartitionId, PartitionKey};
+use restate_types::logs::{LogId, Lsn};
+use restate_types::metadata_store::keys::{
+    NODES_CONFIG_KEY, NODES_LIVENESS_KEY, PARTITION_PLACEMENT_KEY, PARTITION_TABLE_KEY,
+};
+use restate_types::node_liveness::{NodeLiveness, NodesLiveness};
+use restate_types::nodes_config::{NodeState, NodesConfigError, NodesConfiguration, Role};
+use restate_types::partition_placement::PartitionPlacement;
+use restate_types::partition_table::{PartitionTable, PartitionTableUpdateError};
+use restate_types::time::MillisSinceEpoch;
+use restate_types::{GenerationalNodeId, PlainNodeId, Version};
+use restate_wal_protocol::control::{AnnounceLeader, HandOverKeyRange, TakeOverKeyRange};
+use restate_wal_protocol::{
+    append_envelope_to_partition, Command, Destination, Envelope, Header, Source,
+};
+use std::ops::RangeInclusive;
+use tokio::sync::mpsc;
+use tracing::{debug, info, instrument, trace, warn};
 
 #[derive(Debug, thiserror::Error, CodedError)]
 pub enum Error {
-    #[error("error")]
+    #[error("failed reading from metadata store: {0}")]
+    #[code(unknown)]
+    MetadataStoreRead(#[from] ReadError),
+    #[error("failed writing to metadata store: {0}")]
+    #[code(unknown)]
+    MetadataStoreWrite(#[from] WriteError),
+    #[error(transparent)]
+    #[code(unknown)]
+    Shutdown(#[from] ShutdownError),
+    #[error("cannot update the partition table: {0}")]
+    #[code(unknown)]
+    PartitionTableUpdate(#[from] PartitionTableUpdateError),
+    #[error("the partition table has not been initialized yet")]
+    #[code(unknown)]
+    MissingPartitionTable,
+    #[error("failed creating log: {0}")]
+    #[code(unknown)]
+    CreateLog(#[from] restate_bifrost::Error),
+    #[error("failed appending to log: {0}")]
+    #[code(unknown)]
+    Append(#[from] restate_wal_protocol::Error),
+    #[error(transparent)]
+    #[code(unknown)]
+    NodesConfig(#[from] NodesConfigError),
+    #[error("the nodes configuration has not been initialized yet")]
+    #[code(unknown)]
+    MissingNodesConfiguration,
+    #[error("node {0} cannot be removed because it has not been drained yet, its state is '{1}'")]
     #[code(unknown)]
-    Error,
+    NodeNotDrained(PlainNodeId, NodeState),
+    #[error("roles cannot be assigned to node {0} because its state is '{1}'")]
+    #[code(unknown)]
+    NodeNotActive(PlainNodeId, NodeState),
+    #[error("the '{0}' role cannot be changed while the node is running")]
+    #[code(unknown)]
+    UnsupportedRoleChange(Role),
+    #[error(
+        "node {0} cannot be drained because no other active worker is alive to take over its partitions"
+    )]
+    #[code(unknown)]
+    NoOtherWorker(PlainNodeId),
+    #[error(
+        "the '{1}' role cannot be removed from node {0} because no other active node which is alive has it"
+    )]
+    #[code(unknown)]
+    LastRoleHolder(PlainNodeId, Role),
+    #[error("cluster controller is not running")]
+    #[code(unknown)]
+    ControllerClosed,
 }
 
-#[derive(Debug)]
-pub struct Service {
-    #[allow(dead_code)]
+pub struct Service<N> {
     options: Options,
+    networking: N,
+    metadata_writer: MetadataWriter,
+    metadata_store_client: MetadataStoreClient,
+    bifrost: Bifrost,
+    heartbeat_responses: BoxStream<'static, MessageEnvelope<HeartbeatResponse>>,
+    failure_detector: FailureDetector,
+    heartbeat_seq: u64,
+    partition_processors_status_responses:
+        BoxStream<'static, MessageEnvelope<PartitionProcessorsStatus>>,
+    partition_processors_status: HashMap<GenerationalNodeId, Vec<PartitionProcessorStatus>>,
+    placement: PartitionPlacement,
+    nodes_liveness: NodesLiveness,
+    announced_leader_epochs: HashMap<PartitionId, LeaderEpoch>,
+    handle: ClusterControllerHandle,
+    api_cmd_rx: UnboundedCommandReceiver<ClusterControllerRequest, ClusterControllerResponse>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClusterControllerHandle(
+    UnboundedCommandSender<ClusterControllerRequest, ClusterControllerResponse>,
+);
+
+enum ClusterControllerRequest {
+    SplitPartition {
+        partition_id: PartitionId,
+        split_key: Option<PartitionKey>,
+    },
+    MergePartitions {
+        partition_id: PartitionId,
+        other_partition_id: PartitionId,
+    },
+    DrainNode {
+        node_id: PlainNodeId,
+    },
+    RemoveNode {
+        node_id: PlainNodeId,
+    },
+    UpdateNodeRoles {
+        node_id: PlainNodeId,
+        role: Role,
+        assign: bool,
+    },
+    ClusterStatus,
+}
+
+enum ClusterControllerResponse {
+    SplitPartition(Result<PartitionId, Error>),
+    MergePartitions(Result<PartitionId, Error>),
+    DrainNode(Result<NodeState, Error>),
+    RemoveNode(Result<(), Error>),
+    UpdateNodeRoles(Result<EnumSet<Role>, Error>),
+    ClusterStatus(ClusterStatus),
+}
+
+impl ClusterControllerHandle {
+    /// Splits the key range of the given partition at the split key, or in the middle if no split
+    /// key is given. The partition keeps the lower part of its key range. Returns the id of the
+    /// new partition which takes over the upper part.
+    pub async fn split_partition(
+        &self,
+        partition_id: PartitionId,
+        split_key: Option<PartitionKey>,
+    ) -> Result<PartitionId, Error> {
+        let (cmd, response_tx) = HandleCommand::prepare(ClusterControllerRequest::SplitPartition {
+            partition_id,
+            split_key,
+        });
+        self.0.send(cmd).map_err(|_e| Error::ControllerClosed)?;
+        response_tx
+            .await
+            .map(|res| match res {
+                ClusterControllerResponse::SplitPartition(res) => res,
+                _ => panic!("Unexpected response message, this is a bug"),
+            })
+            .map_err(|_e| Error::ControllerClosed)?
+    }
+
+    /// Merges two partitions with adjacent key ranges. Returns the id of the new partition which
+    /// takes over the key ranges of both partitions.
+    pub async fn merge_partitions(
+        &self,
+        partition_id: PartitionId,
+        other_partition_id: PartitionId,
+    ) -> Result<PartitionId, Error> {
+        let (cmd, response_tx) =
+            HandleCommand::prepare(ClusterControllerRequest::MergePartitions {
+                partition_id,
+                other_partition_id,
+            });
+        self.0.send(cmd).map_err(|_e| Error::ControllerClosed)?;
+        response_tx
+            .await
+            .map(|res| match res {
+                ClusterControllerResponse::MergePartitions(res) => res,
+                _ => panic!("Unexpected response message, this is a bug"),
+            })
+            .map_err(|_e| Error::ControllerClosed)?
+    }
+
+    /// Marks the node as draining. Its partitions are moved onto other workers and its ingress
+    /// stops accepting requests. Once it has been drained, the node marks itself as drained.
+    /// Nodes without the worker role are drained right away. Returns the state of the node.
+    pub async fn drain_node(&self, node_id: PlainNodeId) -> Result<NodeState, Error> {
+        let (cmd, response_tx) =
+            HandleCommand::prepare(ClusterControllerRequest::DrainNode { node_id });
+        self.0.send(cmd).map_err(|_e| Error::ControllerClosed)?;
+        response_tx
+            .await
+            .map(|res| match res {
+                ClusterControllerResponse::DrainNode(res) => res,
+                _ => panic!("Unexpected response message, this is a bug"),
+            })
+            .map_err(|_e| Error::ControllerClosed)?
+    }
+
+    /// Removes a drained node from the nodes configuration. Its node id won't be reused.
+    pub async fn remove_node(&self, node_id: PlainNodeId) -> Result<(), Error> {
+        let (cmd, response_tx) =
+            HandleCommand::prepare(ClusterControllerRequest::RemoveNode { node_id });
+        self.0.send(cmd).map_err(|_e| Error::ControllerClosed)?;
+        response_tx
+            .await
+            .map(|res| match res {
+                ClusterControllerResponse::RemoveNode(res) => res,
+                _ => panic!("Unexpected response message, this is a bug"),
+            })
+            .map_err(|_e| Error::ControllerClosed)?
+    }
+
+    /// Assigns the role to the node. The node starts the role's services once it learns about the
+    /// updated nodes configuration. Returns the roles of the node.
+    pub async fn assign_role(
+        &self,
+        node_id: PlainNodeId,
+        role: Role,
+    ) -> Result<EnumSet<Role>, Error> {
+        self.update_node_roles(node_id, role, true).await
+    }
+
+    /// Removes the role from the node. The node stops the role's services once it learns about
+    /// the updated nodes configuration. Partitions of a node which loses the worker role are moved
+    /// to other workers. Returns the roles of the node.
+    pub async fn unassign_role(
+        &self,
+        node_id: PlainNodeId,
+        role: Role,
+    ) -> Result<EnumSet<Role>, Error> {
+        self.update_node_roles(node_id, role, false).await
+    }
+
+    async fn update_node_roles(
+        &self,
+        node_id: PlainNodeId,
+        role: Role,
+        assign: bool,
+    ) -> Result<EnumSet<Role>, Error> {
+        let (cmd, response_tx) =
+            HandleCommand::prepare(ClusterControllerRequest::UpdateNodeRoles {
+                node_id,
+                role,
+                assign,
+            });
+        self.0.send(cmd).map_err(|_e| Error::ControllerClosed)?;
+        response_tx
+            .await
+            .map(|res| match res {
+                ClusterControllerResponse::UpdateNodeRoles(res) => res,
+                _ => panic!("Unexpected response message, this is a bug"),
+            })
+            .map_err(|_e| Error::ControllerClosed)?
+    }
+
+    /// Returns the nodes of the cluster with their liveness and partition processors, and the
+    /// partitions with their replicas and log tails.
+    pub async fn cluster_status(&self) -> Result<ClusterStatus, Error> {
+        let (cmd, response_tx) = HandleCommand::prepare(ClusterControllerRequest::ClusterStatus);
+        self.0.send(cmd).map_err(|_e| Error::ControllerClosed)?;
+        response_tx
+            .await
+            .map(|res| match res {
+                ClusterControllerResponse::ClusterStatus(res) => res,
+                _ => panic!("Unexpected response message, this is a bug"),
+            })
+            .map_err(|_e| Error::ControllerClosed)
+    }
 }
 
-// todo: Replace with proper handle
-pub struct ClusterControllerHandle;
+impl<N> Service<N>
+where
+    N: NetworkSender + 'static,
+{
+    pub fn new(
+        options: Options,
+        networking: N,
+        metadata_writer: MetadataWriter,
+        bifrost: Bifrost,
+        router_builder: &mut MessageRouterBuilder,
+    ) -> Self {
+        let metadata_store_client = metadata_writer.metadata_store_client().clone();
+        let heartbeat_responses = router_builder.subscribe_to_stream(64);
+        let partition_processors_status_responses = router_builder.subscribe_to_stream(64);
+        let failure_detector = FailureDetector::new(
+            options.node_suspect_timeout.into(),
+            options.node_dead_timeout.into(),
+        );
+        let (api_cmd_tx, api_cmd_rx) = mpsc::unbounded_channel();
 
-impl Service {
-    pub fn new(options: Options) -> Self {
-        Service { options }
+        Service {
+            options,
+            networking,
+            metadata_writer,
+            metadata_store_client,
+            bifrost,
+            heartbeat_responses,
+            failure_detector,
+            heartbeat_seq: 0,
+            partition_processors_status_responses,
+            partition_processors_status: HashMap::default(),
+            placement: PartitionPlacement::default(),
+            nodes_liveness: NodesLiveness::default(),
+            announced_leader_epochs: HashMap::default(),
+            handle: ClusterControllerHandle(api_cmd_tx),
+            api_cmd_rx,
+        }
     }
 
     pub fn handle(&self) -> ClusterControllerHandle {
-        ClusterControllerHandle
+        self.handle.clone()
     }
 
-    pub async fn run(self) -> anyhow::Result<()> {
-        let _ = cancellation_watcher().await;
+    pub async fn run(mut self) -> anyhow::Result<()> {
+        let mut shutdown = std::pin::pin!(cancellation_watcher());
+        let mut nodes_config_watch = metadata().watch(MetadataKind::NodesConfiguration);
+        let mut partition_table_watch = metadata().watch(MetadataKind::PartitionTable);
+        let mut scheduling_interval =
+            tokio::time::interval(self.options.scheduling_interval.into());
+        scheduling_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut heartbeat_interval = tokio::time::interval(self.options.heartbeat_interval.into());
+        heartbeat_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        self.placement = self.load_placement().await?;
+        self.nodes_liveness = self.load_nodes_liveness().await?;
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => {
+                    break;
+                }
+                _ = scheduling_interval.tick() => {
+                    // refresh the nodes configuration to learn about newly joined workers
+                    if let Err(err) = self.sync_nodes_configuration().await {
+                        warn!("Failed refreshing the nodes configuration: {err}");
+                    }
+                    self.schedule_partitions(true).await;
+                }
+                result = nodes_config_watch.changed() => {
+                    result.map_err(|_| ShutdownError)?;
+                    self.schedule_partitions(true).await;
+                }
+                result = partition_table_watch.changed() => {
+                    result.map_err(|_| ShutdownError)?;
+                    self.schedule_partitions(true).await;
+                }
+                _ = heartbeat_interval.tick() => {
+                    let now = Instant::now();
+                    self.failure_detector.update_nodes(&metadata().nodes_config(), now);
+                    self.send_heartbeats().await;
+                    self.failure_detector.evaluate(now);
+                    self.on_liveness_update().await;
+                    self.request_partition_processors_status().await;
+                }
+                cmd = self.api_cmd_rx.recv() => {
+                    let (req, replier) = cmd.expect("This channel should never be closed").into_inner();
+
+                    let res = match req {
+                        ClusterControllerRequest::SplitPartition { partition_id, split_key } => {
+                            ClusterControllerResponse::SplitPartition(
+                                self.split_partition(partition_id, split_key).await,
+                            )
+                        }
+                        ClusterControllerRequest::MergePartitions { partition_id, other_partition_id } => {
+                            ClusterControllerResponse::MergePartitions(
+                                self.merge_partitions(partition_id, other_partition_id).await,
+                            )
+                        }
+                        ClusterControllerRequest::DrainNode { node_id } => {
+                            ClusterControllerResponse::DrainNode(self.drain_node(node_id).await)
+                        }
+                        ClusterControllerRequest::RemoveNode { node_id } => {
+                            ClusterControllerResponse::RemoveNode(self.remove_node(node_id).await)
+                        }
+                        ClusterControllerRequest::UpdateNodeRoles { node_id, role, assign } => {
+                            ClusterControllerResponse::UpdateNodeRoles(
+                                self.update_node_roles(node_id, role, assign).await,
+                            )
+                        }
+                        ClusterControllerRequest::ClusterStatus => {
+                            // finding the log tails takes round trips to the log servers
+                            // which must not block the controller, hence the reply is sent
+                            // from a separate task
+                            let cluster_status = self.cluster_status();
+                            let bifrost = self.bifrost.clone();
+                            if let Err(err) = task_center().spawn_child(
+                                TaskKind::Disposable,
+                                "cluster-status",
+                                None,
+                                async move {
+                                    let cluster_status =
+                                        find_log_tails(&bifrost, cluster_status).await;
+                                    let _ = replier.send(
+                                        ClusterControllerResponse::ClusterStatus(cluster_status),
+                                    );
+                                    Ok(())
+                                },
+                            ) {
+                                debug!("Failed spawning the cluster status task: {err}");
+                            }
+                            continue;
+                        }
+                    };
+
+                    let _ = replier.send(res);
+                }
+                Some(heartbeat_response) = self.heartbeat_responses.next() => {
+                    let (peer, heartbeat_response) = heartbeat_response.split();
+                    trace!("Received heartbeat response {} from {peer}", heartbeat_response.seq);
+                    if self.failure_detector.on_heartbeat_response(peer, Instant::now()) {
+                        self.on_liveness_update().await;
+                    }
+                }
+                Some(status) = self.partition_processors_status_responses.next() => {
+                    let (peer, status) = status.split();
+                    trace!("Received status of {} partition processors from {peer}", status.processors.len());
+                    self.partition_processors_status.insert(peer, status.processors);
+                }
+            }
+        }
+
         Ok(())
     }
+
+    /// Updates the placement, announces its leaders and instructs the workers about it. If the
+    /// placement has not changed, the workers are only instructed if `instruct_unchanged` is set.
+    async fn schedule_partitions(&mut self, instruct_unchanged: bool) {
+        if let Err(err) = self.complete_hand_overs().await {
+            warn!("Failed completing the hand overs of key ranges: {err}");
+        }
+
+        match self.update_placement().await {
+            Ok(changed) => {
+                self.announce_leaders().await;
+                if changed || instruct_unchanged {
+                    self.instruct_workers().await;
+                }
+            }
+            Err(Error::MetadataStoreWrite(WriteError::FailedPrecondition(_))) => {
+                debug!("Partition placement has been changed concurrently, reloading it");
+                match self.load_placement().await {
+                    Ok(placement) => self.placement = placement,
+                    Err(err) => warn!("Failed reloading the partition placement: {err}"),
+                }
+            }
+            Err(err) => warn!("Failed scheduling partitions: {err}"),
+        }
+    }
+
+    async fn load_placement(&self) -> Result<PartitionPlacement, Error> {
+        Ok(self
+            .metadata_store_client
+            .get::<PartitionPlacement>(PARTITION_PLACEMENT_KEY.clone())
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn load_nodes_liveness(&self) -> Result<NodesLiveness, Error> {
+        Ok(self
+            .metadata_store_client
+            .get::<NodesLiveness>(NODES_LIVENESS_KEY.clone())
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn sync_nodes_configuration(&self) -> Result<(), Error> {
+        if let Some(nodes_config) = self
+            .metadata_store_client
+            .get::<NodesConfiguration>(NODES_CONFIG_KEY.clone())
+            .await?
+        {
+            // the metadata manager ignores outdated versions
+            self.metadata_writer.update(nodes_config).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Computes the placement for the current partition table, nodes configuration and liveness
+    /// and persists it if it has changed. Returns whether the placement has changed.
+    async fn update_placement(&mut self) -> Result<bool, Error> {
+        let Some(next_placement) = scheduler::compute_placement(
+            &self.placement,
+            &metadata().partition_table(),
+            &metadata().nodes_config(),
+            &self.nodes_liveness,
+            self.options.partition_replication_factor,
+        ) else {
+            return Ok(false);
+        };
+
+        self.metadata_store_client
+            .put(
+                PARTITION_PLACEMENT_KEY.clone(),
+                next_placement.clone(),
+                precondition_for(self.placement.version()),
+            )
+            .await?;
+
+        info!(
+            "Updated partition placement to {}",
+            next_placement.version()
+        );
+        self.placement = next_placement;
+        Ok(true)
+    }
+
+    /// Appends an [`AnnounceLeader`] command to the log of every placed partition whose leader
+    /// epoch has not been announced yet. The partition processors of the partition switch between
+    /// leader and follower when they apply it. Failed announcements are retried in the next
+    /// round, announcing an epoch more than once is harmless because processors ignore
+    /// announcements which don't increase the epoch.
+    async fn announce_leaders(&mut self) {
+        let placement = self.placement.clone();
+
+        for (partition_id, replicas) in placement.iter() {
+            if self
+                .announced_leader_epochs
+                .get(&partition_id)
+                .is_some_and(|announced_epoch| *announced_epoch >= replicas.leader_epoch)
+            {
+                continue;
+            }
+
+            let announce_leader = AnnounceLeader {
+                node_id: replicas.leader,
+                leader_epoch: replicas.leader_epoch,
+            };
+
+            match self
+                .append_to_partition(partition_id, Command::AnnounceLeader(announce_leader))
+                .await
+            {
+                Ok(_) => {
+                    info!(
+                        "Announced {} as leader of partition {partition_id} in epoch {}",
+                        replicas.leader, replicas.leader_epoch
+                    );
+                    self.announced_leader_epochs
+                        .insert(partition_id, replicas.leader_epoch);
+                }
+                Err(err) => {
+                    warn!("Failed announcing the leader of partition {partition_id}: {err}")
+                }
+            }
+        }
+    }
+
+    /// Tells every worker which is not dead to run the partitions for which it has been chosen as
+    /// leader or follower and to stop all other partitions. Workers which miss the instructions receive them again
+    /// in the next round.
+    async fn instruct_workers(&self) {
+        let nodes_config = metadata().nodes_config();
+        let partition_table = metadata().partition_table();
+
+        for (node_id, _) in nodes_config.iter().filter(|(_, node)| {
+            node.roles.contains(Role::Worker)
+                && !self.nodes_liveness.is_dead(node.current_generation)
+        }) {
+            let commands = partition_table
+                .partitioner()
+                .map(|(partition_id, _)| partition_id)
+                .chain(
+                    partition_table
+                        .merged_partitions()
+                        .map(|(partition_id, _)| partition_id),
+                )
+                .map(|partition_id| ControlProcessor {
+                    partition_id,
+                    command: if self
+                        .placement
+                        .replicas(partition_id)
+                        .is_some_and(|replicas| replicas.contains(node_id))
+                    {
+                        ProcessorCommand::Start
+                    } else {
+                        ProcessorCommand::Stop
+                    },
+                })
+                .collect();
+
+            let control_processors = ControlProcessors {
+                placement_version: self.placement.version(),
+                partition_table_version: partition_table.version(),
+                commands,
+            };
+
+            if let Err(err) = self
+                .networking
+                .send(node_id.into(), &control_processors)
+                .await
+            {
+                warn!("Failed instructing worker {node_id} to control its partition processors: {err}");
+            }
+        }
+    }
+
+    /// Splits the key range of the partition into a new partition which takes over the keys from
+    /// the split key onwards:
+    ///
+    /// 1. The id of the new partition is allocated in the partition table.
+    /// 2. The log of the new partition is created and a [`TakeOverKeyRange`] is appended to it.
+    ///    This makes sure that the new partition processor takes over the keys before processing
+    ///    any of their records.
+    /// 3. The split is added to the partition table, from now on the keys are routed to the new
+    ///    partition.
+    /// 4. The hand over is appended to the log of the split partition, see
+    ///    [`Self::complete_hand_overs`], and the new partition is placed onto the workers.
+    ///
+    /// If the split fails before the partition table has been updated, the allocated partition id
+    /// is left unused.
+    #[instrument(level = "debug", skip(self))]
+    async fn split_partition(
+        &mut self,
+        partition_id: PartitionId,
+        split_key: Option<PartitionKey>,
+    ) -> Result<PartitionId, Error> {
+        let partition_table = metadata().partition_table();
+        let key_range = partition_table
+            .key_range(partition_id)
+            .ok_or(PartitionTableUpdateError::UnknownPartition(partition_id))?;
+        let split_key = split_key
+            .unwrap_or_else(|| key_range.start() + (key_range.end() - key_range.start()) / 2 + 1);
+
+        // validate the split before allocating anything
+        let mut next_partition_table = PartitionTable::clone(&partition_table);
+        let new_partition_id = next_partition_table.allocate_partition_id();
+        next_partition_table.split(partition_id, split_key, new_partition_id)?;
+
+        let new_partition_id = self
+            .update_partition_table(|partition_table| Ok(partition_table.allocate_partition_id()))
+            .await?;
+        self.prepare_partition(
+            new_partition_id,
+            [(partition_id, split_key..=*key_range.end())],
+        )
+        .await?;
+        self.update_partition_table(|partition_table| {
+            partition_table.split(partition_id, split_key, new_partition_id)
+        })
+        .await?;
+        info!("Split partition {partition_id} at key {split_key} into new partition {new_partition_id}");
+
+        // hands over the keys and places the new partition
+        self.schedule_partitions(true).await;
+
+        Ok(new_partition_id)
+    }
+
+    /// Merges two partitions with adjacent key ranges into a new partition. It follows the same
+    /// steps as [`Self::split_partition`] with the new partition taking over the keys of both
+    /// partitions. The merged partitions keep running until their outbox has been delivered.
+    #[instrument(level = "debug", skip(self))]
+    async fn merge_partitions(
+        &mut self,
+        partition_id: PartitionId,
+        other_partition_id: PartitionId,
+    ) -> Result<PartitionId, Error> {
+        let partition_table = metadata().partition_table();
+
+        // validate the merge before allocating anything
+        let mut next_partition_table = PartitionTable::clone(&partition_table);
+        let new_partition_id = next_partition_table.allocate_partition_id();
+        next_partition_table.merge(partition_id, other_partition_id, new_partition_id)?;
+
+        let hand_overs = [partition_id, other_partition_id].map(|partition_id| {
+            (
+                partition_id,
+                partition_table
+                    .key_range(partition_id)
+                    .expect("merged partition to exist"),
+            )
+        });
+
+        let new_partition_id = self
+            .update_partition_table(|partition_table| Ok(partition_table.allocate_partition_id()))
+            .await?;
+        self.prepare_partition(new_partition_id, hand_overs).await?;
+        self.update_partition_table(|partition_table| {
+            partition_table.merge(partition_id, other_partition_id, new_partition_id)
+        })
+        .await?;
+        info!("Merged partitions {partition_id} and {other_partition_id} into new partition {new_partition_id}");
+
+        // hands over the keys and places the new partition
+        self.schedule_partitions(true).await;
+
+        Ok(new_partition_id)
+    }
+
+    /// Creates the log of a new partition which starts with taking over the given key ranges.
+    async fn prepare_partition(
+        &mut self,
+        partition_id: PartitionId,
+        take_overs: impl IntoIterator<Item = (PartitionId, RangeInclusive<PartitionKey>)>,
+    ) -> Result<(), Error> {
+        self.bifrost.create_log(LogId::from(partition_id)).await?;
+
+        for (from, key_range) in take_overs {
+            self.append_to_partition(
+                partition_id,
+                Command::TakeOverKeyRange(TakeOverKeyRange { key_range, from }),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Appends the pending hand overs of key ranges to the logs of the partitions which hand over
+    /// the keys and removes them from the partition table afterwards. Appending a hand over more
+    /// than once is harmless because processors ignore hand overs of keys they don't own.
+    async fn complete_hand_overs(&mut self) -> Result<(), Error> {
+        let partition_table = metadata().partition_table();
+
+        for hand_over in partition_table.pending_hand_overs() {
+            let hand_over_key_range = HandOverKeyRange {
+                key_range: hand_over.key_range.clone(),
+                to: hand_over.to,
+                partition_table_version: partition_table.version(),
+            };
+            self.append_to_partition(
+                hand_over.from,
+                Command::HandOverKeyRange(hand_over_key_range),
+            )
+            .await?;
+
+            self.update_partition_table(|partition_table| {
+                partition_table.complete_hand_over(hand_over);
+                Ok(())
+            })
+            .await?;
+            debug!(
+                "Handed over keys {:?} from partition {} to partition {}",
+                hand_over.key_range, hand_over.from, hand_over.to
+            );
+        }
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    async fn drain_node(&mut self, node_id: PlainNodeId) -> Result<NodeState, Error> {
+        let state = self
+            .update_nodes_configuration(|nodes_config| {
+                let node = nodes_config.find_node_by_id(node_id)?;
+                if node.state != NodeState::Active {
+                    return Ok(node.state);
+                }
+
+                let state = if node.roles.contains(Role::Worker) {
+                    // the partitions of the node need to be moved onto another worker
+                    if !has_other_available_holder(
+                        nodes_config,
+                        &self.nodes_liveness,
+                        node_id,
+                        Role::Worker,
+                    ) {
+                        return Err(Error::NoOtherWorker(node_id));
+                    }
+                    NodeState::Draining
+                } else {
+                    NodeState::Drained
+                };
+                nodes_config.set_node_state(node_id, state)?;
+                nodes_config.increment_version();
+                Ok(state)
+            })
+            .await?;
+        info!("Node {node_id} is {state}");
+
+        // move the partitions of the node away right away
+        self.schedule_partitions(true).await;
+        Ok(state)
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    async fn remove_node(&mut self, node_id: PlainNodeId) -> Result<(), Error> {
+        self.update_nodes_configuration(|nodes_config| {
+            let node = nodes_config.find_node_by_id(node_id)?;
+            if node.state != NodeState::Drained {
+                return Err(Error::NodeNotDrained(node_id, node.state));
+            }
+
+            nodes_config.remove_node(node_id)?;
+            nodes_config.increment_version();
+            Ok(())
+        })
+        .await?;
+        info!("Node {node_id} has been removed from the cluster");
+
+        Ok(())
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    async fn update_node_roles(
+        &mut self,
+        node_id: PlainNodeId,
+        role: Role,
+        assign: bool,
+    ) -> Result<EnumSet<Role>, Error> {
+        if !role.is_dynamic() {
+            return Err(Error::UnsupportedRoleChange(role));
+        }
+
+        let roles = self
+            .update_nodes_configuration(|nodes_config| {
+                let node = nodes_config.find_node_by_id(node_id)?;
+                if node.roles.contains(role) == assign {
+                    return Ok(node.roles);
+                }
+
+                let roles = if assign {
+                    if node.state != NodeState::Active {
+                        return Err(Error::NodeNotActive(node_id, node.state));
+                    }
+                    node.roles | role
+                } else {
+                    if !has_other_available_holder(
+                        nodes_config,
+                        &self.nodes_liveness,
+                        node_id,
+                        role,
+                    ) {
+                        return Err(Error::LastRoleHolder(node_id, role));
+                    }
+                    node.roles - role
+                };
+
+                nodes_config.set_node_roles(node_id, roles)?;
+                nodes_config.increment_version();
+                Ok(roles)
+            })
+            .await?;
+        info!("Node {node_id} has the roles {roles}");
+
+        if role == Role::Worker {
+            // place partitions on the new worker or move them away from the old one
+            self.schedule_partitions(true).await;
+        }
+        Ok(roles)
+    }
+
+    /// Applies the update to the stored nodes configuration and stores the result if the update
+    /// has changed the nodes configuration. Concurrent modifications, e.g. by joining nodes, are
+    /// retried.
+    async fn update_nodes_configuration<T>(
+        &self,
+        mut update: impl FnMut(&mut NodesConfiguration) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        loop {
+            let mut nodes_config = self
+                .metadata_store_client
+                .get::<NodesConfiguration>(NODES_CONFIG_KEY.clone())
+                .await?
+                .ok_or(Error::MissingNodesConfiguration)?;
+            let version = nodes_config.version();
+
+            let result = update(&mut nodes_config)?;
+
+            if nodes_config.version() == version {
+                return Ok(result);
+            }
+
+            match self
+                .metadata_store_client
+                .put(
+                    NODES_CONFIG_KEY.clone(),
+                    nodes_config.clone(),
+                    Precondition::MatchesVersion(version),
+                )
+                .await
+            {
+                Ok(()) => {
+                    self.metadata_writer.update(nodes_config).await?;
+                    return Ok(result);
+                }
+                Err(WriteError::FailedPrecondition(_)) => {
+                    debug!(
+                        "Nodes configuration has been changed concurrently, retrying the update"
+                    );
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// Applies the update to the stored partition table and stores the result if the update has
+    /// changed the partition table. Concurrent modifications of the partition table are retried.
+    async fn update_partition_table<T>(
+        &self,
+        mut update: impl FnMut(&mut PartitionTable) -> Result<T, PartitionTableUpdateError>,
+    ) -> Result<T, Error> {
+        loop {
+            let mut partition_table = self
+                .metadata_store_client
+                .get::<PartitionTable>(PARTITION_TABLE_KEY.clone())
+                .await?
+                .ok_or(Error::MissingPartitionTable)?;
+            let version = partition_table.version();
+
+            let result = update(&mut partition_table)?;
+
+            if partition_table.version() == version {
+                return Ok(result);
+            }
+
+            match self
+                .metadata_store_client
+                .put(
+                    PARTITION_TABLE_KEY.clone(),
+                    partition_table.clone(),
+                    Precondition::MatchesVersion(version),
+                )
+                .await
+            {
+                Ok(()) => {
+                    self.metadata_writer.update(partition_table).await?;
+                    return Ok(result);
+                }
+                Err(WriteError::FailedPrecondition(_)) => {
+                    debug!("Partition table has been changed concurrently, retrying the update");
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// Appends a command which is targeted at the given partition to its log.
+    async fn append_to_partition(
+        &mut self,
+        partition_id: PartitionId,
+        command: Command,
+    ) -> Result<(), Error> {
+        debug_assert!(command.is_partition_scoped());
+        let partition_key = metadata()
+            .partition_table()
+            .key_range(partition_id)
+            .map(|key_range| *key_range.start())
+            .unwrap_or_default();
+        let header = Header {
+            dest: Destination::Processor {
+                partition_key,
+                dedup: None,
+            },
+            source: Source::ControlPlane {},
+            created_at: Some(MillisSinceEpoch::now()),
+        };
+
+        append_envelope_to_partition(
+            &mut self.bifrost,
+            partition_id,
+            Envelope::new(header, command),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn send_heartbeats(&mut self) {
+        self.heartbeat_seq += 1;
+        let heartbeat = Heartbeat {
+            seq: self.heartbeat_seq,
+        };
+
+        for node_id in self.failure_detector.nodes() {
+            if let Err(err) = self.networking.send(node_id.into(), &heartbeat).await {
+                trace!("Failed sending heartbeat to {node_id}: {err}");
+            }
+        }
+    }
+
+    /// Asks every worker which is not dead for the status of its partition processors. The status
+    /// of dead workers and of previous node generations is forgotten. The request tells the
+    /// leaders up to which lsn all replicas have applied the logs of their partitions.
+    async fn request_partition_processors_status(&mut self) {
+        let liveness = self.failure_detector.liveness();
+        self.partition_processors_status.retain(|node_id, _| {
+            liveness
+                .get(node_id)
+                .is_some_and(|liveness| *liveness != NodeLiveness::Dead)
+        });
+
+        let replicas_applied_lsns =
+            replicas_applied_lsns(&self.placement, &self.partition_processors_status);
+        let nodes_config = metadata().nodes_config();
+        for (_, node) in nodes_config.iter().filter(|(_, node)| {
+            node.roles.contains(Role::Worker)
+                && liveness
+                    .get(&node.current_generation)
+                    .is_some_and(|liveness| *liveness != NodeLiveness::Dead)
+        }) {
+            if let Err(err) = self
+                .networking
+                .send(
+                    node.current_generation.into(),
+                    &GetPartitionProcessorsStatus {
+                        replicas_applied_lsns: replicas_applied_lsns.clone(),
+                    },
+                )
+                .await
+            {
+                trace!(
+                    "Failed requesting the partition processors status of {}: {err}",
+                    node.current_generation
+                );
+            }
+        }
+    }
+
+    /// Returns the status of the cluster without the log tails of its partitions, see
+    /// [`find_log_tails`].
+    fn cluster_status(&self) -> ClusterStatus {
+        let liveness = self.failure_detector.liveness();
+        let nodes: Vec<_> = metadata()
+            .nodes_config()
+            .iter()
+            .map(|(_, node)| {
+                let mut partition_processors = self
+                    .partition_processors_status
+                    .get(&node.current_generation)
+                    .cloned()
+                    .unwrap_or_default();
+                partition_processors.sort_by_key(|status| status.partition_id);
+
+                NodeStatus {
+                    node_id: node.current_generation,
+                    name: node.name.clone(),
+                    roles: node.roles,
+                    state: node.state,
+                    liveness: liveness.get(&node.current_generation).copied(),
+                    partition_processors,
+                }
+            })
+            .collect();
+
+        let partitions = metadata()
+            .partition_table()
+            .partitioner()
+            .map(|(partition_id, key_range)| {
+                // the leader has applied the most records, unless it has not reported its
+                // status yet, in which case the furthest replica is used
+                let processors = nodes
+                    .iter()
+                    .flat_map(|node| &node.partition_processors)
+                    .filter(|processor| processor.partition_id == partition_id);
+                let applied_lsn = processors
+                    .clone()
+                    .find(|processor| processor.run_mode == RunMode::Leader)
+                    .or_else(|| processors.max_by_key(|processor| processor.last_applied_lsn))
+                    .and_then(|processor| processor.last_applied_lsn);
+
+                PartitionStatus {
+                    partition_id,
+                    key_range,
+                    replicas: self.placement.replicas(partition_id).cloned(),
+                    applied_lsn,
+                    log_tail: None,
+                }
+            })
+            .collect();
+
+        ClusterStatus { nodes, partitions }
+    }
+
+    /// Persists the liveness of the failure detector if it differs from the stored liveness and
+    /// fails over the partitions of dead workers.
+    async fn on_liveness_update(&mut self) {
+        let liveness = self.failure_detector.liveness();
+        if self.nodes_liveness.iter().collect::<HashMap<_, _>>() == liveness {
+            return;
+        }
+
+        let nodes_liveness = NodesLiveness::new(self.nodes_liveness.version().next(), liveness);
+
+        match self
+            .metadata_store_client
+            .put(
+                NODES_LIVENESS_KEY.clone(),
+                nodes_liveness.clone(),
+                precondition_for(self.nodes_liveness.version()),
+            )
+            .await
+        {
+            Ok(()) => {
+                debug!("Updated nodes liveness to {}", nodes_liveness.version());
+                self.nodes_liveness = nodes_liveness;
+                self.schedule_partitions(false).await;
+            }
+            Err(err) => {
+                // retried with the next heartbeat
+                warn!("Failed storing nodes liveness: {err}");
+                if let WriteError::FailedPrecondition(_) = err {
+                    match self.load_nodes_liveness().await {
+                        Ok(stored_liveness) => self.nodes_liveness = stored_liveness,
+                        Err(err) => warn!("Failed reloading the nodes liveness: {err}"),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Finds the log tails of all partitions of the cluster status concurrently.
+async fn find_log_tails(bifrost: &Bifrost, mut cluster_status: ClusterStatus) -> ClusterStatus {
+    let log_tails = futures::future::join_all(cluster_status.partitions.iter().map(|partition| {
+        let partition_id = partition.partition_id;
+        async move {
+            match bifrost
+                .find_tail(LogId::from(partition_id), FindTailAttributes::default())
+                .await
+            {
+                Ok(log_tail) => log_tail,
+                Err(err) => {
+                    debug!("Failed finding the log tail of partition {partition_id}: {err}");
+                    None
+                }
+            }
+        }
+    }))
+    .await;
+
+    for (partition, log_tail) in cluster_status.partitions.iter_mut().zip(log_tails) {
+        partition.log_tail = log_tail;
+    }
+    cluster_status
+}
+
+/// Returns the lowest lsn which all replicas of each placed partition have applied according to
+/// their last reported status. Partitions of which some replica hasn't reported an applied lsn
+/// are left out.
+fn replicas_applied_lsns(
+    placement: &PartitionPlacement,
+    partition_processors_status: &HashMap<GenerationalNodeId, Vec<PartitionProcessorStatus>>,
+) -> BTreeMap<PartitionId, Lsn> {
+    placement
+        .iter()
+        .filter_map(|(partition_id, replicas)| {
+            iter::once(replicas.leader.as_plain())
+                .chain(replicas.followers.iter().copied())
+                .map(|replica| {
+                    partition_processors_status
+                        .iter()
+                        .filter(|(node_id, _)| node_id.as_plain() == replica)
+                        .flat_map(|(_, processors)| processors)
+                        .filter(|processor| processor.partition_id == partition_id)
+                        .map(|processor| processor.last_applied_lsn)
+                        .min()
+                        .flatten()
+                })
+                .min()
+                .flatten()
+                .map(|applied_lsn| (partition_id, applied_lsn))
+        })
+        .collect()
+}
+
+/// Returns whether a node other than the given one has the role, is active and is not known to
+/// have failed.
+fn has_other_available_holder(
+    nodes_config: &NodesConfiguration,
+    nodes_liveness: &NodesLiveness,
+    node_id: PlainNodeId,
+    role: Role,
+) -> bool {
+    nodes_config.iter().any(|(other_id, other)| {
+        other_id != node_id
+            && other.roles.contains(role)
+            && other.state == NodeState::Active
+            && !nodes_liveness.is_dead(other.current_generation)
+    })
+}
+
+fn precondition_for(version: Version) -> Precondition {
+    if version == Version::INVALID {
+        Precondition::DoesNotExist
+    } else {
+        Precondition::MatchesVersion(version)
+    }
 }
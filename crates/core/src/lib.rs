@@ -9,6 +9,7 @@
 // by the Apache License, Version 2.0.
 
 mod metadata;
+pub mod metadata_store;
 mod metric_definitions;
 pub mod network;
 pub mod options;
@@ -23,4 +24,7 @@ pub use task_center_types::*;
 mod test_env;
 
 #[cfg(any(test, feature = "test-util"))]
-pub use test_env::{create_mock_nodes_config, MockNetworkSender, TestCoreEnv, TestCoreEnvBuilder};
+pub use test_env::{
+    create_mock_nodes_config, MockClusterNetwork, MockClusterSender, MockNetworkSender,
+    TestCoreEnv, TestCoreEnvBuilder,
+};
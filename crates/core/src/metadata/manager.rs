@@ -9,23 +9,35 @@
 // by the Apache License, Version 2.0.
 
 use arc_swap::ArcSwapOption;
+use bytestring::ByteString;
+use enum_map::{enum_map, EnumMap};
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
 use std::ops::Deref;
 use std::sync::Arc;
+use std::time::Duration;
 
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tracing::{debug, info, warn};
 
-use restate_node_protocol::metadata::{MetadataMessage, MetadataUpdate};
+use restate_node_protocol::metadata::{GetMetadataRequest, MetadataMessage, MetadataUpdate};
 use restate_node_protocol::MessageEnvelope;
+use restate_types::logs::metadata::Logs;
+use restate_types::metadata_store::keys::{
+    BIFROST_CONFIG_KEY, NODES_CONFIG_KEY, PARTITION_TABLE_KEY, SCHEMA_INFORMATION_KEY,
+};
 use restate_types::nodes_config::NodesConfiguration;
-use restate_types::partition_table::FixedPartitionTable;
+use restate_types::partition_table::PartitionTable;
+use restate_types::retries::RetryPolicy;
+use restate_types::schema::SchemaInformation;
 use restate_types::GenerationalNodeId;
 use restate_types::{Version, Versioned};
 
 use crate::cancellation_watcher;
 use crate::is_cancellation_requested;
 use crate::metadata;
+use crate::metadata_store::MetadataStoreClient;
 use crate::network::{MessageHandler, MessageRouterBuilder, NetworkSender};
 use crate::task_center;
 
@@ -34,8 +46,17 @@ use super::{Metadata, MetadataContainer, MetadataInner, MetadataKind, MetadataWr
 pub(super) type CommandSender = mpsc::UnboundedSender<Command>;
 pub(super) type CommandReceiver = mpsc::UnboundedReceiver<Command>;
 
+/// How often the metadata manager syncs metadata from the metadata store whose newer versions
+/// have been observed but not received yet.
+const OBSERVED_VERSIONS_SYNC_INTERVAL: Duration = Duration::from_secs(1);
+
 pub(super) enum Command {
     UpdateMetadata(MetadataContainer, Option<oneshot::Sender<()>>),
+    ObservedVersion {
+        metadata_kind: MetadataKind,
+        version: Version,
+        peer: Option<GenerationalNodeId>,
+    },
 }
 
 /// A handler for processing network messages targeting metadata manager
@@ -58,28 +79,53 @@ where
         metadata_kind: MetadataKind,
         min_version: Option<Version>,
     ) {
+        if metadata().version(metadata_kind) == Version::INVALID {
+            debug!(
+                "Peer requested '{}' which has not been loaded yet, ignoring their request",
+                metadata_kind
+            );
+            return;
+        }
+
         match metadata_kind {
-            MetadataKind::NodesConfiguration => self.send_nodes_config(peer, min_version),
-            _ => {
-                todo!("Can't send metadata '{}' to peer", metadata_kind)
+            MetadataKind::NodesConfiguration => {
+                let config = metadata().nodes_config();
+                self.send_metadata_internal(peer, min_version, config.deref().clone());
+            }
+            MetadataKind::Logs => {
+                let logs = metadata().logs();
+                self.send_metadata_internal(peer, min_version, logs.deref().clone());
+            }
+            MetadataKind::Schema => {
+                let schema_information = metadata().schema_information();
+                self.send_metadata_internal(peer, min_version, schema_information.deref().clone());
+            }
+            MetadataKind::PartitionTable => {
+                let partition_table = metadata().partition_table();
+                self.send_metadata_internal(peer, min_version, partition_table.deref().clone());
             }
         };
     }
 
-    fn send_nodes_config(&self, to: GenerationalNodeId, version: Option<Version>) {
-        let config = metadata().nodes_config();
-        if version.is_some_and(|min_version| min_version > config.version()) {
+    fn send_metadata_internal<T>(&self, to: GenerationalNodeId, version: Option<Version>, value: T)
+    where
+        T: Into<MetadataContainer> + Versioned,
+    {
+        let container = value.into();
+        if version.is_some_and(|min_version| min_version > container.version()) {
             // We don't have the version that the peer is asking for. Just ignore.
             info!(
-                "Peer requested nodes config version {} but we have {}, ignoring their request",
+                "Peer requested '{}' version {} but we have {}, ignoring their request",
+                container.kind(),
                 version.unwrap(),
-                config.version()
+                container.version()
             );
             return;
         }
         info!(
-            "Sending nodes config {} to peer, requested version? {:?}",
-            config.version(),
+            "Sending '{}' {} to peer, requested version? {:?}",
+            container.kind(),
+            container.version(),
             version,
         );
         let _ = task_center().spawn_child(
@@ -92,11 +138,7 @@ where
                     networking
                         .send(
                             to.into(),
-                            &MetadataMessage::MetadataUpdate(MetadataUpdate {
-                                container: MetadataContainer::NodesConfiguration(
-                                    config.deref().clone(),
-                                ),
-                            }),
+                            &MetadataMessage::MetadataUpdate(MetadataUpdate { container }),
                         )
                         .await?;
                     Ok(())
@@ -150,7 +192,9 @@ where
 /// - Accepts adhoc requests from system components that might have observed higher
 /// metadata version through other means. Metadata manager takes note and schedules a
 /// sync so that we don't end up with thundering herd by direct metadata update
-/// requests from components
+/// requests from components. Peers announce their metadata versions in the header of
+/// every message. Newer versions are requested from the announcing peer and, if they
+/// don't arrive, fetched from the metadata store.
 ///
 /// Metadata to be managed by MetadataManager:
 /// - Bifrost's log metadata
@@ -162,19 +206,24 @@ pub struct MetadataManager<N> {
     inner: Arc<MetadataInner>,
     inbound: CommandReceiver,
     networking: N,
+    metadata_store_client: MetadataStoreClient,
+    /// Newer versions which have been observed but not received yet.
+    observed_versions: EnumMap<MetadataKind, Version>,
 }
 
 impl<N> MetadataManager<N>
 where
     N: NetworkSender + 'static + Clone,
 {
-    pub fn build(networking: N) -> Self {
+    pub fn build(networking: N, metadata_store_client: MetadataStoreClient) -> Self {
         let (self_sender, inbound) = mpsc::unbounded_channel();
         Self {
             inner: Arc::new(MetadataInner::default()),
             inbound,
             self_sender,
             networking,
+            metadata_store_client,
+            observed_versions: enum_map! { _ => Version::INVALID },
         }
     }
 
@@ -190,13 +239,26 @@ where
     }
 
     pub fn writer(&self) -> MetadataWriter {
-        MetadataWriter::new(self.self_sender.clone(), self.inner.clone())
+        MetadataWriter::new(
+            self.self_sender.clone(),
+            self.inner.clone(),
+            self.metadata_store_client.clone(),
+        )
     }
 
     /// Start and wait for shutdown signal.
     pub async fn run(mut self) -> anyhow::Result<()> {
         info!("Metadata manager started");
 
+        // React to changes in the metadata store immediately instead of waiting for peers to
+        // tell us about newer versions.
+        self.spawn_metadata_store_watch::<NodesConfiguration>(NODES_CONFIG_KEY.clone())?;
+        self.spawn_metadata_store_watch::<PartitionTable>(PARTITION_TABLE_KEY.clone())?;
+        self.spawn_metadata_store_watch::<SchemaInformation>(SCHEMA_INFORMATION_KEY.clone())?;
+
+        let mut sync_interval = tokio::time::interval(OBSERVED_VERSIONS_SYNC_INTERVAL);
+        sync_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
         loop {
             tokio::select! {
                 biased;
@@ -207,17 +269,199 @@ where
                 Some(cmd) = self.inbound.recv() => {
                     self.handle_command(cmd)
                 }
+                _ = sync_interval.tick() => {
+                    self.sync_observed_versions();
+                }
             }
         }
         Ok(())
     }
 
+    fn spawn_metadata_store_watch<T>(&self, key: ByteString) -> anyhow::Result<()>
+    where
+        T: Into<MetadataContainer> + Versioned + DeserializeOwned + Send + 'static,
+    {
+        task_center().spawn_child(
+            crate::TaskKind::MetadataBackgroundSync,
+            "metadata-store-watch",
+            None,
+            Self::watch_metadata_store::<T>(
+                self.metadata_store_client.clone(),
+                key,
+                self.self_sender.clone(),
+            ),
+        )?;
+        Ok(())
+    }
+
+    /// Submits every value of the key in the metadata store as metadata update. The watch is
+    /// restarted if it fails, e.g. because the metadata store is not reachable.
+    async fn watch_metadata_store<T>(
+        metadata_store_client: MetadataStoreClient,
+        key: ByteString,
+        sender: CommandSender,
+    ) -> anyhow::Result<()>
+    where
+        T: Into<MetadataContainer> + Versioned + DeserializeOwned + Send + 'static,
+    {
+        let max_backoff = Duration::from_secs(5);
+        let retry_policy = RetryPolicy::exponential(
+            Duration::from_millis(100),
+            2.0,
+            usize::MAX,
+            Some(max_backoff),
+        );
+        let mut backoff = retry_policy.clone().into_iter();
+
+        loop {
+            match metadata_store_client.watch::<T>(key.clone()).await {
+                Ok(mut values) => {
+                    while let Some(value) = values.next().await {
+                        match value {
+                            Ok(Some(value)) => {
+                                backoff = retry_policy.clone().into_iter();
+                                if sender
+                                    .send(Command::UpdateMetadata(value.into(), None))
+                                    .is_err()
+                                {
+                                    // metadata manager has stopped
+                                    return Ok(());
+                                }
+                            }
+                            // cached metadata is kept if it is deleted from the metadata store
+                            Ok(None) => {}
+                            Err(err) => {
+                                debug!("Watch of '{}' failed: {}", key, err);
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(err) => debug!("Failed watching '{}' in metadata store: {}", key, err),
+            }
+
+            tokio::time::sleep(backoff.next().unwrap_or(max_backoff)).await;
+        }
+    }
+
     fn handle_command(&mut self, cmd: Command) {
         match cmd {
             Command::UpdateMetadata(value, callback) => self.update_metadata(value, callback),
+            Command::ObservedVersion {
+                metadata_kind,
+                version,
+                peer,
+            } => self.on_observed_version(metadata_kind, version, peer),
         }
     }
 
+    fn on_observed_version(
+        &mut self,
+        metadata_kind: MetadataKind,
+        version: Version,
+        peer: Option<GenerationalNodeId>,
+    ) {
+        // only the first observation of a version triggers a sync, the others are covered by
+        // the periodic sync with the metadata store
+        if version <= self.current_version(metadata_kind)
+            || version <= self.observed_versions[metadata_kind]
+        {
+            return;
+        }
+
+        debug!(
+            "Observed '{}' version {}, syncing it from {}",
+            metadata_kind,
+            version,
+            peer.map_or_else(|| "metadata store".to_owned(), |peer| peer.to_string())
+        );
+        self.observed_versions[metadata_kind] = version;
+
+        match peer {
+            Some(peer) => self.request_metadata_from_peer(peer, metadata_kind, version),
+            None => self.fetch_from_metadata_store(metadata_kind),
+        }
+    }
+
+    /// Fetches metadata from the metadata store whose observed version has not been received yet.
+    fn sync_observed_versions(&self) {
+        for (metadata_kind, observed_version) in self.observed_versions.iter() {
+            if *observed_version > self.current_version(metadata_kind) {
+                self.fetch_from_metadata_store(metadata_kind);
+            }
+        }
+    }
+
+    fn current_version(&self, metadata_kind: MetadataKind) -> Version {
+        *self.inner.write_watches[metadata_kind].receive.borrow()
+    }
+
+    fn request_metadata_from_peer(
+        &self,
+        peer: GenerationalNodeId,
+        metadata_kind: MetadataKind,
+        min_version: Version,
+    ) {
+        let _ = task_center().spawn_child(
+            crate::TaskKind::Disposable,
+            "request-metadata-from-peer",
+            None,
+            {
+                let networking = self.networking.clone();
+                async move {
+                    networking
+                        .send(
+                            peer.into(),
+                            &MetadataMessage::GetMetadataRequest(GetMetadataRequest {
+                                metadata_kind,
+                                min_version: Some(min_version),
+                            }),
+                        )
+                        .await?;
+                    Ok(())
+                }
+            },
+        );
+    }
+
+    fn fetch_from_metadata_store(&self, metadata_kind: MetadataKind) {
+        match metadata_kind {
+            MetadataKind::NodesConfiguration => {
+                self.spawn_metadata_store_fetch::<NodesConfiguration>(NODES_CONFIG_KEY.clone())
+            }
+            MetadataKind::Schema => {
+                self.spawn_metadata_store_fetch::<SchemaInformation>(SCHEMA_INFORMATION_KEY.clone())
+            }
+            MetadataKind::PartitionTable => {
+                self.spawn_metadata_store_fetch::<PartitionTable>(PARTITION_TABLE_KEY.clone())
+            }
+            MetadataKind::Logs => {
+                self.spawn_metadata_store_fetch::<Logs>(BIFROST_CONFIG_KEY.clone())
+            }
+        }
+    }
+
+    fn spawn_metadata_store_fetch<T>(&self, key: ByteString)
+    where
+        T: Into<MetadataContainer> + Versioned + DeserializeOwned + Send + 'static,
+    {
+        let _ = task_center().spawn_child(
+            crate::TaskKind::Disposable,
+            "fetch-metadata-from-store",
+            None,
+            {
+                let metadata_store_client = self.metadata_store_client.clone();
+                let sender = self.self_sender.clone();
+                async move {
+                    if let Some(value) = metadata_store_client.get::<T>(key).await? {
+                        let _ = sender.send(Command::UpdateMetadata(value.into(), None));
+                    }
+                    Ok(())
+                }
+            },
+        );
+    }
+
     fn update_metadata(&mut self, value: MetadataContainer, callback: Option<oneshot::Sender<()>>) {
         match value {
             MetadataContainer::NodesConfiguration(config) => {
@@ -226,6 +470,12 @@ where
             MetadataContainer::PartitionTable(partition_table) => {
                 self.update_partition_table(partition_table);
             }
+            MetadataContainer::Logs(logs) => {
+                self.update_logs(logs);
+            }
+            MetadataContainer::Schema(schema_information) => {
+                self.update_schema_information(schema_information);
+            }
         }
 
         if let Some(callback) = callback {
@@ -239,12 +489,25 @@ where
         self.notify_watches(maybe_new_version, MetadataKind::NodesConfiguration);
     }
 
-    fn update_partition_table(&mut self, partition_table: FixedPartitionTable) {
+    fn update_partition_table(&mut self, partition_table: PartitionTable) {
         let maybe_new_version = Self::update_internal(&self.inner.partition_table, partition_table);
 
         self.notify_watches(maybe_new_version, MetadataKind::PartitionTable);
     }
 
+    fn update_logs(&mut self, logs: Logs) {
+        let maybe_new_version = Self::update_internal(&self.inner.logs, logs);
+
+        self.notify_watches(maybe_new_version, MetadataKind::Logs);
+    }
+
+    fn update_schema_information(&mut self, schema_information: SchemaInformation) {
+        let maybe_new_version =
+            Self::update_internal(&self.inner.schema_information, schema_information);
+
+        self.notify_watches(maybe_new_version, MetadataKind::Schema);
+    }
+
     fn update_internal<T: Versioned>(container: &ArcSwapOption<T>, new_value: T) -> Version {
         let current_value = container.load();
         let mut maybe_new_version = new_value.version();
@@ -289,13 +552,18 @@ mod tests {
 
     use super::*;
 
+    use bytes::Bytes;
     use googletest::prelude::*;
+    use restate_node_protocol::codec::WireSerde;
+    use restate_node_protocol::common::CURRENT_PROTOCOL_VERSION;
+    use restate_node_protocol::node::message::{self, BinaryMessage};
     use restate_test_util::assert_eq;
     use restate_types::net::AdvertisedAddress;
     use restate_types::nodes_config::{NodeConfig, Role};
     use restate_types::{GenerationalNodeId, Version};
 
     use crate::metadata::spawn_metadata_manager;
+    use crate::metadata_store::Precondition;
     use crate::test_env::MockNetworkSender;
     use crate::TaskCenterFactory;
 
@@ -313,7 +581,7 @@ mod tests {
     #[tokio::test]
     async fn test_partition_table_updates() -> Result<()> {
         test_updates(
-            FixedPartitionTable::new(Version::MIN, 42),
+            PartitionTable::new(Version::MIN, 42),
             MetadataKind::PartitionTable,
             |metadata| metadata.partition_table_version(),
             |value, version| value.set_version(version),
@@ -334,7 +602,8 @@ mod tests {
     {
         let network_sender = MockNetworkSender::default();
         let tc = TaskCenterFactory::create(tokio::runtime::Handle::current());
-        let metadata_manager = MetadataManager::build(network_sender);
+        let metadata_manager =
+            MetadataManager::build(network_sender, MetadataStoreClient::new_in_memory());
         let metadata_writer = metadata_manager.writer();
         let metadata = metadata_manager.metadata();
 
@@ -378,6 +647,148 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_metadata_store_updates() -> Result<()> {
+        let tc = TaskCenterFactory::create(tokio::runtime::Handle::current());
+        let metadata_store_client = MetadataStoreClient::new_in_memory();
+        let metadata_manager =
+            MetadataManager::build(MockNetworkSender::default(), metadata_store_client.clone());
+        let metadata = metadata_manager.metadata();
+
+        // values which are stored before the metadata manager starts are picked up
+        let partition_table = PartitionTable::new(Version::MIN, 42);
+        metadata_store_client
+            .put(
+                PARTITION_TABLE_KEY.clone(),
+                partition_table,
+                Precondition::None,
+            )
+            .await?;
+
+        spawn_metadata_manager(&tc, metadata_manager)?;
+
+        let version = metadata
+            .wait_for_version(MetadataKind::PartitionTable, Version::MIN)
+            .await?;
+        assert_eq!(Version::MIN, version);
+
+        // as well as values which are stored later on
+        let mut nodes_config = create_mock_nodes_config();
+        metadata_store_client
+            .put(
+                NODES_CONFIG_KEY.clone(),
+                nodes_config.clone(),
+                Precondition::DoesNotExist,
+            )
+            .await?;
+        let previous_version = nodes_config.version();
+        nodes_config.increment_version();
+        metadata_store_client
+            .put(
+                NODES_CONFIG_KEY.clone(),
+                nodes_config.clone(),
+                Precondition::MatchesVersion(previous_version),
+            )
+            .await?;
+
+        let version = metadata
+            .wait_for_version(MetadataKind::NodesConfiguration, nodes_config.version())
+            .await?;
+        assert_eq!(nodes_config.version(), version);
+        assert_eq!(nodes_config.version(), metadata.nodes_config_version());
+
+        let schema_information = SchemaInformation::new(Version::MIN, Bytes::from_static(b"42"));
+        metadata_store_client
+            .put(
+                SCHEMA_INFORMATION_KEY.clone(),
+                schema_information.clone(),
+                Precondition::DoesNotExist,
+            )
+            .await?;
+
+        metadata
+            .wait_for_version(MetadataKind::Schema, Version::MIN)
+            .await?;
+        assert_eq!(schema_information, *metadata.schema_information());
+
+        tc.cancel_tasks(None, None).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_observed_version_is_requested_from_peer() -> Result<()> {
+        let tc = TaskCenterFactory::create(tokio::runtime::Handle::current());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let metadata_manager = MetadataManager::build(
+            MockNetworkSender::from_sender(tx),
+            MetadataStoreClient::new_in_memory(),
+        );
+        let metadata = metadata_manager.metadata();
+        // the mock network sender announces the metadata versions of the global metadata
+        tc.try_set_global_metadata(metadata.clone());
+
+        spawn_metadata_manager(&tc, metadata_manager)?;
+
+        let peer = GenerationalNodeId::new(2, 1);
+        metadata.notify_observed_version(
+            MetadataKind::PartitionTable,
+            Version::from(3),
+            Some(peer),
+        );
+        // observing the same version again doesn't request it again
+        metadata.notify_observed_version(
+            MetadataKind::PartitionTable,
+            Version::from(3),
+            Some(peer),
+        );
+
+        let (to, message) = rx.recv().await.expect("request is sent");
+        assert_eq!(peer, to);
+        let Some(message::Body::Encoded(BinaryMessage { payload, .. })) = message.body else {
+            panic!("expected an encoded message");
+        };
+        let MetadataMessage::GetMetadataRequest(request) =
+            MetadataMessage::decode(payload, CURRENT_PROTOCOL_VERSION)?
+        else {
+            panic!("expected a metadata request");
+        };
+        assert_eq!(MetadataKind::PartitionTable, request.metadata_kind);
+        assert_eq!(Some(Version::from(3)), request.min_version);
+        assert!(rx.try_recv().is_err());
+
+        tc.cancel_tasks(None, None).await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_observed_version_is_fetched_from_metadata_store() -> Result<()> {
+        let tc = TaskCenterFactory::create(tokio::runtime::Handle::current());
+        let metadata_store_client = MetadataStoreClient::new_in_memory();
+        let metadata_manager =
+            MetadataManager::build(MockNetworkSender::default(), metadata_store_client.clone());
+        let metadata = metadata_manager.metadata();
+
+        // logs are not watched in the metadata store
+        let mut logs = create_mock_logs();
+        logs.set_version(Version::from(2));
+        metadata_store_client
+            .put(BIFROST_CONFIG_KEY.clone(), logs, Precondition::None)
+            .await?;
+
+        spawn_metadata_manager(&tc, metadata_manager)?;
+        assert_eq!(Version::INVALID, metadata.logs_version());
+
+        metadata.notify_observed_version(MetadataKind::Logs, Version::from(2), None);
+
+        let version = metadata
+            .wait_for_version(MetadataKind::Logs, Version::from(2))
+            .await?;
+        assert_eq!(Version::from(2), version);
+
+        tc.cancel_tasks(None, None).await;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_nodes_config_watchers() -> Result<()> {
         test_watchers(
@@ -392,7 +803,7 @@ mod tests {
     #[tokio::test]
     async fn test_partition_table_watchers() -> Result<()> {
         test_watchers(
-            FixedPartitionTable::new(Version::MIN, 42),
+            PartitionTable::new(Version::MIN, 42),
             MetadataKind::PartitionTable,
             |metadata| metadata.partition_table_version(),
             |value| value.increment_version(),
@@ -400,6 +811,17 @@ mod tests {
         .await
     }
 
+    #[tokio::test]
+    async fn test_logs_watchers() -> Result<()> {
+        test_watchers(
+            create_mock_logs(),
+            MetadataKind::Logs,
+            |metadata| metadata.logs_version(),
+            |value| value.increment_version(),
+        )
+        .await
+    }
+
     async fn test_watchers<T, F, I>(
         value: T,
         kind: MetadataKind,
@@ -413,7 +835,8 @@ mod tests {
     {
         let network_sender = MockNetworkSender::default();
         let tc = TaskCenterFactory::create(tokio::runtime::Handle::current());
-        let metadata_manager = MetadataManager::build(network_sender);
+        let metadata_manager =
+            MetadataManager::build(network_sender, MetadataStoreClient::new_in_memory());
         let metadata_writer = metadata_manager.writer();
         let metadata = metadata_manager.metadata();
 
@@ -467,4 +890,10 @@ mod tests {
         nodes_config.upsert_node(my_node);
         nodes_config
     }
+
+    fn create_mock_logs() -> Logs {
+        let mut logs = Logs::empty();
+        logs.set_version(Version::MIN);
+        logs
+    }
 }
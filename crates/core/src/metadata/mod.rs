@@ -21,10 +21,13 @@ use enum_map::EnumMap;
 use tokio::sync::{oneshot, watch};
 
 use restate_node_protocol::metadata::{MetadataContainer, MetadataKind};
+use restate_types::logs::metadata::Logs;
 use restate_types::nodes_config::NodesConfiguration;
-use restate_types::partition_table::FixedPartitionTable;
-use restate_types::{GenerationalNodeId, Version};
+use restate_types::partition_table::PartitionTable;
+use restate_types::schema::SchemaInformation;
+use restate_types::{GenerationalNodeId, Version, Versioned};
 
+use crate::metadata_store::MetadataStoreClient;
 use crate::network::NetworkSender;
 use crate::{ShutdownError, TaskCenter, TaskId, TaskKind};
 
@@ -63,7 +66,7 @@ impl Metadata {
 
     /// Panics if partition table is not loaded yet.
     #[track_caller]
-    pub fn partition_table(&self) -> Arc<FixedPartitionTable> {
+    pub fn partition_table(&self) -> Arc<PartitionTable> {
         self.inner
             .partition_table
             .load_full()
@@ -79,6 +82,75 @@ impl Metadata {
         }
     }
 
+    /// Panics if logs metadata is not loaded yet.
+    #[track_caller]
+    pub fn logs(&self) -> Arc<Logs> {
+        self.inner
+            .logs
+            .load_full()
+            .expect("logs metadata is loaded")
+    }
+
+    /// Returns Version::INVALID if logs metadata has not been loaded yet.
+    pub fn logs_version(&self) -> Version {
+        let c = self.inner.logs.load();
+        match c.as_deref() {
+            Some(c) => c.version(),
+            None => Version::INVALID,
+        }
+    }
+
+    /// Panics if schema information is not loaded yet.
+    #[track_caller]
+    pub fn schema_information(&self) -> Arc<SchemaInformation> {
+        self.inner
+            .schema_information
+            .load_full()
+            .expect("schema information is loaded")
+    }
+
+    /// Returns Version::INVALID if schema information has not been loaded yet.
+    pub fn schema_information_version(&self) -> Version {
+        let c = self.inner.schema_information.load();
+        match c.as_deref() {
+            Some(c) => c.version(),
+            None => Version::INVALID,
+        }
+    }
+
+    /// Returns the version of the given metadata kind, Version::INVALID if it has not been loaded
+    /// yet.
+    pub fn version(&self, metadata_kind: MetadataKind) -> Version {
+        match metadata_kind {
+            MetadataKind::NodesConfiguration => self.nodes_config_version(),
+            MetadataKind::Schema => self.schema_information_version(),
+            MetadataKind::PartitionTable => self.partition_table_version(),
+            MetadataKind::Logs => self.logs_version(),
+        }
+    }
+
+    /// Tells the metadata manager that a newer version of the metadata kind exists, e.g. because
+    /// a peer has announced it. The metadata manager syncs the newer version from the peer, if
+    /// given, or from the metadata store.
+    pub fn notify_observed_version(
+        &self,
+        metadata_kind: MetadataKind,
+        version: Version,
+        peer: Option<GenerationalNodeId>,
+    ) {
+        if version <= self.version(metadata_kind) {
+            return;
+        }
+
+        // Ignore the error, task-center takes care of safely shutting down the
+        // system if metadata manager failed
+        let _ = self.sender.send(manager::Command::ObservedVersion {
+            metadata_kind,
+            version,
+            peer,
+        });
+    }
+
     // Returns when the metadata kind is at the provided version (or newer)
     pub async fn wait_for_version(
         &self,
@@ -103,7 +175,9 @@ impl Metadata {
 struct MetadataInner {
     my_node_id: OnceLock<GenerationalNodeId>,
     nodes_config: ArcSwapOption<NodesConfiguration>,
-    partition_table: ArcSwapOption<FixedPartitionTable>,
+    partition_table: ArcSwapOption<PartitionTable>,
+    logs: ArcSwapOption<Logs>,
+    schema_information: ArcSwapOption<SchemaInformation>,
     write_watches: EnumMap<MetadataKind, VersionWatch>,
 }
 
@@ -117,11 +191,26 @@ pub struct MetadataWriter {
     /// strictly used to set my node id. Do not use this to update metadata
     /// directly to avoid race conditions.
     inner: Arc<MetadataInner>,
+    metadata_store_client: MetadataStoreClient,
 }
 
 impl MetadataWriter {
-    fn new(sender: manager::CommandSender, inner: Arc<MetadataInner>) -> Self {
-        Self { sender, inner }
+    fn new(
+        sender: manager::CommandSender,
+        inner: Arc<MetadataInner>,
+        metadata_store_client: MetadataStoreClient,
+    ) -> Self {
+        Self {
+            sender,
+            inner,
+            metadata_store_client,
+        }
+    }
+
+    /// The client of the metadata store that holds the durable copy of the metadata. Writers
+    /// update the metadata store first and then the locally cached metadata.
+    pub fn metadata_store_client(&self) -> &MetadataStoreClient {
+        &self.metadata_store_client
     }
 
     // Returns when the nodes configuration update is performed.
@@ -0,0 +1,943 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use bytestring::ByteString;
+use futures::stream::BoxStream;
+use futures::{future, StreamExt, TryStreamExt};
+use restate_types::errors::GenericError;
+use restate_types::retries::RetryPolicy;
+use restate_types::{Version, Versioned};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::debug;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReadError {
+    #[error("network error: {0}")]
+    Network(GenericError),
+    #[error("internal error: {0}")]
+    Internal(String),
+    #[error("codec error: {0}")]
+    Codec(GenericError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WriteError {
+    #[error("failed precondition: {0}")]
+    FailedPrecondition(String),
+    #[error("network error: {0}")]
+    Network(GenericError),
+    #[error("internal error: {0}")]
+    Internal(String),
+    #[error("codec error: {0}")]
+    Codec(GenericError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransactionError {
+    #[error("failed preconditions: {}", display_failed_preconditions(.0))]
+    FailedPreconditions(Vec<FailedPrecondition>),
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("network error: {0}")]
+    Network(GenericError),
+    #[error("internal error: {0}")]
+    Internal(String),
+    #[error("codec error: {0}")]
+    Codec(GenericError),
+}
+
+fn display_failed_preconditions(failed_preconditions: &[FailedPrecondition]) -> String {
+    failed_preconditions
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct VersionedValue {
+    pub version: Version,
+    pub value: Bytes,
+}
+
+impl VersionedValue {
+    pub fn new(version: Version, value: Bytes) -> Self {
+        Self { version, value }
+    }
+}
+
+/// Preconditions for the write operations of the [`MetadataStore`].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub enum Precondition {
+    /// No precondition
+    None,
+    /// Key-value pair must not exist for the write operation to succeed.
+    DoesNotExist,
+    /// Key-value pair must have the provided [`Version`] for the write operation to succeed.
+    MatchesVersion(Version),
+}
+
+impl Precondition {
+    /// Returns whether the precondition holds for a key-value pair with the given current version.
+    pub fn holds(&self, current_version: Option<Version>) -> bool {
+        match self {
+            Precondition::None => true,
+            Precondition::DoesNotExist => current_version.is_none(),
+            Precondition::MatchesVersion(version) => current_version == Some(*version),
+        }
+    }
+}
+
+/// Operation on a key-value pair as part of a [`MetadataStore::transaction`].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub enum TransactionOperation {
+    /// Puts the versioned value.
+    Put(VersionedValue),
+    /// Deletes the key-value pair. Deleting a non-existing key-value pair is a no-op.
+    Delete,
+}
+
+impl TransactionOperation {
+    /// Puts the given [`Versioned`] value, see [`MetadataStoreClient::put`].
+    pub fn put<T: Versioned + Serialize>(value: &T) -> Result<Self, TransactionError> {
+        encode(value)
+            .map(TransactionOperation::Put)
+            .map_err(TransactionError::Codec)
+    }
+}
+
+/// Precondition of a [`MetadataStore::transaction`] which did not hold.
+#[derive(Debug, Clone, thiserror::Error, serde::Serialize, serde::Deserialize)]
+#[error("key '{key}' expected {precondition:?} but found version '{current_version:?}'")]
+pub struct FailedPrecondition {
+    pub key: ByteString,
+    pub precondition: Precondition,
+    pub current_version: Option<Version>,
+}
+
+impl FailedPrecondition {
+    pub fn new(
+        key: ByteString,
+        precondition: Precondition,
+        current_version: Option<Version>,
+    ) -> Self {
+        Self {
+            key,
+            precondition,
+            current_version,
+        }
+    }
+}
+
+/// Operations of a [`MetadataStore::transaction`], each on a key with the precondition which the
+/// key-value pair must fulfill.
+pub type TransactionOperations = Vec<(ByteString, Precondition, TransactionOperation)>;
+
+/// Returns a key which is targeted by more than one operation of a transaction.
+pub fn find_duplicate_key(operations: &TransactionOperations) -> Option<&ByteString> {
+    let mut keys = std::collections::HashSet::with_capacity(operations.len());
+    operations
+        .iter()
+        .map(|(key, _, _)| key)
+        .find(|key| !keys.insert(*key))
+}
+
+/// Change of a key-value pair which is observed by a watch. Deleted key-value pairs have no value.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WatchEvent {
+    pub key: ByteString,
+    pub value: Option<VersionedValue>,
+}
+
+impl WatchEvent {
+    pub fn new(key: ByteString, value: Option<VersionedValue>) -> Self {
+        Self { key, value }
+    }
+}
+
+pub type WatchStream = BoxStream<'static, Result<WatchEvent, ReadError>>;
+
+/// Version of the encoding of [`MetadataStoreSnapshot`]s. It needs to be increased whenever the
+/// encoding changes in an incompatible way.
+const SNAPSHOT_FORMAT_VERSION: u16 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotCodecError {
+    #[error("snapshot is missing its format version")]
+    MissingFormatVersion,
+    #[error("snapshot has incompatible format version '{0}'; supported version is '{SNAPSHOT_FORMAT_VERSION}'")]
+    IncompatibleFormatVersion(u16),
+    #[error("codec error: {0}")]
+    Codec(GenericError),
+}
+
+/// Consistent copy of all key-value pairs of a [`MetadataStore`]. Its encoding is portable, so it
+/// can be written to a file and later be restored into a fresh metadata store, see
+/// [`MetadataStore::restore`].
+#[derive(Debug, Clone, Default)]
+pub struct MetadataStoreSnapshot {
+    kv_pairs: Vec<(ByteString, VersionedValue)>,
+}
+
+impl MetadataStoreSnapshot {
+    pub fn new(kv_pairs: Vec<(ByteString, VersionedValue)>) -> Self {
+        Self { kv_pairs }
+    }
+
+    pub fn kv_pairs(&self) -> &[(ByteString, VersionedValue)] {
+        &self.kv_pairs
+    }
+
+    pub fn into_kv_pairs(self) -> Vec<(ByteString, VersionedValue)> {
+        self.kv_pairs
+    }
+
+    /// Encodes the snapshot prefixed with the big-endian [`SNAPSHOT_FORMAT_VERSION`].
+    pub fn encode(&self) -> Result<Bytes, SnapshotCodecError> {
+        let mut bytes = SNAPSHOT_FORMAT_VERSION.to_be_bytes().to_vec();
+        bincode::serde::encode_into_std_write(
+            &self.kv_pairs,
+            &mut bytes,
+            bincode::config::standard(),
+        )
+        .map_err(|err| SnapshotCodecError::Codec(err.into()))?;
+        Ok(bytes.into())
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, SnapshotCodecError> {
+        if bytes.len() < 2 {
+            return Err(SnapshotCodecError::MissingFormatVersion);
+        }
+        let (format_version, kv_pairs) = bytes.split_at(2);
+
+        let format_version = u16::from_be_bytes([format_version[0], format_version[1]]);
+        if format_version != SNAPSHOT_FORMAT_VERSION {
+            return Err(SnapshotCodecError::IncompatibleFormatVersion(
+                format_version,
+            ));
+        }
+
+        let (kv_pairs, _) =
+            bincode::serde::decode_from_slice(kv_pairs, bincode::config::standard())
+                .map_err(|err| SnapshotCodecError::Codec(err.into()))?;
+        Ok(Self { kv_pairs })
+    }
+}
+
+/// Creates the [`WatchStream`] of a metadata store implementation which broadcasts all changes.
+/// The stream yields the `snapshot` of the current key-value pairs followed by the `changes` of
+/// the key-value pairs with the given prefix. The `changes` receiver must have been subscribed
+/// before the snapshot was taken.
+pub fn broadcast_watch_stream(
+    prefix: ByteString,
+    snapshot: Vec<(ByteString, VersionedValue)>,
+    changes: broadcast::Receiver<WatchEvent>,
+) -> WatchStream {
+    let snapshot = futures::stream::iter(
+        snapshot
+            .into_iter()
+            .map(|(key, value)| Ok(WatchEvent::new(key, Some(value)))),
+    );
+
+    let changes = futures::stream::unfold(Some(changes), move |changes| {
+        let prefix = prefix.clone();
+        async move {
+            let mut changes = changes?;
+            loop {
+                match changes.recv().await {
+                    Ok(event) if event.key.starts_with(&*prefix) => {
+                        return Some((Ok(event), Some(changes)))
+                    }
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(missed)) => {
+                        // the watcher missed changes, it needs to watch again to get a snapshot
+                        return Some((
+                            Err(ReadError::Internal(format!(
+                                "watch fell behind by {missed} changes"
+                            ))),
+                            None,
+                        ));
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }
+    });
+
+    snapshot.chain(changes).boxed()
+}
+
+/// Metadata store abstraction. The metadata store implementations need to support linearizable
+/// reads and atomic compare and swap operations.
+#[async_trait]
+pub trait MetadataStore {
+    /// Gets the value and its current version for the given key. If key-value pair is not present,
+    /// then return [`None`].
+    async fn get(&self, key: ByteString) -> Result<Option<VersionedValue>, ReadError>;
+
+    /// Gets the current version for the given key. If key-value pair is not present, then return
+    /// [`None`].
+    async fn get_version(&self, key: ByteString) -> Result<Option<Version>, ReadError>;
+
+    /// Puts the versioned value under the given key following the provided precondition. If the
+    /// precondition is not met, then the operation returns a [`WriteError::PreconditionViolation`].
+    async fn put(
+        &self,
+        key: ByteString,
+        value: VersionedValue,
+        precondition: Precondition,
+    ) -> Result<(), WriteError>;
+
+    /// Deletes the key-value pair for the given key following the provided precondition. If the
+    /// precondition is not met, then the operation returns a [`WriteError::PreconditionViolation`].
+    async fn delete(&self, key: ByteString, precondition: Precondition) -> Result<(), WriteError>;
+
+    /// Atomically applies the operations if all of their preconditions hold, otherwise none of
+    /// them is applied and the operation returns a [`TransactionError::FailedPreconditions`]
+    /// with every precondition that did not hold. Every key may only occur once per transaction.
+    async fn transaction(&self, operations: TransactionOperations) -> Result<(), TransactionError>;
+
+    /// Lists the key-value pairs whose keys start with the given prefix, ordered by their keys.
+    async fn list(
+        &self,
+        prefix: ByteString,
+    ) -> Result<Vec<(ByteString, VersionedValue)>, ReadError>;
+
+    /// Watches the key-value pairs whose keys start with the given prefix. The stream yields the
+    /// current key-value pairs first and then every change of them. Watchers are not guaranteed
+    /// to observe every intermediate version, but they eventually observe the latest one. If a
+    /// watcher falls behind, its stream ends with an error and the watch needs to be restarted.
+    async fn watch_prefix(&self, prefix: ByteString) -> Result<WatchStream, ReadError>;
+
+    /// Takes a consistent snapshot of all key-value pairs.
+    async fn snapshot(&self) -> Result<MetadataStoreSnapshot, ReadError>;
+
+    /// Restores the key-value pairs of the snapshot. The metadata store must be empty, otherwise
+    /// the operation returns a [`WriteError::FailedPrecondition`].
+    async fn restore(&self, snapshot: MetadataStoreSnapshot) -> Result<(), WriteError>;
+
+    /// Watches the key-value pair with the given key, see [`MetadataStore::watch_prefix`].
+    async fn watch(&self, key: ByteString) -> Result<WatchStream, ReadError> {
+        let stream = self.watch_prefix(key.clone()).await?;
+        Ok(stream
+            .try_filter(move |event| future::ready(event.key == key))
+            .boxed())
+    }
+}
+
+/// Metadata store client which allows storing [`Versioned`] values into a [`MetadataStore`].
+#[derive(Clone)]
+pub struct MetadataStoreClient {
+    // premature optimization? Maybe introduce trait object once we have multiple implementations?
+    inner: Arc<dyn MetadataStore + Send + Sync>,
+}
+
+impl std::fmt::Debug for MetadataStoreClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MetadataStoreClient")
+            .finish_non_exhaustive()
+    }
+}
+
+impl MetadataStoreClient {
+    pub fn new<S>(metadata_store: S) -> Self
+    where
+        S: MetadataStore + Send + Sync + 'static,
+    {
+        Self {
+            inner: Arc::new(metadata_store),
+        }
+    }
+
+    /// Creates a client for an in-memory metadata store, for testing.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn new_in_memory() -> Self {
+        Self::new(test_util::InMemoryMetadataStore::default())
+    }
+
+    pub async fn get<T: Versioned + DeserializeOwned>(
+        &self,
+        key: ByteString,
+    ) -> Result<Option<T>, ReadError> {
+        let value = self.inner.get(key).await?;
+        value.map(Self::decode).transpose()
+    }
+
+    pub async fn get_version(&self, key: ByteString) -> Result<Option<Version>, ReadError> {
+        self.inner.get_version(key).await
+    }
+
+    pub async fn put<T>(
+        &self,
+        key: ByteString,
+        value: T,
+        precondition: Precondition,
+    ) -> Result<(), WriteError>
+    where
+        T: Versioned + Serialize,
+    {
+        let value = encode(&value).map_err(WriteError::Codec)?;
+        self.inner.put(key, value, precondition).await
+    }
+
+    pub async fn delete(
+        &self,
+        key: ByteString,
+        precondition: Precondition,
+    ) -> Result<(), WriteError> {
+        self.inner.delete(key, precondition).await
+    }
+
+    /// Atomically applies all operations or none of them, see [`MetadataStore::transaction`].
+    /// Use [`TransactionOperation::put`] to put [`Versioned`] values.
+    pub async fn transaction(
+        &self,
+        operations: TransactionOperations,
+    ) -> Result<(), TransactionError> {
+        self.inner.transaction(operations).await
+    }
+
+    /// Lists the values whose keys start with the given prefix, ordered by their keys.
+    pub async fn list<T: Versioned + DeserializeOwned>(
+        &self,
+        prefix: ByteString,
+    ) -> Result<Vec<(ByteString, T)>, ReadError> {
+        self.inner
+            .list(prefix)
+            .await?
+            .into_iter()
+            .map(|(key, value)| Ok((key, Self::decode(value)?)))
+            .collect()
+    }
+
+    /// Watches the value of the given key. The stream yields the current value first, if it
+    /// exists, and then every change. Deletions are yielded as [`None`]. See
+    /// [`MetadataStore::watch_prefix`] for the guarantees of watches.
+    pub async fn watch<T: Versioned + DeserializeOwned + Send + 'static>(
+        &self,
+        key: ByteString,
+    ) -> Result<BoxStream<'static, Result<Option<T>, ReadError>>, ReadError> {
+        let stream = self.inner.watch(key).await?;
+        Ok(stream
+            .and_then(|event| future::ready(event.value.map(Self::decode).transpose()))
+            .boxed())
+    }
+
+    /// Watches the values whose keys start with the given prefix. The stream yields the current
+    /// values first and then every change. Deletions are yielded as [`None`]. See
+    /// [`MetadataStore::watch_prefix`] for the guarantees of watches.
+    pub async fn watch_prefix<T: Versioned + DeserializeOwned + Send + 'static>(
+        &self,
+        prefix: ByteString,
+    ) -> Result<BoxStream<'static, Result<(ByteString, Option<T>), ReadError>>, ReadError> {
+        let stream = self.inner.watch_prefix(prefix).await?;
+        Ok(stream
+            .and_then(|event| {
+                future::ready(
+                    event
+                        .value
+                        .map(Self::decode)
+                        .transpose()
+                        .map(|value| (event.key, value)),
+                )
+            })
+            .boxed())
+    }
+
+    /// Takes a consistent snapshot of all key-value pairs, see [`MetadataStore::snapshot`].
+    pub async fn snapshot(&self) -> Result<MetadataStoreSnapshot, ReadError> {
+        self.inner.snapshot().await
+    }
+
+    /// Restores the snapshot into an empty metadata store, see [`MetadataStore::restore`].
+    pub async fn restore(&self, snapshot: MetadataStoreSnapshot) -> Result<(), WriteError> {
+        self.inner.restore(snapshot).await
+    }
+
+    fn decode<T: Versioned + DeserializeOwned>(
+        versioned_value: VersionedValue,
+    ) -> Result<T, ReadError> {
+        // todo add proper format version
+        let (value, _) = bincode::serde::decode_from_slice::<T, _>(
+            versioned_value.value.as_ref(),
+            bincode::config::standard(),
+        )
+        .map_err(|err| ReadError::Codec(err.into()))?;
+
+        assert_eq!(
+            versioned_value.version,
+            value.version(),
+            "versions must align"
+        );
+
+        Ok(value)
+    }
+
+    pub async fn read_modify_write<T, F>(
+        &self,
+        key: ByteString,
+        mut modify: F,
+    ) -> Result<T, ReadModifyWriteError>
+    where
+        T: Versioned + Serialize + DeserializeOwned + Clone,
+        F: FnMut(Option<T>) -> Operation<T>,
+    {
+        let max_backoff = Duration::from_millis(100);
+        let mut backoff_policy = RetryPolicy::exponential(
+            Duration::from_millis(10),
+            2.0,
+            usize::MAX,
+            Some(max_backoff),
+        )
+        .into_iter();
+
+        loop {
+            let value = self.get::<T>(key.clone()).await?;
+
+            let precondition = value
+                .as_ref()
+                .map(|c| Precondition::MatchesVersion(c.version()))
+                .unwrap_or(Precondition::DoesNotExist);
+
+            let result = modify(value);
+
+            match result {
+                Operation::Upsert(value) => {
+                    match self.put(key.clone(), value.clone(), precondition).await {
+                        Ok(()) => return Ok(value),
+                        Err(WriteError::FailedPrecondition(msg)) => {
+                            let backoff = backoff_policy.next().unwrap_or(max_backoff);
+                            debug!(
+                                "concurrent value update: {msg}; retrying in '{}'",
+                                humantime::format_duration(backoff)
+                            );
+                            tokio::time::sleep(backoff).await;
+                        }
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+                Operation::Return(value) => return Ok(value),
+                Operation::Fail(msg) => return Err(ReadModifyWriteError::FailedOperation(msg)),
+            }
+        }
+    }
+}
+
+fn encode<T: Versioned + Serialize>(value: &T) -> Result<VersionedValue, GenericError> {
+    // todo add proper format version
+    let bytes = bincode::serde::encode_to_vec(value, bincode::config::standard())?;
+    Ok(VersionedValue::new(value.version(), bytes.into()))
+}
+
+pub enum Operation<T> {
+    /// Upsert the provided value and return if successful.
+    Upsert(T),
+    /// Return the provided value w/o upserting it.
+    Return(T),
+    /// Fail the read modify write call.
+    Fail(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReadModifyWriteError {
+    #[error("network error: {0}")]
+    Network(GenericError),
+    #[error("internal error: {0}")]
+    Internal(String),
+    #[error("codec error: {0}")]
+    Codec(GenericError),
+    #[error("failed read-modify-write operation: {0}")]
+    FailedOperation(String),
+}
+
+impl From<ReadError> for ReadModifyWriteError {
+    fn from(value: ReadError) -> Self {
+        match value {
+            ReadError::Network(err) => ReadModifyWriteError::Network(err),
+            ReadError::Internal(msg) => ReadModifyWriteError::Internal(msg),
+            ReadError::Codec(err) => ReadModifyWriteError::Codec(err),
+        }
+    }
+}
+
+impl From<WriteError> for ReadModifyWriteError {
+    fn from(value: WriteError) -> Self {
+        match value {
+            WriteError::FailedPrecondition(_) => {
+                unreachable!("failed preconditions should be treated separately")
+            }
+            WriteError::Network(err) => ReadModifyWriteError::Network(err),
+            WriteError::Internal(msg) => ReadModifyWriteError::Internal(msg),
+            WriteError::Codec(err) => ReadModifyWriteError::Codec(err),
+        }
+    }
+}
+
+static_assertions::assert_impl_all!(MetadataStoreClient: Send, Sync, Clone);
+
+#[cfg(any(test, feature = "test-util"))]
+mod test_util {
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use bytestring::ByteString;
+    use restate_types::Version;
+    use tokio::sync::broadcast;
+
+    use super::{
+        broadcast_watch_stream, find_duplicate_key, FailedPrecondition, MetadataStore,
+        MetadataStoreSnapshot, Precondition, ReadError, TransactionError, TransactionOperation,
+        TransactionOperations, VersionedValue, WatchEvent, WatchStream, WriteError,
+    };
+
+    /// A metadata store that keeps its key-value pairs in memory.
+    #[derive(Debug)]
+    pub struct InMemoryMetadataStore {
+        kv_pairs: Mutex<BTreeMap<ByteString, VersionedValue>>,
+        changes: broadcast::Sender<WatchEvent>,
+    }
+
+    impl Default for InMemoryMetadataStore {
+        fn default() -> Self {
+            Self {
+                kv_pairs: Mutex::default(),
+                changes: broadcast::channel(64).0,
+            }
+        }
+    }
+
+    impl InMemoryMetadataStore {
+        fn check_precondition(
+            current_version: Option<Version>,
+            precondition: Precondition,
+        ) -> Result<(), WriteError> {
+            match precondition {
+                Precondition::None => Ok(()),
+                Precondition::DoesNotExist if current_version.is_none() => Ok(()),
+                Precondition::DoesNotExist => Err(WriteError::FailedPrecondition(
+                    "key-value pair already exists".to_owned(),
+                )),
+                Precondition::MatchesVersion(version) if current_version == Some(version) => Ok(()),
+                Precondition::MatchesVersion(version) => {
+                    Err(WriteError::FailedPrecondition(format!(
+                        "Expected version '{}' but found version '{:?}'",
+                        version, current_version
+                    )))
+                }
+            }
+        }
+
+        fn list_locked(
+            kv_pairs: &BTreeMap<ByteString, VersionedValue>,
+            prefix: &ByteString,
+        ) -> Vec<(ByteString, VersionedValue)> {
+            kv_pairs
+                .range(prefix.clone()..)
+                .take_while(|(key, _)| key.starts_with(&**prefix))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect()
+        }
+    }
+
+    #[async_trait]
+    impl MetadataStore for InMemoryMetadataStore {
+        async fn get(&self, key: ByteString) -> Result<Option<VersionedValue>, ReadError> {
+            Ok(self.kv_pairs.lock().unwrap().get(&key).cloned())
+        }
+
+        async fn get_version(&self, key: ByteString) -> Result<Option<Version>, ReadError> {
+            Ok(self
+                .kv_pairs
+                .lock()
+                .unwrap()
+                .get(&key)
+                .map(|value| value.version))
+        }
+
+        async fn put(
+            &self,
+            key: ByteString,
+            value: VersionedValue,
+            precondition: Precondition,
+        ) -> Result<(), WriteError> {
+            let mut kv_pairs = self.kv_pairs.lock().unwrap();
+            let current_version = kv_pairs.get(&key).map(|value| value.version);
+            Self::check_precondition(current_version, precondition)?;
+            kv_pairs.insert(key.clone(), value.clone());
+            let _ = self.changes.send(WatchEvent::new(key, Some(value)));
+            Ok(())
+        }
+
+        async fn delete(
+            &self,
+            key: ByteString,
+            precondition: Precondition,
+        ) -> Result<(), WriteError> {
+            let mut kv_pairs = self.kv_pairs.lock().unwrap();
+            let current_version = kv_pairs.get(&key).map(|value| value.version);
+            Self::check_precondition(current_version, precondition)?;
+            if kv_pairs.remove(&key).is_some() {
+                let _ = self.changes.send(WatchEvent::new(key, None));
+            }
+            Ok(())
+        }
+
+        async fn transaction(
+            &self,
+            operations: TransactionOperations,
+        ) -> Result<(), TransactionError> {
+            if let Some(key) = find_duplicate_key(&operations) {
+                return Err(TransactionError::InvalidArgument(format!(
+                    "key '{key}' occurs more than once"
+                )));
+            }
+
+            let mut kv_pairs = self.kv_pairs.lock().unwrap();
+            let failed_preconditions: Vec<_> = operations
+                .iter()
+                .filter_map(|(key, precondition, _)| {
+                    let current_version = kv_pairs.get(key).map(|value| value.version);
+                    (!precondition.holds(current_version)).then(|| {
+                        FailedPrecondition::new(key.clone(), precondition.clone(), current_version)
+                    })
+                })
+                .collect();
+            if !failed_preconditions.is_empty() {
+                return Err(TransactionError::FailedPreconditions(failed_preconditions));
+            }
+
+            for (key, _, operation) in operations {
+                match operation {
+                    TransactionOperation::Put(value) => {
+                        kv_pairs.insert(key.clone(), value.clone());
+                        let _ = self.changes.send(WatchEvent::new(key, Some(value)));
+                    }
+                    TransactionOperation::Delete => {
+                        if kv_pairs.remove(&key).is_some() {
+                            let _ = self.changes.send(WatchEvent::new(key, None));
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        async fn list(
+            &self,
+            prefix: ByteString,
+        ) -> Result<Vec<(ByteString, VersionedValue)>, ReadError> {
+            Ok(Self::list_locked(&self.kv_pairs.lock().unwrap(), &prefix))
+        }
+
+        async fn snapshot(&self) -> Result<MetadataStoreSnapshot, ReadError> {
+            Ok(MetadataStoreSnapshot::new(Self::list_locked(
+                &self.kv_pairs.lock().unwrap(),
+                &ByteString::default(),
+            )))
+        }
+
+        async fn restore(&self, snapshot: MetadataStoreSnapshot) -> Result<(), WriteError> {
+            let mut kv_pairs = self.kv_pairs.lock().unwrap();
+            if !kv_pairs.is_empty() {
+                return Err(WriteError::FailedPrecondition(
+                    "metadata store is not empty".to_owned(),
+                ));
+            }
+
+            for (key, value) in snapshot.into_kv_pairs() {
+                kv_pairs.insert(key.clone(), value.clone());
+                let _ = self.changes.send(WatchEvent::new(key, Some(value)));
+            }
+            Ok(())
+        }
+
+        async fn watch_prefix(&self, prefix: ByteString) -> Result<WatchStream, ReadError> {
+            let kv_pairs = self.kv_pairs.lock().unwrap();
+            // subscribe while holding the lock, so that no change gets lost after the snapshot
+            let changes = self.changes.subscribe();
+            let snapshot = Self::list_locked(&kv_pairs, &prefix);
+            Ok(broadcast_watch_stream(prefix, snapshot, changes))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_util::InMemoryMetadataStore;
+    use super::*;
+
+    use bytes::{Bytes, BytesMut};
+    use futures::StreamExt;
+
+    fn value(version: u32) -> VersionedValue {
+        VersionedValue::new(Version::from(version), Bytes::from_static(b"value"))
+    }
+
+    #[tokio::test]
+    async fn list_and_watch_prefix() -> anyhow::Result<()> {
+        let store = InMemoryMetadataStore::default();
+        store
+            .put("a/1".into(), value(1), Precondition::None)
+            .await?;
+        store.put("b".into(), value(1), Precondition::None).await?;
+
+        let listed = store.list("a/".into()).await?;
+        assert_eq!(vec![ByteString::from("a/1")], {
+            listed.into_iter().map(|(key, _)| key).collect::<Vec<_>>()
+        });
+
+        let mut watch = store.watch_prefix("a/".into()).await?;
+        let mut key_watch = store.watch("a/2".into()).await?;
+
+        store
+            .put("a/2".into(), value(1), Precondition::DoesNotExist)
+            .await?;
+        store.put("b".into(), value(2), Precondition::None).await?;
+        store.delete("a/1".into(), Precondition::None).await?;
+
+        let mut events = Vec::new();
+        for _ in 0..3 {
+            let event = watch.next().await.expect("stream is open")?;
+            events.push((event.key, event.value.map(|value| value.version)));
+        }
+        assert_eq!(
+            vec![
+                (ByteString::from("a/1"), Some(Version::from(1))),
+                (ByteString::from("a/2"), Some(Version::from(1))),
+                (ByteString::from("a/1"), None),
+            ],
+            events
+        );
+
+        let event = key_watch.next().await.expect("stream is open")?;
+        assert_eq!(ByteString::from("a/2"), event.key);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn transaction_applies_all_or_nothing() -> anyhow::Result<()> {
+        let store = InMemoryMetadataStore::default();
+        store.put("a".into(), value(1), Precondition::None).await?;
+        store.put("b".into(), value(1), Precondition::None).await?;
+
+        let result = store
+            .transaction(vec![
+                (
+                    "a".into(),
+                    Precondition::MatchesVersion(Version::from(1)),
+                    TransactionOperation::Put(value(2)),
+                ),
+                (
+                    "b".into(),
+                    Precondition::MatchesVersion(Version::from(2)),
+                    TransactionOperation::Delete,
+                ),
+                (
+                    "c".into(),
+                    Precondition::MatchesVersion(Version::from(1)),
+                    TransactionOperation::Put(value(2)),
+                ),
+            ])
+            .await;
+        let Err(TransactionError::FailedPreconditions(failed)) = result else {
+            panic!("expected failed preconditions, got {result:?}");
+        };
+        assert_eq!(
+            vec![
+                (ByteString::from("b"), Some(Version::from(1))),
+                (ByteString::from("c"), None)
+            ],
+            failed
+                .into_iter()
+                .map(|failed| (failed.key, failed.current_version))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(Version::from(1)), store.get_version("a".into()).await?);
+
+        store
+            .transaction(vec![
+                (
+                    "a".into(),
+                    Precondition::MatchesVersion(Version::from(1)),
+                    TransactionOperation::Put(value(2)),
+                ),
+                ("b".into(), Precondition::None, TransactionOperation::Delete),
+                (
+                    "c".into(),
+                    Precondition::DoesNotExist,
+                    TransactionOperation::Put(value(1)),
+                ),
+            ])
+            .await?;
+        assert_eq!(Some(Version::from(2)), store.get_version("a".into()).await?);
+        assert_eq!(None, store.get_version("b".into()).await?);
+        assert_eq!(Some(Version::from(1)), store.get_version("c".into()).await?);
+
+        let result = store
+            .transaction(vec![
+                ("a".into(), Precondition::None, TransactionOperation::Delete),
+                ("a".into(), Precondition::None, TransactionOperation::Delete),
+            ])
+            .await;
+        assert!(matches!(result, Err(TransactionError::InvalidArgument(_))));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn snapshot_restores_into_empty_store() -> anyhow::Result<()> {
+        let store = InMemoryMetadataStore::default();
+        store.put("a".into(), value(1), Precondition::None).await?;
+        store.put("b".into(), value(2), Precondition::None).await?;
+
+        let snapshot = store.snapshot().await?;
+        let snapshot = MetadataStoreSnapshot::decode(&snapshot.encode()?)?;
+
+        let restored_store = InMemoryMetadataStore::default();
+        restored_store.restore(snapshot.clone()).await?;
+        let versions = |kv_pairs: Vec<(ByteString, VersionedValue)>| {
+            kv_pairs
+                .into_iter()
+                .map(|(key, value)| (key, value.version))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec![
+                (ByteString::from("a"), Version::from(1)),
+                (ByteString::from("b"), Version::from(2))
+            ],
+            versions(restored_store.list(ByteString::default()).await?)
+        );
+
+        // only empty stores can be restored
+        assert!(matches!(
+            restored_store.restore(snapshot).await,
+            Err(WriteError::FailedPrecondition(_))
+        ));
+
+        let mut incompatible_snapshot = BytesMut::from(b"\xff\xff".as_slice());
+        incompatible_snapshot.extend_from_slice(&store.snapshot().await?.encode()?[2..]);
+        assert!(matches!(
+            MetadataStoreSnapshot::decode(&incompatible_snapshot),
+            Err(SnapshotCodecError::IncompatibleFormatVersion(u16::MAX))
+        ));
+
+        Ok(())
+    }
+}
@@ -11,6 +11,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use arc_swap::ArcSwap;
 use async_trait::async_trait;
 
 use futures::stream::BoxStream;
@@ -57,12 +58,45 @@ pub trait Handler: Send + Sync {
     ) -> Result<(), Self::Error>;
 }
 
+type HandlerMap = HashMap<TargetName, Arc<dyn Handler<Error = CodecError> + Send + Sync>>;
+
 #[derive(Clone, Default)]
 pub struct MessageRouter(Arc<MessageRouterInner>);
 
 #[derive(Default)]
 struct MessageRouterInner {
-    handlers: HashMap<TargetName, Box<dyn Handler<Error = CodecError> + Send + Sync>>,
+    handlers: ArcSwap<HandlerMap>,
+}
+
+impl MessageRouter {
+    /// Adds the handlers of the builder to this router. Unlike replacing the router, this also
+    /// impacts established connections. Returns the targets of the added handlers so that they
+    /// can be removed again.
+    #[track_caller]
+    pub fn add_handlers(&self, builder: MessageRouterBuilder) -> Vec<TargetName> {
+        self.0.handlers.rcu(|handlers| {
+            let mut handlers = HandlerMap::clone(handlers);
+            for (target, handler) in &builder.handlers {
+                if handlers.insert(*target, Arc::clone(handler)).is_some() {
+                    panic!("Handler for target {} has been registered already!", target);
+                }
+            }
+            handlers
+        });
+        builder.handlers.into_keys().collect()
+    }
+
+    /// Removes the handlers of the given targets. Messages for these targets are rejected
+    /// afterwards.
+    pub fn remove_handlers(&self, targets: &[TargetName]) {
+        self.0.handlers.rcu(|handlers| {
+            let mut handlers = HandlerMap::clone(handlers);
+            for target in targets {
+                handlers.remove(target);
+            }
+            handlers
+        });
+    }
 }
 
 #[async_trait]
@@ -77,7 +111,7 @@ impl Handler for MessageRouter {
         message: BinaryMessage,
     ) -> Result<(), Self::Error> {
         let target = message.target();
-        let Some(handler) = self.0.handlers.get(&target) else {
+        let Some(handler) = self.0.handlers.load().get(&target).cloned() else {
             return Err(RouterError::NotRegisteredTarget(target.to_string()));
         };
         handler
@@ -89,7 +123,7 @@ impl Handler for MessageRouter {
 
 #[derive(Default)]
 pub struct MessageRouterBuilder {
-    handlers: HashMap<TargetName, Box<dyn Handler<Error = CodecError> + Send + Sync>>,
+    handlers: HandlerMap,
 }
 
 impl MessageRouterBuilder {
@@ -102,7 +136,7 @@ impl MessageRouterBuilder {
     {
         let wrapped = MessageHandlerWrapper { inner: handler };
         let target = H::MessageType::TARGET;
-        if self.handlers.insert(target, Box::new(wrapped)).is_some() {
+        if self.handlers.insert(target, Arc::new(wrapped)).is_some() {
             panic!("Handler for target {} has been registered already!", target);
         }
     }
@@ -122,7 +156,7 @@ impl MessageRouterBuilder {
 
         let wrapped = StreamHandlerWrapper { sender: tx };
         let target = M::TARGET;
-        if self.handlers.insert(target, Box::new(wrapped)).is_some() {
+        if self.handlers.insert(target, Arc::new(wrapped)).is_some() {
             panic!("Handler for target {} has been registered already!", target);
         }
         Box::pin(ReceiverStream::new(rx))
@@ -132,7 +166,7 @@ impl MessageRouterBuilder {
     /// [`crate::ConnectionManager`]
     pub fn build(self) -> MessageRouter {
         MessageRouter(Arc::new(MessageRouterInner {
-            handlers: self.handlers,
+            handlers: ArcSwap::from_pointee(self.handlers),
         }))
     }
 }
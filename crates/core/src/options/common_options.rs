@@ -8,6 +8,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use derive_getters::Getters;
@@ -62,6 +63,14 @@ pub struct CommonOptions {
     #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
     advertise_address: AdvertisedAddress,
 
+    /// # TLS
+    ///
+    /// If set, the node server and the metadata store only accept TLS connections from clients
+    /// with a certificate issued by the configured certificate authority. The node uses its
+    /// certificate when connecting to other nodes and to the metadata store, whose addresses
+    /// must use the `https` scheme.
+    tls: Option<TlsOptions>,
+
     /// # Shutdown grace timeout
     ///
     /// This timeout is used when shutting down the various Restate components to drain all the internal queues.
@@ -156,6 +165,7 @@ impl Default for CommonOptions {
                 .expect("valid metadata store address"),
             bind_address: "0.0.0.0:5122".parse().unwrap(),
             advertise_address: AdvertisedAddress::from_str("http://127.0.0.1:5122/").unwrap(),
+            tls: None,
             histogram_inactivity_timeout: None,
             disable_prometheus: false,
             shutdown_timeout: std::time::Duration::from_secs(60).into(),
@@ -170,6 +180,31 @@ impl Default for CommonOptions {
     }
 }
 
+/// # TLS options
+///
+/// Certificates used for mutually authenticated TLS between the nodes of a cluster.
+#[derive(Debug, Clone, Getters, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "options_schema", derive(schemars::JsonSchema))]
+pub struct TlsOptions {
+    /// # Certificate path
+    ///
+    /// Path to the PEM encoded certificate chain of this node. The certificate must be valid for
+    /// the node name as well as for the host of the advertised address, as other nodes verify
+    /// it against both.
+    cert_path: PathBuf,
+
+    /// # Private key path
+    ///
+    /// Path to the PEM encoded private key (PKCS#8 or RSA) of the node's certificate.
+    key_path: PathBuf,
+
+    /// # CA certificate path
+    ///
+    /// Path to the PEM encoded certificate of the authority which has issued the certificates
+    /// of all nodes in the cluster.
+    ca_cert_path: PathBuf,
+}
+
 /// # Log format
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 #[derive(Debug, Clone, Copy, Hash, Default, Serialize, Deserialize)]
@@ -126,6 +126,7 @@ impl TaskCenter {
             partition_id,
             cancel: cancel.clone(),
             join_handle: Mutex::new(None),
+            created_at: Instant::now(),
         });
 
         inner.tasks.lock().unwrap().insert(id, Arc::clone(&task));
@@ -330,6 +331,7 @@ impl TaskCenter {
             partition_id,
             cancel: cancel_token.clone(),
             join_handle: Mutex::new(None),
+            created_at: Instant::now(),
         });
         // Clone the currently set METADATA (and is Some()), otherwise fallback to global metadata.
         let metadata = METADATA
@@ -368,6 +370,7 @@ impl TaskCenter {
             partition_id,
             cancel: cancel_token.clone(),
             join_handle: Mutex::new(None),
+            created_at: Instant::now(),
         });
         // Clone the currently set METADATA (and is Some()), otherwise fallback to global metadata.
         let metadata = METADATA
@@ -382,6 +385,28 @@ impl TaskCenter {
         })
     }
 
+    /// Lists the tasks which are currently managed by task center, ordered by their id. Tasks
+    /// running in scope (see [`Self::run_in_scope`]) are not included.
+    pub fn running_tasks(&self) -> Vec<TaskSummary> {
+        let mut tasks: Vec<_> = self
+            .inner
+            .tasks
+            .lock()
+            .unwrap()
+            .values()
+            .map(|task| TaskSummary {
+                id: task.id,
+                name: task.name,
+                kind: task.kind,
+                partition_id: task.partition_id,
+                age: task.created_at.elapsed(),
+                cancellation_requested: task.cancel.is_cancelled(),
+            })
+            .collect();
+        tasks.sort_by_key(|task| task.id);
+        tasks
+    }
+
     /// Take control over the running task from task-center. This returns None if the task was not
     /// found, completed, or has been cancelled.
     pub fn take_task(&self, task_id: TaskId) -> Option<JoinHandle<()>> {
@@ -506,6 +531,20 @@ pub struct Task {
     /// for cancellation of tasks associated with that partition.
     partition_id: Option<PartitionId>,
     join_handle: Mutex<Option<JoinHandle<()>>>,
+    created_at: Instant,
+}
+
+/// A snapshot of a task which is managed by task center.
+#[derive(Debug, Clone)]
+pub struct TaskSummary {
+    pub id: TaskId,
+    pub name: &'static str,
+    pub kind: TaskKind,
+    pub partition_id: Option<PartitionId>,
+    /// Time since the task has been spawned.
+    pub age: Duration,
+    /// Whether the task has been asked to stop but hasn't finished yet.
+    pub cancellation_requested: bool,
 }
 
 task_local! {
@@ -654,4 +693,37 @@ mod tests {
         assert!(start.elapsed() >= Duration::from_secs(10));
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_running_tasks() -> Result<()> {
+        let tc = TaskCenterFactory::create(tokio::runtime::Handle::current());
+        let role_runner = tc.spawn(TaskKind::RoleRunner, "worker-role", None, async {
+            cancellation_watcher().await;
+            Ok(())
+        })?;
+        let partition_processor = tc.spawn(
+            TaskKind::PartitionProcessor,
+            "partition-processor",
+            Some(3),
+            async {
+                cancellation_watcher().await;
+                Ok(())
+            },
+        )?;
+
+        let tasks = tc.running_tasks();
+        assert_eq!(2, tasks.len());
+        assert_eq!(role_runner, tasks[0].id);
+        assert_eq!("worker-role", tasks[0].name);
+        assert_eq!(TaskKind::RoleRunner, tasks[0].kind);
+        assert_eq!(None, tasks[0].partition_id);
+        assert_eq!(partition_processor, tasks[1].id);
+        assert_eq!(TaskKind::PartitionProcessor, tasks[1].kind);
+        assert_eq!(Some(3), tasks[1].partition_id);
+        assert!(tasks.iter().all(|task| !task.cancellation_requested));
+
+        tc.cancel_tasks(None, None).await;
+        assert!(tc.running_tasks().is_empty());
+        Ok(())
+    }
 }
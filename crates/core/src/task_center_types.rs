@@ -74,6 +74,7 @@ pub enum TaskKind {
     ConnectionReactor,
     Shuffle,
     MetadataStore,
+    LogServer,
     // -- Bifrost Tasks
     /// A background task that the system needs for its operation. The task requires a system
     /// shutdown on errors and the system will wait for its graceful cancellation on shutdown.
@@ -8,6 +8,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
 
@@ -19,9 +20,10 @@ use restate_node_protocol::node::{Header, Message};
 use restate_node_protocol::CURRENT_PROTOCOL_VERSION;
 use restate_types::net::AdvertisedAddress;
 use restate_types::nodes_config::{NodeConfig, NodesConfiguration, Role};
-use restate_types::{GenerationalNodeId, NodeId, Version};
+use restate_types::{GenerationalNodeId, NodeId, PlainNodeId, Version};
 use tracing::info;
 
+use crate::metadata_store::MetadataStoreClient;
 use crate::network::{
     Handler, MessageHandler, MessageRouter, MessageRouterBuilder, NetworkSendError, NetworkSender,
 };
@@ -116,6 +118,100 @@ impl MockNetworkSender {
     }
 }
 
+/// An in-process network between several nodes that share the same task center, for instance to
+/// test components that replicate across nodes. Every node has its own message router and the
+/// messages of a node are delivered in the order they were sent.
+#[derive(Clone, Default)]
+pub struct MockClusterNetwork {
+    nodes: Arc<std::sync::Mutex<HashMap<PlainNodeId, ClusterNode>>>,
+}
+
+struct ClusterNode {
+    node_id: GenerationalNodeId,
+    sender: mpsc::UnboundedSender<(GenerationalNodeId, Message)>,
+    task_id: TaskId,
+}
+
+impl MockClusterNetwork {
+    /// Starts routing the messages that are sent to `node_id` to the given router.
+    pub fn add_node(
+        &self,
+        tc: &TaskCenter,
+        node_id: GenerationalNodeId,
+        router: MessageRouter,
+    ) -> Result<(), ShutdownError> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let network_receiver = NetworkReceiver {
+            router: Arc::new(RwLock::new(router)),
+        };
+        let task_id = tc.spawn(
+            crate::TaskKind::ConnectionReactor,
+            "test-cluster-network-receiver",
+            None,
+            async move { network_receiver.run(receiver).await },
+        )?;
+        let previous = self.nodes.lock().unwrap().insert(
+            node_id.as_plain(),
+            ClusterNode {
+                node_id,
+                sender,
+                task_id,
+            },
+        );
+        if let Some(previous) = previous {
+            tc.cancel_task(previous.task_id);
+        }
+        Ok(())
+    }
+
+    /// Disconnects the node from the network. Messages sent to this node fail with
+    /// [`NetworkSendError::ConnectionClosed`].
+    pub fn remove_node(&self, tc: &TaskCenter, node_id: PlainNodeId) {
+        if let Some(node) = self.nodes.lock().unwrap().remove(&node_id) {
+            tc.cancel_task(node.task_id);
+        }
+    }
+
+    /// Creates a network sender that sends messages on behalf of `node_id`.
+    pub fn sender_for(&self, node_id: GenerationalNodeId) -> MockClusterSender {
+        MockClusterSender {
+            my_node_id: node_id,
+            network: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MockClusterSender {
+    my_node_id: GenerationalNodeId,
+    network: MockClusterNetwork,
+}
+
+impl NetworkSender for MockClusterSender {
+    async fn send<M>(&self, to: NodeId, message: &M) -> Result<(), NetworkSendError>
+    where
+        M: WireSerde + Targeted + Send + Sync,
+    {
+        let header = Header::new(metadata().nodes_config_version());
+        let body = serialize_message(message, CURRENT_PROTOCOL_VERSION)?;
+
+        let nodes = self.network.nodes.lock().unwrap();
+        let Some(node) = nodes.get(&to.id()) else {
+            return Err(NetworkSendError::ConnectionClosed);
+        };
+        if to
+            .as_generational()
+            .is_some_and(|to| to.generation() < node.node_id.generation())
+        {
+            return Err(NetworkSendError::OldPeerGeneration(to.to_string()));
+        }
+        node.sender
+            .send((self.my_node_id, Message::new(header, body)))
+            .map_err(|_| NetworkSendError::ConnectionClosed)?;
+        Ok(())
+    }
+}
+
 pub struct TestCoreEnvBuilder<N> {
     pub tc: TaskCenter,
     pub my_node_id: GenerationalNodeId,
@@ -135,7 +231,8 @@ impl TestCoreEnvBuilder<MockNetworkSender> {
         let (tx, rx) = mpsc::unbounded_channel();
         let network_sender = MockNetworkSender::from_sender(tx);
         let my_node_id = GenerationalNodeId::new(1, 1);
-        let metadata_manager = MetadataManager::build(network_sender.clone());
+        let metadata_manager =
+            MetadataManager::build(network_sender.clone(), MetadataStoreClient::new_in_memory());
         let metadata = metadata_manager.metadata();
         let metadata_writer = metadata_manager.writer();
         let router_builder = MessageRouterBuilder::default();
@@ -163,7 +260,8 @@ where
         let tc = TaskCenterFactory::create(tokio::runtime::Handle::current());
 
         let my_node_id = GenerationalNodeId::new(1, 1);
-        let metadata_manager = MetadataManager::build(network_sender.clone());
+        let metadata_manager =
+            MetadataManager::build(network_sender.clone(), MetadataStoreClient::new_in_memory());
         let metadata = metadata_manager.metadata();
         let metadata_writer = metadata_manager.writer();
         let router_builder = MessageRouterBuilder::default();
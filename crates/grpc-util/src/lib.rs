@@ -8,27 +8,42 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::future::Future;
+mod tls;
+
+use std::convert::Infallible;
+use std::future::{ready, Future};
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::pin::Pin;
 use std::time::Duration;
 
+use futures::StreamExt;
 use http::Uri;
 use hyper::body::HttpBody;
 use hyper::server::accept::Accept;
-use hyper::server::conn::AddrIncoming;
+use hyper::server::conn::{AddrIncoming, AddrStream};
 use restate_types::net::{AdvertisedAddress, BindAddress};
 use tokio::io;
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::{UnixListener, UnixStream};
+use tokio_rustls::server::TlsStream;
 use tokio_stream::wrappers::UnixListenerStream;
-use tonic::transport::{Channel, Endpoint};
-use tower::service_fn;
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use tower::{service_fn, ServiceExt};
 use tracing::{debug, info};
 
+pub use tls::{PeerCertificates, TlsConfig, TlsError};
+
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_CONCURRENT_TLS_HANDSHAKES: usize = 128;
+
+/// Creates a lazily connecting channel to the given address. If a TLS configuration is given,
+/// connections to `https` addresses are established with TLS; unix domain sockets are never
+/// encrypted.
 pub fn create_grpc_channel_from_advertised_address(
     address: AdvertisedAddress,
-) -> Result<Channel, http::Error> {
+    tls: Option<ClientTlsConfig>,
+) -> Result<Channel, tonic::transport::Error> {
     let channel = match address {
         AdvertisedAddress::Uds(uds_path) => {
             // dummy endpoint required to specify an uds connector, it is not used anywhere
@@ -40,11 +55,14 @@ pub fn create_grpc_channel_from_advertised_address(
         }
         AdvertisedAddress::Http(uri) => {
             // todo: Make the channel settings configurable
-            Channel::builder(uri)
+            let mut endpoint = Channel::builder(uri)
                 .connect_timeout(Duration::from_secs(5))
                 // todo: configure the channel from configuration file
-                .http2_adaptive_window(true)
-                .connect_lazy()
+                .http2_adaptive_window(true);
+            if let Some(tls) = tls {
+                endpoint = endpoint.tls_config(tls)?;
+            }
+            endpoint.connect_lazy()
         }
     };
     Ok(channel)
@@ -68,8 +86,13 @@ pub enum Error {
     Running(#[from] hyper::Error),
 }
 
+/// Runs the server until the shutdown signal fires. If a TLS configuration is given, the server
+/// only accepts TLS connections from clients with a certificate issued by the configured
+/// certificate authority when bound to a socket address. The client's certificates are added to
+/// the request extensions as [`PeerCertificates`].
 pub async fn run_hyper_server<S, B, F>(
     bind_address: &BindAddress,
+    tls: Option<&TlsConfig>,
     service: S,
     shutdown_signal: F,
     server_name: &str,
@@ -99,9 +122,12 @@ where
 
             run_server(acceptor, service, shutdown_signal).await?
         }
-        BindAddress::Socket(socket_addr) => {
-            run_tcp_server(socket_addr, service, shutdown_signal, server_name).await?
-        }
+        BindAddress::Socket(socket_addr) => match tls {
+            Some(tls) => {
+                run_tls_server(socket_addr, tls, service, shutdown_signal, server_name).await?
+            }
+            None => run_tcp_server(socket_addr, service, shutdown_signal, server_name).await?,
+        },
     }
 
     debug!("Stopped server '{}'", server_name);
@@ -141,6 +167,90 @@ where
     run_server(acceptor, service, shutdown_signal).await
 }
 
+async fn run_tls_server<S, B, F>(
+    socket_addr: &SocketAddr,
+    tls: &TlsConfig,
+    service: S,
+    shutdown_signal: F,
+    server_name: &str,
+) -> Result<(), Error>
+where
+    S: hyper::service::Service<http::Request<hyper::Body>, Response = hyper::Response<B>>
+        + Send
+        + Clone
+        + 'static,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    S::Future: Send,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    F: Future<Output = ()>,
+{
+    let mut incoming = AddrIncoming::bind(socket_addr).map_err(|err| Error::TcpBinding {
+        address: *socket_addr,
+        source: err,
+    })?;
+
+    info!(
+        net.host.addr = %incoming.local_addr().ip(),
+        net.host.port = %incoming.local_addr().port(),
+        "Server '{}' listening with TLS", server_name
+    );
+
+    let tls_acceptor = tls.acceptor();
+    // Handshakes run concurrently so that a slow client cannot block accepting other connections.
+    // Failed handshakes only drop the affected connection.
+    let connections = futures::stream::poll_fn(move |cx| Pin::new(&mut incoming).poll_accept(cx))
+        .filter_map(|conn| {
+            ready(
+                conn.map_err(|err| debug!("Failed accepting connection: {}", err))
+                    .ok(),
+            )
+        })
+        .map(move |conn| {
+            let handshake = tls_acceptor.accept(conn);
+            async move { tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, handshake).await }
+        })
+        .buffer_unordered(MAX_CONCURRENT_TLS_HANDSHAKES)
+        .filter_map(|handshake| {
+            ready(match handshake {
+                Ok(Ok(conn)) => Some(Ok::<_, io::Error>(conn)),
+                Ok(Err(err)) => {
+                    debug!("TLS handshake failed: {}", err);
+                    None
+                }
+                Err(_) => {
+                    debug!("TLS handshake timed out");
+                    None
+                }
+            })
+        });
+
+    let make_service = hyper::service::make_service_fn(move |conn: &TlsStream<AddrStream>| {
+        let peer_certificates = conn
+            .get_ref()
+            .1
+            .peer_certificates()
+            .map(|certificates| PeerCertificates::new(certificates.to_vec()));
+        let service =
+            service
+                .clone()
+                .map_request(move |mut request: http::Request<hyper::Body>| {
+                    if let Some(peer_certificates) = &peer_certificates {
+                        request.extensions_mut().insert(peer_certificates.clone());
+                    }
+                    request
+                });
+        ready(Ok::<_, Infallible>(service))
+    });
+
+    hyper::Server::builder(hyper::server::accept::from_stream(connections))
+        .serve(make_service)
+        .with_graceful_shutdown(shutdown_signal)
+        .await
+        .map_err(Error::Running)
+}
+
 async fn run_server<S, B, Conn, Err, F>(
     acceptor: impl Accept<Conn = Conn, Error = Err>,
     service: S,
@@ -0,0 +1,165 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rustls::server::AllowAnyAuthenticatedClient;
+use rustls::{RootCertStore, ServerConfig};
+use tokio_rustls::TlsAcceptor;
+use tonic::transport::{Certificate, ClientTlsConfig, Endpoint, Identity};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TlsError {
+    #[error("failed reading '{path}': {source}")]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("no certificate found in '{0}'")]
+    MissingCertificate(PathBuf),
+    #[error("no private key found in '{0}'")]
+    MissingPrivateKey(PathBuf),
+    #[error("invalid tls configuration: {0}")]
+    Rustls(#[from] rustls::Error),
+    #[error("invalid client tls configuration: {0}")]
+    Client(#[from] tonic::transport::Error),
+}
+
+/// Certificates for mutually authenticated TLS. The same certificate is presented when accepting
+/// and when opening connections, and peers are only trusted if their certificate has been issued
+/// by the configured certificate authority.
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    identity: Identity,
+    ca_certificate: Certificate,
+    server_config: Arc<ServerConfig>,
+}
+
+impl TlsConfig {
+    /// Loads the PEM encoded certificate chain, its private key (PKCS#8 or RSA) and the
+    /// certificate authority which has issued the certificates of all peers.
+    pub fn load(cert_path: &Path, key_path: &Path, ca_cert_path: &Path) -> Result<Self, TlsError> {
+        let cert_pem = read(cert_path)?;
+        let key_pem = read(key_path)?;
+        let ca_cert_pem = read(ca_cert_path)?;
+
+        let cert_chain: Vec<_> = rustls_pemfile::certs(&mut cert_pem.as_slice())
+            .map_err(|err| read_error(cert_path, err))?
+            .into_iter()
+            .map(rustls::Certificate)
+            .collect();
+        if cert_chain.is_empty() {
+            return Err(TlsError::MissingCertificate(cert_path.to_owned()));
+        }
+
+        let key = private_key(&key_pem).map_err(|err| read_error(key_path, err))?;
+        let Some(key) = key else {
+            return Err(TlsError::MissingPrivateKey(key_path.to_owned()));
+        };
+
+        let mut roots = RootCertStore::empty();
+        for ca_cert in rustls_pemfile::certs(&mut ca_cert_pem.as_slice())
+            .map_err(|err| read_error(ca_cert_path, err))?
+        {
+            roots.add(&rustls::Certificate(ca_cert))?;
+        }
+        if roots.is_empty() {
+            return Err(TlsError::MissingCertificate(ca_cert_path.to_owned()));
+        }
+
+        let mut server_config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots).boxed())
+            .with_single_cert(cert_chain, key)?;
+        server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+        let tls_config = Self {
+            identity: Identity::from_pem(cert_pem, key_pem),
+            ca_certificate: Certificate::from_pem(ca_cert_pem),
+            server_config: Arc::new(server_config),
+        };
+
+        // tonic only parses the client identity when creating a channel, validate it upfront
+        Endpoint::from_static("https://localhost").tls_config(tls_config.client_config(None))?;
+
+        Ok(tls_config)
+    }
+
+    /// Client configuration which presents this node's certificate and requires the server's
+    /// certificate to be valid for `domain_name`. If no domain name is given, the host of the
+    /// server's address is used.
+    pub fn client_config(&self, domain_name: Option<&str>) -> ClientTlsConfig {
+        let client_config = ClientTlsConfig::new()
+            .identity(self.identity.clone())
+            .ca_certificate(self.ca_certificate.clone());
+
+        match domain_name {
+            Some(domain_name) => client_config.domain_name(domain_name),
+            None => client_config,
+        }
+    }
+
+    pub(crate) fn acceptor(&self) -> TlsAcceptor {
+        TlsAcceptor::from(Arc::clone(&self.server_config))
+    }
+}
+
+/// The certificate chain a client has presented when establishing a TLS connection. It is added
+/// to the extensions of every request received over that connection.
+#[derive(Debug, Clone)]
+pub struct PeerCertificates(Arc<Vec<rustls::Certificate>>);
+
+impl PeerCertificates {
+    pub(crate) fn new(certificates: Vec<rustls::Certificate>) -> Self {
+        Self(Arc::new(certificates))
+    }
+
+    /// Returns true if the peer's certificate is valid for the given DNS name. The chain itself
+    /// has already been verified against the certificate authority during the handshake.
+    pub fn is_valid_for(&self, name: &str) -> bool {
+        let Some(certificate) = self.0.first() else {
+            return false;
+        };
+        let Ok(name) = webpki::DnsNameRef::try_from_ascii_str(name) else {
+            return false;
+        };
+
+        webpki::EndEntityCert::try_from(certificate.0.as_slice())
+            .and_then(|certificate| certificate.verify_is_valid_for_subject_name(name.into()))
+            .is_ok()
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, TlsError> {
+    std::fs::read(path).map_err(|err| read_error(path, err))
+}
+
+fn read_error(path: &Path, source: io::Error) -> TlsError {
+    TlsError::Read {
+        path: path.to_owned(),
+        source,
+    }
+}
+
+fn private_key(pem: &[u8]) -> io::Result<Option<rustls::PrivateKey>> {
+    let mut reader = pem;
+    while let Some(item) = rustls_pemfile::read_one(&mut reader)? {
+        match item {
+            rustls_pemfile::Item::PKCS8Key(key) | rustls_pemfile::Item::RSAKey(key) => {
+                return Ok(Some(rustls::PrivateKey(key)))
+            }
+            _ => {}
+        }
+    }
+    Ok(None)
+}
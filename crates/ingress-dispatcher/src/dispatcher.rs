@@ -8,32 +8,41 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::collections::HashMap;
 use std::sync::atomic::AtomicU64;
 use std::sync::Arc;
+use std::time::Duration;
 
 use bytes::{BufMut, Bytes, BytesMut};
 use dashmap::DashMap;
 use prost::Message;
 use restate_bifrost::Bifrost;
-use restate_wal_protocol::append_envelope_to_bifrost;
+use restate_wal_protocol::Envelope;
+use tokio::sync::{mpsc, oneshot};
 use tracing::{debug, info, trace};
 
-use restate_core::metadata;
 use restate_core::network::MessageHandler;
+use restate_core::{cancellation_watcher, metadata, ShutdownError};
 use restate_node_protocol::codec::Targeted;
 use restate_node_protocol::ingress::IngressMessage;
 use restate_pb::restate::internal::{
     idempotent_invoke_response, IdempotentInvokeRequest, IdempotentInvokeResponse,
 };
 use restate_types::errors::InvocationError;
-use restate_types::identifiers::{FullInvocationId, InvocationId, ServiceId};
-use restate_types::invocation::{self, ServiceInvocation, ServiceInvocationResponseSink};
+use restate_types::identifiers::{FullInvocationId, InvocationId, ServiceId, WithPartitionKey};
+use restate_types::invocation::{
+    self, AttachInvocationRequest, AttachedResponseSink, ServiceInvocation,
+    ServiceInvocationResponseSink,
+};
+use restate_types::logs::{LogId, Lsn, Payload};
 use restate_types::message::MessageIndex;
+use restate_types::partition_table::FindPartition;
 
 use crate::error::IngressDispatchError;
 use crate::{
-    wrap_service_invocation_in_envelope, ExpiringIngressResponse, IdempotencyMode, IngressRequest,
-    IngressRequestMode, IngressResponseSender,
+    wrap_attach_invocation_request_in_envelope, wrap_service_invocation_in_envelope,
+    ExpiringIngressResponse, IdempotencyMode, IngressAttach, IngressInvocation, IngressRequest,
+    IngressRequestInner, IngressRequestMode, IngressResponseSender,
 };
 
 /// Dispatches a request from ingress to bifrost
@@ -51,6 +60,8 @@ struct IngressDispatcherState {
     // This map can be unbounded, because we enforce concurrency limits in the ingress
     // services using the global semaphore
     waiting_responses: DashMap<InvocationId, (MapResponseAction, IngressResponseSender)>,
+    // Requests attached to invocations, by their request id
+    waiting_attach_responses: DashMap<u64, IngressResponseSender>,
 }
 
 impl IngressDispatcherState {
@@ -62,13 +73,17 @@ impl IngressDispatcherState {
 
 #[derive(Clone)]
 pub struct IngressDispatcher {
-    bifrost: Bifrost,
+    appender: IngressAppenderHandle,
+    completion_retention_time: Duration,
     state: Arc<IngressDispatcherState>,
 }
 impl IngressDispatcher {
-    pub fn new(bifrost: Bifrost) -> Self {
+    /// The results of dispatched invocations are retained for the `completion_retention_time`
+    /// after they completed.
+    pub fn new(appender: IngressAppenderHandle, completion_retention_time: Duration) -> Self {
         Self {
-            bifrost,
+            appender,
+            completion_retention_time,
             state: Arc::new(IngressDispatcherState::default()),
         }
     }
@@ -83,9 +98,24 @@ impl DispatchIngressRequest for IngressDispatcher {
         &self,
         ingress_request: IngressRequest,
     ) -> Result<(), IngressDispatchError> {
-        let mut bifrost = self.bifrost.clone();
+        match ingress_request.inner {
+            IngressRequestInner::Invocation(ingress_invocation) => {
+                self.dispatch_invocation(*ingress_invocation).await
+            }
+            IngressRequestInner::Attach(ingress_attach) => {
+                self.dispatch_attach(ingress_attach).await
+            }
+        }
+    }
+}
+
+impl IngressDispatcher {
+    async fn dispatch_invocation(
+        &self,
+        ingress_invocation: IngressInvocation,
+    ) -> Result<(), IngressDispatchError> {
         let my_node_id = metadata().my_node_id();
-        let IngressRequest {
+        let IngressInvocation {
             fid,
             method_name,
             argument,
@@ -93,7 +123,7 @@ impl DispatchIngressRequest for IngressDispatcher {
             request_mode,
             idempotency,
             headers,
-        } = ingress_request;
+        } = ingress_invocation;
 
         let invocation_id: InvocationId = fid.clone().into();
         let response_sink = if matches!(request_mode, IngressRequestMode::RequestResponse(_)) {
@@ -137,6 +167,9 @@ impl DispatchIngressRequest for IngressDispatcher {
                         span_context,
                         headers,
                         execution_time: None,
+                        // the idempotent invoker retains the result itself
+                        completion_retention_time: Duration::ZERO,
+                        attached_response_sinks: vec![],
                     },
                     MapResponseAction::IdempotentInvokerResponse,
                 )
@@ -151,6 +184,8 @@ impl DispatchIngressRequest for IngressDispatcher {
                         span_context,
                         headers,
                         execution_time: None,
+                        completion_retention_time: self.completion_retention_time,
+                        attached_response_sinks: vec![],
                     },
                     MapResponseAction::None,
                 )
@@ -177,7 +212,7 @@ impl DispatchIngressRequest for IngressDispatcher {
             dedup_source,
             msg_index,
         );
-        let (log_id, lsn) = append_envelope_to_bifrost(&mut bifrost, envelope).await?;
+        let (log_id, lsn) = self.appender.append(envelope).await?;
 
         info!(
             restate.invocation.id = %invocation_id,
@@ -187,6 +222,174 @@ impl DispatchIngressRequest for IngressDispatcher {
         );
         Ok(())
     }
+
+    async fn dispatch_attach(
+        &self,
+        ingress_attach: IngressAttach,
+    ) -> Result<(), IngressDispatchError> {
+        let my_node_id = metadata().my_node_id();
+        let IngressAttach {
+            invocation_id,
+            block_on_inflight,
+            response_sender,
+        } = ingress_attach;
+
+        let request_id = self.state.get_and_increment_msg_index();
+        self.state
+            .waiting_attach_responses
+            .insert(request_id, response_sender);
+
+        let envelope = wrap_attach_invocation_request_in_envelope(
+            AttachInvocationRequest {
+                invocation_id: invocation_id.clone(),
+                block_on_inflight,
+                response_sink: AttachedResponseSink {
+                    node_id: my_node_id,
+                    request_id,
+                },
+            },
+            my_node_id,
+        );
+        let result = self.appender.append(envelope).await;
+        let (log_id, lsn) = match result {
+            Ok(appended) => appended,
+            Err(err) => {
+                self.state.waiting_attach_responses.remove(&request_id);
+                return Err(err);
+            }
+        };
+
+        info!(
+            restate.invocation.id = %invocation_id,
+            log_id = %log_id,
+            lsn = %lsn,
+            "Ingress attach request written to bifrost"
+        );
+        Ok(())
+    }
+}
+
+struct PendingAppend {
+    log_id: LogId,
+    payload: Payload,
+    ack: oneshot::Sender<Result<Lsn, restate_bifrost::Error>>,
+}
+
+/// Appends the envelopes of the ingress requests to bifrost in batches. Envelopes which are
+/// enqueued while a batch is being appended are appended together with the next batch (group
+/// commit). The appender runs as its own task, so that appends don't depend on the requests
+/// which enqueued them.
+pub struct IngressAppender {
+    bifrost: Bifrost,
+    pending_tx: mpsc::UnboundedSender<PendingAppend>,
+    pending_rx: mpsc::UnboundedReceiver<PendingAppend>,
+}
+
+impl IngressAppender {
+    pub fn new(bifrost: Bifrost) -> Self {
+        let (pending_tx, pending_rx) = mpsc::unbounded_channel();
+        Self {
+            bifrost,
+            pending_tx,
+            pending_rx,
+        }
+    }
+
+    pub fn handle(&self) -> IngressAppenderHandle {
+        IngressAppenderHandle {
+            pending_tx: self.pending_tx.clone(),
+        }
+    }
+
+    pub async fn run(self) -> anyhow::Result<()> {
+        let IngressAppender {
+            mut bifrost,
+            pending_rx: mut pending,
+            ..
+        } = self;
+        let shutdown = cancellation_watcher();
+        tokio::pin!(shutdown);
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => {
+                    debug!("Stopping ingress appender");
+                    return Ok(());
+                }
+                Some(first) = pending.recv() => {
+                    let mut batch = vec![first];
+                    while let Ok(pending_append) = pending.try_recv() {
+                        batch.push(pending_append);
+                    }
+                    Self::append_batch(&mut bifrost, batch).await;
+                }
+            }
+        }
+    }
+
+    async fn append_batch(bifrost: &mut Bifrost, batch: Vec<PendingAppend>) {
+        // the order of the envelopes is preserved within each log
+        let mut batches: HashMap<LogId, (Vec<Payload>, Vec<_>)> = HashMap::new();
+        for PendingAppend {
+            log_id,
+            payload,
+            ack,
+        } in batch
+        {
+            let (payloads, acks) = batches.entry(log_id).or_default();
+            payloads.push(payload);
+            acks.push(ack);
+        }
+
+        for (log_id, (payloads, acks)) in batches {
+            trace!(%log_id, "Appending batch of {} ingress requests", payloads.len());
+            match bifrost.append_batch(log_id, payloads).await {
+                Ok(lsns) => {
+                    for (ack, lsn) in acks.into_iter().zip(u64::from(lsns.start)..) {
+                        let _ = ack.send(Ok(Lsn::from(lsn)));
+                    }
+                }
+                Err(err) => {
+                    for ack in acks {
+                        let _ = ack.send(Err(err.clone()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Enqueues envelopes at the [`IngressAppender`].
+#[derive(Clone)]
+pub struct IngressAppenderHandle {
+    pending_tx: mpsc::UnboundedSender<PendingAppend>,
+}
+
+impl IngressAppenderHandle {
+    async fn append(&self, envelope: Envelope) -> Result<(LogId, Lsn), IngressDispatchError> {
+        let partition_id = metadata()
+            .partition_table()
+            .find_partition_id(envelope.partition_key())?;
+        let log_id = LogId::from(partition_id);
+        let payload = Payload::from(
+            envelope
+                .encode()
+                .map_err(restate_wal_protocol::Error::from)?,
+        );
+
+        let (ack, appended) = oneshot::channel();
+        // the appender only stops on shutdown, then the ack is dropped
+        let _ = self.pending_tx.send(PendingAppend {
+            log_id,
+            payload,
+            ack,
+        });
+        let lsn = appended
+            .await
+            .unwrap_or(Err(restate_bifrost::Error::Shutdown(ShutdownError)))
+            .map_err(restate_wal_protocol::Error::from)?;
+        Ok((log_id, lsn))
+    }
 }
 
 impl MessageHandler for IngressDispatcher {
@@ -223,6 +426,29 @@ impl MessageHandler for IngressDispatcher {
                     debug!("Failed to handle response '{:?}' because no handler was found locally waiting for its invocation Id", &response);
                 }
             }
+            IngressMessage::AttachResponse(response) => {
+                if let Some((_, sender)) = self
+                    .state
+                    .waiting_attach_responses
+                    .remove(&response.request_id)
+                {
+                    let result: Result<Bytes, InvocationError> = response.response.into();
+                    if let Err(result) = sender.send(result.into()) {
+                        debug!(
+                            "Failed to send response '{:?}' because the handler has been \
+                                closed, probably caused by the client connection that went away",
+                            result
+                        );
+                    } else {
+                        debug!(
+                            restate.invocation.id = %response.id,
+                            partition_processor_peer = %peer,
+                            "Sent response of attached invocation out");
+                    }
+                } else {
+                    debug!("Failed to handle response '{:?}' because no handler was found locally waiting for its request id", &response);
+                }
+            }
         }
     }
 }
@@ -278,7 +504,7 @@ mod tests {
 
     use googletest::{assert_that, pat};
     use restate_core::network::NetworkSender;
-    use restate_core::TestCoreEnvBuilder;
+    use restate_core::{task_center, TaskKind, TestCoreEnvBuilder};
     use restate_node_protocol::ingress::InvocationResponse;
     use restate_types::identifiers::WithPartitionKey;
     use restate_wal_protocol::Command;
@@ -289,7 +515,7 @@ mod tests {
     use restate_types::identifiers::ServiceId;
     use restate_types::invocation::{ResponseResult, SpanRelation};
     use restate_types::logs::{LogId, Lsn, SequenceNumber};
-    use restate_types::partition_table::{FindPartition, FixedPartitionTable};
+    use restate_types::partition_table::{FindPartition, PartitionTable};
     use restate_types::Version;
 
     #[test(tokio::test)]
@@ -299,14 +525,22 @@ mod tests {
         // set it to 1 partition so that we know where the invocation for the IdempotentInvoker goes to
         let num_partitions = 1;
         tc.run_in_scope("test", None, async {
-            let bifrost = Bifrost::new_in_memory(num_partitions).await;
-            let dispatcher = IngressDispatcher::new(bifrost.clone());
+            let bifrost =
+                Bifrost::new_in_memory(env_builder.metadata_writer.clone(), num_partitions).await;
+            let appender = IngressAppender::new(bifrost.clone());
+            let dispatcher = IngressDispatcher::new(appender.handle(), Duration::ZERO);
+            task_center().spawn(
+                TaskKind::SystemService,
+                "ingress-appender",
+                None,
+                appender.run(),
+            )?;
             env_builder = env_builder.add_message_handler(dispatcher.clone());
             let node_env = env_builder.build().await;
 
             node_env
                 .metadata_writer
-                .update(FixedPartitionTable::new(Version::MIN, num_partitions))
+                .update(PartitionTable::new(Version::MIN, num_partitions))
                 .await?;
 
             // Ask for a response, then drop the receiver
@@ -331,8 +565,7 @@ mod tests {
             let log_id = LogId::from(partition_id);
             let log_record = bifrost.read_next_single(log_id, Lsn::INVALID).await?;
 
-            let output_message =
-                Envelope::decode_with_bincode(log_record.record.payload().unwrap().as_ref())?;
+            let output_message = Envelope::decode(log_record.record.payload().unwrap().as_ref())?;
 
             let_assert!(
                 Envelope {
@@ -14,9 +14,12 @@ use prost::Message;
 use restate_pb::restate::internal::Event;
 use restate_schema_api::subscription::{EventReceiverComponentType, Sink, Subscription};
 use restate_types::errors::InvocationError;
-use restate_types::identifiers::{FullInvocationId, ServiceId, WithPartitionKey};
-use restate_types::invocation::{ServiceInvocation, ServiceInvocationSpanContext, SpanRelation};
+use restate_types::identifiers::{FullInvocationId, InvocationId, ServiceId, WithPartitionKey};
+use restate_types::invocation::{
+    AttachInvocationRequest, ServiceInvocation, ServiceInvocationSpanContext, SpanRelation,
+};
 use restate_types::message::MessageIndex;
+use restate_types::time::MillisSinceEpoch;
 use restate_types::GenerationalNodeId;
 use std::fmt::Display;
 use std::time::Duration;
@@ -25,7 +28,9 @@ use tokio::sync::oneshot;
 mod dispatcher;
 pub mod error;
 
-pub use dispatcher::{DispatchIngressRequest, IngressDispatcher};
+pub use dispatcher::{
+    DispatchIngressRequest, IngressAppender, IngressAppenderHandle, IngressDispatcher,
+};
 
 use restate_core::metadata;
 use restate_types::dedup::DedupInformation;
@@ -49,6 +54,17 @@ impl IdempotencyMode {
 
 #[derive(Debug)]
 pub struct IngressRequest {
+    inner: IngressRequestInner,
+}
+
+#[derive(Debug)]
+enum IngressRequestInner {
+    Invocation(Box<IngressInvocation>),
+    Attach(IngressAttach),
+}
+
+#[derive(Debug)]
+struct IngressInvocation {
     fid: FullInvocationId,
     method_name: ByteString,
     argument: Bytes,
@@ -58,6 +74,21 @@ pub struct IngressRequest {
     headers: Vec<restate_types::invocation::Header>,
 }
 
+#[derive(Debug)]
+struct IngressAttach {
+    invocation_id: InvocationId,
+    block_on_inflight: bool,
+    response_sender: IngressResponseSender,
+}
+
+impl From<IngressInvocation> for IngressRequest {
+    fn from(value: IngressInvocation) -> Self {
+        IngressRequest {
+            inner: IngressRequestInner::Invocation(Box::new(value)),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ExpiringIngressResponse {
     idempotency_expiry_time: Option<String>,
@@ -111,7 +142,7 @@ impl IngressRequest {
         let (result_tx, result_rx) = oneshot::channel();
 
         (
-            IngressRequest {
+            IngressInvocation {
                 fid,
                 method_name: method_name.into(),
                 argument: argument.into(),
@@ -119,6 +150,27 @@ impl IngressRequest {
                 span_context,
                 idempotency,
                 headers,
+            }
+            .into(),
+            result_rx,
+        )
+    }
+
+    /// Attaches to the invocation in order to receive its result. Unless `block_on_inflight` is
+    /// set, the response for an invocation which hasn't completed yet is a not ready failure.
+    pub fn attach(
+        invocation_id: InvocationId,
+        block_on_inflight: bool,
+    ) -> (Self, IngressResponseReceiver) {
+        let (result_tx, result_rx) = oneshot::channel();
+
+        (
+            IngressRequest {
+                inner: IngressRequestInner::Attach(IngressAttach {
+                    invocation_id,
+                    block_on_inflight,
+                    response_sender: result_tx,
+                }),
             },
             result_rx,
         )
@@ -133,7 +185,7 @@ impl IngressRequest {
         headers: Vec<restate_types::invocation::Header>,
     ) -> Self {
         let span_context = ServiceInvocationSpanContext::start(&fid, related_span);
-        IngressRequest {
+        IngressInvocation {
             fid,
             method_name: method_name.into(),
             argument: argument.into(),
@@ -145,6 +197,7 @@ impl IngressRequest {
             idempotency: IdempotencyMode::None,
             headers,
         }
+        .into()
     }
 
     pub fn event<D: DeduplicationId>(
@@ -204,7 +257,7 @@ impl IngressRequest {
                 proxying_key,
             ));
 
-            IngressRequest {
+            IngressInvocation {
                 fid: proxy_fid,
                 method_name: ByteString::from_static(restate_pb::PROXY_PROXY_THROUGH_METHOD_NAME),
                 argument: restate_pb::restate::internal::ProxyThroughRequest {
@@ -221,8 +274,9 @@ impl IngressRequest {
                 idempotency: IdempotencyMode::None,
                 headers,
             }
+            .into()
         } else {
-            IngressRequest {
+            IngressInvocation {
                 fid: target_fid,
                 method_name: ByteString::from(&**handler),
                 argument,
@@ -231,6 +285,7 @@ impl IngressRequest {
                 idempotency: IdempotencyMode::None,
                 headers,
             }
+            .into()
         })
     }
 }
@@ -250,11 +305,31 @@ pub fn wrap_service_invocation_in_envelope(
             partition_key: service_invocation.fid.partition_key(),
             dedup: deduplication_source.map(|src| DedupInformation::ingress(src, msg_index)),
         },
+        created_at: Some(MillisSinceEpoch::now()),
     };
 
     Envelope::new(header, Command::Invoke(service_invocation))
 }
 
+pub fn wrap_attach_invocation_request_in_envelope(
+    attach_invocation_request: AttachInvocationRequest,
+    from_node_id: GenerationalNodeId,
+) -> Envelope {
+    let header = Header {
+        source: Source::Ingress {
+            node_id: from_node_id,
+            nodes_config_version: metadata().nodes_config_version(),
+        },
+        dest: Destination::Processor {
+            partition_key: attach_invocation_request.partition_key(),
+            dedup: None,
+        },
+        created_at: Some(MillisSinceEpoch::now()),
+    };
+
+    Envelope::new(header, Command::AttachInvocation(attach_invocation_request))
+}
+
 #[cfg(feature = "mocks")]
 pub mod mocks {
     use super::*;
@@ -262,7 +337,6 @@ pub mod mocks {
     use tokio::sync::mpsc;
 
     use restate_test_util::let_assert;
-    use restate_types::identifiers::InvocationId;
 
     use self::error::IngressDispatchError;
 
@@ -300,8 +374,9 @@ pub mod mocks {
             IngressResponseSender,
             Vec<restate_types::invocation::Header>,
         ) {
+            let_assert!(IngressRequestInner::Invocation(ingress_invocation) = self.inner);
             let_assert!(
-                IngressRequest {
+                IngressInvocation {
                     fid,
                     method_name,
                     argument,
@@ -310,7 +385,7 @@ pub mod mocks {
                     idempotency,
                     headers,
                     ..
-                } = self
+                } = *ingress_invocation
             );
             (
                 fid,
@@ -331,15 +406,16 @@ pub mod mocks {
             Bytes,
             ServiceInvocationSpanContext,
         ) {
+            let_assert!(IngressRequestInner::Invocation(ingress_invocation) = self.inner);
             let_assert!(
-                IngressRequest {
+                IngressInvocation {
                     fid,
                     method_name,
                     argument,
                     span_context,
                     request_mode: IngressRequestMode::FireAndForget,
                     ..
-                } = self
+                } = *ingress_invocation
             );
             (fid, method_name, argument, span_context)
         }
@@ -353,17 +429,29 @@ pub mod mocks {
             ServiceInvocationSpanContext,
             IngressDeduplicationId,
         ) {
+            let_assert!(IngressRequestInner::Invocation(ingress_invocation) = self.inner);
             let_assert!(
-                IngressRequest {
+                IngressInvocation {
                     fid,
                     method_name,
                     argument,
                     span_context,
                     request_mode: IngressRequestMode::DedupFireAndForget(dedup_id),
                     ..
-                } = self
+                } = *ingress_invocation
             );
             (fid, method_name, argument, span_context, dedup_id)
         }
+
+        pub fn expect_attach(self) -> (InvocationId, bool, IngressResponseSender) {
+            let_assert!(
+                IngressRequestInner::Attach(IngressAttach {
+                    invocation_id,
+                    block_on_inflight,
+                    response_sender,
+                }) = self.inner
+            );
+            (invocation_id, block_on_inflight, response_sender)
+        }
     }
 }
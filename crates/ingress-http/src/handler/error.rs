@@ -13,7 +13,7 @@ use super::APPLICATION_JSON;
 use bytes::Bytes;
 use http::{header, Response, StatusCode};
 use restate_schema_api::invocation_target::InputValidationError;
-use restate_types::errors::InvocationError;
+use restate_types::errors::{IdDecodeError, InvocationError};
 use serde::Serialize;
 use std::string;
 
@@ -29,6 +29,12 @@ pub(crate) enum HandlerError {
         "bad path, expected either /restate/awakeables/:id/resolve or /restate/awakeables/:id/reject"
     )]
     BadAwakeablesPath,
+    #[error(
+        "bad path, expected either /restate/invocation/:id/attach or /restate/invocation/:id/output"
+    )]
+    BadInvocationsPath,
+    #[error("bad invocation id: {0}")]
+    BadInvocationId(#[source] IdDecodeError),
     #[error("not implemented")]
     NotImplemented,
     #[error("bad header {0}: {1:?}")]
@@ -83,6 +89,8 @@ impl HandlerError {
             HandlerError::UrlDecodingError(_) => StatusCode::BAD_REQUEST,
             HandlerError::SendAndIdempotencyKey => StatusCode::NOT_IMPLEMENTED,
             HandlerError::BadAwakeablesPath => StatusCode::BAD_REQUEST,
+            HandlerError::BadInvocationsPath => StatusCode::BAD_REQUEST,
+            HandlerError::BadInvocationId(_) => StatusCode::BAD_REQUEST,
             HandlerError::NotImplemented => StatusCode::NOT_IMPLEMENTED,
             HandlerError::Invocation(e) => {
                 StatusCode::from_u16(e.code().into()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
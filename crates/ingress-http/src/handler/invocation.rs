@@ -0,0 +1,83 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use super::path_parsing::InvocationRequestType;
+use super::Handler;
+use super::HandlerError;
+
+use bytes::Bytes;
+use http::{Method, Request, Response};
+use http_body_util::Full;
+use restate_ingress_dispatcher::DispatchIngressRequest;
+use restate_ingress_dispatcher::IngressRequest;
+use tracing::{info, trace, warn};
+
+impl<Schemas, Dispatcher> Handler<Schemas, Dispatcher>
+where
+    Dispatcher: DispatchIngressRequest + Clone + Send + Sync + 'static,
+{
+    pub(crate) async fn handle_invocation<B: http_body::Body>(
+        self,
+        req: Request<B>,
+        invocation_request_type: InvocationRequestType,
+    ) -> Result<Response<Full<Bytes>>, HandlerError> {
+        // Check HTTP Method
+        if req.method() != Method::GET {
+            return Err(HandlerError::MethodNotAllowed);
+        }
+
+        // Attach waits for the in-flight invocation to complete, get output returns immediately
+        let (invocation_id, block_on_inflight) = match invocation_request_type {
+            InvocationRequestType::Attach(invocation_id) => (invocation_id, true),
+            InvocationRequestType::GetOutput(invocation_id) => (invocation_id, false),
+        };
+        info!(
+            restate.invocation.id = %invocation_id,
+            "Processing invocation {} request",
+            if block_on_inflight { "attach" } else { "get output" }
+        );
+
+        let (attach_request, response_rx) =
+            IngressRequest::attach(invocation_id.clone(), block_on_inflight);
+        if let Err(e) = self
+            .dispatcher
+            .dispatch_ingress_request(attach_request)
+            .await
+        {
+            warn!(
+                restate.invocation.id = %invocation_id,
+                "Failed to dispatch ingress request: {}",
+                e,
+            );
+            return Err(HandlerError::Unavailable);
+        }
+
+        // Wait on response
+        let response = if let Ok(response) = response_rx.await {
+            response
+        } else {
+            warn!("Response channel was closed");
+            return Err(HandlerError::Unavailable);
+        };
+
+        match response.into() {
+            Ok(response_payload) => {
+                trace!(rpc.response = ?response_payload, "Complete invocation request successfully");
+                Ok(Response::builder()
+                    .body(Full::new(response_payload))
+                    .unwrap())
+            }
+            Err(error) => {
+                info!(rpc.response = ?error, "Complete invocation request with a failure");
+                Ok(HandlerError::Invocation(error).into_response())
+            }
+        }
+    }
+}
@@ -27,6 +27,7 @@ mod awakeables;
 mod component_handler;
 mod error;
 mod health;
+mod invocation;
 mod path_parsing;
 #[cfg(test)]
 mod tests;
@@ -79,6 +80,9 @@ where
                 RequestType::Awakeable(awakeable_request) => {
                     this.handle_awakeable(req, awakeable_request).await
                 }
+                RequestType::Invocation(invocation_request) => {
+                    this.handle_invocation(req, invocation_request).await
+                }
                 RequestType::Component(component_request) => {
                     this.handle_component_request(req, component_request).await
                 }
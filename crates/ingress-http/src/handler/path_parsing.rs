@@ -15,6 +15,7 @@ use component::ComponentType;
 use http::Uri;
 use restate_schema_api::component;
 use restate_schema_api::component::ComponentMetadataResolver;
+use restate_types::identifiers::InvocationId;
 use std::collections::VecDeque;
 
 pub(crate) enum AwakeableRequestType {
@@ -42,6 +43,38 @@ impl AwakeableRequestType {
     }
 }
 
+pub(crate) enum InvocationRequestType {
+    Attach(InvocationId),
+    GetOutput(InvocationId),
+}
+
+impl InvocationRequestType {
+    fn from_path_chunks(mut path_parts: VecDeque<&str>) -> Result<Self, HandlerError> {
+        // Parse invocation id
+        let invocation_id: InvocationId = path_parts
+            .pop_front()
+            .ok_or(HandlerError::BadInvocationsPath)?
+            .parse()
+            .map_err(HandlerError::BadInvocationId)?;
+
+        // Attach or get output
+        let request_type = match path_parts
+            .pop_front()
+            .ok_or(HandlerError::BadInvocationsPath)?
+        {
+            "attach" => InvocationRequestType::Attach(invocation_id),
+            "output" => InvocationRequestType::GetOutput(invocation_id),
+            _ => return Err(HandlerError::NotFound),
+        };
+
+        if !path_parts.is_empty() {
+            return Err(HandlerError::BadInvocationsPath);
+        }
+
+        Ok(request_type)
+    }
+}
+
 pub(crate) enum TargetType {
     Service,
     VirtualObject { key: String },
@@ -116,6 +149,7 @@ pub(crate) enum RequestType {
     Health,
     OpenAPI,
     Awakeable(AwakeableRequestType),
+    Invocation(InvocationRequestType),
     Component(ComponentRequestType),
 }
 
@@ -135,6 +169,9 @@ where
                 "awakeables" | "a" => Ok(RequestType::Awakeable(
                     AwakeableRequestType::from_path_chunks(path_parts)?,
                 )),
+                "invocation" => Ok(RequestType::Invocation(
+                    InvocationRequestType::from_path_chunks(path_parts)?,
+                )),
                 _ => Err(HandlerError::NotFound),
             },
             "openapi" => Ok(RequestType::OpenAPI),
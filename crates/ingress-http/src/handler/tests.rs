@@ -27,6 +27,8 @@ use restate_schema_api::invocation_target::{
     InputContentType, InputRules, InputValidationRule, InvocationTargetMetadata,
     OutputContentTypeRule, OutputRules,
 };
+use restate_types::errors::NOT_READY_INVOCATION_ERROR;
+use restate_types::identifiers::{FullInvocationId, InvocationId, ServiceId};
 use restate_types::invocation::Header;
 use tokio::sync::mpsc;
 use tower::ServiceExt;
@@ -560,6 +562,81 @@ async fn health() {
     let _: HealthResponse = serde_json::from_slice(&response_bytes).unwrap();
 }
 
+#[tokio::test]
+#[traced_test]
+async fn attach_invocation() {
+    let invocation_id: InvocationId =
+        FullInvocationId::generate(ServiceId::unkeyed("greeter.Greeter")).into();
+    let expected_invocation_id = invocation_id.clone();
+
+    let req = hyper::Request::builder()
+        .uri(format!(
+            "http://localhost/restate/invocation/{}/attach",
+            invocation_id
+        ))
+        .method(Method::GET)
+        .body(Empty::<Bytes>::default())
+        .unwrap();
+
+    let response = handle(req, move |ingress_req| {
+        let (invocation_id, block_on_inflight, response_tx) = ingress_req.expect_attach();
+        restate_test_util::assert_eq!(invocation_id, expected_invocation_id);
+        assert!(block_on_inflight);
+
+        response_tx
+            .send(Ok(Bytes::from_static(b"123")).into())
+            .unwrap();
+    })
+    .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let (_, response_body) = response.into_parts();
+    let response_bytes = response_body.collect().await.unwrap().to_bytes();
+    assert_eq!(response_bytes, Bytes::from_static(b"123"));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn get_invocation_output_not_ready() {
+    let invocation_id: InvocationId =
+        FullInvocationId::generate(ServiceId::unkeyed("greeter.Greeter")).into();
+
+    let req = hyper::Request::builder()
+        .uri(format!(
+            "http://localhost/restate/invocation/{}/output",
+            invocation_id
+        ))
+        .method(Method::GET)
+        .body(Empty::<Bytes>::default())
+        .unwrap();
+
+    let response = handle(req, |ingress_req| {
+        let (_, block_on_inflight, response_tx) = ingress_req.expect_attach();
+        assert!(!block_on_inflight);
+
+        response_tx
+            .send(Err(NOT_READY_INVOCATION_ERROR).into())
+            .unwrap();
+    })
+    .await;
+
+    assert_eq!(response.status().as_u16(), 470);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn bad_invocation_id() {
+    let req = hyper::Request::builder()
+        .uri("http://localhost/restate/invocation/not-an-id/output")
+        .method(Method::GET)
+        .body(Empty::<Bytes>::default())
+        .unwrap();
+
+    let response = handle(req, request_handler_not_reached).await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
 fn request_handler_not_reached(_req: IngressRequest) {
     panic!("This code should not be reached in this test");
 }
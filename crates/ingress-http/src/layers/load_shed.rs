@@ -8,50 +8,92 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use crate::metric_definitions::{INGRESS_REQUESTS, REQUEST_ADMITTED, REQUEST_DENIED_THROTTLE};
+use crate::metric_definitions::{
+    INGRESS_REQUESTS, REQUEST_ADMITTED, REQUEST_DENIED_DRAINING, REQUEST_DENIED_THROTTLE,
+};
 use futures::ready;
 use http::{Request, Response, StatusCode};
 use metrics::counter;
 use pin_project_lite::pin_project;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tower::{Layer, Service};
 use tracing::warn;
 
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 // This service is inspired by tower-util LoadShed and ConcurrencyLimit, but returns a http response.
 
-pub struct LoadShedLayer {
+/// Drains the ingress before its node is removed from the cluster. Once draining, new requests
+/// are rejected with `503 Service Unavailable`.
+#[derive(Debug, Clone)]
+pub struct DrainHandle {
     semaphore: Arc<Semaphore>,
+    permits: usize,
+    draining: Arc<AtomicBool>,
+}
+
+impl DrainHandle {
+    /// Stops accepting new requests and waits until the in-flight requests have completed.
+    pub async fn drain(&self) {
+        self.draining.store(true, Ordering::Relaxed);
+
+        // in-flight requests hold their permit until they have completed
+        while self.semaphore.available_permits() < self.permits {
+            tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+        }
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Relaxed)
+    }
+}
+
+pub struct LoadShedLayer {
+    drain_handle: DrainHandle,
 }
 
 impl LoadShedLayer {
     pub fn new(permits: usize) -> Self {
         Self {
-            semaphore: Arc::new(Semaphore::new(permits)),
+            drain_handle: DrainHandle {
+                semaphore: Arc::new(Semaphore::new(permits)),
+                permits,
+                draining: Arc::new(AtomicBool::new(false)),
+            },
         }
     }
+
+    pub fn drain_handle(&self) -> DrainHandle {
+        self.drain_handle.clone()
+    }
 }
 
 impl<S> Layer<S> for LoadShedLayer {
     type Service = LoadShed<S>;
 
     fn layer(&self, inner: S) -> Self::Service {
-        LoadShed::new(inner, self.semaphore.clone())
+        LoadShed::new(inner, self.drain_handle.clone())
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct LoadShed<S> {
     inner: S,
-    semaphore: Arc<Semaphore>,
+    drain_handle: DrainHandle,
 }
 
 impl<S> LoadShed<S> {
-    pub fn new(inner: S, semaphore: Arc<Semaphore>) -> Self {
-        LoadShed { inner, semaphore }
+    pub fn new(inner: S, drain_handle: DrainHandle) -> Self {
+        LoadShed {
+            inner,
+            drain_handle,
+        }
     }
 }
 
@@ -69,8 +111,17 @@ where
     }
 
     fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        if self.drain_handle.is_draining() {
+            // Register request denied
+            counter!(INGRESS_REQUESTS, "status" => REQUEST_DENIED_DRAINING).increment(1);
+
+            return ResponseFuture {
+                state: ResponseState::Draining,
+            };
+        }
+
         // Acquire the semaphore permit to check if we have available quota
-        let permit = if let Ok(p) = self.semaphore.clone().try_acquire_owned() {
+        let permit = if let Ok(p) = self.drain_handle.semaphore.clone().try_acquire_owned() {
             p
         } else {
             warn!("No available quota to process the request");
@@ -112,6 +163,7 @@ pin_project! {
             _permit: OwnedSemaphorePermit,
         },
         Overloaded,
+        Draining,
     }
 }
 
@@ -129,6 +181,10 @@ where
                 .status(StatusCode::TOO_MANY_REQUESTS)
                 .body(Default::default())
                 .unwrap())),
+            ResponseStateProj::Draining => Poll::Ready(Ok(Response::builder()
+                .status(StatusCode::SERVICE_UNAVAILABLE)
+                .body(Default::default())
+                .unwrap())),
         }
     }
 }
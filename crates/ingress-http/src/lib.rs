@@ -14,6 +14,7 @@ mod metric_definitions;
 mod options;
 mod server;
 
+pub use layers::load_shed::DrainHandle;
 pub use options::{Options, OptionsBuilder, OptionsBuilderError};
 pub use server::{HyperServerIngress, IngressServerError, StartSignal};
 
@@ -17,6 +17,7 @@ pub const INGRESS_REQUESTS: &str = "restate.ingress.requests.total";
 pub const REQUEST_ADMITTED: &str = "admitted";
 pub const REQUEST_COMPLETED: &str = "completed";
 pub const REQUEST_DENIED_THROTTLE: &str = "throttled";
+pub const REQUEST_DENIED_DRAINING: &str = "draining";
 
 pub const INGRESS_REQUEST_DURATION: &str = "restate.ingress.request_duration.seconds";
 
@@ -11,6 +11,7 @@
 use super::*;
 
 use crate::handler::Handler;
+use crate::layers::load_shed::{DrainHandle, LoadShedLayer};
 use codederror::CodedError;
 use http::{Request, Response};
 use http_body_util::Full;
@@ -52,7 +53,7 @@ pub enum IngressServerError {
 
 pub struct HyperServerIngress<Schemas, Dispatcher> {
     listening_addr: SocketAddr,
-    concurrency_limit: usize,
+    load_shed: LoadShedLayer,
 
     // Parameters to build the layers
     schemas: Schemas,
@@ -77,7 +78,7 @@ where
 
         let ingress = Self {
             listening_addr,
-            concurrency_limit,
+            load_shed: LoadShedLayer::new(concurrency_limit),
             schemas,
             dispatcher,
             start_signal_tx,
@@ -86,10 +87,15 @@ where
         (ingress, start_signal_rx)
     }
 
+    /// Returns the handle to drain the ingress before its node is removed from the cluster.
+    pub fn drain_handle(&self) -> DrainHandle {
+        self.load_shed.drain_handle()
+    }
+
     pub async fn run(self) -> anyhow::Result<()> {
         let HyperServerIngress {
             listening_addr,
-            concurrency_limit,
+            load_shed,
             schemas,
             dispatcher,
             start_signal_tx,
@@ -113,7 +119,7 @@ where
         // Prepare the handler
         let service = ServiceBuilder::new()
             .layer(NormalizePathLayer::trim_trailing_slash())
-            .layer(layers::load_shed::LoadShedLayer::new(concurrency_limit))
+            .layer(load_shed)
             .layer(CorsLayer::very_permissive())
             .layer(layers::tracing_context_extractor::HttpTraceContextExtractorLayer)
             .service(Handler::new(schemas, dispatcher));
@@ -221,6 +227,7 @@ mod tests {
     use restate_test_util::assert_eq;
     use serde::{Deserialize, Serialize};
     use std::net::SocketAddr;
+    use std::time::Duration;
     use tokio::sync::{mpsc, Semaphore};
     use tokio::task::JoinHandle;
     use tracing_test::traced_test;
@@ -238,7 +245,7 @@ mod tests {
     #[tokio::test]
     #[traced_test]
     async fn test_http_post() {
-        let (address, input, handle) = bootstrap_test().await;
+        let (address, input, _, handle) = bootstrap_test().await;
         let process_fut = tokio::task::spawn(async move {
             // Get the function invocation and assert on it
             let (fid, method_name, argument, _, _, response_tx, _) =
@@ -294,7 +301,71 @@ mod tests {
         handle.close().await;
     }
 
-    async fn bootstrap_test() -> (SocketAddr, JoinHandle<Option<IngressRequest>>, TestHandle) {
+    #[tokio::test]
+    #[traced_test]
+    async fn test_drain_waits_for_in_flight_requests() {
+        let (address, input, drain_handle, handle) = bootstrap_test().await;
+        let client = Client::builder(TokioExecutor::new())
+            .http2_only(true)
+            .build_http::<Full<Bytes>>();
+        let greet = || {
+            http::Request::post(format!("http://{address}/greeter.Greeter/greet"))
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .body(Full::new(
+                    serde_json::to_vec(&GreetingRequest {
+                        person: "Francesco".to_string(),
+                    })
+                    .unwrap()
+                    .into(),
+                ))
+                .unwrap()
+        };
+
+        let in_flight_request = tokio::spawn(client.request(greet()));
+        let (_, _, _, _, _, response_tx, _) = input.await.unwrap().unwrap().expect_invocation();
+
+        // draining waits for the in-flight request
+        let drain = tokio::spawn({
+            let drain_handle = drain_handle.clone();
+            async move { drain_handle.drain().await }
+        });
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(!drain.is_finished());
+
+        // new requests are rejected
+        let http_response = client.request(greet()).await.unwrap();
+        assert_eq!(
+            http_response.status(),
+            http::StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        response_tx
+            .send(
+                Ok(serde_json::to_vec(&GreetingResponse {
+                    greeting: "Igal".to_string(),
+                })
+                .unwrap()
+                .into())
+                .into(),
+            )
+            .unwrap();
+        let http_response = in_flight_request.await.unwrap().unwrap();
+        assert_eq!(http_response.status(), http::StatusCode::OK);
+
+        tokio::time::timeout(Duration::from_secs(5), drain)
+            .await
+            .expect("drain completes once the in-flight request has completed")
+            .unwrap();
+
+        handle.close().await;
+    }
+
+    async fn bootstrap_test() -> (
+        SocketAddr,
+        JoinHandle<Option<IngressRequest>>,
+        DrainHandle,
+        TestHandle,
+    ) {
         let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
         let (ingress_request_tx, mut ingress_request_rx) = mpsc::unbounded_channel();
 
@@ -305,6 +376,7 @@ mod tests {
             mock_schemas(),
             MockDispatcher::new(ingress_request_tx),
         );
+        let drain_handle = ingress.drain_handle();
         node_env
             .tc
             .spawn(TaskKind::SystemService, "ingress", None, ingress.run())
@@ -316,7 +388,7 @@ mod tests {
         // Wait server to start
         let address = start_signal.await.unwrap();
 
-        (address, input, TestHandle(node_env.tc))
+        (address, input, drain_handle, TestHandle(node_env.tc))
     }
 
     struct TestHandle(TaskCenter);
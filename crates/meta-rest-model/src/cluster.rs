@@ -0,0 +1,176 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use serde::{Deserialize, Serialize};
+
+// Export schema types to be used by other crates without exposing the fact
+// that we are using proxying to restate-types
+pub use restate_types::node_liveness::NodeLiveness;
+pub use restate_types::nodes_config::{NodeState, Role};
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodesLivenessResponse {
+    /// # Version
+    ///
+    /// Version of the stored nodes liveness. It's 0 if the cluster controller has not
+    /// stored any liveness yet.
+    pub version: u32,
+    pub nodes: Vec<NodeLivenessResponse>,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeLivenessResponse {
+    /// # Node id
+    ///
+    /// Generational node id of the form `N<id>:<generation>`.
+    pub node_id: String,
+    pub liveness: NodeLiveness,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SplitPartitionRequest {
+    /// # Split key
+    ///
+    /// First partition key of the upper part of the key range, which is taken over by the new
+    /// partition. If unset, the key range is split in the middle.
+    #[serde(default)]
+    pub split_key: Option<u64>,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergePartitionsRequest {
+    /// # Other partition id
+    ///
+    /// Partition whose key range is adjacent to the key range of the merged partition.
+    pub other_partition_id: u64,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PartitionCreatedResponse {
+    /// # Partition id
+    ///
+    /// Id of the new partition which takes over the keys.
+    pub partition_id: u64,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeRolesResponse {
+    /// # Roles
+    ///
+    /// Roles of the node after the update. The node starts and stops the services of its roles
+    /// once it has learned about the update.
+    pub roles: Vec<Role>,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DrainNodeResponse {
+    /// # State
+    ///
+    /// State of the node. Draining nodes become drained once their partitions have been moved
+    /// to other nodes and their in-flight ingress requests have completed.
+    pub state: NodeState,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClusterStatusResponse {
+    pub nodes: Vec<NodeStatusResponse>,
+    pub partitions: Vec<PartitionStatusResponse>,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeStatusResponse {
+    /// # Node id
+    ///
+    /// Generational node id of the current generation of the node, of the form
+    /// `N<id>:<generation>`.
+    pub node_id: String,
+    pub name: String,
+    pub roles: Vec<Role>,
+    pub state: NodeState,
+    /// # Liveness
+    ///
+    /// Liveness of the current generation of the node. It's unset if the cluster controller has
+    /// not started tracking the node yet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub liveness: Option<NodeLiveness>,
+    /// # Partition processors
+    ///
+    /// Partition processors which the node has reported running.
+    pub partition_processors: Vec<PartitionProcessorStatusResponse>,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PartitionProcessorStatusResponse {
+    pub partition_id: u64,
+    pub run_mode: RunMode,
+    /// # Last applied LSN
+    ///
+    /// LSN of the last record of the partition's log which the processor has applied. It's unset
+    /// if the processor has not applied any record yet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_applied_lsn: Option<u64>,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunMode {
+    Leader,
+    Follower,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PartitionStatusResponse {
+    pub partition_id: u64,
+    /// # Start key
+    ///
+    /// First partition key of the partition's key range.
+    pub start_key: u64,
+    /// # End key
+    ///
+    /// Last partition key of the partition's key range, inclusive.
+    pub end_key: u64,
+    /// # Leader
+    ///
+    /// Generational node id of the node which leads the partition. It's unset if the partition
+    /// has not been placed yet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leader: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leader_epoch: Option<u64>,
+    /// # Followers
+    ///
+    /// Plain node ids of the nodes which follow the partition's log.
+    pub followers: Vec<String>,
+    /// # Applied LSN
+    ///
+    /// LSN of the last record which the leader of the partition has applied, or the furthest
+    /// replica if the leader has not reported its status yet. It's unset if no replica has
+    /// reported an applied record.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub applied_lsn: Option<u64>,
+    /// # Log tail
+    ///
+    /// LSN of the last record in the partition's log. It's unset if the log has no readable
+    /// records.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_tail: Option<u64>,
+}
@@ -0,0 +1,50 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use serde::{Deserialize, Serialize};
+
+// Export schema types to be used by other crates without exposing the fact
+// that we are using proxying to restate-types
+pub use restate_types::time::MillisSinceEpoch;
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InvocationResponse {
+    /// # Invocation id
+    pub id: String,
+    /// # Status
+    ///
+    /// Status of the invocation, either `invoked`, `suspended` or `completed`. Completed
+    /// invocations are kept until their completion retention time expired or they are purged.
+    pub status: String,
+    /// # Component
+    pub component: Option<String>,
+    /// # Component key
+    pub component_key: Option<String>,
+    /// # Handler
+    pub handler: Option<String>,
+    /// # Created at
+    ///
+    /// Milliseconds since the unix epoch at which the invocation was created.
+    pub created_at: Option<MillisSinceEpoch>,
+    /// # Modified at
+    ///
+    /// Milliseconds since the unix epoch at which the invocation was last modified. For
+    /// completed invocations, this is the completion time.
+    pub modified_at: Option<MillisSinceEpoch>,
+    /// # Completion result
+    ///
+    /// Either `success` or `failure` for completed invocations.
+    pub completion_result: Option<String>,
+    /// # Completion failure
+    ///
+    /// Failure of completed invocations which failed.
+    pub completion_failure: Option<String>,
+}
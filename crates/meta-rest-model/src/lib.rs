@@ -8,7 +8,10 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+pub mod cluster;
 pub mod components;
 pub mod deployments;
 pub mod handlers;
+pub mod invocations;
 pub mod subscriptions;
+pub mod tasks;
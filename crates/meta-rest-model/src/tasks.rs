@@ -0,0 +1,41 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use serde::{Deserialize, Serialize};
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TasksResponse {
+    pub tasks: Vec<TaskResponse>,
+}
+
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskResponse {
+    pub id: u64,
+    pub name: String,
+    /// # Kind
+    ///
+    /// Kind of the task which determines how it is treated on cancellation and failure.
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub partition_id: Option<u64>,
+    /// # Age
+    ///
+    /// Time since the task has been spawned.
+    #[serde(with = "serde_with::As::<serde_with::DisplayFromStr>")]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub age: humantime::Duration,
+    /// # Cancellation requested
+    ///
+    /// Whether the task has been asked to stop but hasn't finished yet.
+    pub cancellation_requested: bool,
+}
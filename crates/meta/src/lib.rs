@@ -12,10 +12,10 @@ mod error;
 mod service;
 mod storage;
 
+use restate_core::metadata_store::MetadataStoreClient;
 use restate_schema_impl::Schemas;
 use restate_service_client::AssumeRoleCacheMode;
 use restate_types::retries::RetryPolicy;
-use std::path::{Path, PathBuf};
 
 pub use error::Error;
 pub use restate_service_client::{
@@ -23,62 +23,39 @@ pub use restate_service_client::{
     OptionsBuilderError as LambdaClientOptionsBuilderError,
 };
 pub use service::{ApplyMode, Force, MetaHandle, MetaService};
-pub use storage::{FileMetaReader, FileMetaStorage, MetaReader, MetaStorage};
+pub use storage::{
+    decode_schema_updates, encode_schema_updates, MetaReader, MetaStorage, MetadataStoreMetaReader,
+    MetadataStoreMetaStorage,
+};
 
 use std::time::Duration;
 
-use codederror::CodedError;
 use restate_schema_api::subscription::SubscriptionValidator;
-use restate_types::DEFAULT_STORAGE_DIRECTORY;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 
-#[derive(Debug, thiserror::Error, CodedError)]
-#[error("failed building the meta service: {0}")]
-pub struct BuildError(
-    #[from]
-    #[code]
-    storage::BuildError,
-);
-
 /// # Meta options
 #[serde_as]
-#[derive(Debug, Clone, Serialize, Deserialize, derive_builder::Builder)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, derive_builder::Builder)]
 #[cfg_attr(feature = "options_schema", derive(schemars::JsonSchema))]
 #[cfg_attr(feature = "options_schema", schemars(rename = "MetaOptions", default))]
 #[builder(default)]
 pub struct Options {
-    /// # Storage path
-    ///
-    /// Root path for Meta storage.
-    storage_path: PathBuf,
-
     service_client: ServiceClientOptions,
 }
 
-impl Default for Options {
-    fn default() -> Self {
-        Self {
-            storage_path: Path::new(DEFAULT_STORAGE_DIRECTORY).join("meta"),
-            service_client: Default::default(),
-        }
-    }
-}
-
 impl Options {
-    pub fn storage_path(&self) -> &Path {
-        self.storage_path.as_path()
-    }
-
+    /// Builds the meta service which stores the schema information in the metadata store.
     pub fn build<SV: SubscriptionValidator>(
         self,
         subscription_validator: SV,
-    ) -> Result<MetaService<FileMetaStorage, SV>, BuildError> {
+        metadata_store_client: MetadataStoreClient,
+    ) -> MetaService<MetadataStoreMetaStorage, SV> {
         let schemas = Schemas::default();
         let client = self.service_client.build(AssumeRoleCacheMode::None);
-        Ok(MetaService::new(
+        MetaService::new(
             schemas.clone(),
-            FileMetaStorage::new(self.storage_path)?,
+            MetadataStoreMetaStorage::new(metadata_store_client),
             subscription_validator,
             // Total duration roughly 66 seconds
             RetryPolicy::exponential(
@@ -88,6 +65,6 @@ impl Options {
                 Some(Duration::from_secs(20)),
             ),
             client,
-        ))
+        )
     }
 }
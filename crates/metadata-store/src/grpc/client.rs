@@ -0,0 +1,230 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::grpc::pb_conversions::ConversionError;
+use crate::grpc_svc::metadata_store_svc_client::MetadataStoreSvcClient;
+use crate::grpc_svc::{
+    DeleteRequest, GetRequest, ListRequest, PutRequest, RestoreRequest, TransactionRequest,
+    WatchRequest,
+};
+use crate::{
+    FailedPrecondition, MetadataStore, MetadataStoreSnapshot, Precondition, ReadError,
+    TransactionError, TransactionOperations, VersionedValue, WatchEvent, WatchStream, WriteError,
+};
+use async_trait::async_trait;
+use bytestring::ByteString;
+use futures::{StreamExt, TryStreamExt};
+use restate_grpc_util::{create_grpc_channel_from_advertised_address, TlsConfig};
+use restate_types::net::AdvertisedAddress;
+use restate_types::Version;
+use tonic::transport::Channel;
+use tonic::{Code, Status};
+
+/// Client end to interact with a metadata store through its grpc service.
+#[derive(Debug, Clone)]
+pub struct GrpcMetadataStoreClient {
+    svc_client: MetadataStoreSvcClient<Channel>,
+}
+impl GrpcMetadataStoreClient {
+    pub fn new(metadata_store_address: AdvertisedAddress, tls: Option<&TlsConfig>) -> Self {
+        // the server's certificate must be valid for the host of its address
+        let tls = tls.map(|tls| tls.client_config(None));
+        let channel = create_grpc_channel_from_advertised_address(metadata_store_address, tls)
+            .expect("should not fail");
+
+        Self {
+            svc_client: MetadataStoreSvcClient::new(channel),
+        }
+    }
+}
+
+#[async_trait]
+impl MetadataStore for GrpcMetadataStoreClient {
+    async fn get(&self, key: ByteString) -> Result<Option<VersionedValue>, ReadError> {
+        let response = self
+            .svc_client
+            .clone()
+            .get(GetRequest { key: key.into() })
+            .await
+            .map_err(map_status_to_read_error)?;
+
+        response
+            .into_inner()
+            .try_into()
+            .map_err(|err: ConversionError| ReadError::Internal(err.to_string()))
+    }
+
+    async fn get_version(&self, key: ByteString) -> Result<Option<Version>, ReadError> {
+        let response = self
+            .svc_client
+            .clone()
+            .get_version(GetRequest { key: key.into() })
+            .await
+            .map_err(map_status_to_read_error)?;
+
+        Ok(response.into_inner().into())
+    }
+
+    async fn put(
+        &self,
+        key: ByteString,
+        value: VersionedValue,
+        precondition: Precondition,
+    ) -> Result<(), WriteError> {
+        self.svc_client
+            .clone()
+            .put(PutRequest {
+                key: key.into(),
+                value: Some(value.into()),
+                precondition: Some(precondition.into()),
+            })
+            .await
+            .map_err(map_status_to_write_error)?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, key: ByteString, precondition: Precondition) -> Result<(), WriteError> {
+        self.svc_client
+            .clone()
+            .delete(DeleteRequest {
+                key: key.into(),
+                precondition: Some(precondition.into()),
+            })
+            .await
+            .map_err(map_status_to_write_error)?;
+
+        Ok(())
+    }
+
+    async fn transaction(&self, operations: TransactionOperations) -> Result<(), TransactionError> {
+        let response = self
+            .svc_client
+            .clone()
+            .transaction(TransactionRequest {
+                operations: operations.into_iter().map(Into::into).collect(),
+            })
+            .await
+            .map_err(map_status_to_transaction_error)?;
+
+        let failed_preconditions = response
+            .into_inner()
+            .failed_preconditions
+            .into_iter()
+            .map(FailedPrecondition::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err: ConversionError| TransactionError::Internal(err.to_string()))?;
+
+        if failed_preconditions.is_empty() {
+            Ok(())
+        } else {
+            Err(TransactionError::FailedPreconditions(failed_preconditions))
+        }
+    }
+
+    async fn list(
+        &self,
+        prefix: ByteString,
+    ) -> Result<Vec<(ByteString, VersionedValue)>, ReadError> {
+        let response = self
+            .svc_client
+            .clone()
+            .list(ListRequest {
+                prefix: prefix.into(),
+            })
+            .await
+            .map_err(map_status_to_read_error)?;
+
+        response
+            .into_inner()
+            .kv_pairs
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_, _>>()
+            .map_err(|err: ConversionError| ReadError::Internal(err.to_string()))
+    }
+
+    async fn snapshot(&self) -> Result<MetadataStoreSnapshot, ReadError> {
+        let response = self
+            .svc_client
+            .clone()
+            .snapshot(())
+            .await
+            .map_err(map_status_to_read_error)?;
+
+        response
+            .into_inner()
+            .kv_pairs
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_, _>>()
+            .map(MetadataStoreSnapshot::new)
+            .map_err(|err: ConversionError| ReadError::Internal(err.to_string()))
+    }
+
+    async fn restore(&self, snapshot: MetadataStoreSnapshot) -> Result<(), WriteError> {
+        self.svc_client
+            .clone()
+            .restore(RestoreRequest {
+                kv_pairs: snapshot
+                    .into_kv_pairs()
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+            })
+            .await
+            .map_err(map_status_to_write_error)?;
+
+        Ok(())
+    }
+
+    async fn watch_prefix(&self, prefix: ByteString) -> Result<WatchStream, ReadError> {
+        let response = self
+            .svc_client
+            .clone()
+            .watch(WatchRequest {
+                prefix: prefix.into(),
+            })
+            .await
+            .map_err(map_status_to_read_error)?;
+
+        Ok(response
+            .into_inner()
+            .map_err(map_status_to_read_error)
+            .and_then(|event| async move {
+                WatchEvent::try_from(event)
+                    .map_err(|err: ConversionError| ReadError::Internal(err.to_string()))
+            })
+            .boxed())
+    }
+}
+
+fn map_status_to_read_error(status: Status) -> ReadError {
+    match &status.code() {
+        Code::Unavailable => ReadError::Network(status.into()),
+        _ => ReadError::Internal(status.to_string()),
+    }
+}
+
+fn map_status_to_write_error(status: Status) -> WriteError {
+    match &status.code() {
+        Code::Unavailable => WriteError::Network(status.into()),
+        Code::FailedPrecondition => WriteError::FailedPrecondition(status.message().to_string()),
+        _ => WriteError::Internal(status.to_string()),
+    }
+}
+
+fn map_status_to_transaction_error(status: Status) -> TransactionError {
+    match &status.code() {
+        Code::Unavailable => TransactionError::Network(status.into()),
+        Code::InvalidArgument => TransactionError::InvalidArgument(status.message().to_string()),
+        _ => TransactionError::Internal(status.to_string()),
+    }
+}
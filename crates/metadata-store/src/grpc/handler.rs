@@ -0,0 +1,286 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::grpc::pb_conversions::ConversionError;
+use crate::grpc_svc;
+use crate::grpc_svc::metadata_store_svc_server::MetadataStoreSvc;
+use crate::grpc_svc::{
+    DeleteRequest, GetRequest, GetResponse, GetVersionResponse, ListRequest, ListResponse,
+    PutRequest, RestoreRequest, SnapshotResponse, TransactionRequest, TransactionResponse,
+    WatchRequest,
+};
+use crate::request::{Error, MetadataStoreRequest, RequestSender};
+use crate::MetadataStoreSnapshot;
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use tokio::sync::oneshot;
+use tonic::{Request, Response, Status};
+
+/// Grpc svc handler which passes the requests on to a metadata store implementation.
+#[derive(Debug)]
+pub struct MetadataStoreHandler {
+    request_tx: RequestSender,
+}
+
+impl MetadataStoreHandler {
+    pub fn new(request_tx: RequestSender) -> Self {
+        Self { request_tx }
+    }
+}
+
+#[async_trait]
+impl MetadataStoreSvc for MetadataStoreHandler {
+    type WatchStream = BoxStream<'static, Result<grpc_svc::WatchEvent, Status>>;
+
+    async fn get(&self, request: Request<GetRequest>) -> Result<Response<GetResponse>, Status> {
+        let (result_tx, result_rx) = oneshot::channel();
+
+        let request = request.into_inner();
+        self.request_tx
+            .send(MetadataStoreRequest::Get {
+                key: request.key.into(),
+                result_tx,
+            })
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))?;
+
+        let result = result_rx
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))??;
+
+        Ok(Response::new(GetResponse {
+            value: result.map(Into::into),
+        }))
+    }
+
+    async fn get_version(
+        &self,
+        request: Request<GetRequest>,
+    ) -> Result<Response<GetVersionResponse>, Status> {
+        let (result_tx, result_rx) = oneshot::channel();
+
+        let request = request.into_inner();
+        self.request_tx
+            .send(MetadataStoreRequest::GetVersion {
+                key: request.key.into(),
+                result_tx,
+            })
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))?;
+
+        let result = result_rx
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))??;
+
+        Ok(Response::new(GetVersionResponse {
+            version: result.map(Into::into),
+        }))
+    }
+
+    async fn put(&self, request: Request<PutRequest>) -> Result<Response<()>, Status> {
+        let (result_tx, result_rx) = oneshot::channel();
+
+        let request = request.into_inner();
+        self.request_tx
+            .send(MetadataStoreRequest::Put {
+                key: request.key.into(),
+                value: request
+                    .value
+                    .ok_or_else(|| Status::invalid_argument("missing value field"))?
+                    .try_into()
+                    .map_err(|err: ConversionError| Status::invalid_argument(err.to_string()))?,
+                precondition: request
+                    .precondition
+                    .ok_or_else(|| Status::invalid_argument("missing precondition field"))?
+                    .try_into()
+                    .map_err(|err: ConversionError| Status::invalid_argument(err.to_string()))?,
+                result_tx,
+            })
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))?;
+
+        result_rx
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))??;
+
+        Ok(Response::new(()))
+    }
+
+    async fn delete(&self, request: Request<DeleteRequest>) -> Result<Response<()>, Status> {
+        let (result_tx, result_rx) = oneshot::channel();
+
+        let request = request.into_inner();
+        self.request_tx
+            .send(MetadataStoreRequest::Delete {
+                key: request.key.into(),
+                precondition: request
+                    .precondition
+                    .ok_or_else(|| Status::invalid_argument("missing precondition field"))?
+                    .try_into()
+                    .map_err(|err: ConversionError| Status::invalid_argument(err.to_string()))?,
+                result_tx,
+            })
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))?;
+
+        result_rx
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))??;
+
+        Ok(Response::new(()))
+    }
+
+    async fn transaction(
+        &self,
+        request: Request<TransactionRequest>,
+    ) -> Result<Response<TransactionResponse>, Status> {
+        let (result_tx, result_rx) = oneshot::channel();
+
+        let operations = request
+            .into_inner()
+            .operations
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_, _>>()
+            .map_err(|err: ConversionError| Status::invalid_argument(err.to_string()))?;
+        self.request_tx
+            .send(MetadataStoreRequest::Transaction {
+                operations,
+                result_tx,
+            })
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))?;
+
+        let result = result_rx
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))?;
+
+        let failed_preconditions = match result {
+            Ok(()) => Vec::new(),
+            Err(Error::FailedPreconditions(failed_preconditions)) => failed_preconditions,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Response::new(TransactionResponse {
+            failed_preconditions: failed_preconditions.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn list(&self, request: Request<ListRequest>) -> Result<Response<ListResponse>, Status> {
+        let (result_tx, result_rx) = oneshot::channel();
+
+        let request = request.into_inner();
+        self.request_tx
+            .send(MetadataStoreRequest::List {
+                prefix: request.prefix.into(),
+                result_tx,
+            })
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))?;
+
+        let result = result_rx
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))??;
+
+        Ok(Response::new(ListResponse {
+            kv_pairs: result.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn watch(
+        &self,
+        request: Request<WatchRequest>,
+    ) -> Result<Response<Self::WatchStream>, Status> {
+        let (result_tx, result_rx) = oneshot::channel();
+
+        let prefix = request.into_inner().prefix;
+        self.request_tx
+            .send(MetadataStoreRequest::Watch {
+                prefix: prefix.clone().into(),
+                result_tx,
+            })
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))?;
+
+        let subscription = result_rx
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))??;
+
+        Ok(Response::new(
+            subscription
+                .into_stream(prefix.into())
+                .map(|event| {
+                    event
+                        .map(Into::into)
+                        .map_err(|err| Status::aborted(err.to_string()))
+                })
+                .boxed(),
+        ))
+    }
+
+    async fn snapshot(&self, _request: Request<()>) -> Result<Response<SnapshotResponse>, Status> {
+        let (result_tx, result_rx) = oneshot::channel();
+
+        self.request_tx
+            .send(MetadataStoreRequest::Snapshot { result_tx })
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))?;
+
+        let snapshot = result_rx
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))??;
+
+        Ok(Response::new(SnapshotResponse {
+            kv_pairs: snapshot
+                .into_kv_pairs()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }))
+    }
+
+    async fn restore(&self, request: Request<RestoreRequest>) -> Result<Response<()>, Status> {
+        let (result_tx, result_rx) = oneshot::channel();
+
+        let kv_pairs = request
+            .into_inner()
+            .kv_pairs
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_, _>>()
+            .map_err(|err: ConversionError| Status::invalid_argument(err.to_string()))?;
+        self.request_tx
+            .send(MetadataStoreRequest::Restore {
+                snapshot: MetadataStoreSnapshot::new(kv_pairs),
+                result_tx,
+            })
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))?;
+
+        result_rx
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))??;
+
+        Ok(Response::new(()))
+    }
+}
+
+impl From<Error> for Status {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::FailedPrecondition(msg) => Status::failed_precondition(msg),
+            Error::InvalidArgument(msg) => Status::invalid_argument(msg),
+            Error::Unavailable(msg) => Status::unavailable(msg),
+            Error::Unimplemented(msg) => Status::unimplemented(msg),
+            err => Status::internal(err.to_string()),
+        }
+    }
+}
@@ -13,7 +13,11 @@ pub mod handler;
 
 pub mod pb_conversions {
     use crate::grpc_svc::{GetResponse, GetVersionResponse, PreconditionKind};
-    use crate::{grpc_svc, Precondition, VersionedValue};
+    use crate::{
+        grpc_svc, FailedPrecondition, Precondition, TransactionOperation, VersionedValue,
+        WatchEvent,
+    };
+    use bytestring::ByteString;
     use restate_types::Version;
 
     #[derive(Debug, thiserror::Error)]
@@ -72,6 +76,102 @@ pub mod pb_conversions {
         }
     }
 
+    impl From<(ByteString, VersionedValue)> for grpc_svc::KvPair {
+        fn from((key, value): (ByteString, VersionedValue)) -> Self {
+            grpc_svc::KvPair {
+                key: key.into(),
+                value: Some(value.into()),
+            }
+        }
+    }
+
+    impl TryFrom<grpc_svc::KvPair> for (ByteString, VersionedValue) {
+        type Error = ConversionError;
+
+        fn try_from(value: grpc_svc::KvPair) -> Result<Self, Self::Error> {
+            let versioned_value = value
+                .value
+                .ok_or_else(|| ConversionError::missing_field("value"))?;
+            Ok((value.key.into(), versioned_value.try_into()?))
+        }
+    }
+
+    impl From<(ByteString, Precondition, TransactionOperation)> for grpc_svc::TransactionOperation {
+        fn from(
+            (key, precondition, operation): (ByteString, Precondition, TransactionOperation),
+        ) -> Self {
+            grpc_svc::TransactionOperation {
+                key: key.into(),
+                precondition: Some(precondition.into()),
+                value: match operation {
+                    TransactionOperation::Put(value) => Some(value.into()),
+                    TransactionOperation::Delete => None,
+                },
+            }
+        }
+    }
+
+    impl TryFrom<grpc_svc::TransactionOperation> for (ByteString, Precondition, TransactionOperation) {
+        type Error = ConversionError;
+
+        fn try_from(value: grpc_svc::TransactionOperation) -> Result<Self, Self::Error> {
+            let precondition = value
+                .precondition
+                .ok_or_else(|| ConversionError::missing_field("precondition"))?
+                .try_into()?;
+            let operation = match value.value {
+                Some(value) => TransactionOperation::Put(value.try_into()?),
+                None => TransactionOperation::Delete,
+            };
+            Ok((value.key.into(), precondition, operation))
+        }
+    }
+
+    impl From<FailedPrecondition> for grpc_svc::FailedPrecondition {
+        fn from(value: FailedPrecondition) -> Self {
+            grpc_svc::FailedPrecondition {
+                key: value.key.into(),
+                precondition: Some(value.precondition.into()),
+                current_version: value.current_version.map(Into::into),
+            }
+        }
+    }
+
+    impl TryFrom<grpc_svc::FailedPrecondition> for FailedPrecondition {
+        type Error = ConversionError;
+
+        fn try_from(value: grpc_svc::FailedPrecondition) -> Result<Self, Self::Error> {
+            Ok(FailedPrecondition::new(
+                value.key.into(),
+                value
+                    .precondition
+                    .ok_or_else(|| ConversionError::missing_field("precondition"))?
+                    .try_into()?,
+                value.current_version.map(Into::into),
+            ))
+        }
+    }
+
+    impl From<WatchEvent> for grpc_svc::WatchEvent {
+        fn from(value: WatchEvent) -> Self {
+            grpc_svc::WatchEvent {
+                key: value.key.into(),
+                value: value.value.map(Into::into),
+            }
+        }
+    }
+
+    impl TryFrom<grpc_svc::WatchEvent> for WatchEvent {
+        type Error = ConversionError;
+
+        fn try_from(value: grpc_svc::WatchEvent) -> Result<Self, Self::Error> {
+            Ok(WatchEvent::new(
+                value.key.into(),
+                value.value.map(TryInto::try_into).transpose()?,
+            ))
+        }
+    }
+
     impl From<grpc_svc::Version> for Version {
         fn from(value: grpc_svc::Version) -> Self {
             Version::from(value.value)
@@ -8,22 +8,26 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-mod grpc;
 mod store;
 
 mod service;
 
 pub use options::Options;
+use restate_grpc_util::TlsConfig;
 use restate_types::net::AdvertisedAddress;
 pub use service::LocalMetadataStoreService;
 pub use store::BuildError;
 
-use crate::local::grpc::client::LocalMetadataStoreClient;
+use crate::grpc::client::GrpcMetadataStoreClient;
 use crate::MetadataStoreClient;
 
 /// Creates a [`MetadataStoreClient`] for the [`LocalMetadataStoreService`].
-pub fn create_client(advertised_address: AdvertisedAddress) -> MetadataStoreClient {
-    MetadataStoreClient::new(LocalMetadataStoreClient::new(advertised_address))
+/// If a TLS configuration is given, `https` addresses are connected to with TLS.
+pub fn create_client(
+    advertised_address: AdvertisedAddress,
+    tls: Option<&TlsConfig>,
+) -> MetadataStoreClient {
+    MetadataStoreClient::new(GrpcMetadataStoreClient::new(advertised_address, tls))
 }
 
 mod options;
@@ -10,6 +10,7 @@
 
 use crate::local::store::{BuildError, LocalMetadataStore};
 use crate::local::LocalMetadataStoreService;
+use restate_grpc_util::TlsConfig;
 use restate_types::net::BindAddress;
 use restate_types::DEFAULT_STORAGE_DIRECTORY;
 use std::path::{Path, PathBuf};
@@ -42,9 +43,15 @@ impl Default for Options {
 }
 
 impl Options {
-    pub fn build(self) -> Result<LocalMetadataStoreService, BuildError> {
+    /// Builds the metadata store service. If a TLS configuration is given, the service only
+    /// accepts TLS connections.
+    pub fn build(self, tls: Option<TlsConfig>) -> Result<LocalMetadataStoreService, BuildError> {
         let store = LocalMetadataStore::new(self.path, self.request_queue_length)?;
-        Ok(LocalMetadataStoreService::new(store, self.bind_address))
+        Ok(LocalMetadataStoreService::new(
+            store,
+            self.bind_address,
+            tls,
+        ))
     }
 
     pub fn storage_path(&self) -> &Path {
@@ -8,17 +8,19 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use crate::grpc::handler::MetadataStoreHandler;
 use crate::grpc_svc;
 use crate::grpc_svc::metadata_store_svc_server::MetadataStoreSvcServer;
-use crate::local::grpc::handler::LocalMetadataStoreHandler;
 use crate::local::store::LocalMetadataStore;
 use restate_core::{cancellation_watcher, task_center, ShutdownError, TaskKind};
+use restate_grpc_util::TlsConfig;
 use restate_types::net::BindAddress;
 use tonic::server::NamedService;
 
 pub struct LocalMetadataStoreService {
     metadata_store: LocalMetadataStore,
     bind_address: BindAddress,
+    tls: Option<TlsConfig>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -32,15 +34,20 @@ pub enum Error {
 }
 
 impl LocalMetadataStoreService {
-    pub fn new(metadata_store: LocalMetadataStore, bind_address: BindAddress) -> Self {
+    pub fn new(
+        metadata_store: LocalMetadataStore,
+        bind_address: BindAddress,
+        tls: Option<TlsConfig>,
+    ) -> Self {
         Self {
             metadata_store,
             bind_address,
+            tls,
         }
     }
 
     pub fn grpc_service_name(&self) -> &str {
-        MetadataStoreSvcServer::<LocalMetadataStoreHandler>::NAME
+        MetadataStoreSvcServer::<MetadataStoreHandler>::NAME
     }
 
     pub async fn run(self) -> Result<(), Error> {
@@ -54,13 +61,13 @@ impl LocalMetadataStoreService {
 
         let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
         health_reporter
-            .set_serving::<MetadataStoreSvcServer<LocalMetadataStoreHandler>>()
+            .set_serving::<MetadataStoreSvcServer<MetadataStoreHandler>>()
             .await;
 
         let server_builder = tonic::transport::Server::builder()
             .layer(tower_http::trace::TraceLayer::new_for_grpc().make_span_with(span_factory))
             .add_service(health_service)
-            .add_service(MetadataStoreSvcServer::new(LocalMetadataStoreHandler::new(
+            .add_service(MetadataStoreSvcServer::new(MetadataStoreHandler::new(
                 self.metadata_store.request_sender(),
             )))
             .add_service(reflection_service_builder.build()?);
@@ -74,6 +81,7 @@ impl LocalMetadataStoreService {
             async move {
                 restate_grpc_util::run_hyper_server(
                     &self.bind_address,
+                    self.tls.as_ref(),
                     service,
                     cancellation_watcher(),
                     "metadata-store-grpc",
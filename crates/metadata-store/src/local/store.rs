@@ -8,75 +8,30 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use crate::{Precondition, VersionedValue};
+use crate::request::{
+    check_transaction, Error, MetadataStoreRequest, RequestReceiver, RequestSender,
+    WatchSubscription, Watches,
+};
+use crate::{
+    FailedPrecondition, MetadataStoreSnapshot, Precondition, TransactionOperation,
+    TransactionOperations, VersionedValue,
+};
 use bytes::Bytes;
 use bytestring::ByteString;
 use codederror::CodedError;
 use restate_core::cancellation_watcher;
-use restate_types::errors::GenericError;
 use restate_types::Version;
-use rocksdb::{ColumnFamily, Options, WriteOptions, DB};
+use rocksdb::{ColumnFamily, Direction, IteratorMode, Options, WriteBatch, WriteOptions, DB};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::path::Path;
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::mpsc;
 use tracing::{debug, trace};
 
-pub type RequestSender = mpsc::Sender<MetadataStoreRequest>;
-pub type RequestReceiver = mpsc::Receiver<MetadataStoreRequest>;
-
 type Result<T> = std::result::Result<T, Error>;
 
 const KV_PAIRS: &str = "kv_pairs";
 
-#[derive(Debug)]
-pub enum MetadataStoreRequest {
-    Get {
-        key: ByteString,
-        result_tx: oneshot::Sender<Result<Option<VersionedValue>>>,
-    },
-    GetVersion {
-        key: ByteString,
-        result_tx: oneshot::Sender<Result<Option<Version>>>,
-    },
-    Put {
-        key: ByteString,
-        value: VersionedValue,
-        precondition: Precondition,
-        result_tx: oneshot::Sender<Result<()>>,
-    },
-    Delete {
-        key: ByteString,
-        precondition: Precondition,
-        result_tx: oneshot::Sender<Result<()>>,
-    },
-}
-
-#[derive(Debug, thiserror::Error)]
-pub enum Error {
-    #[error("storage error: {0}")]
-    Storage(#[from] rocksdb::Error),
-    #[error("failed precondition: {0}")]
-    FailedPrecondition(String),
-    #[error("invalid argument: {0}")]
-    InvalidArgument(String),
-    #[error("codec error: {0}")]
-    Codec(GenericError),
-}
-
-impl Error {
-    fn kv_pair_exists() -> Self {
-        Error::FailedPrecondition("key-value pair already exists".to_owned())
-    }
-
-    fn version_mismatch(expected: Version, actual: Option<Version>) -> Self {
-        Error::FailedPrecondition(format!(
-            "Expected version '{}' but found version '{:?}'",
-            expected, actual
-        ))
-    }
-}
-
 #[derive(Debug, thiserror::Error, CodedError)]
 pub enum BuildError {
     #[error("failed opening rocksdb: {0}")]
@@ -92,6 +47,7 @@ pub struct LocalMetadataStore {
     db: DB,
     write_opts: WriteOptions,
     request_rx: RequestReceiver,
+    watches: Watches,
 
     // for creating other senders
     request_tx: RequestSender,
@@ -115,6 +71,7 @@ impl LocalMetadataStore {
             db,
             write_opts: Self::default_write_options(),
             request_rx,
+            watches: Watches::default(),
             request_tx,
         })
     }
@@ -206,6 +163,37 @@ impl LocalMetadataStore {
                 Self::log_error(&result, "Delete");
                 let _ = result_tx.send(result);
             }
+            MetadataStoreRequest::Transaction {
+                operations,
+                result_tx,
+            } => {
+                let result = self.transaction(operations);
+                Self::log_error(&result, "Transaction");
+                let _ = result_tx.send(result);
+            }
+            MetadataStoreRequest::List { prefix, result_tx } => {
+                let result = self.list(&prefix);
+                Self::log_error(&result, "List");
+                let _ = result_tx.send(result);
+            }
+            MetadataStoreRequest::Watch { prefix, result_tx } => {
+                let result = self.watch(&prefix);
+                Self::log_error(&result, "Watch");
+                let _ = result_tx.send(result);
+            }
+            MetadataStoreRequest::Snapshot { result_tx } => {
+                let result = self.snapshot();
+                Self::log_error(&result, "Snapshot");
+                let _ = result_tx.send(result);
+            }
+            MetadataStoreRequest::Restore {
+                snapshot,
+                result_tx,
+            } => {
+                let result = self.restore(snapshot);
+                Self::log_error(&result, "Restore");
+                let _ = result_tx.send(result);
+            }
         };
     }
 
@@ -265,6 +253,7 @@ impl LocalMetadataStore {
         let versioned_value = Self::encode(value)?;
         self.db
             .put_cf_opt(cf_handle, key, versioned_value, &self.write_opts)?;
+        self.watches.notify(key.clone(), Some(value.clone()));
         Ok(())
     }
 
@@ -295,9 +284,111 @@ impl LocalMetadataStore {
     }
 
     fn delete_kv_pair(&self, key: &ByteString) -> Result<()> {
+        if self.get_version(key)?.is_none() {
+            // nothing to delete
+            return Ok(());
+        }
+
         self.db
-            .delete_cf_opt(self.kv_cf_handle(), key, &self.write_opts)
-            .map_err(Into::into)
+            .delete_cf_opt(self.kv_cf_handle(), key, &self.write_opts)?;
+        self.watches.notify(key.clone(), None);
+        Ok(())
+    }
+
+    fn transaction(&self, operations: TransactionOperations) -> Result<()> {
+        check_transaction(&operations)?;
+
+        let mut failed_preconditions = Vec::new();
+        let mut current_versions = Vec::with_capacity(operations.len());
+        for (key, precondition, _) in &operations {
+            let current_version = self.get_version(key)?;
+            if !precondition.holds(current_version) {
+                failed_preconditions.push(FailedPrecondition::new(
+                    key.clone(),
+                    precondition.clone(),
+                    current_version,
+                ));
+            }
+            current_versions.push(current_version);
+        }
+
+        if !failed_preconditions.is_empty() {
+            return Err(Error::FailedPreconditions(failed_preconditions));
+        }
+
+        let mut batch = WriteBatch::default();
+        let mut changes = Vec::with_capacity(operations.len());
+        for ((key, _, operation), current_version) in operations.into_iter().zip(current_versions) {
+            match operation {
+                TransactionOperation::Put(value) => {
+                    batch.put_cf(self.kv_cf_handle(), &key, Self::encode(&value)?);
+                    changes.push((key, Some(value)));
+                }
+                // deleting a non-existing key-value pair is a no-op
+                TransactionOperation::Delete if current_version.is_none() => {}
+                TransactionOperation::Delete => {
+                    batch.delete_cf(self.kv_cf_handle(), &key);
+                    changes.push((key, None));
+                }
+            }
+        }
+
+        self.db.write_opt(batch, &self.write_opts)?;
+        for (key, value) in changes {
+            self.watches.notify(key, value);
+        }
+        Ok(())
+    }
+
+    fn list(&self, prefix: &ByteString) -> Result<Vec<(ByteString, VersionedValue)>> {
+        let iter = self.db.iterator_cf(
+            self.kv_cf_handle(),
+            IteratorMode::From(prefix.as_bytes(), Direction::Forward),
+        );
+
+        let mut kv_pairs = Vec::new();
+        for item in iter {
+            let (key, value) = item?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let key = String::from_utf8(key.into_vec()).map_err(|err| Error::Codec(err.into()))?;
+            kv_pairs.push((ByteString::from(key), Self::decode(value)?));
+        }
+        Ok(kv_pairs)
+    }
+
+    fn watch(&self, prefix: &ByteString) -> Result<WatchSubscription> {
+        // requests are processed one after the other, no change can happen in between
+        Ok(self.watches.subscribe(self.list(prefix)?))
+    }
+
+    fn snapshot(&self) -> Result<MetadataStoreSnapshot> {
+        // requests are processed one after the other, no change can happen while listing
+        Ok(MetadataStoreSnapshot::new(
+            self.list(&ByteString::default())?,
+        ))
+    }
+
+    fn restore(&self, snapshot: MetadataStoreSnapshot) -> Result<()> {
+        let mut iter = self
+            .db
+            .iterator_cf(self.kv_cf_handle(), IteratorMode::Start);
+        if iter.next().transpose()?.is_some() {
+            return Err(Error::not_empty());
+        }
+
+        let mut batch = WriteBatch::default();
+        for (key, value) in snapshot.kv_pairs() {
+            batch.put_cf(self.kv_cf_handle(), key, Self::encode(value)?);
+        }
+
+        self.db.write_opt(batch, &self.write_opts)?;
+        debug!("Restored {} key-value pairs", snapshot.kv_pairs().len());
+        for (key, value) in snapshot.into_kv_pairs() {
+            self.watches.notify(key, Some(value));
+        }
+        Ok(())
     }
 
     fn encode<T: Serialize>(value: T) -> Result<Bytes> {
@@ -8,10 +8,13 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use crate::local::grpc::client::LocalMetadataStoreClient;
+use crate::grpc::client::GrpcMetadataStoreClient;
 use crate::local::service::LocalMetadataStoreService;
 use crate::local::store::LocalMetadataStore;
-use crate::{MetadataStoreClient, Precondition, WriteError};
+use crate::{
+    MetadataStoreClient, MetadataStoreSnapshot, Precondition, TransactionError,
+    TransactionOperation, WriteError,
+};
 use bytestring::ByteString;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
@@ -141,6 +144,195 @@ async fn basic_metadata_store_operations() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests listing and watching key-value pairs by prefix.
+#[test(tokio::test)]
+async fn list_and_watch() -> anyhow::Result<()> {
+    let (client, env) = create_test_environment().await?;
+
+    env.tc
+        .run_in_scope("test", None, async move {
+            let value = |version: u32| Value {
+                version: Version::from(version),
+                value: format!("value-{version}"),
+            };
+
+            client
+                .put("nodes/1".into(), value(1), Precondition::None)
+                .await?;
+            client
+                .put("nodes/2".into(), value(1), Precondition::None)
+                .await?;
+            client
+                .put("partitions/1".into(), value(1), Precondition::None)
+                .await?;
+
+            let listed = client.list::<Value>("nodes/".into()).await?;
+            assert_eq!(
+                listed,
+                vec![
+                    (ByteString::from("nodes/1"), value(1)),
+                    (ByteString::from("nodes/2"), value(1))
+                ]
+            );
+
+            let mut prefix_watch = client.watch_prefix::<Value>("nodes/".into()).await?;
+            let mut key_watch = client.watch::<Value>("nodes/2".into()).await?;
+
+            // current values first
+            assert_eq!(
+                prefix_watch.next().await.transpose()?,
+                Some((ByteString::from("nodes/1"), Some(value(1))))
+            );
+            assert_eq!(
+                prefix_watch.next().await.transpose()?,
+                Some((ByteString::from("nodes/2"), Some(value(1))))
+            );
+            assert_eq!(key_watch.next().await.transpose()?, Some(Some(value(1))));
+
+            // then changes
+            client
+                .put(
+                    "partitions/1".into(),
+                    value(2),
+                    Precondition::MatchesVersion(Version::from(1)),
+                )
+                .await?;
+            client
+                .put(
+                    "nodes/2".into(),
+                    value(2),
+                    Precondition::MatchesVersion(Version::from(1)),
+                )
+                .await?;
+            client.delete("nodes/1".into(), Precondition::None).await?;
+
+            assert_eq!(
+                prefix_watch.next().await.transpose()?,
+                Some((ByteString::from("nodes/2"), Some(value(2))))
+            );
+            assert_eq!(
+                prefix_watch.next().await.transpose()?,
+                Some((ByteString::from("nodes/1"), None))
+            );
+            assert_eq!(key_watch.next().await.transpose()?, Some(Some(value(2))));
+
+            Ok::<(), anyhow::Error>(())
+        })
+        .await?;
+
+    env.tc.shutdown_node("shutdown", 0).await;
+    Ok(())
+}
+
+/// Tests that transactions apply all operations or none and report the failed preconditions.
+#[test(tokio::test)]
+async fn transactions() -> anyhow::Result<()> {
+    let (client, env) = create_test_environment().await?;
+
+    env.tc
+        .run_in_scope("test", None, async move {
+            let value = |version: u32| Value {
+                version: Version::from(version),
+                value: format!("value-{version}"),
+            };
+
+            client
+                .put("partition_table".into(), value(1), Precondition::None)
+                .await?;
+            client
+                .put("logs".into(), value(1), Precondition::None)
+                .await?;
+            let mut watch = client.watch_prefix::<Value>("".into()).await?;
+
+            let result = client
+                .transaction(vec![
+                    (
+                        "partition_table".into(),
+                        Precondition::MatchesVersion(Version::from(1)),
+                        TransactionOperation::put(&value(2))?,
+                    ),
+                    (
+                        "logs".into(),
+                        Precondition::MatchesVersion(Version::from(2)),
+                        TransactionOperation::put(&value(3))?,
+                    ),
+                    (
+                        "stale".into(),
+                        Precondition::DoesNotExist,
+                        TransactionOperation::Delete,
+                    ),
+                ])
+                .await;
+            let Err(TransactionError::FailedPreconditions(failed)) = result else {
+                panic!("expected failed preconditions, got {result:?}");
+            };
+            assert_eq!(failed.len(), 1);
+            assert_eq!(failed[0].key, ByteString::from("logs"));
+            assert_eq!(failed[0].current_version, Some(Version::from(1)));
+            assert_eq!(
+                client.get::<Value>("partition_table".into()).await?,
+                Some(value(1))
+            );
+
+            client
+                .transaction(vec![
+                    (
+                        "partition_table".into(),
+                        Precondition::MatchesVersion(Version::from(1)),
+                        TransactionOperation::put(&value(2))?,
+                    ),
+                    (
+                        "logs".into(),
+                        Precondition::MatchesVersion(Version::from(1)),
+                        TransactionOperation::Delete,
+                    ),
+                ])
+                .await?;
+            assert_eq!(
+                client.get::<Value>("partition_table".into()).await?,
+                Some(value(2))
+            );
+            assert_eq!(client.get::<Value>("logs".into()).await?, None);
+
+            // watchers observe the initial values and then the changes of the transaction
+            let mut events = Vec::new();
+            for _ in 0..4 {
+                events.push(watch.next().await.transpose()?.expect("watch is open"));
+            }
+            assert_eq!(
+                events,
+                vec![
+                    (ByteString::from("logs"), Some(value(1))),
+                    (ByteString::from("partition_table"), Some(value(1))),
+                    (ByteString::from("partition_table"), Some(value(2))),
+                    (ByteString::from("logs"), None),
+                ]
+            );
+
+            let result = client
+                .transaction(vec![
+                    (
+                        "logs".into(),
+                        Precondition::None,
+                        TransactionOperation::Delete,
+                    ),
+                    (
+                        "logs".into(),
+                        Precondition::None,
+                        TransactionOperation::Delete,
+                    ),
+                ])
+                .await;
+            assert!(matches!(result, Err(TransactionError::InvalidArgument(_))));
+
+            Ok::<(), anyhow::Error>(())
+        })
+        .await?;
+
+    env.tc.shutdown_node("shutdown", 0).await;
+    Ok(())
+}
+
 /// Tests multiple concurrent operations issued by the same client
 #[test(tokio::test)]
 async fn concurrent_operations() -> anyhow::Result<()> {
@@ -263,6 +455,73 @@ async fn durable_storage() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests that a snapshot of the metadata store can be written to a file and restored into a fresh
+/// metadata store.
+#[test(tokio::test)]
+async fn snapshot_and_restore() -> anyhow::Result<()> {
+    let snapshot_path = tempfile::tempdir()?
+        .into_path()
+        .join("metadata-store.snapshot");
+    let value = |key: u32| Value {
+        version: Version::from(key),
+        value: key.to_string(),
+    };
+
+    let (client, env) = create_test_environment().await?;
+
+    // write data and export a snapshot of it
+    let path = snapshot_path.clone();
+    env.tc
+        .run_in_scope("export-snapshot", None, async move {
+            for key in 1u32..=10 {
+                client
+                    .put(
+                        key.to_string().into(),
+                        value(key),
+                        Precondition::DoesNotExist,
+                    )
+                    .await?;
+            }
+
+            let snapshot = client.snapshot().await?;
+            assert_eq!(snapshot.kv_pairs().len(), 10);
+            std::fs::write(path, snapshot.encode()?)?;
+
+            Ok::<(), anyhow::Error>(())
+        })
+        .await?;
+
+    env.tc.shutdown_node("shutdown", 0).await;
+
+    let (client, env) = create_test_environment().await?;
+
+    // restore the snapshot into a fresh metadata store
+    env.tc
+        .run_in_scope("restore-snapshot", None, async move {
+            let snapshot = MetadataStoreSnapshot::decode(&std::fs::read(snapshot_path)?)?;
+            client.restore(snapshot.clone()).await?;
+
+            for key in 1u32..=10 {
+                assert_eq!(
+                    client.get::<Value>(key.to_string().into()).await?,
+                    Some(value(key))
+                );
+            }
+
+            // only empty metadata stores can be restored
+            assert!(matches!(
+                client.restore(snapshot).await,
+                Err(WriteError::FailedPrecondition(_))
+            ));
+
+            Ok::<(), anyhow::Error>(())
+        })
+        .await?;
+
+    env.tc.shutdown_node("shutdown", 0).await;
+    Ok(())
+}
+
 async fn create_test_environment(
 ) -> anyhow::Result<(MetadataStoreClient, TestCoreEnv<MockNetworkSender>)> {
     create_test_environment_with_path(tempfile::tempdir()?.into_path()).await
@@ -277,10 +536,10 @@ async fn create_test_environment_with_path(
     let bind_address = BindAddress::Uds(uds_path.clone());
     let advertised_address = AdvertisedAddress::Uds(uds_path);
     let store = LocalMetadataStore::new(rocksdb_path, 32)?;
-    let service = LocalMetadataStoreService::new(store, bind_address);
+    let service = LocalMetadataStoreService::new(store, bind_address, None);
     let grpc_service_name = service.grpc_service_name().to_owned();
 
-    let rocksdb_client = LocalMetadataStoreClient::new(advertised_address.clone());
+    let rocksdb_client = GrpcMetadataStoreClient::new(advertised_address.clone(), None);
 
     let client = MetadataStoreClient::new(rocksdb_client);
 
@@ -299,6 +558,7 @@ async fn create_test_environment_with_path(
     // await start-up of metadata store
     let health_client = HealthClient::new(create_grpc_channel_from_advertised_address(
         advertised_address,
+        None,
     )?);
     let retry_policy = RetryPolicy::exponential(Duration::from_millis(10), 2.0, usize::MAX, None);
 
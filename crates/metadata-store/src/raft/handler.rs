@@ -0,0 +1,112 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_trait::async_trait;
+use restate_types::PlainNodeId;
+use tokio::sync::mpsc;
+use tonic::{Request, Response, Status};
+
+use crate::grpc_svc;
+use crate::grpc_svc::metadata_store_raft_svc_server::MetadataStoreRaftSvc;
+use crate::grpc_svc::{AddMemberRequest, MembersResponse, RemoveMemberRequest};
+use crate::raft::protocol::{Member, Members, RaftEnvelope};
+use crate::raft::store::{MembershipError, MembershipHandle};
+
+/// Grpc svc handler which passes raft messages and membership changes on to the
+/// [`RaftMetadataStore`](crate::raft::RaftMetadataStore).
+#[derive(Debug)]
+pub struct MetadataStoreRaftHandler {
+    message_tx: mpsc::Sender<RaftEnvelope>,
+    membership: MembershipHandle,
+}
+
+impl MetadataStoreRaftHandler {
+    pub fn new(message_tx: mpsc::Sender<RaftEnvelope>, membership: MembershipHandle) -> Self {
+        Self {
+            message_tx,
+            membership,
+        }
+    }
+}
+
+#[async_trait]
+impl MetadataStoreRaftSvc for MetadataStoreRaftHandler {
+    async fn raft(&self, request: Request<grpc_svc::RaftMessage>) -> Result<Response<()>, Status> {
+        let envelope = RaftEnvelope::decode(request.into_inner().payload)
+            .map_err(|err| Status::invalid_argument(err.to_string()))?;
+
+        self.message_tx
+            .send(envelope)
+            .await
+            .map_err(|_| Status::unavailable("metadata store is shut down"))?;
+
+        Ok(Response::new(()))
+    }
+
+    async fn add_member(
+        &self,
+        request: Request<AddMemberRequest>,
+    ) -> Result<Response<MembersResponse>, Status> {
+        let member = request
+            .into_inner()
+            .member
+            .ok_or_else(|| Status::invalid_argument("missing member field"))?
+            .try_into()?;
+
+        let members = self.membership.add_member(member).await?;
+        Ok(Response::new(members.into()))
+    }
+
+    async fn remove_member(
+        &self,
+        request: Request<RemoveMemberRequest>,
+    ) -> Result<Response<MembersResponse>, Status> {
+        let node_id = PlainNodeId::from(request.into_inner().node_id);
+
+        let members = self.membership.remove_member(node_id).await?;
+        Ok(Response::new(members.into()))
+    }
+}
+
+impl TryFrom<grpc_svc::Member> for Member {
+    type Error = Status;
+
+    fn try_from(value: grpc_svc::Member) -> Result<Self, Self::Error> {
+        let address = value
+            .address
+            .parse()
+            .map_err(|err| Status::invalid_argument(format!("invalid address: {err}")))?;
+        Ok(Member::new(PlainNodeId::from(value.node_id), address))
+    }
+}
+
+impl From<Members> for MembersResponse {
+    fn from(value: Members) -> Self {
+        MembersResponse {
+            members: value
+                .iter()
+                .map(|member| grpc_svc::Member {
+                    node_id: member.node_id.into(),
+                    address: member.address.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<MembershipError> for Status {
+    fn from(err: MembershipError) -> Self {
+        match err {
+            MembershipError::Unavailable(msg) => Status::unavailable(msg),
+            MembershipError::InvalidArgument(msg) => Status::invalid_argument(msg),
+            MembershipError::Internal(msg) => Status::internal(msg),
+        }
+    }
+}
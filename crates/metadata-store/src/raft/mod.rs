@@ -0,0 +1,54 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Metadata store which replicates its key-value pairs to a group of members with raft.
+//!
+//! Every member runs the [`RaftMetadataStoreService`], which serves the same grpc api as the
+//! local metadata store and additionally the `MetadataStoreRaftSvc` for the communication between
+//! the members. Clients can connect to any member, requests are forwarded to the current leader.
+//! The store remains available as long as a majority of the members is running.
+//!
+//! The members are changed one at a time through [`MembershipHandle`] or the `AddMember` and
+//! `RemoveMember` rpcs of any member.
+//!
+//! Members remove applied entries from their raft log once it exceeds the configured log
+//! compaction threshold. The leader sends a snapshot of its key-value pairs to members which
+//! need removed entries.
+
+mod handler;
+mod network;
+mod options;
+mod protocol;
+mod service;
+mod storage;
+mod store;
+
+pub use options::Options;
+pub use protocol::{Member, Members, MembershipChange};
+use restate_grpc_util::TlsConfig;
+use restate_types::net::AdvertisedAddress;
+pub use service::RaftMetadataStoreService;
+pub use storage::BuildError;
+pub use store::{MembershipError, MembershipHandle, RaftMetadataStore};
+
+use crate::grpc::client::GrpcMetadataStoreClient;
+use crate::MetadataStoreClient;
+
+/// Creates a [`MetadataStoreClient`] for a member of the [`RaftMetadataStoreService`].
+/// If a TLS configuration is given, `https` addresses are connected to with TLS.
+pub fn create_client(
+    advertised_address: AdvertisedAddress,
+    tls: Option<&TlsConfig>,
+) -> MetadataStoreClient {
+    MetadataStoreClient::new(GrpcMetadataStoreClient::new(advertised_address, tls))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,143 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use restate_core::{cancellation_watcher, task_center, TaskKind};
+use restate_grpc_util::{create_grpc_channel_from_advertised_address, TlsConfig};
+use restate_types::net::AdvertisedAddress;
+use restate_types::PlainNodeId;
+use tokio::sync::mpsc;
+use tracing::{debug, trace};
+
+use crate::grpc_svc;
+use crate::grpc_svc::metadata_store_raft_svc_client::MetadataStoreRaftSvcClient;
+use crate::raft::protocol::{Member, RaftEnvelope};
+
+/// Number of messages which are buffered per member before further messages are dropped.
+const PEER_QUEUE_LENGTH: usize = 128;
+
+/// Delivers raft messages to the other members. Delivery is best-effort, raft copes with lost
+/// messages.
+pub(crate) trait RaftNetwork: Send + 'static {
+    fn send(&self, to: &Member, envelope: RaftEnvelope);
+}
+
+/// Sends raft messages to the `MetadataStoreRaftSvc` of the other members. Every member gets its
+/// own sender task, so that an unreachable member doesn't delay the messages to the others.
+pub(crate) struct GrpcRaftNetwork {
+    peers: Mutex<HashMap<PlainNodeId, Peer>>,
+    tls: Option<TlsConfig>,
+}
+
+struct Peer {
+    address: AdvertisedAddress,
+    tx: mpsc::Sender<RaftEnvelope>,
+}
+
+impl GrpcRaftNetwork {
+    pub(crate) fn new(tls: Option<TlsConfig>) -> Self {
+        Self {
+            peers: Mutex::default(),
+            tls,
+        }
+    }
+
+    fn start_peer(&self, to: &Member) -> Option<Peer> {
+        let tls = self.tls.as_ref().map(|tls| tls.client_config(None));
+        let channel = match create_grpc_channel_from_advertised_address(to.address.clone(), tls) {
+            Ok(channel) => channel,
+            Err(err) => {
+                debug!(
+                    "Cannot create channel to metadata store {} at {}: {}",
+                    to.node_id, to.address, err
+                );
+                return None;
+            }
+        };
+        let mut client = MetadataStoreRaftSvcClient::new(channel);
+        let (tx, mut rx) = mpsc::channel::<RaftEnvelope>(PEER_QUEUE_LENGTH);
+
+        let node_id = to.node_id;
+        let spawn_result = task_center().spawn_child(
+            TaskKind::MetadataStore,
+            "metadata-store-raft-sender",
+            None,
+            async move {
+                loop {
+                    let envelope = tokio::select! {
+                        Some(envelope) = rx.recv() => envelope,
+                        _ = cancellation_watcher() => break,
+                        else => break,
+                    };
+
+                    let payload = match envelope.encode() {
+                        Ok(payload) => payload,
+                        Err(err) => {
+                            debug!("Failed encoding raft message: {}", err);
+                            continue;
+                        }
+                    };
+                    if let Err(status) = client
+                        .raft(grpc_svc::RaftMessage {
+                            payload: payload.into(),
+                        })
+                        .await
+                    {
+                        trace!(
+                            "Failed sending raft message to metadata store {}: {}",
+                            node_id,
+                            status
+                        );
+                    }
+                }
+                Ok(())
+            },
+        );
+
+        match spawn_result {
+            Ok(_) => Some(Peer {
+                address: to.address.clone(),
+                tx,
+            }),
+            // system is shutting down
+            Err(_) => None,
+        }
+    }
+}
+
+impl RaftNetwork for GrpcRaftNetwork {
+    fn send(&self, to: &Member, envelope: RaftEnvelope) {
+        let mut peers = self.peers.lock().expect("lock is not poisoned");
+
+        let is_current = peers
+            .get(&to.node_id)
+            .is_some_and(|peer| peer.address == to.address && !peer.tx.is_closed());
+        if !is_current {
+            // dropping the sender of a previous peer stops its task
+            peers.remove(&to.node_id);
+            match self.start_peer(to) {
+                Some(peer) => {
+                    peers.insert(to.node_id, peer);
+                }
+                None => return,
+            }
+        }
+
+        let peer = peers.get(&to.node_id).expect("peer exists");
+        if peer.tx.try_send(envelope).is_err() {
+            trace!(
+                "Dropping raft message to metadata store {}, its queue is full",
+                to.node_id
+            );
+        }
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::raft::network::GrpcRaftNetwork;
+use crate::raft::protocol::Member;
+use crate::raft::storage::BuildError;
+use crate::raft::store::{RaftMetadataStore, Timeouts};
+use crate::raft::RaftMetadataStoreService;
+use restate_grpc_util::TlsConfig;
+use restate_types::net::{AdvertisedAddress, BindAddress};
+use restate_types::{PlainNodeId, DEFAULT_STORAGE_DIRECTORY};
+use serde_with::serde_as;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[serde_as]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, derive_builder::Builder)]
+#[cfg_attr(feature = "options_schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "options_schema",
+    schemars(rename = "RaftMetadataStoreOptions", default)
+)]
+#[builder(default)]
+pub struct Options {
+    /// Address to which the metadata store will bind to.
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    bind_address: BindAddress,
+    /// Address under which the other members reach this metadata store.
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    advertised_address: AdvertisedAddress,
+    /// Id of this member of the raft group. Must be unique within the group.
+    #[cfg_attr(feature = "options_schema", schemars(with = "u32"))]
+    node_id: PlainNodeId,
+    /// Storage path under which the metadata store will store its raft log and data.
+    path: PathBuf,
+    /// Number of in-flight metadata store requests.
+    request_queue_length: usize,
+    /// Members of the raft group when it is created. All members must be started with the same
+    /// initial members. Later changes of the members are stored in the raft log and take
+    /// precedence over this setting.
+    initial_members: Vec<Member>,
+    /// Followers start an election if they don't hear from the leader for a random duration
+    /// between the election timeout and twice the election timeout.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    election_timeout: humantime::Duration,
+    /// Interval in which the leader sends heartbeats to the followers. Must be considerably
+    /// shorter than the election timeout.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    heartbeat_interval: humantime::Duration,
+    /// Requests fail with an unavailable error if no leader can be reached within this timeout.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde_as(as = "serde_with::DisplayFromStr")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    request_timeout: humantime::Duration,
+    /// Number of applied raft log entries after which a member removes them from its log. The
+    /// key-value pairs contain their effects, members which fall behind the removed entries
+    /// receive a snapshot of the key-value pairs from the leader.
+    log_compaction_threshold: u64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            bind_address: "0.0.0.0:5123".parse().expect("valid bind address"),
+            advertised_address: "http://127.0.0.1:5123"
+                .parse()
+                .expect("valid advertised address"),
+            node_id: PlainNodeId::from(1),
+            path: Path::new(DEFAULT_STORAGE_DIRECTORY).join("raft_metadata_store"),
+            request_queue_length: 32,
+            initial_members: Vec::new(),
+            election_timeout: Duration::from_millis(500).into(),
+            heartbeat_interval: Duration::from_millis(100).into(),
+            request_timeout: Duration::from_secs(5).into(),
+            log_compaction_threshold: 1000,
+        }
+    }
+}
+
+impl Options {
+    /// Builds the metadata store service. If a TLS configuration is given, the service only
+    /// accepts TLS connections and connects to the other members with TLS.
+    pub fn build(self, tls: Option<TlsConfig>) -> Result<RaftMetadataStoreService, BuildError> {
+        let timeouts = self.timeouts();
+        let me = Member::new(self.node_id, self.advertised_address);
+        let initial_members = if self.initial_members.is_empty() {
+            // a single member group
+            [me.clone()].into_iter().collect()
+        } else {
+            self.initial_members.into_iter().collect()
+        };
+
+        let store = RaftMetadataStore::new(
+            me,
+            self.path,
+            GrpcRaftNetwork::new(tls.clone()),
+            initial_members,
+            timeouts,
+            self.log_compaction_threshold,
+            self.request_queue_length,
+        )?;
+        Ok(RaftMetadataStoreService::new(store, self.bind_address, tls))
+    }
+
+    pub fn storage_path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    pub fn advertised_address(&self) -> &AdvertisedAddress {
+        &self.advertised_address
+    }
+
+    pub(crate) fn timeouts(&self) -> Timeouts {
+        Timeouts {
+            election_timeout: self.election_timeout.into(),
+            heartbeat_interval: self.heartbeat_interval.into(),
+            request_timeout: self.request_timeout.into(),
+        }
+    }
+}
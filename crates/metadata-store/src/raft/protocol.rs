@@ -0,0 +1,281 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::BTreeMap;
+
+use bytestring::ByteString;
+use restate_types::net::AdvertisedAddress;
+use restate_types::{PlainNodeId, Version};
+use serde::{Deserialize, Serialize};
+
+use crate::request::Error;
+use crate::{FailedPrecondition, Precondition, TransactionOperations, VersionedValue};
+
+pub(crate) type Term = u64;
+pub(crate) type LogIndex = u64;
+
+/// A member of the raft group.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "options_schema", derive(schemars::JsonSchema))]
+pub struct Member {
+    #[cfg_attr(feature = "options_schema", schemars(with = "u32"))]
+    pub node_id: PlainNodeId,
+    /// Address of the metadata store of the member.
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    pub address: AdvertisedAddress,
+}
+
+impl Member {
+    pub fn new(node_id: PlainNodeId, address: AdvertisedAddress) -> Self {
+        Self { node_id, address }
+    }
+}
+
+/// The voting members of the raft group.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Members(BTreeMap<PlainNodeId, AdvertisedAddress>);
+
+impl Members {
+    pub fn contains(&self, node_id: PlainNodeId) -> bool {
+        self.0.contains_key(&node_id)
+    }
+
+    pub fn get(&self, node_id: PlainNodeId) -> Option<Member> {
+        self.0
+            .get(&node_id)
+            .map(|address| Member::new(node_id, address.clone()))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Member> + '_ {
+        self.0
+            .iter()
+            .map(|(node_id, address)| Member::new(*node_id, address.clone()))
+    }
+
+    pub fn node_ids(&self) -> impl Iterator<Item = PlainNodeId> + '_ {
+        self.0.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Number of members which form a majority.
+    pub(crate) fn quorum(&self) -> usize {
+        self.0.len() / 2 + 1
+    }
+
+    pub(crate) fn insert(&mut self, member: Member) {
+        self.0.insert(member.node_id, member.address);
+    }
+
+    pub(crate) fn remove(&mut self, node_id: PlainNodeId) {
+        self.0.remove(&node_id);
+    }
+}
+
+impl FromIterator<Member> for Members {
+    fn from_iter<T: IntoIterator<Item = Member>>(iter: T) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|member| (member.node_id, member.address))
+                .collect(),
+        )
+    }
+}
+
+/// Changes the members of the raft group by a single member at a time, so that the majorities
+/// of the old and the new group always overlap.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MembershipChange {
+    Add(Member),
+    Remove(PlainNodeId),
+}
+
+/// Request of a client of the metadata store. Reads go through the raft log as well, which
+/// makes them linearizable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum Request {
+    Get {
+        key: ByteString,
+    },
+    GetVersion {
+        key: ByteString,
+    },
+    Put {
+        key: ByteString,
+        value: VersionedValue,
+        precondition: Precondition,
+    },
+    Delete {
+        key: ByteString,
+        precondition: Precondition,
+    },
+    Transaction {
+        operations: TransactionOperations,
+    },
+    List {
+        prefix: ByteString,
+    },
+}
+
+/// Result of a [`Request`] or of a [`MembershipChange`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum Response {
+    Value(Option<VersionedValue>),
+    Version(Option<Version>),
+    KvPairs(Vec<(ByteString, VersionedValue)>),
+    Done,
+    Members(Members),
+}
+
+/// Something a member wants the leader to append to the log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum Proposal {
+    Request(Request),
+    ChangeMembership(MembershipChange),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Entry {
+    pub term: Term,
+    pub payload: EntryPayload,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum EntryPayload {
+    /// Appended by a new leader to commit the entries of previous terms.
+    Noop,
+    Request(Request),
+    /// The members of the group from this entry on. A membership takes effect as soon as it is
+    /// appended to the log, not only once it is committed.
+    Membership(Members),
+}
+
+/// Describes the entries up to and including `last_index`, which can be removed from the log once
+/// their effects are contained in the key-value pairs of the state machine.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct SnapshotMetadata {
+    pub last_index: LogIndex,
+    pub last_term: Term,
+    /// The latest membership entry up to `last_index` and its index, `None` if the initial
+    /// members are in place.
+    pub membership: Option<(LogIndex, Members)>,
+}
+
+/// The key-value pairs of a member after applying the entries up to and including
+/// `metadata.last_index`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Snapshot {
+    pub metadata: SnapshotMetadata,
+    pub kv_pairs: Vec<(ByteString, VersionedValue)>,
+}
+
+/// Errors which are sent back to members that forwarded a proposal to the leader.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum ForwardError {
+    /// The receiver was not the leader, the proposal has not been appended.
+    NotLeader,
+    FailedPrecondition(String),
+    FailedPreconditions(Vec<FailedPrecondition>),
+    InvalidArgument(String),
+    Unavailable(String),
+    Internal(String),
+}
+
+impl From<Error> for ForwardError {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::FailedPrecondition(msg) => ForwardError::FailedPrecondition(msg),
+            Error::FailedPreconditions(failed) => ForwardError::FailedPreconditions(failed),
+            Error::InvalidArgument(msg) => ForwardError::InvalidArgument(msg),
+            Error::Unavailable(msg) => ForwardError::Unavailable(msg),
+            err => ForwardError::Internal(err.to_string()),
+        }
+    }
+}
+
+impl From<ForwardError> for Error {
+    fn from(err: ForwardError) -> Self {
+        match err {
+            ForwardError::NotLeader => Error::Unavailable("leader changed".to_owned()),
+            ForwardError::FailedPrecondition(msg) => Error::FailedPrecondition(msg),
+            ForwardError::FailedPreconditions(failed) => Error::FailedPreconditions(failed),
+            ForwardError::InvalidArgument(msg) => Error::InvalidArgument(msg),
+            ForwardError::Unavailable(msg) => Error::Unavailable(msg),
+            ForwardError::Internal(msg) => Error::Internal(msg),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct RaftEnvelope {
+    /// The sender, which can be addressed by the receiver even if it doesn't know it as member.
+    pub from: Member,
+    pub message: RaftMessage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum RaftMessage {
+    RequestVote {
+        term: Term,
+        last_log_index: LogIndex,
+        last_log_term: Term,
+    },
+    Vote {
+        term: Term,
+        granted: bool,
+    },
+    AppendEntries {
+        term: Term,
+        prev_log_index: LogIndex,
+        prev_log_term: Term,
+        entries: Vec<Entry>,
+        leader_commit: LogIndex,
+    },
+    /// Sent instead of the entries which the leader has removed from its log already. The
+    /// follower responds with an [`RaftMessage::AppendEntriesResponse`].
+    InstallSnapshot {
+        term: Term,
+        snapshot: Snapshot,
+    },
+    AppendEntriesResponse {
+        term: Term,
+        success: bool,
+        /// The last index that matches the log of the leader if successful, otherwise a hint
+        /// where the logs might match.
+        last_log_index: LogIndex,
+    },
+    Forward {
+        request_id: u64,
+        proposal: Proposal,
+    },
+    ForwardResponse {
+        request_id: u64,
+        result: Result<Response, ForwardError>,
+    },
+}
+
+impl RaftEnvelope {
+    pub fn encode(&self) -> Result<Vec<u8>, Error> {
+        bincode::serde::encode_to_vec(self, bincode::config::standard())
+            .map_err(|err| Error::Codec(err.into()))
+    }
+
+    pub fn decode(bytes: impl AsRef<[u8]>) -> Result<Self, Error> {
+        bincode::serde::decode_from_slice(bytes.as_ref(), bincode::config::standard())
+            .map(|(envelope, _)| envelope)
+            .map_err(|err| Error::Codec(err.into()))
+    }
+}
@@ -0,0 +1,108 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::grpc::handler::MetadataStoreHandler;
+use crate::grpc_svc;
+use crate::grpc_svc::metadata_store_raft_svc_server::MetadataStoreRaftSvcServer;
+use crate::grpc_svc::metadata_store_svc_server::MetadataStoreSvcServer;
+use crate::raft::handler::MetadataStoreRaftHandler;
+use crate::raft::store::RaftMetadataStore;
+use restate_core::{cancellation_watcher, task_center, ShutdownError, TaskKind};
+use restate_grpc_util::TlsConfig;
+use restate_types::net::BindAddress;
+use tonic::server::NamedService;
+
+pub struct RaftMetadataStoreService {
+    metadata_store: RaftMetadataStore,
+    bind_address: BindAddress,
+    tls: Option<TlsConfig>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("failed running grpc server: {0}")]
+    GrpcServer(#[from] restate_grpc_util::Error),
+    #[error("error while running server server grpc reflection service: {0}")]
+    GrpcReflection(#[from] tonic_reflection::server::Error),
+    #[error("raft metadata store failed: {0}")]
+    MetadataStore(#[from] anyhow::Error),
+    #[error("system is shutting down")]
+    Shutdown(#[from] ShutdownError),
+}
+
+impl RaftMetadataStoreService {
+    pub fn new(
+        metadata_store: RaftMetadataStore,
+        bind_address: BindAddress,
+        tls: Option<TlsConfig>,
+    ) -> Self {
+        Self {
+            metadata_store,
+            bind_address,
+            tls,
+        }
+    }
+
+    pub fn grpc_service_name(&self) -> &str {
+        MetadataStoreSvcServer::<MetadataStoreHandler>::NAME
+    }
+
+    pub async fn run(self) -> Result<(), Error> {
+        // Trace layer
+        let span_factory = tower_http::trace::DefaultMakeSpan::new()
+            .include_headers(true)
+            .level(tracing::Level::ERROR);
+
+        let reflection_service_builder = tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(grpc_svc::FILE_DESCRIPTOR_SET);
+
+        let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
+        health_reporter
+            .set_serving::<MetadataStoreSvcServer<MetadataStoreHandler>>()
+            .await;
+
+        let server_builder = tonic::transport::Server::builder()
+            .layer(tower_http::trace::TraceLayer::new_for_grpc().make_span_with(span_factory))
+            .add_service(health_service)
+            .add_service(MetadataStoreSvcServer::new(MetadataStoreHandler::new(
+                self.metadata_store.request_sender(),
+            )))
+            .add_service(MetadataStoreRaftSvcServer::new(
+                MetadataStoreRaftHandler::new(
+                    self.metadata_store.message_sender(),
+                    self.metadata_store.membership_handle(),
+                ),
+            ))
+            .add_service(reflection_service_builder.build()?);
+
+        let service = server_builder.into_service();
+
+        task_center().spawn_child(
+            TaskKind::RpcServer,
+            "metadata-store-grpc",
+            None,
+            async move {
+                restate_grpc_util::run_hyper_server(
+                    &self.bind_address,
+                    self.tls.as_ref(),
+                    service,
+                    cancellation_watcher(),
+                    "metadata-store-grpc",
+                )
+                .await?;
+                Ok(())
+            },
+        )?;
+
+        self.metadata_store.run().await?;
+
+        Ok(())
+    }
+}
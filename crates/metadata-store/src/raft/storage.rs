@@ -0,0 +1,456 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use bytestring::ByteString;
+use codederror::CodedError;
+use restate_types::{PlainNodeId, Version};
+use rocksdb::{ColumnFamily, IteratorMode, Options, WriteBatch, WriteOptions, DB};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::raft::protocol::{
+    Entry, EntryPayload, LogIndex, Members, Request, Response, Snapshot, SnapshotMetadata, Term,
+};
+use crate::request::{check_transaction, Error};
+use crate::{
+    FailedPrecondition, Precondition, TransactionOperation, TransactionOperations, VersionedValue,
+    WatchEvent,
+};
+
+type Result<T> = std::result::Result<T, Error>;
+
+const LOG: &str = "raft_log";
+const STATE: &str = "raft_state";
+const KV_PAIRS: &str = "kv_pairs";
+
+const HARD_STATE_KEY: &[u8] = b"hard_state";
+const APPLIED_INDEX_KEY: &[u8] = b"applied_index";
+const SNAPSHOT_KEY: &[u8] = b"snapshot";
+
+#[derive(Debug, thiserror::Error, CodedError)]
+pub enum BuildError {
+    #[error("failed opening rocksdb: {0}")]
+    #[code(unknown)]
+    RocksDB(#[from] rocksdb::Error),
+}
+
+/// The state which a member must persist before it responds to raft messages.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct HardState {
+    pub term: Term,
+    pub voted_for: Option<PlainNodeId>,
+}
+
+/// Stores the raft log, the hard state and the key-value pairs of the state machine of a member
+/// of the raft group.
+///
+/// Applied entries are removed from the log with [`RaftStorage::compact`]. The key-value pairs
+/// contain their effects, the [`SnapshotMetadata`] describes the removed entries.
+pub(crate) struct RaftStorage {
+    db: DB,
+    write_opts: WriteOptions,
+}
+
+impl RaftStorage {
+    pub fn open(path: impl AsRef<Path>) -> std::result::Result<Self, BuildError> {
+        let mut db_options = Options::default();
+        db_options.create_if_missing(true);
+        db_options.create_missing_column_families(true);
+
+        let cfs = [LOG, STATE, KV_PAIRS]
+            .into_iter()
+            .map(|name| rocksdb::ColumnFamilyDescriptor::new(name, Options::default()));
+        let db = DB::open_cf_descriptors(&db_options, path, cfs)?;
+
+        // raft requires that the log and the hard state are durable before we respond
+        let mut write_opts = WriteOptions::default();
+        write_opts.disable_wal(false);
+        write_opts.set_sync(true);
+
+        Ok(Self { db, write_opts })
+    }
+
+    fn cf(&self, name: &str) -> &ColumnFamily {
+        self.db
+            .cf_handle(name)
+            .expect("raft storage column families exist")
+    }
+
+    pub fn hard_state(&self) -> Result<HardState> {
+        Ok(self
+            .get(STATE, HARD_STATE_KEY)?
+            .unwrap_or_else(HardState::default))
+    }
+
+    pub fn store_hard_state(&self, hard_state: &HardState) -> Result<()> {
+        self.db.put_cf_opt(
+            self.cf(STATE),
+            HARD_STATE_KEY,
+            encode(hard_state)?,
+            &self.write_opts,
+        )?;
+        Ok(())
+    }
+
+    pub fn applied_index(&self) -> Result<LogIndex> {
+        Ok(self.get(STATE, APPLIED_INDEX_KEY)?.unwrap_or_default())
+    }
+
+    /// Describes the entries which have been removed from the log.
+    pub fn snapshot_metadata(&self) -> Result<SnapshotMetadata> {
+        Ok(self.get(STATE, SNAPSHOT_KEY)?.unwrap_or_default())
+    }
+
+    /// Returns the index and the term of the last entry of the log. If the log is empty, the
+    /// last removed entry is the last entry, `(0, 0)` if no entry has been removed.
+    pub fn last_entry_id(&self) -> Result<(LogIndex, Term)> {
+        let mut iter = self.db.iterator_cf(self.cf(LOG), IteratorMode::End);
+        match iter.next().transpose()? {
+            Some((key, value)) => Ok((decode_index(&key), decode::<Entry>(&value)?.term)),
+            None => {
+                let metadata = self.snapshot_metadata()?;
+                Ok((metadata.last_index, metadata.last_term))
+            }
+        }
+    }
+
+    pub fn entry(&self, index: LogIndex) -> Result<Option<Entry>> {
+        self.get(LOG, index.to_be_bytes())
+    }
+
+    /// Returns the term of the entry at `index`. The (non-existing) entry at index 0 has term 0.
+    /// Of the removed entries, only the term of the last one is known.
+    pub fn term_at(&self, index: LogIndex) -> Result<Option<Term>> {
+        if index == 0 {
+            return Ok(Some(0));
+        }
+        if let Some(entry) = self.entry(index)? {
+            return Ok(Some(entry.term));
+        }
+        let metadata = self.snapshot_metadata()?;
+        Ok((metadata.last_index == index).then_some(metadata.last_term))
+    }
+
+    /// Returns up to `max_entries` entries starting at `from`.
+    pub fn entries(&self, from: LogIndex, max_entries: usize) -> Result<Vec<Entry>> {
+        let from = from.to_be_bytes();
+        self.db
+            .iterator_cf(
+                self.cf(LOG),
+                IteratorMode::From(&from, rocksdb::Direction::Forward),
+            )
+            .take(max_entries)
+            .map(|item| {
+                let (_, value) = item?;
+                decode(&value)
+            })
+            .collect()
+    }
+
+    /// Appends the entries to the log, the first entry gets index `first_index`. Existing entries
+    /// at and after `first_index` are removed.
+    pub fn append(&self, first_index: LogIndex, entries: &[Entry]) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        batch.delete_range_cf(
+            self.cf(LOG),
+            first_index.to_be_bytes(),
+            LogIndex::MAX.to_be_bytes(),
+        );
+        for (index, entry) in (first_index..).zip(entries) {
+            batch.put_cf(self.cf(LOG), index.to_be_bytes(), encode(entry)?);
+        }
+        self.db.write_opt(batch, &self.write_opts)?;
+        Ok(())
+    }
+
+    /// Returns the latest membership in the log and its index.
+    pub fn latest_membership(&self) -> Result<Option<(LogIndex, Members)>> {
+        self.latest_membership_at(LogIndex::MAX)
+    }
+
+    /// Returns the latest membership up to and including `index` and its index.
+    fn latest_membership_at(&self, index: LogIndex) -> Result<Option<(LogIndex, Members)>> {
+        let until = index.to_be_bytes();
+        for item in self.db.iterator_cf(
+            self.cf(LOG),
+            IteratorMode::From(&until, rocksdb::Direction::Reverse),
+        ) {
+            let (key, value) = item?;
+            if let EntryPayload::Membership(members) = decode::<Entry>(&value)?.payload {
+                return Ok(Some((decode_index(&key), members)));
+            }
+        }
+        // the membership might have been removed from the log together with older entries
+        Ok(self.snapshot_metadata()?.membership)
+    }
+
+    /// Removes the entries up to and including `index` from the log. The entries must have been
+    /// applied before.
+    pub fn compact(&self, index: LogIndex) -> Result<()> {
+        let Some(last_entry) = self.entry(index)? else {
+            // removed already
+            return Ok(());
+        };
+        let metadata = SnapshotMetadata {
+            last_index: index,
+            last_term: last_entry.term,
+            membership: self.latest_membership_at(index)?,
+        };
+
+        // Entries are applied without syncing, their effects must be durable before the
+        // entries are removed.
+        self.db.flush_wal(true)?;
+        let mut batch = WriteBatch::default();
+        batch.put_cf(self.cf(STATE), SNAPSHOT_KEY, encode(&metadata)?);
+        batch.delete_range_cf(self.cf(LOG), 0_u64.to_be_bytes(), (index + 1).to_be_bytes());
+        self.db.write_opt(batch, &self.write_opts)?;
+        Ok(())
+    }
+
+    /// Returns a snapshot of the applied key-value pairs.
+    pub fn snapshot(&self) -> Result<Snapshot> {
+        let last_index = self.applied_index()?;
+        let last_term = self
+            .term_at(last_index)?
+            .expect("applied entries are in the log or are the last removed entry");
+        Ok(Snapshot {
+            metadata: SnapshotMetadata {
+                last_index,
+                last_term,
+                membership: self.latest_membership_at(last_index)?,
+            },
+            kv_pairs: self.list(&ByteString::new())?,
+        })
+    }
+
+    /// Replaces the log and the key-value pairs with the snapshot of another member. Returns the
+    /// changed key-value pairs as [`WatchEvent`]s.
+    pub fn install_snapshot(&self, snapshot: Snapshot) -> Result<Vec<WatchEvent>> {
+        let mut current_versions: HashMap<_, _> = self
+            .list(&ByteString::new())?
+            .into_iter()
+            .map(|(key, value)| (key, value.version))
+            .collect();
+
+        let mut batch = WriteBatch::default();
+        let mut changes = Vec::new();
+        for (key, value) in snapshot.kv_pairs {
+            if current_versions.remove(&key) != Some(value.version) {
+                changes.push(WatchEvent::new(key.clone(), Some(value.clone())));
+            }
+            batch.put_cf(self.cf(KV_PAIRS), &key, encode(&value)?);
+        }
+        for key in current_versions.into_keys() {
+            batch.delete_cf(self.cf(KV_PAIRS), &key);
+            changes.push(WatchEvent::new(key, None));
+        }
+        batch.delete_range_cf(
+            self.cf(LOG),
+            0_u64.to_be_bytes(),
+            LogIndex::MAX.to_be_bytes(),
+        );
+        batch.put_cf(
+            self.cf(STATE),
+            APPLIED_INDEX_KEY,
+            encode(snapshot.metadata.last_index)?,
+        );
+        batch.put_cf(self.cf(STATE), SNAPSHOT_KEY, encode(&snapshot.metadata)?);
+        self.db.write_opt(batch, &self.write_opts)?;
+        Ok(changes)
+    }
+
+    /// Applies the committed entry at `index` to the state machine. The outer result fails if
+    /// the storage fails, the inner result is the result of the request. Changed key-value pairs
+    /// are returned as [`WatchEvent`]s.
+    pub fn apply(
+        &self,
+        index: LogIndex,
+        entry: &Entry,
+    ) -> Result<(std::result::Result<Response, Error>, Vec<WatchEvent>)> {
+        let mut batch = WriteBatch::default();
+        batch.put_cf(self.cf(STATE), APPLIED_INDEX_KEY, encode(index)?);
+
+        let (result, changes) = match &entry.payload {
+            EntryPayload::Noop => (Ok(Response::Done), Vec::new()),
+            EntryPayload::Membership(members) => {
+                (Ok(Response::Members(members.clone())), Vec::new())
+            }
+            EntryPayload::Request(request) => self.apply_request(request, &mut batch)?,
+        };
+
+        // Entries which are lost because the write is not synced are applied again on restart.
+        self.db.write(batch)?;
+        Ok((result, changes))
+    }
+
+    fn apply_request(
+        &self,
+        request: &Request,
+        batch: &mut WriteBatch,
+    ) -> Result<(std::result::Result<Response, Error>, Vec<WatchEvent>)> {
+        let applied = match request {
+            Request::Get { key } => (Ok(Response::Value(self.get(KV_PAIRS, key)?)), Vec::new()),
+            Request::GetVersion { key } => {
+                (Ok(Response::Version(self.get_version(key)?)), Vec::new())
+            }
+            Request::List { prefix } => (Ok(Response::KvPairs(self.list(prefix)?)), Vec::new()),
+            Request::Transaction { operations } => self.apply_transaction(operations, batch)?,
+            Request::Put {
+                key,
+                value,
+                precondition,
+            } => {
+                let current_version = self.get_version(key)?;
+                match check_precondition(precondition, current_version) {
+                    Ok(()) => {
+                        batch.put_cf(self.cf(KV_PAIRS), key, encode(value)?);
+                        (
+                            Ok(Response::Done),
+                            vec![WatchEvent::new(key.clone(), Some(value.clone()))],
+                        )
+                    }
+                    Err(err) => (Err(err), Vec::new()),
+                }
+            }
+            Request::Delete { key, precondition } => {
+                let current_version = self.get_version(key)?;
+                if current_version.is_none()
+                    && !matches!(precondition, Precondition::MatchesVersion(_))
+                {
+                    // deleting a non-existing key-value pair is a no-op
+                    (Ok(Response::Done), Vec::new())
+                } else {
+                    match check_precondition(precondition, current_version) {
+                        Ok(()) => {
+                            batch.delete_cf(self.cf(KV_PAIRS), key);
+                            (Ok(Response::Done), vec![WatchEvent::new(key.clone(), None)])
+                        }
+                        Err(err) => (Err(err), Vec::new()),
+                    }
+                }
+            }
+        };
+        Ok(applied)
+    }
+
+    fn apply_transaction(
+        &self,
+        operations: &TransactionOperations,
+        batch: &mut WriteBatch,
+    ) -> Result<(std::result::Result<Response, Error>, Vec<WatchEvent>)> {
+        if let Err(err) = check_transaction(operations) {
+            return Ok((Err(err), Vec::new()));
+        }
+
+        let mut failed_preconditions = Vec::new();
+        let mut current_versions = Vec::with_capacity(operations.len());
+        for (key, precondition, _) in operations {
+            let current_version = self.get_version(key)?;
+            if !precondition.holds(current_version) {
+                failed_preconditions.push(FailedPrecondition::new(
+                    key.clone(),
+                    precondition.clone(),
+                    current_version,
+                ));
+            }
+            current_versions.push(current_version);
+        }
+
+        if !failed_preconditions.is_empty() {
+            return Ok((
+                Err(Error::FailedPreconditions(failed_preconditions)),
+                Vec::new(),
+            ));
+        }
+
+        let mut changes = Vec::with_capacity(operations.len());
+        for ((key, _, operation), current_version) in operations.iter().zip(current_versions) {
+            match operation {
+                TransactionOperation::Put(value) => {
+                    batch.put_cf(self.cf(KV_PAIRS), key, encode(value)?);
+                    changes.push(WatchEvent::new(key.clone(), Some(value.clone())));
+                }
+                // deleting a non-existing key-value pair is a no-op
+                TransactionOperation::Delete if current_version.is_none() => {}
+                TransactionOperation::Delete => {
+                    batch.delete_cf(self.cf(KV_PAIRS), key);
+                    changes.push(WatchEvent::new(key.clone(), None));
+                }
+            }
+        }
+        Ok((Ok(Response::Done), changes))
+    }
+
+    /// Lists the key-value pairs of the state machine whose keys start with `prefix`.
+    pub fn list(&self, prefix: &ByteString) -> Result<Vec<(ByteString, VersionedValue)>> {
+        let iter = self.db.iterator_cf(
+            self.cf(KV_PAIRS),
+            IteratorMode::From(prefix.as_bytes(), rocksdb::Direction::Forward),
+        );
+
+        let mut kv_pairs = Vec::new();
+        for item in iter {
+            let (key, value) = item?;
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            let key = String::from_utf8(key.into_vec()).map_err(|err| Error::Codec(err.into()))?;
+            kv_pairs.push((ByteString::from(key), decode(&value)?));
+        }
+        Ok(kv_pairs)
+    }
+
+    fn get_version(&self, key: &ByteString) -> Result<Option<Version>> {
+        // todo only deserialize the version part
+        Ok(self
+            .get::<VersionedValue>(KV_PAIRS, key)?
+            .map(|value| value.version))
+    }
+
+    fn get<T: DeserializeOwned>(&self, cf: &str, key: impl AsRef<[u8]>) -> Result<Option<T>> {
+        self.db
+            .get_pinned_cf(self.cf(cf), key)?
+            .map(|bytes| decode(&bytes))
+            .transpose()
+    }
+}
+
+fn check_precondition(
+    precondition: &Precondition,
+    current_version: Option<Version>,
+) -> std::result::Result<(), Error> {
+    match precondition {
+        Precondition::None => Ok(()),
+        Precondition::DoesNotExist if current_version.is_none() => Ok(()),
+        Precondition::DoesNotExist => Err(Error::kv_pair_exists()),
+        Precondition::MatchesVersion(version) if current_version == Some(*version) => Ok(()),
+        Precondition::MatchesVersion(version) => {
+            Err(Error::version_mismatch(*version, current_version))
+        }
+    }
+}
+
+fn decode_index(key: &[u8]) -> LogIndex {
+    LogIndex::from_be_bytes(key.try_into().expect("log keys are indexes"))
+}
+
+fn encode<T: Serialize>(value: T) -> Result<Vec<u8>> {
+    bincode::serde::encode_to_vec(value, bincode::config::standard())
+        .map_err(|err| Error::Codec(err.into()))
+}
+
+fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    bincode::serde::decode_from_slice(bytes, bincode::config::standard())
+        .map(|(value, _)| value)
+        .map_err(|err| Error::Codec(err.into()))
+}
@@ -0,0 +1,1331 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::time::Duration;
+
+use rand::Rng;
+use restate_core::cancellation_watcher;
+use restate_types::{PlainNodeId, Version};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+use tracing::{debug, info, trace};
+
+use crate::raft::network::RaftNetwork;
+use crate::raft::protocol::{
+    Entry, EntryPayload, ForwardError, LogIndex, Member, Members, MembershipChange, Proposal,
+    RaftEnvelope, RaftMessage, Request, Response, Snapshot, Term,
+};
+use crate::raft::storage::{BuildError, HardState, RaftStorage};
+use crate::request::{Error, MetadataStoreRequest, RequestReceiver, RequestSender, Watches};
+use crate::VersionedValue;
+use bytestring::ByteString;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Maximum number of entries the leader sends in a single append entries message.
+const MAX_ENTRIES_PER_MESSAGE: usize = 64;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Timeouts {
+    /// Followers start an election if they don't hear from a leader within a random timeout
+    /// between this and twice this timeout.
+    pub election_timeout: Duration,
+    pub heartbeat_interval: Duration,
+    /// Requests fail if they cannot be passed on to a leader within this timeout.
+    pub request_timeout: Duration,
+}
+
+#[derive(Debug)]
+pub(crate) struct MembershipRequest {
+    pub change: MembershipChange,
+    pub result_tx: oneshot::Sender<Result<Members>>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MembershipError {
+    #[error("metadata store is unavailable: {0}")]
+    Unavailable(String),
+    #[error("invalid membership change: {0}")]
+    InvalidArgument(String),
+    #[error("internal error: {0}")]
+    Internal(String),
+}
+
+impl From<Error> for MembershipError {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Unavailable(msg) => MembershipError::Unavailable(msg),
+            Error::InvalidArgument(msg) => MembershipError::InvalidArgument(msg),
+            err => MembershipError::Internal(err.to_string()),
+        }
+    }
+}
+
+/// Changes the members of a [`RaftMetadataStore`]. Requests are passed on to the leader.
+#[derive(Debug, Clone)]
+pub struct MembershipHandle {
+    membership_tx: mpsc::Sender<MembershipRequest>,
+}
+
+impl MembershipHandle {
+    /// Adds the member to the raft group and returns the new members once the change is
+    /// committed.
+    pub async fn add_member(
+        &self,
+        member: Member,
+    ) -> std::result::Result<Members, MembershipError> {
+        self.change(MembershipChange::Add(member)).await
+    }
+
+    /// Removes the member from the raft group and returns the new members once the change is
+    /// committed.
+    pub async fn remove_member(
+        &self,
+        node_id: PlainNodeId,
+    ) -> std::result::Result<Members, MembershipError> {
+        self.change(MembershipChange::Remove(node_id)).await
+    }
+
+    async fn change(
+        &self,
+        change: MembershipChange,
+    ) -> std::result::Result<Members, MembershipError> {
+        let (result_tx, result_rx) = oneshot::channel();
+        self.membership_tx
+            .send(MembershipRequest { change, result_tx })
+            .await
+            .map_err(|_| MembershipError::Unavailable("metadata store is shut down".to_owned()))?;
+        Ok(result_rx.await.map_err(|_| {
+            MembershipError::Unavailable("metadata store is shut down".to_owned())
+        })??)
+    }
+}
+
+/// Metadata store whose key-value pairs are replicated to the members of a raft group.
+///
+/// Members that are not the leader forward the requests of their clients to the leader. Reads
+/// are appended to the raft log as well, which makes them linearizable. Watches are served from
+/// the key-value pairs which the member has applied, hence they can lag behind the leader.
+pub struct RaftMetadataStore {
+    me: Member,
+    storage: RaftStorage,
+    network: Box<dyn RaftNetwork>,
+    initial_members: Members,
+    timeouts: Timeouts,
+    log_compaction_threshold: u64,
+
+    request_rx: RequestReceiver,
+    membership_rx: mpsc::Receiver<MembershipRequest>,
+    message_rx: mpsc::Receiver<RaftEnvelope>,
+
+    // for creating other senders
+    request_tx: RequestSender,
+    membership_tx: mpsc::Sender<MembershipRequest>,
+    message_tx: mpsc::Sender<RaftEnvelope>,
+}
+
+impl RaftMetadataStore {
+    pub(crate) fn new(
+        me: Member,
+        path: impl AsRef<Path>,
+        network: impl RaftNetwork,
+        initial_members: Members,
+        timeouts: Timeouts,
+        log_compaction_threshold: u64,
+        request_queue_length: usize,
+    ) -> std::result::Result<Self, BuildError> {
+        let (request_tx, request_rx) = mpsc::channel(request_queue_length);
+        let (membership_tx, membership_rx) = mpsc::channel(request_queue_length);
+        let (message_tx, message_rx) = mpsc::channel(request_queue_length);
+
+        Ok(Self {
+            me,
+            storage: RaftStorage::open(path)?,
+            network: Box::new(network),
+            initial_members,
+            timeouts,
+            log_compaction_threshold,
+            request_rx,
+            membership_rx,
+            message_rx,
+            request_tx,
+            membership_tx,
+            message_tx,
+        })
+    }
+
+    pub fn request_sender(&self) -> RequestSender {
+        self.request_tx.clone()
+    }
+
+    pub fn membership_handle(&self) -> MembershipHandle {
+        MembershipHandle {
+            membership_tx: self.membership_tx.clone(),
+        }
+    }
+
+    /// Sender for the raft messages which this member receives from the other members.
+    pub(crate) fn message_sender(&self) -> mpsc::Sender<RaftEnvelope> {
+        self.message_tx.clone()
+    }
+
+    pub async fn run(self) -> anyhow::Result<()> {
+        debug!("Running RaftMetadataStore as {}", self.me.node_id);
+        RaftNode::recover(self)?.run().await?;
+        debug!("Stopped RaftMetadataStore");
+        Ok(())
+    }
+}
+
+/// Who gets the result of a proposal.
+#[derive(Debug)]
+enum Callback {
+    Get(oneshot::Sender<Result<Option<VersionedValue>>>),
+    GetVersion(oneshot::Sender<Result<Option<Version>>>),
+    Write(oneshot::Sender<Result<()>>),
+    List(oneshot::Sender<Result<Vec<(ByteString, VersionedValue)>>>),
+    Membership(oneshot::Sender<Result<Members>>),
+    /// The proposal was forwarded by another member.
+    Forwarded {
+        from: Member,
+        request_id: u64,
+    },
+}
+
+/// Returns the proposal for requests which go through the raft log.
+fn into_proposal(
+    request: MetadataStoreRequest,
+) -> std::result::Result<(Proposal, Callback), MetadataStoreRequest> {
+    let proposal = match request {
+        MetadataStoreRequest::Get { key, result_tx } => (
+            Proposal::Request(Request::Get { key }),
+            Callback::Get(result_tx),
+        ),
+        MetadataStoreRequest::GetVersion { key, result_tx } => (
+            Proposal::Request(Request::GetVersion { key }),
+            Callback::GetVersion(result_tx),
+        ),
+        MetadataStoreRequest::Put {
+            key,
+            value,
+            precondition,
+            result_tx,
+        } => (
+            Proposal::Request(Request::Put {
+                key,
+                value,
+                precondition,
+            }),
+            Callback::Write(result_tx),
+        ),
+        MetadataStoreRequest::Delete {
+            key,
+            precondition,
+            result_tx,
+        } => (
+            Proposal::Request(Request::Delete { key, precondition }),
+            Callback::Write(result_tx),
+        ),
+        MetadataStoreRequest::Transaction {
+            operations,
+            result_tx,
+        } => (
+            Proposal::Request(Request::Transaction { operations }),
+            Callback::Write(result_tx),
+        ),
+        MetadataStoreRequest::List { prefix, result_tx } => (
+            Proposal::Request(Request::List { prefix }),
+            Callback::List(result_tx),
+        ),
+        request @ (MetadataStoreRequest::Watch { .. }
+        | MetadataStoreRequest::Snapshot { .. }
+        | MetadataStoreRequest::Restore { .. }) => return Err(request),
+    };
+    Ok(proposal)
+}
+
+#[derive(Debug)]
+enum Role {
+    Follower,
+    Candidate { votes: HashSet<PlainNodeId> },
+    Leader(LeaderState),
+}
+
+#[derive(Debug)]
+struct LeaderState {
+    followers: HashMap<PlainNodeId, Progress>,
+    heartbeat_deadline: Instant,
+}
+
+/// The replication progress of a follower, as seen by the leader.
+#[derive(Debug)]
+struct Progress {
+    next_index: LogIndex,
+    match_index: LogIndex,
+    last_response: Instant,
+}
+
+struct PendingProposal {
+    proposal: Proposal,
+    callback: Callback,
+    deadline: Instant,
+}
+
+struct RaftNode {
+    me: Member,
+    storage: RaftStorage,
+    network: Box<dyn RaftNetwork>,
+    initial_members: Members,
+    timeouts: Timeouts,
+    /// Number of applied entries after which they are removed from the log.
+    log_compaction_threshold: u64,
+
+    request_rx: RequestReceiver,
+    membership_rx: mpsc::Receiver<MembershipRequest>,
+    message_rx: mpsc::Receiver<RaftEnvelope>,
+    // keep the channels open
+    _request_tx: RequestSender,
+    _membership_tx: mpsc::Sender<MembershipRequest>,
+    _message_tx: mpsc::Sender<RaftEnvelope>,
+
+    hard_state: HardState,
+    role: Role,
+    leader: Option<Member>,
+    last_leader_contact: Option<Instant>,
+    election_deadline: Instant,
+
+    last_log_index: LogIndex,
+    last_log_term: Term,
+    commit_index: LogIndex,
+    applied_index: LogIndex,
+    /// Index of the last entry which has been removed from the log. Followers which need older
+    /// entries get a snapshot.
+    snapshot_index: LogIndex,
+    members: Members,
+    /// Index of the log entry of the current members, 0 if they are the initial members.
+    members_index: LogIndex,
+
+    /// Proposals of the leader that wait for their entries to be committed, with the term in
+    /// which they were appended.
+    appended: BTreeMap<LogIndex, (Term, Callback)>,
+    /// Proposals that were forwarded to the leader and wait for its response.
+    forwarded: HashMap<u64, PendingProposal>,
+    next_request_id: u64,
+    /// Proposals that wait for a leader to be known.
+    queued: VecDeque<PendingProposal>,
+    watches: Watches,
+}
+
+impl RaftNode {
+    fn recover(store: RaftMetadataStore) -> Result<Self> {
+        let hard_state = store.storage.hard_state()?;
+        let (last_log_index, last_log_term) = store.storage.last_entry_id()?;
+        let applied_index = store.storage.applied_index()?;
+        let snapshot_index = store.storage.snapshot_metadata()?.last_index;
+        let (members_index, members) = store
+            .storage
+            .latest_membership()?
+            .unwrap_or_else(|| (0, store.initial_members.clone()));
+
+        debug!(
+            "Recovered raft state: term {}, last log index {}, applied index {}, snapshot index \
+            {}, members {:?}",
+            hard_state.term, last_log_index, applied_index, snapshot_index, members
+        );
+
+        let election_deadline = Self::random_election_deadline(&store.timeouts);
+        Ok(Self {
+            me: store.me,
+            storage: store.storage,
+            network: store.network,
+            initial_members: store.initial_members,
+            timeouts: store.timeouts,
+            log_compaction_threshold: store.log_compaction_threshold,
+            request_rx: store.request_rx,
+            membership_rx: store.membership_rx,
+            message_rx: store.message_rx,
+            _request_tx: store.request_tx,
+            _membership_tx: store.membership_tx,
+            _message_tx: store.message_tx,
+            hard_state,
+            role: Role::Follower,
+            leader: None,
+            last_leader_contact: None,
+            election_deadline,
+            last_log_index,
+            last_log_term,
+            // everything that has been applied is committed
+            commit_index: applied_index,
+            applied_index,
+            snapshot_index,
+            members,
+            members_index,
+            appended: BTreeMap::new(),
+            forwarded: HashMap::new(),
+            next_request_id: 1,
+            queued: VecDeque::new(),
+            watches: Watches::default(),
+        })
+    }
+
+    async fn run(mut self) -> Result<()> {
+        loop {
+            let deadline = self.next_deadline();
+            tokio::select! {
+                Some(request) = self.request_rx.recv() => {
+                    self.on_request(request)?;
+                }
+                Some(request) = self.membership_rx.recv() => {
+                    self.propose(
+                        Proposal::ChangeMembership(request.change),
+                        Callback::Membership(request.result_tx),
+                    )?;
+                }
+                Some(envelope) = self.message_rx.recv() => {
+                    self.on_message(envelope)?;
+                }
+                _ = tokio::time::sleep_until(deadline) => {
+                    self.on_timeout()?;
+                }
+                _ = cancellation_watcher() => {
+                    break;
+                }
+            }
+            self.apply_committed_entries()?;
+        }
+        Ok(())
+    }
+
+    fn random_election_deadline(timeouts: &Timeouts) -> Instant {
+        let timeout =
+            rand::thread_rng().gen_range(timeouts.election_timeout..timeouts.election_timeout * 2);
+        Instant::now() + timeout
+    }
+
+    fn next_deadline(&self) -> Instant {
+        let role_deadline = match &self.role {
+            Role::Leader(leader_state) => leader_state.heartbeat_deadline,
+            _ => self.election_deadline,
+        };
+        self.forwarded
+            .values()
+            .chain(self.queued.iter())
+            .map(|pending| pending.deadline)
+            .fold(role_deadline, Instant::min)
+    }
+
+    fn send(&self, to: &Member, message: RaftMessage) {
+        trace!("Sending {:?} to {}", message, to.node_id);
+        self.network.send(
+            to,
+            RaftEnvelope {
+                from: self.me.clone(),
+                message,
+            },
+        );
+    }
+
+    // -- proposals
+
+    fn on_request(&mut self, request: MetadataStoreRequest) -> Result<()> {
+        match into_proposal(request) {
+            Ok((proposal, callback)) => self.propose(proposal, callback),
+            Err(MetadataStoreRequest::Watch { prefix, result_tx }) => {
+                // entries are applied in this task, no change can happen in between
+                let result = self
+                    .storage
+                    .list(&prefix)
+                    .map(|snapshot| self.watches.subscribe(snapshot));
+                let _ = result_tx.send(result);
+                Ok(())
+            }
+            Err(MetadataStoreRequest::Snapshot { result_tx }) => {
+                let _ = result_tx.send(Err(Self::unsupported("snapshots")));
+                Ok(())
+            }
+            Err(MetadataStoreRequest::Restore { result_tx, .. }) => {
+                let _ = result_tx.send(Err(Self::unsupported("restoring snapshots")));
+                Ok(())
+            }
+            Err(request) => unreachable!("request {:?} is proposed", request),
+        }
+    }
+
+    fn unsupported(operation: &str) -> Error {
+        Error::Unimplemented(format!(
+            "{operation} are not supported by the raft metadata store"
+        ))
+    }
+
+    fn propose(&mut self, proposal: Proposal, callback: Callback) -> Result<()> {
+        if matches!(self.role, Role::Leader(_)) {
+            return self.append_proposal(proposal, callback);
+        }
+
+        let pending = PendingProposal {
+            proposal,
+            callback,
+            deadline: Instant::now() + self.timeouts.request_timeout,
+        };
+        match self.leader.clone() {
+            Some(leader) => self.forward(&leader, pending),
+            None => self.queued.push_back(pending),
+        }
+        Ok(())
+    }
+
+    fn forward(&mut self, leader: &Member, pending: PendingProposal) {
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        self.send(
+            leader,
+            RaftMessage::Forward {
+                request_id,
+                proposal: pending.proposal.clone(),
+            },
+        );
+        self.forwarded.insert(request_id, pending);
+    }
+
+    fn append_proposal(&mut self, proposal: Proposal, callback: Callback) -> Result<()> {
+        let payload = match proposal {
+            Proposal::Request(request) => EntryPayload::Request(request),
+            Proposal::ChangeMembership(change) => match self.change_members(change) {
+                Ok(Some(members)) => EntryPayload::Membership(members),
+                Ok(None) => {
+                    // nothing to change
+                    self.complete(callback, Ok(Response::Members(self.members.clone())));
+                    return Ok(());
+                }
+                Err(err) => {
+                    self.complete(callback, Err(err));
+                    return Ok(());
+                }
+            },
+        };
+
+        let index = self.append(payload)?;
+        self.appended
+            .insert(index, (self.hard_state.term, callback));
+        self.replicate_to_all()?;
+        self.advance_commit_index()
+    }
+
+    /// Returns the members after the change or `None` if the change has no effect.
+    fn change_members(&self, change: MembershipChange) -> Result<Option<Members>> {
+        if self.members_index > self.commit_index {
+            return Err(Error::Unavailable(
+                "another membership change is in progress".to_owned(),
+            ));
+        }
+
+        let mut members = self.members.clone();
+        match change {
+            MembershipChange::Add(member) => {
+                if members.get(member.node_id).as_ref() == Some(&member) {
+                    return Ok(None);
+                }
+                members.insert(member);
+            }
+            MembershipChange::Remove(node_id) => {
+                if !members.contains(node_id) {
+                    return Ok(None);
+                }
+                if members.len() == 1 {
+                    return Err(Error::InvalidArgument(
+                        "cannot remove the last member".to_owned(),
+                    ));
+                }
+                members.remove(node_id);
+            }
+        }
+        Ok(Some(members))
+    }
+
+    /// Appends an entry of the current term to the log of the leader.
+    fn append(&mut self, payload: EntryPayload) -> Result<LogIndex> {
+        let index = self.last_log_index + 1;
+        let entry = Entry {
+            term: self.hard_state.term,
+            payload,
+        };
+        self.storage.append(index, std::slice::from_ref(&entry))?;
+        self.last_log_index = index;
+        self.last_log_term = entry.term;
+
+        if let EntryPayload::Membership(members) = entry.payload {
+            self.set_members(index, members);
+        }
+        Ok(index)
+    }
+
+    fn complete(&self, callback: Callback, result: Result<Response>) {
+        fn unexpected<T>(response: Response) -> Result<T> {
+            Err(Error::Internal(format!(
+                "unexpected response {:?}",
+                response
+            )))
+        }
+
+        match callback {
+            Callback::Get(tx) => {
+                let _ = tx.send(result.and_then(|response| match response {
+                    Response::Value(value) => Ok(value),
+                    response => unexpected(response),
+                }));
+            }
+            Callback::GetVersion(tx) => {
+                let _ = tx.send(result.and_then(|response| match response {
+                    Response::Version(version) => Ok(version),
+                    response => unexpected(response),
+                }));
+            }
+            Callback::Write(tx) => {
+                let _ = tx.send(result.and_then(|response| match response {
+                    Response::Done => Ok(()),
+                    response => unexpected(response),
+                }));
+            }
+            Callback::List(tx) => {
+                let _ = tx.send(result.and_then(|response| match response {
+                    Response::KvPairs(kv_pairs) => Ok(kv_pairs),
+                    response => unexpected(response),
+                }));
+            }
+            Callback::Membership(tx) => {
+                let _ = tx.send(result.and_then(|response| match response {
+                    Response::Members(members) => Ok(members),
+                    response => unexpected(response),
+                }));
+            }
+            Callback::Forwarded { from, request_id } => self.send(
+                &from,
+                RaftMessage::ForwardResponse {
+                    request_id,
+                    result: result.map_err(Into::into),
+                },
+            ),
+        }
+    }
+
+    // -- messages
+
+    fn on_message(&mut self, envelope: RaftEnvelope) -> Result<()> {
+        let RaftEnvelope { from, message } = envelope;
+        trace!("Received {:?} from {}", message, from.node_id);
+
+        match message {
+            RaftMessage::RequestVote {
+                term,
+                last_log_index,
+                last_log_term,
+            } => self.on_request_vote(from, term, last_log_index, last_log_term),
+            RaftMessage::Vote { term, granted } => self.on_vote(from, term, granted),
+            RaftMessage::AppendEntries {
+                term,
+                prev_log_index,
+                prev_log_term,
+                entries,
+                leader_commit,
+            } => self.on_append_entries(
+                from,
+                term,
+                prev_log_index,
+                prev_log_term,
+                entries,
+                leader_commit,
+            ),
+            RaftMessage::AppendEntriesResponse {
+                term,
+                success,
+                last_log_index,
+            } => self.on_append_entries_response(from, term, success, last_log_index),
+            RaftMessage::InstallSnapshot { term, snapshot } => {
+                self.on_install_snapshot(from, term, snapshot)
+            }
+            RaftMessage::Forward {
+                request_id,
+                proposal,
+            } => {
+                if matches!(self.role, Role::Leader(_)) {
+                    self.append_proposal(proposal, Callback::Forwarded { from, request_id })
+                } else {
+                    self.send(
+                        &from,
+                        RaftMessage::ForwardResponse {
+                            request_id,
+                            result: Err(ForwardError::NotLeader),
+                        },
+                    );
+                    Ok(())
+                }
+            }
+            RaftMessage::ForwardResponse { request_id, result } => {
+                self.on_forward_response(from, request_id, result)
+            }
+        }
+    }
+
+    /// Adopts the term of a message with a higher term.
+    fn observe_term(&mut self, term: Term) -> Result<()> {
+        if term > self.hard_state.term {
+            self.become_follower(term, None)?;
+        }
+        Ok(())
+    }
+
+    fn on_request_vote(
+        &mut self,
+        from: Member,
+        term: Term,
+        last_log_index: LogIndex,
+        last_log_term: Term,
+    ) -> Result<()> {
+        // Members ignore vote requests while they believe that a leader exists. This keeps
+        // removed members, which don't hear from the leader anymore, from disrupting the group.
+        let leader_alive = match self.role {
+            Role::Leader(_) => true,
+            _ => self
+                .last_leader_contact
+                .is_some_and(|contact| contact.elapsed() < self.timeouts.election_timeout),
+        };
+        if leader_alive && term > self.hard_state.term {
+            trace!(
+                "Ignoring vote request of {} while the leader is alive",
+                from.node_id
+            );
+            return Ok(());
+        }
+
+        self.observe_term(term)?;
+
+        let log_is_up_to_date =
+            (last_log_term, last_log_index) >= (self.last_log_term, self.last_log_index);
+        let granted = term == self.hard_state.term
+            && self
+                .hard_state
+                .voted_for
+                .map_or(true, |voted_for| voted_for == from.node_id)
+            && log_is_up_to_date;
+
+        if granted {
+            self.hard_state.voted_for = Some(from.node_id);
+            self.storage.store_hard_state(&self.hard_state)?;
+            self.election_deadline = Self::random_election_deadline(&self.timeouts);
+        }
+
+        self.send(
+            &from,
+            RaftMessage::Vote {
+                term: self.hard_state.term,
+                granted,
+            },
+        );
+        Ok(())
+    }
+
+    fn on_vote(&mut self, from: Member, term: Term, granted: bool) -> Result<()> {
+        self.observe_term(term)?;
+
+        let Role::Candidate { votes } = &mut self.role else {
+            return Ok(());
+        };
+        if term != self.hard_state.term || !granted || !self.members.contains(from.node_id) {
+            return Ok(());
+        }
+
+        votes.insert(from.node_id);
+        if votes.len() >= self.members.quorum() {
+            self.become_leader()?;
+        }
+        Ok(())
+    }
+
+    /// Follows the sender of a message of the leader. Returns `false` if the message is from an
+    /// outdated leader and has been rejected.
+    fn accept_leader(&mut self, from: &Member, term: Term) -> Result<bool> {
+        if term < self.hard_state.term {
+            self.send(
+                from,
+                RaftMessage::AppendEntriesResponse {
+                    term: self.hard_state.term,
+                    success: false,
+                    last_log_index: self.last_log_index,
+                },
+            );
+            return Ok(false);
+        }
+
+        if term > self.hard_state.term || !matches!(self.role, Role::Follower) {
+            self.become_follower(term, Some(from.clone()))?;
+        } else if self.leader.as_ref() != Some(from) {
+            self.set_leader(from.clone());
+        }
+        self.last_leader_contact = Some(Instant::now());
+        self.election_deadline = Self::random_election_deadline(&self.timeouts);
+        Ok(true)
+    }
+
+    fn on_append_entries(
+        &mut self,
+        from: Member,
+        term: Term,
+        mut prev_log_index: LogIndex,
+        mut prev_log_term: Term,
+        mut entries: Vec<Entry>,
+        leader_commit: LogIndex,
+    ) -> Result<()> {
+        if !self.accept_leader(&from, term)? {
+            return Ok(());
+        }
+
+        // The removed entries have been applied, so they are committed and match the entries of
+        // the leader. Only the entries after the last removed entry are checked.
+        if prev_log_index < self.snapshot_index {
+            let removed = usize::try_from(self.snapshot_index - prev_log_index)
+                .expect("index difference fits into usize");
+            if entries.len() < removed {
+                self.send(
+                    &from,
+                    RaftMessage::AppendEntriesResponse {
+                        term,
+                        success: true,
+                        last_log_index: self.snapshot_index,
+                    },
+                );
+                return Ok(());
+            }
+            entries.drain(..removed);
+            prev_log_index = self.snapshot_index;
+            prev_log_term = self
+                .storage
+                .term_at(prev_log_index)?
+                .expect("term of the last removed entry is known");
+        }
+
+        // Does our log contain the entry preceding the new entries?
+        if prev_log_index > self.last_log_index
+            || self.storage.term_at(prev_log_index)? != Some(prev_log_term)
+        {
+            self.send(
+                &from,
+                RaftMessage::AppendEntriesResponse {
+                    term,
+                    success: false,
+                    last_log_index: self.last_log_index.min(prev_log_index.saturating_sub(1)),
+                },
+            );
+            return Ok(());
+        }
+
+        let last_new_index = prev_log_index + entries.len() as LogIndex;
+
+        // Skip the entries we already have, the first conflicting entry and all following
+        // entries are replaced.
+        let mut first_new = 0;
+        for (index, entry) in (prev_log_index + 1..).zip(&entries) {
+            if index > self.last_log_index || self.storage.term_at(index)? != Some(entry.term) {
+                break;
+            }
+            first_new += 1;
+        }
+
+        if first_new < entries.len() {
+            let first_index = prev_log_index + 1 + first_new as LogIndex;
+            assert!(
+                first_index > self.commit_index,
+                "committed entries must never be replaced"
+            );
+            let new_entries = &entries[first_new..];
+            self.storage.append(first_index, new_entries)?;
+            self.last_log_index = last_new_index;
+            self.last_log_term = new_entries.last().expect("non empty").term;
+
+            if self.members_index >= first_index {
+                // the membership was replaced, fall back to the previous one
+                let (members_index, members) = self
+                    .storage
+                    .latest_membership()?
+                    .unwrap_or_else(|| (0, self.initial_members.clone()));
+                self.set_members(members_index, members);
+            } else if let Some((index, members)) = (first_index..)
+                .zip(new_entries)
+                .filter_map(|(index, entry)| match &entry.payload {
+                    EntryPayload::Membership(members) => Some((index, members.clone())),
+                    _ => None,
+                })
+                .last()
+            {
+                self.set_members(index, members);
+            }
+        }
+
+        if leader_commit > self.commit_index {
+            self.commit_index = leader_commit.min(last_new_index);
+        }
+
+        self.send(
+            &from,
+            RaftMessage::AppendEntriesResponse {
+                term,
+                success: true,
+                last_log_index: last_new_index,
+            },
+        );
+        Ok(())
+    }
+
+    fn on_install_snapshot(&mut self, from: Member, term: Term, snapshot: Snapshot) -> Result<()> {
+        if !self.accept_leader(&from, term)? {
+            return Ok(());
+        }
+
+        let metadata = snapshot.metadata.clone();
+        // A snapshot of committed entries which this member has applied already is ignored.
+        if metadata.last_index > self.commit_index {
+            info!(
+                "Metadata store {} installs the snapshot of the leader {} at index {}",
+                self.me.node_id, from.node_id, metadata.last_index
+            );
+            let changes = self.storage.install_snapshot(snapshot)?;
+            self.last_log_index = metadata.last_index;
+            self.last_log_term = metadata.last_term;
+            self.commit_index = metadata.last_index;
+            self.applied_index = metadata.last_index;
+            self.snapshot_index = metadata.last_index;
+            let (members_index, members) = metadata
+                .membership
+                .unwrap_or_else(|| (0, self.initial_members.clone()));
+            self.set_members(members_index, members);
+            for change in changes {
+                self.watches.notify(change.key, change.value);
+            }
+        }
+
+        self.send(
+            &from,
+            RaftMessage::AppendEntriesResponse {
+                term,
+                success: true,
+                last_log_index: metadata.last_index,
+            },
+        );
+        Ok(())
+    }
+
+    fn on_append_entries_response(
+        &mut self,
+        from: Member,
+        term: Term,
+        success: bool,
+        last_log_index: LogIndex,
+    ) -> Result<()> {
+        self.observe_term(term)?;
+        if term != self.hard_state.term {
+            return Ok(());
+        }
+        let Role::Leader(leader_state) = &mut self.role else {
+            return Ok(());
+        };
+        let Some(progress) = leader_state.followers.get_mut(&from.node_id) else {
+            return Ok(());
+        };
+
+        progress.last_response = Instant::now();
+        if success {
+            progress.match_index = progress.match_index.max(last_log_index);
+            progress.next_index = progress.next_index.max(last_log_index + 1);
+        } else {
+            progress.next_index = (progress.next_index - 1).min(last_log_index + 1).max(1);
+        }
+
+        if progress.next_index <= self.last_log_index || !success {
+            self.replicate_to(&from)?;
+        }
+        self.advance_commit_index()
+    }
+
+    fn on_forward_response(
+        &mut self,
+        from: Member,
+        request_id: u64,
+        result: std::result::Result<Response, ForwardError>,
+    ) -> Result<()> {
+        let Some(pending) = self.forwarded.remove(&request_id) else {
+            return Ok(());
+        };
+
+        match result {
+            Err(ForwardError::NotLeader) => {
+                // the proposal was not appended, it can be passed on to the actual leader
+                if self.leader.as_ref() == Some(&from) {
+                    self.leader = None;
+                }
+                self.propose(pending.proposal, pending.callback)
+            }
+            result => {
+                self.complete(pending.callback, result.map_err(Into::into));
+                Ok(())
+            }
+        }
+    }
+
+    // -- role changes
+
+    fn become_follower(&mut self, term: Term, leader: Option<Member>) -> Result<()> {
+        if term > self.hard_state.term {
+            self.hard_state = HardState {
+                term,
+                voted_for: None,
+            };
+            self.storage.store_hard_state(&self.hard_state)?;
+        }
+
+        if matches!(self.role, Role::Leader(_)) {
+            info!(
+                "Metadata store {} is no longer the raft leader in term {}",
+                self.me.node_id, term
+            );
+            for (_, (_, callback)) in std::mem::take(&mut self.appended) {
+                self.complete(
+                    callback,
+                    Err(Error::Unavailable("leadership was lost".to_owned())),
+                );
+            }
+        }
+
+        self.role = Role::Follower;
+        self.leader = None;
+        if let Some(leader) = leader {
+            self.set_leader(leader);
+        }
+        self.election_deadline = Self::random_election_deadline(&self.timeouts);
+        Ok(())
+    }
+
+    fn set_leader(&mut self, leader: Member) {
+        debug!(
+            "Metadata store {} follows the raft leader {} in term {}",
+            self.me.node_id, leader.node_id, self.hard_state.term
+        );
+        for pending in std::mem::take(&mut self.queued) {
+            self.forward(&leader, pending);
+        }
+        self.leader = Some(leader);
+    }
+
+    fn start_election(&mut self) -> Result<()> {
+        self.hard_state = HardState {
+            term: self.hard_state.term + 1,
+            voted_for: Some(self.me.node_id),
+        };
+        self.storage.store_hard_state(&self.hard_state)?;
+        self.role = Role::Candidate {
+            votes: HashSet::from([self.me.node_id]),
+        };
+        self.leader = None;
+        self.election_deadline = Self::random_election_deadline(&self.timeouts);
+        debug!(
+            "Metadata store {} starts an election in term {}",
+            self.me.node_id, self.hard_state.term
+        );
+
+        if self.members.quorum() == 1 {
+            return self.become_leader();
+        }
+
+        for member in self.members.iter() {
+            if member.node_id != self.me.node_id {
+                self.send(
+                    &member,
+                    RaftMessage::RequestVote {
+                        term: self.hard_state.term,
+                        last_log_index: self.last_log_index,
+                        last_log_term: self.last_log_term,
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn become_leader(&mut self) -> Result<()> {
+        info!(
+            "Metadata store {} became the raft leader in term {}",
+            self.me.node_id, self.hard_state.term
+        );
+        let now = Instant::now();
+        let followers = self
+            .members
+            .node_ids()
+            .filter(|node_id| *node_id != self.me.node_id)
+            .map(|node_id| {
+                (
+                    node_id,
+                    Progress {
+                        next_index: self.last_log_index + 1,
+                        match_index: 0,
+                        last_response: now,
+                    },
+                )
+            })
+            .collect();
+        self.role = Role::Leader(LeaderState {
+            followers,
+            heartbeat_deadline: now + self.timeouts.heartbeat_interval,
+        });
+        self.leader = Some(self.me.clone());
+
+        // Entries of previous terms are only committed together with an entry of this term.
+        self.append(EntryPayload::Noop)?;
+        self.replicate_to_all()?;
+        self.advance_commit_index()?;
+
+        for pending in std::mem::take(&mut self.queued) {
+            self.append_proposal(pending.proposal, pending.callback)?;
+        }
+        Ok(())
+    }
+
+    fn set_members(&mut self, index: LogIndex, members: Members) {
+        debug!(
+            "Metadata store {} uses the members {:?} from index {}",
+            self.me.node_id, members, index
+        );
+        if let Role::Leader(leader_state) = &mut self.role {
+            leader_state
+                .followers
+                .retain(|node_id, _| members.contains(*node_id));
+            for node_id in members.node_ids() {
+                if node_id != self.me.node_id {
+                    leader_state
+                        .followers
+                        .entry(node_id)
+                        .or_insert_with(|| Progress {
+                            next_index: self.last_log_index + 1,
+                            match_index: 0,
+                            last_response: Instant::now(),
+                        });
+                }
+            }
+        }
+        self.members = members;
+        self.members_index = index;
+    }
+
+    // -- replication
+
+    fn replicate_to_all(&self) -> Result<()> {
+        for member in self.members.iter() {
+            if member.node_id != self.me.node_id {
+                self.replicate_to(&member)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends the entries the follower is missing, or a heartbeat if it is up to date. Followers
+    /// which miss entries that have been removed from the log get a snapshot instead.
+    fn replicate_to(&self, follower: &Member) -> Result<()> {
+        let Role::Leader(leader_state) = &self.role else {
+            return Ok(());
+        };
+        let Some(progress) = leader_state.followers.get(&follower.node_id) else {
+            return Ok(());
+        };
+
+        if progress.next_index <= self.snapshot_index {
+            self.send(
+                follower,
+                RaftMessage::InstallSnapshot {
+                    term: self.hard_state.term,
+                    snapshot: self.storage.snapshot()?,
+                },
+            );
+            return Ok(());
+        }
+
+        let prev_log_index = progress.next_index - 1;
+        let prev_log_term = self
+            .storage
+            .term_at(prev_log_index)?
+            .expect("leader has all entries up to its last index");
+        let entries = if progress.next_index <= self.last_log_index {
+            self.storage
+                .entries(progress.next_index, MAX_ENTRIES_PER_MESSAGE)?
+        } else {
+            Vec::new()
+        };
+
+        self.send(
+            follower,
+            RaftMessage::AppendEntries {
+                term: self.hard_state.term,
+                prev_log_index,
+                prev_log_term,
+                entries,
+                leader_commit: self.commit_index,
+            },
+        );
+        Ok(())
+    }
+
+    fn advance_commit_index(&mut self) -> Result<()> {
+        let Role::Leader(leader_state) = &self.role else {
+            return Ok(());
+        };
+
+        let mut match_indexes: Vec<_> = self
+            .members
+            .node_ids()
+            .map(|node_id| {
+                if node_id == self.me.node_id {
+                    self.last_log_index
+                } else {
+                    leader_state
+                        .followers
+                        .get(&node_id)
+                        .map_or(0, |progress| progress.match_index)
+                }
+            })
+            .collect();
+        match_indexes.sort_unstable_by(|a, b| b.cmp(a));
+
+        // the highest index which is stored by a majority
+        let majority_index = match_indexes[self.members.quorum() - 1];
+        if majority_index > self.commit_index
+            && self.storage.term_at(majority_index)? == Some(self.hard_state.term)
+        {
+            trace!("Advancing the commit index to {}", majority_index);
+            self.commit_index = majority_index;
+        }
+        Ok(())
+    }
+
+    fn apply_committed_entries(&mut self) -> Result<()> {
+        while self.applied_index < self.commit_index {
+            let index = self.applied_index + 1;
+            let entry = self
+                .storage
+                .entry(index)?
+                .expect("committed entries are in the log");
+            let (result, changes) = self.storage.apply(index, &entry)?;
+            self.applied_index = index;
+            for change in changes {
+                self.watches.notify(change.key, change.value);
+            }
+
+            if let Some((term, callback)) = self.appended.remove(&index) {
+                if term == entry.term {
+                    self.complete(callback, result);
+                } else {
+                    self.complete(
+                        callback,
+                        Err(Error::Unavailable("entry was replaced".to_owned())),
+                    );
+                }
+            }
+
+            // A leader that removed itself steps down once the removal is committed.
+            if matches!(entry.payload, EntryPayload::Membership(_))
+                && matches!(self.role, Role::Leader(_))
+                && !self.members.contains(self.me.node_id)
+            {
+                self.become_follower(self.hard_state.term, None)?;
+            }
+        }
+
+        if self.applied_index - self.snapshot_index >= self.log_compaction_threshold {
+            debug!(
+                "Metadata store {} removes the applied entries up to index {} from its log",
+                self.me.node_id, self.applied_index
+            );
+            self.storage.compact(self.applied_index)?;
+            self.snapshot_index = self.applied_index;
+        }
+        Ok(())
+    }
+
+    // -- timeouts
+
+    fn on_timeout(&mut self) -> Result<()> {
+        let now = Instant::now();
+
+        match &mut self.role {
+            Role::Leader(leader_state) => {
+                if now >= leader_state.heartbeat_deadline {
+                    leader_state.heartbeat_deadline = now + self.timeouts.heartbeat_interval;
+
+                    // A leader that doesn't hear from a majority steps down, so that its
+                    // clients can find the actual leader.
+                    let responsive = self
+                        .members
+                        .node_ids()
+                        .filter(|node_id| {
+                            *node_id == self.me.node_id
+                                || leader_state.followers.get(node_id).is_some_and(|progress| {
+                                    now.duration_since(progress.last_response)
+                                        < self.timeouts.election_timeout * 2
+                                })
+                        })
+                        .count();
+                    if responsive < self.members.quorum() {
+                        debug!(
+                            "Metadata store {} lost contact to the majority of the members",
+                            self.me.node_id
+                        );
+                        self.become_follower(self.hard_state.term, None)?;
+                    } else {
+                        self.replicate_to_all()?;
+                    }
+                }
+            }
+            _ => {
+                if now >= self.election_deadline {
+                    if self.members.contains(self.me.node_id) {
+                        self.start_election()?;
+                    } else {
+                        self.election_deadline = Self::random_election_deadline(&self.timeouts);
+                    }
+                }
+            }
+        }
+
+        let expired: Vec<_> = self
+            .forwarded
+            .iter()
+            .filter(|(_, pending)| pending.deadline <= now)
+            .map(|(request_id, _)| *request_id)
+            .collect();
+        for request_id in expired {
+            let pending = self.forwarded.remove(&request_id).expect("exists");
+            self.complete(
+                pending.callback,
+                Err(Error::Unavailable(
+                    "leader did not respond in time".to_owned(),
+                )),
+            );
+        }
+        while self
+            .queued
+            .front()
+            .is_some_and(|pending| pending.deadline <= now)
+        {
+            let pending = self.queued.pop_front().expect("exists");
+            self.complete(
+                pending.callback,
+                Err(Error::Unavailable("no leader is known".to_owned())),
+            );
+        }
+        Ok(())
+    }
+}
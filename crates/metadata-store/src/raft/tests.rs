@@ -0,0 +1,633 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use crate::raft::network::RaftNetwork;
+use crate::raft::protocol::{Member, Members, RaftEnvelope, RaftMessage};
+use crate::raft::store::{MembershipHandle, RaftMetadataStore, Timeouts};
+use crate::request::{Error, MetadataStoreRequest, RequestSender};
+use crate::{Precondition, TransactionOperation, TransactionOperations, VersionedValue};
+use bytes::Bytes;
+use bytestring::ByteString;
+use futures::StreamExt;
+use restate_core::{MockNetworkSender, TaskKind, TestCoreEnv, TestCoreEnvBuilder};
+use restate_types::{PlainNodeId, Version};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use test_log::test;
+use tokio::sync::{mpsc, oneshot};
+
+/// Network which delivers the raft messages in memory. Members can be isolated from the others.
+#[derive(Clone, Default)]
+struct TestNetwork {
+    inner: Arc<Mutex<TestNetworkInner>>,
+}
+
+#[derive(Default)]
+struct TestNetworkInner {
+    members: HashMap<PlainNodeId, mpsc::Sender<RaftEnvelope>>,
+    isolated: HashSet<PlainNodeId>,
+    /// The last member which replicated entries or sent heartbeats.
+    leader: Option<PlainNodeId>,
+}
+
+impl TestNetwork {
+    fn register(&self, node_id: PlainNodeId, message_tx: mpsc::Sender<RaftEnvelope>) {
+        self.inner
+            .lock()
+            .unwrap()
+            .members
+            .insert(node_id, message_tx);
+    }
+
+    fn isolate(&self, node_id: PlainNodeId) {
+        self.inner.lock().unwrap().isolated.insert(node_id);
+    }
+
+    fn heal(&self, node_id: PlainNodeId) {
+        self.inner.lock().unwrap().isolated.remove(&node_id);
+    }
+
+    fn leader(&self) -> Option<PlainNodeId> {
+        self.inner.lock().unwrap().leader
+    }
+}
+
+impl RaftNetwork for TestNetwork {
+    fn send(&self, to: &Member, envelope: RaftEnvelope) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.isolated.contains(&to.node_id) || inner.isolated.contains(&envelope.from.node_id) {
+            return;
+        }
+        if matches!(envelope.message, RaftMessage::AppendEntries { .. }) {
+            inner.leader = Some(envelope.from.node_id);
+        }
+        if let Some(message_tx) = inner.members.get(&to.node_id) {
+            let _ = message_tx.try_send(envelope);
+        }
+    }
+}
+
+/// Low enough that the tests exercise the compaction of the raft log.
+const LOG_COMPACTION_THRESHOLD: u64 = 8;
+
+struct TestMember {
+    request_tx: RequestSender,
+    membership: MembershipHandle,
+}
+
+fn member(id: u32) -> Member {
+    Member::new(
+        PlainNodeId::from(id),
+        format!("http://node-{id}:5123")
+            .parse()
+            .expect("valid address"),
+    )
+}
+
+fn members(ids: impl IntoIterator<Item = u32>) -> Members {
+    ids.into_iter().map(member).collect()
+}
+
+fn start_member(
+    env: &TestCoreEnv<MockNetworkSender>,
+    network: &TestNetwork,
+    id: u32,
+    initial_members: Members,
+) -> anyhow::Result<TestMember> {
+    let store = RaftMetadataStore::new(
+        member(id),
+        tempfile::tempdir()?.into_path(),
+        network.clone(),
+        initial_members,
+        Timeouts {
+            election_timeout: Duration::from_millis(100),
+            heartbeat_interval: Duration::from_millis(20),
+            request_timeout: Duration::from_millis(500),
+        },
+        LOG_COMPACTION_THRESHOLD,
+        32,
+    )?;
+    network.register(PlainNodeId::from(id), store.message_sender());
+
+    let test_member = TestMember {
+        request_tx: store.request_sender(),
+        membership: store.membership_handle(),
+    };
+    env.tc.spawn(
+        TaskKind::MetadataStore,
+        "raft-metadata-store",
+        None,
+        store.run(),
+    )?;
+    Ok(test_member)
+}
+
+/// Retries the request as long as the metadata store is unavailable, e.g. during elections.
+async fn retry_unavailable<T, F>(mut request: impl FnMut() -> F) -> Result<T, Error>
+where
+    F: std::future::Future<Output = Result<T, Error>>,
+{
+    let mut attempts = 0;
+    loop {
+        match request().await {
+            Err(Error::Unavailable(_)) if attempts < 50 => {
+                attempts += 1;
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+async fn put(
+    member: &TestMember,
+    key: &str,
+    version: u32,
+    precondition: Precondition,
+) -> Result<(), Error> {
+    retry_unavailable(|| async {
+        let (result_tx, result_rx) = oneshot::channel();
+        member
+            .request_tx
+            .send(MetadataStoreRequest::Put {
+                key: key.into(),
+                value: VersionedValue::new(
+                    Version::from(version),
+                    Bytes::from(format!("{key}-{version}")),
+                ),
+                precondition: precondition.clone(),
+                result_tx,
+            })
+            .await
+            .expect("metadata store is running");
+        result_rx.await.expect("metadata store responds")
+    })
+    .await
+}
+
+async fn delete(member: &TestMember, key: &str, precondition: Precondition) -> Result<(), Error> {
+    retry_unavailable(|| async {
+        let (result_tx, result_rx) = oneshot::channel();
+        member
+            .request_tx
+            .send(MetadataStoreRequest::Delete {
+                key: key.into(),
+                precondition: precondition.clone(),
+                result_tx,
+            })
+            .await
+            .expect("metadata store is running");
+        result_rx.await.expect("metadata store responds")
+    })
+    .await
+}
+
+async fn transaction(member: &TestMember, operations: TransactionOperations) -> Result<(), Error> {
+    retry_unavailable(|| async {
+        let (result_tx, result_rx) = oneshot::channel();
+        member
+            .request_tx
+            .send(MetadataStoreRequest::Transaction {
+                operations: operations.clone(),
+                result_tx,
+            })
+            .await
+            .expect("metadata store is running");
+        result_rx.await.expect("metadata store responds")
+    })
+    .await
+}
+
+fn put_operation(key: &str, version: u32) -> TransactionOperation {
+    TransactionOperation::Put(VersionedValue::new(
+        Version::from(version),
+        Bytes::from(format!("{key}-{version}")),
+    ))
+}
+
+/// Returns the version of the value and checks that the value belongs to the version.
+async fn get_version(member: &TestMember, key: &str) -> Result<Option<u32>, Error> {
+    let value = retry_unavailable(|| async {
+        let (result_tx, result_rx) = oneshot::channel();
+        member
+            .request_tx
+            .send(MetadataStoreRequest::Get {
+                key: key.into(),
+                result_tx,
+            })
+            .await
+            .expect("metadata store is running");
+        result_rx.await.expect("metadata store responds")
+    })
+    .await?;
+
+    Ok(value.map(|value| {
+        let version = u32::from(value.version);
+        assert_eq!(value.value, Bytes::from(format!("{key}-{version}")));
+        version
+    }))
+}
+
+/// Tests that writes through any member are visible through all members and that
+/// preconditions are checked against the replicated state.
+#[test(tokio::test)]
+async fn replicated_metadata_store_operations() -> anyhow::Result<()> {
+    let env = TestCoreEnvBuilder::new_with_mock_network().build().await;
+    let network = TestNetwork::default();
+
+    let group = members([1, 2, 3]);
+    let nodes = [1, 2, 3]
+        .into_iter()
+        .map(|id| start_member(&env, &network, id, group.clone()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    env.tc
+        .run_in_scope("test", None, async move {
+            put(&nodes[0], "key", 1, Precondition::DoesNotExist).await?;
+            assert_eq!(get_version(&nodes[1], "key").await?, Some(1));
+            assert_eq!(get_version(&nodes[2], "key").await?, Some(1));
+
+            assert!(matches!(
+                put(&nodes[2], "key", 2, Precondition::DoesNotExist).await,
+                Err(Error::FailedPrecondition(_))
+            ));
+            assert!(matches!(
+                put(
+                    &nodes[1],
+                    "key",
+                    2,
+                    Precondition::MatchesVersion(Version::from(2))
+                )
+                .await,
+                Err(Error::FailedPrecondition(_))
+            ));
+
+            put(
+                &nodes[1],
+                "key",
+                2,
+                Precondition::MatchesVersion(Version::from(1)),
+            )
+            .await?;
+            assert_eq!(get_version(&nodes[0], "key").await?, Some(2));
+
+            delete(
+                &nodes[2],
+                "key",
+                Precondition::MatchesVersion(Version::from(2)),
+            )
+            .await?;
+            for node in &nodes {
+                assert_eq!(get_version(node, "key").await?, None);
+            }
+
+            Ok::<(), anyhow::Error>(())
+        })
+        .await?;
+
+    env.tc.shutdown_node("shutdown", 0).await;
+    Ok(())
+}
+
+/// Tests that transactions are applied atomically by all members and that failed preconditions
+/// are reported per key, also if the transaction was forwarded to the leader.
+#[test(tokio::test)]
+async fn replicated_transactions() -> anyhow::Result<()> {
+    let env = TestCoreEnvBuilder::new_with_mock_network().build().await;
+    let network = TestNetwork::default();
+
+    let group = members([1, 2, 3]);
+    let nodes = [1, 2, 3]
+        .into_iter()
+        .map(|id| start_member(&env, &network, id, group.clone()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    env.tc
+        .run_in_scope("test", None, async move {
+            put(&nodes[0], "a", 1, Precondition::None).await?;
+
+            for node in &nodes {
+                let result = transaction(
+                    node,
+                    vec![
+                        (
+                            "a".into(),
+                            Precondition::DoesNotExist,
+                            put_operation("a", 2),
+                        ),
+                        (
+                            "b".into(),
+                            Precondition::DoesNotExist,
+                            put_operation("b", 1),
+                        ),
+                        (
+                            "c".into(),
+                            Precondition::MatchesVersion(Version::from(1)),
+                            TransactionOperation::Delete,
+                        ),
+                    ],
+                )
+                .await;
+                let Err(Error::FailedPreconditions(failed)) = result else {
+                    panic!("expected failed preconditions, got {result:?}");
+                };
+                let failed_keys: Vec<_> = failed.into_iter().map(|failed| failed.key).collect();
+                assert_eq!(
+                    failed_keys,
+                    vec![ByteString::from("a"), ByteString::from("c")]
+                );
+                assert_eq!(get_version(node, "b").await?, None);
+            }
+
+            transaction(
+                &nodes[2],
+                vec![
+                    (
+                        "a".into(),
+                        Precondition::MatchesVersion(Version::from(1)),
+                        TransactionOperation::Delete,
+                    ),
+                    (
+                        "b".into(),
+                        Precondition::DoesNotExist,
+                        put_operation("b", 1),
+                    ),
+                ],
+            )
+            .await?;
+            for node in &nodes {
+                assert_eq!(get_version(node, "a").await?, None);
+                assert_eq!(get_version(node, "b").await?, Some(1));
+            }
+
+            Ok::<(), anyhow::Error>(())
+        })
+        .await?;
+
+    env.tc.shutdown_node("shutdown", 0).await;
+    Ok(())
+}
+
+/// Tests that watches on any member observe the replicated changes.
+#[test(tokio::test)]
+async fn watch_replicated_changes() -> anyhow::Result<()> {
+    let env = TestCoreEnvBuilder::new_with_mock_network().build().await;
+    let network = TestNetwork::default();
+
+    let group = members([1, 2, 3]);
+    let nodes = [1, 2, 3]
+        .into_iter()
+        .map(|id| start_member(&env, &network, id, group.clone()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    env.tc
+        .run_in_scope("test", None, async move {
+            put(&nodes[0], "nodes/1", 1, Precondition::None).await?;
+
+            let mut watches = Vec::new();
+            for node in &nodes {
+                let (result_tx, result_rx) = oneshot::channel();
+                node.request_tx
+                    .send(MetadataStoreRequest::Watch {
+                        prefix: "nodes/".into(),
+                        result_tx,
+                    })
+                    .await?;
+                watches.push(result_rx.await??.into_stream("nodes/".into()));
+            }
+
+            put(&nodes[1], "nodes/2", 1, Precondition::None).await?;
+            put(&nodes[2], "other", 1, Precondition::None).await?;
+            delete(&nodes[2], "nodes/1", Precondition::None).await?;
+
+            for watch in &mut watches {
+                // members which haven't applied the first put yet observe it as change
+                let mut events = Vec::new();
+                while events.last() != Some(&(ByteString::from("nodes/1"), None)) {
+                    let event = watch.next().await.expect("watch is open")?;
+                    events.push((event.key, event.value.map(|value| value.version)));
+                }
+                assert_eq!(
+                    events[events.len() - 2..],
+                    [
+                        (ByteString::from("nodes/2"), Some(Version::from(1))),
+                        (ByteString::from("nodes/1"), None)
+                    ]
+                );
+            }
+
+            let (result_tx, result_rx) = oneshot::channel();
+            nodes[2]
+                .request_tx
+                .send(MetadataStoreRequest::List {
+                    prefix: "nodes/".into(),
+                    result_tx,
+                })
+                .await?;
+            let listed: Vec<_> = result_rx
+                .await??
+                .into_iter()
+                .map(|(key, value)| (key, value.version))
+                .collect();
+            assert_eq!(
+                listed,
+                vec![(ByteString::from("nodes/2"), Version::from(1))]
+            );
+
+            Ok::<(), anyhow::Error>(())
+        })
+        .await?;
+
+    env.tc.shutdown_node("shutdown", 0).await;
+    Ok(())
+}
+
+/// Tests that the remaining members elect a new leader if the leader is isolated, and that the
+/// old leader catches up once it can reach the others again.
+#[test(tokio::test)]
+async fn leader_failover() -> anyhow::Result<()> {
+    let env = TestCoreEnvBuilder::new_with_mock_network().build().await;
+    let network = TestNetwork::default();
+
+    let group = members([1, 2, 3]);
+    let nodes = [1, 2, 3]
+        .into_iter()
+        .map(|id| start_member(&env, &network, id, group.clone()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    env.tc
+        .run_in_scope("test", None, async move {
+            put(&nodes[0], "key", 1, Precondition::None).await?;
+
+            let old_leader = network.leader().expect("a leader was elected");
+            let old_leader_index = usize::try_from(u32::from(old_leader))? - 1;
+            network.isolate(old_leader);
+
+            // the isolated leader cannot commit anything
+            let (result_tx, result_rx) = oneshot::channel();
+            nodes[old_leader_index]
+                .request_tx
+                .send(MetadataStoreRequest::Get {
+                    key: "key".into(),
+                    result_tx,
+                })
+                .await?;
+            assert!(matches!(result_rx.await?, Err(Error::Unavailable(_))));
+
+            let other = &nodes[(old_leader_index + 1) % 3];
+            put(
+                other,
+                "key",
+                2,
+                Precondition::MatchesVersion(Version::from(1)),
+            )
+            .await?;
+            assert_ne!(network.leader(), Some(old_leader));
+
+            network.heal(old_leader);
+            assert_eq!(get_version(&nodes[old_leader_index], "key").await?, Some(2));
+
+            Ok::<(), anyhow::Error>(())
+        })
+        .await?;
+
+    env.tc.shutdown_node("shutdown", 0).await;
+    Ok(())
+}
+
+/// Tests that members can be added to and removed from a running group.
+#[test(tokio::test)]
+async fn membership_changes() -> anyhow::Result<()> {
+    let env = TestCoreEnvBuilder::new_with_mock_network().build().await;
+    let network = TestNetwork::default();
+
+    let node_1 = start_member(&env, &network, 1, members([1]))?;
+    // new members learn the actual members from the log of the leader
+    let node_2 = start_member(&env, &network, 2, members([1]))?;
+    let node_3 = start_member(&env, &network, 3, members([1]))?;
+
+    env.tc
+        .run_in_scope("test", None, async move {
+            put(&node_1, "key", 1, Precondition::None).await?;
+
+            assert_eq!(
+                node_1.membership.add_member(member(2)).await?,
+                members([1, 2])
+            );
+            assert_eq!(get_version(&node_2, "key").await?, Some(1));
+
+            // members can be changed through any member
+            assert_eq!(
+                node_2.membership.add_member(member(3)).await?,
+                members([1, 2, 3])
+            );
+            assert_eq!(
+                node_3
+                    .membership
+                    .remove_member(PlainNodeId::from(1))
+                    .await?,
+                members([2, 3])
+            );
+
+            // the remaining members elect a leader among themselves if node 1 was the leader
+            put(
+                &node_3,
+                "key",
+                2,
+                Precondition::MatchesVersion(Version::from(1)),
+            )
+            .await?;
+            assert_eq!(get_version(&node_2, "key").await?, Some(2));
+            assert_ne!(network.leader(), Some(PlainNodeId::from(1)));
+
+            Ok::<(), anyhow::Error>(())
+        })
+        .await?;
+
+    env.tc.shutdown_node("shutdown", 0).await;
+    Ok(())
+}
+
+/// Tests that a member which misses entries that have been removed from the log of the leader
+/// catches up with a snapshot.
+#[test(tokio::test)]
+async fn lagging_member_installs_snapshot() -> anyhow::Result<()> {
+    let env = TestCoreEnvBuilder::new_with_mock_network().build().await;
+    let network = TestNetwork::default();
+
+    let group = members([1, 2, 3]);
+    let nodes = [1, 2, 3]
+        .into_iter()
+        .map(|id| start_member(&env, &network, id, group.clone()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    env.tc
+        .run_in_scope("test", None, async move {
+            put(&nodes[0], "key", 1, Precondition::None).await?;
+            put(&nodes[0], "removed", 1, Precondition::None).await?;
+
+            let leader = network.leader().expect("a leader was elected");
+            // the member after the leader lags behind
+            let lagging_index = usize::try_from(u32::from(leader))? % 3;
+            let lagging_id = PlainNodeId::from(u32::try_from(lagging_index)? + 1);
+            let lagging = &nodes[lagging_index];
+            let other = &nodes[(lagging_index + 1) % 3];
+
+            let (result_tx, result_rx) = oneshot::channel();
+            lagging
+                .request_tx
+                .send(MetadataStoreRequest::Watch {
+                    prefix: "".into(),
+                    result_tx,
+                })
+                .await?;
+            let mut watch = result_rx.await??.into_stream("".into());
+
+            network.isolate(lagging_id);
+            for version in 2..=(3 * LOG_COMPACTION_THRESHOLD as u32) {
+                put(
+                    other,
+                    "key",
+                    version,
+                    Precondition::MatchesVersion(Version::from(version - 1)),
+                )
+                .await?;
+            }
+            delete(other, "removed", Precondition::None).await?;
+            network.heal(lagging_id);
+
+            // the lagging member applies the changes of the snapshot
+            let last_version = Version::from(3 * LOG_COMPACTION_THRESHOLD as u32);
+            let mut current = HashMap::new();
+            while current.get(&ByteString::from("key")) != Some(&Some(last_version))
+                || current.get(&ByteString::from("removed")) != Some(&None)
+            {
+                let event = watch.next().await.expect("watch is open")?;
+                current.insert(event.key, event.value.map(|value| value.version));
+            }
+
+            // the caught up member can form a majority with the other member
+            network.isolate(leader);
+            put(
+                lagging,
+                "key",
+                3 * LOG_COMPACTION_THRESHOLD as u32 + 1,
+                Precondition::MatchesVersion(last_version),
+            )
+            .await?;
+
+            Ok::<(), anyhow::Error>(())
+        })
+        .await?;
+
+    env.tc.shutdown_node("shutdown", 0).await;
+    Ok(())
+}
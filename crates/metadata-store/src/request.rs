@@ -0,0 +1,161 @@
+// Copyright (c) 2024 - Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Requests which the grpc handler sends to the metadata store implementations.
+
+use crate::{
+    FailedPrecondition, MetadataStoreSnapshot, Precondition, TransactionOperations, VersionedValue,
+    WatchEvent,
+};
+use bytestring::ByteString;
+use restate_core::metadata_store::{broadcast_watch_stream, find_duplicate_key, WatchStream};
+use restate_types::errors::GenericError;
+use restate_types::Version;
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+pub type RequestSender = mpsc::Sender<MetadataStoreRequest>;
+pub type RequestReceiver = mpsc::Receiver<MetadataStoreRequest>;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Number of changes a watcher can fall behind before its watch fails.
+const WATCH_CHANGES_CAPACITY: usize = 1024;
+
+#[derive(Debug)]
+pub enum MetadataStoreRequest {
+    Get {
+        key: ByteString,
+        result_tx: oneshot::Sender<Result<Option<VersionedValue>>>,
+    },
+    GetVersion {
+        key: ByteString,
+        result_tx: oneshot::Sender<Result<Option<Version>>>,
+    },
+    Put {
+        key: ByteString,
+        value: VersionedValue,
+        precondition: Precondition,
+        result_tx: oneshot::Sender<Result<()>>,
+    },
+    Delete {
+        key: ByteString,
+        precondition: Precondition,
+        result_tx: oneshot::Sender<Result<()>>,
+    },
+    Transaction {
+        operations: TransactionOperations,
+        result_tx: oneshot::Sender<Result<()>>,
+    },
+    List {
+        prefix: ByteString,
+        result_tx: oneshot::Sender<Result<Vec<(ByteString, VersionedValue)>>>,
+    },
+    Watch {
+        prefix: ByteString,
+        result_tx: oneshot::Sender<Result<WatchSubscription>>,
+    },
+    Snapshot {
+        result_tx: oneshot::Sender<Result<MetadataStoreSnapshot>>,
+    },
+    Restore {
+        snapshot: MetadataStoreSnapshot,
+        result_tx: oneshot::Sender<Result<()>>,
+    },
+}
+
+/// The current key-value pairs of a watched prefix and the receiver of all later changes.
+#[derive(Debug)]
+pub struct WatchSubscription {
+    snapshot: Vec<(ByteString, VersionedValue)>,
+    changes: broadcast::Receiver<WatchEvent>,
+}
+
+impl WatchSubscription {
+    pub fn into_stream(self, prefix: ByteString) -> WatchStream {
+        broadcast_watch_stream(prefix, self.snapshot, self.changes)
+    }
+}
+
+/// Broadcasts the changes of the key-value pairs of a metadata store to its watchers.
+#[derive(Debug)]
+pub(crate) struct Watches {
+    changes: broadcast::Sender<WatchEvent>,
+}
+
+impl Default for Watches {
+    fn default() -> Self {
+        Self {
+            changes: broadcast::channel(WATCH_CHANGES_CAPACITY).0,
+        }
+    }
+}
+
+impl Watches {
+    pub fn notify(&self, key: ByteString, value: Option<VersionedValue>) {
+        // no receivers if nobody watches
+        let _ = self.changes.send(WatchEvent::new(key, value));
+    }
+
+    /// Subscribes to all changes after the given snapshot. The caller must ensure that no
+    /// change happens between taking the snapshot and subscribing.
+    pub fn subscribe(&self, snapshot: Vec<(ByteString, VersionedValue)>) -> WatchSubscription {
+        WatchSubscription {
+            snapshot,
+            changes: self.changes.subscribe(),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("storage error: {0}")]
+    Storage(#[from] rocksdb::Error),
+    #[error("failed precondition: {0}")]
+    FailedPrecondition(String),
+    #[error("failed preconditions: {0:?}")]
+    FailedPreconditions(Vec<FailedPrecondition>),
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("codec error: {0}")]
+    Codec(GenericError),
+    #[error("unavailable: {0}")]
+    Unavailable(String),
+    #[error("unimplemented: {0}")]
+    Unimplemented(String),
+    #[error("internal error: {0}")]
+    Internal(String),
+}
+
+impl Error {
+    pub(crate) fn kv_pair_exists() -> Self {
+        Error::FailedPrecondition("key-value pair already exists".to_owned())
+    }
+
+    pub(crate) fn not_empty() -> Self {
+        Error::FailedPrecondition("metadata store is not empty".to_owned())
+    }
+
+    pub(crate) fn version_mismatch(expected: Version, actual: Option<Version>) -> Self {
+        Error::FailedPrecondition(format!(
+            "Expected version '{}' but found version '{:?}'",
+            expected, actual
+        ))
+    }
+}
+
+/// Fails if a key occurs more than once in the operations of a transaction.
+pub(crate) fn check_transaction(operations: &TransactionOperations) -> Result<()> {
+    match find_duplicate_key(operations) {
+        Some(key) => Err(Error::InvalidArgument(format!(
+            "key '{key}' occurs more than once"
+        ))),
+        None => Ok(()),
+    }
+}
@@ -15,8 +15,8 @@ use std::time::Instant;
 use tokio::sync::mpsc;
 use tracing::instrument;
 
-use restate_core::metadata;
 use restate_core::network::NetworkSendError;
+use restate_core::{metadata, Metadata};
 use restate_node_protocol::codec::serialize_message;
 use restate_node_protocol::codec::Targeted;
 use restate_node_protocol::codec::WireSerde;
@@ -29,6 +29,24 @@ use restate_types::GenerationalNodeId;
 use crate::metric_definitions::CONNECTION_SEND_DURATION;
 use crate::metric_definitions::MESSAGE_SENT;
 
+/// Creates the header of a message, which announces the metadata versions of this node to the
+/// peer.
+pub(crate) fn metadata_header(metadata: &Metadata) -> Header {
+    Header::from_metadata_versions(|metadata_kind| metadata.version(metadata_kind))
+}
+
+/// Tells the metadata manager about newer metadata versions which the peer has announced in the
+/// header of a message.
+pub(crate) fn observe_metadata_versions(
+    metadata: &Metadata,
+    header: &Header,
+    peer: Option<GenerationalNodeId>,
+) {
+    for (metadata_kind, version) in header.metadata_versions() {
+        metadata.notify_observed_version(metadata_kind, version, peer);
+    }
+}
+
 /// A single streaming connection with a channel to the peer. A connection can be
 /// opened by either ends of the connection and has no direction. Any connection
 /// can be used to send or receive from a peer.
@@ -121,7 +139,7 @@ impl ConnectionSender {
         M: WireSerde + Targeted,
     {
         let send_start = Instant::now();
-        let header = Header::new(metadata().nodes_config_version());
+        let header = metadata_header(&metadata());
         let body = serialize_message(message, self.protocol_version)?;
         let res = self
             .connection
@@ -24,14 +24,14 @@ use tracing::{debug, info, warn, Instrument, Span};
 
 use restate_core::metadata;
 use restate_core::{cancellation_watcher, current_task_id, task_center, TaskId, TaskKind};
-use restate_grpc_util::create_grpc_channel_from_advertised_address;
+use restate_grpc_util::{create_grpc_channel_from_advertised_address, PeerCertificates, TlsConfig};
 use restate_node_protocol::node::message::{self, ConnectionControl};
-use restate_node_protocol::node::{Header, Hello, Message, Welcome};
+use restate_node_protocol::node::{Hello, Message, Welcome};
 use restate_node_services::node_svc::node_svc_client::NodeSvcClient;
 use restate_types::net::AdvertisedAddress;
 use restate_types::{GenerationalNodeId, NodeId, PlainNodeId};
 
-use super::connection::{Connection, ConnectionSender};
+use super::connection::{metadata_header, observe_metadata_versions, Connection, ConnectionSender};
 use super::handshake::{negotiate_protocol_version, wait_for_hello, wait_for_welcome};
 use crate::error::{NetworkError, ProtocolError};
 use crate::metric_definitions::{
@@ -88,9 +88,19 @@ impl Default for ConnectionManagerInner {
 #[derive(Clone, Default)]
 pub struct ConnectionManager {
     inner: Arc<Mutex<ConnectionManagerInner>>,
+    tls: Option<TlsConfig>,
 }
 
 impl ConnectionManager {
+    /// Creates a connection manager which establishes outgoing connections with TLS if a TLS
+    /// configuration is given.
+    pub fn new(tls: Option<TlsConfig>) -> Self {
+        Self {
+            inner: Default::default(),
+            tls,
+        }
+    }
+
     /// Updates the message router. Note that this only impacts new connections.
     /// In general, this should be called once on application start after
     /// initializing all message handlers.
@@ -98,10 +108,13 @@ impl ConnectionManager {
         self.inner.lock().unwrap().router = router;
     }
 
-    /// Accept a new incoming connection stream and register a network reactor task for it.
+    /// Accept a new incoming connection stream and register a network reactor task for it. If
+    /// the connection has been established with TLS, the peer's certificate must be valid for
+    /// the name of the node it claims to be.
     pub async fn accept_incoming_connection<S>(
         &self,
         mut incoming: S,
+        peer_certificates: Option<PeerCertificates>,
     ) -> Result<BoxStream<'static, Result<Message, tonic::Status>>, NetworkError>
     where
         S: Stream<Item = Result<Message, ProtocolError>> + Unpin + Send + 'static,
@@ -170,8 +183,9 @@ impl ConnectionManager {
             selected_protocol_version
         );
 
-        // If nodeId is unrecognized and peer is at higher nodes configuration version,
-        // TODO: issue a sync to the higher version
+        // If nodeId is unrecognized and peer is at higher nodes configuration version, we sync
+        // the higher version from the metadata store since we can't reach the peer. The peer
+        // will retry connecting.
         let peer_is_in_the_future = header
             .my_nodes_config_version
             .as_ref()
@@ -186,24 +200,26 @@ impl ConnectionManager {
                     header.my_nodes_config_version,
                     metadata.nodes_config_version()
                 );
-                // TODO: notify metadata about higher version.
-                // let _ = self
-                //     .metadata
-                //     .notify_observed_version(
-                //         MetadataKind::NodesConfiguration,
-                //         header.my_nodes_config_version.unwrap().into(),
-                //     )
-                //     .await?;
+                observe_metadata_versions(&metadata, &header, None);
             }
             return Err(NetworkError::UnknownNode(e));
         }
 
+        if let Some(peer_certificates) = peer_certificates {
+            let nodes_config = metadata.nodes_config();
+            let peer_name = &nodes_config.find_node_by_id(peer_node_id)?.name;
+            if !peer_certificates.is_valid_for(peer_name) {
+                return Err(NetworkError::UntrustedPeer(peer_node_id, peer_name.clone()));
+            }
+        }
+
+        observe_metadata_versions(&metadata, &header, Some(peer_node_id));
+
         let (tx, rx) = mpsc::channel(SEND_QUEUE_SIZE);
         // Enqueue the welcome message
         let welcome = Welcome::new(metadata.my_node_id(), selected_protocol_version);
 
-        let node_config_version = metadata.nodes_config_version();
-        let welcome = Message::new(Header::new(node_config_version), welcome);
+        let welcome = Message::new(metadata_header(&metadata), welcome);
 
         tx.try_send(welcome)
             .expect("channel accept Welcome message");
@@ -252,18 +268,20 @@ impl ConnectionManager {
     }
 
     async fn connect(&self, node_id: GenerationalNodeId) -> Result<Arc<Connection>, NetworkError> {
-        let address = metadata()
-            .nodes_config()
-            .find_node_by_id(node_id)?
-            .address
-            .clone();
+        let (address, name) = {
+            let nodes_config = metadata().nodes_config();
+            let node_config = nodes_config.find_node_by_id(node_id)?;
+            (node_config.address.clone(), node_config.name.clone())
+        };
 
         info!("Attempting to connect to node {} at {}", node_id, address);
         // Do we have a channel in cache for this address?
         let channel = {
             let mut guard = self.inner.lock().unwrap();
             if let hash_map::Entry::Vacant(entry) = guard.channel_cache.entry(address.clone()) {
-                let channel = create_grpc_channel_from_advertised_address(address)
+                // the peer's certificate must be valid for the name of the node
+                let tls = self.tls.as_ref().map(|tls| tls.client_config(Some(&name)));
+                let channel = create_grpc_channel_from_advertised_address(address, tls)
                     .map_err(|e| NetworkError::BadNodeAddress(node_id.into(), e))?;
                 entry.insert(channel.clone());
                 channel
@@ -311,7 +329,7 @@ impl ConnectionManager {
         let hello = Hello::new(metadata.my_node_id(), cluster_name.to_owned());
 
         // perform handshake.
-        let hello = Message::new(Header::new(nodes_config.version()), hello);
+        let hello = Message::new(metadata_header(&metadata), hello);
 
         // Prime the channel with the hello message before connecting.
         tx.send(hello).await.expect("Channel accept hello message");
@@ -324,7 +342,7 @@ impl ConnectionManager {
 
         let mut transformed = incoming.map(|x| x.map_err(ProtocolError::from));
         // finish the handshake
-        let (_header, welcome) = wait_for_welcome(&mut transformed).await?;
+        let (header, welcome) = wait_for_welcome(&mut transformed).await?;
         let protocol_version = welcome.protocol_version();
 
         if !protocol_version.is_supported() {
@@ -349,14 +367,13 @@ impl ConnectionManager {
             .into());
         }
 
+        let peer_node_id = peer_node_id
+            .as_generational()
+            .expect("must be generational id");
+        observe_metadata_versions(&metadata, &header, Some(peer_node_id));
+
         OUTGOING_CONNECTION.increment(1);
-        let connection = Connection::new(
-            peer_node_id
-                .as_generational()
-                .expect("must be generational id"),
-            protocol_version,
-            tx,
-        );
+        let connection = Connection::new(peer_node_id, protocol_version, tx);
 
         self.start_connection_reactor(connection, transformed)
     }
@@ -476,8 +493,8 @@ where
         MESSAGE_RECEIVED.increment(1);
         let processing_started = Instant::now();
         // header is optional on non-hello messages.
-        if let Some(_header) = msg.header {
-            // todo: if header contains newer config or metadata versions, notify metadata().
+        if let Some(header) = msg.header {
+            observe_metadata_versions(&metadata(), &header, Some(connection.peer));
         };
 
         // body are not allowed to be empty.
@@ -605,7 +622,7 @@ mod tests {
     use googletest::prelude::*;
 
     use restate_core::TestCoreEnv;
-    use restate_node_protocol::node::message;
+    use restate_node_protocol::node::{message, Header};
     use restate_node_protocol::{
         common::ProtocolVersion, CURRENT_PROTOCOL_VERSION, MIN_SUPPORTED_PROTOCOL_VERSION,
     };
@@ -634,7 +651,7 @@ mod tests {
 
                 let incoming = ReceiverStream::new(rx);
                 let mut output_stream = connections
-                    .accept_incoming_connection(incoming)
+                    .accept_incoming_connection(incoming, None)
                     .await
                     .expect("handshake");
                 let msg = output_stream
@@ -663,7 +680,7 @@ mod tests {
 
                 let start = tokio::time::Instant::now();
                 let incoming = ReceiverStream::new(rx);
-                let resp = connections.accept_incoming_connection(incoming).await;
+                let resp = connections.accept_incoming_connection(incoming, None).await;
                 assert!(resp.is_err());
                 assert!(matches!(
                     resp,
@@ -702,7 +719,7 @@ mod tests {
 
                 let connections = ConnectionManager::default();
                 let incoming = ReceiverStream::new(rx);
-                let resp = connections.accept_incoming_connection(incoming).await;
+                let resp = connections.accept_incoming_connection(incoming, None).await;
                 assert!(resp.is_err());
                 assert!(matches!(
                     resp,
@@ -726,7 +743,7 @@ mod tests {
                 let connections = ConnectionManager::default();
                 let incoming = ReceiverStream::new(rx);
                 let err = connections
-                    .accept_incoming_connection(incoming)
+                    .accept_incoming_connection(incoming, None)
                     .await
                     .err()
                     .unwrap();
@@ -768,7 +785,7 @@ mod tests {
 
                 let incoming = ReceiverStream::new(rx);
                 let err = connections
-                    .accept_incoming_connection(incoming)
+                    .accept_incoming_connection(incoming, None)
                     .await
                     .err()
                     .unwrap();
@@ -798,7 +815,7 @@ mod tests {
 
                 let incoming = ReceiverStream::new(rx);
                 let err = connections
-                    .accept_incoming_connection(incoming)
+                    .accept_incoming_connection(incoming, None)
                     .await
                     .err()
                     .unwrap();
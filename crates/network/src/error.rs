@@ -11,7 +11,7 @@
 use restate_core::ShutdownError;
 use restate_node_protocol::common::MIN_SUPPORTED_PROTOCOL_VERSION;
 use restate_types::nodes_config::NodesConfigError;
-use restate_types::NodeId;
+use restate_types::{GenerationalNodeId, NodeId};
 
 #[derive(Debug, thiserror::Error)]
 pub enum NetworkError {
@@ -20,7 +20,7 @@ pub enum NetworkError {
     #[error("operation aborted, node is shutting down")]
     Shutdown(#[from] ShutdownError),
     #[error("node {0} address is bad: {1}")]
-    BadNodeAddress(NodeId, http::Error),
+    BadNodeAddress(NodeId, tonic::transport::Error),
     #[error("timeout: {0}")]
     Timeout(&'static str),
     #[error("protocol error: {0}")]
@@ -31,6 +31,8 @@ pub enum NetworkError {
     OldPeerGeneration(String),
     #[error("peer is not connected")]
     ConnectionClosed,
+    #[error("certificate of peer {0} is not valid for its node name '{1}'")]
+    UntrustedPeer(GenerationalNodeId, String),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -75,6 +77,7 @@ impl From<NetworkError> for tonic::Status {
             NetworkError::Timeout(e) => tonic::Status::deadline_exceeded(e),
             NetworkError::OldPeerGeneration(e) => tonic::Status::already_exists(e),
             NetworkError::ConnectError(s) => s,
+            NetworkError::UntrustedPeer(..) => tonic::Status::permission_denied(value.to_string()),
             e => tonic::Status::internal(e.to_string()),
         }
     }
@@ -15,6 +15,7 @@ use tracing::{info, instrument, trace};
 
 use restate_core::metadata;
 use restate_core::network::{NetworkSendError, NetworkSender};
+use restate_grpc_util::TlsConfig;
 use restate_node_protocol::codec::{Targeted, WireSerde};
 use restate_types::NodeId;
 
@@ -32,6 +33,14 @@ pub struct Networking {
 }
 
 impl Networking {
+    /// Creates the networking whose connections to other nodes are established with TLS if a
+    /// TLS configuration is given.
+    pub fn new(tls: Option<TlsConfig>) -> Self {
+        Self {
+            connections: ConnectionManager::new(tls),
+        }
+    }
+
     pub fn connection_manager(&self) -> ConnectionManager {
         self.connections.clone()
     }
@@ -0,0 +1,65 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Heartbeats with which the cluster controller detects failed nodes. Every node answers a
+//! [`Heartbeat`] with a [`HeartbeatResponse`] that echoes the heartbeat's sequence number.
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+
+use crate::codec::{decode_default, encode_default, Targeted, WireSerde};
+use crate::common::{ProtocolVersion, TargetName};
+use crate::CodecError;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Heartbeat {
+    pub seq: u64,
+}
+
+impl Targeted for Heartbeat {
+    const TARGET: TargetName = TargetName::Heartbeat;
+
+    fn kind(&self) -> &'static str {
+        "Heartbeat"
+    }
+}
+
+impl WireSerde for Heartbeat {
+    fn encode(&self, protocol_version: ProtocolVersion) -> Result<Bytes, CodecError> {
+        encode_default(self, protocol_version)
+    }
+
+    fn decode(payload: Bytes, protocol_version: ProtocolVersion) -> Result<Self, CodecError> {
+        decode_default(payload, protocol_version)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatResponse {
+    pub seq: u64,
+}
+
+impl Targeted for HeartbeatResponse {
+    const TARGET: TargetName = TargetName::ClusterController;
+
+    fn kind(&self) -> &'static str {
+        "HeartbeatResponse"
+    }
+}
+
+impl WireSerde for HeartbeatResponse {
+    fn encode(&self, protocol_version: ProtocolVersion) -> Result<Bytes, CodecError> {
+        encode_default(self, protocol_version)
+    }
+
+    fn decode(payload: Bytes, protocol_version: ProtocolVersion) -> Result<Self, CodecError> {
+        decode_default(payload, protocol_version)
+    }
+}
@@ -28,6 +28,7 @@ use crate::CodecError;
 )]
 pub enum IngressMessage {
     InvocationResponse(InvocationResponse),
+    AttachResponse(AttachResponse),
 }
 
 impl Targeted for IngressMessage {
@@ -55,3 +56,11 @@ pub struct InvocationResponse {
     pub id: InvocationId,
     pub response: ResponseResult,
 }
+
+/// Response to a request which attached to an invocation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachResponse {
+    pub request_id: u64,
+    pub id: InvocationId,
+    pub response: ResponseResult,
+}
@@ -10,10 +10,13 @@
 
 pub mod codec;
 pub mod common;
+pub mod heartbeat;
 mod error;
 pub mod ingress;
+pub mod log_server;
 pub mod metadata;
 pub mod node;
+pub mod partition_processor_manager;
 
 // re-exports for convenience
 pub use common::CURRENT_PROTOCOL_VERSION;
@@ -0,0 +1,215 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Messages exchanged between replicated loglets and the log servers that store their records.
+//!
+//! Every request carries a `request_id` that is chosen by the sender and echoed back in the
+//! corresponding response. Offsets are loglet offsets, offset 0 is the invalid offset.
+
+use bytes::Bytes;
+use restate_types::logs::SealReason;
+use serde::{Deserialize, Serialize};
+
+use crate::codec::{decode_default, encode_default, Targeted, WireSerde};
+use crate::common::{ProtocolVersion, TargetName};
+use crate::CodecError;
+
+#[derive(
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+    derive_more::From,
+    strum_macros::EnumIs,
+    strum_macros::IntoStaticStr,
+)]
+pub enum LogServerRequest {
+    Store(Store),
+    Seal(Seal),
+    GetTailInfo(GetTailInfo),
+    GetRecords(GetRecords),
+    Trim(Trim),
+}
+
+impl LogServerRequest {
+    pub fn request_id(&self) -> u64 {
+        match self {
+            LogServerRequest::Store(msg) => msg.request_id,
+            LogServerRequest::Seal(msg) => msg.request_id,
+            LogServerRequest::GetTailInfo(msg) => msg.request_id,
+            LogServerRequest::GetRecords(msg) => msg.request_id,
+            LogServerRequest::Trim(msg) => msg.request_id,
+        }
+    }
+}
+
+impl Targeted for LogServerRequest {
+    const TARGET: TargetName = TargetName::LogServer;
+
+    fn kind(&self) -> &'static str {
+        self.into()
+    }
+}
+
+impl WireSerde for LogServerRequest {
+    fn encode(&self, protocol_version: ProtocolVersion) -> Result<Bytes, CodecError> {
+        encode_default(self, protocol_version)
+    }
+
+    fn decode(payload: Bytes, protocol_version: ProtocolVersion) -> Result<Self, CodecError> {
+        decode_default(payload, protocol_version)
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+    derive_more::From,
+    strum_macros::EnumIs,
+    strum_macros::IntoStaticStr,
+)]
+pub enum LogServerResponse {
+    Stored(Stored),
+    Sealed(Sealed),
+    TailInfo(TailInfo),
+    Records(Records),
+    Trimmed(Trimmed),
+}
+
+impl LogServerResponse {
+    pub fn request_id(&self) -> u64 {
+        match self {
+            LogServerResponse::Stored(msg) => msg.request_id,
+            LogServerResponse::Sealed(msg) => msg.request_id,
+            LogServerResponse::TailInfo(msg) => msg.request_id,
+            LogServerResponse::Records(msg) => msg.request_id,
+            LogServerResponse::Trimmed(msg) => msg.request_id,
+        }
+    }
+}
+
+impl Targeted for LogServerResponse {
+    const TARGET: TargetName = TargetName::LogServerClient;
+
+    fn kind(&self) -> &'static str {
+        self.into()
+    }
+}
+
+impl WireSerde for LogServerResponse {
+    fn encode(&self, protocol_version: ProtocolVersion) -> Result<Bytes, CodecError> {
+        encode_default(self, protocol_version)
+    }
+
+    fn decode(payload: Bytes, protocol_version: ProtocolVersion) -> Result<Self, CodecError> {
+        decode_default(payload, protocol_version)
+    }
+}
+
+/// The outcome of a request that modifies the copy of a loglet on a log server.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Status {
+    Ok,
+    /// The loglet is sealed on the log server, the request was rejected.
+    Sealed(SealReason),
+    /// The log server failed to process the request.
+    Failed(String),
+}
+
+/// Stores records at consecutive offsets starting from `first_offset`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Store {
+    pub request_id: u64,
+    pub loglet_id: u64,
+    pub first_offset: u64,
+    pub payloads: Vec<Bytes>,
+    /// The highest offset that the sender knows to be committed.
+    pub known_global_tail: u64,
+    /// Repair stores re-replicate committed records and are accepted on sealed loglets.
+    pub repair: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stored {
+    pub request_id: u64,
+    pub status: Status,
+    pub local_tail: u64,
+}
+
+/// Seals the copy of the loglet on the log server. Sealed copies reject all non-repair stores.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Seal {
+    pub request_id: u64,
+    pub loglet_id: u64,
+    pub reason: SealReason,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sealed {
+    pub request_id: u64,
+    pub status: Status,
+    /// The highest offset that was stored on the log server before it was sealed.
+    pub local_tail: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetTailInfo {
+    pub request_id: u64,
+    pub loglet_id: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TailInfo {
+    pub request_id: u64,
+    /// The highest offset stored on the log server.
+    pub local_tail: u64,
+    /// The highest offset that the log server knows to be committed.
+    pub known_global_tail: u64,
+    pub trim_point: u64,
+    pub seal: Option<SealReason>,
+}
+
+/// Reads the records stored in the inclusive offset range `from_offset..=to_offset`, up to
+/// `max_records` records.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetRecords {
+    pub request_id: u64,
+    pub loglet_id: u64,
+    pub from_offset: u64,
+    pub to_offset: u64,
+    pub max_records: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Records {
+    pub request_id: u64,
+    pub status: Status,
+    /// The stored records in offset order. Offsets that are missing on the log server are
+    /// skipped.
+    pub records: Vec<(u64, Bytes)>,
+    pub trim_point: u64,
+}
+
+/// Trims the copy of the loglet on the log server up to and including `trim_point`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trim {
+    pub request_id: u64,
+    pub loglet_id: u64,
+    pub trim_point: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trimmed {
+    pub request_id: u64,
+    pub status: Status,
+    pub trim_point: u64,
+}
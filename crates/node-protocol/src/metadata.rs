@@ -10,8 +10,11 @@
 
 use bytes::Bytes;
 use enum_map::Enum;
+use restate_types::logs::metadata::Logs;
 use restate_types::nodes_config::NodesConfiguration;
-use restate_types::partition_table::FixedPartitionTable;
+use restate_types::partition_table::PartitionTable;
+use restate_types::schema::SchemaInformation;
+use restate_types::{Version, Versioned};
 use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
@@ -76,7 +79,9 @@ pub enum MetadataKind {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MetadataContainer {
     NodesConfiguration(NodesConfiguration),
-    PartitionTable(FixedPartitionTable),
+    PartitionTable(PartitionTable),
+    Logs(Logs),
+    Schema(SchemaInformation),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,6 +100,17 @@ impl MetadataContainer {
         match self {
             MetadataContainer::NodesConfiguration(_) => MetadataKind::NodesConfiguration,
             MetadataContainer::PartitionTable(_) => MetadataKind::PartitionTable,
+            MetadataContainer::Logs(_) => MetadataKind::Logs,
+            MetadataContainer::Schema(_) => MetadataKind::Schema,
+        }
+    }
+
+    pub fn version(&self) -> Version {
+        match self {
+            MetadataContainer::NodesConfiguration(c) => c.version(),
+            MetadataContainer::PartitionTable(p) => p.version(),
+            MetadataContainer::Logs(l) => l.version(),
+            MetadataContainer::Schema(s) => s.version(),
         }
     }
 }
@@ -105,8 +121,20 @@ impl From<NodesConfiguration> for MetadataContainer {
     }
 }
 
-impl From<FixedPartitionTable> for MetadataContainer {
-    fn from(value: FixedPartitionTable) -> Self {
+impl From<PartitionTable> for MetadataContainer {
+    fn from(value: PartitionTable) -> Self {
         MetadataContainer::PartitionTable(value)
     }
 }
+
+impl From<Logs> for MetadataContainer {
+    fn from(value: Logs) -> Self {
+        MetadataContainer::Logs(value)
+    }
+}
+
+impl From<SchemaInformation> for MetadataContainer {
+    fn from(value: SchemaInformation) -> Self {
+        MetadataContainer::Schema(value)
+    }
+}
@@ -9,8 +9,10 @@
 // by the Apache License, Version 2.0.
 
 use restate_types::GenerationalNodeId;
+use strum::IntoEnumIterator;
 
 use crate::common::{ProtocolVersion, CURRENT_PROTOCOL_VERSION, MIN_SUPPORTED_PROTOCOL_VERSION};
+use crate::metadata::MetadataKind;
 
 use self::message::{BinaryMessage, ConnectionControl, Signal};
 
@@ -34,8 +36,50 @@ impl Header {
     pub fn new(nodes_config_version: restate_types::Version) -> Self {
         Self {
             my_nodes_config_version: Some(nodes_config_version.into()),
+            ..Default::default()
         }
     }
+
+    /// Creates a header which announces the version of every metadata kind to the peer.
+    pub fn from_metadata_versions(
+        metadata_version: impl Fn(MetadataKind) -> restate_types::Version,
+    ) -> Self {
+        let mut header = Self::default();
+        for metadata_kind in MetadataKind::iter() {
+            let version = Some(metadata_version(metadata_kind).into());
+            match metadata_kind {
+                MetadataKind::NodesConfiguration => header.my_nodes_config_version = version,
+                MetadataKind::Schema => header.my_schema_version = version,
+                MetadataKind::PartitionTable => header.my_partition_table_version = version,
+                MetadataKind::Logs => header.my_logs_version = version,
+            }
+        }
+        header
+    }
+
+    /// The metadata versions which the peer has announced.
+    pub fn metadata_versions(
+        &self,
+    ) -> impl Iterator<Item = (MetadataKind, restate_types::Version)> + '_ {
+        [
+            (
+                MetadataKind::NodesConfiguration,
+                &self.my_nodes_config_version,
+            ),
+            (MetadataKind::Schema, &self.my_schema_version),
+            (
+                MetadataKind::PartitionTable,
+                &self.my_partition_table_version,
+            ),
+            (MetadataKind::Logs, &self.my_logs_version),
+        ]
+        .into_iter()
+        .filter_map(|(metadata_kind, version)| {
+            version
+                .clone()
+                .map(|version| (metadata_kind, version.into()))
+        })
+    }
 }
 
 impl Welcome {
@@ -0,0 +1,146 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Messages with which the cluster controller tells the workers which partition processors to
+//! run and with which the workers report the status of their partition processors.
+
+use std::collections::BTreeMap;
+
+use bytes::Bytes;
+use restate_types::identifiers::PartitionId;
+use restate_types::logs::Lsn;
+use restate_types::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::codec::{decode_default, encode_default, Targeted, WireSerde};
+use crate::common::{ProtocolVersion, TargetName};
+use crate::CodecError;
+
+/// Instructs a worker to start or stop partition processors. The commands are derived from the
+/// partition placement with the given version. Since messages can be re-ordered, workers ignore
+/// messages which are derived from an older placement than the last one they have applied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlProcessors {
+    pub placement_version: Version,
+    /// The version of the partition table which contains the partitions of the commands. Workers
+    /// wait for it before starting partition processors.
+    pub partition_table_version: Version,
+    pub commands: Vec<ControlProcessor>,
+}
+
+impl Targeted for ControlProcessors {
+    const TARGET: TargetName = TargetName::PartitionProcessorManager;
+
+    fn kind(&self) -> &'static str {
+        "ControlProcessors"
+    }
+}
+
+impl WireSerde for ControlProcessors {
+    fn encode(&self, protocol_version: ProtocolVersion) -> Result<Bytes, CodecError> {
+        encode_default(self, protocol_version)
+    }
+
+    fn decode(payload: Bytes, protocol_version: ProtocolVersion) -> Result<Self, CodecError> {
+        decode_default(payload, protocol_version)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlProcessor {
+    pub partition_id: PartitionId,
+    pub command: ProcessorCommand,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum_macros::Display)]
+pub enum ProcessorCommand {
+    /// Start the partition processor if it is not running yet.
+    Start,
+    /// Stop the partition processor if it is running.
+    Stop,
+}
+
+/// Asks a worker for the status of its partition processors. The worker answers with
+/// [`PartitionProcessorsStatus`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetPartitionProcessorsStatus {
+    /// The lowest lsn which all replicas of a partition have applied, as far as the cluster
+    /// controller knows. Partitions of which some replica hasn't reported an applied lsn are
+    /// missing. The leaders of the partitions trim their logs only up to it.
+    #[serde(default)]
+    pub replicas_applied_lsns: BTreeMap<PartitionId, Lsn>,
+}
+
+impl Targeted for GetPartitionProcessorsStatus {
+    const TARGET: TargetName = TargetName::PartitionProcessorsStatus;
+
+    fn kind(&self) -> &'static str {
+        "GetPartitionProcessorsStatus"
+    }
+}
+
+impl WireSerde for GetPartitionProcessorsStatus {
+    fn encode(&self, protocol_version: ProtocolVersion) -> Result<Bytes, CodecError> {
+        encode_default(self, protocol_version)
+    }
+
+    fn decode(payload: Bytes, protocol_version: ProtocolVersion) -> Result<Self, CodecError> {
+        decode_default(payload, protocol_version)
+    }
+}
+
+/// The status of the partition processors which are running on a worker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartitionProcessorsStatus {
+    pub processors: Vec<PartitionProcessorStatus>,
+}
+
+impl Targeted for PartitionProcessorsStatus {
+    const TARGET: TargetName = TargetName::PartitionProcessorsStatusClient;
+
+    fn kind(&self) -> &'static str {
+        "PartitionProcessorsStatus"
+    }
+}
+
+impl WireSerde for PartitionProcessorsStatus {
+    fn encode(&self, protocol_version: ProtocolVersion) -> Result<Bytes, CodecError> {
+        encode_default(self, protocol_version)
+    }
+
+    fn decode(payload: Bytes, protocol_version: ProtocolVersion) -> Result<Self, CodecError> {
+        decode_default(payload, protocol_version)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartitionProcessorStatus {
+    pub partition_id: PartitionId,
+    pub run_mode: RunMode,
+    /// The lsn of the last record which the processor has applied, `None` if it has not applied
+    /// any record yet.
+    pub last_applied_lsn: Option<Lsn>,
+}
+
+impl PartitionProcessorStatus {
+    pub fn new(partition_id: PartitionId) -> Self {
+        Self {
+            partition_id,
+            run_mode: RunMode::Follower,
+            last_applied_lsn: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, strum_macros::Display)]
+pub enum RunMode {
+    Leader,
+    Follower,
+}
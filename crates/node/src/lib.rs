@@ -14,7 +14,8 @@ mod roles;
 
 pub use options::{Options, OptionsBuilder as NodeOptionsBuilder};
 pub use restate_admin::OptionsBuilder as AdminOptionsBuilder;
-use restate_bifrost::BifrostService;
+use restate_bifrost::{BifrostService, LogServer};
+use restate_cluster_controller::HeartbeatResponder;
 use restate_core::network::MessageRouterBuilder;
 use restate_core::options::CommonOptions;
 pub use restate_meta::OptionsBuilder as MetaOptionsBuilder;
@@ -29,16 +30,16 @@ use tracing::{error, info, trace};
 
 use restate_core::{spawn_metadata_manager, MetadataManager};
 use restate_core::{task_center, TaskKind};
-use restate_metadata_store::local::LocalMetadataStoreService;
+use restate_grpc_util::TlsConfig;
 use restate_metadata_store::{MetadataStoreClient, Operation, ReadModifyWriteError};
 use restate_types::metadata_store::keys::{NODES_CONFIG_KEY, PARTITION_TABLE_KEY};
 use restate_types::nodes_config::{NodeConfig, NodesConfiguration, Role};
-use restate_types::partition_table::FixedPartitionTable;
+use restate_types::partition_table::PartitionTable;
 use restate_types::retries::RetryPolicy;
 use restate_types::Version;
 
-use crate::network_server::{AdminDependencies, NetworkServer, WorkerDependencies};
-use crate::roles::{AdminRole, WorkerRole};
+use crate::network_server::NetworkServer;
+use crate::roles::RoleManager;
 
 #[derive(Debug, thiserror::Error, CodedError)]
 pub enum Error {
@@ -55,34 +56,34 @@ pub enum BuildError {
         #[code]
         roles::WorkerRoleBuildError,
     ),
-    #[error("building cluster controller failed: {0}")]
-    ClusterController(
-        #[from]
-        #[code]
-        roles::AdminRoleBuildError,
-    ),
     #[error("building metadata store failed: {0}")]
     MetadataStore(
         #[from]
         #[code]
-        restate_metadata_store::local::BuildError,
+        roles::MetadataStoreRoleBuildError,
     ),
+    #[error("building log server failed: {0}")]
+    #[code(unknown)]
+    LogServer(#[from] restate_bifrost::ProviderError),
     #[error("node neither runs cluster controller nor its address has been configured")]
     #[code(unknown)]
     UnknownClusterController,
     #[error("cluster bootstrap failed: {0}")]
     #[code(unknown)]
     Bootstrap(String),
+    #[error("loading tls configuration failed: {0}")]
+    #[code(unknown)]
+    Tls(#[from] restate_grpc_util::TlsError),
 }
 
 pub struct Node {
     common_opts: CommonOptions,
     options: Options,
     metadata_manager: MetadataManager<Networking>,
+    metadata_store_client: MetadataStoreClient,
     bifrost: BifrostService,
-    metadata_store_role: Option<LocalMetadataStoreService>,
-    admin_role: Option<AdminRole>,
-    worker_role: Option<WorkerRole>,
+    log_server: Option<LogServer<Networking>>,
+    role_manager: RoleManager,
     server: NetworkServer,
 }
 
@@ -103,91 +104,90 @@ impl Node {
             }
         }
 
-        let metadata_store_role = if common_opts.roles().contains(Role::MetadataStore) {
-            Some(options.metadata_store.clone().build()?)
-        } else {
-            None
-        };
+        let tls = common_opts
+            .tls()
+            .as_ref()
+            .map(|tls| TlsConfig::load(tls.cert_path(), tls.key_path(), tls.ca_cert_path()))
+            .transpose()?;
+
+        let metadata_store_client = restate_metadata_store::local::create_client(
+            common_opts.metadata_store_address().clone(),
+            tls.as_ref(),
+        );
 
         let mut router_builder = MessageRouterBuilder::default();
-        let networking = Networking::default();
-        let bifrost = options.bifrost.clone().build(options.worker.partitions);
-        let metadata_manager = MetadataManager::build(networking.clone());
+        let networking = Networking::new(tls.clone());
+        let metadata_manager =
+            MetadataManager::build(networking.clone(), metadata_store_client.clone());
         metadata_manager.register_in_message_router(&mut router_builder);
-
-        let admin_role = if common_opts.roles().contains(Role::Admin) {
-            Some(AdminRole::new(options.clone(), networking.clone())?)
-        } else {
-            None
-        };
-
-        let worker_role = if common_opts.roles().contains(Role::Worker) {
-            Some(WorkerRole::new(
-                options.clone(),
-                &mut router_builder,
+        router_builder.add_message_handler(HeartbeatResponder::new(networking.clone()));
+        let bifrost = options
+            .bifrost
+            .clone()
+            .build(options.worker.partitions, metadata_manager.writer())
+            .with_networking(networking.clone(), &mut router_builder);
+
+        let log_server = if common_opts.roles().contains(Role::LogServer) {
+            Some(LogServer::create(
+                options.bifrost.replicated.clone(),
                 networking.clone(),
-                bifrost.handle(),
+                &mut router_builder,
             )?)
         } else {
             None
         };
 
+        // Roles register their message handlers with the router when they are built, which also
+        // happens while the node is running.
+        let message_router = router_builder.build();
+        networking
+            .connection_manager()
+            .set_message_router(message_router.clone());
+
+        let mut role_manager = RoleManager::new(
+            options.clone(),
+            *common_opts.allow_bootstrap(),
+            tls.clone(),
+            networking.clone(),
+            metadata_manager.writer(),
+            metadata_store_client.clone(),
+            bifrost.handle(),
+            message_router,
+        );
+        // the log server is not managed by the role manager
+        for role in common_opts
+            .roles()
+            .iter()
+            .filter(|role| *role != Role::LogServer)
+        {
+            role_manager.build(role)?;
+        }
+
         let server = NetworkServer::new(
             common_opts.clone(),
+            tls,
             networking.connection_manager(),
-            worker_role.as_ref().map(|worker| {
-                WorkerDependencies::new(
-                    worker.rocksdb_storage().clone(),
-                    worker.storage_query_context().clone(),
-                    worker.schemas(),
-                    worker.subscription_controller(),
-                )
-            }),
-            admin_role.as_ref().map(|cluster_controller| {
-                AdminDependencies::new(
-                    cluster_controller.cluster_controller_handle(),
-                    cluster_controller.schema_reader(),
-                )
-            }),
+            role_manager.role_deps(),
         );
 
-        // Ensures that message router is updated after all services have registered themselves in
-        // the builder.
-        let message_router = router_builder.build();
-        networking
-            .connection_manager()
-            .set_message_router(message_router);
-
         Ok(Node {
             common_opts,
             options: opts,
             metadata_manager,
+            metadata_store_client,
             bifrost,
-            metadata_store_role,
-            admin_role,
-            worker_role,
+            log_server,
+            role_manager,
             server,
         })
     }
 
-    pub async fn start(self) -> Result<(), anyhow::Error> {
+    pub async fn start(mut self) -> Result<(), anyhow::Error> {
         let tc = task_center();
 
-        if let Some(metadata_store) = self.metadata_store_role {
-            tc.spawn(
-                TaskKind::MetadataStore,
-                "local-metadata-store",
-                None,
-                async move {
-                    metadata_store.run().await?;
-                    Ok(())
-                },
-            )?;
-        }
+        self.role_manager.start(Role::MetadataStore)?;
 
-        let metadata_store_client = restate_metadata_store::local::create_client(
-            self.common_opts.metadata_store_address().clone(),
-        );
+        let metadata_store_client = self.metadata_store_client;
 
         let nodes_config =
             Self::upsert_node_config(&metadata_store_client, &self.common_opts).await?;
@@ -202,7 +202,7 @@ impl Node {
 
         metadata_writer.update(nodes_config).await?;
 
-        let partition_table: FixedPartitionTable =
+        let partition_table: PartitionTable =
             Self::fetch_or_insert_partition_table(metadata_store_client, &self.options).await?;
 
         metadata_writer.update(partition_table).await?;
@@ -244,28 +244,28 @@ impl Node {
         metadata_writer.set_my_node_id(my_node_id);
         info!("My Node ID is {}", my_node_config.current_generation);
 
-        let bifrost = self.bifrost.handle();
+        if let Some(log_server) = self.log_server {
+            tc.spawn(TaskKind::LogServer, "log-server", None, log_server.run())?;
+        }
 
         // Ensures bifrost has initial metadata synced up before starting the worker.
         self.bifrost.start().await?;
 
-        if let Some(admin_role) = self.admin_role {
-            tc.spawn(
-                TaskKind::SystemBoot,
-                "admin-init",
-                None,
-                admin_role.start(*self.common_opts.allow_bootstrap(), bifrost.clone()),
-            )?;
+        // the roles which have been changed at runtime take precedence over the configured ones,
+        // the role manager starts assigned roles which have not been configured and drops
+        // configured roles which have been removed
+        for role in [Role::Admin, Role::Worker] {
+            if my_node_config.roles.contains(role) {
+                self.role_manager.start(role)?;
+            }
         }
 
-        if let Some(worker_role) = self.worker_role {
-            tc.spawn(
-                TaskKind::SystemBoot,
-                "worker-init",
-                None,
-                worker_role.start(bifrost),
-            )?;
-        }
+        tc.spawn(
+            TaskKind::SystemService,
+            "role-manager",
+            None,
+            self.role_manager.run(),
+        )?;
 
         tc.spawn(
             TaskKind::RpcServer,
@@ -280,7 +280,7 @@ impl Node {
     async fn fetch_or_insert_partition_table(
         metadata_store_client: MetadataStoreClient,
         options: &Options,
-    ) -> Result<FixedPartitionTable, ReadModifyWriteError> {
+    ) -> Result<PartitionTable, ReadModifyWriteError> {
         Self::retry_on_network_error(|| {
             metadata_store_client.read_modify_write(
                 PARTITION_TABLE_KEY.clone(),
@@ -288,7 +288,7 @@ impl Node {
                     if let Some(partition_table) = partition_table {
                         Operation::Return(partition_table)
                     } else {
-                        Operation::Upsert(FixedPartitionTable::new(
+                        Operation::Upsert(PartitionTable::new(
                             Version::MIN,
                             options.worker.partitions,
                         ))
@@ -335,8 +335,19 @@ impl Node {
                                     previous_node_generation = Some(node_config.current_generation);
                                 }
 
-                                // update node_config
-                                node_config.roles = *common_opts.roles();
+                                // update node_config, the roles which can be changed at
+                                // runtime are kept, the others follow the configuration
+                                node_config.roles = node_config
+                                    .roles
+                                    .iter()
+                                    .filter(|role| role.is_dynamic())
+                                    .chain(
+                                        common_opts
+                                            .roles()
+                                            .iter()
+                                            .filter(|role| !role.is_dynamic()),
+                                    )
+                                    .collect();
                                 node_config.address = common_opts.advertise_address().clone();
                                 node_config.current_generation.bump_generation();
 
@@ -16,15 +16,15 @@ use restate_node_services::cluster_ctrl::cluster_ctrl_svc_server::ClusterCtrlSvc
 use restate_node_services::cluster_ctrl::{AttachmentRequest, AttachmentResponse};
 use restate_node_services::cluster_ctrl::{FetchSchemasRequest, FetchSchemasResponse};
 
-use crate::network_server::AdminDependencies;
+use crate::network_server::RoleDependencies;
 
 pub struct ClusterCtrlSvcHandler {
-    admin_deps: AdminDependencies,
+    role_deps: RoleDependencies,
 }
 
 impl ClusterCtrlSvcHandler {
-    pub fn new(admin_deps: AdminDependencies) -> Self {
-        Self { admin_deps }
+    pub fn new(role_deps: RoleDependencies) -> Self {
+        Self { role_deps }
     }
 }
 
@@ -34,6 +34,9 @@ impl ClusterCtrlSvc for ClusterCtrlSvcHandler {
         &self,
         request: Request<AttachmentRequest>,
     ) -> Result<Response<AttachmentResponse>, Status> {
+        if self.role_deps.admin().is_none() {
+            return Err(Status::failed_precondition("Not an admin node"));
+        }
         let node_id = request.into_inner().node_id.expect("node id must be set");
         debug!("Attaching node '{:?}'", node_id);
         Ok(Response::new(AttachmentResponse {}))
@@ -43,7 +46,10 @@ impl ClusterCtrlSvc for ClusterCtrlSvcHandler {
         &self,
         _request: Request<FetchSchemasRequest>,
     ) -> Result<Response<FetchSchemasResponse>, Status> {
-        let schema_updates = self.admin_deps.schema_reader.read().await.map_err(|err| {
+        let Some(admin_deps) = self.role_deps.admin() else {
+            return Err(Status::failed_precondition("Not an admin node"));
+        };
+        let schema_updates = admin_deps.schema_reader.read().await.map_err(|err| {
             Status::internal(format!("Could not read schema information: '{}'", err))
         })?;
 
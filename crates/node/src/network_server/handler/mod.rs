@@ -13,10 +13,13 @@ pub mod node;
 
 use std::fmt::Write;
 use std::iter::once;
+use std::time::Duration;
 
 use axum::extract::State;
 use axum::response::IntoResponse;
+use axum::Json;
 use metrics_exporter_prometheus::formatting;
+use restate_meta_rest_model::tasks::{TaskResponse, TasksResponse};
 use restate_storage_rocksdb::{TableKind, DB};
 use rocksdb::statistics::{Histogram, Ticker};
 use rocksdb::AsColumnFamilyRef;
@@ -127,6 +130,26 @@ static ROCKSDB_PROPERTIES: &[(&str, MetricUnit)] = &[
 ];
 
 // -- Direct HTTP Handlers --
+/// Lists the tasks which are currently managed by the node's task center.
+pub async fn render_tasks(State(state): State<NodeCtrlHandlerState>) -> Json<TasksResponse> {
+    let tasks = state
+        .task_center
+        .running_tasks()
+        .into_iter()
+        .map(|task| TaskResponse {
+            id: task.id.into(),
+            name: task.name.to_owned(),
+            kind: task.kind.to_string(),
+            partition_id: task.partition_id,
+            // sub-millisecond precision is noise for an overview of running tasks
+            age: Duration::from_millis(task.age.as_millis() as u64).into(),
+            cancellation_requested: task.cancellation_requested,
+        })
+        .collect();
+
+    Json(TasksResponse { tasks })
+}
+
 pub async fn render_metrics(State(state): State<NodeCtrlHandlerState>) -> String {
     let mut out = String::new();
 
@@ -138,9 +161,10 @@ pub async fn render_metrics(State(state): State<NodeCtrlHandlerState>) -> String
 
     // Load metrics from rocksdb (if the node runs rocksdb, and rocksdb
     // stat collection is enabled)
-    let Some(db) = state.rocksdb_storage else {
+    let Some(worker) = state.role_deps.worker() else {
         return out;
     };
+    let db = &worker.rocksdb;
 
     let raw_db = db.inner();
     let options = db.options();
@@ -222,9 +246,10 @@ pub async fn render_metrics(State(state): State<NodeCtrlHandlerState>) -> String
 }
 
 pub async fn rocksdb_stats(State(state): State<NodeCtrlHandlerState>) -> impl IntoResponse {
-    let Some(db) = state.rocksdb_storage else {
+    let Some(worker) = state.role_deps.worker() else {
         return String::new();
     };
+    let db = &worker.rocksdb;
 
     let options = db.options();
     options.get_statistics().unwrap_or_default()
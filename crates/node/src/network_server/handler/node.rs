@@ -13,6 +13,7 @@ use arrow_flight::error::FlightError;
 use futures::stream::BoxStream;
 use futures::TryStreamExt;
 use restate_core::{metadata, TaskCenter};
+use restate_grpc_util::PeerCertificates;
 use restate_network::error::ProtocolError;
 use tokio_stream::StreamExt;
 use tonic::{Request, Response, Status, Streaming};
@@ -21,28 +22,25 @@ use restate_network::ConnectionManager;
 use restate_node_protocol::node::Message;
 use restate_node_services::node_svc::node_svc_server::NodeSvc;
 use restate_node_services::node_svc::{IdentResponse, NodeStatus};
-use restate_node_services::node_svc::{
-    StorageQueryRequest, StorageQueryResponse, UpdateSchemaRequest,
-};
-use restate_schema_impl::SchemasUpdateCommand;
+use restate_node_services::node_svc::{StorageQueryRequest, StorageQueryResponse};
 
-use crate::network_server::WorkerDependencies;
+use crate::network_server::RoleDependencies;
 
 pub struct NodeSvcHandler {
     task_center: TaskCenter,
-    worker: Option<WorkerDependencies>,
+    role_deps: RoleDependencies,
     connections: ConnectionManager,
 }
 
 impl NodeSvcHandler {
     pub fn new(
         task_center: TaskCenter,
-        worker: Option<WorkerDependencies>,
+        role_deps: RoleDependencies,
         connections: ConnectionManager,
     ) -> Self {
         Self {
             task_center,
-            worker,
+            role_deps,
             connections,
         }
     }
@@ -66,7 +64,7 @@ impl NodeSvc for NodeSvcHandler {
         &self,
         request: Request<StorageQueryRequest>,
     ) -> Result<Response<Self::QueryStorageStream>, Status> {
-        let Some(ref worker) = self.worker else {
+        let Some(worker) = self.role_deps.worker() else {
             return Err(Status::failed_precondition("Not a worker node"));
         };
         let query = request.into_inner().query;
@@ -96,38 +94,6 @@ impl NodeSvc for NodeSvcHandler {
         Ok(Response::new(Box::pin(response_stream)))
     }
 
-    async fn update_schemas(
-        &self,
-        request: Request<UpdateSchemaRequest>,
-    ) -> Result<Response<()>, Status> {
-        let Some(ref worker) = self.worker else {
-            return Err(Status::failed_precondition("Not a worker node"));
-        };
-
-        let (schema_updates, _) =
-            bincode::serde::decode_from_slice::<Vec<SchemasUpdateCommand>, _>(
-                &request.into_inner().schema_bin,
-                bincode::config::standard(),
-            )
-            .map_err(|err| Status::invalid_argument(err.to_string()))?;
-
-        self.task_center
-            .run_in_scope("update-schema", None, async move {
-                crate::roles::update_schemas(
-                    &worker.schemas,
-                    worker.subscription_controller.as_ref(),
-                    schema_updates,
-                )
-                .await
-                .map_err(|err| {
-                    Status::internal(format!("failed updating the schema information: {err}"))
-                })
-            })
-            .await?;
-
-        Ok(Response::new(()))
-    }
-
     type CreateConnectionStream = BoxStream<'static, Result<Message, Status>>;
 
     // Status codes returned in different scenarios:
@@ -142,6 +108,8 @@ impl NodeSvc for NodeSvcHandler {
         &self,
         request: Request<Streaming<Message>>,
     ) -> Result<Response<Self::CreateConnectionStream>, Status> {
+        // only set if the connection has been established with TLS
+        let peer_certificates = request.extensions().get::<PeerCertificates>().cloned();
         let incoming = request.into_inner();
         let transformed = incoming.map(|x| x.map_err(ProtocolError::from));
         let output_stream = self
@@ -149,7 +117,8 @@ impl NodeSvc for NodeSvcHandler {
             .run_in_scope(
                 "accept-connection",
                 None,
-                self.connections.accept_incoming_connection(transformed),
+                self.connections
+                    .accept_incoming_connection(transformed, peer_certificates),
             )
             .await?;
 
@@ -8,14 +8,17 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::sync::Arc;
+
+use arc_swap::ArcSwapOption;
 use axum::routing::get;
 use restate_core::options::CommonOptions;
 use tower_http::trace::TraceLayer;
 
 use restate_cluster_controller::ClusterControllerHandle;
 use restate_core::{cancellation_watcher, task_center};
-use restate_grpc_util::run_hyper_server;
-use restate_meta::FileMetaReader;
+use restate_grpc_util::{run_hyper_server, TlsConfig};
+use restate_meta::MetadataStoreMetaReader;
 use restate_network::ConnectionManager;
 use restate_node_protocol::{common, node};
 use restate_node_services::cluster_ctrl;
@@ -35,33 +38,31 @@ use crate::network_server::state::NodeCtrlHandlerStateBuilder;
 
 pub struct NetworkServer {
     common_opts: CommonOptions,
+    tls: Option<TlsConfig>,
     connection_manager: ConnectionManager,
-    worker_deps: Option<WorkerDependencies>,
-    admin_deps: Option<AdminDependencies>,
+    role_deps: RoleDependencies,
 }
 
 impl NetworkServer {
     pub fn new(
         common_opts: CommonOptions,
+        tls: Option<TlsConfig>,
         connection_manager: ConnectionManager,
-        worker_deps: Option<WorkerDependencies>,
-        admin_deps: Option<AdminDependencies>,
+        role_deps: RoleDependencies,
     ) -> Self {
         Self {
             common_opts,
+            tls,
             connection_manager,
-            worker_deps,
-            admin_deps,
+            role_deps,
         }
     }
 
     pub async fn run(self) -> Result<(), anyhow::Error> {
         // Configure Metric Exporter
         let mut state_builder = NodeCtrlHandlerStateBuilder::default();
-
-        if let Some(WorkerDependencies { rocksdb, .. }) = self.worker_deps.as_ref() {
-            state_builder.rocksdb_storage(Some(rocksdb.clone()));
-        }
+        state_builder.task_center(task_center());
+        state_builder.role_deps(self.role_deps.clone());
 
         if !*self.common_opts.disable_prometheus() {
             state_builder
@@ -79,32 +80,29 @@ impl NetworkServer {
         let router = axum::Router::new()
             .route("/metrics", get(handler::render_metrics))
             .route("/rocksdb-stats", get(handler::rocksdb_stats))
+            .route("/tasks", get(handler::render_tasks))
             .with_state(shared_state)
             .layer(TraceLayer::new_for_http().make_span_with(span_factory.clone()))
             .fallback(handler_404);
 
         // -- GRPC Service Setup
-        let mut reflection_service_builder = tonic_reflection::server::Builder::configure()
+        // the cluster controller service is always registered since the admin role can be
+        // assigned while the node is running
+        let reflection_service_builder = tonic_reflection::server::Builder::configure()
             .register_encoded_file_descriptor_set(node::FILE_DESCRIPTOR_SET)
-            .register_encoded_file_descriptor_set(common::FILE_DESCRIPTOR_SET);
-
-        if self.admin_deps.is_some() {
-            reflection_service_builder = reflection_service_builder
-                .register_encoded_file_descriptor_set(cluster_ctrl::FILE_DESCRIPTOR_SET);
-        }
-
-        let cluster_controller_service = self
-            .admin_deps
-            .map(|admin_deps| ClusterCtrlSvcServer::new(ClusterCtrlSvcHandler::new(admin_deps)));
+            .register_encoded_file_descriptor_set(common::FILE_DESCRIPTOR_SET)
+            .register_encoded_file_descriptor_set(cluster_ctrl::FILE_DESCRIPTOR_SET);
 
         let server_builder = tonic::transport::Server::builder()
             .layer(TraceLayer::new_for_grpc().make_span_with(span_factory))
             .add_service(NodeSvcServer::new(NodeSvcHandler::new(
                 task_center(),
-                self.worker_deps,
+                self.role_deps.clone(),
                 self.connection_manager,
             )))
-            .add_optional_service(cluster_controller_service)
+            .add_service(ClusterCtrlSvcServer::new(ClusterCtrlSvcHandler::new(
+                self.role_deps,
+            )))
             .add_service(reflection_service_builder.build()?);
 
         // Multiplex both grpc and http based on content-type
@@ -112,6 +110,7 @@ impl NetworkServer {
 
         run_hyper_server(
             self.common_opts.bind_address(),
+            self.tls.as_ref(),
             service,
             cancellation_watcher(),
             "node-grpc",
@@ -130,6 +129,32 @@ async fn handler_404() -> (http::StatusCode, &'static str) {
     )
 }
 
+/// Dependencies on the services of the worker and admin roles. They are updated when the roles
+/// are started or stopped while the node is running.
+#[derive(Clone, Default)]
+pub struct RoleDependencies {
+    worker: Arc<ArcSwapOption<WorkerDependencies>>,
+    admin: Arc<ArcSwapOption<AdminDependencies>>,
+}
+
+impl RoleDependencies {
+    pub fn worker(&self) -> Option<Arc<WorkerDependencies>> {
+        self.worker.load_full()
+    }
+
+    pub fn set_worker(&self, worker: Option<WorkerDependencies>) {
+        self.worker.store(worker.map(Arc::new));
+    }
+
+    pub fn admin(&self) -> Option<Arc<AdminDependencies>> {
+        self.admin.load_full()
+    }
+
+    pub fn set_admin(&self, admin: Option<AdminDependencies>) {
+        self.admin.store(admin.map(Arc::new));
+    }
+}
+
 pub struct WorkerDependencies {
     pub rocksdb: RocksDBStorage,
     pub query_context: QueryContext,
@@ -155,13 +180,13 @@ impl WorkerDependencies {
 
 pub struct AdminDependencies {
     pub _cluster_controller_handle: ClusterControllerHandle,
-    pub schema_reader: FileMetaReader,
+    pub schema_reader: MetadataStoreMetaReader,
 }
 
 impl AdminDependencies {
     pub fn new(
         cluster_controller_handle: ClusterControllerHandle,
-        schema_reader: FileMetaReader,
+        schema_reader: MetadataStoreMetaReader,
     ) -> Self {
         AdminDependencies {
             _cluster_controller_handle: cluster_controller_handle,
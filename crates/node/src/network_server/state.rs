@@ -9,12 +9,15 @@
 // by the Apache License, Version 2.0.
 
 use metrics_exporter_prometheus::PrometheusHandle;
-use restate_storage_rocksdb::RocksDBStorage;
+use restate_core::TaskCenter;
+
+use crate::network_server::RoleDependencies;
 
 #[derive(Clone, derive_builder::Builder)]
 pub struct NodeCtrlHandlerState {
+    pub task_center: TaskCenter,
     #[builder(default)]
     pub prometheus_handle: Option<PrometheusHandle>,
     #[builder(default)]
-    pub rocksdb_storage: Option<RocksDBStorage>,
+    pub role_deps: RoleDependencies,
 }
@@ -24,6 +24,9 @@ pub struct Options {
     pub bifrost: restate_bifrost::Options,
     pub cluster_controller: restate_cluster_controller::Options,
     pub metadata_store: restate_metadata_store::local::Options,
+    /// Configures the raft metadata store. If it is specified, then the metadata store role
+    /// runs a member of a raft-replicated metadata store instead of the local metadata store.
+    pub metadata_store_raft: Option<restate_metadata_store::raft::Options>,
 
     /// Configures the admin address. If it is not specified, then this
     /// node needs to run the admin role
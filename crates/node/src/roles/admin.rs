@@ -9,7 +9,6 @@
 // by the Apache License, Version 2.0.
 
 use anyhow::Context;
-use codederror::CodedError;
 use restate_network::Networking;
 use restate_node_services::node_svc::node_svc_client::NodeSvcClient;
 use tonic::transport::Channel;
@@ -18,48 +17,54 @@ use tracing::info;
 use restate_admin::service::AdminService;
 use restate_bifrost::Bifrost;
 use restate_cluster_controller::ClusterControllerHandle;
-use restate_core::{task_center, TaskKind};
-use restate_meta::{FileMetaReader, FileMetaStorage, MetaService};
+use restate_core::metadata_store::MetadataStoreClient;
+use restate_core::network::MessageRouterBuilder;
+use restate_core::{task_center, MetadataWriter, TaskKind};
+use restate_meta::{MetaService, MetadataStoreMetaReader, MetadataStoreMetaStorage};
 use restate_worker::KafkaIngressOptions;
 
 use crate::Options;
 
-#[derive(Debug, thiserror::Error, CodedError)]
-pub enum AdminRoleBuildError {
-    #[error("failed creating meta: {0}")]
-    Meta(
-        #[from]
-        #[code]
-        restate_meta::BuildError,
-    ),
-}
-
-#[derive(Debug)]
 pub struct AdminRole {
-    controller: restate_cluster_controller::Service,
+    controller: restate_cluster_controller::Service<Networking>,
     admin: AdminService,
-    meta: MetaService<FileMetaStorage, KafkaIngressOptions>,
+    meta: MetaService<MetadataStoreMetaStorage, KafkaIngressOptions>,
 }
 
 impl AdminRole {
-    pub fn new(options: Options, _networking: Networking) -> Result<Self, AdminRoleBuildError> {
-        let meta = options.meta.build(options.worker.kafka.clone())?;
+    pub fn new(
+        options: Options,
+        networking: Networking,
+        metadata_writer: MetadataWriter,
+        metadata_store_client: MetadataStoreClient,
+        bifrost: Bifrost,
+        router_builder: &mut MessageRouterBuilder,
+    ) -> Self {
+        let meta = options
+            .meta
+            .build(options.worker.kafka.clone(), metadata_store_client.clone());
         let admin = options
             .admin
-            .build(meta.schemas(), meta.meta_handle(), meta.schema_reader());
-
-        Ok(AdminRole {
-            controller: restate_cluster_controller::Service::new(options.cluster_controller),
+            .build(meta.schemas(), meta.meta_handle(), metadata_store_client);
+
+        AdminRole {
+            controller: restate_cluster_controller::Service::new(
+                options.cluster_controller,
+                networking,
+                metadata_writer,
+                bifrost,
+                router_builder,
+            ),
             admin,
             meta,
-        })
+        }
     }
 
     pub fn cluster_controller_handle(&self) -> ClusterControllerHandle {
         self.controller.handle()
     }
 
-    pub fn schema_reader(&self) -> FileMetaReader {
+    pub fn schema_reader(&self) -> MetadataStoreMetaReader {
         self.meta.schema_reader()
     }
 
@@ -70,6 +75,8 @@ impl AdminRole {
     ) -> Result<(), anyhow::Error> {
         info!("Running admin role");
 
+        let cluster_controller_handle = self.controller.handle();
+
         // Init the meta. This will reload the schemas in memory.
         self.meta.init().await?;
 
@@ -100,7 +107,8 @@ impl AdminRole {
             TaskKind::RpcServer,
             "admin-rpc-server",
             None,
-            self.admin.run(node_svc_client, bifrost),
+            self.admin
+                .run(node_svc_client, bifrost, cluster_controller_handle),
         )?;
 
         Ok(())
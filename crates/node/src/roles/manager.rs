@@ -0,0 +1,274 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tracing::{info, warn};
+
+use restate_bifrost::Bifrost;
+use restate_core::metadata_store::MetadataStoreClient;
+use restate_core::network::{MessageRouter, MessageRouterBuilder};
+use restate_core::{
+    cancellation_watcher, metadata, task_center, MetadataWriter, ShutdownError, TaskId, TaskKind,
+};
+use restate_grpc_util::TlsConfig;
+use restate_network::Networking;
+use restate_node_protocol::common::TargetName;
+use restate_node_protocol::metadata::MetadataKind;
+use restate_types::nodes_config::Role;
+
+use crate::network_server::{AdminDependencies, RoleDependencies, WorkerDependencies};
+use crate::roles::{AdminRole, MetadataStoreRole, WorkerRole};
+use crate::{BuildError, Options};
+
+/// Roles which can be assigned to and removed from a running node, see [`Role::is_dynamic`], in
+/// the order in which they are started.
+const DYNAMIC_ROLES: [Role; 2] = [Role::Admin, Role::Worker];
+
+/// Interval after which starting a role is retried if it could not be built. Building a role
+/// fails, for example, if the services of a previous instance still hold on to its storage.
+const RETRY_START_INTERVAL: Duration = Duration::from_secs(5);
+
+enum BuiltRole {
+    MetadataStore(MetadataStoreRole),
+    Admin(AdminRole),
+    Worker(WorkerRole),
+}
+
+struct ManagedRole {
+    /// Set until the role's services have been started.
+    built: Option<BuiltRole>,
+    /// Root task of the role's services.
+    task_id: Option<TaskId>,
+    /// Targets of the message handlers which the role has registered.
+    targets: Vec<TargetName>,
+}
+
+/// Builds, starts and stops the services of the node's roles. While the node is running, it
+/// starts and stops roles when the roles of the node change in the nodes configuration.
+pub struct RoleManager {
+    options: Options,
+    bootstrap_cluster: bool,
+    tls: Option<TlsConfig>,
+    networking: Networking,
+    metadata_writer: MetadataWriter,
+    metadata_store_client: MetadataStoreClient,
+    bifrost: Bifrost,
+    message_router: MessageRouter,
+    role_deps: RoleDependencies,
+    roles: HashMap<Role, ManagedRole>,
+}
+
+impl RoleManager {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        options: Options,
+        bootstrap_cluster: bool,
+        tls: Option<TlsConfig>,
+        networking: Networking,
+        metadata_writer: MetadataWriter,
+        metadata_store_client: MetadataStoreClient,
+        bifrost: Bifrost,
+        message_router: MessageRouter,
+    ) -> Self {
+        Self {
+            options,
+            bootstrap_cluster,
+            tls,
+            networking,
+            metadata_writer,
+            metadata_store_client,
+            bifrost,
+            message_router,
+            role_deps: RoleDependencies::default(),
+            roles: HashMap::default(),
+        }
+    }
+
+    /// Dependencies of the network server on the services of the running roles.
+    pub fn role_deps(&self) -> RoleDependencies {
+        self.role_deps.clone()
+    }
+
+    /// Builds the role and registers its message handlers. The role's services are started by
+    /// [`Self::start`].
+    pub fn build(&mut self, role: Role) -> Result<(), BuildError> {
+        let mut router_builder = MessageRouterBuilder::default();
+        let built = match role {
+            Role::MetadataStore => {
+                BuiltRole::MetadataStore(MetadataStoreRole::new(&self.options, self.tls.clone())?)
+            }
+            Role::Admin => {
+                let admin_role = AdminRole::new(
+                    self.options.clone(),
+                    self.networking.clone(),
+                    self.metadata_writer.clone(),
+                    self.metadata_store_client.clone(),
+                    self.bifrost.clone(),
+                    &mut router_builder,
+                );
+                self.role_deps.set_admin(Some(AdminDependencies::new(
+                    admin_role.cluster_controller_handle(),
+                    admin_role.schema_reader(),
+                )));
+                BuiltRole::Admin(admin_role)
+            }
+            Role::Worker => {
+                let worker_role = WorkerRole::new(
+                    self.options.clone(),
+                    &mut router_builder,
+                    self.networking.clone(),
+                    self.bifrost.clone(),
+                    self.metadata_store_client.clone(),
+                    self.tls.clone(),
+                )?;
+                self.role_deps.set_worker(Some(WorkerDependencies::new(
+                    worker_role.rocksdb_storage().clone(),
+                    worker_role.storage_query_context().clone(),
+                    worker_role.schemas(),
+                    worker_role.subscription_controller(),
+                )));
+                BuiltRole::Worker(worker_role)
+            }
+            Role::LogServer => unreachable!("the log server is not managed by the role manager"),
+        };
+
+        let targets = self.message_router.add_handlers(router_builder);
+        self.roles.insert(
+            role,
+            ManagedRole {
+                built: Some(built),
+                task_id: None,
+                targets,
+            },
+        );
+        Ok(())
+    }
+
+    /// Starts the services of the role if it has been built and is not running yet.
+    pub fn start(&mut self, role: Role) -> Result<(), ShutdownError> {
+        let Some(managed_role) = self.roles.get_mut(&role) else {
+            return Ok(());
+        };
+        let Some(built) = managed_role.built.take() else {
+            return Ok(());
+        };
+
+        let tc = task_center();
+        let task_id = match built {
+            BuiltRole::MetadataStore(metadata_store_role) => tc.spawn(
+                TaskKind::MetadataStore,
+                "metadata-store",
+                None,
+                metadata_store_role.run(),
+            )?,
+            BuiltRole::Admin(admin_role) => {
+                let bootstrap_cluster = self.bootstrap_cluster;
+                let bifrost = self.bifrost.clone();
+                // the services are spawned as children so that they are stopped with the role
+                tc.spawn(TaskKind::RoleRunner, "admin-role", None, async move {
+                    admin_role.start(bootstrap_cluster, bifrost).await?;
+                    cancellation_watcher().await;
+                    Ok::<(), anyhow::Error>(())
+                })?
+            }
+            BuiltRole::Worker(worker_role) => {
+                tc.spawn(TaskKind::RoleRunner, "worker-role", None, async move {
+                    worker_role.start().await?;
+                    cancellation_watcher().await;
+                    Ok::<(), anyhow::Error>(())
+                })?
+            }
+        };
+        managed_role.task_id = Some(task_id);
+        Ok(())
+    }
+
+    /// Stops the services of the role and removes its message handlers. The tasks which have been
+    /// spawned by the role's services are cancelled as well, but only the role's root task is
+    /// awaited.
+    async fn stop(&mut self, role: Role) {
+        let Some(managed_role) = self.roles.remove(&role) else {
+            return;
+        };
+
+        if let Some(join_handle) = managed_role
+            .task_id
+            .and_then(|task_id| task_center().cancel_task(task_id))
+        {
+            // errors have been reported by task center already
+            let _ = join_handle.await;
+        }
+
+        self.message_router.remove_handlers(&managed_role.targets);
+        match role {
+            Role::Admin => self.role_deps.set_admin(None),
+            Role::Worker => self.role_deps.set_worker(None),
+            Role::MetadataStore | Role::LogServer => {}
+        }
+    }
+
+    /// Follows the roles which are assigned to this node in the nodes configuration.
+    pub async fn run(mut self) -> anyhow::Result<()> {
+        let mut nodes_config_watch = metadata().watch(MetadataKind::NodesConfiguration);
+
+        loop {
+            let retry_start = !self.apply_assigned_roles().await?;
+
+            tokio::select! {
+                _ = cancellation_watcher() => {
+                    return Ok(());
+                }
+                result = nodes_config_watch.changed() => {
+                    result.map_err(|_| ShutdownError)?;
+                }
+                _ = tokio::time::sleep(RETRY_START_INTERVAL), if retry_start => {}
+            }
+        }
+    }
+
+    /// Starts the assigned roles which are not running and stops the running roles which are not
+    /// assigned anymore. Returns false if a role could not be started.
+    async fn apply_assigned_roles(&mut self) -> Result<bool, ShutdownError> {
+        let metadata = metadata();
+        let assigned_roles = match metadata
+            .nodes_config()
+            .find_node_by_id(metadata.my_node_id())
+        {
+            Ok(node_config) => node_config.roles,
+            // the node has been removed or superseded by a newer generation, which is handled
+            // elsewhere
+            Err(_) => return Ok(true),
+        };
+
+        let mut all_started = true;
+        for role in DYNAMIC_ROLES {
+            let is_running = self.roles.contains_key(&role);
+
+            if assigned_roles.contains(role) && !is_running {
+                info!("Starting {role} role");
+                if let Err(err) = self.build(role) {
+                    warn!(
+                        "Failed starting {role} role, retrying in {RETRY_START_INTERVAL:?}: {err}"
+                    );
+                    all_started = false;
+                    continue;
+                }
+                self.start(role)?;
+            } else if !assigned_roles.contains(role) && is_running {
+                info!("Stopping {role} role");
+                self.stop(role).await;
+            }
+        }
+
+        Ok(all_started)
+    }
+}
@@ -0,0 +1,68 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use codederror::CodedError;
+use tracing::info;
+
+use restate_grpc_util::TlsConfig;
+use restate_metadata_store::local::LocalMetadataStoreService;
+use restate_metadata_store::raft::RaftMetadataStoreService;
+
+use crate::Options;
+
+#[derive(Debug, thiserror::Error, CodedError)]
+pub enum MetadataStoreRoleBuildError {
+    #[error("failed building local metadata store: {0}")]
+    Local(
+        #[from]
+        #[code]
+        restate_metadata_store::local::BuildError,
+    ),
+    #[error("failed building raft metadata store: {0}")]
+    Raft(
+        #[from]
+        #[code]
+        restate_metadata_store::raft::BuildError,
+    ),
+}
+
+/// Runs the raft metadata store if it is configured, otherwise the local metadata store.
+pub enum MetadataStoreRole {
+    Local(LocalMetadataStoreService),
+    Raft(RaftMetadataStoreService),
+}
+
+impl MetadataStoreRole {
+    pub fn new(
+        options: &Options,
+        tls: Option<TlsConfig>,
+    ) -> Result<Self, MetadataStoreRoleBuildError> {
+        match &options.metadata_store_raft {
+            Some(raft_options) => Ok(MetadataStoreRole::Raft(raft_options.clone().build(tls)?)),
+            None => Ok(MetadataStoreRole::Local(
+                options.metadata_store.clone().build(tls)?,
+            )),
+        }
+    }
+
+    pub async fn run(self) -> Result<(), anyhow::Error> {
+        match self {
+            MetadataStoreRole::Local(service) => {
+                info!("Running local metadata store");
+                service.run().await?;
+            }
+            MetadataStoreRole::Raft(service) => {
+                info!("Running raft metadata store");
+                service.run().await?;
+            }
+        }
+        Ok(())
+    }
+}
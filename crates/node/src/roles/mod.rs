@@ -9,7 +9,11 @@
 // by the Apache License, Version 2.0.
 
 mod admin;
+mod manager;
+mod metadata_store;
 mod worker;
 
-pub use admin::{AdminRole, AdminRoleBuildError};
-pub use worker::{update_schemas, WorkerRole, WorkerRoleBuildError};
+pub use admin::AdminRole;
+pub use manager::RoleManager;
+pub use metadata_store::{MetadataStoreRole, MetadataStoreRoleBuildError};
+pub use worker::{WorkerRole, WorkerRoleBuildError};
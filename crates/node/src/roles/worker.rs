@@ -11,25 +11,28 @@
 use std::time::Duration;
 
 use codederror::CodedError;
+use restate_core::metadata_store::{MetadataStoreClient, ReadError};
 use restate_core::network::MessageRouterBuilder;
 use restate_network::Networking;
-use tonic::transport::Channel;
+use tokio::sync::watch;
+use tonic::transport::ClientTlsConfig;
 use tracing::subscriber::NoSubscriber;
 use tracing::trace;
 
 use restate_bifrost::Bifrost;
-use restate_core::TaskKind;
-use restate_core::{metadata, task_center};
-use restate_grpc_util::create_grpc_channel_from_advertised_address;
+use restate_core::{cancellation_watcher, metadata, task_center, ShutdownError, TaskKind};
+use restate_grpc_util::{create_grpc_channel_from_advertised_address, TlsConfig};
+use restate_node_protocol::metadata::MetadataKind;
 use restate_node_services::cluster_ctrl::cluster_ctrl_svc_client::ClusterCtrlSvcClient;
 use restate_node_services::cluster_ctrl::AttachmentRequest;
-use restate_node_services::cluster_ctrl::FetchSchemasRequest;
 use restate_schema_api::subscription::SubscriptionResolver;
 use restate_schema_impl::{Schemas, SchemasUpdateCommand};
 use restate_storage_query_datafusion::context::QueryContext;
 use restate_storage_rocksdb::RocksDBStorage;
+use restate_types::metadata_store::keys::SCHEMA_INFORMATION_KEY;
 use restate_types::net::AdvertisedAddress;
 use restate_types::retries::RetryPolicy;
+use restate_types::Version;
 use restate_worker::{SubscriptionControllerHandle, Worker};
 use restate_worker_api::SubscriptionController;
 use tracing::info;
@@ -52,7 +55,7 @@ pub enum WorkerRoleError {
     ),
     #[error("invalid cluster controller address: {0}")]
     #[code(unknown)]
-    InvalidClusterControllerAddress(http::Error),
+    InvalidClusterControllerAddress(tonic::transport::Error),
     #[error("failed to attach to cluster at '{0}': {1}")]
     #[code(unknown)]
     Attachment(AdvertisedAddress, tonic::Status),
@@ -60,12 +63,15 @@ pub enum WorkerRoleError {
 
 #[derive(Debug, thiserror::Error, CodedError)]
 pub enum SchemaError {
-    #[error("failed to fetch schema updates: {0}")]
+    #[error("failed to read schema information version: {0}")]
     #[code(unknown)]
-    Fetch(#[from] tonic::Status),
-    #[error("failed decoding grpc payload: {0}")]
+    Read(#[from] ReadError),
+    #[error("failed decoding schema information: {0}")]
     #[code(unknown)]
     Decode(#[from] bincode::error::DecodeError),
+    #[error(transparent)]
+    #[code(unknown)]
+    Shutdown(#[from] ShutdownError),
     #[error("failed updating schemas: {0}")]
     Update(
         #[from]
@@ -85,17 +91,13 @@ pub enum WorkerRoleBuildError {
         #[code]
         restate_worker::BuildError,
     ),
-    #[error("failed creating meta: {0}")]
-    Meta(
-        #[from]
-        #[code]
-        restate_meta::BuildError,
-    ),
 }
 
 pub struct WorkerRole {
     schemas: Schemas,
     worker: Worker,
+    metadata_store_client: MetadataStoreClient,
+    tls: Option<TlsConfig>,
 }
 
 impl WorkerRole {
@@ -104,13 +106,24 @@ impl WorkerRole {
         router_builder: &mut MessageRouterBuilder,
         networking: Networking,
         bifrost: Bifrost,
+        metadata_store_client: MetadataStoreClient,
+        tls: Option<TlsConfig>,
     ) -> Result<Self, WorkerRoleBuildError> {
         let schemas = Schemas::default();
-        let worker = options
-            .worker
-            .build(networking, bifrost, router_builder, schemas.clone())?;
+        let worker = options.worker.build(
+            networking,
+            bifrost,
+            router_builder,
+            schemas.clone(),
+            metadata_store_client.clone(),
+        )?;
 
-        Ok(WorkerRole { schemas, worker })
+        Ok(WorkerRole {
+            schemas,
+            worker,
+            metadata_store_client,
+            tls,
+        })
     }
 
     pub fn rocksdb_storage(&self) -> &RocksDBStorage {
@@ -129,49 +142,60 @@ impl WorkerRole {
         Some(self.worker.subscription_controller_handle())
     }
 
-    pub async fn start(self, bifrost: Bifrost) -> anyhow::Result<()> {
+    pub async fn start(self) -> anyhow::Result<()> {
         // todo: only run subscriptions on node 0 once being distributed
         let subscription_controller = Some(self.worker.subscription_controller_handle());
 
-        let admin_address = metadata()
-            .nodes_config()
-            .get_admin_node()
-            .expect("at least one admin node")
-            .address
-            .clone();
-
-        let channel = create_grpc_channel_from_advertised_address(admin_address.clone())
-            .expect("valid admin address");
-        let mut cluster_ctrl_client = ClusterCtrlSvcClient::new(channel);
-
-        // Fetch latest schema information and fail if this is not possible
-        Self::fetch_and_update_schemas(
+        let (admin_address, admin_name) = {
+            let nodes_config = metadata().nodes_config();
+            let admin_node = nodes_config
+                .get_admin_node()
+                .expect("at least one admin node");
+            (admin_node.address.clone(), admin_node.name.clone())
+        };
+        // the admin node's certificate must be valid for its name
+        let tls = self
+            .tls
+            .as_ref()
+            .map(|tls| tls.client_config(Some(&admin_name)));
+
+        // Subscribe before the initial update so that no schema information update is missed
+        let schema_information_watch = metadata().watch(MetadataKind::Schema);
+
+        // Wait for the latest schema information and fail if this is not possible
+        Self::await_schema_information(
             &self.schemas,
             subscription_controller.as_ref(),
-            &mut cluster_ctrl_client,
+            &self.metadata_store_client,
         )
         .await?;
 
-        // todo: replace by watchdog
         task_center().spawn_child(
             TaskKind::MetadataBackgroundSync,
             "schema-updater",
             None,
-            Self::reload_schemas(subscription_controller, self.schemas, cluster_ctrl_client),
+            Self::reload_schemas(
+                subscription_controller,
+                self.schemas,
+                schema_information_watch,
+            ),
         )?;
 
         task_center().spawn_child(TaskKind::RoleRunner, "worker-service", None, async {
-            Self::attach_node(admin_address).await?;
-            self.worker.run(bifrost).await
+            Self::attach_node(admin_address, tls).await?;
+            self.worker.run().await
         })?;
 
         Ok(())
     }
 
-    async fn attach_node(admin_address: AdvertisedAddress) -> Result<(), WorkerRoleError> {
+    async fn attach_node(
+        admin_address: AdvertisedAddress,
+        tls: Option<ClientTlsConfig>,
+    ) -> Result<(), WorkerRoleError> {
         info!("Worker attaching to admin at '{admin_address}'");
 
-        let channel = create_grpc_channel_from_advertised_address(admin_address.clone())
+        let channel = create_grpc_channel_from_advertised_address(admin_address.clone(), tls)
             .map_err(WorkerRoleError::InvalidClusterControllerAddress)?;
 
         let cc_client = ClusterCtrlSvcClient::new(channel);
@@ -190,86 +214,69 @@ impl WorkerRole {
         Ok(())
     }
 
-    fn ignore_fetch_error(result: Result<(), SchemaError>) -> Result<(), SchemaError> {
-        if let Err(err) = result {
-            match err {
-                SchemaError::Fetch(err) => {
-                    trace!("Failed fetching schema information: {err}. Retrying.");
-                }
-                SchemaError::Decode(_) | SchemaError::Update(_) | SchemaError::Subscription(_) => {
-                    Err(err)?
-                }
-            }
-        }
-        Ok(())
+    /// Waits until this node has learned about the schema information version which is currently
+    /// stored in the metadata store and applies it. This makes sure that the worker does not
+    /// process requests based on outdated schema information.
+    async fn await_schema_information<SC>(
+        schemas: &Schemas,
+        subscription_controller: Option<&SC>,
+        metadata_store_client: &MetadataStoreClient,
+    ) -> Result<(), SchemaError>
+    where
+        SC: SubscriptionController + Send + Sync,
+    {
+        let Some(version) = metadata_store_client
+            .get_version(SCHEMA_INFORMATION_KEY.clone())
+            .await?
+        else {
+            trace!("No schema information has been stored yet");
+            return Ok(());
+        };
+
+        metadata()
+            .wait_for_version(MetadataKind::Schema, version)
+            .await?;
+        Self::apply_schema_information(schemas, subscription_controller).await
     }
 
     async fn reload_schemas<SC>(
         subscription_controller: Option<SC>,
         schemas: Schemas,
-        mut cluster_ctrl_client: ClusterCtrlSvcClient<Channel>,
+        mut schema_information_watch: watch::Receiver<Version>,
     ) -> anyhow::Result<()>
     where
         SC: SubscriptionController + Clone + Send + Sync,
     {
-        // todo: make this configurable
-        let mut fetch_interval = tokio::time::interval(Duration::from_secs(5));
-
         loop {
-            fetch_interval.tick().await;
-
-            trace!("Trying to fetch schema information");
-
-            Self::ignore_fetch_error(
-                Self::fetch_and_update_schemas(
-                    &schemas,
-                    subscription_controller.as_ref(),
-                    &mut cluster_ctrl_client,
-                )
-                .await,
-            )?;
+            tokio::select! {
+                _ = cancellation_watcher() => {
+                    break;
+                }
+                result = schema_information_watch.changed() => {
+                    result.map_err(|_| ShutdownError)?;
+                    let version = *schema_information_watch.borrow();
+                    trace!("Updating schemas to schema information {version}");
+                    Self::apply_schema_information(&schemas, subscription_controller.as_ref()).await?;
+                }
+            }
         }
+
+        Ok(())
     }
 
-    async fn fetch_and_update_schemas<SC>(
+    async fn apply_schema_information<SC>(
         schemas: &Schemas,
         subscription_controller: Option<&SC>,
-        cluster_ctrl_client: &mut ClusterCtrlSvcClient<Channel>,
     ) -> Result<(), SchemaError>
     where
         SC: SubscriptionController + Send + Sync,
     {
-        let schema_updates = Self::fetch_schemas(cluster_ctrl_client).await?;
-        update_schemas(schemas, subscription_controller, schema_updates).await?;
-
-        Ok(())
-    }
-
-    async fn fetch_schemas(
-        cluster_ctrl_client: &ClusterCtrlSvcClient<Channel>,
-    ) -> Result<Vec<SchemasUpdateCommand>, SchemaError> {
-        let response = RetryPolicy::exponential(Duration::from_millis(50), 2.0, 10, None)
-            .retry(|| {
-                let mut cluster_ctrl_client = cluster_ctrl_client.clone();
-                async move {
-                    cluster_ctrl_client
-                        // todo introduce schema version information to avoid fetching and overwriting the schema information
-                        //  over and over again
-                        .fetch_schemas(FetchSchemasRequest {})
-                        .await
-                }
-            })
-            .await?;
-
-        let (schema_updates, _) = bincode::serde::decode_from_slice::<Vec<SchemasUpdateCommand>, _>(
-            &response.into_inner().schemas_bin,
-            bincode::config::standard(),
-        )?;
-        Ok(schema_updates)
+        let schema_updates = restate_meta::decode_schema_updates(&metadata().schema_information())?;
+        update_schemas(schemas, subscription_controller, schema_updates).await
     }
 }
 
-pub async fn update_schemas<SC>(
+async fn update_schemas<SC>(
     schemas: &Schemas,
     subscription_controller: Option<&SC>,
     schema_updates: Vec<SchemasUpdateCommand>,
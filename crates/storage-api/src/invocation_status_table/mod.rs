@@ -15,12 +15,14 @@ use restate_types::identifiers::{
     DeploymentId, EntryIndex, FullInvocationId, InvocationId, PartitionKey, ServiceId,
 };
 use restate_types::invocation::{
-    ServiceInvocationResponseSink, ServiceInvocationSpanContext, Source,
+    AttachedResponseSink, ResponseResult, ServiceInvocationResponseSink,
+    ServiceInvocationSpanContext, Source,
 };
 use restate_types::time::MillisSinceEpoch;
 use std::collections::HashSet;
 use std::future::Future;
 use std::ops::RangeInclusive;
+use std::time::Duration;
 
 /// Holds timestamps of the [`InvocationStatus`].
 #[derive(Debug, Clone, PartialEq)]
@@ -71,6 +73,8 @@ pub enum InvocationStatus {
         metadata: InvocationMetadata,
         waiting_for_completed_entries: HashSet<EntryIndex>,
     },
+    /// Invocation has completed and its result is retained until the retention time expired
+    Completed(CompletedInvocation),
     /// Service instance is currently not invoked
     #[default]
     Free,
@@ -82,6 +86,7 @@ impl InvocationStatus {
         match self {
             InvocationStatus::Invoked(metadata) => Some(metadata.service_id.clone()),
             InvocationStatus::Suspended { metadata, .. } => Some(metadata.service_id.clone()),
+            InvocationStatus::Completed(completed) => Some(completed.service_id.clone()),
             _ => None,
         }
     }
@@ -91,7 +96,7 @@ impl InvocationStatus {
         match self {
             InvocationStatus::Invoked(metadata) => Some(metadata.journal_metadata),
             InvocationStatus::Suspended { metadata, .. } => Some(metadata.journal_metadata),
-            InvocationStatus::Free => None,
+            InvocationStatus::Completed(_) | InvocationStatus::Free => None,
         }
     }
 
@@ -100,7 +105,7 @@ impl InvocationStatus {
         match self {
             InvocationStatus::Invoked(metadata) => Some(&metadata.journal_metadata),
             InvocationStatus::Suspended { metadata, .. } => Some(&metadata.journal_metadata),
-            InvocationStatus::Free => None,
+            InvocationStatus::Completed(_) | InvocationStatus::Free => None,
         }
     }
 
@@ -109,7 +114,16 @@ impl InvocationStatus {
         match self {
             InvocationStatus::Invoked(metadata) => Some(&mut metadata.journal_metadata),
             InvocationStatus::Suspended { metadata, .. } => Some(&mut metadata.journal_metadata),
-            InvocationStatus::Free => None,
+            InvocationStatus::Completed(_) | InvocationStatus::Free => None,
+        }
+    }
+
+    #[inline]
+    pub fn get_invocation_metadata_mut(&mut self) -> Option<&mut InvocationMetadata> {
+        match self {
+            InvocationStatus::Invoked(metadata) => Some(metadata),
+            InvocationStatus::Suspended { metadata, .. } => Some(metadata),
+            InvocationStatus::Completed(_) | InvocationStatus::Free => None,
         }
     }
 
@@ -118,6 +132,7 @@ impl InvocationStatus {
         match self {
             InvocationStatus::Invoked(metadata) => Some(&metadata.timestamps),
             InvocationStatus::Suspended { metadata, .. } => Some(&metadata.timestamps),
+            InvocationStatus::Completed(completed) => Some(&completed.timestamps),
             InvocationStatus::Free => None,
         }
     }
@@ -126,6 +141,7 @@ impl InvocationStatus {
         match self {
             InvocationStatus::Invoked(metadata) => metadata.timestamps.update(),
             InvocationStatus::Suspended { metadata, .. } => metadata.timestamps.update(),
+            InvocationStatus::Completed(completed) => completed.timestamps.update(),
             InvocationStatus::Free => {}
         }
     }
@@ -158,8 +174,12 @@ pub struct InvocationMetadata {
     pub deployment_id: Option<DeploymentId>,
     pub method: ByteString,
     pub response_sink: Option<ServiceInvocationResponseSink>,
+    /// Ingress requests which attached to the invocation and are waiting for its result
+    pub attached_response_sinks: Vec<AttachedResponseSink>,
     pub timestamps: StatusTimestamps,
     pub source: Source,
+    /// Time for which the result is retained after the invocation completed
+    pub completion_retention_time: Duration,
 }
 
 impl InvocationMetadata {
@@ -172,6 +192,7 @@ impl InvocationMetadata {
         response_sink: Option<ServiceInvocationResponseSink>,
         timestamps: StatusTimestamps,
         source: Source,
+        completion_retention_time: Duration,
     ) -> Self {
         Self {
             service_id,
@@ -179,8 +200,40 @@ impl InvocationMetadata {
             deployment_id,
             method,
             response_sink,
+            attached_response_sinks: vec![],
             timestamps,
             source,
+            completion_retention_time,
+        }
+    }
+}
+
+/// Completed invocation whose result is retained.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletedInvocation {
+    pub service_id: ServiceId,
+    pub method: ByteString,
+    pub span_context: ServiceInvocationSpanContext,
+    pub source: Source,
+    /// Output or failure of the invocation
+    pub response_result: ResponseResult,
+    /// The modification time is the completion time of the invocation.
+    pub timestamps: StatusTimestamps,
+}
+
+impl CompletedInvocation {
+    /// Completes the invocation with the given result.
+    pub fn from_metadata(metadata: InvocationMetadata, response_result: ResponseResult) -> Self {
+        let mut timestamps = metadata.timestamps;
+        timestamps.update();
+
+        Self {
+            service_id: metadata.service_id,
+            method: metadata.method,
+            span_context: metadata.journal_metadata.span_context,
+            source: metadata.source,
+            response_result,
+            timestamps,
         }
     }
 }
@@ -222,8 +275,10 @@ mod mocks {
                 deployment_id: None,
                 method: ByteString::from("mock"),
                 response_sink: None,
+                attached_response_sinks: vec![],
                 timestamps: StatusTimestamps::now(),
                 source: Source::Ingress,
+                completion_retention_time: Duration::ZERO,
             }
         }
     }
@@ -45,6 +45,8 @@ impl Ord for TimerKey {
 pub enum Timer {
     CompleteSleepEntry(ServiceId),
     Invoke(ServiceInvocation),
+    /// Removes the status of a completed invocation once its retention time expired
+    CleanInvocationStatus(ServiceId),
 }
 
 impl Timer {
@@ -52,6 +54,7 @@ impl Timer {
         match self {
             CompleteSleepEntry(service_id) => service_id,
             Timer::Invoke(service_invocation) => &service_invocation.fid.service_id,
+            Timer::CleanInvocationStatus(service_id) => service_id,
         }
     }
 }
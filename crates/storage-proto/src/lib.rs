@@ -25,7 +25,7 @@ pub mod storage {
                 Awakeable, BackgroundCall, ClearAllState, ClearState, CompleteAwakeable, Custom,
                 GetState, GetStateKeys, Input, Invoke, Output, SetState, Sleep,
             };
-            use crate::storage::v1::invocation_status::{Free, Invoked, Suspended};
+            use crate::storage::v1::invocation_status::{Completed, Free, Invoked, Suspended};
             use crate::storage::v1::journal_entry::completion_result::{Empty, Failure, Success};
             use crate::storage::v1::journal_entry::{
                 completion_result, CompletionResult, Entry, Kind,
@@ -40,12 +40,13 @@ pub mod storage {
             use crate::storage::v1::{
                 enriched_entry_header, inbox_entry, invocation_resolution_result,
                 invocation_status, maybe_full_invocation_id, outbox_message, response_result,
-                service_status, source, span_relation, timer, BackgroundCallResolutionResult,
-                DedupSequenceNumber, EnrichedEntryHeader, EpochSequenceNumber, FullInvocationId,
-                Header, InboxEntry, InvocationResolutionResult, InvocationStatus, JournalEntry,
-                JournalMeta, KvPair, MaybeFullInvocationId, OutboxMessage, ResponseResult,
-                ServiceId, ServiceInvocation, ServiceInvocationResponseSink, ServiceStatus, Source,
-                SpanContext, SpanRelation, StateMutation, Timer,
+                service_status, source, span_relation, timer, AttachedResponseSink,
+                BackgroundCallResolutionResult, DedupSequenceNumber, EnrichedEntryHeader,
+                EpochSequenceNumber, FullInvocationId, Header, InboxEntry,
+                InvocationResolutionResult, InvocationStatus, JournalEntry, JournalMeta, KvPair,
+                MaybeFullInvocationId, OutboxMessage, ResponseResult, ServiceId, ServiceInvocation,
+                ServiceInvocationResponseSink, ServiceStatus, Source, SpanContext, SpanRelation,
+                StateMutation, Timer,
             };
             use anyhow::anyhow;
             use bytes::{Buf, Bytes};
@@ -59,6 +60,7 @@ pub mod storage {
             use restate_types::GenerationalNodeId;
             use std::collections::HashSet;
             use std::str::FromStr;
+            use std::time::Duration;
 
             /// Error type for conversion related problems (e.g. Rust <-> Protobuf)
             #[derive(Debug, thiserror::Error)]
@@ -149,6 +151,11 @@ pub mod storage {
                                 waiting_for_completed_entries,
                             }
                         }
+                        invocation_status::Status::Completed(completed) => {
+                            restate_storage_api::invocation_status_table::InvocationStatus::Completed(
+                                completed.try_into()?,
+                            )
+                        }
                         invocation_status::Status::Free(_) => {
                             restate_storage_api::invocation_status_table::InvocationStatus::Free
                         }
@@ -173,6 +180,9 @@ pub mod storage {
                             metadata,
                             waiting_for_completed_entries,
                         ))),
+                        restate_storage_api::invocation_status_table::InvocationStatus::Completed(
+                            completed,
+                        ) => invocation_status::Status::Completed(Completed::from(completed)),
                         restate_storage_api::invocation_status_table::InvocationStatus::Free => {
                             invocation_status::Status::Free(Free {})
                         }
@@ -221,13 +231,19 @@ pub mod storage {
                             .ok_or(ConversionError::missing_field("response_sink"))?,
                     )?;
 
+                    let attached_response_sinks = value
+                        .attached_response_sinks
+                        .into_iter()
+                        .map(restate_types::invocation::AttachedResponseSink::try_from)
+                        .collect::<Result<Vec<_>, _>>()?;
+
                     let source = restate_types::invocation::Source::try_from(
                         value
                             .source
                             .ok_or(ConversionError::missing_field("source"))?,
                     )?;
 
-                    Ok(
+                    let mut metadata =
                         restate_storage_api::invocation_status_table::InvocationMetadata::new(
                             service_id,
                             journal_metadata,
@@ -239,8 +255,11 @@ pub mod storage {
                                 MillisSinceEpoch::new(value.modification_time),
                             ),
                             source,
-                        ),
-                    )
+                            Duration::from_millis(value.completion_retention_time),
+                        );
+                    metadata.attached_response_sinks = attached_response_sinks;
+
+                    Ok(metadata)
                 }
             }
 
@@ -253,9 +272,11 @@ pub mod storage {
                         deployment_id,
                         method,
                         response_sink,
+                        attached_response_sinks,
                         journal_metadata,
                         timestamps,
                         source,
+                        completion_retention_time,
                     } = value;
 
                     Invoked {
@@ -272,6 +293,11 @@ pub mod storage {
                         creation_time: timestamps.creation_time().as_u64(),
                         modification_time: timestamps.modification_time().as_u64(),
                         source: Some(Source::from(source)),
+                        completion_retention_time: completion_retention_time.as_millis() as u64,
+                        attached_response_sinks: attached_response_sinks
+                            .into_iter()
+                            .map(AttachedResponseSink::from)
+                            .collect(),
                     }
                 }
             }
@@ -316,6 +342,12 @@ pub mod storage {
                             .ok_or(ConversionError::missing_field("response_sink"))?,
                     )?;
 
+                    let attached_response_sinks = value
+                        .attached_response_sinks
+                        .into_iter()
+                        .map(restate_types::invocation::AttachedResponseSink::try_from)
+                        .collect::<Result<Vec<_>, _>>()?;
+
                     let waiting_for_completed_entries =
                         value.waiting_for_completed_entries.into_iter().collect();
 
@@ -325,7 +357,7 @@ pub mod storage {
                             .ok_or(ConversionError::missing_field("source"))?,
                     )?;
 
-                    Ok((
+                    let mut metadata =
                         restate_storage_api::invocation_status_table::InvocationMetadata::new(
                             service_id,
                             journal_metadata,
@@ -337,9 +369,11 @@ pub mod storage {
                                 MillisSinceEpoch::new(value.modification_time),
                             ),
                             caller,
-                        ),
-                        waiting_for_completed_entries,
-                    ))
+                            Duration::from_millis(value.completion_retention_time),
+                        );
+                    metadata.attached_response_sinks = attached_response_sinks;
+
+                    Ok((metadata, waiting_for_completed_entries))
                 }
             }
 
@@ -377,6 +411,89 @@ pub mod storage {
                         modification_time: metadata.timestamps.modification_time().as_u64(),
                         waiting_for_completed_entries,
                         source: Some(Source::from(metadata.source)),
+                        completion_retention_time: metadata.completion_retention_time.as_millis()
+                            as u64,
+                        attached_response_sinks: metadata
+                            .attached_response_sinks
+                            .into_iter()
+                            .map(AttachedResponseSink::from)
+                            .collect(),
+                    }
+                }
+            }
+
+            impl TryFrom<Completed> for restate_storage_api::invocation_status_table::CompletedInvocation {
+                type Error = ConversionError;
+
+                fn try_from(value: Completed) -> Result<Self, Self::Error> {
+                    let service_id = value
+                        .service_id
+                        .ok_or(ConversionError::missing_field("service_id"))?
+                        .try_into()?;
+
+                    let method = value.method_name.try_into().map_err(|e| {
+                        ConversionError::InvalidData(anyhow!(
+                            "Cannot decode method_name string {e}"
+                        ))
+                    })?;
+
+                    let span_context =
+                        restate_types::invocation::ServiceInvocationSpanContext::try_from(
+                            value
+                                .span_context
+                                .ok_or(ConversionError::missing_field("span_context"))?,
+                        )?;
+
+                    let source = restate_types::invocation::Source::try_from(
+                        value
+                            .source
+                            .ok_or(ConversionError::missing_field("source"))?,
+                    )?;
+
+                    let response_result = restate_types::invocation::ResponseResult::try_from(
+                        value
+                            .result
+                            .ok_or(ConversionError::missing_field("result"))?,
+                    )?;
+
+                    Ok(
+                        restate_storage_api::invocation_status_table::CompletedInvocation {
+                            service_id,
+                            method,
+                            span_context,
+                            source,
+                            response_result,
+                            timestamps:
+                                restate_storage_api::invocation_status_table::StatusTimestamps::new(
+                                    MillisSinceEpoch::new(value.creation_time),
+                                    MillisSinceEpoch::new(value.modification_time),
+                                ),
+                        },
+                    )
+                }
+            }
+
+            impl From<restate_storage_api::invocation_status_table::CompletedInvocation> for Completed {
+                fn from(
+                    value: restate_storage_api::invocation_status_table::CompletedInvocation,
+                ) -> Self {
+                    let restate_storage_api::invocation_status_table::CompletedInvocation {
+                        service_id,
+                        method,
+                        span_context,
+                        source,
+                        response_result,
+                        timestamps,
+                    } = value;
+
+                    Completed {
+                        service_id: Some(service_id.into()),
+                        method_name: method.into_bytes(),
+                        span_context: Some(SpanContext::from(span_context)),
+                        source: Some(Source::from(source)),
+                        result: Some(ResponseResult::from(response_result)),
+                        creation_time: timestamps.creation_time().as_u64(),
+                        modification_time: timestamps.modification_time().as_u64(),
                     }
                 }
             }
@@ -521,6 +638,8 @@ pub mod storage {
                         source,
                         headers,
                         execution_time,
+                        completion_retention_time,
+                        attached_response_sinks,
                     } = value;
 
                     let id = restate_types::identifiers::FullInvocationId::try_from(
@@ -556,6 +675,11 @@ pub mod storage {
                         Some(MillisSinceEpoch::new(execution_time))
                     };
 
+                    let attached_response_sinks = attached_response_sinks
+                        .into_iter()
+                        .map(restate_types::invocation::AttachedResponseSink::try_from)
+                        .collect::<Result<Vec<_>, _>>()?;
+
                     Ok(restate_types::invocation::ServiceInvocation {
                         fid: id,
                         method_name,
@@ -565,6 +689,8 @@ pub mod storage {
                         span_context,
                         headers,
                         execution_time,
+                        completion_retention_time: Duration::from_millis(completion_retention_time),
+                        attached_response_sinks,
                     })
                 }
             }
@@ -590,6 +716,13 @@ pub mod storage {
                             .execution_time
                             .map(|m| m.as_u64())
                             .unwrap_or_default(),
+                        completion_retention_time: value.completion_retention_time.as_millis()
+                            as u64,
+                        attached_response_sinks: value
+                            .attached_response_sinks
+                            .into_iter()
+                            .map(AttachedResponseSink::from)
+                            .collect(),
                     }
                 }
             }
@@ -956,6 +1089,30 @@ pub mod storage {
                 }
             }
 
+            impl TryFrom<AttachedResponseSink> for restate_types::invocation::AttachedResponseSink {
+                type Error = ConversionError;
+
+                fn try_from(value: AttachedResponseSink) -> Result<Self, Self::Error> {
+                    let node_id = value
+                        .node_id
+                        .ok_or(ConversionError::missing_field("node_id"))?;
+
+                    Ok(restate_types::invocation::AttachedResponseSink {
+                        node_id: GenerationalNodeId::from(node_id),
+                        request_id: value.request_id,
+                    })
+                }
+            }
+
+            impl From<restate_types::invocation::AttachedResponseSink> for AttachedResponseSink {
+                fn from(value: restate_types::invocation::AttachedResponseSink) -> Self {
+                    AttachedResponseSink {
+                        node_id: Some(super::GenerationalNodeId::from(value.node_id)),
+                        request_id: value.request_id,
+                    }
+                }
+            }
+
             impl TryFrom<Header> for restate_types::invocation::Header {
                 type Error = ConversionError;
 
@@ -1585,6 +1742,11 @@ pub mod storage {
                                     restate_types::invocation::ServiceInvocation::try_from(si)?,
                                 )
                             }
+                            timer::Value::CleanInvocationStatus(service_id) => {
+                                restate_storage_api::timer_table::Timer::CleanInvocationStatus(
+                                    service_id.try_into()?,
+                                )
+                            }
                         },
                     )
                 }
@@ -1606,6 +1768,13 @@ pub mod storage {
                         restate_storage_api::timer_table::Timer::Invoke(si) => Timer {
                             value: Some(timer::Value::Invoke(ServiceInvocation::from(si))),
                         },
+                        restate_storage_api::timer_table::Timer::CleanInvocationStatus(
+                            service_id,
+                        ) => Timer {
+                            value: Some(timer::Value::CleanInvocationStatus(ServiceId::from(
+                                service_id,
+                            ))),
+                        },
                     }
                 }
             }
@@ -11,11 +11,12 @@
 use crate::invocation_status::schema::{InvocationStatusBuilder, InvocationStatusRowBuilder};
 use crate::table_util::format_using;
 use restate_storage_api::invocation_status_table::{
-    InvocationMetadata, InvocationStatus, JournalMetadata, StatusTimestamps,
+    CompletedInvocation, InvocationMetadata, InvocationStatus, JournalMetadata, StatusTimestamps,
 };
 use restate_storage_rocksdb::invocation_status_table::OwnedInvocationStatusRow;
+use restate_types::errors::InvocationError;
 use restate_types::identifiers::InvocationId;
-use restate_types::invocation::{Source, TraceId};
+use restate_types::invocation::{ResponseResult, Source, TraceId};
 
 #[inline]
 pub(crate) fn append_invocation_status_row(
@@ -59,6 +60,11 @@ pub(crate) fn append_invocation_status_row(
             row.status("suspended");
             Some(metadata)
         }
+        InvocationStatus::Completed(completed) => {
+            row.status("completed");
+            fill_completed_invocation(&mut row, output, completed);
+            None
+        }
         InvocationStatus::Free => {
             row.status("free");
             None
@@ -80,7 +86,40 @@ fn fill_invocation_metadata(
     if let Some(deployment_id) = meta.deployment_id {
         row.pinned_deployment_id(deployment_id.to_string());
     }
-    match meta.source {
+    fill_invoked_by(row, output, meta.source);
+}
+
+#[inline]
+fn fill_completed_invocation(
+    row: &mut InvocationStatusRowBuilder,
+    output: &mut String,
+    completed: CompletedInvocation,
+) {
+    // stats are filled by another function
+    row.handler(completed.method);
+    if row.is_trace_id_defined() {
+        let tid = completed.span_context.trace_id();
+        if tid != TraceId::INVALID {
+            row.trace_id(format_using(output, &tid));
+        }
+    }
+    fill_invoked_by(row, output, completed.source);
+    match completed.response_result {
+        ResponseResult::Success(_) => {
+            row.completion_result("success");
+        }
+        ResponseResult::Failure(code, message) => {
+            row.completion_result("failure");
+            if row.is_completion_failure_defined() {
+                row.completion_failure(format_using(output, &InvocationError::new(code, message)));
+            }
+        }
+    }
+}
+
+#[inline]
+fn fill_invoked_by(row: &mut InvocationStatusRowBuilder, output: &mut String, source: Source) {
+    match source {
         Source::Service(caller) => {
             row.invoked_by("component");
             row.invoked_by_component(&caller.service_id.service_name);
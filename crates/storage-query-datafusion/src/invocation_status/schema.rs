@@ -29,4 +29,6 @@ define_table!(invocation_status(
     journal_size: DataType::UInt32,
     created_at: DataType::Date64,
     modified_at: DataType::Date64,
+    completion_result: DataType::LargeUtf8,
+    completion_failure: DataType::LargeUtf8,
 ));
@@ -19,6 +19,7 @@ pub mod outbox_table;
 mod owned_iter;
 pub mod scan;
 pub mod service_status_table;
+pub mod snapshots;
 pub mod state_table;
 pub mod timer_table;
 mod writer;
@@ -121,6 +122,12 @@ impl TableKind {
         cf_name(*self)
     }
 
+    pub fn from_cf_name(cf_name: &str) -> Option<TableKind> {
+        Self::all()
+            .find(|table| table.cf_name() == cf_name)
+            .copied()
+    }
+
     pub fn all() -> core::slice::Iter<'static, TableKind> {
         static VARIANTS: &[TableKind] = &[
             State,
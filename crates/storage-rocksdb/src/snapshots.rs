@@ -0,0 +1,793 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Snapshots of the state of a single partition.
+//!
+//! A snapshot consists of one SST file per table which contains all keys of the partition, and a
+//! metadata file which records the applied LSN of the partition at the time of the snapshot. The
+//! snapshots are stored in a [`SnapshotRepository`] under
+//! `<repository>/<partition_id>/<applied_lsn>/`. A snapshot directory is only moved to its final
+//! location once all of its files have been written, so partially written snapshots are never
+//! picked up.
+
+use crate::TableKind::{
+    Deduplication, Inbox, InvocationStatus, Journal, Outbox, PartitionStateMachine, ServiceStatus,
+    State, Timers,
+};
+use crate::{RocksDBStorage, RocksDBTransaction, StorageAccess, TableKind, WriteBatch};
+use bytes::Bytes;
+use restate_storage_api::StorageError;
+use restate_types::identifiers::{PartitionId, PartitionKey};
+use restate_types::logs::Lsn;
+use rocksdb::{ReadOptions, SstFileWriter};
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const METADATA_FILE_NAME: &str = "metadata.json";
+const TMP_DIR_PREFIX: &str = ".tmp-";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotError {
+    #[error("rocksdb error: {0}")]
+    RocksDB(#[from] rocksdb::Error),
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid snapshot metadata: {0}")]
+    Metadata(#[from] serde_json::Error),
+    #[error("snapshot of partition {partition_id} contains unknown table '{table}'")]
+    UnknownTable {
+        partition_id: PartitionId,
+        table: String,
+    },
+}
+
+/// Describes the contents of a partition snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PartitionSnapshotMetadata {
+    pub partition_id: PartitionId,
+    /// Key range which the partition owned at the time of the snapshot. Partitions which own no
+    /// keys only snapshot the tables which are keyed by the partition id.
+    pub key_range: Option<RangeInclusive<PartitionKey>>,
+    /// Lsn of the last log record which is reflected in the snapshot.
+    pub applied_lsn: Lsn,
+    pub created_at: SystemTime,
+    pub files: Vec<SnapshotFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SnapshotFile {
+    /// Name of the column family to which the file belongs.
+    pub table: String,
+    pub file_name: String,
+    pub size: u64,
+}
+
+/// A complete snapshot in a [`SnapshotRepository`].
+#[derive(Debug, Clone)]
+pub struct PartitionSnapshot {
+    pub path: PathBuf,
+    pub metadata: PartitionSnapshotMetadata,
+}
+
+/// Directory in which the snapshots of all partitions are stored. The directory can be shared by
+/// several nodes, e.g. by mounting an object store bucket, so that partition processors can
+/// bootstrap from the snapshots which other nodes have created. Object stores are only supported
+/// through such a mount, which needs to support renaming directories.
+#[derive(Debug, Clone)]
+pub struct SnapshotRepository {
+    base_dir: PathBuf,
+}
+
+impl SnapshotRepository {
+    pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: base_dir.into(),
+        }
+    }
+
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    fn partition_dir(&self, partition_id: PartitionId) -> PathBuf {
+        self.base_dir.join(partition_id.to_string())
+    }
+
+    /// Creates a snapshot of the partition, tagged with the given applied lsn. The caller must
+    /// make sure that no records of the partition are applied while the snapshot is created. If
+    /// the repository already contains a snapshot of the partition at this lsn, e.g. because
+    /// another node created it, the existing snapshot is returned.
+    pub fn create(
+        &self,
+        storage: &RocksDBStorage,
+        partition_id: PartitionId,
+        key_range: Option<RangeInclusive<PartitionKey>>,
+        applied_lsn: Lsn,
+    ) -> Result<PartitionSnapshot, SnapshotError> {
+        let partition_dir = self.partition_dir(partition_id);
+        fs::create_dir_all(&partition_dir)?;
+
+        let final_dir = partition_dir.join(snapshot_dir_name(applied_lsn));
+        if final_dir.exists() {
+            return read_snapshot(final_dir);
+        }
+
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let tmp_dir = partition_dir.join(format!(
+            "{TMP_DIR_PREFIX}{}-{nanos}",
+            u64::from(applied_lsn)
+        ));
+
+        let metadata = match storage.write_partition_snapshot(
+            partition_id,
+            key_range,
+            applied_lsn,
+            &tmp_dir,
+        ) {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                let _ = fs::remove_dir_all(&tmp_dir);
+                return Err(err);
+            }
+        };
+
+        if let Err(err) = fs::rename(&tmp_dir, &final_dir) {
+            let _ = fs::remove_dir_all(&tmp_dir);
+            // somebody else was faster in creating the same snapshot
+            return if final_dir.exists() {
+                read_snapshot(final_dir)
+            } else {
+                Err(err.into())
+            };
+        }
+
+        Ok(PartitionSnapshot {
+            path: final_dir,
+            metadata,
+        })
+    }
+
+    /// Lists the complete snapshots of the partition, ordered by their applied lsn.
+    pub fn list(&self, partition_id: PartitionId) -> Result<Vec<PartitionSnapshot>, SnapshotError> {
+        let entries = match fs::read_dir(self.partition_dir(partition_id)) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut snapshots = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(TMP_DIR_PREFIX)
+            {
+                continue;
+            }
+            match read_snapshot(entry.path()) {
+                Ok(snapshot) => snapshots.push(snapshot),
+                // the snapshot might have been pruned concurrently
+                Err(SnapshotError::Io(err)) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        snapshots.sort_by_key(|snapshot| snapshot.metadata.applied_lsn);
+        Ok(snapshots)
+    }
+
+    /// Returns the snapshot of the partition with the highest applied lsn.
+    pub fn latest(
+        &self,
+        partition_id: PartitionId,
+    ) -> Result<Option<PartitionSnapshot>, SnapshotError> {
+        Ok(self.list(partition_id)?.pop())
+    }
+
+    /// Removes all but the `retain` latest snapshots of the partition.
+    pub fn prune(&self, partition_id: PartitionId, retain: usize) -> Result<(), SnapshotError> {
+        let snapshots = self.list(partition_id)?;
+        let prune_count = snapshots.len().saturating_sub(retain);
+
+        for snapshot in snapshots.into_iter().take(prune_count) {
+            match fs::remove_dir_all(&snapshot.path) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Lsns are zero-padded so that the snapshot directories sort by their applied lsn.
+fn snapshot_dir_name(applied_lsn: Lsn) -> String {
+    format!("{:020}", u64::from(applied_lsn))
+}
+
+fn read_snapshot(path: PathBuf) -> Result<PartitionSnapshot, SnapshotError> {
+    let metadata = serde_json::from_slice(&fs::read(path.join(METADATA_FILE_NAME))?)?;
+    Ok(PartitionSnapshot { path, metadata })
+}
+
+/// Whether the keys of the table start with the partition key. The other tables are keyed by
+/// partition id.
+fn is_keyed_by_partition_key(table: TableKind) -> bool {
+    matches!(
+        table,
+        State | InvocationStatus | ServiceStatus | Inbox | Journal
+    )
+}
+
+/// Returns the bounds of the keys of the key range in a table which is keyed by partition key, as
+/// start key and exclusive end key.
+fn key_range_bounds(key_range: &RangeInclusive<PartitionKey>) -> (Vec<u8>, Option<Vec<u8>>) {
+    (
+        key_range.start().to_be_bytes().to_vec(),
+        key_range
+            .end()
+            .checked_add(1)
+            .map(|end| end.to_be_bytes().to_vec()),
+    )
+}
+
+/// Returns the bounds of the keys of the table which belong to the partition, as start key and
+/// exclusive end key. Returns `None` if the partition owns no keys of the table.
+fn partition_key_bounds(
+    table: TableKind,
+    partition_id: PartitionId,
+    key_range: Option<&RangeInclusive<PartitionKey>>,
+) -> Option<(Vec<u8>, Option<Vec<u8>>)> {
+    if is_keyed_by_partition_key(table) {
+        key_range.map(key_range_bounds)
+    } else {
+        debug_assert!(matches!(
+            table,
+            Outbox | Timers | Deduplication | PartitionStateMachine
+        ));
+        Some((
+            partition_id.to_be_bytes().to_vec(),
+            partition_id
+                .checked_add(1)
+                .map(|next| next.to_be_bytes().to_vec()),
+        ))
+    }
+}
+
+fn read_options(start: Vec<u8>, end: Option<Vec<u8>>) -> ReadOptions {
+    let mut opts = ReadOptions::default();
+    match end {
+        Some(end) => opts.set_iterate_range(start..end),
+        None => opts.set_iterate_range(start..),
+    }
+    opts
+}
+
+impl RocksDBStorage {
+    /// Writes the keys of the partition into one SST file per table, followed by the snapshot
+    /// metadata. All tables are read from the same RocksDB snapshot.
+    fn write_partition_snapshot(
+        &self,
+        partition_id: PartitionId,
+        key_range: Option<RangeInclusive<PartitionKey>>,
+        applied_lsn: Lsn,
+        dir: &Path,
+    ) -> Result<PartitionSnapshotMetadata, SnapshotError> {
+        fs::create_dir_all(dir)?;
+
+        let db_snapshot = self.db.snapshot();
+        let sst_options = rocksdb::Options::default();
+        let mut files = Vec::new();
+
+        for table in TableKind::all() {
+            let Some((start, end)) = partition_key_bounds(*table, partition_id, key_range.as_ref())
+            else {
+                continue;
+            };
+
+            let mut iterator = db_snapshot
+                .raw_iterator_cf_opt(self.table_handle(*table), read_options(start.clone(), end));
+            iterator.seek(start);
+
+            let file_name = format!("{}.sst", table.cf_name());
+            let mut writer = SstFileWriter::create(&sst_options);
+            let mut is_empty = true;
+            while let Some((key, value)) = iterator.item() {
+                if is_empty {
+                    writer.open(dir.join(&file_name))?;
+                    is_empty = false;
+                }
+                writer.put(key, value)?;
+                iterator.next();
+            }
+            iterator.status()?;
+
+            // RocksDB cannot create SST files without entries
+            if !is_empty {
+                writer.finish()?;
+                files.push(SnapshotFile {
+                    table: table.cf_name().to_owned(),
+                    file_name,
+                    size: writer.file_size(),
+                });
+            }
+        }
+
+        let metadata = PartitionSnapshotMetadata {
+            partition_id,
+            key_range,
+            applied_lsn,
+            created_at: SystemTime::now(),
+            files,
+        };
+        fs::write(
+            dir.join(METADATA_FILE_NAME),
+            serde_json::to_vec_pretty(&metadata)?,
+        )?;
+
+        Ok(metadata)
+    }
+
+    /// Replaces the local state of the partition with the given snapshot. The partition must not
+    /// be running while it is restored.
+    ///
+    /// `key_range` is the range of keys which the partition owns in this partition store. Only
+    /// these keys are replaced, because the keys outside of it belong to other partitions which
+    /// share the store, even if the snapshot still contains them.
+    ///
+    /// The partition state machine table is ingested last, so that a restore which is interrupted
+    /// leaves the partition without an applied lsn, or with an older one, and is repeated.
+    pub fn restore_partition_snapshot(
+        &self,
+        snapshot: &PartitionSnapshot,
+        key_range: Option<&RangeInclusive<PartitionKey>>,
+    ) -> Result<(), SnapshotError> {
+        let metadata = &snapshot.metadata;
+
+        // remove the current keys of the partition, since the snapshot only contains the keys
+        // which existed when it was taken
+        let mut write_batch = WriteBatch::default();
+        for table in TableKind::all() {
+            let Some((start, end)) = partition_key_bounds(*table, metadata.partition_id, key_range)
+            else {
+                continue;
+            };
+
+            let table_handle = self.table_handle(*table);
+            let mut iterator = self
+                .db
+                .raw_iterator_cf_opt(table_handle, read_options(start.clone(), end));
+            iterator.seek(start);
+            while let Some(key) = iterator.key() {
+                write_batch.delete_cf(table_handle, key);
+                iterator.next();
+            }
+            iterator.status()?;
+        }
+        self.db.write(write_batch)?;
+        self.db.flush_wal(true)?;
+
+        // the snapshot's files can only be ingested as a whole if all of their keys are owned
+        let is_owned = |snapshot_key_range: &RangeInclusive<PartitionKey>| {
+            key_range.is_some_and(|key_range| {
+                key_range.start() <= snapshot_key_range.start()
+                    && snapshot_key_range.end() <= key_range.end()
+            })
+        };
+
+        let mut files: Vec<_> = metadata.files.iter().collect();
+        files.sort_by_key(|file| file.table == PartitionStateMachine.cf_name());
+
+        for file in files {
+            let table = TableKind::from_cf_name(&file.table).ok_or_else(|| {
+                SnapshotError::UnknownTable {
+                    partition_id: metadata.partition_id,
+                    table: file.table.clone(),
+                }
+            })?;
+            let path = snapshot.path.join(&file.file_name);
+
+            match (
+                is_keyed_by_partition_key(table),
+                metadata.key_range.as_ref(),
+                key_range,
+            ) {
+                (false, _, _) => {
+                    self.db
+                        .ingest_external_file_cf(self.table_handle(table), vec![path])?;
+                }
+                (true, Some(snapshot_key_range), _) if is_owned(snapshot_key_range) => {
+                    self.db
+                        .ingest_external_file_cf(self.table_handle(table), vec![path])?;
+                }
+                (true, _, Some(key_range)) => {
+                    self.ingest_key_range(metadata.partition_id, table, &path, key_range)?;
+                }
+                // the partition owns none of the keys in this store anymore
+                (true, _, None) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Copies the entries of the SST file which belong to the key range into the table. RocksDB
+    /// can only ingest SST files as a whole, so the file is ingested into a temporary database
+    /// first, from which the entries of the key range are read.
+    fn ingest_key_range(
+        &self,
+        partition_id: PartitionId,
+        table: TableKind,
+        file: &Path,
+        key_range: &RangeInclusive<PartitionKey>,
+    ) -> Result<(), SnapshotError> {
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let tmp_dir = self.db.path().with_file_name(format!(
+            "{TMP_DIR_PREFIX}restore-{partition_id}-{}-{nanos}",
+            table.cf_name()
+        ));
+
+        let result = (|| -> Result<(), SnapshotError> {
+            let tmp_db = rocksdb::DB::open_default(&tmp_dir)?;
+            tmp_db.ingest_external_file(vec![file])?;
+
+            let (start, end) = key_range_bounds(key_range);
+            let table_handle = self.table_handle(table);
+            let mut write_batch = WriteBatch::default();
+            let mut iterator = tmp_db.raw_iterator_opt(read_options(start.clone(), end));
+            iterator.seek(start);
+            while let Some((key, value)) = iterator.item() {
+                write_batch.put_cf(table_handle, key, value);
+                iterator.next();
+            }
+            iterator.status()?;
+
+            self.db.write(write_batch)?;
+            Ok(())
+        })();
+
+        let _ = fs::remove_dir_all(&tmp_dir);
+        result
+    }
+}
+
+impl RocksDBStorage {
+    /// Reads the entries of the key range from the tables which are keyed by partition key, so
+    /// that another partition store can take over the keys with
+    /// [`RocksDBTransaction::import_key_range`]. All tables are read from the same RocksDB
+    /// snapshot.
+    pub fn export_key_range(
+        &self,
+        key_range: &RangeInclusive<PartitionKey>,
+    ) -> Result<Vec<(TableKind, Vec<(Bytes, Bytes)>)>, StorageError> {
+        let db_snapshot = self.db.snapshot();
+        let mut tables = Vec::new();
+
+        for table in TableKind::all().filter(|table| is_keyed_by_partition_key(**table)) {
+            let (start, end) = key_range_bounds(key_range);
+            let mut iterator = db_snapshot
+                .raw_iterator_cf_opt(self.table_handle(*table), read_options(start.clone(), end));
+            iterator.seek(start);
+
+            let mut entries = Vec::new();
+            while let Some((key, value)) = iterator.item() {
+                entries.push((Bytes::copy_from_slice(key), Bytes::copy_from_slice(value)));
+                iterator.next();
+            }
+            iterator
+                .status()
+                .map_err(|err| StorageError::Generic(err.into()))?;
+
+            if !entries.is_empty() {
+                tables.push((*table, entries));
+            }
+        }
+
+        Ok(tables)
+    }
+}
+
+impl<'a> RocksDBTransaction<'a> {
+    /// Replaces the entries of the key range in the tables which are keyed by partition key with
+    /// the entries which have been exported by [`RocksDBStorage::export_key_range`]. Entries which
+    /// are not part of the key range are ignored.
+    pub fn import_key_range(
+        &mut self,
+        key_range: &RangeInclusive<PartitionKey>,
+        tables: &[(TableKind, Vec<(Bytes, Bytes)>)],
+    ) -> Result<(), StorageError> {
+        let (start, end) = key_range_bounds(key_range);
+        let is_in_key_range = |key: &[u8]| {
+            key >= start.as_slice() && end.as_ref().map_or(true, |end| key < end.as_slice())
+        };
+
+        for table in TableKind::all().filter(|table| is_keyed_by_partition_key(**table)) {
+            let mut stale_keys = Vec::new();
+            let mut iterator = match end.clone() {
+                Some(end) => self.range_iterator(*table, start.clone()..end),
+                None => self.range_iterator(*table, start.clone()..),
+            };
+            iterator.seek(&start);
+            while let Some(key) = iterator.key() {
+                stale_keys.push(key.to_vec());
+                iterator.next();
+            }
+            iterator
+                .status()
+                .map_err(|err| StorageError::Generic(err.into()))?;
+            drop(iterator);
+
+            for key in stale_keys {
+                self.delete_cf(*table, key);
+            }
+        }
+
+        for (table, entries) in tables {
+            if !is_keyed_by_partition_key(*table) {
+                continue;
+            }
+            for (key, value) in entries {
+                if is_in_key_range(key) {
+                    self.put_cf(*table, key, value);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Options;
+    use restate_storage_api::Transaction;
+    use tempfile::tempdir;
+
+    fn partition_key(key: PartitionKey) -> Vec<u8> {
+        key.to_be_bytes().to_vec()
+    }
+
+    fn partition_id_key(partition_id: PartitionId, suffix: u64) -> Vec<u8> {
+        let mut key = partition_id.to_be_bytes().to_vec();
+        key.extend_from_slice(&suffix.to_be_bytes());
+        key
+    }
+
+    fn storage(path: PathBuf) -> RocksDBStorage {
+        let opts = Options {
+            path,
+            ..Default::default()
+        };
+        let (storage, _) = opts
+            .build()
+            .expect("RocksDB storage creation should succeed");
+        storage
+    }
+
+    fn get(storage: &RocksDBStorage, table: TableKind, key: &[u8]) -> Option<Vec<u8>> {
+        storage
+            .get(table, key)
+            .unwrap()
+            .map(|value| value.as_ref().to_vec())
+    }
+
+    #[test]
+    fn snapshot_and_restore_partition() {
+        let db_dir = tempdir().unwrap();
+        let snapshots_dir = tempdir().unwrap();
+        let repository = SnapshotRepository::new(snapshots_dir.path());
+
+        let mut source = storage(db_dir.path().join("source"));
+        // partition 1 owns keys 0..=99, partition 2 owns keys 100..=199
+        source.put_cf(State, partition_key(10), b"state-1");
+        source.put_cf(Journal, partition_key(99), b"journal-1");
+        source.put_cf(State, partition_key(100), b"state-2");
+        source.put_cf(Outbox, partition_id_key(1, 0), b"outbox-1");
+        source.put_cf(Outbox, partition_id_key(2, 0), b"outbox-2");
+        source.put_cf(PartitionStateMachine, partition_id_key(1, 2), b"fsm-1");
+
+        let snapshot = repository
+            .create(&source, 1, Some(0..=99), Lsn::from(42))
+            .unwrap();
+        assert_eq!(snapshot.metadata.applied_lsn, Lsn::from(42));
+        assert_eq!(snapshot.metadata.files.len(), 4);
+
+        let mut target = storage(db_dir.path().join("target"));
+        // stale keys of partition 1 are removed by the restore, keys of partition 2 are kept
+        target.put_cf(State, partition_key(11), b"stale-1");
+        target.put_cf(State, partition_key(150), b"state-2");
+
+        let latest = repository.latest(1).unwrap().unwrap();
+        assert_eq!(latest.metadata, snapshot.metadata);
+        target
+            .restore_partition_snapshot(&latest, Some(&(0..=99)))
+            .unwrap();
+
+        assert_eq!(
+            get(&target, State, &partition_key(10)),
+            Some(b"state-1".to_vec())
+        );
+        assert_eq!(
+            get(&target, Journal, &partition_key(99)),
+            Some(b"journal-1".to_vec())
+        );
+        assert_eq!(
+            get(&target, Outbox, &partition_id_key(1, 0)),
+            Some(b"outbox-1".to_vec())
+        );
+        assert_eq!(
+            get(&target, PartitionStateMachine, &partition_id_key(1, 2)),
+            Some(b"fsm-1".to_vec())
+        );
+        assert_eq!(get(&target, State, &partition_key(11)), None);
+        assert_eq!(get(&target, State, &partition_key(100)), None);
+        assert_eq!(get(&target, Outbox, &partition_id_key(2, 0)), None);
+        assert_eq!(
+            get(&target, State, &partition_key(150)),
+            Some(b"state-2".to_vec())
+        );
+    }
+
+    #[test]
+    fn restore_only_replaces_the_owned_keys() {
+        let db_dir = tempdir().unwrap();
+        let snapshots_dir = tempdir().unwrap();
+        let repository = SnapshotRepository::new(snapshots_dir.path());
+
+        let mut source = storage(db_dir.path().join("source"));
+        source.put_cf(State, partition_key(10), b"state-1");
+        source.put_cf(State, partition_key(60), b"old-state-2");
+        source.put_cf(PartitionStateMachine, partition_id_key(1, 2), b"fsm-1");
+
+        let snapshot = repository
+            .create(&source, 1, Some(0..=99), Lsn::from(42))
+            .unwrap();
+
+        // partition 1 has handed over keys 50..=99 to partition 2, which runs on the same node
+        let mut target = storage(db_dir.path().join("target"));
+        target.put_cf(State, partition_key(11), b"stale-1");
+        target.put_cf(State, partition_key(60), b"state-2");
+        target.put_cf(State, partition_key(70), b"state-2");
+
+        target
+            .restore_partition_snapshot(&snapshot, Some(&(0..=49)))
+            .unwrap();
+
+        assert_eq!(
+            get(&target, State, &partition_key(10)),
+            Some(b"state-1".to_vec())
+        );
+        assert_eq!(get(&target, State, &partition_key(11)), None);
+        assert_eq!(
+            get(&target, State, &partition_key(60)),
+            Some(b"state-2".to_vec())
+        );
+        assert_eq!(
+            get(&target, State, &partition_key(70)),
+            Some(b"state-2".to_vec())
+        );
+        assert_eq!(
+            get(&target, PartitionStateMachine, &partition_id_key(1, 2)),
+            Some(b"fsm-1".to_vec())
+        );
+    }
+
+    #[tokio::test]
+    async fn export_and_import_key_range_between_stores() {
+        let db_dir = tempdir().unwrap();
+
+        // the partition which hands over keys 50..=99 and the one which takes them over run on
+        // different nodes and don't share a partition store
+        let mut source = storage(db_dir.path().join("source"));
+        source.put_cf(State, partition_key(10), b"state-1");
+        source.put_cf(State, partition_key(50), b"state-2");
+        source.put_cf(Inbox, partition_key(99), b"inbox-2");
+        source.put_cf(Timers, partition_id_key(1, 0), b"timer-1");
+
+        let opts = Options {
+            path: db_dir.path().join("target"),
+            ..Default::default()
+        };
+        let (mut target, writer) = opts
+            .build()
+            .expect("RocksDB storage creation should succeed");
+        let (signal, watch) = drain::channel();
+        let writer_join_handle = writer.run(watch);
+
+        target.put_cf(State, partition_key(60), b"stale-2");
+        target.put_cf(State, partition_key(100), b"state-3");
+
+        let tables = source.export_key_range(&(50..=99)).unwrap();
+        assert_eq!(
+            tables,
+            vec![
+                (
+                    State,
+                    vec![(
+                        Bytes::from(partition_key(50)),
+                        Bytes::from_static(b"state-2")
+                    )]
+                ),
+                (
+                    Inbox,
+                    vec![(
+                        Bytes::from(partition_key(99)),
+                        Bytes::from_static(b"inbox-2")
+                    )]
+                ),
+            ]
+        );
+
+        let mut transaction = target.transaction();
+        transaction.import_key_range(&(50..=99), &tables).unwrap();
+        transaction.commit().await.unwrap();
+
+        assert_eq!(
+            get(&target, State, &partition_key(50)),
+            Some(b"state-2".to_vec())
+        );
+        assert_eq!(
+            get(&target, Inbox, &partition_key(99)),
+            Some(b"inbox-2".to_vec())
+        );
+        assert_eq!(get(&target, State, &partition_key(60)), None);
+        assert_eq!(get(&target, State, &partition_key(10)), None);
+        assert_eq!(get(&target, Timers, &partition_id_key(1, 0)), None);
+        assert_eq!(
+            get(&target, State, &partition_key(100)),
+            Some(b"state-3".to_vec())
+        );
+
+        signal.drain().await;
+        writer_join_handle.await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn prune_retains_latest_snapshots() {
+        let db_dir = tempdir().unwrap();
+        let snapshots_dir = tempdir().unwrap();
+        let repository = SnapshotRepository::new(snapshots_dir.path());
+
+        let mut storage = storage(db_dir.path().to_path_buf());
+        storage.put_cf(PartitionStateMachine, partition_id_key(1, 2), b"fsm-1");
+
+        for lsn in [5, 10, 15] {
+            repository
+                .create(&storage, 1, None, Lsn::from(lsn))
+                .unwrap();
+        }
+        // creating an existing snapshot again returns the existing one
+        let existing = repository.create(&storage, 1, None, Lsn::from(10)).unwrap();
+        assert_eq!(existing.metadata.applied_lsn, Lsn::from(10));
+        assert_eq!(repository.list(1).unwrap().len(), 3);
+
+        repository.prune(1, 2).unwrap();
+
+        let applied_lsns: Vec<_> = repository
+            .list(1)
+            .unwrap()
+            .into_iter()
+            .map(|snapshot| snapshot.metadata.applied_lsn)
+            .collect();
+        assert_eq!(applied_lsns, vec![Lsn::from(10), Lsn::from(15)]);
+        assert!(repository.latest(2).unwrap().is_none());
+    }
+}
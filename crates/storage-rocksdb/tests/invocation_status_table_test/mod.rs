@@ -9,17 +9,20 @@
 // by the Apache License, Version 2.0.
 
 use crate::assert_stream_eq;
+use bytes::Bytes;
 use once_cell::sync::Lazy;
 use restate_storage_api::invocation_status_table::{
-    InvocationMetadata, InvocationStatus, InvocationStatusTable, JournalMetadata, StatusTimestamps,
+    CompletedInvocation, InvocationMetadata, InvocationStatus, InvocationStatusTable,
+    JournalMetadata, StatusTimestamps,
 };
 use restate_storage_rocksdb::RocksDBStorage;
 use restate_types::identifiers::{
     FullInvocationId, InvocationId, InvocationUuid, ServiceId, WithPartitionKey,
 };
-use restate_types::invocation::{ServiceInvocationSpanContext, Source};
+use restate_types::invocation::{ResponseResult, ServiceInvocationSpanContext, Source};
 use restate_types::time::MillisSinceEpoch;
 use std::collections::HashSet;
+use std::time::Duration;
 
 static SERVICE_ID_1: Lazy<ServiceId> = Lazy::new(|| ServiceId::new("abc", "1"));
 static INVOCATION_ID_1: Lazy<InvocationId> = Lazy::new(|| {
@@ -45,6 +48,14 @@ static INVOCATION_ID_3: Lazy<InvocationId> = Lazy::new(|| {
     )
 });
 
+static SERVICE_ID_4: Lazy<ServiceId> = Lazy::new(|| ServiceId::new("abc", "4"));
+static INVOCATION_ID_4: Lazy<InvocationId> = Lazy::new(|| {
+    InvocationId::new(
+        SERVICE_ID_4.partition_key(),
+        InvocationUuid::from_parts(1706027034946, 12345678900004),
+    )
+});
+
 fn invoked_status(service_id: impl Into<ServiceId>) -> InvocationStatus {
     InvocationStatus::Invoked(InvocationMetadata::new(
         service_id.into(),
@@ -54,6 +65,7 @@ fn invoked_status(service_id: impl Into<ServiceId>) -> InvocationStatus {
         None,
         StatusTimestamps::new(MillisSinceEpoch::new(0), MillisSinceEpoch::new(0)),
         Source::Ingress,
+        Duration::from_secs(60),
     ))
 }
 
@@ -67,11 +79,23 @@ fn suspended_status(service_id: impl Into<ServiceId>) -> InvocationStatus {
             None,
             StatusTimestamps::new(MillisSinceEpoch::new(0), MillisSinceEpoch::new(0)),
             Source::Ingress,
+            Duration::ZERO,
         ),
         waiting_for_completed_entries: HashSet::default(),
     }
 }
 
+fn completed_status(service_id: impl Into<ServiceId>) -> InvocationStatus {
+    InvocationStatus::Completed(CompletedInvocation {
+        service_id: service_id.into(),
+        method: "service".into(),
+        span_context: ServiceInvocationSpanContext::empty(),
+        source: Source::Ingress,
+        response_result: ResponseResult::Success(Bytes::from_static(b"output")),
+        timestamps: StatusTimestamps::new(MillisSinceEpoch::new(0), MillisSinceEpoch::new(1)),
+    })
+}
+
 async fn populate_data<T: InvocationStatusTable>(txn: &mut T) {
     txn.put_invocation_status(&INVOCATION_ID_1, invoked_status(SERVICE_ID_1.clone()))
         .await;
@@ -81,6 +105,9 @@ async fn populate_data<T: InvocationStatusTable>(txn: &mut T) {
 
     txn.put_invocation_status(&INVOCATION_ID_3, suspended_status(SERVICE_ID_3.clone()))
         .await;
+
+    txn.put_invocation_status(&INVOCATION_ID_4, completed_status(SERVICE_ID_4.clone()))
+        .await;
 }
 
 async fn verify_point_lookups<T: InvocationStatusTable>(txn: &mut T) {
@@ -90,6 +117,13 @@ async fn verify_point_lookups<T: InvocationStatusTable>(txn: &mut T) {
         .expect("should not fail");
 
     assert_eq!(status, invoked_status(SERVICE_ID_1.clone()));
+
+    let status = txn
+        .get_invocation_status(&INVOCATION_ID_4)
+        .await
+        .expect("should not fail");
+
+    assert_eq!(status, completed_status(SERVICE_ID_4.clone()));
 }
 
 async fn verify_all_svc_with_status_invoked<T: InvocationStatusTable>(txn: &mut T) {
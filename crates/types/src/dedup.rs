@@ -41,6 +41,16 @@ impl DedupInformation {
             sequence_number: DedupSequenceNumber::Sn(sequence_number),
         }
     }
+
+    /// Deduplication information for records which a partition forwards to the partition which
+    /// has taken over their keys. The sequence number is the record's lsn in the forwarding
+    /// partition's log.
+    pub fn forwarded(producer_id: PartitionId, lsn: u64) -> Self {
+        DedupInformation {
+            producer_id: ProducerId::Other(format!("forwarded-from-{producer_id}").into()),
+            sequence_number: DedupSequenceNumber::Sn(lsn),
+        }
+    }
 }
 
 static SELF_PRODUCER: ByteString = ByteString::from_static("SELF");
@@ -76,6 +76,7 @@ pub mod codes {
     pub const UNKNOWN: InvocationErrorCode = INTERNAL;
     pub const ABORTED: InvocationErrorCode = InvocationErrorCode(409);
     pub const KILLED: InvocationErrorCode = ABORTED;
+    pub const NOT_READY: InvocationErrorCode = InvocationErrorCode(470);
     pub const JOURNAL_MISMATCH: InvocationErrorCode = InvocationErrorCode(570);
     pub const PROTOCOL_VIOLATION: InvocationErrorCode = InvocationErrorCode(571);
 }
@@ -203,6 +204,13 @@ pub const KILLED_INVOCATION_ERROR: InvocationError =
 pub const CANCELED_INVOCATION_ERROR: InvocationError =
     InvocationError::new_static(codes::ABORTED, "canceled");
 
+pub const NOT_FOUND_INVOCATION_ERROR: InvocationError =
+    InvocationError::new_static(codes::NOT_FOUND, "invocation not found");
+
+/// The result of the invocation is not available yet, because it hasn't completed.
+pub const NOT_READY_INVOCATION_ERROR: InvocationError =
+    InvocationError::new_static(codes::NOT_READY, "invocation not ready");
+
 /// Error parsing/decoding a resource ID.
 #[derive(Debug, thiserror::Error, Clone, Eq, PartialEq)]
 pub enum IdDecodeError {
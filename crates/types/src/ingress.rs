@@ -8,7 +8,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use crate::identifiers::FullInvocationId;
+use crate::identifiers::{FullInvocationId, InvocationId};
 use crate::invocation::ResponseResult;
 use crate::GenerationalNodeId;
 
@@ -19,3 +19,13 @@ pub struct IngressResponse {
     pub full_invocation_id: FullInvocationId,
     pub response: ResponseResult,
 }
+
+/// Response to a request received at an ingress, which attached to an invocation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IngressAttachResponse {
+    pub target_node: GenerationalNodeId,
+    pub request_id: u64,
+    pub invocation_id: InvocationId,
+    pub response: ResponseResult,
+}
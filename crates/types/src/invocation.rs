@@ -22,6 +22,7 @@ use opentelemetry_api::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags,
 use opentelemetry_api::Context;
 use std::fmt;
 use std::str::FromStr;
+use std::time::Duration;
 use tracing::Span;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
@@ -44,6 +45,14 @@ pub struct ServiceInvocation {
     pub headers: Vec<Header>,
     /// Time when the request should be executed
     pub execution_time: Option<MillisSinceEpoch>,
+    /// Time for which the result of the invocation is retained after it completed. A zero
+    /// duration doesn't retain the result.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub completion_retention_time: Duration,
+    /// Requests which attached to the invocation while it was waiting in the inbox. They are
+    /// moved to the invocation status once the invocation is invoked.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attached_response_sinks: Vec<AttachedResponseSink>,
 }
 
 impl ServiceInvocation {
@@ -74,6 +83,8 @@ impl ServiceInvocation {
             span_context,
             headers,
             execution_time,
+            completion_retention_time: Duration::ZERO,
+            attached_response_sinks: vec![],
         }
     }
 }
@@ -193,6 +204,47 @@ pub enum ServiceInvocationResponseSink {
     Ingress(GenerationalNodeId),
 }
 
+/// Response sink of a request received at an ingress, which attached to an invocation and is
+/// expecting its result back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttachedResponseSink {
+    pub node_id: GenerationalNodeId,
+    /// Identifies the waiting request on the ingress
+    pub request_id: u64,
+}
+
+/// Request to attach to an invocation, in order to receive its result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttachInvocationRequest {
+    pub invocation_id: InvocationId,
+    /// If the invocation is still in-flight, wait for its completion rather than responding
+    /// right away that the result is not ready.
+    pub block_on_inflight: bool,
+    pub response_sink: AttachedResponseSink,
+}
+
+impl WithPartitionKey for AttachInvocationRequest {
+    fn partition_key(&self) -> PartitionKey {
+        self.invocation_id.partition_key()
+    }
+}
+
+/// Request to purge a completed invocation, dropping its retained result before the retention
+/// time expired.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PurgeInvocationRequest {
+    pub invocation_id: InvocationId,
+}
+
+impl WithPartitionKey for PurgeInvocationRequest {
+    fn partition_key(&self) -> PartitionKey {
+        self.invocation_id.partition_key()
+    }
+}
+
 /// Source of an invocation
 #[derive(Debug, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -569,6 +621,8 @@ mod mocks {
                 span_context: Default::default(),
                 headers: vec![],
                 execution_time: None,
+                completion_retention_time: Duration::ZERO,
+                attached_response_sinks: vec![],
             }
         }
     }
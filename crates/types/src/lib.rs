@@ -27,9 +27,12 @@ pub mod logs;
 pub mod message;
 pub mod metadata_store;
 pub mod net;
+pub mod node_liveness;
 pub mod nodes_config;
+pub mod partition_placement;
 pub mod partition_table;
 pub mod retries;
+pub mod schema;
 pub mod state_mut;
 pub mod subscription;
 pub mod time;
@@ -0,0 +1,298 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! The metadata of bifrost's logs. It's stored in the metadata store under
+//! [`crate::metadata_store::keys::BIFROST_CONFIG_KEY`] and shared between all nodes.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use enum_map::Enum;
+
+use super::{LogId, Lsn, SequenceNumber};
+use crate::{Version, Versioned};
+
+/// An enum with the list of supported loglet providers.
+/// For each variant bifrost must have a corresponding loglet provider implementation.
+#[derive(
+    Debug, Clone, Hash, Eq, PartialEq, Copy, Enum, strum_macros::EnumIter, strum_macros::Display,
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ProviderKind {
+    /// A local rocksdb-backed loglet.
+    Local,
+    /// An in-memory loglet, primarily for testing.
+    InMemory,
+    /// A loglet that replicates its records to the log servers of a nodeset.
+    Replicated,
+}
+
+/// Log metadata is the map of logs known to the system with the corresponding chain.
+/// Metadata updates are versioned and atomic.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Logs {
+    version: Version,
+    logs: HashMap<LogId, Chain>,
+}
+
+/// the chain is a list of segments in (from Lsn) order.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chain {
+    chain: BTreeMap<Lsn, Arc<LogletConfig>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub base_lsn: Lsn,
+    pub config: Arc<LogletConfig>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AppendSegmentError {
+    #[error("unknown log '{0}'")]
+    UnknownLog(LogId),
+    #[error("segment base lsn {base_lsn} must not be lower than the tail segment base lsn {tail_base_lsn}")]
+    BeforeTail { base_lsn: Lsn, tail_base_lsn: Lsn },
+}
+
+/// A segment in the chain of loglet instances.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogletConfig {
+    pub kind: ProviderKind,
+    pub params: LogletParams,
+}
+
+impl LogletConfig {
+    pub fn new(kind: ProviderKind, params: LogletParams) -> Self {
+        Self { kind, params }
+    }
+}
+
+/// The configuration of a single loglet segment. This holds information needed
+/// for a loglet kind to construct a configured loglet instance modulo the log-id
+/// and start-lsn. It's provided by bifrost on loglet creation. This allows the
+/// parameters to be shared between segments and logs if needed.
+#[derive(Debug, Clone, Hash, Eq, PartialEq, derive_more::From)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogletParams(String);
+
+impl LogletParams {
+    pub fn id(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Logs {
+    pub fn new(version: Version, logs: HashMap<LogId, Chain>) -> Self {
+        Self { version, logs }
+    }
+
+    /// empty metadata with an invalid version
+    pub fn empty() -> Self {
+        Self {
+            version: Version::INVALID,
+            logs: Default::default(),
+        }
+    }
+
+    pub fn increment_version(&mut self) {
+        self.version = self.version.next();
+    }
+
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn set_version(&mut self, version: Version) {
+        self.version = version;
+    }
+
+    pub fn tail_segment(&self, log_id: LogId) -> Option<Segment> {
+        self.logs
+            .get(&log_id)
+            .and_then(|chain| chain.tail())
+            .map(|(base_lsn, config)| Segment {
+                base_lsn: *base_lsn,
+                config: Arc::clone(config),
+            })
+    }
+
+    /// Finds the segment that contains the `lsn`, that is the segment with the highest base LSN
+    /// that is lower or equal to `lsn`. LSNs before the first segment resolve to the first
+    /// segment.
+    pub fn find_segment_for_lsn(&self, log_id: LogId, lsn: Lsn) -> Option<Segment> {
+        self.logs
+            .get(&log_id)
+            .and_then(|chain| chain.find_segment_for_lsn(lsn))
+    }
+
+    /// Returns all segments of the log in base LSN order.
+    pub fn segments(&self, log_id: LogId) -> Option<Vec<Segment>> {
+        self.logs
+            .get(&log_id)
+            .map(|chain| chain.segments().collect())
+    }
+
+    /// Adds a new log with the given chain and bumps the metadata version. Returns `false` if the
+    /// log exists already.
+    pub fn add_log(&mut self, log_id: LogId, chain: Chain) -> bool {
+        if self.logs.contains_key(&log_id) {
+            return false;
+        }
+        self.logs.insert(log_id, chain);
+        self.version = self.version.next();
+        true
+    }
+
+    /// Appends a new segment to the chain of `log_id` and bumps the metadata version. See
+    /// [`Chain::append_segment`].
+    pub fn append_segment(
+        &mut self,
+        log_id: LogId,
+        base_lsn: Lsn,
+        config: LogletConfig,
+    ) -> Result<(), AppendSegmentError> {
+        let Some(chain) = self.logs.get_mut(&log_id) else {
+            return Err(AppendSegmentError::UnknownLog(log_id));
+        };
+        chain.append_segment(base_lsn, config)?;
+        self.version = self.version.next();
+        Ok(())
+    }
+}
+
+impl Versioned for Logs {
+    fn version(&self) -> Version {
+        self.version
+    }
+}
+
+impl Chain {
+    /// Creates a new chain starting from Lsn(1) with a given loglet config.
+    pub fn new(kind: ProviderKind, config: LogletParams) -> Self {
+        let mut chain = BTreeMap::new();
+        let from_lsn = Lsn::OLDEST;
+        chain.insert(from_lsn, Arc::new(LogletConfig::new(kind, config)));
+        Self { chain }
+    }
+
+    pub fn tail(&self) -> Option<(&Lsn, &Arc<LogletConfig>)> {
+        self.chain.last_key_value()
+    }
+
+    /// Appends a segment starting at `base_lsn` to the chain. Previous segments must be sealed
+    /// before, the new segment continues the log right after the sealed tail.
+    ///
+    /// If the tail segment starts at the same `base_lsn`, it didn't contain any records and the
+    /// new segment replaces it.
+    ///
+    /// Fails if `base_lsn` is lower than the base LSN of the current tail segment.
+    pub fn append_segment(
+        &mut self,
+        base_lsn: Lsn,
+        config: LogletConfig,
+    ) -> Result<(), AppendSegmentError> {
+        if let Some((tail_base_lsn, _)) = self.tail() {
+            if base_lsn < *tail_base_lsn {
+                return Err(AppendSegmentError::BeforeTail {
+                    base_lsn,
+                    tail_base_lsn: *tail_base_lsn,
+                });
+            }
+        }
+        self.chain.insert(base_lsn, Arc::new(config));
+        Ok(())
+    }
+
+    pub fn find_segment_for_lsn(&self, lsn: Lsn) -> Option<Segment> {
+        // NOTE: Hopefully at some point we will use the nightly Cursor API for
+        // effecient cursor seeks in the chain (or use nightly channel)
+        // Reference: https://github.com/rust-lang/rust/issues/107540
+        self.chain
+            .range(..=lsn)
+            .next_back()
+            // LSNs before the first segment (e.g. INVALID) belong to the first segment
+            .or_else(|| self.chain.first_key_value())
+            .map(|(base_lsn, config)| Segment {
+                base_lsn: *base_lsn,
+                config: Arc::clone(config),
+            })
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = Segment> + '_ {
+        self.chain.iter().map(|(base_lsn, config)| Segment {
+            base_lsn: *base_lsn,
+            config: Arc::clone(config),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use restate_test_util::let_assert;
+
+    use super::*;
+
+    #[test]
+    fn test_chain_new() {
+        let chain = Chain::new(ProviderKind::Local, LogletParams::from("test".to_string()));
+        assert_eq!(chain.chain.len(), 1);
+        let_assert!(Some((lsn, loglet_config)) = chain.tail());
+        assert_eq!(Lsn::OLDEST, *lsn);
+        assert_eq!(ProviderKind::Local, loglet_config.kind);
+        assert_eq!("test".to_string(), loglet_config.params.0);
+    }
+
+    #[test]
+    fn test_chain_find_segment_for_lsn() -> Result<(), AppendSegmentError> {
+        let mut chain = Chain::new(ProviderKind::Local, LogletParams::from("1".to_string()));
+        chain.append_segment(
+            Lsn::from(11),
+            LogletConfig::new(ProviderKind::InMemory, LogletParams::from("2".to_string())),
+        )?;
+        chain.append_segment(
+            Lsn::from(21),
+            LogletConfig::new(ProviderKind::Local, LogletParams::from("3".to_string())),
+        )?;
+        assert_eq!(3, chain.segments().count());
+
+        let_assert!(Some(segment) = chain.find_segment_for_lsn(Lsn::INVALID));
+        assert_eq!(Lsn::OLDEST, segment.base_lsn);
+        let_assert!(Some(segment) = chain.find_segment_for_lsn(Lsn::from(10)));
+        assert_eq!(Lsn::OLDEST, segment.base_lsn);
+        let_assert!(Some(segment) = chain.find_segment_for_lsn(Lsn::from(11)));
+        assert_eq!(Lsn::from(11), segment.base_lsn);
+        assert_eq!(ProviderKind::InMemory, segment.config.kind);
+        let_assert!(Some(segment) = chain.find_segment_for_lsn(Lsn::MAX));
+        assert_eq!(Lsn::from(21), segment.base_lsn);
+        assert_eq!("3", segment.config.params.id());
+
+        // an empty tail segment is replaced
+        chain.append_segment(
+            Lsn::from(21),
+            LogletConfig::new(ProviderKind::InMemory, LogletParams::from("4".to_string())),
+        )?;
+        assert_eq!(3, chain.segments().count());
+        let_assert!(Some((lsn, loglet_config)) = chain.tail());
+        assert_eq!(Lsn::from(21), *lsn);
+        assert_eq!("4", loglet_config.params.id());
+
+        // segments can't be appended before the tail segment
+        let_assert!(
+            Err(AppendSegmentError::BeforeTail { .. }) = chain.append_segment(
+                Lsn::from(11),
+                LogletConfig::new(ProviderKind::InMemory, LogletParams::from("5".to_string())),
+            )
+        );
+        assert_eq!(3, chain.segments().count());
+        Ok(())
+    }
+}
@@ -10,6 +10,8 @@
 
 use bytes::Bytes;
 
+pub mod metadata;
+
 #[derive(
     Debug,
     Clone,
@@ -94,6 +96,19 @@ where
     fn prev(self) -> Self;
 }
 
+/// Details about why a log was sealed
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SealReason {
+    /// Log was sealed to perform a repartitioning operation (split or unsplit).
+    /// The reader/writer need to figure out where to read/write next.
+    Resharding,
+    Other(String),
+    /// The loglet was sealed to continue the log in a new segment, for instance to move the log
+    /// to another loglet provider. Readers and writers transparently move to the next segment.
+    Reconfiguration,
+}
+
 /// Owned payload.
 #[derive(
     Debug,
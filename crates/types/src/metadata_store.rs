@@ -16,5 +16,8 @@ pub mod keys {
     pub static NODES_CONFIG_KEY: ByteString = ByteString::from_static("nodes_config");
     pub static BIFROST_CONFIG_KEY: ByteString = ByteString::from_static("bifrost_config");
     pub static PARTITION_TABLE_KEY: ByteString = ByteString::from_static("partition_table");
+    pub static PARTITION_PLACEMENT_KEY: ByteString = ByteString::from_static("partition_placement");
+    pub static NODES_LIVENESS_KEY: ByteString = ByteString::from_static("nodes_liveness");
+    pub static SCHEMA_INFORMATION_KEY: ByteString = ByteString::from_static("schema_information");
     pub static EPOCH_TABLE_KEY: ByteString = ByteString::from_static("epoch_table");
 }
@@ -0,0 +1,78 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! The liveness of the cluster's nodes as observed by the failure detector of the cluster
+//! controller. It's stored in the metadata store under
+//! [`crate::metadata_store::keys::NODES_LIVENESS_KEY`].
+
+use std::collections::HashMap;
+
+use crate::{GenerationalNodeId, Version, Versioned};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, strum_macros::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "serde_schema", derive(schemars::JsonSchema))]
+#[strum(serialize_all = "snake_case")]
+pub enum NodeLiveness {
+    /// The node answers heartbeats.
+    Alive,
+    /// The node has not answered heartbeats recently or has not answered any heartbeat yet.
+    Suspect,
+    /// The node has not answered heartbeats for so long that it's considered to have failed.
+    Dead,
+}
+
+/// Versioned liveness of the current generation of every node.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodesLiveness {
+    version: Version,
+    nodes: HashMap<GenerationalNodeId, NodeLiveness>,
+}
+
+impl NodesLiveness {
+    pub fn new(version: Version, nodes: HashMap<GenerationalNodeId, NodeLiveness>) -> Self {
+        Self { version, nodes }
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Returns the liveness of the given node generation if it is known.
+    pub fn get(&self, node_id: GenerationalNodeId) -> Option<NodeLiveness> {
+        self.nodes.get(&node_id).copied()
+    }
+
+    /// Returns whether the given node generation is known to have failed.
+    pub fn is_dead(&self, node_id: GenerationalNodeId) -> bool {
+        self.get(node_id) == Some(NodeLiveness::Dead)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (GenerationalNodeId, NodeLiveness)> + '_ {
+        self.nodes
+            .iter()
+            .map(|(node_id, liveness)| (*node_id, *liveness))
+    }
+}
+
+impl Default for NodesLiveness {
+    /// The empty liveness which precedes the first stored liveness.
+    fn default() -> Self {
+        Self::new(Version::INVALID, HashMap::default())
+    }
+}
+
+impl Versioned for NodesLiveness {
+    fn version(&self) -> Version {
+        self.version()
+    }
+}
@@ -36,6 +36,7 @@ pub enum NodesConfigError {
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", enumset(serialize_repr = "list"))]
 #[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "serde_schema", derive(schemars::JsonSchema))]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 #[cfg_attr(feature = "clap", clap(rename_all = "snake_case"))]
 pub enum Role {
@@ -44,6 +45,18 @@ pub enum Role {
     /// Admin runs cluster controller and user-facing admin APIs
     Admin,
     MetadataStore,
+    /// A log server stores the records of replicated loglets
+    LogServer,
+}
+
+impl Role {
+    /// Whether the role can be assigned to and removed from a running node. Log servers hold
+    /// replicas of the logs and metadata stores are members of the raft group, both of which
+    /// would need to be moved first. The other roles are kept across restarts once they have
+    /// been changed, whereas these roles always follow the configuration of the node.
+    pub fn is_dynamic(self) -> bool {
+        matches!(self, Role::Worker | Role::Admin)
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -62,6 +75,23 @@ enum MaybeNode {
     Node(NodeConfig),
 }
 
+/// Lifecycle of a node which is taken out of the cluster.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, strum_macros::Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[cfg_attr(feature = "serde_schema", derive(schemars::JsonSchema))]
+#[strum(serialize_all = "snake_case")]
+pub enum NodeState {
+    /// The node takes part in the cluster.
+    #[default]
+    Active,
+    /// The node is being taken out of the cluster. It is not assigned any partitions anymore and
+    /// doesn't accept new ingress requests.
+    Draining,
+    /// The node has no partitions and in-flight ingress requests anymore and can be removed.
+    Drained,
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeConfig {
@@ -69,6 +99,8 @@ pub struct NodeConfig {
     pub current_generation: GenerationalNodeId,
     pub address: AdvertisedAddress,
     pub roles: EnumSet<Role>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub state: NodeState,
 }
 
 impl NodeConfig {
@@ -83,6 +115,7 @@ impl NodeConfig {
             current_generation,
             address,
             roles,
+            state: NodeState::default(),
         }
     }
 }
@@ -124,6 +157,51 @@ impl NodesConfiguration {
         self.name_lookup.insert(name, plain_id);
     }
 
+    /// Updates the state of the node. Fails if the node is unknown or has been deleted.
+    pub fn set_node_state(
+        &mut self,
+        id: impl Into<NodeId>,
+        state: NodeState,
+    ) -> Result<(), NodesConfigError> {
+        let node_id: NodeId = id.into();
+        // validates the generation if a generational id is given
+        self.find_node_by_id(node_id)?;
+
+        if let Some(MaybeNode::Node(node)) = self.nodes.get_mut(&node_id.id()) {
+            node.state = state;
+        }
+        Ok(())
+    }
+
+    /// Updates the roles of the node. Fails if the node is unknown or has been deleted.
+    pub fn set_node_roles(
+        &mut self,
+        id: impl Into<NodeId>,
+        roles: EnumSet<Role>,
+    ) -> Result<(), NodesConfigError> {
+        let node_id: NodeId = id.into();
+        // validates the generation if a generational id is given
+        self.find_node_by_id(node_id)?;
+
+        if let Some(MaybeNode::Node(node)) = self.nodes.get_mut(&node_id.id()) {
+            node.roles = roles;
+        }
+        Ok(())
+    }
+
+    /// Permanently deletes a node from the config. Its plain node id won't be reused.
+    pub fn remove_node(&mut self, id: impl Into<NodeId>) -> Result<NodeConfig, NodesConfigError> {
+        let node_id: NodeId = id.into();
+        self.find_node_by_id(node_id)?;
+
+        let Some(MaybeNode::Node(node)) = self.nodes.insert(node_id.id(), MaybeNode::Tombstone)
+        else {
+            unreachable!("node has been found");
+        };
+        self.name_lookup.remove(&node.name);
+        Ok(node)
+    }
+
     /// Current version of the config
     pub fn version(&self) -> Version {
         self.version
@@ -175,6 +253,16 @@ impl NodesConfiguration {
         })
     }
 
+    /// Iterates over all nodes which have not been deleted.
+    pub fn iter(&self) -> impl Iterator<Item = (PlainNodeId, &NodeConfig)> {
+        self.nodes
+            .iter()
+            .filter_map(|(node_id, maybe)| match maybe {
+                MaybeNode::Node(node) => Some((*node_id, node)),
+                MaybeNode::Tombstone => None,
+            })
+    }
+
     /// Returns the maximum known plain node id.
     pub fn max_plain_node_id(&self) -> Option<PlainNodeId> {
         self.nodes.keys().max().cloned()
@@ -267,4 +355,77 @@ mod tests {
         let found = config.find_node_by_name("nodeX").expect("known id");
         assert_eq!(&node, found);
     }
+
+    #[test]
+    fn test_set_node_roles() {
+        let mut config = NodesConfiguration::new(Version::MIN, "test-cluster".to_owned());
+        let address: AdvertisedAddress = "unix:/tmp/my_socket".parse().unwrap();
+        let current_gen = GenerationalNodeId::new(1, 1);
+        let node = NodeConfig::new(
+            "node1".to_owned(),
+            current_gen,
+            address,
+            EnumSet::only(Role::Worker),
+        );
+        config.upsert_node(node);
+
+        config
+            .set_node_roles(NodeId::new_plain(1), Role::Worker | Role::Admin)
+            .expect("known id");
+        assert_eq!(
+            Role::Worker | Role::Admin,
+            config.find_node_by_id(current_gen).unwrap().roles
+        );
+        assert_eq!(
+            current_gen,
+            config.get_admin_node().unwrap().current_generation
+        );
+
+        let res = config.set_node_roles(NodeId::new_plain(2), EnumSet::only(Role::Worker));
+        assert!(matches!(res, Err(NodesConfigError::UnknownNodeId(_))));
+    }
+
+    #[test]
+    fn test_drain_and_remove_node() {
+        let mut config = NodesConfiguration::new(Version::MIN, "test-cluster".to_owned());
+        let address: AdvertisedAddress = "unix:/tmp/my_socket".parse().unwrap();
+        let current_gen = GenerationalNodeId::new(1, 1);
+        let node = NodeConfig::new(
+            "node1".to_owned(),
+            current_gen,
+            address,
+            EnumSet::only(Role::Worker),
+        );
+        config.upsert_node(node);
+        assert_eq!(
+            NodeState::Active,
+            config.find_node_by_id(current_gen).unwrap().state
+        );
+
+        config
+            .set_node_state(NodeId::new_plain(1), NodeState::Draining)
+            .expect("known id");
+        assert_eq!(
+            NodeState::Draining,
+            config.find_node_by_id(current_gen).unwrap().state
+        );
+
+        // state updates are rejected for other generations
+        let res = config.set_node_state(NodeId::new_generational(1, 2), NodeState::Drained);
+        assert!(matches!(
+            res,
+            Err(NodesConfigError::GenerationMismatch { .. })
+        ));
+
+        let removed = config.remove_node(NodeId::new_plain(1)).expect("known id");
+        assert_eq!("node1", removed.name);
+        assert!(matches!(
+            config.find_node_by_id(current_gen),
+            Err(NodesConfigError::Deleted(_))
+        ));
+        assert_eq!(None, config.find_node_by_name("node1"));
+        assert_eq!(0, config.iter().count());
+        // the plain node id stays taken
+        assert_eq!(Some(PlainNodeId::from(1)), config.max_plain_node_id());
+    }
 }
@@ -0,0 +1,102 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! The placement of partitions onto worker nodes. It's computed by the cluster controller and
+//! stored in the metadata store under [`crate::metadata_store::keys::PARTITION_PLACEMENT_KEY`].
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::identifiers::{LeaderEpoch, PartitionId};
+use crate::{GenerationalNodeId, PlainNodeId, Version, Versioned};
+
+/// The worker nodes which run the partition processors of a partition.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartitionReplicas {
+    /// The node generation whose partition processor leads the partition.
+    pub leader: GenerationalNodeId,
+    /// The epoch in which `leader` has been elected. It's announced to the partition processors
+    /// via the partition's log.
+    pub leader_epoch: LeaderEpoch,
+    /// The nodes whose partition processors follow the log of the partition so that they can
+    /// take over if the leader fails.
+    pub followers: BTreeSet<PlainNodeId>,
+}
+
+impl PartitionReplicas {
+    /// Returns whether the given node runs a partition processor for the partition.
+    pub fn contains(&self, node_id: PlainNodeId) -> bool {
+        self.leader.as_plain() == node_id || self.followers.contains(&node_id)
+    }
+}
+
+/// Versioned assignment of partitions to the worker nodes which run their partition processors.
+/// Partitions which are not contained in the placement are not run by any node.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartitionPlacement {
+    version: Version,
+    partitions: BTreeMap<PartitionId, PartitionReplicas>,
+}
+
+impl PartitionPlacement {
+    pub fn new(version: Version, partitions: BTreeMap<PartitionId, PartitionReplicas>) -> Self {
+        Self {
+            version,
+            partitions,
+        }
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Returns the replicas of the given partition.
+    pub fn replicas(&self, partition_id: PartitionId) -> Option<&PartitionReplicas> {
+        self.partitions.get(&partition_id)
+    }
+
+    /// Returns the node generation which leads the given partition.
+    pub fn leader_for_partition(&self, partition_id: PartitionId) -> Option<GenerationalNodeId> {
+        self.replicas(partition_id).map(|replicas| replicas.leader)
+    }
+
+    /// Returns the partitions for which the given node runs a partition processor, either as
+    /// leader or as follower, in ascending order.
+    pub fn partitions_of_node(
+        &self,
+        node_id: PlainNodeId,
+    ) -> impl Iterator<Item = PartitionId> + '_ {
+        self.partitions
+            .iter()
+            .filter(move |(_, replicas)| replicas.contains(node_id))
+            .map(|(partition_id, _)| *partition_id)
+    }
+
+    /// Iterates over all assigned partitions in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = (PartitionId, &PartitionReplicas)> + '_ {
+        self.partitions
+            .iter()
+            .map(|(partition_id, replicas)| (*partition_id, replicas))
+    }
+}
+
+impl Default for PartitionPlacement {
+    /// The empty placement which precedes the first stored placement.
+    fn default() -> Self {
+        Self::new(Version::INVALID, BTreeMap::default())
+    }
+}
+
+impl Versioned for PartitionPlacement {
+    fn version(&self) -> Version {
+        self.version()
+    }
+}
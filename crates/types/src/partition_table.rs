@@ -11,12 +11,31 @@
 use crate::identifiers::{PartitionId, PartitionKey};
 use crate::{Version, Versioned};
 use std::borrow::Borrow;
+use std::collections::BTreeMap;
 use std::ops::RangeInclusive;
 
 #[derive(Debug, thiserror::Error)]
 #[error("Cannot find target peer for partition key {0}")]
 pub struct PartitionTableError(PartitionKey);
 
+#[derive(Debug, thiserror::Error)]
+pub enum PartitionTableUpdateError {
+    #[error("unknown partition {0}")]
+    UnknownPartition(PartitionId),
+    #[error("partition key {split_key} does not split the key range {key_range:?} of partition {partition_id}")]
+    InvalidSplitKey {
+        partition_id: PartitionId,
+        key_range: RangeInclusive<PartitionKey>,
+        split_key: PartitionKey,
+    },
+    #[error("partitions {0} and {1} don't have adjacent key ranges")]
+    NotAdjacent(PartitionId, PartitionId),
+    #[error("partition id {0} has not been allocated or is in use already")]
+    UnavailablePartitionId(PartitionId),
+    #[error("partition {0} is still involved in handing over a key range")]
+    PendingHandOver(PartitionId),
+}
+
 pub trait FindPartition {
     fn find_partition_id(
         &self,
@@ -24,20 +43,72 @@ pub trait FindPartition {
     ) -> Result<PartitionId, PartitionTableError>;
 }
 
+/// Maps consecutive key ranges which cover the whole partition key space to partitions.
+///
+/// The key space is split evenly at bootstrap. Afterwards, partitions can be split into two or
+/// two partitions with adjacent key ranges can be merged, each of which results in a new version
+/// of the partition table. A split partition keeps the lower part of its key range and hands over
+/// the upper part to a new partition. Merged partitions hand over their key ranges to a new
+/// partition which covers both. They are remembered as merged because their processors keep
+/// running to deliver their outbox.
+///
+/// Every hand over of keys stays pending until the cluster controller has appended it to the log
+/// of the partition which owned the keys, see [`KeyRangeHandOver`].
 #[derive(Debug, Clone, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct FixedPartitionTable {
+pub struct PartitionTable {
     version: Version,
-    num_partitions: u64,
+    /// Partitions by the inclusive end of their key range.
+    partitions: BTreeMap<PartitionKey, Partition>,
+    /// Merged partitions by the partition they have been merged into.
+    merged_partitions: BTreeMap<PartitionId, PartitionId>,
+    pending_hand_overs: Vec<KeyRangeHandOver>,
+    num_initial_partitions: u64,
+    next_partition_id: PartitionId,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Partition {
+    id: PartitionId,
+    /// Inclusive start of the key range.
+    start: PartitionKey,
 }
 
-impl FixedPartitionTable {
+/// Keys which have been assigned from one partition to another by a split or merge.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyRangeHandOver {
+    pub from: PartitionId,
+    pub to: PartitionId,
+    pub key_range: RangeInclusive<PartitionKey>,
+}
+
+impl PartitionTable {
     const PARTITION_KEY_RANGE_END: u128 = 1 << 64;
 
     pub fn new(version: Version, num_partitions: u64) -> Self {
+        let partitions = (0..num_partitions)
+            .map(|partition_id| {
+                let key_range =
+                    Self::initial_partition_id_to_partition_range(num_partitions, partition_id);
+                (
+                    *key_range.end(),
+                    Partition {
+                        id: partition_id,
+                        start: *key_range.start(),
+                    },
+                )
+            })
+            .collect();
+
         Self {
             version,
-            num_partitions,
+            partitions,
+            merged_partitions: BTreeMap::default(),
+            pending_hand_overs: Vec::default(),
+            num_initial_partitions: num_partitions,
+            next_partition_id: num_partitions,
         }
     }
 
@@ -55,21 +126,222 @@ impl FixedPartitionTable {
     }
 
     pub fn partitioner(&self) -> Partitioner {
-        Partitioner::new(self.num_partitions)
+        Partitioner {
+            partitions: self
+                .partitions
+                .iter()
+                .map(|(end, partition)| (partition.id, partition.start..=*end))
+                .collect::<Vec<_>>()
+                .into_iter(),
+        }
     }
 
-    fn partition_key_to_partition_id(
-        num_partitions: u64,
-        partition_key: PartitionKey,
-    ) -> PartitionId {
-        let num_partitions = u128::from(num_partitions);
-        let partition_key = u128::from(partition_key);
+    pub fn num_partitions(&self) -> usize {
+        self.partitions.len()
+    }
+
+    /// Returns the key range of the given partition if it is part of the partition table.
+    pub fn key_range(&self, partition_id: PartitionId) -> Option<RangeInclusive<PartitionKey>> {
+        self.partitions
+            .iter()
+            .find(|(_, partition)| partition.id == partition_id)
+            .map(|(end, partition)| partition.start..=*end)
+    }
+
+    /// Returns the key range with which the given partition has been created. Partitions which
+    /// are created by splits or merges start without keys and take over their key range from
+    /// other partitions.
+    pub fn initial_key_range(
+        &self,
+        partition_id: PartitionId,
+    ) -> Option<RangeInclusive<PartitionKey>> {
+        (partition_id < self.num_initial_partitions).then(|| {
+            Self::initial_partition_id_to_partition_range(self.num_initial_partitions, partition_id)
+        })
+    }
+
+    /// Returns the partitions which have been merged together with the partition which owns
+    /// their former keys now.
+    pub fn merged_partitions(&self) -> impl Iterator<Item = (PartitionId, PartitionId)> + '_ {
+        self.merged_partitions
+            .keys()
+            .map(|partition_id| (*partition_id, self.resolve_merged(*partition_id)))
+    }
 
-        u64::try_from(partition_key * num_partitions / Self::PARTITION_KEY_RANGE_END)
-            .expect("u64::MAX * u64::MAX / 1^64 should be <= u64::MAX.")
+    /// Returns the hand overs of keys which have not been appended to the log of the partition
+    /// which owned the keys yet.
+    pub fn pending_hand_overs(&self) -> &[KeyRangeHandOver] {
+        &self.pending_hand_overs
     }
 
-    fn partition_id_to_partition_range(
+    /// Removes the given hand over once it has been appended to the log of the partition which
+    /// owned the keys. Returns `false` if the hand over is not pending.
+    pub fn complete_hand_over(&mut self, hand_over: &KeyRangeHandOver) -> bool {
+        let Some(position) = self
+            .pending_hand_overs
+            .iter()
+            .position(|pending| pending == hand_over)
+        else {
+            return false;
+        };
+
+        self.pending_hand_overs.remove(position);
+        self.increment_version();
+        true
+    }
+
+    /// Allocates the id of a new partition. The id can be used by a single split or merge.
+    /// Allocating the id up front allows preparing the log of the new partition before it
+    /// becomes part of the partition table.
+    pub fn allocate_partition_id(&mut self) -> PartitionId {
+        let partition_id = self.next_partition_id;
+        self.next_partition_id += 1;
+        self.increment_version();
+        partition_id
+    }
+
+    /// Splits the key range of the given partition at the split key. The partition keeps the keys
+    /// below the split key and hands over the keys from the split key onwards to the new
+    /// partition. The id of the new partition must have been allocated before.
+    pub fn split(
+        &mut self,
+        partition_id: PartitionId,
+        split_key: PartitionKey,
+        new_partition_id: PartitionId,
+    ) -> Result<(), PartitionTableUpdateError> {
+        let key_range = self.key_range_for_update(partition_id)?;
+        self.check_available(new_partition_id)?;
+
+        if split_key <= *key_range.start() || split_key > *key_range.end() {
+            return Err(PartitionTableUpdateError::InvalidSplitKey {
+                partition_id,
+                key_range,
+                split_key,
+            });
+        }
+
+        self.partitions.insert(
+            split_key - 1,
+            Partition {
+                id: partition_id,
+                start: *key_range.start(),
+            },
+        );
+        self.partitions.insert(
+            *key_range.end(),
+            Partition {
+                id: new_partition_id,
+                start: split_key,
+            },
+        );
+        self.pending_hand_overs.push(KeyRangeHandOver {
+            from: partition_id,
+            to: new_partition_id,
+            key_range: split_key..=*key_range.end(),
+        });
+        self.increment_version();
+
+        Ok(())
+    }
+
+    /// Merges two partitions with adjacent key ranges into a new partition which covers both key
+    /// ranges. The id of the new partition must have been allocated before.
+    pub fn merge(
+        &mut self,
+        partition_id: PartitionId,
+        other_partition_id: PartitionId,
+        new_partition_id: PartitionId,
+    ) -> Result<(), PartitionTableUpdateError> {
+        let key_range = self.key_range_for_update(partition_id)?;
+        let other_key_range = self.key_range_for_update(other_partition_id)?;
+        self.check_available(new_partition_id)?;
+
+        let merged_key_range = if key_range.end().checked_add(1) == Some(*other_key_range.start()) {
+            *key_range.start()..=*other_key_range.end()
+        } else if other_key_range.end().checked_add(1) == Some(*key_range.start()) {
+            *other_key_range.start()..=*key_range.end()
+        } else {
+            return Err(PartitionTableUpdateError::NotAdjacent(
+                partition_id,
+                other_partition_id,
+            ));
+        };
+
+        self.partitions.remove(key_range.end());
+        self.partitions.remove(other_key_range.end());
+        self.partitions.insert(
+            *merged_key_range.end(),
+            Partition {
+                id: new_partition_id,
+                start: *merged_key_range.start(),
+            },
+        );
+
+        for (merged_partition_id, key_range) in [
+            (partition_id, key_range),
+            (other_partition_id, other_key_range),
+        ] {
+            self.merged_partitions
+                .insert(merged_partition_id, new_partition_id);
+            self.pending_hand_overs.push(KeyRangeHandOver {
+                from: merged_partition_id,
+                to: new_partition_id,
+                key_range,
+            });
+        }
+        self.increment_version();
+
+        Ok(())
+    }
+
+    fn key_range_for_update(
+        &self,
+        partition_id: PartitionId,
+    ) -> Result<RangeInclusive<PartitionKey>, PartitionTableUpdateError> {
+        let key_range = self
+            .key_range(partition_id)
+            .ok_or(PartitionTableUpdateError::UnknownPartition(partition_id))?;
+
+        // a partition hands over suffixes of the keys it owns in the order of the hand overs in
+        // its log, so another update has to wait for the pending hand overs to be appended
+        if self
+            .pending_hand_overs
+            .iter()
+            .any(|hand_over| hand_over.from == partition_id || hand_over.to == partition_id)
+        {
+            return Err(PartitionTableUpdateError::PendingHandOver(partition_id));
+        }
+
+        Ok(key_range)
+    }
+
+    fn check_available(&self, partition_id: PartitionId) -> Result<(), PartitionTableUpdateError> {
+        if partition_id >= self.next_partition_id
+            || self.key_range(partition_id).is_some()
+            || self.merged_partitions.contains_key(&partition_id)
+            || self
+                .merged_partitions
+                .values()
+                .any(|merged_into| *merged_into == partition_id)
+        {
+            return Err(PartitionTableUpdateError::UnavailablePartitionId(
+                partition_id,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Follows the merges of the given merged partition until reaching a partition which is part
+    /// of the partition table.
+    fn resolve_merged(&self, mut partition_id: PartitionId) -> PartitionId {
+        while let Some(merged_into) = self.merged_partitions.get(&partition_id) {
+            partition_id = *merged_into;
+        }
+        partition_id
+    }
+
+    fn initial_partition_id_to_partition_range(
         num_partitions: u64,
         partition_id: PartitionId,
     ) -> RangeInclusive<PartitionKey> {
@@ -98,7 +370,7 @@ impl FixedPartitionTable {
     }
 }
 
-impl Versioned for FixedPartitionTable {
+impl Versioned for PartitionTable {
     fn version(&self) -> Version {
         self.version()
     }
@@ -106,53 +378,33 @@ impl Versioned for FixedPartitionTable {
 
 impl<T> FindPartition for T
 where
-    T: Borrow<FixedPartitionTable>,
+    T: Borrow<PartitionTable>,
 {
     fn find_partition_id(
         &self,
         partition_key: PartitionKey,
     ) -> Result<PartitionId, PartitionTableError> {
-        let partition_id = FixedPartitionTable::partition_key_to_partition_id(
-            self.borrow().num_partitions,
-            partition_key,
-        );
-
-        Ok(partition_id)
+        self.borrow()
+            .partitions
+            .range(partition_key..)
+            .next()
+            .filter(|(_, partition)| partition.start <= partition_key)
+            .map(|(_, partition)| partition.id)
+            .ok_or(PartitionTableError(partition_key))
     }
 }
 
+/// Iterates over the partitions of a partition table in the order of their key ranges.
 #[derive(Debug)]
 pub struct Partitioner {
-    num_partitions: u64,
-    next_partition_id: u64,
-}
-
-impl Partitioner {
-    fn new(num_partitions: u64) -> Self {
-        Self {
-            num_partitions,
-            next_partition_id: 0,
-        }
-    }
+    partitions: std::vec::IntoIter<(PartitionId, RangeInclusive<PartitionKey>)>,
 }
 
 impl Iterator for Partitioner {
     type Item = (PartitionId, RangeInclusive<PartitionKey>);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.next_partition_id < self.num_partitions {
-            let partition_id = self.next_partition_id;
-            self.next_partition_id += 1;
-
-            let partition_range = FixedPartitionTable::partition_id_to_partition_range(
-                self.num_partitions,
-                partition_id,
-            );
-
-            Some((partition_id, partition_range))
-        } else {
-            None
-        }
+        self.partitions.next()
     }
 }
 
@@ -161,12 +413,14 @@ mod tests {
     use test_log::test;
 
     use crate::identifiers::{PartitionId, PartitionKey};
-    use crate::partition_table::{FindPartition, FixedPartitionTable, Partitioner};
+    use crate::partition_table::{
+        FindPartition, KeyRangeHandOver, PartitionTable, PartitionTableUpdateError,
+    };
     use crate::Version;
 
     #[test]
     fn partitioner_produces_consecutive_ranges() {
-        let partitioner = Partitioner::new(10);
+        let partitioner = PartitionTable::new(Version::MIN, 10).partitioner();
         let mut previous_end = None;
         let mut previous_length = None::<PartitionKey>;
 
@@ -191,7 +445,7 @@ mod tests {
         assert_eq!(previous_end, Some(PartitionKey::MAX));
     }
 
-    impl FixedPartitionTable {
+    impl PartitionTable {
         fn unchecked_partition_key_to_target_peer(
             &self,
             partition_key: PartitionKey,
@@ -203,7 +457,7 @@ mod tests {
     #[test(tokio::test)]
     async fn partition_table_resolves_partition_keys() {
         let num_partitions = 10;
-        let partition_table = FixedPartitionTable::new(Version::MIN, num_partitions);
+        let partition_table = PartitionTable::new(Version::MIN, num_partitions);
         let partitioner = partition_table.partitioner();
 
         for (partition_id, partition_range) in partitioner {
@@ -217,4 +471,154 @@ mod tests {
             );
         }
     }
+
+    fn assert_consecutive(partition_table: &PartitionTable) {
+        let mut next_start = Some(0);
+        for (partition_id, key_range) in partition_table.partitioner() {
+            assert_eq!(Some(*key_range.start()), next_start);
+            assert_eq!(
+                partition_table.unchecked_partition_key_to_target_peer(*key_range.start()),
+                partition_id
+            );
+            assert_eq!(
+                partition_table.unchecked_partition_key_to_target_peer(*key_range.end()),
+                partition_id
+            );
+            next_start = key_range.end().checked_add(1);
+        }
+        assert_eq!(next_start, None);
+    }
+
+    #[test]
+    fn split_hands_over_upper_keys_to_new_partition() {
+        let mut partition_table = PartitionTable::new(Version::MIN, 2);
+        let key_range = partition_table.key_range(0).unwrap();
+        let split_key = *key_range.end() - 100;
+
+        let new_partition_id = partition_table.allocate_partition_id();
+        partition_table
+            .split(0, split_key, new_partition_id)
+            .unwrap();
+
+        assert_eq!(new_partition_id, 2);
+        assert_eq!(partition_table.version(), Version::from(3));
+        assert_eq!(partition_table.num_partitions(), 3);
+        assert_eq!(
+            partition_table.key_range(0),
+            Some(*key_range.start()..=split_key - 1)
+        );
+        assert_eq!(
+            partition_table.key_range(2),
+            Some(split_key..=*key_range.end())
+        );
+        assert_eq!(
+            partition_table.initial_key_range(0),
+            Some(key_range.clone())
+        );
+        assert_eq!(partition_table.initial_key_range(2), None);
+        assert_consecutive(&partition_table);
+
+        let hand_over = KeyRangeHandOver {
+            from: 0,
+            to: 2,
+            key_range: split_key..=*key_range.end(),
+        };
+        assert_eq!(partition_table.pending_hand_overs(), [hand_over.clone()]);
+
+        // partitions with pending hand overs can't be updated
+        let other_partition_id = partition_table.allocate_partition_id();
+        assert!(matches!(
+            partition_table.split(2, split_key + 1, other_partition_id),
+            Err(PartitionTableUpdateError::PendingHandOver(2))
+        ));
+
+        assert!(partition_table.complete_hand_over(&hand_over));
+        assert!(!partition_table.complete_hand_over(&hand_over));
+        assert!(partition_table.pending_hand_overs().is_empty());
+        partition_table
+            .split(2, split_key + 1, other_partition_id)
+            .unwrap();
+        assert_consecutive(&partition_table);
+    }
+
+    #[test]
+    fn split_rejects_invalid_updates() {
+        let mut partition_table = PartitionTable::new(Version::MIN, 2);
+        let key_range = partition_table.key_range(1).unwrap();
+
+        assert!(matches!(
+            partition_table.split(1, *key_range.start(), 2),
+            Err(PartitionTableUpdateError::UnavailablePartitionId(2))
+        ));
+
+        let new_partition_id = partition_table.allocate_partition_id();
+        assert!(matches!(
+            partition_table.split(1, *key_range.start(), new_partition_id),
+            Err(PartitionTableUpdateError::InvalidSplitKey { .. })
+        ));
+        assert!(matches!(
+            partition_table.split(0, *key_range.end(), new_partition_id),
+            Err(PartitionTableUpdateError::InvalidSplitKey { .. })
+        ));
+        assert!(matches!(
+            partition_table.split(42, 1, new_partition_id),
+            Err(PartitionTableUpdateError::UnknownPartition(42))
+        ));
+        assert!(matches!(
+            partition_table.split(1, *key_range.end(), 1),
+            Err(PartitionTableUpdateError::UnavailablePartitionId(1))
+        ));
+        assert_eq!(partition_table.version(), Version::from(2));
+    }
+
+    #[test]
+    fn merge_creates_partition_for_adjacent_key_ranges() {
+        let mut partition_table = PartitionTable::new(Version::MIN, 3);
+        let new_partition_id = partition_table.allocate_partition_id();
+
+        assert!(matches!(
+            partition_table.merge(0, 2, new_partition_id),
+            Err(PartitionTableUpdateError::NotAdjacent(0, 2))
+        ));
+
+        partition_table.merge(2, 1, new_partition_id).unwrap();
+        let key_range_1 = partition_table.initial_key_range(1).unwrap();
+        let key_range_2 = partition_table.initial_key_range(2).unwrap();
+        assert_eq!(partition_table.num_partitions(), 2);
+        assert_eq!(
+            partition_table.key_range(3),
+            Some(*key_range_1.start()..=PartitionKey::MAX)
+        );
+        assert_eq!(partition_table.key_range(1), None);
+        assert_eq!(partition_table.key_range(2), None);
+        assert_eq!(
+            partition_table.pending_hand_overs(),
+            [
+                KeyRangeHandOver {
+                    from: 2,
+                    to: 3,
+                    key_range: key_range_2,
+                },
+                KeyRangeHandOver {
+                    from: 1,
+                    to: 3,
+                    key_range: key_range_1,
+                }
+            ]
+        );
+        assert_consecutive(&partition_table);
+
+        for hand_over in partition_table.pending_hand_overs().to_vec() {
+            partition_table.complete_hand_over(&hand_over);
+        }
+        let new_partition_id = partition_table.allocate_partition_id();
+        partition_table.merge(0, 3, new_partition_id).unwrap();
+        assert_eq!(partition_table.version(), Version::from(7));
+        assert_eq!(partition_table.key_range(4), Some(0..=PartitionKey::MAX));
+        assert_eq!(
+            partition_table.merged_partitions().collect::<Vec<_>>(),
+            vec![(0, 4), (1, 4), (2, 4), (3, 4)]
+        );
+        assert_consecutive(&partition_table);
+    }
 }
@@ -0,0 +1,50 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! The schema information of the cluster. It's stored in the metadata store under
+//! [`crate::metadata_store::keys::SCHEMA_INFORMATION_KEY`] and shared between all nodes.
+
+use bytes::Bytes;
+
+use crate::{Version, Versioned};
+
+/// Versioned schema information. It contains the encoded update commands of the schema registry
+/// in the order in which they have been applied. Only the schema registry interprets them, all
+/// other components merely store and distribute them.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SchemaInformation {
+    version: Version,
+    schema_updates: Bytes,
+}
+
+impl SchemaInformation {
+    pub fn new(version: Version, schema_updates: Bytes) -> Self {
+        Self {
+            version,
+            schema_updates,
+        }
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// The encoded update commands of the schema registry.
+    pub fn schema_updates(&self) -> &Bytes {
+        &self.schema_updates
+    }
+}
+
+impl Versioned for SchemaInformation {
+    fn version(&self) -> Version {
+        self.version()
+    }
+}
@@ -0,0 +1,632 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Encoding of [`Envelope`]s in bifrost records.
+//!
+//! Records start with a header of two bytes, a marker followed by the [`EnvelopeFormat`] of the
+//! rest of the record. Records without the marker were written before envelopes were versioned
+//! and are bincode encoded, they can still be decoded.
+//!
+//! The current format encodes the envelope as CBOR with named struct fields and enum variants.
+//! This keeps records readable across changes of the types in [`Command`](crate::Command):
+//! * New struct fields need a default value (`#[serde(default)]`) to read older records.
+//! * Removed struct fields are ignored when reading older records.
+//! * Fields and variants must not be renamed, unless they keep their name with
+//!   `#[serde(rename = "...")]`.
+//! * New enum variants can't be read by older versions.
+//!
+//! Changes which can't be made compatible require a new [`EnvelopeFormat`], while decoding of
+//! the existing formats must be kept until no log can contain records of them anymore.
+
+use std::borrow::Cow;
+use std::time::Duration;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use bytestring::ByteString;
+use restate_storage_api::outbox_table::OutboxMessage;
+use restate_storage_api::timer_table::{Timer, TimerKey};
+use restate_types::errors::InvocationError;
+use restate_types::identifiers::{FullInvocationId, ServiceId};
+use restate_types::ingress::IngressResponse;
+use restate_types::invocation::{
+    self, InvocationResponse, InvocationTermination, ServiceInvocation,
+    ServiceInvocationResponseSink, ServiceInvocationSpanContext,
+};
+use restate_types::message::MessageIndex;
+use restate_types::state_mut::ExternalStateMutation;
+use restate_types::time::MillisSinceEpoch;
+
+use crate::control::AnnounceLeader;
+use crate::effects::{BuiltinServiceEffect, BuiltinServiceEffects};
+use crate::timer::TimerValue;
+use crate::{Command, Destination, Envelope, Header, Source};
+
+/// Marks versioned records. Bincode encoded envelopes start with the variant index of their
+/// source, hence they never start with this marker.
+const VERSIONED_RECORD_MARKER: u8 = 0xEA;
+const HEADER_LEN: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+#[repr(u8)]
+pub enum EnvelopeFormat {
+    /// Bincode encoding without a header, written before envelopes were versioned.
+    Bincode = 0,
+    /// CBOR encoding with named fields and variants.
+    Cbor = 1,
+}
+
+impl EnvelopeFormat {
+    /// The format of newly written records.
+    pub const CURRENT: EnvelopeFormat = EnvelopeFormat::Cbor;
+
+    /// Returns the format of the encoded record.
+    pub fn of(record: &[u8]) -> Result<Self, DecodeError> {
+        match record {
+            [] => Err(DecodeError::Empty),
+            [VERSIONED_RECORD_MARKER] => Err(DecodeError::MissingFormat),
+            [VERSIONED_RECORD_MARKER, format, ..] => Self::try_from(*format),
+            _ => Ok(EnvelopeFormat::Bincode),
+        }
+    }
+}
+
+impl TryFrom<u8> for EnvelopeFormat {
+    type Error = DecodeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(EnvelopeFormat::Cbor),
+            // Bincode records have no header, a header with the bincode format is invalid.
+            _ => Err(DecodeError::UnknownFormat(value)),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EncodeError {
+    #[error("cbor: {0}")]
+    Cbor(#[from] ciborium::ser::Error<std::io::Error>),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+    #[error("record is empty")]
+    Empty,
+    #[error("versioned record lacks the format")]
+    MissingFormat,
+    #[error("unknown envelope format {0}, the record was probably written by a newer version")]
+    UnknownFormat(u8),
+    #[error("bincode: {0}")]
+    Bincode(#[from] bincode::error::DecodeError),
+    #[error("cbor: {0}")]
+    Cbor(#[from] ciborium::de::Error<std::io::Error>),
+}
+
+impl Envelope {
+    /// Encodes the envelope in the [current format](EnvelopeFormat::CURRENT).
+    pub fn encode(&self) -> Result<Bytes, EncodeError> {
+        let mut buf = BytesMut::new();
+        buf.put_u8(VERSIONED_RECORD_MARKER);
+        buf.put_u8(EnvelopeFormat::CURRENT as u8);
+        let mut writer = buf.writer();
+        ciborium::into_writer(self, &mut writer)?;
+        Ok(writer.into_inner().freeze())
+    }
+
+    /// Decodes an envelope of any supported format.
+    pub fn decode(record: impl AsRef<[u8]>) -> Result<Self, DecodeError> {
+        let record = record.as_ref();
+        match EnvelopeFormat::of(record)? {
+            EnvelopeFormat::Bincode => {
+                bincode::serde::decode_from_slice(record, bincode::config::standard())
+                    .map(|(envelope, _): (BincodeEnvelope, _)| envelope.into())
+                    .map_err(Into::into)
+            }
+            EnvelopeFormat::Cbor => {
+                ciborium::from_reader(&record[HEADER_LEN..]).map_err(Into::into)
+            }
+        }
+    }
+}
+
+/// Layout of the envelopes in bincode records. Bincode encodes struct fields by position and enum
+/// variants by index, so the records can only be read with the layout of the types at the time
+/// they were written. The layout is frozen, it must not change with the current types.
+///
+/// Types which haven't changed since are used as they are, they need to be frozen here once they
+/// change.
+#[derive(serde::Deserialize)]
+struct BincodeEnvelope {
+    header: BincodeHeader,
+    command: BincodeCommand,
+}
+
+/// Headers had no creation time.
+#[derive(serde::Deserialize)]
+struct BincodeHeader {
+    source: Source,
+    dest: Destination,
+}
+
+#[derive(serde::Deserialize)]
+enum BincodeCommand {
+    AnnounceLeader(AnnounceLeader),
+    PatchState(ExternalStateMutation),
+    TerminateInvocation(InvocationTermination),
+    Invoke(BincodeServiceInvocation),
+    TruncateOutbox(MessageIndex),
+    InvokerEffect(restate_invoker_api::Effect),
+    Timer(BincodeTimerValue),
+    InvocationResponse(InvocationResponse),
+    BuiltInInvokerEffect(BincodeBuiltinServiceEffects),
+}
+
+/// Service invocations had neither a completion retention time nor attached response sinks.
+#[derive(serde::Deserialize)]
+struct BincodeServiceInvocation {
+    fid: FullInvocationId,
+    method_name: ByteString,
+    argument: Bytes,
+    source: invocation::Source,
+    response_sink: Option<ServiceInvocationResponseSink>,
+    span_context: ServiceInvocationSpanContext,
+    headers: Vec<invocation::Header>,
+    execution_time: Option<MillisSinceEpoch>,
+}
+
+#[derive(serde::Deserialize)]
+struct BincodeTimerValue {
+    timer_key: TimerKey,
+    value: BincodeTimer,
+}
+
+#[derive(serde::Deserialize)]
+enum BincodeTimer {
+    CompleteSleepEntry(ServiceId),
+    Invoke(BincodeServiceInvocation),
+}
+
+#[derive(serde::Deserialize)]
+struct BincodeBuiltinServiceEffects {
+    full_invocation_id: FullInvocationId,
+    effects: Vec<BincodeBuiltinServiceEffect>,
+}
+
+#[derive(serde::Deserialize)]
+enum BincodeBuiltinServiceEffect {
+    SetState {
+        key: Cow<'static, str>,
+        value: Bytes,
+    },
+    ClearState(Cow<'static, str>),
+    OutboxMessage(BincodeOutboxMessage),
+    End(Option<InvocationError>),
+    IngressResponse(IngressResponse),
+}
+
+#[derive(serde::Deserialize)]
+enum BincodeOutboxMessage {
+    ServiceInvocation(BincodeServiceInvocation),
+    ServiceResponse(InvocationResponse),
+    InvocationTermination(InvocationTermination),
+}
+
+impl From<BincodeEnvelope> for Envelope {
+    fn from(BincodeEnvelope { header, command }: BincodeEnvelope) -> Self {
+        Envelope::new(
+            Header {
+                source: header.source,
+                dest: header.dest,
+                created_at: None,
+            },
+            command.into(),
+        )
+    }
+}
+
+impl From<BincodeCommand> for Command {
+    fn from(command: BincodeCommand) -> Self {
+        match command {
+            BincodeCommand::AnnounceLeader(announce_leader) => {
+                Command::AnnounceLeader(announce_leader)
+            }
+            BincodeCommand::PatchState(mutation) => Command::PatchState(mutation),
+            BincodeCommand::TerminateInvocation(termination) => {
+                Command::TerminateInvocation(termination)
+            }
+            BincodeCommand::Invoke(service_invocation) => {
+                Command::Invoke(service_invocation.into())
+            }
+            BincodeCommand::TruncateOutbox(index) => Command::TruncateOutbox(index),
+            BincodeCommand::InvokerEffect(effect) => Command::InvokerEffect(effect),
+            BincodeCommand::Timer(timer_value) => Command::Timer(timer_value.into()),
+            BincodeCommand::InvocationResponse(response) => Command::InvocationResponse(response),
+            BincodeCommand::BuiltInInvokerEffect(effects) => {
+                Command::BuiltInInvokerEffect(effects.into())
+            }
+        }
+    }
+}
+
+impl From<BincodeServiceInvocation> for ServiceInvocation {
+    fn from(service_invocation: BincodeServiceInvocation) -> Self {
+        ServiceInvocation {
+            fid: service_invocation.fid,
+            method_name: service_invocation.method_name,
+            argument: service_invocation.argument,
+            source: service_invocation.source,
+            response_sink: service_invocation.response_sink,
+            span_context: service_invocation.span_context,
+            headers: service_invocation.headers,
+            execution_time: service_invocation.execution_time,
+            completion_retention_time: Duration::ZERO,
+            attached_response_sinks: vec![],
+        }
+    }
+}
+
+impl From<BincodeTimerValue> for TimerValue {
+    fn from(BincodeTimerValue { timer_key, value }: BincodeTimerValue) -> Self {
+        let value = match value {
+            BincodeTimer::CompleteSleepEntry(service_id) => Timer::CompleteSleepEntry(service_id),
+            BincodeTimer::Invoke(service_invocation) => Timer::Invoke(service_invocation.into()),
+        };
+        TimerValue::new(timer_key, value)
+    }
+}
+
+impl From<BincodeBuiltinServiceEffects> for BuiltinServiceEffects {
+    fn from(effects: BincodeBuiltinServiceEffects) -> Self {
+        BuiltinServiceEffects::new(
+            effects.full_invocation_id,
+            effects.effects.into_iter().map(Into::into).collect(),
+        )
+    }
+}
+
+impl From<BincodeBuiltinServiceEffect> for BuiltinServiceEffect {
+    fn from(effect: BincodeBuiltinServiceEffect) -> Self {
+        match effect {
+            BincodeBuiltinServiceEffect::SetState { key, value } => {
+                BuiltinServiceEffect::SetState { key, value }
+            }
+            BincodeBuiltinServiceEffect::ClearState(key) => BuiltinServiceEffect::ClearState(key),
+            BincodeBuiltinServiceEffect::OutboxMessage(message) => {
+                BuiltinServiceEffect::OutboxMessage(message.into())
+            }
+            BincodeBuiltinServiceEffect::End(error) => BuiltinServiceEffect::End(error),
+            BincodeBuiltinServiceEffect::IngressResponse(response) => {
+                BuiltinServiceEffect::IngressResponse(response)
+            }
+        }
+    }
+}
+
+impl From<BincodeOutboxMessage> for OutboxMessage {
+    fn from(message: BincodeOutboxMessage) -> Self {
+        match message {
+            BincodeOutboxMessage::ServiceInvocation(service_invocation) => {
+                OutboxMessage::ServiceInvocation(service_invocation.into())
+            }
+            BincodeOutboxMessage::ServiceResponse(response) => {
+                OutboxMessage::ServiceResponse(response)
+            }
+            BincodeOutboxMessage::InvocationTermination(termination) => {
+                OutboxMessage::InvocationTermination(termination)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use restate_invoker_api::{Effect, EffectKind};
+    use restate_test_util::let_assert;
+    use restate_types::identifiers::{InvocationId, InvocationUuid, LeaderEpoch};
+    use restate_types::invocation::{
+        AttachInvocationRequest, AttachedResponseSink, PurgeInvocationRequest, ResponseResult,
+        SpanRelation,
+    };
+    use restate_types::{GenerationalNodeId, PlainNodeId, Version};
+
+    use crate::control::{HandOverKeyRange, KeyRangeData, KeyRangeTable, TakeOverKeyRange};
+
+    fn header() -> Header {
+        Header {
+            source: Source::Processor {
+                partition_id: 1,
+                partition_key: Some(42),
+                leader_epoch: LeaderEpoch::from(3),
+                node_id: PlainNodeId::from(1),
+            },
+            dest: Destination::Processor {
+                partition_key: 42,
+                dedup: None,
+            },
+            created_at: Some(MillisSinceEpoch::new(1_700_000_000_000)),
+        }
+    }
+
+    fn envelopes() -> Vec<Envelope> {
+        vec![
+            Envelope::new(
+                header(),
+                Command::AnnounceLeader(AnnounceLeader {
+                    node_id: GenerationalNodeId::new(1, 2),
+                    leader_epoch: LeaderEpoch::from(3),
+                }),
+            ),
+            Envelope::new(header(), Command::TruncateOutbox(7)),
+            Envelope::new(
+                header(),
+                Command::HandOverKeyRange(HandOverKeyRange {
+                    key_range: 42..=84,
+                    to: 2,
+                    partition_table_version: Version::from(3),
+                }),
+            ),
+            Envelope::new(
+                header(),
+                Command::TakeOverKeyRange(TakeOverKeyRange {
+                    key_range: 42..=84,
+                    from: 1,
+                }),
+            ),
+            Envelope::new(
+                header(),
+                Command::KeyRangeData(KeyRangeData {
+                    from: 1,
+                    hand_over: HandOverKeyRange {
+                        key_range: 42..=84,
+                        to: 2,
+                        partition_table_version: Version::from(3),
+                    },
+                    tables: vec![KeyRangeTable {
+                        table: "state".to_owned(),
+                        entries: vec![(Bytes::from_static(b"key"), Bytes::from_static(b"value"))],
+                    }],
+                    timers: vec![],
+                    dedup_information: vec![],
+                    inbox_seq_number: 7,
+                }),
+            ),
+            Envelope::new(
+                header(),
+                Command::Invoke(ServiceInvocation::new(
+                    FullInvocationId::generate(ServiceId::new("greeter", "key")),
+                    "greet",
+                    Bytes::from_static(b"world"),
+                    invocation::Source::Ingress,
+                    Some(ServiceInvocationResponseSink::Ingress(
+                        GenerationalNodeId::new(1, 2),
+                    )),
+                    SpanRelation::None,
+                    vec![],
+                    None,
+                )),
+            ),
+            Envelope::new(
+                header(),
+                Command::InvocationResponse(InvocationResponse {
+                    id: FullInvocationId::generate(ServiceId::new("greeter", "key")).into(),
+                    entry_index: 3,
+                    result: ResponseResult::Success(Bytes::from_static(b"hello")),
+                }),
+            ),
+            Envelope::new(
+                header(),
+                Command::AttachInvocation(AttachInvocationRequest {
+                    invocation_id: InvocationId::from(FullInvocationId::generate(ServiceId::new(
+                        "greeter", "key",
+                    ))),
+                    block_on_inflight: true,
+                    response_sink: AttachedResponseSink {
+                        node_id: GenerationalNodeId::new(1, 2),
+                        request_id: 7,
+                    },
+                }),
+            ),
+            Envelope::new(
+                header(),
+                Command::PurgeInvocation(PurgeInvocationRequest {
+                    invocation_id: InvocationId::from(FullInvocationId::generate(ServiceId::new(
+                        "greeter", "key",
+                    ))),
+                }),
+            ),
+        ]
+    }
+
+    #[test]
+    fn roundtrip() -> anyhow::Result<()> {
+        for envelope in envelopes() {
+            let record = envelope.encode()?;
+            assert_eq!(EnvelopeFormat::CURRENT, EnvelopeFormat::of(&record)?);
+            assert_eq!(envelope, Envelope::decode(&record)?);
+        }
+        Ok(())
+    }
+
+    // Bincode records of the commands written before envelopes were versioned, hex encoded.
+    const ANNOUNCE_LEADER: &str = "01010201002a0000010201";
+    const PATCH_STATE: &str =
+        "01010201002a00010767726565746572036b6579fd3983587c27fa9f9f0001016b0176";
+    const TERMINATE_INVOCATION: &str =
+        "01010201002a0002010767726565746572036b6579fd3983587c27fa9f9f16306467394d4b6f5632\
+        4c36614b73503377537058793700";
+    const INVOKE: &str =
+        "01010201002a00030767726565746572036b6579fd3983587c27fa9f9f16306467394d4b6f56324c\
+        36614b73503377537058793705677265657405776f726c6400010201020000000000000000000000\
+        000000000000000000000000000000000001046e616d650576616c756501fd0068e5cf8b010000";
+    const TRUNCATE_OUTBOX: &str = "01010201002a000407";
+    const INVOKER_EFFECT: &str =
+        "01010201002a00050767726565746572036b6579fd3983587c27fa9f9f16306467394d4b6f56324c\
+        36614b73503377537058793703";
+    const TIMER: &str =
+        "01010201002a0006fde86be5cf8b01000016306467394d4b6f56324c36614b735033775370587937\
+        02010767726565746572036b6579fd3983587c27fa9f9f16306467394d4b6f56324c36614b735033\
+        77537058793705677265657405776f726c6400010201020000000000000000000000000000000000\
+        000000000000000000000001046e616d650576616c756501fd0068e5cf8b010000";
+    const INVOCATION_RESPONSE: &str =
+        "01010201002a0007010767726565746572036b6579fd3983587c27fa9f9f16306467394d4b6f5632\
+        4c36614b73503377537058793703000568656c6c6f";
+    const BUILT_IN_INVOKER_EFFECT: &str =
+        "01010201002a00080767726565746572036b6579fd3983587c27fa9f9f16306467394d4b6f56324c\
+        36614b7350337753705879370202000767726565746572036b6579fd3983587c27fa9f9f16306467\
+        394d4b6f56324c36614b73503377537058793705677265657405776f726c64000102010200000000\
+        00000000000000000000000000000000000000000000000001046e616d650576616c756501fd0068\
+        e5cf8b0100000300";
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).expect("valid hex"))
+            .collect()
+    }
+
+    fn bincode_fid() -> FullInvocationId {
+        FullInvocationId::new(
+            "greeter",
+            Bytes::from_static(b"key"),
+            InvocationUuid::from_bytes([7; 16]),
+        )
+    }
+
+    fn bincode_service_invocation() -> ServiceInvocation {
+        ServiceInvocation {
+            fid: bincode_fid(),
+            method_name: "greet".into(),
+            argument: Bytes::from_static(b"world"),
+            source: invocation::Source::Ingress,
+            response_sink: Some(ServiceInvocationResponseSink::Ingress(
+                GenerationalNodeId::new(1, 2),
+            )),
+            span_context: ServiceInvocationSpanContext::empty(),
+            headers: vec![invocation::Header::new("name", "value")],
+            execution_time: Some(MillisSinceEpoch::new(1_700_000_000_000)),
+            completion_retention_time: Duration::ZERO,
+            attached_response_sinks: vec![],
+        }
+    }
+
+    #[test]
+    fn decode_bincode_records() -> anyhow::Result<()> {
+        let header = Header {
+            source: Source::Ingress {
+                node_id: GenerationalNodeId::new(1, 2),
+                nodes_config_version: Version::MIN,
+            },
+            dest: Destination::Processor {
+                partition_key: 42,
+                dedup: None,
+            },
+            created_at: None,
+        };
+        let records = [
+            (
+                ANNOUNCE_LEADER,
+                Command::AnnounceLeader(AnnounceLeader {
+                    node_id: GenerationalNodeId::new(1, 2),
+                    leader_epoch: LeaderEpoch::INITIAL,
+                }),
+            ),
+            (
+                PATCH_STATE,
+                Command::PatchState(ExternalStateMutation {
+                    component_id: ServiceId::new("greeter", "key"),
+                    version: None,
+                    state: [(Bytes::from_static(b"k"), Bytes::from_static(b"v"))]
+                        .into_iter()
+                        .collect(),
+                }),
+            ),
+            (
+                TERMINATE_INVOCATION,
+                Command::TerminateInvocation(InvocationTermination::kill(bincode_fid())),
+            ),
+            (INVOKE, Command::Invoke(bincode_service_invocation())),
+            (TRUNCATE_OUTBOX, Command::TruncateOutbox(7)),
+            (
+                INVOKER_EFFECT,
+                Command::InvokerEffect(Effect {
+                    full_invocation_id: bincode_fid(),
+                    kind: EffectKind::End,
+                }),
+            ),
+            (
+                INVOCATION_RESPONSE,
+                Command::InvocationResponse(InvocationResponse {
+                    id: bincode_fid().into(),
+                    entry_index: 3,
+                    result: ResponseResult::Success(Bytes::from_static(b"hello")),
+                }),
+            ),
+            (
+                BUILT_IN_INVOKER_EFFECT,
+                Command::BuiltInInvokerEffect(BuiltinServiceEffects::new(
+                    bincode_fid(),
+                    vec![
+                        BuiltinServiceEffect::OutboxMessage(OutboxMessage::ServiceInvocation(
+                            bincode_service_invocation(),
+                        )),
+                        BuiltinServiceEffect::End(None),
+                    ],
+                )),
+            ),
+        ];
+
+        for (record, command) in records {
+            let record = from_hex(record);
+            assert_eq!(EnvelopeFormat::Bincode, EnvelopeFormat::of(&record)?);
+            assert_eq!(
+                Envelope::new(header.clone(), command),
+                Envelope::decode(&record)?
+            );
+        }
+
+        // timer values are compared by their key only
+        let record = from_hex(TIMER);
+        assert_eq!(EnvelopeFormat::Bincode, EnvelopeFormat::of(&record)?);
+        let envelope = Envelope::decode(&record)?;
+        assert_eq!(header, envelope.header);
+        let_assert!(Command::Timer(timer_value) = envelope.command);
+        let (timer_key, timer) = timer_value.into_inner();
+        assert_eq!(
+            TimerKey {
+                invocation_uuid: bincode_fid().invocation_uuid,
+                timestamp: 1_700_000_001_000,
+                journal_index: 2,
+            },
+            timer_key
+        );
+        assert_eq!(Timer::Invoke(bincode_service_invocation()), timer);
+
+        Ok(())
+    }
+
+    #[test]
+    fn reject_unknown_formats() {
+        assert!(matches!(Envelope::decode([]), Err(DecodeError::Empty)));
+        assert!(matches!(
+            Envelope::decode([VERSIONED_RECORD_MARKER]),
+            Err(DecodeError::MissingFormat)
+        ));
+        assert!(matches!(
+            Envelope::decode([VERSIONED_RECORD_MARKER, 0]),
+            Err(DecodeError::UnknownFormat(0))
+        ));
+        assert!(matches!(
+            Envelope::decode([VERSIONED_RECORD_MARKER, 42, 1, 2, 3]),
+            Err(DecodeError::UnknownFormat(42))
+        ));
+    }
+}
@@ -8,8 +8,14 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use restate_types::identifiers::LeaderEpoch;
-use restate_types::GenerationalNodeId;
+use std::ops::RangeInclusive;
+
+use bytes::Bytes;
+use restate_storage_api::timer_table::{Timer, TimerKey};
+use restate_types::dedup::DedupInformation;
+use restate_types::identifiers::{LeaderEpoch, PartitionId, PartitionKey};
+use restate_types::message::MessageIndex;
+use restate_types::{GenerationalNodeId, Version};
 
 /// Announcing a new leader. This message can be written by any component to make the specified
 /// partition processor the leader.
@@ -21,3 +27,52 @@ pub struct AnnounceLeader {
     pub node_id: GenerationalNodeId,
     pub leader_epoch: LeaderEpoch,
 }
+
+/// Hands over the keys of the key range to another partition after a split or merge of the
+/// partition table. It's appended to the log of the partition which owns the keys. The partition
+/// processor stops processing the keys and records the hand over. Its leader appends the data of
+/// the keys as [`KeyRangeData`] to the log of the other partition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HandOverKeyRange {
+    pub key_range: RangeInclusive<PartitionKey>,
+    pub to: PartitionId,
+    /// The version of the partition table which assigns the keys to the other partition.
+    pub partition_table_version: Version,
+}
+
+/// Takes over the keys of the key range from another partition. It's appended to the log of the
+/// partition which receives the keys before the partition table assigns the keys to it. The
+/// partition processor takes over the keys once the [`KeyRangeData`] of the hand over has been
+/// appended to its log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TakeOverKeyRange {
+    pub key_range: RangeInclusive<PartitionKey>,
+    pub from: PartitionId,
+}
+
+/// The data of keys which have been handed over to another partition. It's appended to the log of
+/// the partition which takes over the keys by the leader of the partition which handed them over,
+/// again by every new leader, so that the keys can be taken over on any node. Only the first
+/// [`KeyRangeData`] of a hand over is taken over, later ones are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyRangeData {
+    pub from: PartitionId,
+    pub hand_over: HandOverKeyRange,
+    /// Entries of the tables which are keyed by partition key, e.g. state, invocations and
+    /// inboxes, in the storage format of the partition store.
+    pub tables: Vec<KeyRangeTable>,
+    pub timers: Vec<(TimerKey, Timer)>,
+    /// Deduplication sequence numbers of the producers which sent messages to the partition.
+    pub dedup_information: Vec<DedupInformation>,
+    pub inbox_seq_number: MessageIndex,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyRangeTable {
+    pub table: String,
+    pub entries: Vec<(Bytes, Bytes)>,
+}
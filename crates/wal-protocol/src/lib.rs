@@ -8,16 +8,21 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use bytes::Bytes;
+use std::ops::Range;
+
 use restate_bifrost::Bifrost;
 use restate_core::metadata;
 use restate_types::identifiers::{LeaderEpoch, PartitionId, PartitionKey, WithPartitionKey};
-use restate_types::invocation::{InvocationResponse, InvocationTermination, ServiceInvocation};
+use restate_types::invocation::{
+    AttachInvocationRequest, InvocationResponse, InvocationTermination, PurgeInvocationRequest,
+    ServiceInvocation,
+};
 use restate_types::message::MessageIndex;
 use restate_types::state_mut::ExternalStateMutation;
+use restate_types::time::MillisSinceEpoch;
 use restate_types::Version;
 
-use crate::control::AnnounceLeader;
+use crate::control::{AnnounceLeader, HandOverKeyRange, KeyRangeData, TakeOverKeyRange};
 use crate::effects::BuiltinServiceEffects;
 use crate::timer::TimerValue;
 use restate_types::dedup::DedupInformation;
@@ -25,10 +30,13 @@ use restate_types::logs::{LogId, Lsn, Payload};
 use restate_types::partition_table::{FindPartition, PartitionTableError};
 use restate_types::{GenerationalNodeId, PlainNodeId};
 
+mod codec;
 pub mod control;
 pub mod effects;
 pub mod timer;
 
+pub use crate::codec::{DecodeError, EncodeError, EnvelopeFormat};
+
 /// The primary envelope for all messages in the system.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -41,17 +49,6 @@ impl Envelope {
     pub fn new(header: Header, command: Command) -> Self {
         Self { header, command }
     }
-
-    pub fn encode_with_bincode(&self) -> Result<Bytes, bincode::error::EncodeError> {
-        bincode::serde::encode_to_vec(self, bincode::config::standard()).map(Into::into)
-    }
-
-    pub fn decode_with_bincode(
-        bytes: impl AsRef<[u8]>,
-    ) -> Result<Self, bincode::error::DecodeError> {
-        bincode::serde::decode_from_slice(bytes.as_ref(), bincode::config::standard())
-            .map(|(envelope, _)| envelope)
-    }
 }
 
 /// Header is set on every message
@@ -60,6 +57,12 @@ impl Envelope {
 pub struct Header {
     pub source: Source,
     pub dest: Destination,
+    /// Time at which the source created the message. Unlike the clocks of the replicas which
+    /// apply the message, it is the same on all of them, so it's used wherever applying the
+    /// message depends on the current time. Unset for messages which were written before
+    /// messages had a creation time.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub created_at: Option<MillisSinceEpoch>,
 }
 
 /// Identifies the source of a message
@@ -134,12 +137,37 @@ pub enum Command {
     InvocationResponse(InvocationResponse),
     /// A built-in invoker reporting effects from an invocation.
     BuiltInInvokerEffect(BuiltinServiceEffects),
+
+    // -- Control-plane related events for splitting and merging partitions
+    HandOverKeyRange(HandOverKeyRange),
+    TakeOverKeyRange(TakeOverKeyRange),
+    KeyRangeData(KeyRangeData),
+
+    // -- Partition processor commands added later. New variants are appended to keep the
+    // variant indices of the existing ones.
+    /// Attach to an invocation in order to receive its result
+    AttachInvocation(AttachInvocationRequest),
+    /// Purge the retained result of a completed invocation
+    PurgeInvocation(PurgeInvocationRequest),
 }
 
 impl Command {
     pub fn name(&self) -> &'static str {
         CommandDiscriminants::from(self).into()
     }
+
+    /// Whether the command is targeted at the partition whose log contains it rather than at the
+    /// partition which owns the destination's partition key.
+    pub fn is_partition_scoped(&self) -> bool {
+        matches!(
+            self,
+            Command::AnnounceLeader(_)
+                | Command::TruncateOutbox(_)
+                | Command::HandOverKeyRange(_)
+                | Command::TakeOverKeyRange(_)
+                | Command::KeyRangeData(_)
+        )
+    }
 }
 
 impl WithPartitionKey for Envelope {
@@ -155,7 +183,7 @@ pub enum Error {
     #[error("partition not found: {0}")]
     PartitionNotFound(#[from] PartitionTableError),
     #[error("failed encoding envelope: {0}")]
-    Encode(#[from] bincode::error::EncodeError),
+    Encode(#[from] EncodeError),
     #[error("failed writing to bifrost: {0}")]
     Bifrost(#[from] restate_bifrost::Error),
 }
@@ -174,8 +202,56 @@ pub async fn append_envelope_to_bifrost(
         .find_partition_id(envelope.partition_key())?;
 
     let log_id = LogId::from(partition_id);
-    let payload = Payload::from(envelope.encode_with_bincode()?);
+    let payload = Payload::from(envelope.encode()?);
+    let lsn = bifrost.append(log_id, payload).await?;
+
+    Ok((log_id, lsn))
+}
+
+/// Appends the given envelope to the log of the given partition, regardless of which partition
+/// owns the envelope's partition key. This is used for commands which are targeted at a
+/// specific partition, see [`Command::is_partition_scoped`].
+pub async fn append_envelope_to_partition(
+    bifrost: &mut Bifrost,
+    partition_id: PartitionId,
+    envelope: Envelope,
+) -> Result<(LogId, Lsn), Error> {
+    let log_id = LogId::from(partition_id);
+    let payload = Payload::from(envelope.encode()?);
     let lsn = bifrost.append(log_id, payload).await?;
 
     Ok((log_id, lsn))
 }
+
+/// Appends the given envelopes to the provided Bifrost instance. Consecutive envelopes which
+/// belong to the same log are appended as a single batch. The order of envelopes is preserved
+/// within each log.
+///
+/// Important: This method must only be called in the context of a [`TaskCenter`] task because
+/// it needs access to [`metadata()`].
+pub async fn append_envelopes_to_bifrost(
+    bifrost: &mut Bifrost,
+    envelopes: impl IntoIterator<Item = Envelope>,
+) -> Result<Vec<(LogId, Range<Lsn>)>, Error> {
+    let partition_table = metadata().partition_table();
+    let mut batches: Vec<(LogId, Vec<Payload>)> = Vec::new();
+
+    for envelope in envelopes {
+        let partition_id = partition_table.find_partition_id(envelope.partition_key())?;
+        let log_id = LogId::from(partition_id);
+        let payload = Payload::from(envelope.encode()?);
+
+        match batches.last_mut() {
+            Some((last_log_id, payloads)) if *last_log_id == log_id => payloads.push(payload),
+            _ => batches.push((log_id, vec![payload])),
+        }
+    }
+
+    let mut appended = Vec::with_capacity(batches.len());
+    for (log_id, payloads) in batches {
+        let lsns = bifrost.append_batch(log_id, payloads).await?;
+        appended.push((log_id, lsns));
+    }
+
+    Ok(appended)
+}
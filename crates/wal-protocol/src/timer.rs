@@ -66,6 +66,24 @@ impl TimerValue {
         }
     }
 
+    pub fn new_clean_invocation_status(
+        full_invocation_id: FullInvocationId,
+        expiration_time: MillisSinceEpoch,
+    ) -> Self {
+        let timer_key = TimerKeyWrapper(TimerKey {
+            invocation_uuid: full_invocation_id.invocation_uuid,
+            timestamp: expiration_time.as_u64(),
+            // sleep timers never use the index of the input entry, and the timer of a delayed
+            // invocation which uses it has fired before the invocation completed
+            journal_index: 0,
+        });
+
+        Self {
+            timer_key,
+            value: Timer::CleanInvocationStatus(full_invocation_id.service_id),
+        }
+    }
+
     pub fn into_inner(self) -> (TimerKey, Timer) {
         (self.timer_key.0, self.value)
     }
@@ -11,13 +11,16 @@
 extern crate core;
 
 use crate::invoker_integration::EntryEnricher;
+use crate::node_drainer::NodeDrainer;
+use crate::partition::snapshots::SnapshotSettings;
 use crate::partition::storage::invoker::InvokerStorageReader;
-use anyhow::Context;
+use crate::partition_processor_manager::PartitionProcessorManager;
 use codederror::CodedError;
 use restate_bifrost::Bifrost;
+use restate_core::metadata_store::MetadataStoreClient;
 use restate_core::network::MessageRouterBuilder;
-use restate_core::{cancellation_watcher, metadata, task_center, TaskKind};
-use restate_ingress_dispatcher::IngressDispatcher;
+use restate_core::{cancellation_watcher, task_center, TaskKind};
+use restate_ingress_dispatcher::{IngressAppender, IngressDispatcher};
 use restate_ingress_http::HyperServerIngress;
 use restate_ingress_kafka::Service as IngressKafkaService;
 use restate_invoker_impl::{
@@ -28,15 +31,18 @@ use restate_schema_impl::Schemas;
 use restate_service_protocol::codec::ProtobufRawEntryCodec;
 use restate_storage_query_datafusion::context::QueryContext;
 use restate_storage_query_postgres::service::PostgresQueryService;
+use restate_storage_rocksdb::snapshots::SnapshotRepository;
 use restate_storage_rocksdb::{RocksDBStorage, RocksDBWriter};
-use restate_types::identifiers::{LeaderEpoch, PartitionId, PartitionKey};
-use std::ops::RangeInclusive;
-use std::path::Path;
+use restate_types::DEFAULT_STORAGE_DIRECTORY;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tracing::debug;
 
 mod invoker_integration;
 mod metric_definitions;
+mod node_drainer;
 mod partition;
+mod partition_processor_manager;
 mod subscription_integration;
 
 pub use restate_ingress_http::{
@@ -72,9 +78,6 @@ pub use restate_storage_query_postgres::{
     Options as StorageQueryPostgresOptions, OptionsBuilder as StorageQueryPostgresOptionsBuilder,
     OptionsBuilderError as StorageQueryPostgresOptionsBuilderError,
 };
-use restate_types::logs::{LogId, Payload};
-use restate_wal_protocol::control::AnnounceLeader;
-use restate_wal_protocol::{Command, Destination, Envelope, Header, Source};
 
 type PartitionProcessor =
     partition::PartitionProcessor<ProtobufRawEntryCodec, InvokerChannelServiceHandle>;
@@ -99,6 +102,57 @@ pub struct Options {
     pub kafka: KafkaIngressOptions,
     invoker: InvokerOptions,
 
+    /// # Log trim interval
+    ///
+    /// Interval at which the leading partition processors trim their log up to the LSN which all
+    /// replicas of the partition have applied and the leader's latest partition snapshot covers.
+    /// Snapshots are local to each node, replicas which are added after the log has been trimmed
+    /// can't recover the trimmed records. The log is not trimmed if there are no snapshots.
+    /// Unsetting it disables log trimming.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde(with = "serde_with::As::<Option<serde_with::DisplayFromStr>>")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "Option<String>"))]
+    log_trim_interval: Option<humantime::Duration>,
+
+    /// # Snapshot interval
+    ///
+    /// Interval at which partition processors create a snapshot of their partition store in the
+    /// snapshots directory. Partition processors bootstrap from the latest snapshot of their
+    /// partition and only replay the log which follows it. Unsetting it disables the creation of
+    /// snapshots.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde(with = "serde_with::As::<Option<serde_with::DisplayFromStr>>")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "Option<String>"))]
+    snapshot_interval: Option<humantime::Duration>,
+
+    /// # Snapshots path
+    ///
+    /// Directory in which partition snapshots are stored. In a cluster with several worker nodes,
+    /// this should be a directory which all of them share, so that partition processors can
+    /// bootstrap from the snapshots of other nodes.
+    ///
+    /// Object stores are not accessed directly, urls like `s3://bucket/snapshots` are rejected.
+    /// To store snapshots in an object store, mount the bucket with a file system which supports
+    /// renaming directories and configure the mount point.
+    snapshots_path: PathBuf,
+
+    /// # Snapshot retention
+    ///
+    /// Number of most recent snapshots which are kept for each partition.
+    snapshot_retention: usize,
+
+    /// # Completion retention time
+    ///
+    /// Time for which the status and result of invocations which were sent through the ingress
+    /// are retained after they completed. A zero duration disables the retention.
+    ///
+    /// Can be configured using the [`humantime`](https://docs.rs/humantime/latest/humantime/fn.parse_duration.html) format.
+    #[serde(with = "serde_with::As::<serde_with::DisplayFromStr>")]
+    #[cfg_attr(feature = "options_schema", schemars(with = "String"))]
+    completion_retention_time: humantime::Duration,
+
     /// # Partitions
     ///
     /// Number of partitions to be used to process messages.
@@ -121,6 +175,11 @@ impl Default for Options {
             ingress: Default::default(),
             kafka: Default::default(),
             invoker: Default::default(),
+            log_trim_interval: Some(Duration::from_secs(60 * 60).into()),
+            snapshot_interval: Some(Duration::from_secs(60 * 60).into()),
+            snapshots_path: Path::new(DEFAULT_STORAGE_DIRECTORY).join("snapshots"),
+            snapshot_retention: 2,
+            completion_retention_time: Duration::from_secs(60 * 60).into(),
             partitions: 64,
         }
     }
@@ -140,6 +199,12 @@ pub enum BuildError {
         #[code]
         restate_storage_rocksdb::BuildError,
     ),
+    #[error(
+        "failed creating worker: snapshots path '{0}' is an object store url, object stores are \
+        only supported as a mounted directory"
+    )]
+    #[code(unknown)]
+    ObjectStoreSnapshotsPath(String),
 }
 
 impl Options {
@@ -153,9 +218,17 @@ impl Options {
         bifrost: Bifrost,
         router_builder: &mut MessageRouterBuilder,
         schemas: Schemas,
+        metadata_store_client: MetadataStoreClient,
     ) -> Result<Worker, BuildError> {
         metric_definitions::describe_metrics();
-        Worker::new(self, networking, bifrost, router_builder, schemas)
+        Worker::new(
+            self,
+            networking,
+            bifrost,
+            router_builder,
+            schemas,
+            metadata_store_client,
+        )
     }
 }
 
@@ -179,8 +252,6 @@ impl Error {
 }
 
 pub struct Worker {
-    options: Options,
-    networking: Networking,
     storage_query_context: QueryContext,
     storage_query_postgres: PostgresQueryService,
     #[allow(clippy::type_complexity)]
@@ -191,10 +262,13 @@ pub struct Worker {
         Schemas,
     >,
     external_client_ingress: ExternalClientIngress,
+    ingress_appender: IngressAppender,
     ingress_kafka: IngressKafkaService,
     subscription_controller_handle: SubscriptionControllerHandle,
     rocksdb_writer: RocksDBWriter,
     rocksdb_storage: RocksDBStorage,
+    partition_processor_manager: PartitionProcessorManager,
+    node_drainer: NodeDrainer,
 }
 
 impl Worker {
@@ -204,19 +278,32 @@ impl Worker {
         bifrost: Bifrost,
         router_builder: &mut MessageRouterBuilder,
         schemas: Schemas,
+        metadata_store_client: MetadataStoreClient,
     ) -> Result<Self, BuildError> {
-        let options = opts.clone();
-
         let Options {
+            channel_size,
+            timers,
             ingress,
             kafka,
             storage_query_datafusion,
             storage_query_postgres,
             storage_rocksdb,
+            invoker,
+            log_trim_interval,
+            snapshot_interval,
+            snapshots_path,
+            snapshot_retention,
+            completion_retention_time,
             ..
         } = opts;
 
-        let ingress_dispatcher = IngressDispatcher::new(bifrost);
+        if let Some(url) = snapshots_path.to_str().filter(|path| path.contains("://")) {
+            return Err(BuildError::ObjectStoreSnapshotsPath(url.to_owned()));
+        }
+
+        let ingress_appender = IngressAppender::new(bifrost.clone());
+        let ingress_dispatcher =
+            IngressDispatcher::new(ingress_appender.handle(), completion_retention_time.into());
         router_builder.add_message_handler(ingress_dispatcher.clone());
 
         // http ingress
@@ -234,7 +321,7 @@ impl Worker {
         let (rocksdb_storage, rocksdb_writer) = storage_rocksdb.build()?;
 
         let invoker_storage_reader = InvokerStorageReader::new(rocksdb_storage.clone());
-        let invoker = opts.invoker.build(
+        let invoker = invoker.build(
             invoker_storage_reader.clone(),
             invoker_storage_reader,
             EntryEnricher::new(schemas.clone()),
@@ -248,39 +335,44 @@ impl Worker {
         )?;
         let storage_query_postgres = storage_query_postgres.build(storage_query_context.clone());
 
-        Ok(Self {
-            options,
+        let partition_processor_manager = PartitionProcessorManager::new(
+            timers,
+            channel_size,
+            log_trim_interval.map(Into::into),
+            SnapshotSettings {
+                repository: SnapshotRepository::new(snapshots_path),
+                interval: snapshot_interval.map(Into::into),
+                retention: snapshot_retention,
+            },
             networking,
+            bifrost,
+            invoker.handle(),
+            rocksdb_storage.clone(),
+            router_builder,
+        );
+
+        let node_drainer = NodeDrainer::new(
+            metadata_store_client,
+            ingress_http.drain_handle(),
+            partition_processor_manager.watch_running_partition_processors(),
+            invoker.status_reader(),
+        );
+
+        Ok(Self {
             storage_query_context,
             storage_query_postgres,
             invoker,
             external_client_ingress: ingress_http,
+            ingress_appender,
             ingress_kafka,
             subscription_controller_handle,
             rocksdb_writer,
             rocksdb_storage,
+            partition_processor_manager,
+            node_drainer,
         })
     }
 
-    #[allow(clippy::too_many_arguments)]
-    fn create_partition_processor(
-        partition_id: PartitionId,
-        partition_key_range: RangeInclusive<PartitionKey>,
-        timer_service_options: restate_timer::Options,
-        channel_size: usize,
-        invoker_sender: InvokerChannelServiceHandle,
-        rocksdb_storage: RocksDBStorage,
-    ) -> PartitionProcessor {
-        PartitionProcessor::new(
-            partition_id,
-            partition_key_range,
-            timer_service_options,
-            channel_size,
-            invoker_sender,
-            rocksdb_storage,
-        )
-    }
-
     pub fn subscription_controller_handle(&self) -> SubscriptionControllerHandle {
         self.subscription_controller_handle.clone()
     }
@@ -293,7 +385,7 @@ impl Worker {
         &self.rocksdb_storage
     }
 
-    pub async fn run(self, bifrost: Bifrost) -> anyhow::Result<()> {
+    pub async fn run(self) -> anyhow::Result<()> {
         let tc = task_center();
         let shutdown = cancellation_watcher();
         let (shutdown_signal, shutdown_watch) = drain::channel();
@@ -307,6 +399,14 @@ impl Worker {
                 .map_err(Error::RocksDBWriter)?)
         })?;
 
+        // Appends the requests of the ingresses to bifrost
+        tc.spawn_child(
+            TaskKind::SystemService,
+            "ingress-appender",
+            None,
+            self.ingress_appender.run(),
+        )?;
+
         // Ingress RPC server
         tc.spawn_child(
             TaskKind::IngressServer,
@@ -331,58 +431,25 @@ impl Worker {
             self.ingress_kafka.run(),
         )?;
 
-        let node_id = metadata().my_node_id();
-        // This only temporary measure until we can acquire leadership plan from
-        // cluster controller.
-        let leader_epoch = LeaderEpoch::from(restate_types::time::MillisSinceEpoch::now().as_u64());
-        let announce_leader = AnnounceLeader {
-            node_id,
-            leader_epoch,
-        };
-
-        for (partition_id, partition_range) in metadata().partition_table().partitioner() {
-            let processor = Self::create_partition_processor(
-                partition_id,
-                partition_range,
-                self.options.timers.clone(),
-                self.options.channel_size,
-                self.invoker.handle(),
-                self.rocksdb_storage.clone(),
-            );
-            let networking = self.networking.clone();
-            let announce_leader = announce_leader.clone();
-            let mut bifrost = bifrost.clone();
-
-            tc.spawn_child(
-                TaskKind::PartitionProcessor,
-                "partition-processor",
-                Some(processor.partition_id),
-                async move {
-                    let header = Header {
-                        dest: Destination::Processor {
-                            partition_key: *processor.partition_key_range.start(),
-                            dedup: None,
-                        },
-                        source: Source::ControlPlane {},
-                    };
-
-                    let envelope =
-                        Envelope::new(header, Command::AnnounceLeader(announce_leader.clone()));
-                    let payload = Payload::from(envelope.encode_with_bincode()?);
-
-                    // todo: Remove once we have proper leader election
-                    bifrost
-                        .append(LogId::from(processor.partition_id), payload)
-                        .await
-                        .context("failed to write AnnounceLeader record to bifrost")?;
-                    processor.run(networking, bifrost).await
-                },
-            )?;
-        }
+        // Partition processors are started and stopped as instructed by the cluster controller
+        tc.spawn_child(
+            TaskKind::SystemService,
+            "partition-processor-manager",
+            None,
+            self.partition_processor_manager.run(),
+        )?;
 
         // Invoker service
         tc.spawn_child(TaskKind::SystemService, "invoker", None, self.invoker.run())?;
 
+        // Drains the worker once its node is taken out of the cluster
+        tc.spawn_child(
+            TaskKind::SystemService,
+            "node-drainer",
+            None,
+            self.node_drainer.run(),
+        )?;
+
         tokio::select! {
             _ = shutdown => {
                 debug!("Initiating shutdown of worker");
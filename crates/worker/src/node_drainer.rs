@@ -0,0 +1,154 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::time::Duration;
+
+use restate_core::metadata_store::{MetadataStoreClient, Operation};
+use restate_core::{cancellation_watcher, metadata};
+use restate_ingress_http::DrainHandle;
+use restate_invoker_api::StatusHandle;
+use restate_invoker_impl::ChannelStatusReader;
+use restate_node_protocol::metadata::MetadataKind;
+use restate_types::identifiers::PartitionKey;
+use restate_types::metadata_store::keys::NODES_CONFIG_KEY;
+use restate_types::nodes_config::{NodeState, NodesConfiguration};
+use tokio::sync::watch;
+use tracing::{debug, info};
+
+/// How often the invoker is asked whether it still runs invocations of this node.
+const INVOKER_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Drains the worker once its node is marked as draining in the nodes configuration. The ingress
+/// stops accepting new requests and the in-flight requests are awaited. The cluster controller
+/// moves the partitions of the node onto other workers. Once the ingress has drained, all
+/// partition processors have been stopped and the invoker has released their invocations, the
+/// node is marked as drained, which allows removing it from the cluster.
+///
+/// In-flight invocations are not run to completion on this node. A partition processor aborts
+/// the invocations of its partition when it stops leading, and the new leader resumes them from
+/// their journal, which only contains entries that have been written to the log. The drainer
+/// waits until the invoker has released all of them, so that no invocation of a moved partition
+/// still runs on a drained node.
+pub(crate) struct NodeDrainer {
+    metadata_store_client: MetadataStoreClient,
+    ingress: DrainHandle,
+    running_partition_processors: watch::Receiver<usize>,
+    invoker_status: ChannelStatusReader,
+}
+
+impl NodeDrainer {
+    pub(crate) fn new(
+        metadata_store_client: MetadataStoreClient,
+        ingress: DrainHandle,
+        running_partition_processors: watch::Receiver<usize>,
+        invoker_status: ChannelStatusReader,
+    ) -> Self {
+        Self {
+            metadata_store_client,
+            ingress,
+            running_partition_processors,
+            invoker_status,
+        }
+    }
+
+    pub(crate) async fn run(mut self) -> anyhow::Result<()> {
+        let mut nodes_config_watch = metadata().watch(MetadataKind::NodesConfiguration);
+        let mut shutdown = std::pin::pin!(cancellation_watcher());
+
+        let state = loop {
+            let state = Self::my_node_state();
+            if state.is_some_and(|state| state != NodeState::Active) {
+                break state;
+            }
+
+            tokio::select! {
+                _ = &mut shutdown => return Ok(()),
+                result = nodes_config_watch.changed() => result?,
+            }
+        };
+
+        info!("Draining node");
+        tokio::select! {
+            _ = &mut shutdown => return Ok(()),
+            result = self.drain() => result?,
+        }
+
+        if state == Some(NodeState::Draining) {
+            self.mark_drained().await?;
+        }
+        info!("Node has been drained");
+
+        Ok(())
+    }
+
+    async fn drain(&mut self) -> anyhow::Result<()> {
+        self.ingress.drain().await;
+        debug!("Ingress has been drained, waiting for partition processors to be moved away");
+
+        self.running_partition_processors
+            .wait_for(|running| *running == 0)
+            .await?;
+        debug!("Partition processors have been stopped, waiting for the invoker to release them");
+
+        loop {
+            let in_flight = self
+                .invoker_status
+                .read_status(PartitionKey::MIN..=PartitionKey::MAX)
+                .await
+                .count();
+            if in_flight == 0 {
+                break;
+            }
+
+            debug!("Invoker still runs {in_flight} invocations");
+            tokio::time::sleep(INVOKER_POLL_INTERVAL).await;
+        }
+        Ok(())
+    }
+
+    fn my_node_state() -> Option<NodeState> {
+        metadata()
+            .nodes_config()
+            .find_node_by_id(metadata().my_node_id())
+            .ok()
+            .map(|node| node.state)
+    }
+
+    async fn mark_drained(&self) -> anyhow::Result<()> {
+        let my_node_id = metadata().my_node_id();
+
+        self.metadata_store_client
+            .read_modify_write(
+                NODES_CONFIG_KEY.clone(),
+                |nodes_config: Option<NodesConfiguration>| {
+                    let Some(mut nodes_config) = nodes_config else {
+                        return Operation::Fail("nodes configuration is missing".to_owned());
+                    };
+
+                    // only the current generation of the node can complete its drain
+                    if nodes_config
+                        .find_node_by_id(my_node_id)
+                        .is_ok_and(|node| node.state == NodeState::Draining)
+                    {
+                        nodes_config
+                            .set_node_state(my_node_id, NodeState::Drained)
+                            .expect("node exists");
+                        nodes_config.increment_version();
+                        Operation::Upsert(nodes_config)
+                    } else {
+                        Operation::Return(nodes_config)
+                    }
+                },
+            )
+            .await?;
+
+        Ok(())
+    }
+}
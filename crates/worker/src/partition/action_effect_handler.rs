@@ -13,17 +13,17 @@ use restate_bifrost::Bifrost;
 use restate_core::metadata;
 use restate_types::dedup::{DedupInformation, EpochSequenceNumber};
 use restate_types::identifiers::{PartitionId, PartitionKey, WithPartitionKey};
+use restate_types::time::MillisSinceEpoch;
 use restate_wal_protocol::effects::BuiltinServiceEffects;
 use restate_wal_protocol::{
-    append_envelope_to_bifrost, Command, Destination, Envelope, Header, Source,
+    append_envelope_to_bifrost, append_envelope_to_partition, append_envelopes_to_bifrost, Command,
+    Destination, Envelope, Header, Source,
 };
-use std::ops::RangeInclusive;
 
 /// Responsible for proposing [ActionEffect].
 pub(super) struct ActionEffectHandler {
     partition_id: PartitionId,
     epoch_sequence_number: EpochSequenceNumber,
-    partition_key_range: RangeInclusive<PartitionKey>,
     bifrost: Bifrost,
 }
 
@@ -31,17 +31,20 @@ impl ActionEffectHandler {
     pub(super) fn new(
         partition_id: PartitionId,
         epoch_sequence_number: EpochSequenceNumber,
-        partition_key_range: RangeInclusive<PartitionKey>,
         bifrost: Bifrost,
     ) -> Self {
         Self {
             partition_id,
             epoch_sequence_number,
-            partition_key_range,
             bifrost,
         }
     }
 
+    /// The epoch sequence number of the last proposal.
+    pub(super) fn epoch_sequence_number(&self) -> EpochSequenceNumber {
+        self.epoch_sequence_number
+    }
+
     pub(super) async fn handle(&mut self, actuator_output: ActionEffect) -> anyhow::Result<()> {
         match actuator_output {
             ActionEffect::Invoker(invoker_output) => {
@@ -53,11 +56,12 @@ impl ActionEffectHandler {
                 .await?;
             }
             ActionEffect::Shuffle(outbox_truncation) => {
-                // todo: Until we support partition splits we need to get rid of outboxes or introduce partition
-                //  specific destination messages that are identified by a partition_id
-                let header = self.create_header(*self.partition_key_range.start());
-                append_envelope_to_bifrost(
+                // the outbox belongs to this partition, independent of the keys it owns. The
+                // partition key is irrelevant because the truncation is targeted at the partition.
+                let header = self.create_header(PartitionKey::default());
+                append_envelope_to_partition(
                     &mut self.bifrost,
+                    self.partition_id,
                     Envelope::new(header, Command::TruncateOutbox(outbox_truncation.index())),
                 )
                 .await?;
@@ -79,22 +83,24 @@ impl ActionEffectHandler {
                 //  to make sure the next command can see the effects of the previous one.
                 //  A problematic example case is a sequence of CreateVirtualJournal and AppendJournalEntry:
                 //  to append a journal entry we must have stored the JournalMetadata first.
+                //  The effects are still appended as a single batch to avoid paying a log write per
+                //  effect.
                 let (fid, effects) = invoker_output.into_inner();
 
-                for effect in effects {
-                    let header = self.create_header(fid.partition_key());
-                    append_envelope_to_bifrost(
-                        &mut self.bifrost,
+                let envelopes: Vec<_> = effects
+                    .into_iter()
+                    .map(|effect| {
+                        let header = self.create_header(fid.partition_key());
                         Envelope::new(
-                            header.clone(),
+                            header,
                             Command::BuiltInInvokerEffect(BuiltinServiceEffects::new(
                                 fid.clone(),
                                 vec![effect],
                             )),
-                        ),
-                    )
-                    .await?;
-                }
+                        )
+                    })
+                    .collect();
+                append_envelopes_to_bifrost(&mut self.bifrost, envelopes).await?;
             }
         };
 
@@ -117,6 +123,7 @@ impl ActionEffectHandler {
                 leader_epoch: self.epoch_sequence_number.leader_epoch,
                 node_id: metadata().my_node_id().as_plain(),
             },
+            created_at: Some(MillisSinceEpoch::now()),
         }
     }
 }
@@ -43,6 +43,7 @@ use restate_types::dedup::EpochSequenceNumber;
 use restate_types::identifiers::{InvocationId, PartitionKey};
 use restate_types::identifiers::{LeaderEpoch, PartitionId, PartitionLeaderEpoch};
 use restate_types::journal::EntryType;
+use restate_types::GenerationalNodeId;
 use restate_wal_protocol::timer::TimerValue;
 
 type PartitionStorage = storage::PartitionStorage<RocksDBStorage>;
@@ -63,7 +64,7 @@ pub(crate) struct FollowerState<I> {
     channel_size: usize,
     invoker_tx: I,
     networking: Networking,
-    partition_key_range: RangeInclusive<PartitionKey>,
+    partition_key_range: Option<RangeInclusive<PartitionKey>>,
     bifrost: Bifrost,
 }
 
@@ -93,7 +94,7 @@ where
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn follower(
         partition_id: PartitionId,
-        partition_key_range: RangeInclusive<PartitionKey>,
+        partition_key_range: Option<RangeInclusive<PartitionKey>>,
         timer_service_options: restate_timer::Options,
         channel_size: usize,
         invoker_tx: InvokerInputSender,
@@ -118,6 +119,13 @@ where
         matches!(self, LeadershipState::Leader { .. })
     }
 
+    pub(crate) fn leader_epoch(&self) -> Option<LeaderEpoch> {
+        match self {
+            LeadershipState::Follower(_) => None,
+            LeadershipState::Leader { leader_state, .. } => Some(leader_state.leader_epoch),
+        }
+    }
+
     pub(crate) async fn become_leader(
         self,
         epoch_sequence_number: EpochSequenceNumber,
@@ -146,15 +154,22 @@ where
 
             let leader_epoch = epoch_sequence_number.leader_epoch;
 
-            let invoker_rx = Self::resume_invoked_invocations(
-                &mut follower_state.invoker_tx,
-                &mut service_invoker,
-                (follower_state.partition_id, leader_epoch),
-                follower_state.partition_key_range.clone(),
-                partition_storage,
-                follower_state.channel_size,
-            )
-            .await?;
+            let invoker_rx = if let Some(partition_key_range) = &follower_state.partition_key_range
+            {
+                Self::resume_invoked_invocations(
+                    &mut follower_state.invoker_tx,
+                    &mut service_invoker,
+                    (follower_state.partition_id, leader_epoch),
+                    partition_key_range.clone(),
+                    partition_storage,
+                    follower_state.channel_size,
+                )
+                .await?
+            } else {
+                // a partition without keys has no invocations, it only delivers its outbox
+                let (_, invoker_rx) = mpsc::channel(1);
+                invoker_rx
+            };
 
             let timer_service = Box::pin(
                 follower_state
@@ -188,7 +203,6 @@ where
             let action_effect_handler = ActionEffectHandler::new(
                 follower_state.partition_id,
                 epoch_sequence_number,
-                follower_state.partition_key_range.clone(),
                 follower_state.bifrost.clone(),
             );
 
@@ -222,14 +236,19 @@ where
         let (invoker_tx, invoker_rx) = mpsc::channel(channel_size);
 
         invoker_handle
-            .register_partition(partition_leader_epoch, partition_key_range, invoker_tx)
+            .register_partition(
+                partition_leader_epoch,
+                partition_key_range.clone(),
+                invoker_tx,
+            )
             .await
             .map_err(Error::Invoker)?;
 
         let mut built_in_invoked_services = Vec::new();
 
         {
-            let invoked_invocations = partition_storage.scan_invoked_invocations();
+            let invoked_invocations =
+                partition_storage.scan_invoked_invocations(partition_key_range);
             tokio::pin!(invoked_invocations);
 
             while let Some(full_invocation_id) = invoked_invocations.next().await {
@@ -335,6 +354,36 @@ where
         }
     }
 
+    /// Changes the key range after the partition has handed over or taken over keys. A leader
+    /// restarts its leadership to run the invocations and timers of its new key range. It
+    /// continues with the epoch sequence number of its last proposal, so that its proposals
+    /// aren't deduplicated.
+    pub(crate) async fn change_key_range(
+        self,
+        partition_key_range: Option<RangeInclusive<PartitionKey>>,
+        partition_storage: &mut PartitionStorage,
+    ) -> Result<(Self, ActionEffectStream), Error> {
+        let epoch_sequence_number = match &self {
+            LeadershipState::Follower(_) => None,
+            LeadershipState::Leader { leader_state, .. } => {
+                Some(leader_state.action_effect_handler.epoch_sequence_number())
+            }
+        };
+
+        let (state, action_effect_stream) = self.become_follower().await?;
+        let_assert!(LeadershipState::Follower(mut follower_state) = state);
+        follower_state.partition_key_range = partition_key_range;
+        let state = LeadershipState::Follower(follower_state);
+
+        if let Some(epoch_sequence_number) = epoch_sequence_number {
+            state
+                .unchecked_become_leader(epoch_sequence_number, partition_storage)
+                .await
+        } else {
+            Ok((state, action_effect_stream))
+        }
+    }
+
     pub(crate) async fn run_timer(&mut self) -> TimerValue {
         match self {
             LeadershipState::Follower { .. } => future::pending().await,
@@ -449,67 +498,85 @@ where
             }
             Action::IngressResponse(ingress_response) => {
                 let invocation_id: InvocationId = ingress_response.full_invocation_id.into();
-                // NOTE: We dispatch the response in a non-blocking task-center task to avoid
-                // blocking partition processor. This comes with the risk of overwhelming the
-                // runtime. This should be a temporary solution until we have a better way to
-                // handle this case. Options are split into two categories:
-                //
-                // Category A) Do not block PP's loop if ingress is slow/unavailable
-                //   - Add timeout to the disposable task to drop old/stale responses in congestion
-                //   scenarios.
-                //   - Limit the number of inflight ingress responses (per ingress node) by
-                //   mapping node_id -> Vec<TaskId>
-                // Category B) Enforce Back-pressure on PP if ingress is slow
-                //   - Either directly or through a channel/buffer, block this loop if ingress node
-                //   cannot keep up with the responses.
-                //
-                //  todo: Decide.
-                let maybe_task = task_center().spawn_child(
-                    TaskKind::Disposable,
-                    "respond-to-ingress",
-                    current_task_partition_id(),
-                    {
-                        let networking = networking.clone();
-                        let invocation_id = invocation_id.clone();
-                        async move {
-                            if let Err(e) = networking
-                                .send(
-                                    ingress_response.target_node.into(),
-                                    &ingress::IngressMessage::InvocationResponse(
-                                        ingress::InvocationResponse {
-                                            id: invocation_id.clone(),
-                                            response: ingress_response.response,
-                                        },
-                                    ),
-                                )
-                                .await
-                            {
-                                warn!(
-                                    ?e,
-                                    ingress_node_id = ?ingress_response.target_node,
-                                    invocation.id = %invocation_id,
-                                    "Failed to send ingress response for invocation, will drop \
-                                        the response on the floor"
-                                );
-                            }
-                            Ok(())
-                        }
-                    },
+                Self::send_to_ingress(
+                    networking,
+                    ingress_response.target_node,
+                    invocation_id.clone(),
+                    ingress::IngressMessage::InvocationResponse(ingress::InvocationResponse {
+                        id: invocation_id,
+                        response: ingress_response.response,
+                    }),
+                );
+            }
+            Action::IngressAttachResponse(ingress_attach_response) => {
+                Self::send_to_ingress(
+                    networking,
+                    ingress_attach_response.target_node,
+                    ingress_attach_response.invocation_id.clone(),
+                    ingress::IngressMessage::AttachResponse(ingress::AttachResponse {
+                        request_id: ingress_attach_response.request_id,
+                        id: ingress_attach_response.invocation_id,
+                        response: ingress_attach_response.response,
+                    }),
                 );
-
-                if maybe_task.is_err() {
-                    trace!(
-                        restate.invocation.id = %invocation_id,
-                        "Partition processor is shutting down, we are not sending response of {} to ingress",
-                        invocation_id
-                    );
-                }
             }
         }
 
         Ok(())
     }
 
+    fn send_to_ingress(
+        networking: &Networking,
+        target_node: GenerationalNodeId,
+        invocation_id: InvocationId,
+        message: ingress::IngressMessage,
+    ) {
+        // NOTE: We dispatch the response in a non-blocking task-center task to avoid
+        // blocking partition processor. This comes with the risk of overwhelming the
+        // runtime. This should be a temporary solution until we have a better way to
+        // handle this case. Options are split into two categories:
+        //
+        // Category A) Do not block PP's loop if ingress is slow/unavailable
+        //   - Add timeout to the disposable task to drop old/stale responses in congestion
+        //   scenarios.
+        //   - Limit the number of inflight ingress responses (per ingress node) by
+        //   mapping node_id -> Vec<TaskId>
+        // Category B) Enforce Back-pressure on PP if ingress is slow
+        //   - Either directly or through a channel/buffer, block this loop if ingress node
+        //   cannot keep up with the responses.
+        //
+        //  todo: Decide.
+        let maybe_task = task_center().spawn_child(
+            TaskKind::Disposable,
+            "respond-to-ingress",
+            current_task_partition_id(),
+            {
+                let networking = networking.clone();
+                let invocation_id = invocation_id.clone();
+                async move {
+                    if let Err(e) = networking.send(target_node.into(), &message).await {
+                        warn!(
+                            ?e,
+                            ingress_node_id = ?target_node,
+                            invocation.id = %invocation_id,
+                            "Failed to send ingress response for invocation, will drop \
+                                the response on the floor"
+                        );
+                    }
+                    Ok(())
+                }
+            },
+        );
+
+        if maybe_task.is_err() {
+            trace!(
+                restate.invocation.id = %invocation_id,
+                "Partition processor is shutting down, we are not sending response of {} to ingress",
+                invocation_id
+            );
+        }
+    }
+
     pub async fn handle_action_effect(
         &mut self,
         action_effect: ActionEffect,
@@ -10,24 +10,36 @@
 
 use crate::metric_definitions::{PARTITION_ACTUATOR_HANDLED, PARTITION_TIMER_DUE_HANDLED};
 use crate::partition::leadership::{ActionEffect, LeadershipState};
+use crate::partition::snapshots::{SnapshotSettings, Snapshotter};
 use crate::partition::state_machine::{ActionCollector, Effects, StateMachine};
-use crate::partition::storage::{DedupSequenceNumberResolver, PartitionStorage, Transaction};
+use crate::partition::storage::{
+    DedupSequenceNumberResolver, KeyRangeOwnership, PartitionStorage, Transaction,
+};
 use assert2::let_assert;
 use futures::StreamExt;
 use metrics::counter;
 use restate_core::metadata;
 use restate_network::Networking;
+use restate_node_protocol::metadata::MetadataKind;
+use restate_node_protocol::partition_processor_manager::{PartitionProcessorStatus, RunMode};
 use restate_storage_rocksdb::{RocksDBStorage, RocksDBTransaction};
-use restate_types::identifiers::{PartitionId, PartitionKey};
+use restate_types::identifiers::{LeaderEpoch, PartitionId, PartitionKey};
+use restate_types::partition_table::FindPartition;
+use restate_types::time::MillisSinceEpoch;
+use restate_types::Version;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::ops::RangeInclusive;
-use tracing::{debug, instrument, trace, Span};
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{debug, info, instrument, trace, warn, Span};
 
 mod action_effect_handler;
 mod leadership;
 mod services;
 pub mod shuffle;
+pub mod snapshots;
 mod state_machine;
 pub mod storage;
 pub mod types;
@@ -39,21 +51,41 @@ use restate_types::dedup::{
     DedupInformation, DedupSequenceNumber, EpochSequenceNumber, ProducerId,
 };
 use restate_types::logs::{LogId, Lsn, SequenceNumber};
-use restate_wal_protocol::control::AnnounceLeader;
-use restate_wal_protocol::{Command, Destination, Envelope, Header};
+use restate_wal_protocol::control::{
+    AnnounceLeader, HandOverKeyRange, KeyRangeData, TakeOverKeyRange,
+};
+use restate_wal_protocol::{
+    append_envelope_to_bifrost, append_envelope_to_partition, Command, Destination, Envelope,
+    Header, Source,
+};
+
+/// How often a partition processor checks whether the partition on the same node which hands over
+/// keys to it has applied the hand over.
+const HAND_OVER_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 #[derive(Debug)]
 pub(super) struct PartitionProcessor<RawEntryCodec, InvokerInputSender> {
     pub partition_id: PartitionId,
-    pub partition_key_range: RangeInclusive<PartitionKey>,
+    /// The key range with which the partition has been created, partitions which are created by
+    /// splits or merges start without keys.
+    pub initial_key_range: Option<RangeInclusive<PartitionKey>>,
 
     timer_service_options: restate_timer::Options,
     channel_size: usize,
+    log_trim_interval: Option<Duration>,
+    snapshot_settings: SnapshotSettings,
 
     invoker_tx: InvokerInputSender,
 
     rocksdb_storage: RocksDBStorage,
 
+    status: watch::Sender<PartitionProcessorStatus>,
+    /// The partitions whose processors run on this node and share its partition store.
+    running_partitions: watch::Receiver<BTreeSet<PartitionId>>,
+    /// The lowest lsn which all replicas of a partition have applied, as reported by the cluster
+    /// controller.
+    replicas_applied_lsns: watch::Receiver<BTreeMap<PartitionId, Lsn>>,
+
     _entry_codec: PhantomData<RawEntryCodec>,
 }
 
@@ -65,20 +97,30 @@ where
     #[allow(clippy::too_many_arguments)]
     pub(super) fn new(
         partition_id: PartitionId,
-        partition_key_range: RangeInclusive<PartitionKey>,
+        initial_key_range: Option<RangeInclusive<PartitionKey>>,
         timer_service_options: restate_timer::Options,
         channel_size: usize,
+        log_trim_interval: Option<Duration>,
+        snapshot_settings: SnapshotSettings,
         invoker_tx: InvokerInputSender,
         rocksdb_storage: RocksDBStorage,
+        status: watch::Sender<PartitionProcessorStatus>,
+        running_partitions: watch::Receiver<BTreeSet<PartitionId>>,
+        replicas_applied_lsns: watch::Receiver<BTreeMap<PartitionId, Lsn>>,
     ) -> Self {
         Self {
             partition_id,
-            partition_key_range,
+            initial_key_range,
             timer_service_options,
             channel_size,
+            log_trim_interval,
+            snapshot_settings,
             invoker_tx,
             _entry_codec: Default::default(),
             rocksdb_storage,
+            status,
+            running_partitions,
+            replicas_applied_lsns,
         }
     }
 
@@ -86,25 +128,42 @@ where
     pub(super) async fn run(self, networking: Networking, bifrost: Bifrost) -> anyhow::Result<()> {
         let PartitionProcessor {
             partition_id,
-            partition_key_range,
+            initial_key_range,
             timer_service_options,
             channel_size,
+            log_trim_interval,
+            snapshot_settings,
             invoker_tx,
             rocksdb_storage,
+            status,
+            running_partitions,
+            replicas_applied_lsns,
             ..
         } = self;
 
-        let mut partition_storage =
-            PartitionStorage::new(partition_id, partition_key_range.clone(), rocksdb_storage);
+        let mut snapshotter =
+            Snapshotter::new(partition_id, rocksdb_storage.clone(), snapshot_settings);
+        let mut partition_storage = PartitionStorage::new(partition_id, None, rocksdb_storage);
+        // bootstrap from the latest snapshot instead of replaying the log that precedes it
+        let applied_lsn = partition_storage.load_applied_lsn().await?;
+        let ownership =
+            Self::load_ownership(&mut partition_storage, partition_id, &initial_key_range).await?;
+        snapshotter
+            .restore_latest(applied_lsn, ownership.owned().cloned())
+            .await?;
+
+        let mut ownership =
+            Self::load_ownership(&mut partition_storage, partition_id, &initial_key_range).await?;
+        partition_storage.set_partition_key_range(ownership.owned().cloned());
 
         let mut state_machine = Self::create_state_machine::<RawEntryCodec>(
             &mut partition_storage,
-            partition_key_range.clone(),
+            ownership.owned().cloned(),
         )
         .await?;
 
         let last_applied_lsn = partition_storage.load_applied_lsn().await?;
-        let last_applied_lsn = last_applied_lsn.unwrap_or(Lsn::INVALID);
+        let mut last_applied_lsn = last_applied_lsn.unwrap_or(Lsn::INVALID);
         if tracing::event_enabled!(tracing::Level::DEBUG) {
             let current_tail = bifrost
                 .find_tail(LogId::from(partition_id), FindTailAttributes::default())
@@ -116,17 +175,26 @@ where
             );
         }
         let mut log_reader = LogReader::new(&bifrost, LogId::from(partition_id), last_applied_lsn);
+        Self::report_status(&status, false, last_applied_lsn);
 
         let mut action_collector = ActionCollector::default();
         let mut effects = Effects::default();
 
+        let mut log_trimmer = LogTrimmer::new(
+            bifrost.clone(),
+            LogId::from(partition_id),
+            log_trim_interval,
+        );
+
+        let mut forwarding_bifrost = bifrost.clone();
+
         let (mut state, mut action_effect_stream) = LeadershipState::follower(
             partition_id,
-            partition_key_range.clone(),
+            ownership.owned().cloned(),
             timer_service_options,
             channel_size,
             invoker_tx,
-            bifrost,
+            bifrost.clone(),
             networking,
         );
 
@@ -134,8 +202,46 @@ where
             tokio::select! {
                 _ = cancellation_watcher() => break,
                 record = log_reader.read_next() => {
-                    let record = record?;
+                    let record = match record? {
+                        LogEntry::Envelope(lsn, envelope) => (lsn, envelope),
+                        LogEntry::TrimGap { from, until } => {
+                            // Records which have not been applied yet have been trimmed. Only a
+                            // snapshot in the repository of this node can reflect them.
+                            warn!(%from, %until, "Partition log has been trimmed beyond the applied lsn, restoring the latest snapshot");
+                            let was_leader = state.is_leader();
+                            (state, action_effect_stream) = state.become_follower().await?;
+                            if was_leader {
+                                Span::current().record("is_leader", state.is_leader());
+                            }
+
+                            last_applied_lsn = snapshotter.restore_trimmed(until, ownership.owned().cloned()).await?;
+                            ownership = Self::load_ownership(&mut partition_storage, partition_id, &initial_key_range).await?;
+                            let partition_key_range = ownership.owned().cloned();
+                            partition_storage.set_partition_key_range(partition_key_range.clone());
+                            state_machine = Self::create_state_machine::<RawEntryCodec>(
+                                &mut partition_storage,
+                                partition_key_range.clone(),
+                            )
+                            .await?;
+                            (state, action_effect_stream) = state.change_key_range(partition_key_range, &mut partition_storage).await?;
+                            log_reader = LogReader::new(&bifrost, LogId::from(partition_id), last_applied_lsn);
+                            Self::report_status(&status, state.is_leader(), last_applied_lsn);
+                            continue;
+                        }
+                    };
                     trace!(lsn = %record.0, "Processing bifrost record for '{}': {:?}", record.1.command.name(), record.1.header);
+                    let lsn = record.0;
+
+                    let mut key_range_data = None;
+                    if let Command::TakeOverKeyRange(take_over) = &record.1.command {
+                        if !ownership.owns(*take_over.key_range.start()) {
+                            let Some(data) = Self::await_key_range_data(&bifrost, &mut partition_storage, &running_partitions, partition_id, lsn, take_over).await? else {
+                                // shutting down
+                                break;
+                            };
+                            key_range_data = Some(data);
+                        }
+                    }
 
                     let mut transaction = partition_storage.create_transaction();
 
@@ -143,16 +249,18 @@ where
                     action_collector.clear();
                     effects.clear();
 
-                    let leadership_change = Self::apply_record(
+                    let outcome = Self::apply_record(
                             record,
                             &mut state_machine,
                             &mut transaction,
                             &mut action_collector,
                             &mut effects, state.is_leader(),
-                            &partition_key_range)
+                            partition_id,
+                            &mut ownership,
+                            key_range_data)
                         .await?;
 
-                    if let Some(announce_leader) = leadership_change {
+                    if let ApplyOutcome::AnnounceLeader(announce_leader) = outcome {
                         let new_esn = EpochSequenceNumber::new(announce_leader.leader_epoch);
 
                         // update our own epoch sequence number to filter out messages from previous leaders
@@ -160,6 +268,7 @@ where
                         // commit all changes so far, this is important so that the actuators see all changes
                         // when becoming leader.
                         transaction.commit().await?;
+                        last_applied_lsn = lsn;
 
                         // We can ignore all actions collected so far because as a new leader we have to instruct the
                         // actuators afresh.
@@ -172,6 +281,11 @@ where
                                 Span::current().record("is_leader", state.is_leader());
                                 debug!(leader_epoch = %new_esn.leader_epoch, "Partition leadership acquired");
                             }
+                            // the previous leader might have failed before appending the data of
+                            // the handed over keys
+                            for hand_over in ownership.handed_over() {
+                                Self::send_key_range_data(&mut forwarding_bifrost, &mut partition_storage, partition_id, new_esn.leader_epoch, hand_over).await?;
+                            }
                         } else {
                             let was_leader = state.is_leader();
                             (state, action_effect_stream) = state.become_follower().await?;
@@ -180,11 +294,37 @@ where
                                 debug!(leader_epoch = %new_esn.leader_epoch, "Partition leadership lost to {}", announce_leader.node_id);
                             }
                         }
+                    } else if let ApplyOutcome::KeyRangeChanged(hand_over) = outcome {
+                        transaction.commit().await?;
+                        last_applied_lsn = lsn;
+
+                        if let (Some(hand_over), Some(leader_epoch)) = (hand_over, state.leader_epoch()) {
+                            Self::send_key_range_data(&mut forwarding_bifrost, &mut partition_storage, partition_id, leader_epoch, &hand_over).await?;
+                        }
+
+                        let partition_key_range = ownership.owned().cloned();
+                        info!(?partition_key_range, "Partition key range changed");
+                        partition_storage.set_partition_key_range(partition_key_range.clone());
+                        state_machine = Self::create_state_machine::<RawEntryCodec>(
+                            &mut partition_storage,
+                            partition_key_range.clone(),
+                        )
+                        .await?;
+                        (state, action_effect_stream) = state.change_key_range(partition_key_range, &mut partition_storage).await?;
                     } else {
                         // Commit our changes and notify actuators about actions if we are the leader
                         transaction.commit().await?;
+                        last_applied_lsn = lsn;
                         state.handle_actions(action_collector.drain(..)).await?;
+
+                        if let ApplyOutcome::Forward(envelope, partition_table_version) = outcome {
+                            if state.is_leader() {
+                                Self::forward_record(&mut forwarding_bifrost, partition_id, lsn, *envelope, partition_table_version).await?;
+                            }
+                        }
                     }
+
+                    Self::report_status(&status, state.is_leader(), last_applied_lsn);
                 },
                 action_effect = action_effect_stream.next() => {
                     counter!(PARTITION_ACTUATOR_HANDLED).increment(1);
@@ -195,6 +335,20 @@ where
                     counter!(PARTITION_TIMER_DUE_HANDLED).increment(1);
                     state.handle_action_effect(ActionEffect::Timer(timer)).await?;
                 },
+                _ = snapshotter.tick() => {
+                    snapshotter.snapshot(ownership.owned().cloned(), last_applied_lsn).await;
+                },
+                _ = log_trimmer.tick() => {
+                    // only the leader trims the log, and only the records which all replicas
+                    // have applied already
+                    let replicas_applied_lsn = replicas_applied_lsns.borrow().get(&partition_id).copied();
+                    if let Some(replicas_applied_lsn) = replicas_applied_lsn.filter(|_| state.is_leader()) {
+                        // the leader keeps the records after its latest snapshot to be able to
+                        // restore its own partition store
+                        let trim_point = snapshotter.latest_snapshot_lsn().await.min(replicas_applied_lsn).min(last_applied_lsn);
+                        log_trimmer.trim(trim_point).await;
+                    }
+                },
             }
         }
 
@@ -204,9 +358,27 @@ where
         Ok(())
     }
 
+    /// Publishes the status which the partition processor manager reports to the cluster
+    /// controller.
+    fn report_status(
+        status: &watch::Sender<PartitionProcessorStatus>,
+        is_leader: bool,
+        last_applied_lsn: Lsn,
+    ) {
+        status.send_modify(|status| {
+            status.run_mode = if is_leader {
+                RunMode::Leader
+            } else {
+                RunMode::Follower
+            };
+            status.last_applied_lsn =
+                (last_applied_lsn != Lsn::INVALID).then_some(last_applied_lsn);
+        });
+    }
+
     async fn create_state_machine<Codec>(
         partition_storage: &mut PartitionStorage<RocksDBStorage>,
-        partition_key_range: RangeInclusive<PartitionKey>,
+        partition_key_range: Option<RangeInclusive<PartitionKey>>,
     ) -> Result<StateMachine<Codec>, restate_storage_api::StorageError>
     where
         Codec: restate_types::journal::raw::RawEntryCodec + Default + Debug,
@@ -220,6 +392,224 @@ where
         Ok(state_machine)
     }
 
+    /// Reads ahead in the partition's log until the [`KeyRangeData`] of the keys which are taken
+    /// over has been appended after the take over and returns it. Every replica takes over the
+    /// first data of the hand over, data which is appended again by later leaders of the other
+    /// partition is ignored. Returns `None` if the partition processor is shutting down.
+    ///
+    /// If the other partition runs on this node as well, this waits until it has applied the hand
+    /// over, since it would modify the taken over keys in the shared partition store otherwise.
+    async fn await_key_range_data(
+        bifrost: &Bifrost,
+        partition_storage: &mut PartitionStorage<RocksDBStorage>,
+        running_partitions: &watch::Receiver<BTreeSet<PartitionId>>,
+        partition_id: PartitionId,
+        lsn: Lsn,
+        take_over: &TakeOverKeyRange,
+    ) -> anyhow::Result<Option<KeyRangeData>> {
+        let mut log_reader = LogReader::new(bifrost, LogId::from(partition_id), lsn);
+        let data = loop {
+            let entry = tokio::select! {
+                _ = cancellation_watcher() => return Ok(None),
+                entry = log_reader.read_next() => entry?,
+            };
+            match entry {
+                LogEntry::Envelope(_, envelope) => {
+                    if let Command::KeyRangeData(data) = envelope.command {
+                        if data.from == take_over.from
+                            && data.hand_over.to == partition_id
+                            && data.hand_over.key_range == take_over.key_range
+                        {
+                            break data;
+                        }
+                    }
+                }
+                LogEntry::TrimGap { from, until } => {
+                    // the log is only trimmed up to the applied lsn
+                    anyhow::bail!(
+                        "log has been trimmed from {from} to {until} while taking over the keys \
+                        {:?} at {lsn}",
+                        take_over.key_range
+                    );
+                }
+            }
+        };
+
+        let mut attempts: u64 = 0;
+        loop {
+            let is_running_locally = running_partitions.borrow().contains(&take_over.from);
+            if !is_running_locally
+                || partition_storage
+                    .load_key_range_ownership(take_over.from)
+                    .await?
+                    .is_some_and(|ownership| {
+                        ownership.has_handed_over(partition_id, &take_over.key_range)
+                    })
+            {
+                break;
+            }
+
+            attempts += 1;
+            if attempts % 100 == 0 {
+                warn!(
+                    "Still waiting for partition {} on this node to hand over the keys {:?}",
+                    take_over.from, take_over.key_range
+                );
+            }
+
+            tokio::select! {
+                _ = cancellation_watcher() => return Ok(None),
+                _ = tokio::time::sleep(HAND_OVER_POLL_INTERVAL) => {},
+            }
+        }
+
+        Ok(Some(data))
+    }
+
+    /// Appends the data of keys which this partition has handed over to the log of the partition
+    /// which takes them over.
+    async fn send_key_range_data(
+        bifrost: &mut Bifrost,
+        partition_storage: &mut PartitionStorage<RocksDBStorage>,
+        partition_id: PartitionId,
+        leader_epoch: LeaderEpoch,
+        hand_over: &HandOverKeyRange,
+    ) -> anyhow::Result<()> {
+        let data = partition_storage.export_key_range(hand_over).await?;
+        let header = Header {
+            source: Source::Processor {
+                partition_id,
+                partition_key: None,
+                leader_epoch,
+                node_id: metadata().my_node_id().as_plain(),
+            },
+            dest: Destination::Processor {
+                partition_key: *hand_over.key_range.start(),
+                dedup: None,
+            },
+            created_at: Some(MillisSinceEpoch::now()),
+        };
+
+        let (log_id, _) = append_envelope_to_partition(
+            bifrost,
+            hand_over.to,
+            Envelope::new(header, Command::KeyRangeData(data)),
+        )
+        .await?;
+        debug!(
+            "Appended the data of the handed over keys {:?} to {log_id}",
+            hand_over.key_range
+        );
+        Ok(())
+    }
+
+    /// Loads the key range ownership of the partition from the partition store, or creates it from
+    /// the initial key range if the partition has not changed its key range on this node yet. The
+    /// keys which other partitions have taken over on this node are handed over.
+    async fn load_ownership(
+        partition_storage: &mut PartitionStorage<RocksDBStorage>,
+        partition_id: PartitionId,
+        initial_key_range: &Option<RangeInclusive<PartitionKey>>,
+    ) -> Result<KeyRangeOwnership, StorageError> {
+        let mut ownership = partition_storage
+            .load_key_range_ownership(partition_id)
+            .await?
+            .unwrap_or_else(|| KeyRangeOwnership::new(initial_key_range.clone()));
+        Self::skip_keys_taken_over_locally(partition_storage, partition_id, &mut ownership).await?;
+        Ok(ownership)
+    }
+
+    /// Hands over the keys which another partition has already taken over on this node. This is
+    /// the case if this partition's processor starts on the node after the keys have been taken
+    /// over there and replays the records which precede the hand over. Applying them would modify
+    /// the keys in the shared partition store. Keys only move to partitions which are created
+    /// later and get higher ids, so a later partition owning the keys on this node has taken them
+    /// over, even if its ownership is outdated.
+    async fn skip_keys_taken_over_locally(
+        partition_storage: &mut PartitionStorage<RocksDBStorage>,
+        partition_id: PartitionId,
+        ownership: &mut KeyRangeOwnership,
+    ) -> Result<(), StorageError> {
+        let partition_table = metadata().partition_table();
+        let other_partitions: Vec<_> = partition_table
+            .partitioner()
+            .map(|(partition_id, _)| partition_id)
+            .chain(
+                partition_table
+                    .merged_partitions()
+                    .map(|(partition_id, _)| partition_id),
+            )
+            .filter(|other| *other > partition_id)
+            .collect();
+
+        for other in other_partitions {
+            let Some(owned) = ownership.owned().cloned() else {
+                break;
+            };
+            let Some(other_owned) = partition_storage
+                .load_key_range_ownership(other)
+                .await?
+                .and_then(|other_ownership| other_ownership.owned().cloned())
+            else {
+                continue;
+            };
+            if other_owned.start() > owned.end() || other_owned.end() < owned.start() {
+                continue;
+            }
+
+            let hand_over = HandOverKeyRange {
+                key_range: *other_owned.start().max(owned.start())..=*owned.end(),
+                to: other,
+                partition_table_version: partition_table.version(),
+            };
+            let key_range = hand_over.key_range.clone();
+            if ownership.hand_over(hand_over) {
+                warn!("Skipping the keys {key_range:?} which partition {other} has taken over on this node");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Forwards a record for keys which this partition has handed over to the partition which
+    /// owns the keys now. The record is deduplicated by the lsn at which it has been appended to
+    /// this partition's log because messages of other producers can reach the new owner directly
+    /// and out of order.
+    ///
+    /// Only the leader forwards records, records which are applied while the leader changes are
+    /// not forwarded.
+    async fn forward_record(
+        bifrost: &mut Bifrost,
+        partition_id: PartitionId,
+        lsn: Lsn,
+        mut envelope: Envelope,
+        partition_table_version: Version,
+    ) -> anyhow::Result<()> {
+        let partition_table = metadata();
+        partition_table
+            .wait_for_version(MetadataKind::PartitionTable, partition_table_version)
+            .await?;
+
+        let Destination::Processor {
+            partition_key,
+            dedup,
+        } = &mut envelope.header.dest;
+        if partition_table
+            .partition_table()
+            .find_partition_id(*partition_key)
+            .is_ok_and(|owner| owner == partition_id)
+        {
+            warn!("Dropping record for key {partition_key} which has been handed over but is still assigned to this partition");
+            return Ok(());
+        }
+        *dedup = Some(DedupInformation::forwarded(partition_id, lsn.into()));
+
+        let (log_id, _) = append_envelope_to_bifrost(bifrost, envelope).await?;
+        trace!(%lsn, "Forwarded record to {log_id}");
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
     async fn apply_record<Codec>(
         record: (Lsn, Envelope),
         state_machine: &mut StateMachine<Codec>,
@@ -227,34 +617,59 @@ where
         action_collector: &mut ActionCollector,
         effects: &mut Effects,
         is_leader: bool,
-        partition_key_range: &RangeInclusive<PartitionKey>,
-    ) -> Result<Option<AnnounceLeader>, state_machine::Error>
+        partition_id: PartitionId,
+        ownership: &mut KeyRangeOwnership,
+        key_range_data: Option<KeyRangeData>,
+    ) -> Result<ApplyOutcome, state_machine::Error>
     where
         Codec: restate_types::journal::raw::RawEntryCodec + Default + Debug,
     {
         let (lsn, envelope) = record;
         transaction.store_applied_lsn(lsn).await?;
 
-        if let Some(dedup_information) = is_targeted_to_me(&envelope.header, partition_key_range) {
-            // deduplicate if deduplication information has been provided
-            if let Some(dedup_information) = dedup_information {
-                if is_outdated_or_duplicate(dedup_information, transaction).await? {
-                    debug!(
-                        "Ignoring outdated or duplicate message: {:?}",
+        let (dedup_information, handed_over_in) =
+            match recipient(&envelope, partition_id, ownership) {
+                Recipient::Me(dedup_information) => (dedup_information, None),
+                Recipient::HandedOver {
+                    dedup_information,
+                    partition_table_version,
+                } => (dedup_information, Some(partition_table_version)),
+                Recipient::Other => {
+                    trace!(
+                        "Ignore message which is not targeted to me: {:?}",
                         envelope.header
                     );
-                    return Ok(None);
-                } else {
-                    transaction
-                        .store_dedup_sequence_number(
-                            dedup_information.producer_id.clone(),
-                            dedup_information.sequence_number,
-                        )
-                        .await;
+                    return Ok(ApplyOutcome::Applied);
                 }
+            };
+
+        // deduplicate if deduplication information has been provided
+        if let Some(dedup_information) = dedup_information {
+            if is_outdated_or_duplicate(dedup_information, transaction).await? {
+                debug!(
+                    "Ignoring outdated or duplicate message: {:?}",
+                    envelope.header
+                );
+                return Ok(ApplyOutcome::Applied);
+            } else {
+                transaction
+                    .store_dedup_sequence_number(
+                        dedup_information.producer_id.clone(),
+                        dedup_information.sequence_number,
+                    )
+                    .await;
             }
+        }
 
-            if let Command::AnnounceLeader(announce_leader) = envelope.command {
+        if let Some(partition_table_version) = handed_over_in {
+            return Ok(ApplyOutcome::Forward(
+                Box::new(envelope),
+                partition_table_version,
+            ));
+        }
+
+        match envelope.command {
+            Command::AnnounceLeader(announce_leader) => {
                 let last_known_esn = transaction
                     .get_dedup_sequence_number(&ProducerId::self_producer())
                     .await?
@@ -273,7 +688,7 @@ where
                     .unwrap_or(true)
                 {
                     // leadership change detected, let's finish our transaction here
-                    return Ok(Some(announce_leader));
+                    return Ok(ApplyOutcome::AnnounceLeader(announce_leader));
                 }
                 debug!(
                     last_known_esn = %last_known_esn.as_ref().unwrap().leader_epoch,
@@ -281,10 +696,47 @@ where
                     node_id = %announce_leader.node_id,
                     "Ignoring outdated leadership announcement."
                 );
-            } else {
+            }
+            Command::HandOverKeyRange(hand_over) => {
+                let to = hand_over.to;
+                let key_range = hand_over.key_range.clone();
+
+                if ownership.hand_over(hand_over.clone()) {
+                    transaction.store_key_range_ownership(ownership).await?;
+                    info!("Handed over keys {key_range:?} to partition {to}");
+                    return Ok(ApplyOutcome::KeyRangeChanged(Some(hand_over)));
+                }
+                debug!("Ignoring hand over of keys {key_range:?} which are not owned");
+            }
+            Command::TakeOverKeyRange(take_over) => {
+                if let Some(data) = key_range_data {
+                    if ownership.take_over(take_over.key_range.clone()) {
+                        let timers = transaction.take_over_key_range(&data).await?;
+                        transaction.store_key_range_ownership(ownership).await?;
+                        info!(
+                            "Took over keys {:?} and {timers} timers from partition {}",
+                            take_over.key_range, take_over.from
+                        );
+                        return Ok(ApplyOutcome::KeyRangeChanged(None));
+                    }
+                }
+                debug!(
+                    "Ignoring take over of keys {:?} which are owned already",
+                    take_over.key_range
+                );
+            }
+            Command::KeyRangeData(data) => {
+                trace!(
+                    "Ignoring data of keys {:?} of partition {}, it is applied with the take over",
+                    data.hand_over.key_range,
+                    data.from
+                );
+            }
+            command => {
                 state_machine
                     .apply(
-                        envelope.command,
+                        command,
+                        envelope.header.created_at,
                         effects,
                         transaction,
                         action_collector,
@@ -292,27 +744,76 @@ where
                     )
                     .await?;
             }
-        } else {
-            trace!(
-                "Ignore message which is not targeted to me: {:?}",
-                envelope.header
-            );
         }
 
-        Ok(None)
+        Ok(ApplyOutcome::Applied)
     }
 }
 
-fn is_targeted_to_me<'a>(
-    header: &'a Header,
-    partition_key_range: &RangeInclusive<PartitionKey>,
-) -> Option<&'a Option<DedupInformation>> {
-    match &header.dest {
-        Destination::Processor {
-            partition_key,
-            dedup,
-        } if partition_key_range.contains(partition_key) => Some(dedup),
-        _ => None,
+/// The result of applying a record which requires the partition processor to act on it.
+enum ApplyOutcome {
+    Applied,
+    AnnounceLeader(AnnounceLeader),
+    /// The partition handed over or took over keys. Hand overs require sending the data of the
+    /// keys to the partition which takes them over.
+    KeyRangeChanged(Option<HandOverKeyRange>),
+    /// The record's keys have been handed over to another partition. The record needs to be
+    /// forwarded once the partition table with the given version is known.
+    Forward(Box<Envelope>, Version),
+}
+
+enum Recipient<'a> {
+    Me(&'a Option<DedupInformation>),
+    HandedOver {
+        dedup_information: &'a Option<DedupInformation>,
+        partition_table_version: Version,
+    },
+    Other,
+}
+
+/// Determines whether the record is targeted to this partition. Partition scoped commands are
+/// targeted to the partition whose log contains them, other commands to the partition which
+/// owns their partition key. Proposals of leaders are only accepted by their own partition. Once
+/// keys have been handed over, proposals for them are stale, and other records for them have
+/// been appended based on an outdated partition table.
+fn recipient<'a>(
+    envelope: &'a Envelope,
+    partition_id: PartitionId,
+    ownership: &KeyRangeOwnership,
+) -> Recipient<'a> {
+    let Destination::Processor {
+        partition_key,
+        dedup,
+    } = &envelope.header.dest;
+
+    if envelope.command.is_partition_scoped() {
+        return Recipient::Me(dedup);
+    }
+
+    let is_self_proposal = dedup
+        .as_ref()
+        .is_some_and(|dedup| dedup.producer_id == ProducerId::self_producer());
+    if is_self_proposal {
+        let is_own_proposal = matches!(
+            envelope.header.source,
+            Source::Processor { partition_id: source_partition_id, .. } if source_partition_id == partition_id
+        );
+        return if is_own_proposal && ownership.owns(*partition_key) {
+            Recipient::Me(dedup)
+        } else {
+            Recipient::Other
+        };
+    }
+
+    if ownership.owns(*partition_key) {
+        Recipient::Me(dedup)
+    } else if let Some(hand_over) = ownership.hand_over_of(*partition_key) {
+        Recipient::HandedOver {
+            dedup_information: dedup,
+            partition_table_version: hand_over.partition_table_version,
+        }
+    } else {
+        Recipient::Other
     }
 }
 
@@ -338,6 +839,16 @@ async fn is_outdated_or_duplicate(
     Ok(is_duplicate)
 }
 
+/// An entry of the partition's log which the partition processor needs to act on.
+enum LogEntry {
+    Envelope(Lsn, Envelope),
+    /// The records in `[from, until]` have been trimmed from the log.
+    TrimGap {
+        from: Lsn,
+        until: Lsn,
+    },
+}
+
 struct LogReader {
     log_reader: LogReadStream,
 }
@@ -349,35 +860,97 @@ impl LogReader {
         }
     }
 
-    async fn read_next(&mut self) -> anyhow::Result<(Lsn, Envelope)> {
-        let LogRecord { record, offset } = self.log_reader.read_next().await?;
-        Self::deserialize_record(record).map(|envelope| (offset, envelope))
+    async fn read_next(&mut self) -> anyhow::Result<LogEntry> {
+        loop {
+            let log_record = self.log_reader.read_next().await?;
+            if let Some(entry) = Self::deserialize_record(log_record)? {
+                return Ok(entry);
+            }
+        }
     }
 
     #[allow(dead_code)]
-    async fn read_next_opt(&mut self) -> anyhow::Result<Option<(Lsn, Envelope)>> {
-        let maybe_log_record = self.log_reader.read_next_opt().await?;
-
-        maybe_log_record
-            .map(|log_record| {
-                Self::deserialize_record(log_record.record)
-                    .map(|envelope| (log_record.offset, envelope))
-            })
-            .transpose()
+    async fn read_next_opt(&mut self) -> anyhow::Result<Option<LogEntry>> {
+        while let Some(log_record) = self.log_reader.read_next_opt().await? {
+            if let Some(entry) = Self::deserialize_record(log_record)? {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
     }
 
-    fn deserialize_record(record: Record) -> anyhow::Result<Envelope> {
-        match record {
+    fn deserialize_record(log_record: LogRecord) -> anyhow::Result<Option<LogEntry>> {
+        match log_record.record {
             Record::Data(payload) => {
-                // todo: Replace bincode with protobuf or something similar
-                let envelope = Envelope::decode_with_bincode(payload.as_ref())?;
-                Ok(envelope)
+                let envelope = Envelope::decode(payload.as_ref())?;
+                Ok(Some(LogEntry::Envelope(log_record.offset, envelope)))
             }
-            Record::TrimGap(_) => {
-                unimplemented!("Currently not supported")
+            Record::TrimGap(trim_gap) => Ok(Some(LogEntry::TrimGap {
+                from: log_record.offset,
+                until: trim_gap.until,
+            })),
+            Record::Seal(reason) => {
+                // The log might still be extended with a new segment, the reader waits for it.
+                info!(
+                    "Partition log has been sealed at lsn {}: {:?}",
+                    log_record.offset, reason
+                );
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Periodically trims the partition's log on the leader up to the lsn which all replicas of the
+/// partition have applied. Snapshots are local to each node, so they don't let other replicas
+/// recover trimmed records. The trim point is also bounded by the latest snapshot of the leader,
+/// which reflects all trimmed records for the leader itself.
+struct LogTrimmer {
+    bifrost: Bifrost,
+    log_id: LogId,
+    interval: Option<tokio::time::Interval>,
+    last_trim_point: Lsn,
+}
+
+impl LogTrimmer {
+    fn new(bifrost: Bifrost, log_id: LogId, trim_interval: Option<Duration>) -> Self {
+        let interval = trim_interval.map(|trim_interval| {
+            let mut interval = tokio::time::interval(trim_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            interval
+        });
+
+        Self {
+            bifrost,
+            log_id,
+            interval,
+            last_trim_point: Lsn::INVALID,
+        }
+    }
+
+    /// Completes when the next trim is due. Never completes if log trimming is disabled.
+    async fn tick(&mut self) {
+        match self.interval.as_mut() {
+            Some(interval) => {
+                interval.tick().await;
+            }
+            None => futures::future::pending().await,
+        }
+    }
+
+    async fn trim(&mut self, trim_point: Lsn) {
+        if trim_point <= self.last_trim_point {
+            return;
+        }
+
+        match self.bifrost.trim(self.log_id, trim_point).await {
+            Ok(()) => {
+                debug!(%trim_point, "Trimmed partition log");
+                self.last_trim_point = trim_point;
             }
-            Record::Seal(_) => {
-                unimplemented!("Currently not supported")
+            Err(err) => {
+                // trimming is best-effort, we will retry on the next tick
+                warn!(%trim_point, "Failed to trim partition log: {err}");
             }
         }
     }
@@ -17,8 +17,9 @@ use restate_storage_api::outbox_table::OutboxMessage;
 use restate_types::dedup::DedupInformation;
 use restate_types::identifiers::{LeaderEpoch, PartitionId, PartitionKey, WithPartitionKey};
 use restate_types::message::{AckKind, MessageIndex};
+use restate_types::time::MillisSinceEpoch;
 use restate_types::NodeId;
-use restate_wal_protocol::{append_envelope_to_bifrost, Destination, Envelope, Header, Source};
+use restate_wal_protocol::{append_envelopes_to_bifrost, Destination, Envelope, Header, Source};
 use std::future::Future;
 use tokio::sync::mpsc;
 use tracing::debug;
@@ -84,6 +85,7 @@ fn create_header(
                 seq_number,
             )),
         },
+        created_at: Some(MillisSinceEpoch::now()),
     }
 }
 
@@ -232,10 +234,10 @@ where
         let state_machine = StateMachine::new(
             metadata,
             outbox_reader,
-            |msg| {
+            |msgs| {
                 let mut bifrost = bifrost.clone();
                 async move {
-                    append_envelope_to_bifrost(&mut bifrost, msg).await?;
+                    append_envelopes_to_bifrost(&mut bifrost, msgs).await?;
                     Ok(())
                 }
             },
@@ -279,10 +281,13 @@ mod state_machine {
     use tokio_util::sync::ReusableBoxFuture;
     use tracing::trace;
 
+    /// Maximum number of outbox messages which are appended to the log as a single batch.
+    const MAX_BATCH_SIZE: usize = 64;
+
     type ReadFuture<OutboxReader> = ReusableBoxFuture<
         'static,
         (
-            Result<Option<(MessageIndex, OutboxMessage)>, OutboxReaderError>,
+            Result<Vec<(MessageIndex, OutboxMessage)>, OutboxReaderError>,
             OutboxReader,
         ),
     >;
@@ -291,7 +296,11 @@ mod state_machine {
     enum State<SendFuture> {
         Idle,
         ReadingOutbox,
-        Sending(#[pin] SendFuture),
+        Sending {
+            batch_size: MessageIndex,
+            #[pin]
+            send_future: SendFuture,
+        },
     }
 
     #[pin_project]
@@ -306,35 +315,74 @@ mod state_machine {
         state: State<SendFuture>,
     }
 
-    async fn get_message<OutboxReader: shuffle::OutboxReader>(
+    fn wrap_messages_in_envelopes(
+        messages: Vec<(MessageIndex, OutboxMessage)>,
+        metadata: &ShuffleMetadata,
+    ) -> Vec<Envelope> {
+        messages
+            .into_iter()
+            .map(|(seq_number, message)| {
+                wrap_outbox_message_in_envelope(message, seq_number, metadata)
+            })
+            .collect()
+    }
+
+    /// Reads the message with the given sequence number and the consecutive messages after it.
+    async fn get_messages<OutboxReader: shuffle::OutboxReader>(
         mut outbox_reader: OutboxReader,
         sequence_number: MessageIndex,
     ) -> (
-        Result<Option<(MessageIndex, OutboxMessage)>, OutboxReaderError>,
+        Result<Vec<(MessageIndex, OutboxMessage)>, OutboxReaderError>,
         OutboxReader,
     ) {
-        let result = outbox_reader.get_message(sequence_number).await;
-        (
-            result.map(|opt| opt.map(|m| (sequence_number, m))),
-            outbox_reader,
-        )
+        let result =
+            read_consecutive_messages(&mut outbox_reader, Vec::new(), sequence_number).await;
+        (result, outbox_reader)
     }
 
-    async fn get_next_message<OutboxReader: shuffle::OutboxReader>(
+    /// Reads the next available message starting from the given sequence number and the
+    /// consecutive messages after it.
+    async fn get_next_messages<OutboxReader: shuffle::OutboxReader>(
         mut outbox_reader: OutboxReader,
         sequence_number: MessageIndex,
     ) -> (
-        Result<Option<(MessageIndex, OutboxMessage)>, OutboxReaderError>,
+        Result<Vec<(MessageIndex, OutboxMessage)>, OutboxReaderError>,
         OutboxReader,
     ) {
-        let result = outbox_reader.get_next_message(sequence_number).await;
+        let result = match outbox_reader.get_next_message(sequence_number).await {
+            Ok(Some((sequence_number, message))) => {
+                read_consecutive_messages(
+                    &mut outbox_reader,
+                    vec![(sequence_number, message)],
+                    sequence_number + 1,
+                )
+                .await
+            }
+            Ok(None) => Ok(Vec::new()),
+            Err(err) => Err(err),
+        };
         (result, outbox_reader)
     }
 
+    async fn read_consecutive_messages<OutboxReader: shuffle::OutboxReader>(
+        outbox_reader: &mut OutboxReader,
+        mut messages: Vec<(MessageIndex, OutboxMessage)>,
+        mut sequence_number: MessageIndex,
+    ) -> Result<Vec<(MessageIndex, OutboxMessage)>, OutboxReaderError> {
+        while messages.len() < MAX_BATCH_SIZE {
+            let Some(message) = outbox_reader.get_message(sequence_number).await? else {
+                break;
+            };
+            messages.push((sequence_number, message));
+            sequence_number += 1;
+        }
+        Ok(messages)
+    }
+
     impl<'a, OutboxReader, SendOp, SendFuture> StateMachine<'a, OutboxReader, SendOp, SendFuture>
     where
         SendFuture: Future<Output = Result<(), anyhow::Error>>,
-        SendOp: Fn(Envelope) -> SendFuture,
+        SendOp: Fn(Vec<Envelope>) -> SendFuture,
         OutboxReader: shuffle::OutboxReader + Send + Sync + 'static,
     {
         pub(super) fn new(
@@ -347,7 +395,7 @@ mod state_machine {
             // find the first message from where to start shuffling; everyday I'm shuffling
             // afterwards we assume that the message sequence numbers are consecutive w/o gaps!
             trace!("Starting shuffle. Finding first outbox message.");
-            let reading_future = get_next_message(outbox_reader, current_sequence_number);
+            let reading_future = get_next_messages(outbox_reader, current_sequence_number);
 
             Self {
                 metadata,
@@ -379,18 +427,40 @@ mod state_machine {
 
                             match seq_number.cmp(this.current_sequence_number) {
                                 Ordering::Equal => {
-                                    let send_future =
-                                        (this.send_operation)(wrap_outbox_message_in_envelope(
-                                            message,
+                                    // append the hinted messages which queued up in the meantime
+                                    // together with this one
+                                    let mut messages = vec![(seq_number, message)];
+                                    while messages.len() < MAX_BATCH_SIZE {
+                                        let Ok(NewOutboxMessage {
                                             seq_number,
-                                            this.metadata,
-                                        ));
-                                    this.state.set(State::Sending(send_future));
+                                            message,
+                                        }) = this.hint_rx.try_recv()
+                                        else {
+                                            break;
+                                        };
+                                        if seq_number
+                                            != *this.current_sequence_number
+                                                + messages.len() as MessageIndex
+                                        {
+                                            // dropping the hint is fine, the next message is
+                                            // read from the outbox after this batch
+                                            break;
+                                        }
+                                        messages.push((seq_number, message));
+                                    }
+                                    let batch_size = messages.len() as MessageIndex;
+                                    let send_future = (this.send_operation)(
+                                        wrap_messages_in_envelopes(messages, this.metadata),
+                                    );
+                                    this.state.set(State::Sending {
+                                        batch_size,
+                                        send_future,
+                                    });
                                     break;
                                 }
                                 Ordering::Greater => {
                                     // we might have missed some hints, so try again reading the next available outbox message (scan)
-                                    this.read_future.set(get_next_message(
+                                    this.read_future.set(get_next_messages(
                                         this.outbox_reader
                                             .take()
                                             .expect("outbox reader should be available"),
@@ -409,30 +479,40 @@ mod state_machine {
                         let (reading_result, outbox_reader) = this.read_future.get_pin().await;
                         *this.outbox_reader = Some(outbox_reader);
 
-                        if let Some((seq_number, message)) = reading_result? {
+                        let messages = reading_result?;
+                        if let Some((seq_number, _)) = messages.first() {
                             assert!(
-                                seq_number >= *this.current_sequence_number,
+                                *seq_number >= *this.current_sequence_number,
                                 "message sequence numbers must not decrease"
                             );
 
-                            *this.current_sequence_number = seq_number;
-
-                            let send_future = (this.send_operation)(
-                                wrap_outbox_message_in_envelope(message, seq_number, this.metadata),
-                            );
-
-                            this.state.set(State::Sending(send_future));
+                            *this.current_sequence_number = *seq_number;
+
+                            let batch_size = messages.len() as MessageIndex;
+                            let send_future = (this.send_operation)(wrap_messages_in_envelopes(
+                                messages,
+                                this.metadata,
+                            ));
+                            this.state.set(State::Sending {
+                                batch_size,
+                                send_future,
+                            });
                         } else {
                             this.state.set(State::Idle);
                         }
                     }
-                    StateProj::Sending(send_future) => {
+                    StateProj::Sending {
+                        batch_size,
+                        send_future,
+                    } => {
+                        let batch_size = *batch_size;
                         send_future.await?;
 
-                        let successfully_shuffled_sequence_number = *this.current_sequence_number;
-                        *this.current_sequence_number += 1;
+                        *this.current_sequence_number += batch_size;
+                        let successfully_shuffled_sequence_number =
+                            *this.current_sequence_number - 1;
 
-                        this.read_future.set(get_message(
+                        this.read_future.set(get_messages(
                             this.outbox_reader
                                 .take()
                                 .expect("outbox reader should be available"),
@@ -0,0 +1,218 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use tracing::{debug, info, warn};
+
+use restate_storage_rocksdb::snapshots::SnapshotRepository;
+use restate_storage_rocksdb::RocksDBStorage;
+use restate_types::identifiers::{PartitionId, PartitionKey};
+use restate_types::logs::{Lsn, SequenceNumber};
+
+/// Where and how often partition processors create snapshots of their partition store.
+#[derive(Debug, Clone)]
+pub(crate) struct SnapshotSettings {
+    pub repository: SnapshotRepository,
+    /// Snapshots are only created if an interval is set. Existing snapshots are still used to
+    /// bootstrap partition processors.
+    pub interval: Option<Duration>,
+    pub retention: usize,
+}
+
+/// Creates periodic snapshots of the partition store and restores the latest snapshot when a
+/// partition processor starts.
+pub(super) struct Snapshotter {
+    partition_id: PartitionId,
+    storage: RocksDBStorage,
+    repository: SnapshotRepository,
+    interval: Option<tokio::time::Interval>,
+    retention: usize,
+    latest_snapshot_lsn: Lsn,
+}
+
+impl Snapshotter {
+    pub(super) fn new(
+        partition_id: PartitionId,
+        storage: RocksDBStorage,
+        settings: SnapshotSettings,
+    ) -> Self {
+        let interval = settings.interval.map(|period| {
+            // the first snapshot is due after one period, not right after the start
+            let mut interval =
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            interval
+        });
+
+        Self {
+            partition_id,
+            storage,
+            repository: settings.repository,
+            interval,
+            retention: settings.retention.max(1),
+            latest_snapshot_lsn: Lsn::INVALID,
+        }
+    }
+
+    /// Replaces the partition store with the latest snapshot if the snapshot is ahead of the
+    /// applied lsn of the partition store. Afterwards, only the log after the snapshot needs to
+    /// be replayed. Only the keys of the given key range, which the partition currently owns in
+    /// the partition store, are replaced.
+    pub(super) async fn restore_latest(
+        &mut self,
+        applied_lsn: Option<Lsn>,
+        key_range: Option<RangeInclusive<PartitionKey>>,
+    ) -> anyhow::Result<()> {
+        let partition_id = self.partition_id;
+        let repository = self.repository.clone();
+        let storage = self.storage.clone();
+
+        let restored_lsn = tokio::task::spawn_blocking(move || {
+            let Some(snapshot) = repository.latest(partition_id)? else {
+                return Ok::<_, anyhow::Error>(None);
+            };
+
+            let snapshot_lsn = snapshot.metadata.applied_lsn;
+            if applied_lsn.is_some_and(|applied_lsn| applied_lsn >= snapshot_lsn) {
+                return Ok(Some(snapshot_lsn));
+            }
+
+            info!(
+                %partition_id,
+                applied_lsn = %snapshot_lsn,
+                "Restoring partition store from snapshot {}",
+                snapshot.path.display()
+            );
+            storage.restore_partition_snapshot(&snapshot, key_range.as_ref())?;
+            Ok(Some(snapshot_lsn))
+        })
+        .await??;
+
+        if let Some(restored_lsn) = restored_lsn {
+            self.latest_snapshot_lsn = restored_lsn;
+        }
+        Ok(())
+    }
+
+    /// Restores the latest snapshot after the log has been trimmed up to `trim_point` before the
+    /// trimmed records have been applied. Returns the applied lsn of the restored snapshot, the
+    /// log needs to be replayed after it. Fails if no snapshot reflects the trimmed records.
+    pub(super) async fn restore_trimmed(
+        &mut self,
+        trim_point: Lsn,
+        key_range: Option<RangeInclusive<PartitionKey>>,
+    ) -> anyhow::Result<Lsn> {
+        let partition_id = self.partition_id;
+        let repository = self.repository.clone();
+        let storage = self.storage.clone();
+
+        let restored_lsn = tokio::task::spawn_blocking(move || {
+            let snapshot = repository
+                .latest(partition_id)?
+                .filter(|snapshot| snapshot.metadata.applied_lsn >= trim_point)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "partition store is behind the log trim point {trim_point} and no \
+                        snapshot reflects the trimmed records"
+                    )
+                })?;
+
+            info!(
+                %partition_id,
+                applied_lsn = %snapshot.metadata.applied_lsn,
+                "Restoring partition store from snapshot {}",
+                snapshot.path.display()
+            );
+            storage.restore_partition_snapshot(&snapshot, key_range.as_ref())?;
+            Ok::<_, anyhow::Error>(snapshot.metadata.applied_lsn)
+        })
+        .await??;
+
+        self.latest_snapshot_lsn = self.latest_snapshot_lsn.max(restored_lsn);
+        Ok(restored_lsn)
+    }
+
+    /// Completes when the next snapshot is due. Never completes if snapshots are disabled.
+    pub(super) async fn tick(&mut self) {
+        match self.interval.as_mut() {
+            Some(interval) => {
+                interval.tick().await;
+            }
+            None => futures::future::pending().await,
+        }
+    }
+
+    /// Snapshots the partition store at the given applied lsn and prunes the snapshots which
+    /// exceed the retention. No records must be applied until the snapshot has been created.
+    /// Snapshots are best-effort, failures are retried with the next snapshot.
+    pub(super) async fn snapshot(
+        &mut self,
+        key_range: Option<RangeInclusive<PartitionKey>>,
+        applied_lsn: Lsn,
+    ) {
+        if applied_lsn == Lsn::INVALID || applied_lsn <= self.latest_snapshot_lsn {
+            return;
+        }
+
+        let partition_id = self.partition_id;
+        let repository = self.repository.clone();
+        let storage = self.storage.clone();
+        let retention = self.retention;
+
+        let result = tokio::task::spawn_blocking(move || {
+            let snapshot = repository.create(&storage, partition_id, key_range, applied_lsn)?;
+            repository.prune(partition_id, retention)?;
+            Ok::<_, anyhow::Error>(snapshot)
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+
+        match result {
+            Ok(snapshot) => {
+                debug!(
+                    %applied_lsn,
+                    "Created partition snapshot {}",
+                    snapshot.path.display()
+                );
+                self.latest_snapshot_lsn = applied_lsn;
+            }
+            Err(err) => {
+                warn!(%applied_lsn, "Failed to create partition snapshot: {err}");
+            }
+        }
+    }
+
+    /// Applied lsn of the latest snapshot of the partition in the repository of this node. Only
+    /// this node can restore the snapshot, it tells nothing about whether other replicas can
+    /// recover records which have been trimmed up to this lsn.
+    pub(super) async fn latest_snapshot_lsn(&mut self) -> Lsn {
+        let partition_id = self.partition_id;
+        let repository = self.repository.clone();
+
+        let result = tokio::task::spawn_blocking(move || repository.latest(partition_id))
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|result| result.map_err(anyhow::Error::from));
+
+        match result {
+            Ok(Some(snapshot)) => {
+                self.latest_snapshot_lsn =
+                    self.latest_snapshot_lsn.max(snapshot.metadata.applied_lsn);
+            }
+            Ok(None) => {}
+            Err(err) => warn!("Failed to list partition snapshots: {err}"),
+        }
+
+        self.latest_snapshot_lsn
+    }
+}
@@ -14,7 +14,7 @@ use restate_invoker_api::InvokeInputJournal;
 use restate_storage_api::outbox_table::OutboxMessage;
 use restate_storage_api::timer_table::TimerKey;
 use restate_types::identifiers::{EntryIndex, FullInvocationId};
-use restate_types::ingress::IngressResponse;
+use restate_types::ingress::{IngressAttachResponse, IngressResponse};
 use restate_types::invocation::{ServiceInvocationResponseSink, ServiceInvocationSpanContext};
 use restate_types::journal::Completion;
 use restate_types::message::MessageIndex;
@@ -53,4 +53,5 @@ pub enum Action {
     },
     AbortInvocation(FullInvocationId),
     IngressResponse(IngressResponse),
+    IngressAttachResponse(IngressAttachResponse),
 }
@@ -22,7 +22,9 @@ use bytestring::ByteString;
 use futures::{Stream, StreamExt};
 use restate_service_protocol::codec::ProtobufRawEntryCodec;
 use restate_storage_api::inbox_table::{InboxEntry, SequenceNumberInvocation};
-use restate_storage_api::invocation_status_table::{InvocationMetadata, InvocationStatus};
+use restate_storage_api::invocation_status_table::{
+    CompletedInvocation, InvocationMetadata, InvocationStatus,
+};
 use restate_storage_api::journal_table::JournalEntry;
 use restate_storage_api::outbox_table::OutboxMessage;
 use restate_storage_api::service_status_table::VirtualObjectStatus;
@@ -30,16 +32,18 @@ use restate_storage_api::timer_table::{Timer, TimerKey};
 use restate_storage_api::Result as StorageResult;
 use restate_types::errors::{
     InvocationError, InvocationErrorCode, CANCELED_INVOCATION_ERROR, KILLED_INVOCATION_ERROR,
+    NOT_FOUND_INVOCATION_ERROR, NOT_READY_INVOCATION_ERROR,
 };
 use restate_types::identifiers::{
     EntryIndex, FullInvocationId, InvocationId, InvocationUuid, PartitionKey, ServiceId,
     WithPartitionKey,
 };
-use restate_types::ingress::IngressResponse;
+use restate_types::ingress::{IngressAttachResponse, IngressResponse};
 use restate_types::invocation::{
-    InvocationResponse, InvocationTermination, MaybeFullInvocationId, ResponseResult,
-    ServiceInvocation, ServiceInvocationResponseSink, ServiceInvocationSpanContext, Source,
-    SpanRelation, SpanRelationCause, TerminationFlavor,
+    AttachInvocationRequest, AttachedResponseSink, InvocationResponse, InvocationTermination,
+    MaybeFullInvocationId, PurgeInvocationRequest, ResponseResult, ServiceInvocation,
+    ServiceInvocationResponseSink, ServiceInvocationSpanContext, Source, SpanRelation,
+    SpanRelationCause, TerminationFlavor,
 };
 use restate_types::journal::enriched::{
     AwakeableEnrichmentResult, EnrichedEntryHeader, EnrichedRawEntry, InvokeEnrichmentResult,
@@ -58,6 +62,7 @@ use std::future::Future;
 use std::marker::PhantomData;
 use std::ops::{Deref, RangeInclusive};
 use std::pin::pin;
+use std::time::{Duration, SystemTime};
 use tracing::{debug, instrument, trace};
 
 pub trait StateReader {
@@ -110,7 +115,9 @@ pub(crate) struct CommandInterpreter<Codec> {
     // initialized from persistent storage
     inbox_seq_number: MessageIndex,
     outbox_seq_number: MessageIndex,
-    partition_key_range: RangeInclusive<PartitionKey>,
+    partition_key_range: Option<RangeInclusive<PartitionKey>>,
+    // creation time of the command which is being applied
+    command_created_at: Option<MillisSinceEpoch>,
 
     _codec: PhantomData<Codec>,
 }
@@ -128,12 +135,13 @@ impl<Codec> CommandInterpreter<Codec> {
     pub(crate) fn new(
         inbox_seq_number: MessageIndex,
         outbox_seq_number: MessageIndex,
-        partition_key_range: RangeInclusive<PartitionKey>,
+        partition_key_range: Option<RangeInclusive<PartitionKey>>,
     ) -> Self {
         Self {
             inbox_seq_number,
             outbox_seq_number,
             partition_key_range,
+            command_created_at: None,
             _codec: PhantomData,
         }
     }
@@ -143,7 +151,9 @@ impl<Codec> CommandInterpreter<Codec>
 where
     Codec: RawEntryCodec,
 {
-    /// Applies the given command and returns effects via the provided effects struct
+    /// Applies the given command and returns effects via the provided effects struct.
+    /// `created_at` is the creation time of the command, see
+    /// [`restate_wal_protocol::Header::created_at`].
     ///
     /// We pass in the effects message as a mutable borrow to be able to reuse it across
     /// invocations of this methods which lies on the hot path.
@@ -153,9 +163,11 @@ where
     pub(crate) async fn on_apply<State: StateReader>(
         &mut self,
         command: Command,
+        created_at: Option<MillisSinceEpoch>,
         effects: &mut Effects,
         state: &mut State,
     ) -> Result<(Option<FullInvocationId>, SpanRelation), Error> {
+        self.command_created_at = created_at;
         match command {
             Command::Invoke(service_invocation) => {
                 self.handle_invoke(effects, state, service_invocation).await
@@ -194,8 +206,17 @@ where
                 self.handle_external_state_mutation(mutation, state, effects)
                     .await
             }
-            Command::AnnounceLeader(_) => {
-                // no-op :-)
+            Command::AttachInvocation(attach_invocation_request) => {
+                Self::handle_attach_invocation(attach_invocation_request, state, effects).await
+            }
+            Command::PurgeInvocation(purge_invocation_request) => {
+                Self::handle_purge_invocation(purge_invocation_request, state, effects).await
+            }
+            Command::AnnounceLeader(_)
+            | Command::HandOverKeyRange(_)
+            | Command::TakeOverKeyRange(_)
+            | Command::KeyRangeData(_) => {
+                // no-op :-), handled by the partition processor
                 Ok((None, SpanRelation::None))
             }
         }
@@ -208,7 +229,7 @@ where
         service_invocation: ServiceInvocation,
     ) -> Result<(Option<FullInvocationId>, SpanRelation), Error> {
         debug_assert!(
-            self.partition_key_range.contains(&service_invocation.fid.partition_key()),
+            self.partition_key_range.as_ref().is_some_and(|partition_key_range| partition_key_range.contains(&service_invocation.fid.partition_key())),
                 "Service invocation with partition key '{}' has been delivered to a partition processor with key range '{:?}'. This indicates a bug.",
                 service_invocation.fid.partition_key(),
                 self.partition_key_range);
@@ -253,6 +274,141 @@ where
         self.inbox_seq_number += 1;
     }
 
+    /// Frees a completed invocation, dropping its retained result. The cleanup timer of the
+    /// invocation is left registered, it finds the invocation freed once it fires.
+    async fn handle_purge_invocation<State: StateReader>(
+        purge_invocation_request: PurgeInvocationRequest,
+        state: &mut State,
+        effects: &mut Effects,
+    ) -> Result<(Option<FullInvocationId>, SpanRelation), Error> {
+        let PurgeInvocationRequest { invocation_id } = purge_invocation_request;
+
+        match state.get_invocation_status(&invocation_id).await? {
+            InvocationStatus::Completed(completed_invocation) => {
+                effects.free_invocation(invocation_id.clone());
+                Ok((
+                    Some(FullInvocationId::combine(
+                        completed_invocation.service_id,
+                        invocation_id,
+                    )),
+                    SpanRelation::None,
+                ))
+            }
+            _ => {
+                trace!("Ignoring purge of invocation {invocation_id} which is not completed");
+                Ok((None, SpanRelation::None))
+            }
+        }
+    }
+
+    async fn handle_attach_invocation<State: StateReader>(
+        attach_invocation_request: AttachInvocationRequest,
+        state: &mut State,
+        effects: &mut Effects,
+    ) -> Result<(Option<FullInvocationId>, SpanRelation), Error> {
+        let AttachInvocationRequest {
+            invocation_id,
+            block_on_inflight,
+            response_sink,
+        } = attach_invocation_request;
+
+        match state.get_invocation_status(&invocation_id).await? {
+            InvocationStatus::Completed(completed_invocation) => {
+                Self::send_attach_response(
+                    effects,
+                    invocation_id.clone(),
+                    response_sink,
+                    completed_invocation.response_result,
+                );
+                Ok((
+                    Some(FullInvocationId::combine(
+                        completed_invocation.service_id,
+                        invocation_id,
+                    )),
+                    SpanRelation::None,
+                ))
+            }
+            invocation_status @ (InvocationStatus::Invoked(_)
+            | InvocationStatus::Suspended { .. }) => {
+                let journal_metadata = invocation_status
+                    .get_journal_metadata()
+                    .expect("In-flight invocations have a journal");
+                let full_invocation_id = FullInvocationId::combine(
+                    invocation_status
+                        .service_id()
+                        .expect("In-flight invocations have a service id"),
+                    invocation_id.clone(),
+                );
+                let span_relation = journal_metadata.span_context.as_parent();
+
+                // the output could have been written to the journal before the invocation ended
+                if let Some(response_result) =
+                    Self::read_output(state, &invocation_id, journal_metadata.length).await?
+                {
+                    Self::send_attach_response(
+                        effects,
+                        invocation_id,
+                        response_sink,
+                        response_result,
+                    );
+                } else if block_on_inflight {
+                    effects.attach_response_sink(invocation_id, invocation_status, response_sink);
+                } else {
+                    Self::send_attach_response(
+                        effects,
+                        invocation_id,
+                        response_sink,
+                        ResponseResult::from(NOT_READY_INVOCATION_ERROR),
+                    );
+                }
+                Ok((Some(full_invocation_id), span_relation))
+            }
+            InvocationStatus::Free => {
+                let Some(inbox_entry) = state.get_inboxed_invocation(invocation_id.clone()).await?
+                else {
+                    Self::send_attach_response(
+                        effects,
+                        invocation_id,
+                        response_sink,
+                        ResponseResult::from(NOT_FOUND_INVOCATION_ERROR),
+                    );
+                    return Ok((None, SpanRelation::None));
+                };
+
+                let full_invocation_id = inbox_entry.invocation.fid.clone();
+                let span_relation = inbox_entry.invocation.span_context.as_parent();
+
+                if block_on_inflight {
+                    // the response sink is moved to the invocation status once the invocation
+                    // is invoked
+                    effects.attach_response_sink_to_inboxed_invocation(inbox_entry, response_sink);
+                } else {
+                    Self::send_attach_response(
+                        effects,
+                        invocation_id,
+                        response_sink,
+                        ResponseResult::from(NOT_READY_INVOCATION_ERROR),
+                    );
+                }
+                Ok((Some(full_invocation_id), span_relation))
+            }
+        }
+    }
+
+    fn send_attach_response(
+        effects: &mut Effects,
+        invocation_id: InvocationId,
+        response_sink: AttachedResponseSink,
+        response: ResponseResult,
+    ) {
+        effects.send_ingress_attach_response(IngressAttachResponse {
+            target_node: response_sink.node_id,
+            request_id: response_sink.request_id,
+            invocation_id,
+            response,
+        });
+    }
+
     async fn handle_external_state_mutation<State: StateReader>(
         &mut self,
         mutation: ExternalStateMutation,
@@ -294,6 +450,7 @@ where
                 for nbis_effect in nbis_effects {
                     self.on_built_in_invoker_effect(
                         effects,
+                        state,
                         &full_invocation_id,
                         &invocation_metadata,
                         nbis_effect,
@@ -312,9 +469,10 @@ where
         }
     }
 
-    async fn on_built_in_invoker_effect(
+    async fn on_built_in_invoker_effect<State: StateReader>(
         &mut self,
         effects: &mut Effects,
+        state: &mut State,
         full_invocation_id: &FullInvocationId,
         invocation_metadata: &InvocationMetadata,
         nbis_effect: BuiltinServiceEffect,
@@ -343,6 +501,7 @@ where
             BuiltinServiceEffect::End(None) => {
                 self.end_invocation(
                     effects,
+                    state,
                     full_invocation_id.clone(),
                     invocation_metadata.clone(),
                 )
@@ -519,6 +678,14 @@ where
         let parent_span = span_context.as_parent();
 
         self.try_send_failure_response(effects, &fid, service_invocation.response_sink, &error);
+        for response_sink in service_invocation.attached_response_sinks {
+            Self::send_attach_response(
+                effects,
+                InvocationId::from(&fid),
+                response_sink,
+                ResponseResult::from(&error),
+            );
+        }
 
         self.notify_invocation_result(
             &fid,
@@ -750,6 +917,24 @@ where
                 // where the invocation should be executed
                 self.handle_invoke(effects, state, service_invocation).await
             }
+            Timer::CleanInvocationStatus(service_id) => {
+                let invocation_id = InvocationId::new(service_id.partition_key(), invocation_uuid);
+
+                // the invocation might have been purged already
+                if let InvocationStatus::Completed(_) =
+                    state.get_invocation_status(&invocation_id).await?
+                {
+                    effects.free_invocation(invocation_id);
+                }
+
+                Ok((
+                    Some(FullInvocationId {
+                        service_id,
+                        invocation_uuid,
+                    }),
+                    SpanRelation::None,
+                ))
+            }
         }
     }
 
@@ -843,7 +1028,7 @@ where
                 }
             }
             InvokerEffectKind::End => {
-                self.end_invocation(effects, full_invocation_id, invocation_metadata)
+                self.end_invocation(effects, state, full_invocation_id, invocation_metadata)
                     .await?;
             }
             InvokerEffectKind::Failed(e) => {
@@ -855,29 +1040,68 @@ where
         Ok((related_sid, span_relation))
     }
 
-    async fn end_invocation(
+    async fn end_invocation<State: StateReader>(
         &mut self,
         effects: &mut Effects,
+        state: &mut State,
         full_invocation_id: FullInvocationId,
         invocation_metadata: InvocationMetadata,
     ) -> Result<(), Error> {
         self.notify_invocation_result(
             &full_invocation_id,
-            invocation_metadata.method,
-            invocation_metadata.journal_metadata.span_context,
+            invocation_metadata.method.clone(),
+            invocation_metadata.journal_metadata.span_context.clone(),
             invocation_metadata.timestamps.creation_time(),
             Ok(()),
             effects,
         );
 
+        let response_result = if invocation_metadata.completion_retention_time.is_zero() {
+            None
+        } else {
+            Self::read_output(
+                state,
+                &InvocationId::from(&full_invocation_id),
+                invocation_metadata.journal_metadata.length,
+            )
+            .await?
+        };
+
         self.end_invocation_lifecycle(
             full_invocation_id,
-            invocation_metadata.journal_metadata.length,
+            invocation_metadata,
+            response_result,
             effects,
         )
         .await
     }
 
+    /// Reads the result of the invocation from the output entry of its journal. Returns `None`
+    /// if the journal has no output entry, e.g. for built-in services.
+    async fn read_output<State: StateReader>(
+        state: &mut State,
+        invocation_id: &InvocationId,
+        journal_length: EntryIndex,
+    ) -> Result<Option<ResponseResult>, Error> {
+        let mut journal_entries = pin!(state.get_journal(invocation_id, journal_length));
+        while let Some(journal_entry) = journal_entries.next().await {
+            let (_, journal_entry) = journal_entry?;
+
+            if let JournalEntry::Entry(enriched_entry) = journal_entry {
+                if let EnrichedEntryHeader::Output { .. } = enriched_entry.header() {
+                    let_assert!(
+                        Entry::Output(OutputEntry { result }) =
+                            enriched_entry.deserialize_entry_ref::<Codec>()?
+                    );
+                    // only the first output is sent to the response sink
+                    return Ok(Some(result.into()));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
     async fn fail_invocation(
         &mut self,
         effects: &mut Effects,
@@ -888,14 +1112,22 @@ where
         self.try_send_failure_response(
             effects,
             &full_invocation_id,
-            invocation_metadata.response_sink,
+            invocation_metadata.response_sink.clone(),
             &error,
         );
+        for response_sink in &invocation_metadata.attached_response_sinks {
+            Self::send_attach_response(
+                effects,
+                InvocationId::from(&full_invocation_id),
+                *response_sink,
+                ResponseResult::from(&error),
+            );
+        }
 
         self.notify_invocation_result(
             &full_invocation_id,
-            invocation_metadata.method,
-            invocation_metadata.journal_metadata.span_context,
+            invocation_metadata.method.clone(),
+            invocation_metadata.journal_metadata.span_context.clone(),
             invocation_metadata.timestamps.creation_time(),
             Err((error.code(), error.to_string())),
             effects,
@@ -903,7 +1135,8 @@ where
 
         self.end_invocation_lifecycle(
             full_invocation_id,
-            invocation_metadata.journal_metadata.length,
+            invocation_metadata,
+            Some(ResponseResult::from(&error)),
             effects,
         )
         .await
@@ -947,20 +1180,33 @@ where
             // nothing to do
             EnrichedEntryHeader::Input { .. } => {}
             EnrichedEntryHeader::Output { .. } => {
-                if let Some(ref response_sink) = invocation_metadata.response_sink {
+                if invocation_metadata.response_sink.is_some()
+                    || !invocation_metadata.attached_response_sinks.is_empty()
+                {
                     let_assert!(
                         Entry::Output(OutputEntry { result }) =
                             journal_entry.deserialize_entry_ref::<Codec>()?
                     );
-
-                    self.send_response(
-                        create_response_message(
-                            &full_invocation_id,
-                            response_sink.clone(),
-                            result.into(),
-                        ),
-                        effects,
-                    );
+                    let response_result = ResponseResult::from(result);
+
+                    if let Some(ref response_sink) = invocation_metadata.response_sink {
+                        self.send_response(
+                            create_response_message(
+                                &full_invocation_id,
+                                response_sink.clone(),
+                                response_result.clone(),
+                            ),
+                            effects,
+                        );
+                    }
+                    for response_sink in &invocation_metadata.attached_response_sinks {
+                        Self::send_attach_response(
+                            effects,
+                            InvocationId::from(&full_invocation_id),
+                            *response_sink,
+                            response_result.clone(),
+                        );
+                    }
                 }
             }
             EnrichedEntryHeader::GetState { is_completed, .. } => {
@@ -1327,13 +1573,40 @@ where
         );
     }
 
+    /// Drops the journal of the invocation and retains its result, if the invocation has a
+    /// completion retention time. Results are only retained if the command which ends the
+    /// invocation has a creation time, because the expiration time must be the same on all
+    /// replicas.
     async fn end_invocation_lifecycle(
         &mut self,
         full_invocation_id: FullInvocationId,
-        journal_length: EntryIndex,
+        invocation_metadata: InvocationMetadata,
+        response_result: Option<ResponseResult>,
         effects: &mut Effects,
     ) -> Result<(), Error> {
-        effects.drop_journal_and_pop_inbox(full_invocation_id, journal_length);
+        let completion_retention_time = invocation_metadata.completion_retention_time;
+        effects.drop_journal_and_pop_inbox(
+            full_invocation_id.clone(),
+            invocation_metadata.journal_metadata.length,
+        );
+
+        if let Some((response_result, completed_at)) = response_result
+            .filter(|_| !completion_retention_time.is_zero())
+            .zip(self.command_created_at)
+        {
+            let span_context = invocation_metadata.journal_metadata.span_context.clone();
+            effects.store_completed_invocation(
+                InvocationId::from(&full_invocation_id),
+                CompletedInvocation::from_metadata(invocation_metadata, response_result),
+            );
+
+            let expiration_time =
+                MillisSinceEpoch::from(SystemTime::from(completed_at) + completion_retention_time);
+            effects.register_timer(
+                TimerValue::new_clean_invocation_status(full_invocation_id, expiration_time),
+                span_context,
+            );
+        }
 
         Ok(())
     }
@@ -1400,6 +1673,8 @@ where
             span_context,
             headers: vec![],
             execution_time,
+            completion_retention_time: Duration::ZERO,
+            attached_response_sinks: vec![],
         }
     }
 }
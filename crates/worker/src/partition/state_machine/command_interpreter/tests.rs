@@ -244,7 +244,7 @@ impl StateReader for StateReaderMock {
 #[test(tokio::test)]
 async fn awakeable_with_success() {
     let mut state_machine: CommandInterpreter<ProtobufRawEntryCodec> =
-        CommandInterpreter::new(0, 0, PartitionKey::MIN..=PartitionKey::MAX);
+        CommandInterpreter::new(0, 0, Some(PartitionKey::MIN..=PartitionKey::MAX));
     let mut effects = Effects::default();
     let mut state_reader = StateReaderMock::default();
 
@@ -278,7 +278,7 @@ async fn awakeable_with_success() {
     );
 
     state_machine
-        .on_apply(cmd, &mut effects, &mut state_reader)
+        .on_apply(cmd, None, &mut effects, &mut state_reader)
         .await
         .unwrap();
     let_assert!(Effect::EnqueueIntoOutbox { message, .. } = effects.drain().next().unwrap());
@@ -299,7 +299,7 @@ async fn awakeable_with_success() {
 #[test(tokio::test)]
 async fn awakeable_with_failure() {
     let mut state_machine: CommandInterpreter<ProtobufRawEntryCodec> =
-        CommandInterpreter::new(0, 0, PartitionKey::MIN..=PartitionKey::MAX);
+        CommandInterpreter::new(0, 0, Some(PartitionKey::MIN..=PartitionKey::MAX));
     let mut effects = Effects::default();
     let mut state_reader = StateReaderMock::default();
 
@@ -333,7 +333,7 @@ async fn awakeable_with_failure() {
     );
 
     state_machine
-        .on_apply(cmd, &mut effects, &mut state_reader)
+        .on_apply(cmd, None, &mut effects, &mut state_reader)
         .await
         .unwrap();
     let_assert!(Effect::EnqueueIntoOutbox { message, .. } = effects.drain().next().unwrap());
@@ -355,7 +355,7 @@ async fn awakeable_with_failure() {
 #[test(tokio::test)]
 async fn send_response_using_invocation_id() {
     let mut state_machine: CommandInterpreter<ProtobufRawEntryCodec> =
-        CommandInterpreter::new(0, 0, PartitionKey::MIN..=PartitionKey::MAX);
+        CommandInterpreter::new(0, 0, Some(PartitionKey::MIN..=PartitionKey::MAX));
     let mut effects = Effects::default();
     let mut state_reader = StateReaderMock::default();
 
@@ -370,7 +370,7 @@ async fn send_response_using_invocation_id() {
     state_reader.register_invoked_status_and_locked(fid.clone(), vec![]);
 
     state_machine
-        .on_apply(cmd, &mut effects, &mut state_reader)
+        .on_apply(cmd, None, &mut effects, &mut state_reader)
         .await
         .unwrap();
     assert_that!(
@@ -393,7 +393,7 @@ async fn kill_inboxed_invocation() -> Result<(), Error> {
     let mut command_interpreter = CommandInterpreter::<ProtobufRawEntryCodec>::new(
         0,
         0,
-        PartitionKey::MIN..=PartitionKey::MAX,
+        Some(PartitionKey::MIN..=PartitionKey::MAX),
     );
 
     let mut effects = Effects::default();
@@ -423,6 +423,7 @@ async fn kill_inboxed_invocation() -> Result<(), Error> {
             Command::TerminateInvocation(InvocationTermination::kill(MaybeFullInvocationId::from(
                 inboxed_fid.clone(),
             ))),
+            None,
             &mut effects,
             &mut state_mock,
         )
@@ -460,7 +461,7 @@ async fn kill_call_tree() -> Result<(), Error> {
     let mut command_interpreter = CommandInterpreter::<ProtobufRawEntryCodec>::new(
         0,
         0,
-        PartitionKey::MIN..=PartitionKey::MAX,
+        Some(PartitionKey::MIN..=PartitionKey::MAX),
     );
     let mut state_reader = StateReaderMock::default();
     let mut effects = Effects::default();
@@ -484,6 +485,7 @@ async fn kill_call_tree() -> Result<(), Error> {
             Command::TerminateInvocation(InvocationTermination::kill(MaybeFullInvocationId::from(
                 fid.clone(),
             ))),
+            None,
             &mut effects,
             &mut state_reader,
         )
@@ -569,7 +571,7 @@ async fn cancel_invoked_invocation() -> Result<(), Error> {
     let mut command_interpreter = CommandInterpreter::<ProtobufRawEntryCodec>::new(
         0,
         0,
-        PartitionKey::MIN..=PartitionKey::MAX,
+        Some(PartitionKey::MIN..=PartitionKey::MAX),
     );
     let mut state_reader = StateReaderMock::default();
     let mut effects = Effects::default();
@@ -593,6 +595,7 @@ async fn cancel_invoked_invocation() -> Result<(), Error> {
             Command::TerminateInvocation(InvocationTermination::cancel(
                 MaybeFullInvocationId::from(fid.clone()),
             )),
+            None,
             &mut effects,
             &mut state_reader,
         )
@@ -622,7 +625,7 @@ async fn cancel_suspended_invocation() -> Result<(), Error> {
     let mut command_interpreter = CommandInterpreter::<ProtobufRawEntryCodec>::new(
         0,
         0,
-        PartitionKey::MIN..=PartitionKey::MAX,
+        Some(PartitionKey::MIN..=PartitionKey::MAX),
     );
     let mut state_reader = StateReaderMock::default();
     let mut effects = Effects::default();
@@ -644,6 +647,7 @@ async fn cancel_suspended_invocation() -> Result<(), Error> {
             Command::TerminateInvocation(InvocationTermination::cancel(
                 MaybeFullInvocationId::from(fid.clone()),
             )),
+            None,
             &mut effects,
             &mut state_reader,
         )
@@ -356,6 +356,54 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
                 Self::pop_from_inbox(state_storage, collector, &full_invocation_id.service_id)
                     .await?;
             }
+            Effect::StoreCompletedInvocation {
+                invocation_id,
+                completed_invocation,
+            } => {
+                state_storage
+                    .store_invocation_status(
+                        &invocation_id,
+                        InvocationStatus::Completed(completed_invocation),
+                    )
+                    .await?;
+            }
+            Effect::FreeInvocation(invocation_id) => {
+                state_storage
+                    .store_invocation_status(&invocation_id, InvocationStatus::Free)
+                    .await?;
+            }
+            Effect::AttachResponseSink {
+                invocation_id,
+                mut previous_invocation_status,
+                response_sink,
+            } => {
+                previous_invocation_status
+                    .get_invocation_metadata_mut()
+                    .expect("Response sinks can only be attached to in-flight invocations")
+                    .attached_response_sinks
+                    .push(response_sink);
+
+                state_storage
+                    .store_invocation_status(&invocation_id, previous_invocation_status)
+                    .await?;
+            }
+            Effect::AttachResponseSinkToInboxedInvocation {
+                mut inbox_entry,
+                response_sink,
+            } => {
+                inbox_entry
+                    .invocation
+                    .attached_response_sinks
+                    .push(response_sink);
+
+                // overwrites the inbox entry, the invocation keeps its position in the inbox
+                state_storage
+                    .enqueue_into_inbox(
+                        inbox_entry.inbox_sequence_number,
+                        InboxEntry::Invocation(inbox_entry.invocation),
+                    )
+                    .await?;
+            }
             Effect::TraceInvocationResult { .. } | Effect::TraceBackgroundInvoke { .. } => {
                 // these effects are only needed for span creation
             }
@@ -374,6 +422,9 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
             Effect::IngressResponse(ingress_response) => {
                 collector.push(Action::IngressResponse(ingress_response));
             }
+            Effect::IngressAttachResponse(ingress_attach_response) => {
+                collector.push(Action::IngressAttachResponse(ingress_attach_response));
+            }
         }
 
         Ok(())
@@ -465,18 +516,22 @@ impl<Codec: RawEntryCodec> EffectInterpreter<Codec> {
                 VirtualObjectStatus::Locked(invocation_id.clone()),
             )
             .await?;
+        let mut invocation_metadata = InvocationMetadata::new(
+            service_invocation.fid.service_id.clone(),
+            journal_metadata.clone(),
+            None,
+            service_invocation.method_name.clone(),
+            service_invocation.response_sink.clone(),
+            StatusTimestamps::now(),
+            service_invocation.source,
+            service_invocation.completion_retention_time,
+        );
+        // requests which attached while the invocation was waiting in the inbox
+        invocation_metadata.attached_response_sinks = service_invocation.attached_response_sinks;
         state_storage
             .store_invocation_status(
                 &invocation_id,
-                InvocationStatus::Invoked(InvocationMetadata::new(
-                    service_invocation.fid.service_id.clone(),
-                    journal_metadata.clone(),
-                    None,
-                    service_invocation.method_name.clone(),
-                    service_invocation.response_sink.clone(),
-                    StatusTimestamps::now(),
-                    service_invocation.source,
-                )),
+                InvocationStatus::Invoked(invocation_metadata),
             )
             .await?;
 
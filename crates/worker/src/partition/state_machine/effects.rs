@@ -11,19 +11,20 @@
 use bytes::Bytes;
 use bytestring::ByteString;
 use opentelemetry_api::trace::SpanId;
-use restate_storage_api::inbox_table::InboxEntry;
-use restate_storage_api::invocation_status_table::InvocationMetadata;
-use restate_storage_api::invocation_status_table::{InvocationStatus, JournalMetadata};
+use restate_storage_api::inbox_table::{InboxEntry, SequenceNumberInvocation};
+use restate_storage_api::invocation_status_table::{
+    CompletedInvocation, InvocationMetadata, InvocationStatus, JournalMetadata,
+};
 use restate_storage_api::outbox_table::OutboxMessage;
 use restate_storage_api::timer_table::{Timer, TimerKey};
 use restate_types::errors::InvocationErrorCode;
 use restate_types::identifiers::{
     DeploymentId, EntryIndex, FullInvocationId, InvocationId, ServiceId,
 };
-use restate_types::ingress::IngressResponse;
+use restate_types::ingress::{IngressAttachResponse, IngressResponse};
 use restate_types::invocation::{
-    InvocationResponse, ResponseResult, ServiceInvocation, ServiceInvocationSpanContext,
-    SpanRelation,
+    AttachedResponseSink, InvocationResponse, ResponseResult, ServiceInvocation,
+    ServiceInvocationSpanContext, SpanRelation,
 };
 use restate_types::journal::enriched::EnrichedRawEntry;
 use restate_types::journal::{Completion, CompletionResult};
@@ -65,6 +66,23 @@ pub(crate) enum Effect {
         full_invocation_id: FullInvocationId,
         journal_length: EntryIndex,
     },
+    /// Must follow [`Effect::DropJournalAndPopInbox`], because it replaces the free status.
+    StoreCompletedInvocation {
+        invocation_id: InvocationId,
+        completed_invocation: CompletedInvocation,
+    },
+    FreeInvocation(InvocationId),
+    /// Adds the response sink to the attached response sinks of the in-flight invocation.
+    AttachResponseSink {
+        invocation_id: InvocationId,
+        previous_invocation_status: InvocationStatus,
+        response_sink: AttachedResponseSink,
+    },
+    /// Adds the response sink to the attached response sinks of the inboxed invocation.
+    AttachResponseSinkToInboxedInvocation {
+        inbox_entry: SequenceNumberInvocation,
+        response_sink: AttachedResponseSink,
+    },
     DeleteInboxEntry {
         service_id: ServiceId,
         sequence_number: MessageIndex,
@@ -145,6 +163,7 @@ pub(crate) enum Effect {
     MutateState(ExternalStateMutation),
 
     IngressResponse(IngressResponse),
+    IngressAttachResponse(IngressAttachResponse),
 }
 
 macro_rules! debug_if_leader {
@@ -297,6 +316,30 @@ impl Effect {
                 error_code,
                 error_msg,
             ),
+            Effect::IngressAttachResponse(IngressAttachResponse {
+                response: ResponseResult::Success(_),
+                invocation_id,
+                request_id,
+                ..
+            }) => debug_if_leader!(
+                is_leader,
+                restate.invocation.id = %invocation_id,
+                "Effect: Send response to ingress request {} attached to the invocation: Success",
+                request_id
+            ),
+            Effect::IngressAttachResponse(IngressAttachResponse {
+                response: ResponseResult::Failure(error_code, error_msg),
+                invocation_id,
+                request_id,
+                ..
+            }) => debug_if_leader!(
+                is_leader,
+                restate.invocation.id = %invocation_id,
+                "Effect: Send response to ingress request {} attached to the invocation: Failure(code: {}, msg: {})",
+                request_id,
+                error_code,
+                error_msg,
+            ),
             Effect::DeleteInboxEntry {
                 service_id,
                 sequence_number,
@@ -318,6 +361,46 @@ impl Effect {
                     "Effect: Drop journal and pop from inbox"
                 );
             }
+            Effect::StoreCompletedInvocation { invocation_id, .. } => {
+                debug_if_leader!(
+                    is_leader,
+                    restate.invocation.id = %invocation_id,
+                    "Effect: Store completed invocation"
+                );
+            }
+            Effect::FreeInvocation(invocation_id) => {
+                debug_if_leader!(
+                    is_leader,
+                    restate.invocation.id = %invocation_id,
+                    "Effect: Free invocation"
+                );
+            }
+            Effect::AttachResponseSink {
+                invocation_id,
+                response_sink,
+                ..
+            } => {
+                debug_if_leader!(
+                    is_leader,
+                    restate.invocation.id = %invocation_id,
+                    "Effect: Attach response sink of ingress request {} on node {}",
+                    response_sink.request_id,
+                    response_sink.node_id
+                );
+            }
+            Effect::AttachResponseSinkToInboxedInvocation {
+                inbox_entry,
+                response_sink,
+            } => {
+                debug_if_leader!(
+                    is_leader,
+                    restate.invocation.id = %inbox_entry.invocation.fid,
+                    restate.inbox.seq = inbox_entry.inbox_sequence_number,
+                    "Effect: Attach response sink of ingress request {} on node {} to inboxed invocation",
+                    response_sink.request_id,
+                    response_sink.node_id
+                );
+            }
             Effect::SetState {
                 service_id,
                 invocation_id,
@@ -419,6 +502,15 @@ impl Effect {
                         "Effect: Register background invoke timer"
                     )
                 }
+                Timer::CleanInvocationStatus(_) => {
+                    debug_if_leader!(
+                        is_leader,
+                        restate.invocation.id = %timer_value.invocation_id(),
+                        restate.timer.key = %TimerKeyDisplay(timer_value.key()),
+                        restate.timer.wake_up_time = %timer_value.wake_up_time(),
+                        "Effect: Register cleanup invocation status timer"
+                    )
+                }
             },
             Effect::DeleteTimer(timer_key) => {
                 let timer_key_display = TimerKeyDisplay(timer_key);
@@ -639,6 +731,39 @@ impl Effects {
         self.effects.push(Effect::IngressResponse(ingress_response));
     }
 
+    pub(crate) fn send_ingress_attach_response(
+        &mut self,
+        ingress_attach_response: IngressAttachResponse,
+    ) {
+        self.effects
+            .push(Effect::IngressAttachResponse(ingress_attach_response));
+    }
+
+    pub(crate) fn attach_response_sink(
+        &mut self,
+        invocation_id: InvocationId,
+        previous_invocation_status: InvocationStatus,
+        response_sink: AttachedResponseSink,
+    ) {
+        self.effects.push(Effect::AttachResponseSink {
+            invocation_id,
+            previous_invocation_status,
+            response_sink,
+        });
+    }
+
+    pub(crate) fn attach_response_sink_to_inboxed_invocation(
+        &mut self,
+        inbox_entry: SequenceNumberInvocation,
+        response_sink: AttachedResponseSink,
+    ) {
+        self.effects
+            .push(Effect::AttachResponseSinkToInboxedInvocation {
+                inbox_entry,
+                response_sink,
+            });
+    }
+
     pub(crate) fn set_state(
         &mut self,
         service_id: ServiceId,
@@ -761,6 +886,21 @@ impl Effects {
         });
     }
 
+    pub(crate) fn store_completed_invocation(
+        &mut self,
+        invocation_id: InvocationId,
+        completed_invocation: CompletedInvocation,
+    ) {
+        self.effects.push(Effect::StoreCompletedInvocation {
+            invocation_id,
+            completed_invocation,
+        });
+    }
+
+    pub(crate) fn free_invocation(&mut self, invocation_id: InvocationId) {
+        self.effects.push(Effect::FreeInvocation(invocation_id));
+    }
+
     pub(crate) fn trace_background_invoke(
         &mut self,
         full_invocation_id: FullInvocationId,
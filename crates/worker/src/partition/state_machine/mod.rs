@@ -27,6 +27,7 @@ pub use effect_interpreter::StateStorage;
 pub use effects::Effects;
 use restate_types::identifiers::PartitionKey;
 use restate_types::journal::raw::{RawEntryCodec, RawEntryCodecError};
+use restate_types::time::MillisSinceEpoch;
 use restate_wal_protocol::Command;
 
 #[derive(Debug)]
@@ -44,7 +45,7 @@ impl<Codec> StateMachine<Codec> {
     pub fn new(
         inbox_seq_number: MessageIndex,
         outbox_seq_number: MessageIndex,
-        partition_key_range: RangeInclusive<PartitionKey>,
+        partition_key_range: Option<RangeInclusive<PartitionKey>>,
     ) -> Self {
         Self(CommandInterpreter::new(
             inbox_seq_number,
@@ -58,6 +59,7 @@ impl<Codec: RawEntryCodec> StateMachine<Codec> {
     pub async fn apply<TransactionType: restate_storage_api::Transaction + Send>(
         &mut self,
         command: Command,
+        created_at: Option<MillisSinceEpoch>,
         effects: &mut Effects,
         transaction: &mut Transaction<TransactionType>,
         action_collector: &mut ActionCollector,
@@ -65,7 +67,10 @@ impl<Codec: RawEntryCodec> StateMachine<Codec> {
     ) -> Result<(), Error> {
         // Handle the command, returns the span_relation to use to log effects
         let command_type = command.name();
-        let (fid, span_relation) = self.0.on_apply(command, effects, transaction).await?;
+        let (fid, span_relation) = self
+            .0
+            .on_apply(command, created_at, effects, transaction)
+            .await?;
         counter!(PARTITION_APPLY_COMMAND, "command" => command_type).increment(1);
 
         // Log the effects
@@ -95,7 +100,7 @@ mod tests {
     use restate_service_protocol::codec::ProtobufRawEntryCodec;
     use restate_storage_api::inbox_table::InboxTable;
     use restate_storage_api::invocation_status_table::{
-        InvocationMetadata, InvocationStatus, ReadOnlyInvocationStatusTable,
+        CompletedInvocation, InvocationMetadata, InvocationStatus, ReadOnlyInvocationStatusTable,
     };
     use restate_storage_api::journal_table::{JournalEntry, ReadOnlyJournalTable};
     use restate_storage_api::outbox_table::OutboxTable;
@@ -103,19 +108,23 @@ mod tests {
     use restate_storage_api::Transaction;
     use restate_storage_rocksdb::RocksDBStorage;
     use restate_test_util::matchers::*;
-    use restate_types::errors::codes;
+    use restate_types::errors::{codes, NOT_READY_INVOCATION_ERROR};
     use restate_types::identifiers::{
         FullInvocationId, InvocationId, PartitionId, PartitionKey, ServiceId,
     };
+    use restate_types::ingress::IngressAttachResponse;
     use restate_types::invocation::{
-        InvocationResponse, InvocationTermination, MaybeFullInvocationId, ResponseResult,
-        ServiceInvocation, ServiceInvocationResponseSink, Source,
+        AttachInvocationRequest, AttachedResponseSink, InvocationResponse, InvocationTermination,
+        MaybeFullInvocationId, PurgeInvocationRequest, ResponseResult, ServiceInvocation,
+        ServiceInvocationResponseSink, Source,
     };
     use restate_types::journal::enriched::EnrichedRawEntry;
     use restate_types::journal::{Completion, CompletionResult};
-    use restate_types::journal::{Entry, EntryType};
+    use restate_types::journal::{Entry, EntryResult, EntryType};
     use restate_types::state_mut::ExternalStateMutation;
+    use restate_types::GenerationalNodeId;
     use std::collections::{HashMap, HashSet};
+    use std::time::Duration;
     use tempfile::tempdir;
     use test_log::test;
     use tracing::info;
@@ -159,7 +168,7 @@ mod tests {
                 state_machine: StateMachine::new(
                     inbox_seq_number,
                     outbox_seq_number,
-                    PartitionKey::MIN..=PartitionKey::MAX,
+                    Some(PartitionKey::MIN..=PartitionKey::MAX),
                 ),
                 rocksdb_storage,
                 effects_buffer: Default::default(),
@@ -169,16 +178,25 @@ mod tests {
         }
 
         pub async fn apply(&mut self, command: Command) -> Vec<Action> {
+            self.apply_at(command, MillisSinceEpoch::now()).await
+        }
+
+        pub async fn apply_at(
+            &mut self,
+            command: Command,
+            created_at: MillisSinceEpoch,
+        ) -> Vec<Action> {
             let partition_id = self.partition_id();
             let mut transaction = crate::partition::storage::Transaction::new(
                 partition_id,
-                0..=PartitionKey::MAX,
+                Some(0..=PartitionKey::MAX),
                 self.rocksdb_storage.transaction(),
             );
             let mut action_collector = ActionCollector::default();
             self.state_machine
                 .apply(
                     command,
+                    Some(created_at),
                     &mut self.effects_buffer,
                     &mut transaction,
                     &mut action_collector,
@@ -541,6 +559,310 @@ mod tests {
         state_machine.shutdown().await
     }
 
+    #[test(tokio::test)]
+    async fn retain_completed_invocation() -> TestResult {
+        let mut state_machine = MockStateMachine::default();
+        let fid = FullInvocationId::generate(ServiceId::new("MySvc", "my-key"));
+        let invocation_id = InvocationId::from(&fid);
+
+        let _ = state_machine
+            .apply(Command::Invoke(ServiceInvocation {
+                fid: fid.clone(),
+                completion_retention_time: Duration::from_secs(60),
+                ..ServiceInvocation::mock()
+            }))
+            .await;
+        let _ = state_machine
+            .apply(Command::InvokerEffect(InvokerEffect {
+                full_invocation_id: fid.clone(),
+                kind: InvokerEffectKind::JournalEntry {
+                    entry_index: 1,
+                    entry: ProtobufRawEntryCodec::serialize_enriched(Entry::output(
+                        EntryResult::Success(Bytes::from_static(b"output")),
+                    )),
+                },
+            }))
+            .await;
+        let completed_at = MillisSinceEpoch::new(1_700_000_000_000);
+        let actions = state_machine
+            .apply_at(
+                Command::InvokerEffect(InvokerEffect {
+                    full_invocation_id: fid.clone(),
+                    kind: InvokerEffectKind::End,
+                }),
+                completed_at,
+            )
+            .await;
+
+        let invocation_status = state_machine
+            .storage()
+            .transaction()
+            .get_invocation_status(&invocation_id)
+            .await?;
+        assert_that!(
+            invocation_status,
+            pat!(InvocationStatus::Completed(pat!(CompletedInvocation {
+                service_id: eq(fid.service_id.clone()),
+                response_result: eq(ResponseResult::Success(Bytes::from_static(b"output")))
+            })))
+        );
+
+        // the cleanup timer frees the invocation once the retention time expired
+        let timer_value = actions
+            .into_iter()
+            .find_map(|action| match action {
+                Action::RegisterTimer { timer_value } => Some(timer_value),
+                _ => None,
+            })
+            .expect("cleanup timer should be registered");
+        // the expiration time derives from the command which ended the invocation
+        assert_eq!(
+            timer_value.wake_up_time(),
+            MillisSinceEpoch::new(1_700_000_060_000)
+        );
+        let _ = state_machine.apply(Command::Timer(timer_value)).await;
+
+        let invocation_status = state_machine
+            .storage()
+            .transaction()
+            .get_invocation_status(&invocation_id)
+            .await?;
+        assert_eq!(invocation_status, InvocationStatus::Free);
+
+        state_machine.shutdown().await
+    }
+
+    #[test(tokio::test)]
+    async fn purge_completed_invocation() -> TestResult {
+        let mut state_machine = MockStateMachine::default();
+        let fid = FullInvocationId::generate(ServiceId::new("MySvc", "my-key"));
+        let invocation_id = InvocationId::from(&fid);
+
+        let _ = state_machine
+            .apply(Command::Invoke(ServiceInvocation {
+                fid: fid.clone(),
+                completion_retention_time: Duration::from_secs(60),
+                ..ServiceInvocation::mock()
+            }))
+            .await;
+        let _ = state_machine
+            .apply(Command::InvokerEffect(InvokerEffect {
+                full_invocation_id: fid.clone(),
+                kind: InvokerEffectKind::End,
+            }))
+            .await;
+
+        let invocation_status = state_machine
+            .storage()
+            .transaction()
+            .get_invocation_status(&invocation_id)
+            .await?;
+        restate_test_util::let_assert!(InvocationStatus::Completed(_) = invocation_status);
+
+        let _ = state_machine
+            .apply(Command::PurgeInvocation(PurgeInvocationRequest {
+                invocation_id: invocation_id.clone(),
+            }))
+            .await;
+
+        let invocation_status = state_machine
+            .storage()
+            .transaction()
+            .get_invocation_status(&invocation_id)
+            .await?;
+        assert_eq!(invocation_status, InvocationStatus::Free);
+
+        state_machine.shutdown().await
+    }
+
+    #[test(tokio::test)]
+    async fn attach_invocation() -> TestResult {
+        let mut state_machine = MockStateMachine::default();
+        let fid = FullInvocationId::generate(ServiceId::new("MySvc", "my-key"));
+        let invocation_id = InvocationId::from(&fid);
+        let node_id = GenerationalNodeId::new(1, 1);
+
+        let attach = |request_id, block_on_inflight| {
+            Command::AttachInvocation(AttachInvocationRequest {
+                invocation_id: invocation_id.clone(),
+                block_on_inflight,
+                response_sink: AttachedResponseSink {
+                    node_id,
+                    request_id,
+                },
+            })
+        };
+        let attach_responses = |actions: Vec<Action>| {
+            actions
+                .into_iter()
+                .filter_map(|action| match action {
+                    Action::IngressAttachResponse(IngressAttachResponse {
+                        request_id,
+                        response,
+                        ..
+                    }) => Some((request_id, response)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let _ = state_machine
+            .apply(Command::Invoke(ServiceInvocation {
+                fid: fid.clone(),
+                completion_retention_time: Duration::from_secs(60),
+                ..ServiceInvocation::mock()
+            }))
+            .await;
+
+        // blocking attach waits for the in-flight invocation, non-blocking returns not ready
+        let actions = state_machine.apply(attach(1, true)).await;
+        assert_eq!(attach_responses(actions), vec![]);
+        let actions = state_machine.apply(attach(2, false)).await;
+        assert_eq!(
+            attach_responses(actions),
+            vec![(2, ResponseResult::from(NOT_READY_INVOCATION_ERROR))]
+        );
+
+        let invocation_status = state_machine
+            .storage()
+            .transaction()
+            .get_invocation_status(&invocation_id)
+            .await?;
+        restate_test_util::let_assert!(InvocationStatus::Invoked(metadata) = invocation_status);
+        assert_eq!(
+            metadata.attached_response_sinks,
+            vec![AttachedResponseSink {
+                node_id,
+                request_id: 1
+            }]
+        );
+
+        // the output is sent to the attached request
+        let actions = state_machine
+            .apply(Command::InvokerEffect(InvokerEffect {
+                full_invocation_id: fid.clone(),
+                kind: InvokerEffectKind::JournalEntry {
+                    entry_index: 1,
+                    entry: ProtobufRawEntryCodec::serialize_enriched(Entry::output(
+                        EntryResult::Success(Bytes::from_static(b"output")),
+                    )),
+                },
+            }))
+            .await;
+        assert_eq!(
+            attach_responses(actions),
+            vec![(1, ResponseResult::Success(Bytes::from_static(b"output")))]
+        );
+        let _ = state_machine
+            .apply(Command::InvokerEffect(InvokerEffect {
+                full_invocation_id: fid.clone(),
+                kind: InvokerEffectKind::End,
+            }))
+            .await;
+
+        // the completed invocation returns its output right away
+        let actions = state_machine.apply(attach(3, false)).await;
+        assert_eq!(
+            attach_responses(actions),
+            vec![(3, ResponseResult::Success(Bytes::from_static(b"output")))]
+        );
+
+        state_machine.shutdown().await
+    }
+
+    #[test(tokio::test)]
+    async fn attach_inboxed_invocation() -> TestResult {
+        let mut state_machine = MockStateMachine::default();
+        let service_id = ServiceId::new("MySvc", "my-key");
+        let first_fid = FullInvocationId::generate(service_id.clone());
+        let inboxed_fid = FullInvocationId::generate(service_id);
+        let inboxed_invocation_id = InvocationId::from(&inboxed_fid);
+        let node_id = GenerationalNodeId::new(1, 1);
+
+        let attach = |request_id, block_on_inflight| {
+            Command::AttachInvocation(AttachInvocationRequest {
+                invocation_id: inboxed_invocation_id.clone(),
+                block_on_inflight,
+                response_sink: AttachedResponseSink {
+                    node_id,
+                    request_id,
+                },
+            })
+        };
+        let attach_responses = |actions: Vec<Action>| {
+            actions
+                .into_iter()
+                .filter_map(|action| match action {
+                    Action::IngressAttachResponse(IngressAttachResponse {
+                        request_id,
+                        response,
+                        ..
+                    }) => Some((request_id, response)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // the second invocation waits in the inbox until the first one ended
+        for fid in [&first_fid, &inboxed_fid] {
+            let _ = state_machine
+                .apply(Command::Invoke(ServiceInvocation {
+                    fid: fid.clone(),
+                    ..ServiceInvocation::mock()
+                }))
+                .await;
+        }
+
+        // blocking attach waits for the inboxed invocation, non-blocking returns not ready
+        let actions = state_machine.apply(attach(1, true)).await;
+        assert_eq!(attach_responses(actions), vec![]);
+        let actions = state_machine.apply(attach(2, false)).await;
+        assert_eq!(
+            attach_responses(actions),
+            vec![(2, ResponseResult::from(NOT_READY_INVOCATION_ERROR))]
+        );
+
+        let _ = state_machine
+            .apply(Command::InvokerEffect(InvokerEffect {
+                full_invocation_id: first_fid.clone(),
+                kind: InvokerEffectKind::End,
+            }))
+            .await;
+
+        // the attached request is moved to the invocation status once it is invoked
+        let invocation_status = state_machine
+            .storage()
+            .transaction()
+            .get_invocation_status(&inboxed_invocation_id)
+            .await?;
+        restate_test_util::let_assert!(InvocationStatus::Invoked(metadata) = invocation_status);
+        assert_eq!(
+            metadata.attached_response_sinks,
+            vec![AttachedResponseSink {
+                node_id,
+                request_id: 1
+            }]
+        );
+
+        let actions = state_machine
+            .apply(Command::InvokerEffect(InvokerEffect {
+                full_invocation_id: inboxed_fid.clone(),
+                kind: InvokerEffectKind::JournalEntry {
+                    entry_index: 1,
+                    entry: ProtobufRawEntryCodec::serialize_enriched(Entry::output(
+                        EntryResult::Success(Bytes::from_static(b"output")),
+                    )),
+                },
+            }))
+            .await;
+        assert_eq!(
+            attach_responses(actions),
+            vec![(1, ResponseResult::Success(Bytes::from_static(b"output")))]
+        );
+
+        state_machine.shutdown().await
+    }
+
     async fn mock_start_invocation_with_service_id(
         state_machine: &mut MockStateMachine,
         service_id: ServiceId,
@@ -557,6 +879,8 @@ mod tests {
                 span_context: Default::default(),
                 headers: vec![],
                 execution_time: None,
+                completion_retention_time: Duration::ZERO,
+                attached_response_sinks: vec![],
             }))
             .await;
 
@@ -13,8 +13,8 @@ use crate::partition::shuffle::{OutboxReader, OutboxReaderError};
 use bytes::{Buf, Bytes};
 use futures::{Stream, StreamExt, TryStreamExt};
 use metrics::counter;
-use restate_storage_api::deduplication_table::ReadOnlyDeduplicationTable;
-use restate_storage_api::fsm_table::ReadOnlyFsmTable;
+use restate_storage_api::deduplication_table::{DeduplicationTable, ReadOnlyDeduplicationTable};
+use restate_storage_api::fsm_table::{FsmTable, ReadOnlyFsmTable};
 use restate_storage_api::inbox_table::{
     InboxEntry, SequenceNumberInboxEntry, SequenceNumberInvocation,
 };
@@ -30,8 +30,9 @@ use restate_storage_api::state_table::ReadOnlyStateTable;
 use restate_storage_api::timer_table::{Timer, TimerKey, TimerTable};
 use restate_storage_api::Result as StorageResult;
 use restate_storage_api::StorageError;
+use restate_storage_rocksdb::{RocksDBStorage, RocksDBTransaction, TableKind};
 use restate_timer::TimerReader;
-use restate_types::dedup::{DedupSequenceNumber, ProducerId};
+use restate_types::dedup::{DedupInformation, DedupSequenceNumber, ProducerId};
 use restate_types::identifiers::{
     EntryIndex, FullInvocationId, InvocationId, PartitionId, PartitionKey, ServiceId,
     WithPartitionKey,
@@ -41,23 +42,107 @@ use restate_types::journal::enriched::EnrichedRawEntry;
 use restate_types::journal::CompletionResult;
 use restate_types::logs::Lsn;
 use restate_types::message::MessageIndex;
+use restate_wal_protocol::control::{HandOverKeyRange, KeyRangeData, KeyRangeTable};
 use restate_wal_protocol::timer::{TimerKeyWrapper, TimerValue};
 use std::future::Future;
 use std::ops::RangeInclusive;
 
 pub mod invoker;
 
+/// The keys which a partition processor owns. Partitions start with the key range which the
+/// partition table assigns to them initially. Splits and merges hand over the keys to other
+/// partitions, which take them over together with the partition scoped data of the keys.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct KeyRangeOwnership {
+    owned: Option<RangeInclusive<PartitionKey>>,
+    handed_over: Vec<HandOverKeyRange>,
+}
+
+impl KeyRangeOwnership {
+    pub fn new(owned: Option<RangeInclusive<PartitionKey>>) -> Self {
+        Self {
+            owned,
+            handed_over: Vec::default(),
+        }
+    }
+
+    pub fn owned(&self) -> Option<&RangeInclusive<PartitionKey>> {
+        self.owned.as_ref()
+    }
+
+    pub fn owns(&self, partition_key: PartitionKey) -> bool {
+        self.owned
+            .as_ref()
+            .is_some_and(|owned| owned.contains(&partition_key))
+    }
+
+    pub fn handed_over(&self) -> &[HandOverKeyRange] {
+        &self.handed_over
+    }
+
+    /// Returns the hand over of the given partition key if it has been handed over.
+    pub fn hand_over_of(&self, partition_key: PartitionKey) -> Option<&HandOverKeyRange> {
+        self.handed_over
+            .iter()
+            .find(|hand_over| hand_over.key_range.contains(&partition_key))
+    }
+
+    pub fn has_handed_over(
+        &self,
+        to: PartitionId,
+        key_range: &RangeInclusive<PartitionKey>,
+    ) -> bool {
+        self.handed_over
+            .iter()
+            .any(|hand_over| hand_over.to == to && hand_over.key_range == *key_range)
+    }
+
+    /// Hands over the keys at the end of the owned key range. Returns `false` if the keys are not
+    /// owned, which is the case for repeated hand overs.
+    pub fn hand_over(&mut self, hand_over: HandOverKeyRange) -> bool {
+        let Some(owned) = &self.owned else {
+            return false;
+        };
+        if hand_over.key_range.end() != owned.end() || hand_over.key_range.start() < owned.start() {
+            return false;
+        }
+
+        self.owned = (hand_over.key_range.start() > owned.start())
+            .then(|| *owned.start()..=hand_over.key_range.start() - 1);
+        self.handed_over.push(hand_over);
+        true
+    }
+
+    /// Takes over keys which are adjacent to the owned key range. Returns `false` if the keys are
+    /// not adjacent, which is the case for repeated take overs.
+    pub fn take_over(&mut self, key_range: RangeInclusive<PartitionKey>) -> bool {
+        let owned = match &self.owned {
+            None => key_range,
+            Some(owned) if owned.end().checked_add(1) == Some(*key_range.start()) => {
+                *owned.start()..=*key_range.end()
+            }
+            Some(owned) if key_range.end().checked_add(1) == Some(*owned.start()) => {
+                *key_range.start()..=*owned.end()
+            }
+            Some(_) => return false,
+        };
+
+        self.owned = Some(owned);
+        true
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct PartitionStorage<Storage> {
     partition_id: PartitionId,
-    partition_key_range: RangeInclusive<PartitionKey>,
+    partition_key_range: Option<RangeInclusive<PartitionKey>>,
     storage: Storage,
 }
 
 impl<Storage> PartitionStorage<Storage> {
     pub(super) fn new(
         partition_id: PartitionId,
-        partition_key_range: RangeInclusive<PartitionKey>,
+        partition_key_range: Option<RangeInclusive<PartitionKey>>,
         storage: Storage,
     ) -> Self {
         Self {
@@ -67,6 +152,13 @@ impl<Storage> PartitionStorage<Storage> {
         }
     }
 
+    pub(super) fn set_partition_key_range(
+        &mut self,
+        partition_key_range: Option<RangeInclusive<PartitionKey>>,
+    ) {
+        self.partition_key_range = partition_key_range;
+    }
+
     pub fn assert_partition_key(&self, partition_key: &impl WithPartitionKey) {
         assert_partition_key(&self.partition_key_range, partition_key);
     }
@@ -86,6 +178,38 @@ where
     }
 }
 
+async fn load_key_range_ownership<F: ReadOnlyFsmTable + Send>(
+    storage: &mut F,
+    partition_id: PartitionId,
+) -> Result<Option<KeyRangeOwnership>, StorageError> {
+    let bytes = storage
+        .get(partition_id, fsm_variable::KEY_RANGE_OWNERSHIP)
+        .await?;
+
+    bytes
+        .map(|bytes| {
+            bincode::serde::decode_from_slice(&bytes, bincode::config::standard())
+                .map(|(ownership, _)| ownership)
+                .map_err(|err| StorageError::Generic(err.into()))
+        })
+        .transpose()
+}
+
+async fn store_key_range_ownership<F: FsmTable + Send>(
+    storage: &mut F,
+    partition_id: PartitionId,
+    ownership: &KeyRangeOwnership,
+) -> Result<(), StorageError> {
+    let bytes = bincode::serde::encode_to_vec(ownership, bincode::config::standard())
+        .map_err(|err| StorageError::Generic(err.into()))?;
+
+    storage
+        .put(partition_id, fsm_variable::KEY_RANGE_OWNERSHIP, bytes)
+        .await;
+
+    Ok(())
+}
+
 async fn load_seq_number<F: ReadOnlyFsmTable + Send>(
     storage: &mut F,
     partition_id: PartitionId,
@@ -146,11 +270,21 @@ where
         })
     }
 
+    /// Loads the key range ownership of the given partition. Partitions which hand over keys
+    /// store the hand over in their ownership, which lets the partition which takes over the keys
+    /// wait for it.
+    pub async fn load_key_range_ownership(
+        &mut self,
+        partition_id: PartitionId,
+    ) -> StorageResult<Option<KeyRangeOwnership>> {
+        load_key_range_ownership(&mut self.storage, partition_id).await
+    }
+
     pub fn scan_invoked_invocations(
         &mut self,
+        partition_key_range: RangeInclusive<PartitionKey>,
     ) -> impl Stream<Item = Result<FullInvocationId, StorageError>> + Send + '_ {
-        self.storage
-            .invoked_invocations(self.partition_key_range.clone())
+        self.storage.invoked_invocations(partition_key_range)
     }
 
     pub fn get_invocation_status<'a>(
@@ -193,19 +327,69 @@ where
 
 #[inline]
 fn assert_partition_key(
-    partition_key_range: &RangeInclusive<PartitionKey>,
+    partition_key_range: &Option<RangeInclusive<PartitionKey>>,
     partition_key: &impl WithPartitionKey,
 ) {
     let partition_key = partition_key.partition_key();
-    assert!(partition_key_range.contains(&partition_key),
+    assert!(partition_key_range.as_ref().is_some_and(|partition_key_range| partition_key_range.contains(&partition_key)),
             "Partition key '{}' is not part of PartitionStorage's partition '{:?}'. This indicates a bug.",
             partition_key,
             partition_key_range);
 }
 
+impl PartitionStorage<RocksDBStorage> {
+    /// Exports the data of keys which the partition has handed over, so that the partition which
+    /// takes over the keys can import it with [`Transaction::take_over_key_range`] on any node.
+    pub async fn export_key_range(
+        &mut self,
+        hand_over: &HandOverKeyRange,
+    ) -> StorageResult<KeyRangeData> {
+        let key_range = &hand_over.key_range;
+
+        let tables = self
+            .storage
+            .export_key_range(key_range)?
+            .into_iter()
+            .map(|(table, entries)| KeyRangeTable {
+                table: table.cf_name().to_owned(),
+                entries,
+            })
+            .collect();
+
+        let timers = self
+            .storage
+            .next_timers_greater_than(self.partition_id, None, usize::MAX)
+            .try_filter(|(_, timer)| {
+                futures::future::ready(key_range.contains(&timer.service_id().partition_key()))
+            })
+            .try_collect()
+            .await?;
+
+        let dedup_information = self
+            .storage
+            .get_all_sequence_numbers(self.partition_id)
+            .try_filter(|dedup_information| {
+                futures::future::ready(dedup_information.producer_id != ProducerId::self_producer())
+            })
+            .try_collect()
+            .await?;
+
+        let inbox_seq_number = self.load_inbox_seq_number().await?;
+
+        Ok(KeyRangeData {
+            from: self.partition_id,
+            hand_over: hand_over.clone(),
+            tables,
+            timers,
+            dedup_information,
+            inbox_seq_number,
+        })
+    }
+}
+
 pub struct Transaction<TransactionType> {
     partition_id: PartitionId,
-    partition_key_range: RangeInclusive<PartitionKey>,
+    partition_key_range: Option<RangeInclusive<PartitionKey>>,
     inner: TransactionType,
 }
 
@@ -222,7 +406,7 @@ where
 {
     pub(super) fn new(
         partition_id: PartitionId,
-        partition_key_range: RangeInclusive<PartitionKey>,
+        partition_key_range: Option<RangeInclusive<PartitionKey>>,
         inner: TransactionType,
     ) -> Self {
         Self {
@@ -272,6 +456,96 @@ where
 
         Ok(())
     }
+
+    pub async fn store_key_range_ownership(
+        &mut self,
+        ownership: &KeyRangeOwnership,
+    ) -> StorageResult<()> {
+        store_key_range_ownership(&mut self.inner, self.partition_id, ownership).await
+    }
+}
+
+impl<'a> Transaction<RocksDBTransaction<'a>> {
+    /// Takes over the data of the keys which another partition has handed over: the entries of
+    /// the keys replace the local ones, the timers of the keys are added, the deduplication
+    /// sequence numbers are merged and the inbox sequence number is advanced so that the inboxes
+    /// of the keys stay ordered. Returns the number of taken over timers.
+    ///
+    /// If the partition which handed over the keys has run on this node, its stored ownership
+    /// records the hand over as well. Otherwise, a replica of it which is started later and
+    /// replays the records preceding the hand over would modify the keys in the shared partition
+    /// store.
+    ///
+    /// The outbox stays with the partition which handed over the keys and keeps being delivered
+    /// by it, so that its messages keep their deduplication identity.
+    pub async fn take_over_key_range(&mut self, data: &KeyRangeData) -> StorageResult<usize> {
+        let key_range = &data.hand_over.key_range;
+
+        let tables = data
+            .tables
+            .iter()
+            .map(|table| {
+                TableKind::from_cf_name(&table.table)
+                    .map(|table_kind| (table_kind, table.entries.clone()))
+                    .ok_or_else(|| {
+                        StorageError::Generic(anyhow::anyhow!(
+                            "unknown table '{}' in the data of keys {key_range:?}",
+                            table.table
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.inner.import_key_range(key_range, &tables)?;
+
+        for (timer_key, timer) in &data.timers {
+            self.inner
+                .add_timer(self.partition_id, timer_key, timer.clone())
+                .await;
+        }
+
+        for DedupInformation {
+            producer_id,
+            sequence_number,
+        } in &data.dedup_information
+        {
+            let is_newer = match self
+                .inner
+                .get_dedup_sequence_number(self.partition_id, producer_id)
+                .await?
+            {
+                Some(DedupSequenceNumber::Sn(current)) => {
+                    matches!(sequence_number, DedupSequenceNumber::Sn(sn) if *sn > current)
+                }
+                Some(DedupSequenceNumber::Esn(current)) => {
+                    matches!(sequence_number, DedupSequenceNumber::Esn(esn) if *esn > current)
+                }
+                None => true,
+            };
+            if is_newer {
+                self.inner
+                    .put_dedup_seq_number(self.partition_id, producer_id.clone(), *sequence_number)
+                    .await;
+            }
+        }
+
+        let inbox_seq_number = load_seq_number(
+            &mut self.inner,
+            self.partition_id,
+            fsm_variable::INBOX_SEQ_NUMBER,
+        )
+        .await?
+        .max(data.inbox_seq_number);
+        self.store_seq_number(inbox_seq_number, fsm_variable::INBOX_SEQ_NUMBER)
+            .await?;
+
+        if let Some(mut ownership) = load_key_range_ownership(&mut self.inner, data.from).await? {
+            if ownership.hand_over(data.hand_over.clone()) {
+                store_key_range_ownership(&mut self.inner, data.from, &ownership).await?;
+            }
+        }
+
+        Ok(data.timers.len())
+    }
 }
 
 impl<TransactionType> super::state_machine::StateReader for Transaction<TransactionType>
@@ -589,6 +863,8 @@ mod fsm_variable {
     pub(crate) const OUTBOX_SEQ_NUMBER: u64 = 1;
 
     pub(crate) const APPLIED_LSN: u64 = 2;
+
+    pub(crate) const KEY_RANGE_OWNERSHIP: u64 = 3;
 }
 
 impl<Storage> OutboxReader for PartitionStorage<Storage>
@@ -636,19 +912,48 @@ where
         num_timers: usize,
         previous_timer_key: Option<TimerKeyWrapper>,
     ) -> Vec<TimerValue> {
-        self.storage
-            .next_timers_greater_than(
-                self.partition_id,
-                previous_timer_key.map(|t| t.into_inner()).as_ref(),
-                num_timers,
-            )
-            .map(|result| result.map(|(timer_key, timer)| TimerValue::new(timer_key, timer)))
-            // TODO: Update timer service to maintain transaction while reading the timer stream: See https://github.com/restatedev/restate/issues/273
-            // have to collect the stream because it depends on the local transaction
-            .try_collect::<Vec<_>>()
-            .await
-            // TODO: Extend TimerReader to return errors: See https://github.com/restatedev/restate/issues/274
-            .expect("timer deserialization should not fail")
+        let mut previous_timer_key = previous_timer_key.map(|t| t.into_inner());
+        let mut timers = Vec::with_capacity(num_timers);
+
+        loop {
+            let batch: Vec<(TimerKey, Timer)> = self
+                .storage
+                .next_timers_greater_than(
+                    self.partition_id,
+                    previous_timer_key.as_ref(),
+                    num_timers,
+                )
+                // TODO: Update timer service to maintain transaction while reading the timer stream: See https://github.com/restatedev/restate/issues/273
+                // have to collect the stream because it depends on the local transaction
+                .try_collect::<Vec<_>>()
+                .await
+                // TODO: Extend TimerReader to return errors: See https://github.com/restatedev/restate/issues/274
+                .expect("timer deserialization should not fail");
+            let is_last_batch = batch.len() < num_timers;
+
+            if let Some((timer_key, _)) = batch.last() {
+                previous_timer_key = Some(timer_key.clone());
+            }
+            // the timers of keys which have been handed over stay in the partition store to be
+            // exported again by new leaders, they fire at the partition which took over the keys
+            timers.extend(
+                batch
+                    .into_iter()
+                    .filter(|(_, timer)| {
+                        self.partition_key_range.as_ref().is_some_and(|key_range| {
+                            key_range.contains(&timer.service_id().partition_key())
+                        })
+                    })
+                    .map(|(timer_key, timer)| TimerValue::new(timer_key, timer)),
+            );
+
+            if is_last_batch || timers.len() >= num_timers {
+                break;
+            }
+        }
+
+        timers.truncate(num_timers);
+        timers
     }
 }
 
@@ -0,0 +1,265 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::Duration;
+
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use restate_bifrost::Bifrost;
+use restate_core::network::{MessageRouterBuilder, NetworkSender};
+use restate_core::{cancellation_watcher, metadata, task_center, TaskId, TaskKind};
+use restate_invoker_impl::ChannelServiceHandle as InvokerChannelServiceHandle;
+use restate_network::Networking;
+use restate_node_protocol::metadata::MetadataKind;
+use restate_node_protocol::partition_processor_manager::{
+    ControlProcessors, GetPartitionProcessorsStatus, PartitionProcessorStatus,
+    PartitionProcessorsStatus, ProcessorCommand,
+};
+use restate_node_protocol::MessageEnvelope;
+use restate_storage_rocksdb::RocksDBStorage;
+use restate_types::identifiers::PartitionId;
+use restate_types::logs::Lsn;
+use restate_types::{GenerationalNodeId, Version};
+use tokio::sync::watch;
+use tracing::{debug, info, trace};
+
+use crate::partition::snapshots::SnapshotSettings;
+use crate::PartitionProcessor;
+
+/// Starts and stops the partition processors of this node as instructed by the cluster
+/// controller and reports their status to it.
+pub struct PartitionProcessorManager {
+    timers: restate_timer::Options,
+    channel_size: usize,
+    log_trim_interval: Option<Duration>,
+    snapshot_settings: SnapshotSettings,
+    networking: Networking,
+    bifrost: Bifrost,
+    invoker_handle: InvokerChannelServiceHandle,
+    rocksdb_storage: RocksDBStorage,
+    incoming_control_messages: BoxStream<'static, MessageEnvelope<ControlProcessors>>,
+    incoming_status_requests: BoxStream<'static, MessageEnvelope<GetPartitionProcessorsStatus>>,
+    running_partition_processors: HashMap<PartitionId, RunningPartitionProcessor>,
+    num_running_partition_processors: watch::Sender<usize>,
+    running_partitions: watch::Sender<BTreeSet<PartitionId>>,
+    /// The lowest lsn which all replicas of a partition have applied, as last reported by the
+    /// cluster controller.
+    replicas_applied_lsns: watch::Sender<BTreeMap<PartitionId, Lsn>>,
+    latest_placement_version: Version,
+}
+
+impl PartitionProcessorManager {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        timers: restate_timer::Options,
+        channel_size: usize,
+        log_trim_interval: Option<Duration>,
+        snapshot_settings: SnapshotSettings,
+        networking: Networking,
+        bifrost: Bifrost,
+        invoker_handle: InvokerChannelServiceHandle,
+        rocksdb_storage: RocksDBStorage,
+        router_builder: &mut MessageRouterBuilder,
+    ) -> Self {
+        let incoming_control_messages = router_builder.subscribe_to_stream(channel_size);
+        let incoming_status_requests = router_builder.subscribe_to_stream(channel_size);
+
+        Self {
+            timers,
+            channel_size,
+            log_trim_interval,
+            snapshot_settings,
+            networking,
+            bifrost,
+            invoker_handle,
+            rocksdb_storage,
+            incoming_control_messages,
+            incoming_status_requests,
+            running_partition_processors: HashMap::default(),
+            num_running_partition_processors: watch::channel(0).0,
+            running_partitions: watch::channel(BTreeSet::default()).0,
+            replicas_applied_lsns: watch::channel(BTreeMap::default()).0,
+            latest_placement_version: Version::INVALID,
+        }
+    }
+
+    /// Watches the number of partition processors which are running on this node.
+    pub fn watch_running_partition_processors(&self) -> watch::Receiver<usize> {
+        self.num_running_partition_processors.subscribe()
+    }
+
+    pub async fn run(mut self) -> anyhow::Result<()> {
+        let mut shutdown = std::pin::pin!(cancellation_watcher());
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => {
+                    debug!("Stopping partition processor manager");
+                    break;
+                }
+                Some(control_processors) = self.incoming_control_messages.next() => {
+                    let (from, control_processors) = control_processors.split();
+                    trace!(
+                        "Received control processors message from {from}: {control_processors:?}"
+                    );
+                    self.on_control_processors(control_processors).await?;
+                }
+                Some(status_request) = self.incoming_status_requests.next() => {
+                    let (from, status_request) = status_request.split();
+                    self.replicas_applied_lsns.send_replace(status_request.replicas_applied_lsns);
+                    self.on_get_status(from)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn on_control_processors(
+        &mut self,
+        control_processors: ControlProcessors,
+    ) -> anyhow::Result<()> {
+        if control_processors.placement_version < self.latest_placement_version {
+            debug!(
+                "Ignoring control processors message of placement {} because placement {} has already been applied",
+                control_processors.placement_version, self.latest_placement_version
+            );
+            return Ok(());
+        }
+
+        self.latest_placement_version = control_processors.placement_version;
+
+        // partitions which have been created by splits or merges need to be known before their
+        // processors can start
+        metadata()
+            .wait_for_version(
+                MetadataKind::PartitionTable,
+                control_processors.partition_table_version,
+            )
+            .await?;
+
+        for control_processor in control_processors.commands {
+            match control_processor.command {
+                ProcessorCommand::Start => {
+                    self.start_partition_processor(control_processor.partition_id)?
+                }
+                ProcessorCommand::Stop => {
+                    self.stop_partition_processor(control_processor.partition_id)
+                        .await
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_get_status(&self, from: GenerationalNodeId) -> anyhow::Result<()> {
+        let status = PartitionProcessorsStatus {
+            processors: self
+                .running_partition_processors
+                .values()
+                .map(|processor| processor.status.borrow().clone())
+                .collect(),
+        };
+
+        task_center().spawn_child(
+            TaskKind::Disposable,
+            "send-partition-processors-status",
+            None,
+            {
+                let networking = self.networking.clone();
+                async move {
+                    networking.send(from.into(), &status).await?;
+                    Ok(())
+                }
+            },
+        )?;
+        Ok(())
+    }
+
+    fn start_partition_processor(&mut self, partition_id: PartitionId) -> anyhow::Result<()> {
+        if self
+            .running_partition_processors
+            .contains_key(&partition_id)
+        {
+            return Ok(());
+        }
+
+        // the processor loads its current key range from the partition store if it has taken over
+        // or handed over keys already
+        let initial_key_range = metadata().partition_table().initial_key_range(partition_id);
+
+        info!("Starting partition processor for partition {partition_id}");
+
+        let (status_tx, status_rx) = watch::channel(PartitionProcessorStatus::new(partition_id));
+
+        let processor = PartitionProcessor::new(
+            partition_id,
+            initial_key_range,
+            self.timers.clone(),
+            self.channel_size,
+            self.log_trim_interval,
+            self.snapshot_settings.clone(),
+            self.invoker_handle.clone(),
+            self.rocksdb_storage.clone(),
+            status_tx,
+            self.running_partitions.subscribe(),
+            self.replicas_applied_lsns.subscribe(),
+        );
+        // processors start as followers, the cluster controller announces the leader of the
+        // partition via its log
+        let task_id = task_center().spawn_child(
+            TaskKind::PartitionProcessor,
+            "partition-processor",
+            Some(processor.partition_id),
+            processor.run(self.networking.clone(), self.bifrost.clone()),
+        )?;
+
+        self.running_partition_processors.insert(
+            partition_id,
+            RunningPartitionProcessor {
+                task_id,
+                status: status_rx,
+            },
+        );
+        self.num_running_partition_processors
+            .send_replace(self.running_partition_processors.len());
+        self.running_partitions.send_modify(|running_partitions| {
+            running_partitions.insert(partition_id);
+        });
+        Ok(())
+    }
+
+    async fn stop_partition_processor(&mut self, partition_id: PartitionId) {
+        let Some(RunningPartitionProcessor { task_id, .. }) =
+            self.running_partition_processors.remove(&partition_id)
+        else {
+            return;
+        };
+
+        info!("Stopping partition processor for partition {partition_id}");
+
+        if let Some(handle) = task_center().cancel_task(task_id) {
+            // wait for the partition processor to release the partition before it is restarted
+            let _ = handle.await;
+        }
+        self.num_running_partition_processors
+            .send_replace(self.running_partition_processors.len());
+        self.running_partitions.send_modify(|running_partitions| {
+            running_partitions.remove(&partition_id);
+        });
+    }
+}
+
+struct RunningPartitionProcessor {
+    task_id: TaskId,
+    status: watch::Receiver<PartitionProcessorStatus>,
+}
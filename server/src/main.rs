@@ -64,11 +64,9 @@ struct RestateArguments {
 enum WipeMode {
     /// Wipe all worker state, including all the service instances and their state, all enqueued invocations, all waiting timers.
     Worker,
-    /// Wipe all the meta information, including discovered services and their respective schemas.
-    Meta,
     /// Wipe the local rocksdb-based loglet.
     LocalLoglet,
-    /// Wipe the local rocksdb-based metadata-store.
+    /// Wipe the local rocksdb-based metadata-store, including discovered services and their respective schemas.
     LocalMetadataStore,
     /// Wipe all
     All,
@@ -77,23 +75,18 @@ enum WipeMode {
 impl WipeMode {
     async fn wipe(
         mode: Option<&WipeMode>,
-        meta_storage_dir: PathBuf,
         worker_storage_dir: PathBuf,
         local_loglet_storage_dir: &Path,
         local_metadata_store_storage_dir: &Path,
     ) -> io::Result<()> {
-        let (wipe_meta, wipe_worker, wipe_local_loglet, wipe_local_metadata_store) = match mode {
-            Some(WipeMode::Worker) => (false, true, true, false),
-            Some(WipeMode::Meta) => (true, false, false, false),
-            Some(WipeMode::LocalLoglet) => (false, false, true, false),
-            Some(WipeMode::LocalMetadataStore) => (false, false, false, true),
-            Some(WipeMode::All) => (true, true, true, true),
-            None => (false, false, false, false),
+        let (wipe_worker, wipe_local_loglet, wipe_local_metadata_store) = match mode {
+            Some(WipeMode::Worker) => (true, true, false),
+            Some(WipeMode::LocalLoglet) => (false, true, false),
+            Some(WipeMode::LocalMetadataStore) => (false, false, true),
+            Some(WipeMode::All) => (true, true, true),
+            None => (false, false, false),
         };
 
-        if wipe_meta {
-            restate_fs_util::remove_dir_all_if_exists(meta_storage_dir).await?;
-        }
         if wipe_worker {
             restate_fs_util::remove_dir_all_if_exists(worker_storage_dir).await?;
         }
@@ -160,7 +153,6 @@ fn main() {
 
             WipeMode::wipe(
                 cli_args.wipe.as_ref(),
-                config.node.meta.storage_path().into(),
                 config.node.worker.storage_path().into(),
                 config.node.bifrost.local.path.as_path(),
                 config.node.metadata_store.storage_path(),
@@ -0,0 +1,217 @@
+// Copyright (c) 2024 -  Restate Software, Inc., Restate GmbH.
+// All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Prints the records of a log that is stored by the local loglet of a node, decoded as WAL
+//! envelopes. Every record is printed as a single line of JSON.
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::builder::PossibleValuesParser;
+use clap::Parser;
+use serde_json::{json, Value};
+use strum::VariantNames;
+
+use restate_bifrost::{LocalLogReader, LocalLogletOptions};
+use restate_types::identifiers::{PartitionKey, WithPartitionKey};
+use restate_types::logs::metadata::{Logs, ProviderKind};
+use restate_types::logs::{LogId, Lsn, SequenceNumber};
+use restate_types::metadata_store::keys::BIFROST_CONFIG_KEY;
+use restate_types::net::AdvertisedAddress;
+use restate_wal_protocol::{Command, Envelope, EnvelopeFormat};
+
+#[derive(Debug, clap::Parser)]
+#[command(author, version, about)]
+struct Arguments {
+    /// Path to the rocksdb directory of the local loglet, e.g. `restate-data/local_loglet`.
+    /// The store is opened read-only, it can be inspected while the node is running.
+    #[arg(long, value_name = "DIR")]
+    path: PathBuf,
+
+    /// The log to read. The log of a partition has the partition id as log id.
+    #[arg(long)]
+    log_id: u64,
+
+    /// Address of the metadata store, e.g. `http://127.0.0.1:5123`, to look up the log chain.
+    /// Without it, the static log metadata is assumed: the log consists of a single local
+    /// loglet that uses the log id as its id.
+    #[arg(long, value_name = "ADDRESS")]
+    metadata_store_address: Option<AdvertisedAddress>,
+
+    /// The LSN of the first record to print.
+    #[arg(long, default_value_t = 1)]
+    from_lsn: u64,
+
+    /// Only print records with the given command. Can be specified multiple times.
+    #[arg(
+        long = "command",
+        value_name = "COMMAND",
+        value_parser = PossibleValuesParser::new(Command::VARIANTS)
+    )]
+    commands: Vec<String>,
+
+    /// Only print records that are destined to the given partition key.
+    #[arg(long)]
+    partition_key: Option<PartitionKey>,
+
+    /// Stop after printing this many records.
+    #[arg(long)]
+    limit: Option<usize>,
+
+    /// Print the whole command of the records, not only its name.
+    #[arg(long)]
+    full: bool,
+}
+
+impl Arguments {
+    fn matches(&self, envelope: &Envelope) -> bool {
+        let command = envelope.command.name();
+        (self.commands.is_empty() || self.commands.iter().any(|c| c == command))
+            && self
+                .partition_key
+                .map_or(true, |key| key == envelope.partition_key())
+    }
+}
+
+/// A segment of the log chain that is stored by the local loglet.
+struct LocalSegment {
+    loglet_id: u64,
+    base_lsn: Lsn,
+}
+
+/// Returns the local loglet segments of the log in LSN order.
+async fn local_segments(args: &Arguments) -> anyhow::Result<Vec<LocalSegment>> {
+    let Some(address) = args.metadata_store_address.clone() else {
+        return Ok(vec![LocalSegment {
+            loglet_id: args.log_id,
+            base_lsn: Lsn::OLDEST,
+        }]);
+    };
+
+    let client = restate_metadata_store::local::create_client(address, None);
+    let logs: Logs = client
+        .get(BIFROST_CONFIG_KEY.clone())
+        .await
+        .context("cannot read the logs metadata from the metadata store")?
+        .context("the logs metadata has not been initialized")?;
+    let log_id = LogId::from(args.log_id);
+    let segments = logs
+        .segments(log_id)
+        .with_context(|| format!("unknown log {log_id}"))?;
+
+    let mut local_segments = Vec::with_capacity(segments.len());
+    for segment in segments {
+        if segment.config.kind != ProviderKind::Local {
+            eprintln!(
+                "Skipping the segment of log {} at LSN {}, its {} loglet is not stored locally",
+                log_id, segment.base_lsn, segment.config.kind
+            );
+            continue;
+        }
+        let loglet_id = segment.config.params.id().parse().with_context(|| {
+            format!(
+                "invalid local loglet params '{}'",
+                segment.config.params.id()
+            )
+        })?;
+        local_segments.push(LocalSegment {
+            loglet_id,
+            base_lsn: segment.base_lsn,
+        });
+    }
+    Ok(local_segments)
+}
+
+fn envelope_to_json(lsn: Lsn, format: EnvelopeFormat, envelope: &Envelope, full: bool) -> Value {
+    let mut value = json!({
+        "lsn": u64::from(lsn),
+        "format": format.to_string(),
+        "partition_key": envelope.partition_key(),
+        "source": envelope.header.source,
+        "destination": envelope.header.dest,
+        "command": envelope.command.name(),
+    });
+    if full {
+        value["command_body"] = serde_json::to_value(&envelope.command)
+            .unwrap_or_else(|err| json!({ "error": format!("cannot print command: {err}") }));
+    }
+    value
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> anyhow::Result<()> {
+    let args = Arguments::parse();
+    let segments = local_segments(&args).await?;
+
+    let options = LocalLogletOptions {
+        path: args.path.clone(),
+        ..LocalLogletOptions::default()
+    };
+    let reader = LocalLogReader::open(&options).with_context(|| {
+        format!(
+            "cannot open the local loglet store at '{}'",
+            args.path.display()
+        )
+    })?;
+
+    let log_id = LogId::from(args.log_id);
+    let mut stdout = std::io::stdout().lock();
+    let mut printed = 0;
+    for segment in segments {
+        match reader.log_state(segment.loglet_id, segment.base_lsn)? {
+            Some(state) => eprintln!(
+                "Log {}, loglet {} starting at LSN {}: last committed LSN {}, trimmed up to LSN {}, \
+                sealed: {:?}",
+                log_id,
+                segment.loglet_id,
+                segment.base_lsn,
+                state.release_pointer,
+                state.trim_point,
+                state.seal
+            ),
+            None => eprintln!(
+                "Log {}, loglet {} starting at LSN {} has no records in the local loglet store",
+                log_id, segment.loglet_id, segment.base_lsn
+            ),
+        }
+
+        let records = reader.records(
+            segment.loglet_id,
+            segment.base_lsn,
+            Lsn::from(args.from_lsn),
+        )?;
+        for record in records {
+            if args.limit.is_some_and(|limit| printed >= limit) {
+                return Ok(());
+            }
+
+            let (lsn, payload) = record?;
+            let decoded = EnvelopeFormat::of(payload.as_ref())
+                .and_then(|format| Ok((format, Envelope::decode(payload.as_ref())?)));
+            let line = match decoded {
+                Ok((format, envelope)) if args.matches(&envelope) => {
+                    envelope_to_json(lsn, format, &envelope, args.full)
+                }
+                Ok(_) => continue,
+                // Records that cannot be decoded are always printed, they might be the reason for
+                // the inspection.
+                Err(err) => json!({
+                    "lsn": u64::from(lsn),
+                    "error": format!("cannot decode envelope: {err}"),
+                }),
+            };
+            writeln!(stdout, "{}", line)?;
+            printed += 1;
+        }
+    }
+
+    Ok(())
+}
@@ -101,7 +101,11 @@ async fn generate_rest_api_doc() -> anyhow::Result<()> {
     let node_env = TestCoreEnv::create_with_mock_nodes_config(1, 1).await;
     let bifrost = node_env
         .tc
-        .run_in_scope("bifrost init", None, Bifrost::new_in_memory(1))
+        .run_in_scope(
+            "bifrost init",
+            None,
+            Bifrost::new_in_memory(node_env.metadata_writer.clone(), 1),
+        )
         .await;
 
     node_env.tc.spawn(